@@ -0,0 +1,314 @@
+//! Server API Description
+//!
+//! The OpenAPI 3 document served at `GET /openapi.json` and a page listing
+//! its operations at `GET /docs`, so other teams can integrate with the
+//! server, or generate a client with any OpenAPI generator, without reading
+//! its source. The page is rendered here, without scripts or assets from
+//! elsewhere, so it works offline. Keep it in step with the routes in
+//! `run_server`.
+
+use crate::profile::escape_html;
+use serde_json::{json, Value as JsonValue};
+
+fn path_param(name: &str, description: &str) -> JsonValue {
+    json!({"name": name, "in": "path", "required": true, "description": description, "schema": {"type": "string"}})
+}
+
+fn query_param(name: &str, kind: &str, description: &str) -> JsonValue {
+    json!({"name": name, "in": "query", "required": false, "description": description, "schema": {"type": kind}})
+}
+
+fn json_response(description: &str, schema: JsonValue) -> JsonValue {
+    json!({"description": description, "content": {"application/json": {"schema": schema}}})
+}
+
+fn schema_ref(name: &str) -> JsonValue {
+    json!({"$ref": format!("#/components/schemas/{}", name)})
+}
+
+/// An operation; `scope` is the API key scope it needs when auth is on
+fn operation(id: &str, tag: &str, summary: &str, scope: Option<&str>, parameters: Vec<JsonValue>, responses: JsonValue) -> JsonValue {
+    let mut op = json!({
+        "operationId": id,
+        "tags": [tag],
+        "summary": summary,
+        "parameters": parameters,
+        "responses": responses,
+    });
+    if let Some(scope) = scope {
+        op["security"] = json!([{"bearer": []}, {"api_key": []}]);
+        op["x-autograph-scope"] = json!(scope);
+    }
+    op
+}
+
+/// `op` with a JSON request body
+fn with_body(mut op: JsonValue, schema: JsonValue) -> JsonValue {
+    op["requestBody"] = json!({"required": true, "content": {"application/json": {"schema": schema}}});
+    op
+}
+
+/// The server's OpenAPI document
+pub fn spec() -> JsonValue {
+    let flow_name = path_param("flow_name", "Name the flow was deployed under: letters, digits, `_` and `-`; anything else answers 400");
+    let run_id = path_param("run_id", "ID of a run");
+    let labels = query_param("labels", "string", "Only runs with all of these labels, as `key=value,key=value`");
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Autograph",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Deploy and run Autograph flows, and read their run history. \
+                With `--auth-config`, requests need an API key with the operation's scope \
+                (`x-autograph-scope`) as `Authorization: Bearer <key>` or `X-API-Key`.",
+        },
+        "tags": [
+            {"name": "flows", "description": "Deploy, inspect and delete flows"},
+            {"name": "runs", "description": "Run flows and read the run history"},
+            {"name": "server", "description": "Health, readiness and metrics"},
+        ],
+        "paths": {
+            "/run/{flow_name}": {"post": with_body(operation(
+                "runFlow", "runs", "Run a deployed flow with the request body as input", Some("run"),
+                vec![
+                    flow_name.clone(),
+                    query_param("offline", "boolean", "Block nodes that reach the network"),
+                    query_param("chaos", "boolean", "Inject the flow's chaos faults"),
+                    query_param("labels", "string", "Labels for the run, as `key=value,key=value`"),
+                    query_param("wait", "boolean", "Queue while every run slot is busy (default); `false` answers 429 at once"),
+                ],
+                json!({
+                    "200": {
+                        "description": "The flow's result, or `{\"error\", \"run_id\"}` if the run failed",
+                        "headers": {"X-Run-Id": {"description": "ID of the recorded run", "schema": {"type": "string"}}},
+                        "content": {"application/json": {"schema": {}}},
+                    },
+                    "400": json_response("The flow name is invalid, or the input doesn't match the flow's input schema", schema_ref("InvalidInput")),
+                    "429": {
+                        "description": "Too many concurrent runs",
+                        "headers": {"Retry-After": {"schema": {"type": "integer"}}},
+                        "content": {"application/json": {"schema": schema_ref("Saturated")}},
+                    },
+                }),
+            ), json!({"description": "Input passed to the flow"}))},
+            "/deploy/{flow_name}": {
+                "post": with_body(operation(
+                    "deployFlow", "flows", "Deploy a flow definition or signed bundle", Some("deploy"),
+                    vec![
+                        flow_name.clone(),
+                        query_param("message", "string", "Changelog message"),
+                        query_param("author", "string", "Changelog author when no API key names one"),
+                        query_param("skip_preflight", "boolean", "Deploy even if required resources are missing"),
+                    ],
+                    json!({"200": json_response("Deployed, with the compiled source; or an error with a `missing` list", schema_ref("Deployed"))}),
+                ), json!({"type": "object", "description": "A flow definition or a bundle"})),
+                "delete": operation(
+                    "deleteFlow", "flows", "Delete a flow; asks for `confirm` if anything depends on it", Some("deploy"),
+                    vec![flow_name.clone(), query_param("confirm", "boolean", "Delete despite dependents")],
+                    json!({"200": json_response("Deleted, or the impact awaiting confirmation", schema_ref("Deleted"))}),
+                ),
+            },
+            "/flows/{flow_name}": {"get": operation(
+                "getFlow", "flows", "A deployed flow's definition and changelog", Some("read"),
+                vec![flow_name.clone()],
+                json!({"200": json_response("The flow", {"type": "object"})}),
+            )},
+            "/flows/{flow_name}/stats": {"get": operation(
+                "getFlowStats", "flows", "Graph metrics and complexity warnings", Some("read"),
+                vec![flow_name.clone()],
+                json!({"200": json_response("Metrics", {"type": "object"})}),
+            )},
+            "/flows/{flow_name}/runs": {"get": operation(
+                "listFlowRuns", "runs", "Runs of one flow", Some("read"),
+                vec![flow_name, labels.clone()],
+                json!({"200": json_response("Run summaries", {
+                    "type": "object",
+                    "properties": {"flow": {"type": "string"}, "runs": {"type": "array", "items": schema_ref("RunSummary")}},
+                })}),
+            )},
+            "/runs": {"get": operation(
+                "searchRuns", "runs", "Runs of every flow, newest first", Some("read"),
+                vec![labels],
+                json!({"200": json_response("Run summaries", {
+                    "type": "object",
+                    "properties": {"runs": {"type": "array", "items": schema_ref("RunSummary")}},
+                })}),
+            )},
+            "/runs/{run_id}": {"get": operation(
+                "getRun", "runs", "A run with its input, result, node outputs and retry attempts", Some("read"),
+                vec![run_id.clone()],
+                json!({"200": json_response("The run record, or an error", schema_ref("Run"))}),
+            )},
+            "/runs/{run_id}/logs": {"get": operation(
+                "getRunLogs", "runs", "A run's structured log", Some("read"),
+                vec![run_id.clone()],
+                json!({"200": json_response("Log events, or an error", schema_ref("RunLog"))}),
+            )},
+            "/runs/{run_id}/report": {"get": operation(
+                "getRunReport", "runs", "A self-contained report of a run for sharing", Some("read"),
+                vec![run_id, query_param("format", "string", "`json` (default) or `html`")],
+                json!({"200": {
+                    "description": "The flow as it ran, input, node outputs and timings, log and result; or an error",
+                    "content": {
+                        "application/json": {"schema": {"type": "object"}},
+                        "text/html": {"schema": {"type": "string"}},
+                    },
+                }}),
+            )},
+            "/health": {"get": operation(
+                "health", "server", "Liveness, load and compile cache statistics", None, vec![],
+                json!({"200": json_response("Up", {"type": "object"})}),
+            )},
+            "/ready": {"get": operation(
+                "ready", "server", "Whether flows can be deployed and run", None, vec![],
+                json!({"200": json_response("Ready", {"type": "object"}), "503": json_response("Not ready", {"type": "object"})}),
+            )},
+            "/metrics": {"get": operation(
+                "metrics", "server", "Prometheus metrics", None, vec![],
+                json!({"200": {"description": "Metrics in the Prometheus text format", "content": {"text/plain": {"schema": {"type": "string"}}}}}),
+            )},
+        },
+        "components": {
+            "securitySchemes": {
+                "bearer": {"type": "http", "scheme": "bearer"},
+                "api_key": {"type": "apiKey", "in": "header", "name": "X-API-Key"},
+            },
+            "schemas": {
+                "Error": {"type": "object", "properties": {"error": {"type": "string"}}},
+                "InvalidInput": {"type": "object", "properties": {
+                    "error": {"type": "string"},
+                    "fields": {"type": "array", "items": {"type": "object", "properties": {
+                        "path": {"type": "string", "description": "JSON pointer into the input"},
+                        "message": {"type": "string"},
+                    }}},
+                }},
+                "Saturated": {"type": "object", "properties": {
+                    "error": {"type": "string"},
+                    "running": {"type": "integer"},
+                    "queue_position": {"type": "integer"},
+                }},
+                "Deployed": {"type": "object", "properties": {
+                    "status": {"type": "string"},
+                    "message": {"type": "string"},
+                    "source": {"type": "string"},
+                    "error": {"type": "string"},
+                    "missing": {"type": "array", "items": {"type": "string"}},
+                }},
+                "Deleted": {"type": "object", "properties": {
+                    "status": {"type": "string", "enum": ["deleted", "confirmation_required"]},
+                    "flow": {"type": "string"},
+                    "impact": {"type": "array", "items": {"type": "object"}},
+                }},
+                "RunStatus": {"type": "string", "enum": ["queued", "running", "success", "error", "interrupted"]},
+                "RunSummary": {"type": "object", "properties": {
+                    "id": {"type": "string"},
+                    "flow_name": {"type": "string"},
+                    "started_at": {"type": "integer", "description": "Unix time in milliseconds"},
+                    "duration_ms": {"type": "integer"},
+                    "status": schema_ref("RunStatus"),
+                    "error": {"type": "string", "nullable": true},
+                    "attempt": {"type": "integer"},
+                    "chain": {"type": "string"},
+                    "retry_at": {"type": "integer", "nullable": true},
+                    "chaos": {"type": "boolean"},
+                    "labels": {"type": "object", "additionalProperties": {"type": "string"}},
+                }},
+                "Run": {"allOf": [schema_ref("RunSummary"), {"type": "object", "properties": {
+                    "input": {},
+                    "result": {},
+                    "node_outputs": {"type": "object"},
+                    "logs": {"type": "array", "items": {"type": "string"}},
+                    "attempts": {"type": "array", "items": schema_ref("RunSummary")},
+                }}]},
+                "LogEvent": {"type": "object", "properties": {
+                    "at": {"type": "integer", "description": "Unix time in milliseconds; node output events are logged when the run ends"},
+                    "level": {"type": "string", "enum": ["info", "warn", "error"]},
+                    "node": {"type": "string"},
+                    "message": {"type": "string"},
+                }},
+                "RunLog": {"type": "object", "properties": {
+                    "run_id": {"type": "string"},
+                    "flow_name": {"type": "string"},
+                    "status": schema_ref("RunStatus"),
+                    "events": {"type": "array", "items": schema_ref("LogEvent")},
+                }},
+            },
+        },
+    })
+}
+
+/// The page at `/docs`: every operation of `spec` with its method, path,
+/// summary and scope, linking to the full document
+pub fn docs_html(spec: &JsonValue) -> String {
+    let mut rows = String::new();
+    for (path, ops) in spec["paths"].as_object().into_iter().flatten() {
+        for (method, op) in ops.as_object().into_iter().flatten() {
+            rows.push_str(&format!(
+                "<tr><td><b>{}</b></td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                method.to_uppercase(),
+                escape_html(path),
+                escape_html(op["summary"].as_str().unwrap_or_default()),
+                escape_html(op["x-autograph-scope"].as_str().unwrap_or_default()),
+            ));
+        }
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title} API</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ padding: 4px 12px; border-bottom: 1px solid #ddd; text-align: left; }}\n\
+         </style>\n</head>\n<body>\n\
+         <h1>{title} API {version}</h1>\n\
+         <p>The full description is at <a href=\"/openapi.json\">/openapi.json</a> (OpenAPI 3).</p>\n\
+         <table>\n<tr><th>Method</th><th>Path</th><th>Summary</th><th>Scope</th></tr>\n{rows}</table>\n\
+         </body>\n</html>\n",
+        title = escape_html(spec["info"]["title"].as_str().unwrap_or("Autograph")),
+        version = escape_html(spec["info"]["version"].as_str().unwrap_or_default()),
+        rows = rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn refs(value: &JsonValue, found: &mut Vec<String>) {
+        match value {
+            JsonValue::Object(map) => {
+                if let Some(r) = map.get("$ref").and_then(|r| r.as_str()) {
+                    found.push(r.trim_start_matches("#/components/schemas/").to_string());
+                }
+                map.values().for_each(|v| refs(v, found));
+            }
+            JsonValue::Array(items) => items.iter().for_each(|v| refs(v, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn operations_are_unique_and_refs_resolve() {
+        let spec = spec();
+        let mut ids = HashSet::new();
+        for (path, ops) in spec["paths"].as_object().unwrap() {
+            for op in ops.as_object().unwrap().values() {
+                assert!(ids.insert(op["operationId"].as_str().unwrap().to_string()), "duplicate id under {}", path);
+                for param in op["parameters"].as_array().unwrap().iter().filter(|p| p["in"] == "path") {
+                    assert!(path.contains(&format!("{{{}}}", param["name"].as_str().unwrap())));
+                }
+            }
+        }
+
+        let mut found = Vec::new();
+        refs(&spec, &mut found);
+        for name in found {
+            assert!(spec["components"]["schemas"].get(&name).is_some(), "unresolved schema {}", name);
+        }
+
+        let docs = docs_html(&spec);
+        assert!(docs.contains("<code>/runs/{run_id}</code>"));
+        assert!(!docs.contains("<script") && !docs.contains("https://"));
+    }
+}
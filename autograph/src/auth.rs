@@ -0,0 +1,113 @@
+//! API Key Authentication
+//!
+//! Static API keys loaded from a JSON config file, each granted a set of
+//! scopes. Requests authenticate with `Authorization: Bearer <key>` or
+//! `X-API-Key: <key>`.
+//!
+//! ```json
+//! {
+//!   "keys": [
+//!     { "name": "ci", "key": "run-only-secret", "scopes": ["run"] },
+//!     { "name": "ops", "key": "admin-secret", "scopes": ["run", "deploy", "read"] }
+//!   ]
+//! }
+//! ```
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Permission granted to an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Execute deployed flows
+    Run,
+    /// Deploy or replace flows
+    Deploy,
+    /// Read run history and flow metadata
+    Read,
+}
+
+/// A configured API key
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiKey {
+    pub name: String,
+    pub key: String,
+    pub scopes: Vec<Scope>,
+}
+
+/// Authentication config file contents
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuthConfig {
+    pub keys: Vec<ApiKey>,
+}
+
+/// Name of the key that authenticated a request, stored in request extensions
+#[derive(Debug, Clone)]
+pub struct Principal(pub String);
+
+impl AuthConfig {
+    /// Load keys from a JSON config file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read auth config {}: {}", path.display(), e))?;
+        let config: AuthConfig = serde_json::from_str(&json)
+            .map_err(|e| anyhow::anyhow!("Invalid auth config {}: {}", path.display(), e))?;
+
+        if config.keys.is_empty() {
+            anyhow::bail!("Auth config {} defines no keys", path.display());
+        }
+        Ok(config)
+    }
+
+    /// Look up the key matching a presented token
+    fn find(&self, token: &str) -> Option<&ApiKey> {
+        self.keys.iter().find(|k| constant_time_eq(k.key.as_bytes(), token.as_bytes()))
+    }
+}
+
+/// Middleware rejecting requests without a key that grants `scope`
+pub async fn require_scope(
+    State((config, scope)): State<(Arc<AuthConfig>, Scope)>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let token = req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| req.headers().get("x-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::trim);
+
+    let Some(key) = token.and_then(|t| config.find(t)) else {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Missing or invalid API key"})),
+        )
+            .into_response();
+    };
+
+    if !key.scopes.contains(&scope) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": format!("API key '{}' lacks the {:?} scope", key.name, scope)})),
+        )
+            .into_response();
+    }
+
+    req.extensions_mut().insert(Principal(key.name.clone()));
+    next.run(req).await
+}
+
+/// Compare secrets without short-circuiting on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
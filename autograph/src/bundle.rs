@@ -0,0 +1,262 @@
+//! Portable Flow Bundles
+//!
+//! A `.autograph` file packages a flow, every flow it references through
+//! a `"flow"` config (recursively), and a manifest into one JSON document
+//! that can be moved between machines. Importing resolves name collisions
+//! by renaming incoming flows and rewriting the references to them.
+//!
+//! The manifest records a SHA-256 checksum of every flow, and bundles can
+//! be signed by their publisher (see [`crate::signing`]).
+
+use crate::flow::Flow;
+use crate::signing::{sha256_hex, BundleSignature, PublisherKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File extension of exported bundles
+pub const BUNDLE_EXTENSION: &str = "autograph";
+
+/// Bundle format understood by this version
+const FORMAT_VERSION: u32 = 1;
+
+/// Describes what a bundle contains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    /// Flow that was exported; the others are its subflows
+    pub root: String,
+    /// Unix timestamp in milliseconds
+    pub exported_at: u64,
+    /// Autograph version that wrote the bundle
+    pub autograph_version: String,
+    /// Every flow in the bundle
+    pub flows: Vec<String>,
+    /// Built-in templates the flows were created from
+    #[serde(default)]
+    pub templates: Vec<String>,
+    /// SHA-256 of each flow's JSON; bundles from before checksums have none
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub checksums: BTreeMap<String, String>,
+}
+
+/// A flow with its subflows and manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub manifest: Manifest,
+    pub flows: BTreeMap<String, Flow>,
+    /// Publisher signature over the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<BundleSignature>,
+}
+
+/// Outcome of importing a bundle
+#[derive(Debug, Clone)]
+pub struct ImportReport {
+    /// Name the root flow was saved under
+    pub root: String,
+    /// Flows saved under a new name because the original was taken
+    pub renamed: BTreeMap<String, String>,
+    /// Flows identical to ones already present, which were left alone
+    pub unchanged: Vec<String>,
+    /// Templates the bundle refers to that this build doesn't have
+    pub missing_templates: Vec<String>,
+}
+
+impl Bundle {
+    /// Package `flow` and every flow it references from `flows_dir`
+    pub fn export(flows_dir: &Path, name: &str, flow: &Flow) -> Result<Self, String> {
+        let mut flows = BTreeMap::new();
+        flows.insert(name.to_string(), flow.clone());
+
+        let mut pending = flow.referenced_flows();
+        while let Some(sub) = pending.pop() {
+            if flows.contains_key(&sub) {
+                continue;
+            }
+            let path = flows_dir.join(format!("{}.json", sub));
+            let json = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Subflow '{}' not found at {}: {}", sub, path.display(), e))?;
+            let subflow: Flow = serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse subflow '{}': {}", sub, e))?;
+            pending.extend(subflow.referenced_flows());
+            flows.insert(sub, subflow);
+        }
+
+        let mut templates: Vec<String> = flows.values().filter_map(|f| f.template.clone()).collect();
+        templates.sort();
+        templates.dedup();
+
+        let checksums = flows.iter().map(|(name, flow)| (name.clone(), checksum(flow))).collect();
+
+        Ok(Self {
+            manifest: Manifest {
+                format_version: FORMAT_VERSION,
+                root: name.to_string(),
+                exported_at: crate::runs::now_ms(),
+                autograph_version: env!("CARGO_PKG_VERSION").to_string(),
+                flows: flows.keys().cloned().collect(),
+                templates,
+                checksums,
+            },
+            flows,
+            signature: None,
+        })
+    }
+
+    /// Sign the bundle as `key`'s publisher
+    pub fn sign(&mut self, key: &PublisherKey) -> Result<(), String> {
+        let manifest = serde_json::to_vec(&self.manifest).map_err(|e| e.to_string())?;
+        self.signature = Some(key.sign(&manifest));
+        Ok(())
+    }
+
+    /// Write the bundle to a file
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize bundle: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Read and check a bundle file
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let bundle: Bundle = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid bundle {}: {}", path.display(), e))?;
+        bundle.check()?;
+        Ok(bundle)
+    }
+
+    /// Check the format, flow names, checksums and signature (if any)
+    pub fn check(&self) -> Result<(), String> {
+        if self.manifest.format_version > FORMAT_VERSION {
+            return Err(format!(
+                "Bundle format {} is newer than this version of Autograph supports ({})",
+                self.manifest.format_version, FORMAT_VERSION
+            ));
+        }
+        if !self.flows.contains_key(&self.manifest.root) {
+            return Err(format!("Bundle is missing its root flow '{}'", self.manifest.root));
+        }
+        for (name, flow) in &self.flows {
+            // Names become file names in the flows directory
+            if !crate::nodes::valid_flow_name(name) {
+                return Err(format!("Bundle contains an invalid flow name {:?}", name));
+            }
+            flow.validate().map_err(|e| format!("Flow '{}' in bundle is invalid: {}", name, e))?;
+        }
+
+        if !self.manifest.checksums.is_empty() || self.signature.is_some() {
+            for (name, flow) in &self.flows {
+                match self.manifest.checksums.get(name) {
+                    Some(expected) if *expected == checksum(flow) => {}
+                    Some(_) => return Err(format!("Flow '{}' does not match its checksum; the bundle was modified", name)),
+                    None => return Err(format!("Flow '{}' has no checksum in the manifest", name)),
+                }
+            }
+        }
+        if let Some(signature) = &self.signature {
+            let manifest = serde_json::to_vec(&self.manifest).map_err(|e| e.to_string())?;
+            signature.verify(&manifest)?;
+        }
+        Ok(())
+    }
+
+    /// Save the bundled flows into `flows_dir`.
+    ///
+    /// A flow whose name is taken by an identical flow is reused; one taken
+    /// by a different flow is saved as `<name>_2`, `<name>_3`, ... and
+    /// references to it inside the bundle are updated.
+    pub fn import(&self, flows_dir: &Path) -> Result<ImportReport, String> {
+        std::fs::create_dir_all(flows_dir).map_err(|e| format!("Failed to create {}: {}", flows_dir.display(), e))?;
+
+        // Pick a free name for every flow before writing anything
+        let mut names: BTreeMap<String, String> = BTreeMap::new();
+        let mut unchanged = Vec::new();
+        for (name, flow) in &self.flows {
+            let mut candidate = name.clone();
+            let mut suffix = 2;
+            loop {
+                if !names.values().any(|n| *n == candidate) {
+                    match slot(flows_dir, &candidate, flow) {
+                        Slot::Free => break,
+                        Slot::Same => {
+                            unchanged.push(name.clone());
+                            break;
+                        }
+                        Slot::Taken => {}
+                    }
+                }
+                candidate = format!("{}_{}", name, suffix);
+                suffix += 1;
+            }
+            names.insert(name.clone(), candidate);
+        }
+
+        for (name, flow) in &self.flows {
+            if unchanged.contains(name) {
+                continue;
+            }
+
+            let mut flow = flow.clone();
+            for node in &mut flow.nodes {
+                let renamed = node.config.get("flow")
+                    .and_then(|f| f.as_str())
+                    .and_then(|f| names.get(f));
+                if let Some(new_name) = renamed.cloned() {
+                    node.config["flow"] = serde_json::json!(new_name);
+                }
+            }
+
+            let path = flows_dir.join(format!("{}.json", names[name]));
+            let json = serde_json::to_string_pretty(&flow)
+                .map_err(|e| format!("Failed to serialize flow '{}': {}", name, e))?;
+            std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+
+        let templates = crate::templates::all_templates();
+        let known: Vec<&str> = templates.iter().map(|t| t.name.as_ref()).collect();
+        let missing_templates = self.manifest.templates.iter()
+            .filter(|t| !known.contains(&t.as_str()))
+            .cloned()
+            .collect();
+
+        Ok(ImportReport {
+            root: names[&self.manifest.root].clone(),
+            renamed: names.into_iter().filter(|(old, new)| old != new).collect(),
+            unchanged,
+            missing_templates,
+        })
+    }
+}
+
+/// SHA-256 of a flow's JSON
+fn checksum(flow: &Flow) -> String {
+    sha256_hex(&serde_json::to_vec(flow).unwrap_or_default())
+}
+
+/// Whether a flow name in the flows directory is available for `flow`
+enum Slot {
+    Free,
+    /// Holds an identical flow
+    Same,
+    Taken,
+}
+
+fn slot(flows_dir: &Path, name: &str, flow: &Flow) -> Slot {
+    let path = flows_dir.join(format!("{}.json", name));
+    if !path.exists() {
+        return Slot::Free;
+    }
+
+    // An unreadable file still occupies the name
+    let existing = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+    if existing.is_some() && existing == serde_json::to_value(flow).ok() {
+        Slot::Same
+    } else {
+        Slot::Taken
+    }
+}
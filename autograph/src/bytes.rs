@@ -0,0 +1,117 @@
+//! Binary Values
+//!
+//! Bytes flow between nodes as `{"$bytes": "<base64>"}`, so images and PDFs
+//! pass through JSON and HLX strings without being mangled as text.
+//! `file_read_binary`, HTTP nodes with `"output": "bytes"` and
+//! `bytes_from_base64` produce them. The properties panel previews them by
+//! size, sniffed type and a hex dump of the first bytes.
+
+use serde_json::Value as JsonValue;
+
+/// Key of the base64 data in a bytes value
+pub const BYTES_KEY: &str = "$bytes";
+
+/// Bytes shown in a preview's hex dump
+pub const PREVIEW_BYTES: usize = 256;
+
+/// The base64 data of a bytes value
+pub fn base64_of(value: &JsonValue) -> Option<&str> {
+    let fields = value.as_object().filter(|fields| fields.len() == 1)?;
+    fields.get(BYTES_KEY)?.as_str()
+}
+
+/// Decoded length of base64 data, without decoding it
+pub fn size(base64: &str) -> usize {
+    let data = base64.trim_end_matches('=').bytes().filter(|b| !b.is_ascii_whitespace()).count();
+    data * 3 / 4
+}
+
+/// Decode up to `max` bytes from the start of base64 data (standard or
+/// URL-safe alphabet); stops at the first invalid character
+pub fn decode_prefix(base64: &str, max: usize) -> Vec<u8> {
+    let sextet = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    };
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in base64.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let Some(value) = sextet(c) else { break };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            if bytes.len() == max {
+                break;
+            }
+        }
+    }
+    bytes
+}
+
+/// MIME type recognized from the first bytes of common formats
+pub fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+    ];
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    SIGNATURES.iter().find(|(magic, _)| bytes.starts_with(magic)).map(|(_, mime)| *mime)
+}
+
+/// Offset, hex and printable ASCII of `bytes`, 16 per line
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes.chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk.iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            format!("{:08x}  {:<47}  {}", i * 16, hex.join(" "), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// One-line summary of a bytes value, e.g. `image/png, 1.2 MiB`
+pub fn describe(base64: &str) -> String {
+    let size = crate::gpu_stats::format_bytes(size(base64) as u64);
+    match sniff(&decode_prefix(base64, 16)) {
+        Some(mime) => format!("{}, {}", mime, size),
+        None => format!("binary, {}", size),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bytes_values_are_sized_and_sniffed_without_decoding_them_whole() {
+        // The 8-byte PNG signature and 4 more bytes
+        let png = "iVBORw0KGgoAAAANSUhEUg==";
+        let value = json!({"$bytes": png});
+        assert_eq!(base64_of(&value), Some(png));
+        assert_eq!(base64_of(&json!({"$bytes": png, "name": "x"})), None);
+
+        assert_eq!(size(png), 16);
+        assert_eq!(decode_prefix(png, 4), b"\x89PNG");
+        assert_eq!(describe(png), "image/png, 16 B");
+        assert!(hex_dump(b"PK\x03\x04").starts_with("00000000  50 4b 03 04"));
+        assert!(hex_dump(b"PK\x03\x04").ends_with("PK.."));
+    }
+}
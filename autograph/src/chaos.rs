@@ -0,0 +1,139 @@
+//! Chaos Mode
+//!
+//! Fault injection for testing error handling. A flow lists fault rules
+//! (node, fault, probability); runs started in chaos mode roll for each rule
+//! and replace the chosen nodes' code with the fault, so error branches and
+//! retry policies can be exercised before a real outage does it. Rules are
+//! ignored by normal runs.
+//!
+//! With a seed the same nodes fail on every run, which makes a chaos run
+//! reproducible.
+
+use crate::nodes::hlx_string;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A failure chaos mode can inject
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fault {
+    /// The node fails as if the server answered 500
+    Http500,
+    /// The node hangs for `timeout_ms`, then fails with a timeout
+    Timeout,
+    /// The node outputs truncated JSON text, failing whoever parses it
+    MalformedJson,
+}
+
+impl Fault {
+    pub const ALL: [Fault; 3] = [Fault::Http500, Fault::Timeout, Fault::MalformedJson];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Fault::Http500 => "HTTP 500",
+            Fault::Timeout => "Timeout",
+            Fault::MalformedJson => "Malformed JSON",
+        }
+    }
+}
+
+/// Inject `fault` into `node` on a `probability` share of chaos runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaultRule {
+    pub node: String,
+    pub fault: Fault,
+    #[serde(default = "always")]
+    pub probability: f64,
+    /// How long a `timeout` fault hangs before failing
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn always() -> f64 {
+    1.0
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+impl FaultRule {
+    pub fn new(node: &str, fault: Fault) -> Self {
+        Self { node: node.to_string(), fault, probability: always(), timeout_ms: default_timeout_ms() }
+    }
+}
+
+/// Roll every rule, returning the faults to inject for one run by node ID.
+/// Without a seed the rolls differ from run to run; with one they depend on
+/// the run's `attempt` too, so retries of a seeded flow can get past a fault.
+pub fn plan(rules: &[FaultRule], seed: Option<u64>, attempt: u32) -> HashMap<String, FaultRule> {
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    });
+    let seed = crate::nodes::splitmix64(seed ^ attempt as u64);
+
+    let mut faults = HashMap::new();
+    for (i, rule) in rules.iter().enumerate() {
+        if faults.contains_key(&rule.node) {
+            continue;
+        }
+        let roll = crate::nodes::splitmix64(crate::nodes::node_seed(seed, &rule.node) ^ i as u64);
+        let roll = (roll >> 11) as f64 / (1u64 << 53) as f64;
+        if roll < rule.probability {
+            faults.insert(rule.node.clone(), rule.clone());
+        }
+    }
+    faults
+}
+
+/// HLX replacing a node's code with its fault
+pub fn fault_code(node_id: &str, rule: &FaultRule) -> String {
+    let fail = |message: String| format!("fail({})", hlx_string(&format!("{} (injected by chaos mode)", message)));
+    match rule.fault {
+        Fault::Http500 => format!(
+            "    let {}_out = {};\n",
+            node_id,
+            fail("HTTP 500 Internal Server Error".to_string())
+        ),
+        Fault::Timeout => format!(
+            "    sleep({});\n    let {}_out = {};\n",
+            rule.timeout_ms,
+            node_id,
+            fail(format!("Timed out after {}ms", rule.timeout_ms))
+        ),
+        Fault::MalformedJson => format!("    let {}_out = {};\n", node_id, hlx_string("{\"data\": [1, 2,")),
+    }
+}
+
+/// Log lines describing a run's injected faults
+pub fn describe(faults: &HashMap<String, FaultRule>) -> Vec<String> {
+    let mut lines: Vec<String> = faults
+        .iter()
+        .map(|(node, rule)| format!("Chaos: injecting {} into {}", rule.fault.label(), node))
+        .collect();
+    lines.sort();
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_plans_repeat_per_attempt() {
+        let rules: Vec<FaultRule> = (0..64)
+            .map(|i| FaultRule { probability: 0.5, ..FaultRule::new(&format!("node_{}", i), Fault::Http500) })
+            .collect();
+        let failing = |attempt| {
+            let mut nodes: Vec<String> = plan(&rules, Some(7), attempt).into_keys().collect();
+            nodes.sort();
+            nodes
+        };
+
+        assert_eq!(failing(1), failing(1));
+        assert_ne!(failing(1), failing(2));
+    }
+}
@@ -0,0 +1,349 @@
+//! Headless CLI Commands
+//!
+//! `compile`, `decompile`, `run`, `validate`, `check` and `profile` work
+//! directly on flow files so CI pipelines can check flows without the UI or
+//! server;
+//! `keygen`, `sign` and `verify` handle bundle signing for releases.
+//! Each command returns an error on failure, which `main` turns into a
+//! non-zero exit.
+
+use crate::flow::{CompileOptions, Flow};
+use serde_json::Value as JsonValue;
+use std::path::Path;
+
+/// Load a flow definition from a JSON file
+fn load_flow(path: &Path) -> anyhow::Result<Flow> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&json)
+        .map_err(|e| anyhow::anyhow!("Invalid flow {}: {}", path.display(), e))
+}
+
+/// Collect every problem that would stop the flow from compiling or running
+fn check_flow(flow: &Flow) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if let Err(e) = flow.validate() {
+        problems.push(e.to_string());
+        // Generated source is meaningless for a structurally broken flow
+        return problems;
+    }
+
+    let defs = crate::nodes::all_nodes();
+    let known: Vec<&str> = defs.iter().map(|def| def.name.as_ref()).collect();
+    for node in &flow.nodes {
+        if !known.contains(&node.type_name.as_str()) {
+            problems.push(format!("Node {} has unknown type {:?}", node.id, node.type_name));
+        }
+    }
+
+    for node in &flow.nodes {
+        if let Err(e) = crate::http_auth::request_auth(&node.config) {
+            problems.push(format!("Node {}: {}", node.id, e));
+        }
+    }
+
+    for (node_id, error) in flow.expression_errors() {
+        problems.push(format!("Node {} has an invalid expression in {}", node_id, error));
+    }
+
+    let source = flow.compile_to_hlx();
+    if let Some(diagnostic) = crate::diagnostics::check(flow, &source) {
+        problems.push(format!("Generated HLX does not compile: {}", diagnostic));
+    }
+
+    problems
+}
+
+/// `autograph validate <flow.json>`
+pub fn validate(path: &Path) -> anyhow::Result<()> {
+    let flow = load_flow(path)?;
+    let problems = check_flow(&flow);
+    for warning in crate::complexity::GraphMetrics::of(&flow).warnings() {
+        eprintln!("⚠ {}", warning);
+    }
+    for mismatch in flow.type_mismatches() {
+        eprintln!("⚠ Type mismatch: {}", mismatch);
+    }
+
+    if problems.is_empty() {
+        println!("✓ {} is valid ({} nodes, {} edges)", path.display(), flow.nodes.len(), flow.edges.len());
+        return Ok(());
+    }
+
+    for problem in &problems {
+        eprintln!("✗ {}", problem);
+    }
+    anyhow::bail!("{} problem(s) found in {}", problems.len(), path.display())
+}
+
+/// `autograph test <flow.json> [--mock]`
+pub fn test(path: &Path, mock: bool) -> anyhow::Result<()> {
+    let flow = load_flow(path)?;
+    flow.validate()?;
+    let mut suite = crate::testing::TestSuite::load(path).map_err(|e| anyhow::anyhow!(e))?;
+    if suite.cases.is_empty() {
+        // Without cases, a run on null input still checks the assertion nodes
+        suite.cases.push(crate::testing::TestCase { name: "default".to_string(), input: JsonValue::Null, expected: None });
+    }
+
+    let execute = |source: &str, input: &JsonValue, vars: &crate::RunVars| {
+        crate::compile_and_run(source, input.clone(), vars)
+            .map_err(|e| e.to_string())
+            .and_then(|result| result.to_json().map_err(|e| format!("Serialization failed: {}", e)))
+    };
+    let flow_name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut failed = 0;
+    for case in &suite.cases {
+        let result = crate::testing::run_case(&flow, &flow_name, case, mock, &execute);
+        let skipped = match result.skipped.as_slice() {
+            [] => String::new(),
+            nodes => format!(" ({} skipped: not reached)", nodes.join(", ")),
+        };
+        if result.passed() {
+            println!("✓ {}{}", result.name, skipped);
+            continue;
+        }
+        failed += 1;
+        eprintln!("✗ {}{}", result.name, skipped);
+        for failure in &result.failures {
+            eprintln!("    {}", failure);
+        }
+    }
+
+    println!("{} passed, {} failed", suite.cases.len() - failed, failed);
+    if failed > 0 {
+        anyhow::bail!("{} of {} test case(s) failed", failed, suite.cases.len());
+    }
+    Ok(())
+}
+
+/// `autograph check <flow.json> [--project-dir .] [--offline]`
+pub fn check(path: &Path, project_dir: &Path, offline: bool) -> anyhow::Result<()> {
+    let flow = load_flow(path)?;
+    let results = crate::preflight::check(&flow, project_dir, &crate::RunVars::new(), offline);
+
+    for result in &results {
+        match &result.error {
+            None => println!("✓ {}", result.resource),
+            Some(e) => eprintln!("✗ {}: {}", result.resource, e),
+        }
+    }
+    let failed = results.iter().filter(|r| !r.ok()).count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} required resource(s) unavailable for {}", failed, results.len(), path.display());
+    }
+    println!("✓ {} has everything it needs ({} resource(s))", path.display(), results.len());
+    Ok(())
+}
+
+/// `autograph compile <flow.json> [--output out.hlxa]`
+pub fn compile(path: &Path, output: Option<&Path>, seed: Option<u64>) -> anyhow::Result<()> {
+    let flow = load_flow(path)?;
+    flow.validate()?;
+
+    let source = flow.compile_with(&CompileOptions { seed, ..Default::default() });
+    match output {
+        Some(out) => {
+            std::fs::write(out, &source)
+                .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", out.display(), e))?;
+            eprintln!("Compiled {} -> {}", path.display(), out.display());
+        }
+        None => print!("{}", source),
+    }
+    Ok(())
+}
+
+/// Load the JSON input for a run, or `null` without one
+fn load_input(input: Option<&Path>) -> anyhow::Result<JsonValue> {
+    match input {
+        Some(input_path) => {
+            let json = std::fs::read_to_string(input_path)
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", input_path.display(), e))?;
+            serde_json::from_str(&json)
+                .map_err(|e| anyhow::anyhow!("Invalid input {}: {}", input_path.display(), e))
+        }
+        None => Ok(JsonValue::Null),
+    }
+}
+
+/// `autograph run <flow.json> [--input input.json] [--offline] [--chaos] [--label key=value]`
+pub fn run(
+    path: &Path,
+    input: Option<&Path>,
+    seed: Option<u64>,
+    offline: bool,
+    chaos: bool,
+    mock: bool,
+    labels: &[String],
+) -> anyhow::Result<()> {
+    let flow = load_flow(path)?;
+    flow.validate()?;
+    let input = load_input(input)?;
+    let labels = crate::runs::parse_labels(&labels.join(",")).map_err(|e| anyhow::anyhow!(e))?;
+
+    let flow_name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut options = CompileOptions { seed, flow_name: Some(flow_name.clone()), ..Default::default() };
+    // The mock file system lives until the run is done
+    let _mock_fs = if mock {
+        let fs = crate::mock::prepare(&flow, &mut options).map_err(|e| anyhow::anyhow!(e))?;
+        for line in crate::mock::describe(&flow) {
+            eprintln!("🎭 {}", line);
+        }
+        Some(fs)
+    } else {
+        None
+    };
+
+    if offline {
+        let blocked = crate::offline::blocked_nodes(&flow, &options.fixed_outputs);
+        for (node_id, error) in &blocked {
+            eprintln!("✗ {}: {}", node_id, error);
+        }
+        if !blocked.is_empty() {
+            anyhow::bail!("Offline mode blocked {} node(s)", blocked.len());
+        }
+    }
+
+    if chaos {
+        options.faults = crate::chaos::plan(&flow.chaos, seed.or(flow.seed), 1);
+        options.faults.retain(|id, _| !options.fixed_outputs.contains_key(id));
+        for line in crate::chaos::describe(&options.faults) {
+            eprintln!("🧪 {}", line);
+        }
+    }
+
+    let source = flow.compile_with(&options);
+    let vars = crate::state_store::prepare(&source, &flow_name, &options).map_err(|e| anyhow::anyhow!(e))?;
+    let started_at = crate::runs::now_ms();
+    let start = std::time::Instant::now();
+    let outcome = crate::compile_and_run(&source, input.clone(), &vars)
+        .map_err(|e| e.to_string())
+        .and_then(|result| result.to_json().map_err(|e| format!("Serialization failed: {}", e)));
+
+    // Labels only mean something in the history, so labelled runs are kept
+    if !labels.is_empty() {
+        let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut record = crate::runs::RunRecord::new(&name, started_at, input);
+        record.labels = labels;
+        record.chaos = chaos;
+        record.finish(start.elapsed().as_millis() as u64, outcome.clone());
+        crate::runs::RunStore::new("runs").save(&mut record)?;
+        eprintln!("Run saved as {}", record.id);
+    }
+
+    let json = outcome.map_err(|e| anyhow::anyhow!(e))?;
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
+
+/// `autograph decompile <flow.hlxa> [--output flow.json]`
+pub fn decompile(path: &Path, output: Option<&Path>) -> anyhow::Result<()> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let decompiled = crate::decompile::decompile(&source).map_err(|e| anyhow::anyhow!(e))?;
+    for warning in &decompiled.warnings {
+        eprintln!("⚠ {}", warning);
+    }
+
+    let json = serde_json::to_string_pretty(&decompiled.flow)?;
+    match output {
+        Some(out) => {
+            std::fs::write(out, &json)
+                .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", out.display(), e))?;
+            eprintln!("Decompiled {} -> {}", path.display(), out.display());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+/// `autograph profile <flow.json> [--input input.json] [--output report.html]`
+///
+/// Writes a markdown report, or HTML when `--format html` is given or the
+/// output file ends in `.html`.
+pub fn profile(
+    path: &Path,
+    input: Option<&Path>,
+    output: Option<&Path>,
+    format: Option<&str>,
+    iterations: usize,
+    seed: Option<u64>,
+) -> anyhow::Result<()> {
+    let flow = load_flow(path)?;
+    flow.validate()?;
+    let input = load_input(input)?;
+
+    let flow_name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    eprintln!("Profiling {} ({} nodes, {} iterations each)...", flow_name, flow.nodes.len(), iterations);
+
+    let profile = crate::profile::profile(&flow_name, &flow, &input, seed, iterations, |source, input| {
+        let vars = crate::state_store::prepare(source, &flow_name, &CompileOptions::default())?;
+        let result = crate::compile_and_run(source, input.clone(), &vars).map_err(|e| e.to_string())?;
+        result.to_json().map_err(|e| format!("Serialization failed: {}", e))
+    })
+    .map_err(|e| anyhow::anyhow!(e))?;
+
+    let html = match format {
+        Some("html") => true,
+        Some("md" | "markdown") => false,
+        Some(other) => anyhow::bail!("Unknown report format {:?} (use md or html)", other),
+        None => output.is_some_and(|out| out.extension().is_some_and(|ext| ext == "html")),
+    };
+    let report = if html { profile.to_html() } else { profile.to_markdown() };
+
+    match output {
+        Some(out) => {
+            std::fs::write(out, &report)
+                .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", out.display(), e))?;
+            eprintln!("Profile written to {}", out.display());
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
+
+/// `autograph keygen <publisher> [--output publisher.key]`
+pub fn keygen(publisher: &str, output: &Path) -> anyhow::Result<()> {
+    let key = crate::signing::PublisherKey::generate(publisher).map_err(|e| anyhow::anyhow!(e))?;
+    key.save(output).map_err(|e| anyhow::anyhow!(e))?;
+
+    eprintln!("Key for '{}' written to {}; keep it private", publisher, output.display());
+    println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+        "name": publisher,
+        "public_key": key.public_key(),
+    }))?);
+    Ok(())
+}
+
+/// `autograph sign <bundle.autograph> --key publisher.key`
+pub fn sign(path: &Path, key: &Path) -> anyhow::Result<()> {
+    let mut bundle = crate::bundle::Bundle::read(path).map_err(|e| anyhow::anyhow!(e))?;
+    let key = crate::signing::PublisherKey::load(key).map_err(|e| anyhow::anyhow!(e))?;
+    bundle.sign(&key).map_err(|e| anyhow::anyhow!(e))?;
+    bundle.write(path).map_err(|e| anyhow::anyhow!(e))?;
+
+    eprintln!("Signed {} as '{}'", path.display(), key.publisher);
+    Ok(())
+}
+
+/// `autograph verify <bundle.autograph> [--trusted-publishers trusted.json]`
+pub fn verify(path: &Path, trusted: Option<&Path>) -> anyhow::Result<()> {
+    let bundle = crate::bundle::Bundle::read(path).map_err(|e| anyhow::anyhow!(e))?;
+
+    match (trusted, &bundle.signature) {
+        (Some(trusted), signature) => {
+            let trusted = crate::signing::TrustedPublishers::load(trusted).map_err(|e| anyhow::anyhow!(e))?;
+            let publisher = trusted.check(signature.as_ref()).map_err(|e| anyhow::anyhow!(e))?;
+            println!("✓ {} is signed by trusted publisher '{}'", path.display(), publisher);
+        }
+        (None, Some(signature)) => {
+            println!("✓ {} has a valid signature by '{}' (key {})", path.display(), signature.publisher, signature.public_key);
+        }
+        (None, None) if bundle.manifest.checksums.is_empty() => {
+            println!("✓ {} is well-formed but has no checksums or signature", path.display());
+        }
+        (None, None) => println!("✓ {} matches its checksums (unsigned)", path.display()),
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+//! Compile Cache
+//!
+//! The server compiles a deployed flow once and reuses the result across
+//! runs. Entries are keyed by flow name and only reused while the flow's
+//! files keep the modification times they had when it was compiled and the
+//! run's source is the same, so a redeploy, an edit on disk or run-specific
+//! source (chaos faults, a working directory) never runs a stale crate.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Modification times of a flow's `.json` definition and `.hlxa` source
+pub type Stamp = (Option<SystemTime>, Option<SystemTime>);
+
+/// A flow's files as they are now
+pub fn stamp(flows_dir: &Path, flow_name: &str) -> Stamp {
+    let modified = |ext: &str| {
+        std::fs::metadata(flows_dir.join(format!("{}.{}", flow_name, ext)))
+            .and_then(|m| m.modified())
+            .ok()
+    };
+    (modified("json"), modified("hlxa"))
+}
+
+/// Every flow in `flows_dir` with its stamp
+pub fn fingerprint(flows_dir: &Path) -> HashMap<String, Stamp> {
+    let Ok(entries) = std::fs::read_dir(flows_dir) else {
+        return HashMap::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json" || ext == "hlxa"))
+        .filter_map(|e| e.path().file_stem()?.to_str().map(str::to_string))
+        .map(|name| {
+            let stamp = stamp(flows_dir, &name);
+            (name, stamp)
+        })
+        .collect()
+}
+
+struct Entry<T> {
+    stamp: Stamp,
+    source: String,
+    compiled: Arc<T>,
+}
+
+/// Compiled flows by name
+pub struct CompileCache<T> {
+    entries: Mutex<HashMap<String, Entry<T>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T> Default for CompileCache<T> {
+    fn default() -> Self {
+        Self { entries: Mutex::new(HashMap::new()), hits: AtomicU64::new(0), misses: AtomicU64::new(0) }
+    }
+}
+
+impl<T> CompileCache<T> {
+    /// The compiled `source` of `flow_name`, compiling and caching it unless
+    /// an entry with the same stamp and source exists. Failures are not cached.
+    pub fn get_or_compile<E>(
+        &self,
+        flow_name: &str,
+        stamp: Stamp,
+        source: &str,
+        compile: impl FnOnce() -> Result<T, E>,
+    ) -> Result<Arc<T>, E> {
+        if let Some(entry) = self.entries.lock().unwrap().get(flow_name) {
+            if entry.stamp == stamp && entry.source == source {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(entry.compiled.clone());
+            }
+        }
+
+        // Compile without holding the lock, so other flows aren't held up
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let compiled = Arc::new(compile()?);
+        self.entries.lock().unwrap().insert(
+            flow_name.to_string(),
+            Entry { stamp, source: source.to_string(), compiled: compiled.clone() },
+        );
+        Ok(compiled)
+    }
+
+    /// Drop a flow's entry, e.g. after a deploy. Returns whether it had one.
+    pub fn invalidate(&self, flow_name: &str) -> bool {
+        self.entries.lock().unwrap().remove(flow_name).is_some()
+    }
+
+    /// Lookups answered from the cache and lookups that compiled
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_entries_until_the_stamp_or_source_changes() {
+        let cache: CompileCache<String> = CompileCache::default();
+        let compile = |source: &str| Ok::<_, ()>(source.to_uppercase());
+        let then = Some(SystemTime::UNIX_EPOCH);
+
+        let first = cache.get_or_compile("f", (then, None), "a", || compile("a")).unwrap();
+        let again = cache.get_or_compile("f", (then, None), "a", || panic!("should be cached")).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        cache.get_or_compile("f", (then, None), "b", || compile("b")).unwrap();
+        cache.get_or_compile("f", (None, then), "b", || compile("b")).unwrap();
+        assert!(cache.invalidate("f"));
+        cache.get_or_compile("f", (None, then), "b", || compile("b")).unwrap();
+        assert!(cache.get_or_compile("f", (None, then), "c", || Err(())).is_err());
+
+        assert_eq!(cache.stats(), (1, 5));
+    }
+}
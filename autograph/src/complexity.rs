@@ -0,0 +1,171 @@
+//! Graph Metrics
+//!
+//! Size and shape of a flow's graph: node counts by category, depth of the
+//! longest chain, the widest fan-out and edges that try to close a cycle.
+//! Flows past the thresholds get warnings suggesting they be split into
+//! subflows, shown in the statistics overlay, by `autograph validate` and
+//! in the API's flow stats.
+
+use crate::flow::Flow;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Nodes in one flow before it should be split into subflows
+pub const MAX_NODES: usize = 40;
+/// Longest chain of nodes before it should be split into subflows
+pub const MAX_DEPTH: usize = 15;
+/// Outgoing edges from one node before its consumers should be grouped
+pub const MAX_FAN_OUT: usize = 6;
+
+/// Metrics of a flow's graph
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GraphMetrics {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Nodes on the longest chain from a source to a sink
+    pub depth: usize,
+    /// Node with the most outgoing edges, and how many
+    pub max_fan_out: Option<(String, usize)>,
+    /// Edges that would close a cycle, as `source -> target`
+    pub cycle_edges: Vec<String>,
+    /// Node count per palette category
+    pub by_category: BTreeMap<String, usize>,
+}
+
+impl GraphMetrics {
+    /// Measure a flow
+    pub fn of(flow: &Flow) -> Self {
+        let mut outgoing: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &flow.edges {
+            outgoing.entry(edge.source.as_str()).or_default().push(edge.target.as_str());
+        }
+
+        let max_fan_out = outgoing
+            .iter()
+            .map(|(id, targets)| (id.to_string(), targets.len()))
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+
+        let defs = crate::nodes::all_nodes();
+        let categories: HashMap<&str, &str> = defs.iter().map(|def| (def.name.as_ref(), def.category.as_ref())).collect();
+        let mut by_category = BTreeMap::new();
+        for node in &flow.nodes {
+            let category = categories.get(node.type_name.as_str()).copied().unwrap_or("Unknown");
+            *by_category.entry(category.to_string()).or_insert(0) += 1;
+        }
+
+        // Depth-first walk: longest chain below each node, with edges back
+        // into the current path recorded as cycle attempts
+        let mut walk = Walk { outgoing: &outgoing, depth: HashMap::new(), on_path: Vec::new(), cycle_edges: Vec::new() };
+        let depth = flow.nodes.iter().map(|node| walk.depth_from(&node.id)).max().unwrap_or(0);
+
+        Self {
+            nodes: flow.nodes.len(),
+            edges: flow.edges.len(),
+            depth,
+            max_fan_out,
+            cycle_edges: walk.cycle_edges,
+            by_category,
+        }
+    }
+
+    /// Thresholds the flow exceeds, each with a suggestion
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.nodes > MAX_NODES {
+            warnings.push(format!(
+                "{} nodes (over {}): consider moving self-contained parts into subflows",
+                self.nodes, MAX_NODES
+            ));
+        }
+        if self.depth > MAX_DEPTH {
+            warnings.push(format!(
+                "Chain of {} nodes (over {}): consider splitting the pipeline into subflow stages",
+                self.depth, MAX_DEPTH
+            ));
+        }
+        if let Some((node, fan_out)) = self.max_fan_out.as_ref().filter(|(_, n)| *n > MAX_FAN_OUT) {
+            warnings.push(format!(
+                "{} feeds {} nodes (over {}): consider grouping its consumers into a subflow",
+                node, fan_out, MAX_FAN_OUT
+            ));
+        }
+        for edge in &self.cycle_edges {
+            warnings.push(format!("Edge {} closes a cycle; flows run once, top to bottom", edge));
+        }
+        warnings
+    }
+}
+
+struct Walk<'a> {
+    outgoing: &'a HashMap<&'a str, Vec<&'a str>>,
+    /// Longest chain starting at each finished node
+    depth: HashMap<&'a str, usize>,
+    on_path: Vec<&'a str>,
+    cycle_edges: Vec<String>,
+}
+
+impl<'a> Walk<'a> {
+    fn depth_from(&mut self, id: &'a str) -> usize {
+        if let Some(depth) = self.depth.get(id) {
+            return *depth;
+        }
+
+        let outgoing = self.outgoing;
+        self.on_path.push(id);
+        let mut below = 0;
+        for &target in outgoing.get(id).map(Vec::as_slice).unwrap_or(&[]) {
+            if self.on_path.contains(&target) {
+                self.cycle_edges.push(format!("{} -> {}", id, target));
+                continue;
+            }
+            below = below.max(self.depth_from(target));
+        }
+        self.on_path.pop();
+
+        self.depth.insert(id, below + 1);
+        below + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::{Edge, Node};
+
+    fn flow(nodes: &[&str], edges: &[(&str, &str)]) -> Flow {
+        Flow {
+            nodes: nodes
+                .iter()
+                .map(|id| Node::new(id, "print", serde_json::json!({})))
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|(source, target)| Edge {
+                    source: source.to_string(),
+                    target: target.to_string(),
+                    source_handle: None,
+                    target_handle: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn measures_depth_and_fan_out() {
+        let metrics = GraphMetrics::of(&flow(&["a", "b", "c", "d"], &[("a", "b"), ("b", "c"), ("a", "d")]));
+        assert_eq!(metrics.depth, 3);
+        assert_eq!(metrics.max_fan_out, Some(("a".to_string(), 2)));
+        assert!(metrics.cycle_edges.is_empty());
+        assert_eq!(metrics.by_category.values().sum::<usize>(), 4);
+        assert!(metrics.warnings().is_empty());
+    }
+
+    #[test]
+    fn reports_edges_that_close_cycles() {
+        let metrics = GraphMetrics::of(&flow(&["a", "b", "c"], &[("a", "b"), ("b", "c"), ("c", "a")]));
+        assert_eq!(metrics.cycle_edges, vec!["c -> a".to_string()]);
+        assert_eq!(metrics.depth, 3);
+        assert_eq!(metrics.warnings().len(), 1);
+    }
+}
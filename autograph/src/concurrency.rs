@@ -0,0 +1,188 @@
+//! Concurrent Run Limits
+//!
+//! Caps how many runs the server executes at once so a burst of webhook
+//! triggers can't exhaust memory. Runs over the cap wait in a queue per
+//! flow; the queues take turns for free slots, so one busy flow can't
+//! starve the others. A flow whose queue is full is refused and the caller
+//! gets a 429 with its position.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Configured limits
+#[derive(Debug, Clone, Copy)]
+pub struct RunLimits {
+    /// Runs executing at once, across all flows
+    pub max_running: usize,
+    /// Runs waiting per flow before new ones are refused
+    pub max_queued: usize,
+}
+
+/// Why a run was not admitted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Saturated {
+    pub running: usize,
+    /// Runs of the flow already waiting
+    pub queued: usize,
+}
+
+#[derive(Default)]
+struct State {
+    running: usize,
+    /// Waiting tickets of each flow, oldest first
+    queues: HashMap<String, VecDeque<u64>>,
+    /// Flows with waiting runs, in the order they get the next free slot
+    turns: VecDeque<String>,
+    next_ticket: u64,
+}
+
+impl State {
+    fn remove_ticket(&mut self, flow_name: &str, ticket: u64) {
+        if let Some(queue) = self.queues.get_mut(flow_name) {
+            queue.retain(|t| *t != ticket);
+            if queue.is_empty() {
+                self.queues.remove(flow_name);
+                self.turns.retain(|f| f != flow_name);
+            }
+        }
+    }
+}
+
+/// Admits runs up to the limits
+pub struct RunLimiter {
+    limits: RunLimits,
+    state: Mutex<State>,
+    /// Woken whenever a slot frees up or a waiter leaves
+    changed: Notify,
+}
+
+/// A run slot, released when dropped
+pub struct RunPermit {
+    limiter: Arc<RunLimiter>,
+}
+
+impl Drop for RunPermit {
+    fn drop(&mut self) {
+        self.limiter.state.lock().unwrap().running -= 1;
+        self.limiter.changed.notify_waiters();
+    }
+}
+
+/// Removes a waiting run from its queue if the request goes away first
+struct Waiting<'a> {
+    limiter: &'a RunLimiter,
+    flow_name: &'a str,
+    ticket: Option<u64>,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(ticket) = self.ticket {
+            self.limiter.state.lock().unwrap().remove_ticket(self.flow_name, ticket);
+            self.limiter.changed.notify_waiters();
+        }
+    }
+}
+
+impl RunLimiter {
+    pub fn new(limits: RunLimits) -> Arc<Self> {
+        Arc::new(Self { limits, state: Mutex::new(State::default()), changed: Notify::new() })
+    }
+
+    pub fn limits(&self) -> RunLimits {
+        self.limits
+    }
+
+    /// Runs executing and runs waiting across all flows
+    pub fn load(&self) -> (usize, usize) {
+        let state = self.state.lock().unwrap();
+        (state.running, state.queues.values().map(VecDeque::len).sum())
+    }
+
+    /// Take a run slot for `flow_name`, waiting in the flow's queue while
+    /// every slot is busy. Refuses at once if the queue is full, or, with
+    /// `wait` false, if the run would have to queue at all.
+    pub async fn acquire(self: &Arc<Self>, flow_name: &str, wait: bool) -> Result<RunPermit, Saturated> {
+        let mut waiting = Waiting { limiter: self, flow_name, ticket: None };
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.running < self.limits.max_running && state.queues.is_empty() {
+                state.running += 1;
+                return Ok(RunPermit { limiter: self.clone() });
+            }
+            let queued = state.queues.get(flow_name).map_or(0, VecDeque::len);
+            if !wait || queued >= self.limits.max_queued {
+                return Err(Saturated { running: state.running, queued });
+            }
+            let ticket = state.next_ticket;
+            state.next_ticket += 1;
+            if queued == 0 {
+                state.turns.push_back(flow_name.to_string());
+            }
+            state.queues.entry(flow_name.to_string()).or_default().push_back(ticket);
+            waiting.ticket = Some(ticket);
+        }
+
+        loop {
+            // Register before checking so a release between the two isn't missed
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            if self.try_take_turn(flow_name, waiting.ticket.unwrap()) {
+                waiting.ticket = None;
+                return Ok(RunPermit { limiter: self.clone() });
+            }
+            changed.await;
+        }
+    }
+
+    /// Take a slot if one is free, it is this flow's turn and `ticket` is
+    /// first in the flow's queue
+    fn try_take_turn(&self, flow_name: &str, ticket: u64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let first = state.queues.get(flow_name).and_then(|q| q.front()) == Some(&ticket);
+        let turn = state.turns.front().map(String::as_str) == Some(flow_name);
+        if state.running >= self.limits.max_running || !first || !turn {
+            return false;
+        }
+        state.running += 1;
+        state.remove_ticket(flow_name, ticket);
+        // Still waiting runs of this flow go to the back of the line
+        if state.queues.contains_key(flow_name) {
+            state.turns.retain(|f| f != flow_name);
+            state.turns.push_back(flow_name.to_string());
+        }
+        drop(state);
+        // The next flow in line may be able to take another free slot
+        self.changed.notify_waiters();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn queues_runs_over_the_limit_and_refuses_full_queues() {
+        let limiter = RunLimiter::new(RunLimits { max_running: 1, max_queued: 1 });
+
+        let first = limiter.acquire("a", true).await.unwrap();
+        assert_eq!(limiter.acquire("a", false).await.err(), Some(Saturated { running: 1, queued: 0 }));
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("a", true).await.map(|_| ()) }
+        });
+        while limiter.load().1 == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(limiter.acquire("a", true).await.err(), Some(Saturated { running: 1, queued: 1 }));
+
+        drop(first);
+        assert!(queued.await.unwrap().is_ok());
+        assert_eq!(limiter.load(), (0, 0));
+    }
+}
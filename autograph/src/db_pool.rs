@@ -0,0 +1,21 @@
+//! Database Connection Pool
+//!
+//! One connection pool per process, handed to every run so `db_query` and
+//! `db_execute` nodes reuse connections across runs instead of reconnecting
+//! each time, which matters for flows run on a schedule or by the server.
+//! The pool keys connections by connection string.
+
+use hlx_runtime::config::RuntimeConfig;
+use hlx_runtime::db::ConnectionPool;
+use std::sync::{Arc, OnceLock};
+
+/// Idle connections kept open per connection string
+const MAX_IDLE_PER_DATABASE: usize = 4;
+
+static POOL: OnceLock<Arc<ConnectionPool>> = OnceLock::new();
+
+/// Attach the shared pool to a run's runtime config
+pub fn attach(config: &mut RuntimeConfig) {
+    let pool = POOL.get_or_init(|| Arc::new(ConnectionPool::new(MAX_IDLE_PER_DATABASE)));
+    config.db_pool = Some(pool.clone());
+}
@@ -0,0 +1,390 @@
+//! HLX Decompiler
+//!
+//! Rebuilds a flow from an `.hlxa` program. Compiled flows carry a source
+//! map of `// @autograph-*` comments holding the flow settings, each edge
+//! and each node's definition, so an untouched program converts back
+//! exactly. Nodes whose code was edited by hand become `hlx_script` nodes
+//! holding the edited code, and programs without a source map are imported
+//! best-effort with one script node per `let <id>_out` statement.
+
+use crate::flow::{Edge, Flow, Node, Position};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+const FLOW_MARKER: &str = "// @autograph-flow ";
+const EDGE_MARKER: &str = "// @autograph-edge ";
+const NODE_MARKER: &str = "// @autograph-node ";
+
+/// Source map line for the flow-level settings
+pub fn flow_marker(flow: &Flow) -> String {
+    let meta = Flow { changelog: Vec::new(), nodes: Vec::new(), edges: Vec::new(), ..flow.clone_meta() };
+    format!("{}{}\n", FLOW_MARKER, to_json(&meta))
+}
+
+/// Source map line for an edge
+pub fn edge_marker(edge: &Edge) -> String {
+    format!("{}{}\n", EDGE_MARKER, to_json(edge))
+}
+
+/// Source map line preceding a node's code in `main`
+pub fn node_marker(node: &Node) -> String {
+    format!("    {}{}\n", NODE_MARKER, to_json(node))
+}
+
+/// Single-line JSON; newlines inside strings are escaped
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// A rebuilt flow and what couldn't be carried over exactly
+pub struct Decompiled {
+    pub flow: Flow,
+    pub warnings: Vec<String>,
+}
+
+/// Rebuild a flow from HLX source
+pub fn decompile(source: &str) -> Result<Decompiled, String> {
+    let program = Program::parse(source);
+    if program.blocks.is_empty() {
+        return Err("No `fn main(input)` statements found".to_string());
+    }
+
+    match program.flow.clone() {
+        Some(meta) => Ok(from_source_map(&program, meta)),
+        None => Ok(best_effort(&program)),
+    }
+}
+
+/// The node each line of compiled source belongs to: its source map
+/// marker and the statements after it in `main`, and its script function
+pub fn line_nodes(source: &str) -> Vec<Option<String>> {
+    let mut current: Option<String> = None;
+    let mut in_main = false;
+
+    source.lines()
+        .map(|raw| {
+            let line = raw.trim();
+            if line.starts_with("fn main(") {
+                in_main = true;
+                current = None;
+            } else if let Some(signature) = line.strip_prefix("fn ") {
+                current = signature.split('(').next().and_then(|name| name.strip_suffix("_script")).map(str::to_string);
+            } else if let Some(json) = line.strip_prefix(NODE_MARKER).filter(|_| in_main) {
+                current = serde_json::from_str::<Node>(json).ok().map(|node| node.id);
+            } else if in_main && line.starts_with("return ") {
+                in_main = false;
+                current = None;
+            }
+
+            let owner = current.clone();
+            if raw.starts_with('}') {
+                in_main = false;
+                current = None;
+            }
+            owner
+        })
+        .collect()
+}
+
+/// Rebuild from the source map, keeping hand edits as script nodes
+fn from_source_map(program: &Program, meta: Flow) -> Decompiled {
+    let mut warnings = Vec::new();
+    let mut flow = Flow { edges: program.edges.clone(), ..meta };
+
+    for block in &program.blocks {
+        match &block.node {
+            Some(node) => flow.nodes.push(node.clone()),
+            None => warnings.push(format!(
+                "Statements outside any node were dropped: {}",
+                block.lines.join(" ")
+            )),
+        }
+    }
+
+    // Script bodies live in their own functions
+    for node in flow.nodes.iter_mut().filter(|n| n.type_name == "hlx_script") {
+        if let Some(body) = program.functions.get(&format!("{}_script", node.id)) {
+            if node.config.is_object() {
+                node.config["code"] = json!(body.join("\n"));
+            }
+        }
+    }
+
+    // Anything that compiles differently from what the node would generate was edited
+    let regenerated = Program::parse(&flow.compile_to_hlx());
+    let expected: HashMap<&str, &Vec<String>> = regenerated.blocks.iter()
+        .filter_map(|b| Some((b.node.as_ref()?.id.as_str(), &b.lines)))
+        .collect();
+
+    for block in &program.blocks {
+        let Some(original) = &block.node else { continue };
+        if expected.get(original.id.as_str()) == Some(&&block.lines) {
+            continue;
+        }
+
+        let script = script_node(&original.id, original.position.clone(), &block.lines);
+        add_reference_edges(&mut flow, &script);
+        warnings.push(format!(
+            "{} ({}) was edited by hand and was imported as an hlx_script node",
+            original.id, original.type_name
+        ));
+        if let Some(node) = flow.nodes.iter_mut().find(|n| n.id == original.id) {
+            *node = script;
+        }
+    }
+
+    Decompiled { flow, warnings }
+}
+
+/// Rebuild a program with no source map: one script node per output variable
+fn best_effort(program: &Program) -> Decompiled {
+    let mut flow = Flow::default();
+    let mut warnings = vec!["No source map found; each statement was imported as an hlx_script node".to_string()];
+
+    let lines: Vec<&String> = program.blocks.iter().flat_map(|b| &b.lines).collect();
+    let mut pending: Vec<String> = Vec::new();
+    for line in lines {
+        pending.push(line.clone());
+        let Some(id) = output_var(line) else { continue };
+
+        let position = Position { x: 100.0 + flow.nodes.len() as f32 * 220.0, y: 200.0 };
+        let node = script_node(&id, Some(position), &pending);
+        if identifiers(&pending.join("\n")).contains(&"input") {
+            warnings.push(format!("{} reads the flow input; connect it to a start node", id));
+        }
+        add_reference_edges(&mut flow, &node);
+        flow.nodes.push(node);
+        pending.clear();
+    }
+
+    if !pending.is_empty() {
+        warnings.push(format!("Trailing statements were dropped: {}", pending.join(" ")));
+    }
+    Decompiled { flow, warnings }
+}
+
+/// An `hlx_script` node running a node's statements from `main`. Other
+/// nodes' `<id>_out` variables become named inputs of the same name.
+fn script_node(id: &str, position: Option<Position>, lines: &[String]) -> Node {
+    let mut code = lines.join("\n");
+    code.push_str(&format!("\nreturn {}_out;", id));
+
+    let own = format!("{}_out", id);
+    let mut inputs = serde_json::Map::new();
+    for name in identifiers(&code) {
+        if let Some(source) = name.strip_suffix("_out").filter(|_| name != own) {
+            inputs.insert(name.to_string(), json!(source));
+        }
+    }
+
+    Node {
+        id: id.to_string(),
+        type_name: "hlx_script".to_string(),
+        config: json!({"inputs": inputs, "code": code}),
+        position,
+        ..Default::default()
+    }
+}
+
+/// Connect a script node to every node it reads, so execution order and
+/// subgraphs still see the dependency
+fn add_reference_edges(flow: &mut Flow, node: &Node) {
+    for (_, source) in crate::nodes::script_inputs(&node.config) {
+        let known = flow.nodes.iter().any(|n| n.id == source);
+        let connected = flow.edges.iter().any(|e| e.source == source && e.target == node.id);
+        if known && !connected {
+            flow.edges.push(Edge {
+                source,
+                target: node.id.clone(),
+                source_handle: None,
+                target_handle: None,
+            });
+        }
+    }
+}
+
+/// `<id>` of a `let <id>_out = ...;` statement
+fn output_var(line: &str) -> Option<String> {
+    let rest = line.trim().strip_prefix("let ")?;
+    let (name, _) = rest.split_once('=')?;
+    name.trim().strip_suffix("_out").map(|id| id.to_string())
+}
+
+/// Identifiers in HLX code, skipping string literals and comments
+fn identifiers(code: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut chars = code.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c == '"' {
+            let mut escaped = false;
+            for (_, c) in chars.by_ref() {
+                match c {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => break,
+                    _ => escaped = false,
+                }
+            }
+        } else if c == '/' && chars.peek().map(|(_, c)| *c) == Some('/') {
+            for (_, c) in chars.by_ref() {
+                if c == '\n' {
+                    break;
+                }
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            out.push(&code[start..end]);
+        }
+    }
+    out
+}
+
+/// Statements of `main` attributed to one node
+struct Block {
+    node: Option<Node>,
+    /// Trimmed, non-empty lines
+    lines: Vec<String>,
+}
+
+/// The parts of a program the decompiler cares about
+struct Program {
+    flow: Option<Flow>,
+    edges: Vec<Edge>,
+    blocks: Vec<Block>,
+    /// Helper functions by name, with their bodies dedented
+    functions: HashMap<String, Vec<String>>,
+}
+
+impl Program {
+    fn parse(source: &str) -> Program {
+        let mut program = Program { flow: None, edges: Vec::new(), blocks: Vec::new(), functions: HashMap::new() };
+        let mut function: Option<(String, Vec<String>)> = None;
+        let mut in_main = false;
+
+        for raw in source.lines() {
+            let line = raw.trim();
+
+            if let Some((name, body)) = &mut function {
+                if raw.starts_with('}') {
+                    program.functions.insert(name.clone(), std::mem::take(body));
+                    function = None;
+                } else {
+                    body.push(raw.strip_prefix("    ").unwrap_or(raw).to_string());
+                }
+                continue;
+            }
+
+            if let Some(json) = line.strip_prefix(FLOW_MARKER) {
+                program.flow = serde_json::from_str(json).ok();
+            } else if let Some(json) = line.strip_prefix(EDGE_MARKER) {
+                program.edges.extend(serde_json::from_str::<Edge>(json).ok());
+            } else if line.starts_with("fn main(") {
+                in_main = true;
+            } else if let Some(signature) = line.strip_prefix("fn ") {
+                let name = signature.split('(').next().unwrap_or("").trim().to_string();
+                function = Some((name, Vec::new()));
+            } else if in_main {
+                if let Some(json) = line.strip_prefix(NODE_MARKER) {
+                    program.blocks.push(Block { node: serde_json::from_str(json).ok(), lines: Vec::new() });
+                } else if line.starts_with("return ") || raw.starts_with('}') {
+                    // The final return only gathers node outputs
+                    in_main = false;
+                } else if !line.is_empty() {
+                    if program.blocks.is_empty() {
+                        program.blocks.push(Block { node: None, lines: Vec::new() });
+                    }
+                    program.blocks.last_mut().unwrap().lines.push(line.to_string());
+                }
+            }
+        }
+        program
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, type_name: &str, config: serde_json::Value) -> Node {
+        Node { position: Some(Position { x: 10.0, y: 20.0 }), ..Node::new(id, type_name, config) }
+    }
+
+    fn edge(source: &str, target: &str) -> Edge {
+        Edge { source: source.to_string(), target: target.to_string(), source_handle: None, target_handle: None }
+    }
+
+    fn sample_flow() -> Flow {
+        Flow {
+            nodes: vec![
+                node("start", "start", json!({})),
+                node("get", "json_get", json!({"key": "name"})),
+                node("upper", "string_upper", json!({})),
+            ],
+            edges: vec![edge("start", "get"), edge("get", "upper")],
+            seed: Some(3),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn untouched_programs_convert_back_exactly() {
+        let flow = sample_flow();
+        let decompiled = decompile(&flow.compile_to_hlx()).unwrap();
+
+        assert!(decompiled.warnings.is_empty(), "{:?}", decompiled.warnings);
+        assert_eq!(to_json(&decompiled.flow), to_json(&flow));
+    }
+
+    #[test]
+    fn edited_nodes_become_scripts() {
+        let flow = sample_flow();
+        let source = flow.compile_to_hlx().replace(
+            "let upper_out = to_upper(get_out);",
+            "let upper_out = to_upper(get_out) + \"!\";",
+        );
+        let decompiled = decompile(&source).unwrap();
+
+        let upper = decompiled.flow.nodes.iter().find(|n| n.id == "upper").unwrap();
+        assert_eq!(upper.type_name, "hlx_script");
+        assert!(upper.config["code"].as_str().unwrap().contains("+ \"!\""));
+        assert_eq!(upper.config["inputs"]["get_out"], "get");
+        assert_eq!(decompiled.warnings.len(), 1);
+    }
+
+    #[test]
+    fn lines_map_to_the_nodes_that_generated_them() {
+        let mut flow = sample_flow();
+        flow.nodes.push(node("calc", "hlx_script", json!({"code": "return input;"})));
+        let source = flow.compile_to_hlx();
+        let owners = line_nodes(&source);
+
+        let owner = |text: &str| {
+            let index = source.lines().position(|line| line.contains(text)).unwrap();
+            owners[index].as_deref()
+        };
+        assert_eq!(owner("let upper_out"), Some("upper"));
+        assert_eq!(owner("fn calc_script"), Some("calc"));
+        assert_eq!(owner("    return input;"), Some("calc"));
+        assert_eq!(owner("fn main"), None);
+        let final_return = source.lines().rposition(|line| line.starts_with("    return ")).unwrap();
+        assert_eq!(owners[final_return], None);
+    }
+
+    #[test]
+    fn programs_without_a_source_map_are_imported_as_scripts() {
+        let source = "program workflow {\n\nfn main(input) {\n    let a_out = 1;\n    let b_out = a_out + 1;\n    return b_out;\n}\n\n}\n";
+        let decompiled = decompile(source).unwrap();
+
+        let ids: Vec<&str> = decompiled.flow.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(decompiled.flow.edges.len(), 1);
+        assert_eq!(decompiled.flow.edges[0].source, "a");
+    }
+}
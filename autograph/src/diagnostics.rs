@@ -0,0 +1,162 @@
+//! Compiler Diagnostics
+//!
+//! Parse and lowering errors from `hlx_compiler` come back as one string
+//! about the generated program. This finds the location in the error
+//! (`line 12, column 5`, `12:5` or a byte offset), maps that line of the
+//! generated source back to the node that produced it through the source
+//! map, and falls back to the `<node>_out` variables the message names,
+//! so the editor can show the error on the offending node.
+
+use crate::flow::Flow;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+/// Compiler stage an error came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Parse,
+    Lowering,
+}
+
+impl Stage {
+    fn label(self) -> &'static str {
+        match self {
+            Stage::Parse => "Parse error",
+            Stage::Lowering => "Lowering error",
+        }
+    }
+}
+
+/// A compiler error located in the generated source and, when possible,
+/// attributed to a node
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub stage: Stage,
+    pub message: String,
+    /// 1-based line of the generated source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// 1-based column of the generated source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Node whose generated code the error is in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(node) = &self.node {
+            write!(f, "{}: ", node)?;
+        }
+        write!(f, "{}", self.stage.label())?;
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, " at line {}:{}", line, column)?,
+            (Some(line), None) => write!(f, " at line {}", line)?,
+            _ => {}
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Diagnostic {
+    /// Locate a compiler error in the `source` compiled from `flow`
+    pub fn locate(stage: Stage, message: &str, flow: &Flow, source: &str) -> Self {
+        let (line, column) = match location(message, source) {
+            Some((line, column)) => (Some(line), column),
+            None => (None, None),
+        };
+        let node = line
+            .and_then(|line| crate::decompile::line_nodes(source).into_iter().nth(line - 1).flatten())
+            .or_else(|| named_node(message, flow));
+        Self { stage, message: message.to_string(), line, column, node }
+    }
+
+    /// The diagnostic for a failed run's error, if the compiler rejected
+    /// the program (runtime errors aren't located)
+    pub fn from_run_error(error: &str, flow: &Flow, source: &str) -> Option<Self> {
+        [Stage::Parse, Stage::Lowering].into_iter().find_map(|stage| {
+            let message = error.strip_prefix(stage.label())?.trim_start_matches(':').trim();
+            Some(Self::locate(stage, message, flow, source))
+        })
+    }
+}
+
+/// Parse and lower `source` compiled from `flow`, locating the first error
+pub fn check(flow: &Flow, source: &str) -> Option<Diagnostic> {
+    use hlx_compiler::hlxa::HlxaParser;
+    use hlx_compiler::parser::Parser;
+
+    match HlxaParser.parse(source) {
+        Err(e) => Some(Diagnostic::locate(Stage::Parse, &e.to_string(), flow, source)),
+        Ok(program) => hlx_compiler::lower::lower_to_crate(&program)
+            .err()
+            .map(|e| Diagnostic::locate(Stage::Lowering, &e.to_string(), flow, source)),
+    }
+}
+
+/// Line and, if given, column of the first location in `message`
+fn location(message: &str, source: &str) -> Option<(usize, Option<usize>)> {
+    static LINE: OnceLock<Regex> = OnceLock::new();
+    static PAIR: OnceLock<Regex> = OnceLock::new();
+    static OFFSET: OnceLock<Regex> = OnceLock::new();
+
+    let line = LINE.get_or_init(|| Regex::new(r"(?i)\bline:?\s*(\d+)(?:\D{1,10}?col(?:umn)?:?\s*(\d+))?").unwrap());
+    if let Some(caps) = line.captures(message) {
+        return Some((caps[1].parse().ok()?, caps.get(2).and_then(|c| c.as_str().parse().ok())));
+    }
+
+    let pair = PAIR.get_or_init(|| Regex::new(r"\b(\d+):(\d+)\b").unwrap());
+    if let Some(caps) = pair.captures(message) {
+        return Some((caps[1].parse().ok()?, caps[2].parse().ok()));
+    }
+
+    // A byte offset into the source
+    let offset = OFFSET.get_or_init(|| Regex::new(r"(?i)\b(?:offset|position|pos|byte)\s*:?\s*(\d+)").unwrap());
+    let offset: usize = offset.captures(message)?[1].parse().ok()?;
+    let before = source.get(..offset.min(source.len()))?;
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    Some((line, Some(column)))
+}
+
+/// The node whose output variable (`<id>_out`) or script function
+/// (`<id>_script`) the message names
+fn named_node(message: &str, flow: &Flow) -> Option<String> {
+    static NAME: OnceLock<Regex> = OnceLock::new();
+    let name = NAME.get_or_init(|| Regex::new(r"\b([A-Za-z_][A-Za-z0-9_]*?)_(?:out|script)\b").unwrap());
+    name.captures_iter(message)
+        .map(|caps| caps[1].to_string())
+        .find(|id| flow.nodes.iter().any(|n| &n.id == id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::Node;
+    use serde_json::json;
+
+    #[test]
+    fn errors_map_back_to_the_node_that_generated_the_line() {
+        let node = |id: &str, type_name: &str| Node::new(id, type_name, json!({}));
+        let flow = Flow { nodes: vec![node("start", "start"), node("shout", "string_upper")], ..Default::default() };
+        let source = flow.compile_to_hlx();
+        let line = source.lines().position(|l| l.contains("let shout_out")).unwrap() + 1;
+
+        let at_line = Diagnostic::from_run_error(&format!("Parse error: unexpected token at line {}, column 9", line), &flow, &source).unwrap();
+        assert_eq!((at_line.stage, at_line.line, at_line.column), (Stage::Parse, Some(line), Some(9)));
+        assert_eq!(at_line.node.as_deref(), Some("shout"));
+
+        let offset = source.find("let shout_out").unwrap();
+        let at_offset = Diagnostic::locate(Stage::Parse, &format!("expected ';' at offset {}", offset), &flow, &source);
+        assert_eq!(at_offset.node.as_deref(), Some("shout"));
+
+        let named = Diagnostic::from_run_error("Lowering error: undefined variable start_out", &flow, &source).unwrap();
+        assert_eq!((named.line, named.node.as_deref()), (None, Some("start")));
+        assert_eq!(named.to_string(), "start: Lowering error: undefined variable start_out");
+
+        assert!(Diagnostic::from_run_error("Runtime error: division by zero", &flow, &source).is_none());
+    }
+}
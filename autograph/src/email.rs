@@ -0,0 +1,132 @@
+//! Email Triggers
+//!
+//! An `email_read` node with `"trigger": true` makes a deployed flow
+//! inbox-driven: while the server runs, it polls the node's IMAP folder
+//! every `poll_secs` and starts a run of the flow per new message, with the
+//! parsed message as its input. Polls run the node itself, in fetch mode,
+//! through the HLX runtime; they always take unseen messages and mark them
+//! read, so each message starts one run. The server saves each message as a
+//! queued run right away, so one a restart cuts off is not lost.
+
+use crate::flow::{Flow, Node};
+use serde_json::Value as JsonValue;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Seconds between polls when the node doesn't say
+pub const DEFAULT_POLL_SECS: u64 = 60;
+
+/// Shortest time between polls, so a typo can't hammer the mail server
+const MIN_POLL_SECS: u64 = 10;
+
+/// How often a waiting poller checks whether to stop
+const STOP_CHECK: Duration = Duration::from_secs(1);
+
+/// A deployed flow's `email_read` trigger
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Trigger {
+    pub flow_name: String,
+    pub node_id: String,
+    pub folder: String,
+    pub poll_secs: u64,
+    /// The node's config as JSON, so a changed mailbox restarts the poller
+    config: String,
+}
+
+impl Trigger {
+    /// `email:<folder>`, used as the run's `trigger` label
+    pub fn label(&self) -> String {
+        format!("email:{}", self.folder)
+    }
+
+    /// A one-node flow fetching the messages a poll picks up
+    pub fn poll_flow(&self) -> Flow {
+        let mut config: JsonValue = serde_json::from_str(&self.config).unwrap_or_default();
+        config["trigger"] = serde_json::json!(false);
+        config["unseen_only"] = serde_json::json!(true);
+        config["mark_read"] = serde_json::json!(true);
+        Flow {
+            nodes: vec![Node {
+                id: self.node_id.clone(),
+                type_name: "email_read".to_string(),
+                config,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+}
+
+/// Triggers of a flow's enabled `email_read` nodes set to trigger
+pub fn triggers(flow_name: &str, flow: &Flow) -> Vec<Trigger> {
+    flow.nodes.iter()
+        .filter(|n| n.type_name == "email_read" && n.enabled && n.config["trigger"].as_bool().unwrap_or(false))
+        .map(|n| Trigger {
+            flow_name: flow_name.to_string(),
+            node_id: n.id.clone(),
+            folder: n.config["folder"].as_str().map(str::trim).filter(|f| !f.is_empty()).unwrap_or("INBOX").to_string(),
+            poll_secs: n.config["poll_secs"].as_u64().unwrap_or(DEFAULT_POLL_SECS).max(MIN_POLL_SECS),
+            config: n.config.to_string(),
+        })
+        .collect()
+}
+
+/// Poll for `trigger` on a background thread until `stop` is set. `poll`
+/// runs the trigger's poll flow, returning its output; `deliver` gets each
+/// message's run input, or the error of a failed poll.
+pub fn spawn_poller(
+    trigger: Trigger,
+    stop: Arc<AtomicBool>,
+    poll: impl Fn(&Flow) -> Result<JsonValue, String> + Send + 'static,
+    deliver: impl Fn(Result<JsonValue, String>) + Send + 'static,
+) {
+    std::thread::spawn(move || {
+        let flow = trigger.poll_flow();
+        let interval = Duration::from_secs(trigger.poll_secs);
+        while !stop.load(Ordering::Relaxed) {
+            let started = Instant::now();
+            match poll(&flow) {
+                Ok(JsonValue::Array(messages)) => messages.into_iter().for_each(|message| deliver(Ok(message))),
+                Ok(other) => deliver(Err(format!("expected an array of messages, got {}", other))),
+                Err(e) => deliver(Err(e)),
+            }
+            while started.elapsed() < interval {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                std::thread::sleep(STOP_CHECK.min(interval.saturating_sub(started.elapsed())));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn trigger_nodes_poll_unseen_messages_and_mark_them_read() {
+        let node = |id: &str, config: JsonValue| Node::new(id, "email_read", config);
+        let flow = Flow {
+            nodes: vec![
+                node("invoices", json!({"host": "imap.example.com", "username": "env:IMAP_USER", "password": "env:IMAP_PASSWORD",
+                    "folder": "Invoices", "unseen_only": false, "trigger": true, "poll_secs": 1})),
+                node("digest", json!({"host": "imap.example.com", "trigger": false})),
+            ],
+            ..Default::default()
+        };
+
+        let triggers = triggers("billing", &flow);
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].label(), "email:Invoices");
+        assert_eq!(triggers[0].poll_secs, MIN_POLL_SECS);
+
+        let source = triggers[0].poll_flow().compile_to_hlx();
+        assert!(source.contains("imap_fetch({ \"host\": \"imap.example.com\", \"port\": 993, \"tls\": true, \"username\": env(\"IMAP_USER\")"));
+        assert!(source.contains("\"Invoices\", true, true, 50)"));
+        // In the deployed flow, the trigger node passes the message on
+        assert!(flow.compile_to_hlx().contains("let invoices_out = input;"));
+    }
+}
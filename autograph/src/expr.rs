@@ -0,0 +1,445 @@
+//! Item Expressions
+//!
+//! The small expression language of `array_map`, `array_filter` and
+//! `array_reduce`: `item.price > 10`, `item.name`, `acc + item.qty * 2`.
+//! Expressions are parsed here and compiled to HLX, so a typo is reported
+//! in the generated code instead of failing at run time.
+//!
+//! Supported: the names the node binds (`item`, `index`, `acc`), numbers,
+//! strings in single or double quotes, `true`/`false`/`null`, field access
+//! (`item.a.b`, `item["a b"]`, `item.tags[0]`), `+ - * / %`, comparisons,
+//! `&&`/`and`, `||`/`or`, `!`/`not` and parentheses.
+//!
+//! The same language fills `{{ ... }}` expressions in any string config
+//! field, with `$input` for the flow's input and `$node("id")` for another
+//! node's output: `{{ $node("http1").body.items[0].id }}`. A node can only
+//! use the output of nodes that run before it. Placeholders without `$`,
+//! like `{{name}}`, are left to template nodes.
+
+use crate::nodes::hlx_string;
+use serde_json::Value as JsonValue;
+
+/// Compile `source` to an HLX expression. `bindings` maps each name the
+/// expression may use to the HLX variable holding it.
+pub fn compile(source: &str, bindings: &[(&str, &str)]) -> Result<String, String> {
+    let tokens = tokenize(source)?;
+    if tokens.is_empty() {
+        return Err("expression is empty".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0, bindings, nodes: &[], upstream: 0 };
+    let code = parser.or()?;
+    match parser.peek() {
+        None => Ok(code),
+        Some(token) => Err(format!("unexpected {}", token.describe())),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Str(String),
+    Name(String),
+    /// Operators and punctuation, with word operators normalized to symbols
+    Op(&'static str),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Number(n) => format!("number {}", n),
+            Token::Str(s) => format!("string {:?}", s),
+            Token::Name(n) => format!("'{}'", n),
+            Token::Op(op) => format!("'{}'", op),
+        }
+    }
+}
+
+const OPERATORS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "+", "-", "*", "/", "%", "!", "(", ")", "[", "]", ".",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            if number.parse::<f64>().is_err() {
+                return Err(format!("invalid number {}", number));
+            }
+            tokens.push(Token::Number(number));
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err("unterminated string".to_string()),
+                    Some(&q) if q == c => break,
+                    Some('\\') if i + 1 < chars.len() => {
+                        text.push(chars[i + 1]);
+                        i += 2;
+                    }
+                    Some(&ch) => {
+                        text.push(ch);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push(Token::Str(text));
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(match word.as_str() {
+                "and" => Token::Op("&&"),
+                "or" => Token::Op("||"),
+                "not" => Token::Op("!"),
+                _ => Token::Name(word),
+            });
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS.iter().find(|op| rest.starts_with(**op)).ok_or_else(|| format!("unexpected '{}'", c))?;
+            i += op.len();
+            tokens.push(Token::Op(op));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent, lowest precedence first. Every binary operation is
+/// parenthesized in the output so HLX precedence never matters.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    bindings: &'a [(&'a str, &'a str)],
+    /// Every node of the flow, in run order
+    nodes: &'a [&'a str],
+    /// How many of `nodes` run before the expression's node, and so may
+    /// be used with `$node("id")`
+    upstream: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn binary(&mut self, ops: &[&str], next: fn(&mut Self) -> Result<String, String>) -> Result<String, String> {
+        let mut left = next(self)?;
+        while let Some(op) = ops.iter().find(|op| self.eat(op)) {
+            let right = next(self)?;
+            left = format!("({} {} {})", left, op, right);
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<String, String> {
+        self.binary(&["||"], Self::and)
+    }
+
+    fn and(&mut self) -> Result<String, String> {
+        self.binary(&["&&"], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<String, String> {
+        self.binary(&["==", "!=", "<=", ">=", "<", ">"], Self::additive)
+    }
+
+    fn additive(&mut self) -> Result<String, String> {
+        self.binary(&["+", "-"], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> Result<String, String> {
+        self.binary(&["*", "/", "%"], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<String, String> {
+        if self.eat("!") {
+            return Ok(format!("!{}", self.unary()?));
+        }
+        if self.eat("-") {
+            return Ok(format!("(0 - {})", self.unary()?));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<String, String> {
+        let mut value = self.primary()?;
+        loop {
+            if self.eat(".") {
+                match self.tokens.get(self.pos).cloned() {
+                    Some(Token::Name(field)) => {
+                        self.pos += 1;
+                        value = format!("get({}, {})", value, hlx_string(&field));
+                    }
+                    other => return Err(format!("expected a field name after '.', found {}", describe(other.as_ref()))),
+                }
+            } else if self.eat("[") {
+                value = match self.tokens.get(self.pos).cloned() {
+                    Some(Token::Str(key)) => {
+                        self.pos += 1;
+                        format!("get({}, {})", value, hlx_string(&key))
+                    }
+                    _ => format!("{}[{}]", value, self.or()?),
+                };
+                if !self.eat("]") {
+                    return Err(format!("expected ']', found {}", describe(self.peek())));
+                }
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<String, String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::Str(s)) => Ok(hlx_string(&s)),
+            Some(Token::Name(name)) => match name.as_str() {
+                "true" | "false" | "null" => Ok(name),
+                "$node" if !self.nodes.is_empty() => self.node_output(),
+                _ => self.bindings
+                    .iter()
+                    .find(|(bound, _)| *bound == name)
+                    .map(|(_, var)| var.to_string())
+                    .ok_or_else(|| {
+                        let names: Vec<&str> = self.bindings.iter().map(|(bound, _)| *bound).collect();
+                        format!("unknown name '{}' (use {})", name, names.join(", "))
+                    }),
+            },
+            Some(Token::Op("(")) => {
+                let inner = self.or()?;
+                if self.eat(")") {
+                    Ok(inner)
+                } else {
+                    Err(format!("expected ')', found {}", describe(self.peek())))
+                }
+            }
+            other => Err(format!("expected a value, found {}", describe(other.as_ref()))),
+        }
+    }
+}
+
+impl Parser<'_> {
+    /// `("id")` after `$node`: the node's output variable
+    fn node_output(&mut self) -> Result<String, String> {
+        if !self.eat("(") {
+            return Err(format!("expected '(' after $node, found {}", describe(self.peek())));
+        }
+        let id = match self.tokens.get(self.pos).cloned() {
+            Some(Token::Str(id)) => id,
+            other => return Err(format!("expected a node ID in quotes, found {}", describe(other.as_ref()))),
+        };
+        self.pos += 1;
+        if !self.eat(")") {
+            return Err(format!("expected ')', found {}", describe(self.peek())));
+        }
+        match self.nodes.iter().position(|n| *n == id) {
+            None => Err(format!("unknown node '{}'", id)),
+            Some(i) if i == self.upstream => Err(format!("node '{}' can't use its own output", id)),
+            Some(i) if i > self.upstream => Err(format!("node '{}' runs after this one", id)),
+            Some(_) => Ok(format!("{}_out", id)),
+        }
+    }
+}
+
+/// Compile the inside of a `{{ $... }}` config expression of the node
+/// that runs after the first `upstream` of `nodes`
+pub fn compile_config_expression(source: &str, nodes: &[&str], upstream: usize) -> Result<String, String> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0, bindings: &[("$input", "input")], nodes, upstream };
+    let code = parser.or()?;
+    match parser.peek() {
+        None => Ok(code),
+        Some(token) => Err(format!("unexpected {} in {{{{ {} }}}}", token.describe(), source)),
+    }
+}
+
+/// Whether `text` holds a `{{ $... }}` expression
+pub fn has_config_expression(text: &str) -> bool {
+    text.split("{{").skip(1).any(|part| part.trim_start().starts_with('$') && part.contains("}}"))
+}
+
+/// A config expression, swapped for a placeholder before the node's code
+/// is generated
+pub struct ConfigExpression {
+    /// Dotted path of the field, e.g. `headers.Authorization`
+    pub field: String,
+    pub placeholder: String,
+    pub code: String,
+}
+
+/// Replace every `{{ $... }}` in the string fields of `config` with a
+/// placeholder, returning the compiled expressions for `splice`. `nodes`
+/// are the flow's nodes in run order, and the config's node runs after
+/// the first `upstream` of them.
+pub fn bind_config_expressions(config: &mut JsonValue, nodes: &[&str], upstream: usize) -> Result<Vec<ConfigExpression>, String> {
+    let mut bound = Vec::new();
+    bind_value(config, "", (nodes, upstream), &mut bound)?;
+    Ok(bound)
+}
+
+fn bind_value(value: &mut JsonValue, path: &str, nodes: (&[&str], usize), bound: &mut Vec<ConfigExpression>) -> Result<(), String> {
+    let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+    match value {
+        JsonValue::String(text) if has_config_expression(text) => {
+            let mut out = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("{{") {
+                let Some(len) = rest[start..].find("}}") else { break };
+                let inner = rest[start + 2..start + len].trim();
+                if inner.starts_with('$') {
+                    let code = compile_config_expression(inner, nodes.0, nodes.1).map_err(|e| format!("{}: {}", path, e))?;
+                    let placeholder = format!("__autograph_expr_{}__", bound.len());
+                    out.push_str(&rest[..start]);
+                    out.push_str(&placeholder);
+                    bound.push(ConfigExpression { field: path.to_string(), placeholder, code });
+                } else {
+                    // A template placeholder, left for the node
+                    out.push_str(&rest[..start + len + 2]);
+                }
+                rest = &rest[start + len + 2..];
+            }
+            out.push_str(rest);
+            *value = JsonValue::String(out);
+        }
+        JsonValue::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                bind_value(field, &child(key), nodes, bound)?;
+            }
+        }
+        JsonValue::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                bind_value(item, &child(&i.to_string()), nodes, bound)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Put the expressions into a node's generated code. A string literal that
+/// is one placeholder becomes the expression's value; other literals
+/// holding placeholders become the text joined with the values as strings.
+/// Fails with the field of a placeholder that didn't end up in a literal,
+/// because the node reworked the field's text.
+pub fn splice(code: &str, expressions: &[ConfigExpression]) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = code;
+
+    while let Some(at) = expressions.iter().filter_map(|e| rest.find(&e.placeholder)).min() {
+        // The literal around the placeholder
+        let open = rest[..at].char_indices().rev().find(|&(i, c)| c == '"' && !is_escaped(rest, i)).map(|(i, _)| i);
+        let close = rest[at..].char_indices().find(|&(i, c)| c == '"' && !is_escaped(rest, at + i)).map(|(i, _)| at + i);
+        let (Some(open), Some(close)) = (open, close) else {
+            let field = expressions.iter().find(|e| rest[at..].starts_with(&e.placeholder)).map(|e| e.field.as_str());
+            return Err(field.unwrap_or_default().to_string());
+        };
+
+        let mut parts: Vec<String> = Vec::new();
+        let mut text = &rest[open + 1..close];
+        while let Some((i, expression)) = expressions.iter().filter_map(|e| Some((text.find(&e.placeholder)?, e))).min_by_key(|(i, _)| *i) {
+            if i > 0 {
+                parts.push(format!("\"{}\"", &text[..i]));
+            }
+            parts.push(format!("to_string({})", expression.code));
+            text = &text[i + expression.placeholder.len()..];
+        }
+        if !text.is_empty() {
+            parts.push(format!("\"{}\"", text));
+        }
+
+        out.push_str(&rest[..open]);
+        match parts.as_slice() {
+            [only] if only.starts_with("to_string(") => out.push_str(&only["to_string(".len()..only.len() - 1]),
+            _ => out.push_str(&format!("({})", parts.join(" + "))),
+        }
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Whether the quote at `index` is escaped by a backslash
+fn is_escaped(text: &str, index: usize) -> bool {
+    text[..index].chars().rev().take_while(|&c| c == '\\').count() % 2 == 1
+}
+
+fn describe(token: Option<&Token>) -> String {
+    token.map_or_else(|| "end of expression".to_string(), Token::describe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINDINGS: &[(&str, &str)] = &[("item", "n_item"), ("index", "n_i")];
+
+    #[test]
+    fn compiles_fields_and_operators() {
+        assert_eq!(compile("item.name", BINDINGS).unwrap(), r#"get(n_item, "name")"#);
+        assert_eq!(
+            compile("item.price > 10 and not item['on sale']", BINDINGS).unwrap(),
+            r#"((get(n_item, "price") > 10) && !get(n_item, "on sale"))"#
+        );
+        assert_eq!(compile("1 + index * 2", BINDINGS).unwrap(), "(1 + (n_i * 2))");
+        assert_eq!(compile("item.tags[0]", BINDINGS).unwrap(), r#"get(n_item, "tags")[0]"#);
+    }
+
+    #[test]
+    fn splices_config_expressions_into_generated_literals() {
+        let nodes = &["http1", "start", "post"];
+        let mut config = serde_json::json!({
+            "url": "https://api/{{ $input.user }}?q={{q}}",
+            "id": "{{ $node(\"http1\").body.items[0].id }}",
+        });
+        let bound = bind_config_expressions(&mut config, nodes, 2).unwrap();
+        assert_eq!(config["url"], "https://api/__autograph_expr_1__?q={{q}}");
+        assert!(!has_config_expression("Hello {{name}}"));
+
+        let code = format!("f({}, {});", hlx_string(config["url"].as_str().unwrap()), hlx_string(config["id"].as_str().unwrap()));
+        assert_eq!(
+            splice(&code, &bound).unwrap(),
+            r#"f(("https://api/" + to_string(get(input, "user")) + "?q={{q}}"), get(get(get(http1_out, "body"), "items")[0], "id"));"#
+        );
+        assert_eq!(splice("f(len(__autograph_expr_0__))", &bound).unwrap_err(), "id");
+
+        let mut config = serde_json::json!({"url": "{{ $node(\"nope\") }}"});
+        assert_eq!(bind_config_expressions(&mut config, nodes, 2).unwrap_err(), "url: unknown node 'nope'");
+        let config = serde_json::json!({"url": "{{ $node(\"post\").id }}"});
+        assert_eq!(bind_config_expressions(&mut config.clone(), nodes, 2).unwrap_err(), "url: node 'post' can't use its own output");
+        assert_eq!(bind_config_expressions(&mut config.clone(), nodes, 1).unwrap_err(), "url: node 'post' runs after this one");
+    }
+
+    #[test]
+    fn rejects_unknown_names_and_bad_syntax() {
+        assert_eq!(compile("acc + 1", BINDINGS).unwrap_err(), "unknown name 'acc' (use item, index)");
+        assert!(compile("item.", BINDINGS).is_err());
+        assert!(compile("(item", BINDINGS).is_err());
+        assert!(compile("item = 1", BINDINGS).is_err());
+        assert!(compile("  ", BINDINGS).is_err());
+    }
+}
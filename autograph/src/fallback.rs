@@ -0,0 +1,74 @@
+//! CPU Fallback for Tensor Nodes
+//!
+//! When a flow fails on the GPU, each tensor node is run on its own (with
+//! its ancestors) to find the ones the Vulkan backend can't handle, e.g.
+//! an unsupported op or running out of device memory. Those nodes are
+//! computed on the CPU instead and their outputs pinned, so the rest of the
+//! flow can run on the GPU again rather than the whole run failing.
+
+use crate::flow::{CompileOptions, Flow};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// Node outputs to pin for the retry, and what was moved to the CPU
+pub struct Fallback {
+    /// Outputs computed while isolating the failing nodes
+    pub fixed_outputs: HashMap<String, JsonValue>,
+    /// Execution log lines describing each fallback
+    pub log: Vec<String>,
+}
+
+/// Whether a node runs on the tensor backend
+pub fn is_tensor_node(type_name: &str) -> bool {
+    type_name.starts_with("tensor_") || type_name == "model_infer"
+}
+
+/// Find the tensor nodes that fail on the GPU and compute them on the CPU.
+///
+/// `run(source, input, on_cpu)` executes HLX on the GPU backend, or on the
+/// CPU when `on_cpu` is set. Returns `None` when no tensor node needed the
+/// CPU, or when one fails there too, meaning the error lies elsewhere.
+pub fn resolve_on_cpu(
+    flow: &Flow,
+    options: &CompileOptions,
+    input: &JsonValue,
+    mut run: impl FnMut(&str, &JsonValue, bool) -> Result<JsonValue, String>,
+) -> Option<Fallback> {
+    let mut known = options.fixed_outputs.clone();
+    let mut log = Vec::new();
+
+    for node in flow.nodes.iter().filter(|n| is_tensor_node(&n.type_name)) {
+        if known.contains_key(&node.id) {
+            continue;
+        }
+
+        // Run the node with its ancestors, reusing outputs already computed
+        let sub_options = CompileOptions {
+            capture_outputs: true,
+            fixed_outputs: known.clone(),
+            ..options.clone()
+        };
+        let source = flow.ancestor_subgraph(&node.id).compile_with(&sub_options);
+
+        let json = match run(&source, input, false) {
+            Ok(json) => json,
+            Err(gpu_error) => {
+                let json = run(&source, input, true).ok()?;
+                log.push(format!(
+                    "⚠ {} ({}) failed on GPU, ran on CPU instead: {}",
+                    node.id, node.type_name, gpu_error
+                ));
+                json
+            }
+        };
+
+        for (id, value) in json["outputs"].as_object().into_iter().flatten() {
+            known.insert(id.clone(), value.clone());
+        }
+    }
+
+    if log.is_empty() {
+        return None;
+    }
+    Some(Fallback { fixed_outputs: known, log })
+}
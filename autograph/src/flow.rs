@@ -1,9 +1,146 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Flow {
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    /// RNG seed that makes random nodes reproducible
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// History of saves and deploys, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changelog: Vec<ChangelogEntry>,
+    /// What the server does with runs interrupted by a crash or restart
+    #[serde(default, skip_serializing_if = "InterruptPolicy::is_default")]
+    pub on_interrupt: InterruptPolicy,
+    /// Template the flow was created from, built-in or user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Retry tensor nodes on the CPU when they fail on the GPU
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cpu_fallback: bool,
+    /// Automatic re-runs of failed runs triggered through the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Faults injected into nodes when the flow runs in chaos mode
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chaos: Vec<crate::chaos::FaultRule>,
+    /// External resources the flow needs, verified by preflight checks
+    #[serde(default, skip_serializing_if = "crate::preflight::Requirements::is_empty")]
+    pub requires: crate::preflight::Requirements,
+    /// Sticky notes documenting the flow on the canvas; never compiled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<StickyNote>,
+    /// Named frames around nodes on the canvas
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<NodeGroup>,
+    /// JSON Schema of the input the flow expects; `/run` rejects payloads
+    /// that don't match and the editor builds its run form from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+    /// Canned node outputs used instead of real services in mock runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mocks: Vec<crate::mock::MockRule>,
+    /// Files, by path, that file nodes see in mock runs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mock_files: BTreeMap<String, String>,
+    /// Node outputs pinned as fixture data, used by editor runs instead of
+    /// running the node
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pinned: BTreeMap<String, serde_json::Value>,
+    /// Rate limits on the HTTP nodes' requests, by host
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rate_limits: BTreeMap<String, crate::throttle::RateLimit>,
+    /// How the canvas draws edges
+    #[serde(default, skip_serializing_if = "EdgeStyle::is_default")]
+    pub edge_style: EdgeStyle,
+}
+
+/// Recovery policy for runs interrupted by a server crash or restart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptPolicy {
+    /// Mark the run as interrupted and leave it
+    #[default]
+    Mark,
+    /// Mark the run as interrupted and run it again with the same input
+    Rerun,
+}
+
+impl InterruptPolicy {
+    fn is_default(&self) -> bool {
+        *self == InterruptPolicy::default()
+    }
+}
+
+/// Shape of the edges on the canvas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeStyle {
+    /// Curves leaving and entering nodes vertically
+    #[default]
+    Bezier,
+    /// Vertical and horizontal segments with right-angle elbows
+    Orthogonal,
+    /// Straight lines from output to input
+    Straight,
+}
+
+impl EdgeStyle {
+    pub const ALL: &'static [EdgeStyle] = &[EdgeStyle::Bezier, EdgeStyle::Orthogonal, EdgeStyle::Straight];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EdgeStyle::Bezier => "Bezier",
+            EdgeStyle::Orthogonal => "Orthogonal",
+            EdgeStyle::Straight => "Straight",
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self == EdgeStyle::default()
+    }
+}
+
+/// When and how often the server re-runs a failed run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Re-runs after the first failure
+    pub max_retries: u32,
+    /// Seconds to wait before each re-run
+    #[serde(default)]
+    pub delay_secs: u64,
+    /// Only retry errors containing one of these codes or messages, e.g.
+    /// `"429"` or `"timed out"`; empty retries every failure
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_errors: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, delay_secs: 60, on_errors: Vec::new() }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether attempt number `attempt` (starting at 1) failing with `error`
+    /// gets another run
+    pub fn should_retry(&self, attempt: u32, error: &str) -> bool {
+        attempt <= self.max_retries
+            && (self.on_errors.is_empty() || self.on_errors.iter().any(|code| error.contains(code.as_str())))
+    }
+}
+
+/// One entry in a flow's changelog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+    pub author: String,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +151,66 @@ pub struct Node {
     pub position: Option<Position>, // For UI only
     #[serde(default)]
     pub breakpoint: bool, // For debugging
+    /// Disabled nodes are bypassed: their input passes straight through
+    #[serde(default = "Node::enabled_by_default", skip_serializing_if = "Node::is_enabled")]
+    pub enabled: bool,
+    /// Name drawn on the canvas instead of the type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Notes for whoever reads the flow, shown when hovering the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Color tag as `#rrggbb`, e.g. to tell production branches from debug ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Icon drawn before the node's name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            type_name: String::new(),
+            config: serde_json::json!({}),
+            position: None,
+            breakpoint: false,
+            enabled: true,
+            label: None,
+            notes: None,
+            color: None,
+            icon: None,
+        }
+    }
+}
+
+impl Node {
+    /// An enabled node with no position or annotations
+    pub fn new(id: &str, type_name: &str, config: serde_json::Value) -> Self {
+        Self { id: id.to_string(), type_name: type_name.to_string(), config, ..Default::default() }
+    }
+
+    /// The node's label, or its type when it has none
+    pub fn display_name(&self) -> &str {
+        self.label.as_deref().map(str::trim).filter(|l| !l.is_empty()).unwrap_or(&self.type_name)
+    }
+
+    /// The node's name on the canvas: its icon, if any, and display name
+    pub fn title(&self) -> String {
+        match self.icon.as_deref().filter(|i| !i.is_empty()) {
+            Some(icon) => format!("{} {}", icon, self.display_name()),
+            None => self.display_name().to_string(),
+        }
+    }
+
+    fn enabled_by_default() -> bool {
+        true
+    }
+
+    fn is_enabled(enabled: &bool) -> bool {
+        *enabled
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,45 +227,448 @@ pub struct Position {
     pub y: f32,
 }
 
+/// A comment on the canvas: a title and a body where `# ` lines are
+/// headings and `- ` lines are bullets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StickyNote {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    /// Top-left corner in canvas coordinates
+    pub position: Position,
+    #[serde(default = "StickyNote::default_size")]
+    pub size: [f32; 2],
+    /// Background as `#rrggbb`
+    #[serde(default = "StickyNote::default_color")]
+    pub color: String,
+}
+
+impl StickyNote {
+    /// Colors offered by the note editor
+    pub const COLORS: &'static [(&'static str, &'static str)] = &[
+        ("Yellow", "#f5e17a"),
+        ("Blue", "#9ccbef"),
+        ("Green", "#a7dba0"),
+        ("Pink", "#f1abc8"),
+        ("Gray", "#cfcfcf"),
+    ];
+
+    /// Smallest size a note can be resized to
+    pub const MIN_SIZE: [f32; 2] = [120.0, 60.0];
+
+    pub fn new(id: String, position: Position) -> Self {
+        Self {
+            id,
+            title: "Note".to_string(),
+            body: String::new(),
+            position,
+            size: Self::default_size(),
+            color: Self::default_color(),
+        }
+    }
+
+    fn default_size() -> [f32; 2] {
+        [220.0, 140.0]
+    }
+
+    fn default_color() -> String {
+        Self::COLORS[0].1.to_string()
+    }
+}
+
+/// Nodes framed together on the canvas, which can be collapsed into one
+/// block and moved as a unit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeGroup {
+    pub id: String,
+    pub name: String,
+    /// IDs of the member nodes
+    pub nodes: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub collapsed: bool,
+    /// Color tag as `#rrggbb`, tinting the frame
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Icon drawn before the group's name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+/// Color tags offered for nodes and groups
+pub const TAG_COLORS: &[(&str, &str)] = &[
+    ("Red", "#e05252"),
+    ("Orange", "#e8923a"),
+    ("Yellow", "#e0c040"),
+    ("Green", "#4caf50"),
+    ("Blue", "#4a90d9"),
+    ("Purple", "#9b6dd6"),
+];
+
+/// Icons offered for nodes and groups
+pub const TAG_ICONS: &[&str] = &["⭐", "🚀", "🐞", "🧪", "⚠", "🔒", "📦", "🔥"];
+
+/// Options that alter how a flow is compiled
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    /// Return every node's output alongside the result, as
+    /// `{"result": ..., "outputs": {node_id: ...}}`
+    pub capture_outputs: bool,
+
+    /// Nodes whose output is replaced by a fixed value instead of being executed
+    pub fixed_outputs: HashMap<String, serde_json::Value>,
+
+    /// Run-level RNG seed, overriding the flow's own seed
+    pub seed: Option<u64>,
+
+    /// Directory that relative `path` configs resolve against, so a run's
+    /// file I/O stays inside its own working directory
+    pub work_dir: Option<PathBuf>,
+
+    /// Nodes whose code is replaced by an injected fault (chaos mode)
+    pub faults: HashMap<String, crate::chaos::FaultRule>,
+
+    /// Keep every `path` config inside `work_dir`, absolute ones included (mock mode)
+    pub confine_paths: bool,
+
+    /// Name the flow runs under, so per-node rate limits of two flows with
+    /// the same node ID don't share run slots
+    pub flow_name: Option<String>,
+}
+
+/// Structural problems that would make a flow compile to invalid HLX
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Node ID is not usable as an HLX identifier
+    InvalidNodeId(String),
+    /// Two nodes share the same ID
+    DuplicateNodeId(String),
+    /// Edge references a node that doesn't exist
+    DanglingEdge { source: String, target: String },
+    /// A `{{ $... }}` config expression doesn't compile, e.g. it uses the
+    /// output of a node that runs later
+    InvalidExpression { node: String, error: String },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::InvalidNodeId(id) => {
+                write!(f, "Invalid node ID {:?}: must be letters, digits or '_' and not start with a digit", id)
+            }
+            ValidationError::DuplicateNodeId(id) => write!(f, "Duplicate node ID {:?}", id),
+            ValidationError::DanglingEdge { source, target } => {
+                write!(f, "Edge {} -> {} references a missing node", source, target)
+            }
+            ValidationError::InvalidExpression { node, error } => {
+                write!(f, "Node {} has an invalid expression in {}", node, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// An edge carrying a value its target doesn't take, e.g. a tensor into
+/// `string_upper`. A warning, not a validation error: the runtime may
+/// still convert the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatch {
+    pub source: String,
+    pub target: String,
+    pub target_handle: Option<String>,
+    pub found: crate::nodes::DataType,
+    pub expected: crate::nodes::DataType,
+}
+
+impl fmt::Display for TypeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target = match &self.target_handle {
+            Some(port) => format!("{}.{}", self.target, port),
+            None => self.target.clone(),
+        };
+        write!(
+            f,
+            "{} outputs {} but {} expects {}",
+            self.source,
+            self.found.name(),
+            target,
+            self.expected.name()
+        )
+    }
+}
+
+/// Whether `id` can be used as the prefix of an HLX variable name
+fn is_valid_identifier(id: &str) -> bool {
+    let mut chars = id.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 impl Flow {
+    /// Check that the flow is structurally sound before compiling it
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut seen = HashSet::new();
+        for node in &self.nodes {
+            if !is_valid_identifier(&node.id) {
+                return Err(ValidationError::InvalidNodeId(node.id.clone()));
+            }
+            if !seen.insert(node.id.as_str()) {
+                return Err(ValidationError::DuplicateNodeId(node.id.clone()));
+            }
+        }
+
+        for edge in &self.edges {
+            if !seen.contains(edge.source.as_str()) || !seen.contains(edge.target.as_str()) {
+                return Err(ValidationError::DanglingEdge {
+                    source: edge.source.clone(),
+                    target: edge.target.clone(),
+                });
+            }
+        }
+
+        if let Some((node, error)) = self.expression_errors().into_iter().next() {
+            return Err(ValidationError::InvalidExpression { node, error });
+        }
+
+        Ok(())
+    }
+
+    /// Output type of every node. Nodes that hand their input on (`print`,
+    /// `sleep` and disabled nodes) take the type of what feeds them.
+    pub fn infer_types(&self) -> HashMap<String, crate::nodes::DataType> {
+        let mut types: HashMap<String, crate::nodes::DataType> = self.nodes.iter()
+            .map(|n| (n.id.clone(), crate::nodes::port_types(&n.type_name).1))
+            .collect();
+
+        // Repeat so chains of pass-through nodes resolve; cycles stop once nothing changes
+        for _ in 0..self.nodes.len() {
+            let mut changed = false;
+            for node in self.nodes.iter().filter(|n| Self::forwards_input(n)) {
+                let found = self.edges.iter()
+                    .find(|e| e.target == node.id)
+                    .and_then(|e| types.get(&e.source).copied());
+                if let Some(found) = found {
+                    if types.insert(node.id.clone(), found) != Some(found) {
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        types
+    }
+
+    /// Config expressions that don't compile, as `(node, error)`
+    pub fn expression_errors(&self) -> Vec<(String, String)> {
+        let node_ids: Vec<&str> = self.nodes.iter().map(|n| n.id.as_str()).collect();
+        self.nodes.iter()
+            .enumerate()
+            .filter(|(_, n)| n.enabled && n.type_name != "hlx_script")
+            .filter_map(|(i, node)| {
+                let mut config = node.config.clone();
+                let error = crate::expr::bind_config_expressions(&mut config, &node_ids, i).err()?;
+                Some((node.id.clone(), error))
+            })
+            .collect()
+    }
+
+    /// Edges whose source outputs a type the target's input doesn't take
+    pub fn type_mismatches(&self) -> Vec<TypeMismatch> {
+        let types = self.infer_types();
+        self.edges.iter()
+            .filter_map(|edge| {
+                let target = self.nodes.iter().find(|n| n.id == edge.target)?;
+                if Self::forwards_input(target) {
+                    return None;
+                }
+                let found = *types.get(&edge.source)?;
+                let expected = crate::nodes::port_types(&target.type_name).0;
+                (!expected.accepts(found)).then(|| TypeMismatch {
+                    source: edge.source.clone(),
+                    target: edge.target.clone(),
+                    target_handle: edge.target_handle.clone(),
+                    found,
+                    expected,
+                })
+            })
+            .collect()
+    }
+
+    fn forwards_input(node: &Node) -> bool {
+        !node.enabled
+            || matches!(node.type_name.as_str(), "print" | "sleep")
+            || crate::testing::ASSERTION_NODES.contains(&node.type_name.as_str())
+    }
+
     pub fn compile_to_hlx(&self) -> String {
+        self.compile_with(&CompileOptions::default())
+    }
+
+    /// Compile to HLX with instrumentation or substituted node outputs
+    pub fn compile_with(&self, options: &CompileOptions) -> String {
         let mut source = String::new();
         
         // Header
         source.push_str("program workflow {\n\n");
+
+        // Source map, so the program can be turned back into this flow
+        source.push_str(&crate::decompile::flow_marker(self));
+        for edge in &self.edges {
+            source.push_str(&crate::decompile::edge_marker(edge));
+        }
+        source.push('\n');
         
         // We need to topologically sort nodes to determine execution order.
         // For this MVP, we'll assume a simple linear chain or manual ordering isn't strictly enforced 
         // by the compiler yet (HLX handles variable dependencies).
         
-        source.push_str("fn main(input) {\n");
+        // Script nodes' code goes in functions ahead of main
+        let mut functions = String::new();
+        let mut body = String::new();
+        let node_ids: Vec<&str> = self.nodes.iter().map(|n| n.id.as_str()).collect();
+        // Rate limits the generated nodes use, declared at the top of main
+        let mut throttled = BTreeMap::new();
         
         // 1. Generate variable declarations for each node output
-        for node in &self.nodes {
+        for (i, node) in self.nodes.iter().enumerate() {
+            body.push_str(&crate::decompile::node_marker(node));
+
+            if !node.enabled {
+                body.push_str(&format!(
+                    "    // {} is disabled\n    let {}_out = {};\n",
+                    node.type_name,
+                    node.id,
+                    self.find_input_var(&node.id).as_deref().unwrap_or("null")
+                ));
+                continue;
+            }
+
+            if let Some(value) = options.fixed_outputs.get(&node.id) {
+                body.push_str(&format!(
+                    "    let {}_out = json_parse({});\n",
+                    node.id,
+                    crate::nodes::hlx_string(&value.to_string())
+                ));
+                continue;
+            }
+
+            if let Some(rule) = options.faults.get(&node.id) {
+                body.push_str(&crate::chaos::fault_code(&node.id, rule));
+                continue;
+            }
+
             // Get input variable from first incoming edge
             let input_var = self.find_input_var(&node.id);
 
-            // Find node definition in registry
-            let node_code = if let Some(node_def) = crate::nodes::all_nodes()
-                .into_iter()
-                .find(|def| def.name == node.type_name)
-            {
-                // Generate code using registry
-                (node_def.generate_code)(&node.id, &node.config, input_var.as_deref())
+            // Derive a per-node seed unless the node pins its own
+            let mut config = node.config.clone();
+            if let Some(seed) = options.seed.or(self.seed) {
+                if config.is_object() && config.get("seed").is_none() {
+                    config["seed"] = serde_json::json!(crate::nodes::node_seed(seed, &node.id));
+                }
+            }
+
+            // Resolve relative paths inside the run's working directory
+            if let Some(work_dir) = &options.work_dir {
+                if let Some(path) = config.get("path").and_then(|p| p.as_str()) {
+                    // Confined paths that climb out with `..` get a name that can't exist
+                    let relative = if options.confine_paths {
+                        Some(crate::mock::confined(path).unwrap_or_else(|| PathBuf::from(".outside-mock-fs")))
+                    } else {
+                        Path::new(path).is_relative().then(|| PathBuf::from(path))
+                    };
+                    if let Some(relative) = relative {
+                        let resolved = work_dir.join(relative).to_string_lossy().into_owned();
+                        config["path"] = serde_json::json!(resolved);
+                    }
+                }
+            }
+
+            if let Some((key, limit)) = crate::throttle::node_limit(self, node, options.flow_name.as_deref()).filter(|_| config.is_object()) {
+                config["throttle"] = crate::throttle::settings(&key, &limit);
+                throttled.insert(key, limit);
+            }
+
+            let ports = crate::nodes::input_ports(&node.type_name);
+            if !ports.is_empty() && config.is_object() {
+                let inputs: serde_json::Map<String, serde_json::Value> = ports.iter()
+                    .zip(self.port_sources(&node.id, ports))
+                    .filter_map(|(port, edge)| Some((port.to_string(), serde_json::json!(edge?.source))))
+                    .collect();
+                config["inputs"] = serde_json::Value::Object(inputs);
+            }
+
+            // `{{ $input.x }}` and `{{ $node("id").x }}` in string fields are
+            // swapped for placeholders, then for their HLX once the code exists
+            let bound = if node.type_name == "hlx_script" {
+                // Script code is HLX already
+                Ok(Vec::new())
             } else {
-                // Fallback for unknown nodes
-                format!("    // Unknown node type: {}\n    let {}_out = null;\n",
-                    node.type_name, node.id)
+                crate::expr::bind_config_expressions(&mut config, &node_ids, i)
+            };
+            let expressions = match bound {
+                Ok(expressions) => expressions,
+                Err(e) => {
+                    // A multi-line error would end the comment early
+                    body.push_str(&format!("    // Invalid expression in {}\n    let {}_out = null;\n", e.replace('\n', " "), node.id));
+                    continue;
+                }
             };
 
-            source.push_str(&node_code);
+            if node.type_name == "hlx_script" {
+                let sources = self.edges.iter().filter(|e| e.target == node.id).map(|e| e.source.as_str());
+                crate::nodes::bind_script_inputs(&mut config, sources);
+                // Only nodes that already ran have an output to pass
+                for name in crate::nodes::retain_script_inputs(&mut config, &node_ids[..i]) {
+                    body.push_str(&format!("    // hlx_script: unknown input {:?}\n", name));
+                }
+                functions.push_str(&crate::nodes::script_function(&node.id, &config));
+            }
+
+            // Generate code using registry
+            let mut node_code = crate::nodes::generate_code(&node.type_name, &node.id, &config, input_var.as_deref())
+                .unwrap_or_else(|| {
+                    // Fallback for unknown nodes
+                    format!("    // Unknown node type: {:?}\n    let {}_out = null;\n",
+                        node.type_name, node.id)
+                });
+            if !expressions.is_empty() {
+                node_code = crate::expr::splice(&node_code, &expressions).unwrap_or_else(|field| {
+                    // The node reworks the field's text instead of using it as is
+                    format!("    // {} doesn't support expressions in {}\n    let {}_out = null;\n", node.type_name, field, node.id)
+                });
+            }
+
+            body.push_str(&node_code);
         }
+
+        source.push_str(&functions);
+        source.push_str("fn main(input) {\n");
+        source.push_str(&crate::throttle::declare_code(&throttled));
+        source.push_str(&body);
         
         // Return the output of the last node (heuristic: node with no outgoing edges)
-        if let Some(last_node) = self.find_leaf_node() {
-            source.push_str(&format!("    return {}_out;\n", last_node.id));
+        let result_var = self.find_leaf_node()
+            .map(|n| format!("{}_out", n.id))
+            .unwrap_or_else(|| "null".to_string());
+
+        if options.capture_outputs {
+            let outputs: Vec<String> = self.nodes.iter()
+                .map(|n| format!("{}: {}_out", crate::nodes::hlx_string(&n.id), n.id))
+                .collect();
+            source.push_str(&format!(
+                "    return {{ \"result\": {}, \"outputs\": {{ {} }} }};\n",
+                result_var,
+                outputs.join(", ")
+            ));
         } else {
-            source.push_str("    return null;\n");
+            source.push_str(&format!("    return {};\n", result_var));
         }
 
         source.push_str("}\n\n");
@@ -77,6 +677,261 @@ impl Flow {
         source
     }
     
+    /// Relative file paths referenced by node configs
+    pub fn relative_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.nodes.iter()
+            .filter_map(|n| n.config.get("path").and_then(|p| p.as_str()))
+            .filter(|p| Path::new(p).is_relative())
+            .map(|p| p.to_string())
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Names of other flows referenced by node configs (`"flow": "<name>"`)
+    pub fn referenced_flows(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.nodes.iter()
+            .filter_map(|n| n.config.get("flow").and_then(|f| f.as_str()))
+            .map(|f| f.to_string())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Generate a node ID that isn't used by any existing node
+    pub fn next_node_id(&self) -> String {
+        (self.nodes.len()..)
+            .map(|i| format!("node_{}", i))
+            .find(|id| !self.nodes.iter().any(|n| &n.id == id))
+            .unwrap()
+    }
+
+    /// First unused `note_N` ID
+    pub fn next_note_id(&self) -> String {
+        (self.notes.len() + 1..)
+            .map(|i| format!("note_{}", i))
+            .find(|id| !self.notes.iter().any(|n| &n.id == id))
+            .unwrap()
+    }
+
+    /// First unused `group_N` ID
+    pub fn next_group_id(&self) -> String {
+        (self.groups.len() + 1..)
+            .map(|i| format!("group_{}", i))
+            .find(|id| !self.groups.iter().any(|g| &g.id == id))
+            .unwrap()
+    }
+
+    /// Frame `node_ids` as a new group, taking them out of any other group.
+    /// Returns the group's ID.
+    pub fn group_nodes(&mut self, node_ids: &[String], name: &str) -> String {
+        for group in &mut self.groups {
+            group.nodes.retain(|id| !node_ids.contains(id));
+        }
+        self.groups.retain(|g| !g.nodes.is_empty());
+
+        let id = self.next_group_id();
+        self.groups.push(NodeGroup { id: id.clone(), name: name.to_string(), nodes: node_ids.to_vec(), collapsed: false, color: None, icon: None });
+        id
+    }
+
+    /// Move a group's nodes into a new flow that a `subflow` node calls in
+    /// their place.
+    ///
+    /// A subflow takes one input and returns one output, so the group may
+    /// be fed from at most one node outside it and must end in exactly one
+    /// node. Nodes fed from outside are fed by a `start` node in the
+    /// subflow instead. Returns the subflow.
+    pub fn extract_group(&mut self, group_id: &str, subflow_name: &str) -> Result<Flow, String> {
+        let group = self.groups.iter().find(|g| g.id == group_id).ok_or("No such group")?.clone();
+        let members: HashSet<&str> = group.nodes.iter().map(String::as_str).collect();
+        let inside = |id: &str| members.contains(id);
+
+        let incoming: Vec<&Edge> = self.edges.iter().filter(|e| !inside(&e.source) && inside(&e.target)).collect();
+        let outgoing: Vec<&Edge> = self.edges.iter().filter(|e| inside(&e.source) && !inside(&e.target)).collect();
+        let internal: Vec<&Edge> = self.edges.iter().filter(|e| inside(&e.source) && inside(&e.target)).collect();
+
+        let mut sources: Vec<&str> = incoming.iter().map(|e| e.source.as_str()).collect();
+        sources.sort();
+        sources.dedup();
+        if sources.len() > 1 {
+            return Err(format!("A subflow takes one input, but the group is fed by {}", sources.join(", ")));
+        }
+
+        let ends: Vec<&str> = group.nodes.iter()
+            .map(String::as_str)
+            .filter(|id| !internal.iter().any(|e| e.source == *id))
+            .collect();
+        if ends.len() != 1 {
+            return Err(format!("A subflow returns one output, but the group ends in {} nodes: {}", ends.len(), ends.join(", ")));
+        }
+        if outgoing.iter().any(|e| e.source != ends[0]) {
+            return Err(format!("Only the group's last node ({}) may feed nodes outside it", ends[0]));
+        }
+
+        // A group holding a start node keeps it as the entry; otherwise the
+        // new start node gets an ID none of the members has
+        let member_start = self.nodes.iter().find(|n| inside(&n.id) && n.type_name == "start").map(|n| n.id.clone());
+        let start = member_start.clone().unwrap_or_else(|| {
+            let mut start = "start".to_string();
+            while inside(&start) {
+                start.push('_');
+            }
+            start
+        });
+        let mut subflow = Flow {
+            edges: incoming.iter()
+                .map(|e| Edge { source: start.clone(), ..(*e).clone() })
+                .chain(internal.iter().map(|e| (*e).clone()))
+                .collect(),
+            seed: self.seed,
+            ..Default::default()
+        };
+        subflow.nodes.extend(self.nodes.iter().filter(|n| inside(&n.id)).cloned());
+        let anchor = subflow.nodes.iter().find_map(|n| n.position);
+        if member_start.is_none() {
+            // The start node sits above the group's first node
+            subflow.nodes.insert(0, Node {
+                id: start,
+                type_name: "start".to_string(),
+                config: serde_json::json!({}),
+                position: anchor.map(|p| Position { x: p.x, y: p.y - 100.0 }),
+                ..Default::default()
+            });
+        }
+
+        // Replace the group with a node calling the subflow
+        let mut id = format!("{}_flow", subflow_name.replace('-', "_"));
+        while self.nodes.iter().any(|n| n.id == id && !inside(&n.id)) {
+            id.push('_');
+        }
+        let call = Node {
+            id: id.clone(),
+            type_name: "subflow".to_string(),
+            config: serde_json::json!({"flow": subflow_name}),
+            position: anchor,
+            ..Default::default()
+        };
+        let new_edges: Vec<Edge> = incoming.iter()
+            .take(1)
+            .map(|e| Edge { target: id.clone(), target_handle: None, ..(*e).clone() })
+            .chain(outgoing.iter().map(|e| Edge { source: id.clone(), source_handle: None, ..(*e).clone() }))
+            .collect();
+
+        self.edges.retain(|e| !inside(&e.source) && !inside(&e.target));
+        self.edges.extend(new_edges);
+        self.nodes.retain(|n| !inside(&n.id));
+        self.nodes.push(call);
+        self.chaos.retain(|rule| !inside(&rule.node));
+        self.groups.retain(|g| g.id != group_id);
+
+        Ok(subflow)
+    }
+
+    /// IDs of `node_id` and every node upstream of it
+    pub fn upstream_ids(&self, node_id: &str) -> HashSet<String> {
+        self.reachable(node_id, false)
+    }
+
+    /// IDs of `node_id` and every node downstream of it
+    pub fn downstream_ids(&self, node_id: &str) -> HashSet<String> {
+        self.reachable(node_id, true)
+    }
+
+    fn reachable(&self, node_id: &str, forward: bool) -> HashSet<String> {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut stack = vec![node_id.to_string()];
+
+        while let Some(id) = stack.pop() {
+            if seen.insert(id.clone()) {
+                for edge in &self.edges {
+                    let (from, to) = if forward {
+                        (&edge.source, &edge.target)
+                    } else {
+                        (&edge.target, &edge.source)
+                    };
+                    if *from == id {
+                        stack.push(to.clone());
+                    }
+                }
+            }
+        }
+
+        seen
+    }
+
+    /// Build the subgraph made of `node_id` and every node upstream of it.
+    ///
+    /// The target node is the only leaf of the result, so compiling the
+    /// subgraph returns that node's output.
+    pub fn ancestor_subgraph(&self, node_id: &str) -> Flow {
+        let keep = self.upstream_ids(node_id);
+
+        Flow {
+            nodes: self.nodes.iter()
+                .filter(|n| keep.contains(&n.id))
+                .cloned()
+                .collect(),
+            edges: self.edges.iter()
+                .filter(|e| keep.contains(&e.source) && keep.contains(&e.target))
+                .cloned()
+                .collect(),
+            ..self.clone_meta()
+        }
+    }
+
+    /// Copy of the flow-level settings without any nodes or edges
+    pub(crate) fn clone_meta(&self) -> Flow {
+        Flow {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            seed: self.seed,
+            changelog: self.changelog.clone(),
+            on_interrupt: self.on_interrupt,
+            template: self.template.clone(),
+            cpu_fallback: self.cpu_fallback,
+            retry: self.retry.clone(),
+            chaos: self.chaos.clone(),
+            requires: self.requires.clone(),
+            notes: self.notes.clone(),
+            groups: self.groups.clone(),
+            input_schema: self.input_schema.clone(),
+            mocks: self.mocks.clone(),
+            mock_files: self.mock_files.clone(),
+            pinned: self.pinned.clone(),
+            rate_limits: self.rate_limits.clone(),
+            edge_style: self.edge_style,
+        }
+    }
+
+    /// Append a changelog entry stamped with the current time
+    pub fn log_change(&mut self, author: &str, message: &str) {
+        self.changelog.push(ChangelogEntry {
+            timestamp: crate::runs::now_ms(),
+            author: author.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    /// The edge feeding each of a node's named input ports. Edges name
+    /// their port in `target_handle`; edges without one (or naming an
+    /// unknown port) fill the remaining ports in order.
+    pub fn port_sources(&self, node_id: &str, ports: &[&str]) -> Vec<Option<&Edge>> {
+        let incoming: Vec<&Edge> = self.edges.iter().filter(|e| e.target == node_id).collect();
+        let mut sources: Vec<Option<&Edge>> = ports.iter()
+            .map(|port| incoming.iter().copied().find(|e| e.target_handle.as_deref() == Some(*port)))
+            .collect();
+
+        let mut unassigned = incoming.iter().copied()
+            .filter(|e| !e.target_handle.as_deref().is_some_and(|h| ports.contains(&h)));
+        for slot in sources.iter_mut().filter(|slot| slot.is_none()) {
+            *slot = unassigned.next();
+        }
+        sources
+    }
+
     fn find_input_var(&self, node_id: &str) -> Option<String> {
         self.edges.iter()
             .find(|e| e.target == node_id)
@@ -88,3 +943,332 @@ impl Flow {
         self.nodes.iter().find(|n| !self.edges.iter().any(|e| e.source == n.id))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hlx_compiler::hlxa::HlxaParser;
+    use hlx_compiler::parser::Parser;
+    use proptest::prelude::*;
+
+    const CONFIG_KEYS: &[&str] = &[
+        "url", "method", "key", "value", "separator", "delimiter", "path",
+        "start", "end", "ms", "rows", "cols", "values", "count", "seed", "inputs",
+    ];
+
+    fn json_leaf() -> impl Strategy<Value = serde_json::Value> {
+        prop_oneof![
+            Just(serde_json::Value::Null),
+            any::<bool>().prop_map(serde_json::Value::from),
+            any::<i64>().prop_map(serde_json::Value::from),
+            (-1.0e9f64..1.0e9f64).prop_map(serde_json::Value::from),
+            any::<String>().prop_map(serde_json::Value::from),
+            prop::collection::vec(-1.0e3f64..1.0e3f64, 0..6).prop_map(serde_json::Value::from),
+        ]
+    }
+
+    fn config() -> impl Strategy<Value = serde_json::Value> {
+        prop::collection::btree_map(prop::sample::select(CONFIG_KEYS), json_leaf(), 0..5)
+            .prop_map(|m| {
+                serde_json::Value::Object(m.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+            })
+    }
+
+    fn type_name() -> impl Strategy<Value = String> {
+        let names: Vec<String> = crate::nodes::all_nodes()
+            .into_iter()
+            .map(|def| def.name.to_string())
+            .collect();
+        prop_oneof![
+            9 => prop::sample::select(names),
+            1 => any::<String>(),
+        ]
+    }
+
+    fn edge(source: &str, target: &str) -> Edge {
+        Edge {
+            source: source.to_string(),
+            target: target.to_string(),
+            source_handle: None,
+            target_handle: None,
+        }
+    }
+
+    /// Flows with unique identifier IDs and edges between existing nodes
+    fn valid_flow() -> impl Strategy<Value = Flow> {
+        prop::collection::vec((type_name(), config()), 0..12)
+            .prop_flat_map(|specs| {
+                let len = specs.len().max(1);
+                (Just(specs), prop::collection::vec((0..len, 0..len), 0..16))
+            })
+            .prop_map(|(specs, pairs)| {
+                let nodes: Vec<Node> = specs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (type_name, config))| Node::new(&format!("n{}", i), &type_name, config))
+                    .collect();
+                let edges = pairs
+                    .into_iter()
+                    .filter(|(s, t)| *s < nodes.len() && *t < nodes.len())
+                    .map(|(s, t)| edge(&nodes[s].id, &nodes[t].id))
+                    .collect();
+                Flow { nodes, edges, ..Default::default() }
+            })
+    }
+
+    /// Flows with arbitrary IDs and edges that may point anywhere
+    fn arbitrary_flow() -> impl Strategy<Value = Flow> {
+        (
+            prop::collection::vec((any::<String>(), type_name(), config()), 0..8),
+            prop::collection::vec((any::<String>(), any::<String>()), 0..8),
+        )
+            .prop_map(|(specs, pairs)| Flow {
+                nodes: specs
+                    .into_iter()
+                    .map(|(id, type_name, config)| Node::new(&id, &type_name, config))
+                    .collect(),
+                edges: pairs.iter().map(|(s, t)| edge(s, t)).collect(),
+                ..Default::default()
+            })
+    }
+
+    fn assert_parses(flow: &Flow) -> Result<(), TestCaseError> {
+        let source = flow.compile_to_hlx();
+        prop_assert!(
+            HlxaParser.parse(&source).is_ok(),
+            "generated HLX failed to parse:\n{}",
+            source
+        );
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn valid_flows_compile_to_parseable_hlx(flow in valid_flow()) {
+            prop_assert_eq!(flow.validate(), Ok(()));
+            assert_parses(&flow)?;
+        }
+
+        #[test]
+        fn arbitrary_flows_are_rejected_or_parse(flow in arbitrary_flow()) {
+            if flow.validate().is_ok() {
+                assert_parses(&flow)?;
+            }
+        }
+
+        #[test]
+        fn ancestor_subgraphs_compile_to_parseable_hlx(flow in valid_flow(), pick in any::<prop::sample::Index>()) {
+            if !flow.nodes.is_empty() {
+                let target = &flow.nodes[pick.index(flow.nodes.len())].id;
+                let subflow = flow.ancestor_subgraph(target);
+                prop_assert_eq!(subflow.validate(), Ok(()));
+                assert_parses(&subflow)?;
+            }
+        }
+    }
+
+    #[test]
+    fn validate_rejects_bad_ids_dangling_edges_and_expressions() {
+        let flow = Flow {
+            nodes: vec![Node::new("1abc", "print", serde_json::json!({}))],
+            ..Default::default()
+        };
+        assert_eq!(flow.validate(), Err(ValidationError::InvalidNodeId("1abc".into())));
+
+        let flow = Flow {
+            nodes: vec![
+                Node::new("a", "print", serde_json::json!({})),
+                Node::new("a", "print", serde_json::json!({})),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(flow.validate(), Err(ValidationError::DuplicateNodeId("a".into())));
+
+        let flow = Flow {
+            nodes: vec![Node::new("a", "print", serde_json::json!({}))],
+            edges: vec![edge("a", "missing")],
+            ..Default::default()
+        };
+        assert_eq!(
+            flow.validate(),
+            Err(ValidationError::DanglingEdge { source: "a".into(), target: "missing".into() })
+        );
+
+        let flow = Flow {
+            nodes: vec![
+                Node::new("a", "print", serde_json::json!({"message": "{{ $node(\"b\").x }}"})),
+                Node::new("b", "print", serde_json::json!({})),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            flow.validate(),
+            Err(ValidationError::InvalidExpression { node: "a".into(), error: "message: node 'b' runs after this one".into() })
+        );
+    }
+
+    #[test]
+    fn seeded_random_nodes_compile_deterministically() {
+        let flow = Flow {
+            nodes: vec![
+                Node::new("r", "math_random", serde_json::json!({})),
+                Node::new("u", "uuid_v4", serde_json::json!({})),
+            ],
+            seed: Some(42),
+            ..Default::default()
+        };
+        assert_eq!(flow.compile_to_hlx(), flow.compile_to_hlx());
+        assert!(!flow.compile_to_hlx().contains("random()"));
+
+        let reseeded = CompileOptions { seed: Some(7), ..Default::default() };
+        assert_ne!(flow.compile_to_hlx(), flow.compile_with(&reseeded));
+    }
+
+    #[test]
+    fn disabled_nodes_pass_their_input_through() {
+        let mut flow = Flow {
+            nodes: vec![
+                Node::new("text", "string_upper", serde_json::json!({})),
+                Node::new("shout", "string_upper", serde_json::json!({})),
+                Node::new("out", "print", serde_json::json!({})),
+            ],
+            edges: vec![edge("text", "shout"), edge("shout", "out")],
+            ..Default::default()
+        };
+        flow.nodes[1].enabled = false;
+
+        let source = flow.compile_to_hlx();
+        assert!(source.contains("let shout_out = text_out;"));
+        assert!(HlxaParser.parse(&source).is_ok());
+
+        let saved = serde_json::to_value(&flow.nodes[1]).unwrap();
+        assert_eq!(saved["enabled"], false);
+        assert!(serde_json::to_value(&flow.nodes[0]).unwrap().get("enabled").is_none());
+    }
+
+    #[test]
+    fn edge_style_is_saved_only_when_changed() {
+        let mut flow = Flow::default();
+        assert!(serde_json::to_value(&flow).unwrap().get("edge_style").is_none());
+        flow.edge_style = EdgeStyle::Orthogonal;
+        let saved = serde_json::to_value(&flow).unwrap();
+        assert_eq!(saved["edge_style"], "orthogonal");
+        let loaded: Flow = serde_json::from_value(saved).unwrap();
+        assert_eq!(loaded.edge_style, EdgeStyle::Orthogonal);
+    }
+
+    #[test]
+    fn labels_notes_and_tags_are_saved_but_not_compiled() {
+        let mut labelled = Node::new("fetch", "http_get", serde_json::json!({"url": "https://example.com/orders"}));
+        let plain = labelled.clone();
+        labelled.label = Some("Fetch orders".to_string());
+        labelled.notes = Some("Paginated; see the API docs".to_string());
+        labelled.color = Some(TAG_COLORS[3].1.to_string());
+        labelled.icon = Some("🚀".to_string());
+        assert_eq!(labelled.display_name(), "Fetch orders");
+        assert_eq!(labelled.title(), "🚀 Fetch orders");
+        assert_eq!(plain.title(), "http_get");
+
+        let saved = serde_json::to_value(&labelled).unwrap();
+        assert_eq!(saved["label"], "Fetch orders");
+        assert_eq!(saved["color"], "#4caf50");
+        let plain_saved = serde_json::to_value(&plain).unwrap();
+        assert!(plain_saved.get("notes").is_none() && plain_saved.get("color").is_none());
+
+        let compile = |node: Node| Flow { nodes: vec![node], ..Default::default() }.compile_to_hlx();
+        assert_eq!(compile(labelled), compile(plain));
+    }
+
+    #[test]
+    fn type_mismatches_follow_pass_through_nodes() {
+        let flow = Flow {
+            nodes: vec![
+                Node::new("t", "tensor_random", serde_json::json!({})),
+                Node::new("p", "print", serde_json::json!({})),
+                Node::new("up", "string_upper", serde_json::json!({})),
+                Node::new("len", "string_length", serde_json::json!({})),
+                Node::new("sq", "math_sqrt", serde_json::json!({})),
+            ],
+            edges: vec![edge("t", "p"), edge("p", "up"), edge("up", "len"), edge("len", "sq")],
+            ..Default::default()
+        };
+        assert_eq!(flow.infer_types()["p"], crate::nodes::DataType::Tensor);
+
+        let mismatches = flow.type_mismatches();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].to_string(), "p outputs tensor but up expects string");
+        assert_eq!(flow.validate(), Ok(()));
+    }
+
+    #[test]
+    fn extracting_a_group_calls_it_as_a_subflow() {
+        let mut flow = Flow {
+            nodes: ["start", "get", "upper", "out"]
+                .iter()
+                .map(|id| Node::new(id, "print", serde_json::json!({})))
+                .collect(),
+            edges: vec![edge("start", "get"), edge("get", "upper"), edge("upper", "out")],
+            ..Default::default()
+        };
+        let group = flow.group_nodes(&["get".to_string(), "upper".to_string()], "Clean up");
+
+        let subflow = flow.extract_group(&group, "clean-up").unwrap();
+        let ids: Vec<&str> = subflow.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["start", "get", "upper"]);
+        assert!(subflow.edges.iter().any(|e| e.source == "start" && e.target == "get"));
+
+        let ids: Vec<&str> = flow.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["start", "out", "clean_up_flow"]);
+        assert!(flow.edges.iter().any(|e| e.source == "start" && e.target == "clean_up_flow"));
+        assert!(flow.edges.iter().any(|e| e.source == "clean_up_flow" && e.target == "out"));
+        assert!(flow.groups.is_empty());
+        assert_eq!(flow.validate(), Ok(()));
+    }
+
+    #[test]
+    fn extracted_groups_get_one_start_node_with_a_free_id() {
+        let mut flow = Flow {
+            nodes: vec![
+                Node::new("fetch", "print", serde_json::json!({})),
+                Node::new("start", "print", serde_json::json!({})),
+                Node::new("next", "print", serde_json::json!({})),
+            ],
+            edges: vec![edge("fetch", "start"), edge("start", "next")],
+            ..Default::default()
+        };
+        let group = flow.group_nodes(&["start".to_string(), "next".to_string()], "Tail");
+        let subflow = flow.extract_group(&group, "tail").unwrap();
+        let ids: Vec<&str> = subflow.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["start_", "start", "next"]);
+        assert_eq!(subflow.validate(), Ok(()));
+
+        // A group with the flow's start node keeps it as the entry
+        let mut flow = Flow {
+            nodes: vec![
+                Node::new("start", "start", serde_json::json!({})),
+                Node::new("upper", "string_upper", serde_json::json!({})),
+            ],
+            edges: vec![edge("start", "upper")],
+            ..Default::default()
+        };
+        let group = flow.group_nodes(&["start".to_string(), "upper".to_string()], "All");
+        let subflow = flow.extract_group(&group, "all").unwrap();
+        let ids: Vec<&str> = subflow.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["start", "upper"]);
+    }
+
+    #[test]
+    fn groups_with_several_ends_cannot_become_subflows() {
+        let mut flow = Flow {
+            nodes: ["a", "b", "c"]
+                .iter()
+                .map(|id| Node::new(id, "print", serde_json::json!({})))
+                .collect(),
+            edges: vec![edge("a", "b"), edge("a", "c")],
+            ..Default::default()
+        };
+        let group = flow.group_nodes(&["a".to_string(), "b".to_string(), "c".to_string()], "Fan out");
+        assert!(flow.extract_group(&group, "fan_out").is_err());
+        assert_eq!(flow.nodes.len(), 3);
+    }
+}
@@ -0,0 +1,81 @@
+//! GPU Memory Statistics
+//!
+//! Per-run device memory usage from the runtime's Vulkan allocator, so
+//! tensor-heavy flows can be sized for the available GPU. Runs that never
+//! touch the Vulkan backend report nothing. The allocator's counters are
+//! process-wide, so runs that overlap on the server share them.
+
+use serde::{Deserialize, Serialize};
+
+/// Device memory used by one run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuMemoryStats {
+    /// Name of the Vulkan device
+    pub device: String,
+    /// Total device-local memory
+    pub device_total_bytes: u64,
+    /// Highest amount allocated at once during the run
+    pub peak_bytes: u64,
+    /// Still allocated when the run finished
+    pub allocated_bytes: u64,
+    /// Number of allocations made during the run
+    pub allocations: u64,
+}
+
+impl GpuMemoryStats {
+    /// Short human-readable form, e.g. `peak 12.5 MiB of 8.0 GiB (34 allocations) on <device>`
+    pub fn summary(&self) -> String {
+        format!(
+            "peak {} of {} ({} allocations) on {}",
+            format_bytes(self.peak_bytes),
+            format_bytes(self.device_total_bytes),
+            self.allocations,
+            self.device
+        )
+    }
+
+    /// Peak usage as a fraction of device memory
+    pub fn peak_fraction(&self) -> f32 {
+        if self.device_total_bytes == 0 {
+            return 0.0;
+        }
+        self.peak_bytes as f32 / self.device_total_bytes as f32
+    }
+}
+
+/// Reset the allocator's counters before a run starts
+pub fn begin_run() {
+    hlx_runtime::backends::vulkan::reset_memory_stats();
+}
+
+/// Read the allocator's counters after a run, if the Vulkan backend was used
+pub fn end_run() -> Option<GpuMemoryStats> {
+    let stats = hlx_runtime::backends::vulkan::memory_stats()?;
+    if stats.allocation_count == 0 {
+        return None;
+    }
+
+    Some(GpuMemoryStats {
+        device: stats.device_name,
+        device_total_bytes: stats.device_local_bytes,
+        peak_bytes: stats.peak_bytes,
+        allocated_bytes: stats.allocated_bytes,
+        allocations: stats.allocation_count,
+    })
+}
+
+/// Format a byte count with a binary unit
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
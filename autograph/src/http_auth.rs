@@ -0,0 +1,405 @@
+//! HTTP Auth Profiles
+//!
+//! Named credentials an HTTP node picks with `"auth": "<profile>"`: basic
+//! auth, a bearer token, an API key sent as a header or query parameter,
+//! or an OAuth2 client-credentials or refresh-token grant. Profiles live in
+//! the project file next to its secrets, and like them their secret values
+//! must be `env:NAME` references, so no secret ends up in the project file,
+//! a flow or the compiled HLX. OAuth2 tokens are fetched before a run,
+//! cached until just before they expire, and handed to that run alone in
+//! its runtime environment, so flows don't need hand-built token nodes.
+
+use crate::nodes::{connection_expr, hlx_string};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Tokens are renewed this long before they expire
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Lifetime assumed for tokens whose response has no `expires_in`
+const DEFAULT_TOKEN_LIFETIME: u64 = 3600;
+
+/// Where an API key goes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyLocation {
+    #[default]
+    Header,
+    Query,
+}
+
+/// A named way to authenticate requests. String values may be `env:NAME`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthProfile {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+    ApiKey {
+        name: String,
+        value: String,
+        #[serde(default, rename = "in")]
+        location: KeyLocation,
+    },
+    #[serde(rename = "oauth2_client_credentials")]
+    OAuth2ClientCredentials {
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+        #[serde(default)]
+        scope: String,
+    },
+    #[serde(rename = "oauth2_refresh_token")]
+    OAuth2RefreshToken {
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+        refresh_token: String,
+    },
+}
+
+impl AuthProfile {
+    /// Profile types, as written in `type`
+    pub const TYPES: &'static [&'static str] =
+        &["basic", "bearer", "api_key", "oauth2_client_credentials", "oauth2_refresh_token"];
+
+    /// An empty profile of a type from `TYPES`
+    pub fn new(type_name: &str) -> Option<Self> {
+        let empty = String::new;
+        Some(match type_name {
+            "basic" => AuthProfile::Basic { username: empty(), password: empty() },
+            "bearer" => AuthProfile::Bearer { token: empty() },
+            "api_key" => AuthProfile::ApiKey { name: "X-API-Key".to_string(), value: empty(), location: KeyLocation::Header },
+            "oauth2_client_credentials" => AuthProfile::OAuth2ClientCredentials {
+                token_url: empty(),
+                client_id: empty(),
+                client_secret: empty(),
+                scope: empty(),
+            },
+            "oauth2_refresh_token" => AuthProfile::OAuth2RefreshToken {
+                token_url: empty(),
+                client_id: empty(),
+                client_secret: empty(),
+                refresh_token: empty(),
+            },
+            _ => return None,
+        })
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            AuthProfile::Basic { .. } => "basic",
+            AuthProfile::Bearer { .. } => "bearer",
+            AuthProfile::ApiKey { .. } => "api_key",
+            AuthProfile::OAuth2ClientCredentials { .. } => "oauth2_client_credentials",
+            AuthProfile::OAuth2RefreshToken { .. } => "oauth2_refresh_token",
+        }
+    }
+
+    fn is_oauth2(&self) -> bool {
+        matches!(self, AuthProfile::OAuth2ClientCredentials { .. } | AuthProfile::OAuth2RefreshToken { .. })
+    }
+
+    /// Secret fields holding a value rather than an `env:NAME` reference
+    pub fn plaintext_secrets(&self) -> Vec<&'static str> {
+        let secrets: Vec<(&'static str, &String)> = match self {
+            AuthProfile::Basic { password, .. } => vec![("password", password)],
+            AuthProfile::Bearer { token } => vec![("token", token)],
+            AuthProfile::ApiKey { value, .. } => vec![("value", value)],
+            AuthProfile::OAuth2ClientCredentials { client_secret, .. } => vec![("client_secret", client_secret)],
+            AuthProfile::OAuth2RefreshToken { client_secret, refresh_token, .. } => {
+                vec![("client_secret", client_secret), ("refresh_token", refresh_token)]
+            }
+        };
+        secrets.into_iter()
+            .filter(|(_, value)| !value.is_empty() && !value.starts_with("env:"))
+            .map(|(field, _)| field)
+            .collect()
+    }
+}
+
+/// Profiles HTTP nodes compile against, from the project file
+static PROFILES: RwLock<BTreeMap<String, AuthProfile>> = RwLock::new(BTreeMap::new());
+
+/// Make `profiles` the ones HTTP nodes can use
+pub fn set_profiles(profiles: &BTreeMap<String, AuthProfile>) {
+    *PROFILES.write().unwrap() = profiles.clone();
+}
+
+pub fn profile_names() -> Vec<String> {
+    PROFILES.read().unwrap().keys().cloned().collect()
+}
+
+/// Variable in a run's environment holding an OAuth2 profile's access token
+pub fn token_var(profile: &str) -> String {
+    let name: String = profile.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("AUTOGRAPH_OAUTH_{}", name)
+}
+
+/// How an HTTP node's request carries its credentials
+#[derive(Debug, Default, PartialEq)]
+pub struct RequestAuth {
+    /// HLX object entries, `"Name": <expr>`
+    pub headers: Vec<String>,
+    /// Query parameter name and HLX value expression
+    pub query: Option<(String, String)>,
+}
+
+impl RequestAuth {
+    /// HLX expression for `url` with the query parameter added
+    pub fn url(&self, url: &str) -> String {
+        match &self.query {
+            Some((name, value)) => {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                format!("{} + url_encode({})", hlx_string(&format!("{}{}{}=", url, separator, name)), value)
+            }
+            None => hlx_string(url),
+        }
+    }
+
+    /// The headers as an HLX object literal
+    pub fn header_object(&self) -> String {
+        if self.headers.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{ {} }}", self.headers.join(", "))
+        }
+    }
+}
+
+/// Credentials for a node with an `auth` profile in its config
+pub fn request_auth(config: &JsonValue) -> Result<RequestAuth, String> {
+    let Some(name) = config["auth"].as_str().filter(|n| !n.is_empty()) else {
+        return Ok(RequestAuth::default());
+    };
+    let profiles = PROFILES.read().unwrap();
+    let profile = profiles.get(name).ok_or_else(|| format!("Unknown auth profile '{}'", name))?;
+
+    let authorization = |value: String| RequestAuth { headers: vec![format!("\"Authorization\": {}", value)], query: None };
+    Ok(match profile {
+        AuthProfile::Basic { username, password } => authorization(format!(
+            "\"Basic \" + base64_encode({} + \":\" + {})",
+            connection_expr(username),
+            connection_expr(password)
+        )),
+        AuthProfile::Bearer { token } => authorization(format!("\"Bearer \" + {}", connection_expr(token))),
+        AuthProfile::ApiKey { name, value, location: KeyLocation::Header } => RequestAuth {
+            headers: vec![format!("{}: {}", hlx_string(name), connection_expr(value))],
+            query: None,
+        },
+        AuthProfile::ApiKey { name, value, location: KeyLocation::Query } => RequestAuth {
+            headers: Vec::new(),
+            query: Some((name.clone(), connection_expr(value))),
+        },
+        AuthProfile::OAuth2ClientCredentials { .. } | AuthProfile::OAuth2RefreshToken { .. } => {
+            authorization(format!("\"Bearer \" + env({})", hlx_string(&token_var(name))))
+        }
+    })
+}
+
+/// An access token and when to stop using it
+struct CachedToken {
+    /// Profile the token was issued for; an edited profile gets a new one
+    profile: AuthProfile,
+    access_token: String,
+    /// Latest refresh token, which some servers rotate on every refresh
+    refresh_token: Option<String>,
+    renew_at: Instant,
+}
+
+static TOKENS: Mutex<BTreeMap<String, CachedToken>> = Mutex::new(BTreeMap::new());
+
+/// Tokens for the OAuth2 profiles compiled into `source`, reusing cached
+/// ones, as variables for the run's environment (`token_var` to token)
+pub fn refresh_tokens(source: &str, vars: &crate::RunVars) -> Result<HashMap<String, String>, String> {
+    let needed: Vec<(String, AuthProfile)> = PROFILES.read().unwrap().iter()
+        .filter(|(name, profile)| profile.is_oauth2() && source.contains(&format!("env(\"{}\")", token_var(name))))
+        .map(|(name, profile)| (name.clone(), profile.clone()))
+        .collect();
+    refresh(needed, |profile, rotated| request_token(profile, rotated, vars))
+}
+
+/// `refresh_tokens` with the token endpoint call passed in. The cache is
+/// only locked to look tokens up and store them, never during a request,
+/// so one slow token endpoint doesn't hold up every other run.
+fn refresh(
+    needed: Vec<(String, AuthProfile)>,
+    fetch: impl Fn(&AuthProfile, Option<String>) -> Result<CachedToken, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut vars = HashMap::new();
+    for (name, profile) in needed {
+        let (valid, refresh_token) = {
+            let tokens = TOKENS.lock().unwrap();
+            let cached = tokens.get(&name).filter(|t| t.profile == profile);
+            (
+                cached.filter(|t| Instant::now() < t.renew_at).map(|t| t.access_token.clone()),
+                cached.and_then(|t| t.refresh_token.clone()),
+            )
+        };
+        let token = match valid {
+            Some(token) => token,
+            None => {
+                let token = fetch(&profile, refresh_token).map_err(|e| format!("Auth profile '{}': {}", name, e))?;
+                let access_token = token.access_token.clone();
+                TOKENS.lock().unwrap().insert(name.clone(), token);
+                access_token
+            }
+        };
+        vars.insert(token_var(&name), token);
+    }
+    Ok(vars)
+}
+
+/// Value of a profile field, reading `env:NAME` from the run's variables
+/// or the environment
+fn resolve(value: &str, vars: &crate::RunVars) -> Result<String, String> {
+    match value.strip_prefix("env:").map(str::trim) {
+        Some(name) => vars.get(name).cloned()
+            .or_else(|| std::env::var(name).ok())
+            .ok_or_else(|| format!("{} is not set", name)),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Ask the profile's token endpoint for an access token
+fn request_token(
+    profile: &AuthProfile,
+    rotated_refresh_token: Option<String>,
+    vars: &crate::RunVars,
+) -> Result<CachedToken, String> {
+    let (token_url, mut form) = match profile {
+        AuthProfile::OAuth2ClientCredentials { token_url, client_id, client_secret, scope } => {
+            let mut form = HashMap::from([
+                ("grant_type", "client_credentials".to_string()),
+                ("client_id", resolve(client_id, vars)?),
+                ("client_secret", resolve(client_secret, vars)?),
+            ]);
+            if !scope.is_empty() {
+                form.insert("scope", scope.clone());
+            }
+            (token_url, form)
+        }
+        AuthProfile::OAuth2RefreshToken { token_url, client_id, client_secret, refresh_token } => {
+            let refresh_token = match rotated_refresh_token {
+                Some(token) => token,
+                None => resolve(refresh_token, vars)?,
+            };
+            let form = HashMap::from([
+                ("grant_type", "refresh_token".to_string()),
+                ("client_id", resolve(client_id, vars)?),
+                ("client_secret", resolve(client_secret, vars)?),
+                ("refresh_token", refresh_token),
+            ]);
+            (token_url, form)
+        }
+        _ => return Err("not an OAuth2 profile".to_string()),
+    };
+    form.retain(|_, value| !value.is_empty());
+
+    let fields: Vec<(&str, &str)> = form.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    let text = ureq::post(&resolve(token_url, vars)?)
+        .timeout(Duration::from_secs(30))
+        .send_form(&fields)
+        .map_err(|e| format!("token request failed: {}", e))?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let response: JsonValue = serde_json::from_str(&text).map_err(|e| format!("invalid token response: {}", e))?;
+
+    let access_token = response["access_token"].as_str()
+        .ok_or_else(|| format!("token response has no access_token: {}", response))?
+        .to_string();
+    let lifetime = Duration::from_secs(response["expires_in"].as_u64().unwrap_or(DEFAULT_TOKEN_LIFETIME));
+    Ok(CachedToken {
+        profile: profile.clone(),
+        access_token,
+        refresh_token: response["refresh_token"].as_str().map(str::to_string).or_else(|| form.remove("refresh_token")),
+        renew_at: Instant::now() + lifetime.saturating_sub(EXPIRY_MARGIN),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn profiles_compile_to_headers_and_query_parameters() {
+        let profiles: BTreeMap<String, AuthProfile> = serde_json::from_value(json!({
+            "github": {"type": "bearer", "token": "env:GITHUB_TOKEN"},
+            "legacy": {"type": "basic", "username": "bot", "password": "env:LEGACY_PASSWORD"},
+            "maps": {"type": "api_key", "name": "key", "value": "env:MAPS_KEY", "in": "query"},
+            "crm": {"type": "oauth2_client_credentials", "token_url": "https://crm.example.com/token",
+                    "client_id": "env:CRM_ID", "client_secret": "env:CRM_SECRET"}
+        }))
+        .unwrap();
+        set_profiles(&profiles);
+
+        let github = request_auth(&json!({"auth": "github"})).unwrap();
+        assert_eq!(github.header_object(), "{ \"Authorization\": \"Bearer \" + env(\"GITHUB_TOKEN\") }");
+
+        let legacy = request_auth(&json!({"auth": "legacy"})).unwrap();
+        assert!(legacy.headers[0].contains("base64_encode(\"bot\" + \":\" + env(\"LEGACY_PASSWORD\"))"));
+
+        let maps = request_auth(&json!({"auth": "maps"})).unwrap();
+        assert_eq!((maps.header_object(), maps.url("https://maps.example.com/geo?q=x")), (
+            "{}".to_string(),
+            "\"https://maps.example.com/geo?q=x&key=\" + url_encode(env(\"MAPS_KEY\"))".to_string(),
+        ));
+
+        let crm = request_auth(&json!({"auth": "crm"})).unwrap();
+        assert!(crm.headers[0].ends_with(&format!("env(\"{}\")", token_var("crm"))));
+        assert_eq!(token_var("crm-eu"), "AUTOGRAPH_OAUTH_CRM_EU");
+
+        assert_eq!(request_auth(&json!({})).unwrap(), RequestAuth::default());
+        assert!(request_auth(&json!({"auth": "nope"})).is_err());
+
+        assert!(profiles["crm"].plaintext_secrets().is_empty());
+        let plaintext = AuthProfile::Basic { username: "bot".to_string(), password: "hunter2".to_string() };
+        assert_eq!(plaintext.plaintext_secrets(), ["password"]);
+    }
+
+    #[test]
+    fn tokens_are_reused_until_they_expire_and_kept_when_a_refresh_fails() {
+        let profile = AuthProfile::OAuth2RefreshToken {
+            token_url: "https://auth.example.com/token".to_string(),
+            client_id: "env:ID".to_string(),
+            client_secret: "env:SECRET".to_string(),
+            refresh_token: "env:REFRESH".to_string(),
+        };
+        let needed = || vec![("expiry_test".to_string(), profile.clone())];
+        let issue = |access: &str, refresh: &str, lifetime: Duration| CachedToken {
+            profile: profile.clone(),
+            access_token: access.to_string(),
+            refresh_token: Some(refresh.to_string()),
+            renew_at: Instant::now() + lifetime,
+        };
+        let var = token_var("expiry_test");
+
+        let first = refresh(needed(), |_, rotated| {
+            assert_eq!(rotated, None);
+            Ok(issue("a1", "r1", Duration::ZERO))
+        });
+        assert_eq!(first.unwrap()[&var], "a1");
+
+        // Expired at once, so the next run refreshes with the rotated token
+        let second = refresh(needed(), |_, rotated| {
+            assert_eq!(rotated.as_deref(), Some("r1"));
+            Ok(issue("a2", "r2", Duration::from_secs(3600)))
+        });
+        assert_eq!(second.unwrap()[&var], "a2");
+
+        // Still valid: no request at all
+        let cached = refresh(needed(), |_, _| Err("should not be called".to_string()));
+        assert_eq!(cached.unwrap()[&var], "a2");
+
+        // A failed refresh fails the run and leaves the cached token alone
+        TOKENS.lock().unwrap().get_mut("expiry_test").unwrap().renew_at = Instant::now();
+        let failed = refresh(needed(), |_, _| Err("token request failed: 401".to_string()));
+        assert_eq!(failed.unwrap_err(), "Auth profile 'expiry_test': token request failed: 401");
+        assert_eq!(TOKENS.lock().unwrap()["expiry_test"].refresh_token.as_deref(), Some("r2"));
+    }
+}
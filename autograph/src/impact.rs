@@ -0,0 +1,146 @@
+//! Impact Analysis
+//!
+//! What a delete would break: downstream nodes losing their inputs, flows
+//! calling a flow as a subflow, its deployed endpoint, queued retries and
+//! recent triggers. The editor and the API show this and ask for
+//! confirmation before deleting, instead of silently breaking automations.
+//! Renaming a flow points its subflow callers at the new name.
+
+use crate::flow::{Flow, Node};
+use crate::retry_queue::ScheduledRetry;
+use crate::runs::RunRecord;
+use std::path::Path;
+
+/// How far back recent runs count as a sign of an active trigger
+const RECENT_RUNS_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Consequences of deleting a node from a flow; empty if nothing depends on it
+pub fn node_impact(flow: &Flow, node_id: &str) -> Vec<String> {
+    let mut impact = Vec::new();
+
+    let targets: Vec<&str> = flow.edges.iter()
+        .filter(|e| e.source == node_id)
+        .map(|e| e.target.as_str())
+        .collect();
+    for target in &targets {
+        let other_inputs = flow.edges.iter().filter(|e| e.target == *target && e.source != node_id).count();
+        if other_inputs == 0 {
+            impact.push(format!("{} loses its only input", target));
+        } else {
+            impact.push(format!("{} loses one of its {} inputs", target, other_inputs + 1));
+        }
+    }
+
+    let mut further: Vec<String> = flow.downstream_ids(node_id)
+        .into_iter()
+        .filter(|id| id != node_id && !targets.contains(&id.as_str()))
+        .collect();
+    if !further.is_empty() {
+        further.sort();
+        impact.push(format!("{} more node(s) downstream get different data: {}", further.len(), further.join(", ")));
+    }
+
+    impact
+}
+
+/// Consequences of deleting the flow `flow_name` from `flows_dir`; empty if
+/// nothing depends on it
+pub fn flow_impact(flows_dir: &Path, flow_name: &str, retries: &[ScheduledRetry], runs: &[RunRecord]) -> Vec<String> {
+    let mut impact = Vec::new();
+
+    for (caller, nodes) in subflow_callers(flows_dir, flow_name) {
+        impact.push(format!("Flow '{}' calls it as a subflow (nodes: {})", caller, nodes.join(", ")));
+    }
+
+    if flows_dir.join(format!("{}.hlxa", flow_name)).exists() {
+        impact.push(format!("It is deployed: POST /run/{} will stop working", flow_name));
+    }
+
+    let pending = retries.iter().filter(|r| r.flow_name == flow_name).count();
+    if pending > 0 {
+        impact.push(format!("{} scheduled retry(ies) will be dropped", pending));
+    }
+
+    let since = crate::runs::now_ms().saturating_sub(RECENT_RUNS_MS);
+    let recent = runs.iter().filter(|r| r.flow_name == flow_name && r.started_at >= since).count();
+    if recent > 0 {
+        impact.push(format!("It ran {} time(s) in the last 24 hours; its triggers will fail", recent));
+    }
+
+    impact
+}
+
+/// Flows in `flows_dir` with nodes calling `flow_name`, with those node IDs
+fn subflow_callers(flows_dir: &Path, flow_name: &str) -> Vec<(String, Vec<String>)> {
+    let Ok(entries) = std::fs::read_dir(flows_dir) else {
+        return Vec::new();
+    };
+
+    let mut callers: Vec<(String, Vec<String>)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            let flow: Flow = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+            let nodes: Vec<String> = flow.nodes.iter()
+                .filter(|n| calls(n, flow_name))
+                .map(|n| n.id.clone())
+                .collect();
+            (name != flow_name && !nodes.is_empty()).then_some((name, nodes))
+        })
+        .collect();
+    callers.sort();
+    callers
+}
+
+fn calls(node: &Node, flow_name: &str) -> bool {
+    node.config.get("flow").and_then(|f| f.as_str()) == Some(flow_name)
+}
+
+/// Point the nodes of `flow` calling `from` as a subflow at `to`
+pub fn rename_subflow_calls(flow: &mut Flow, from: &str, to: &str) {
+    for node in flow.nodes.iter_mut().filter(|n| calls(n, from)) {
+        node.config["flow"] = serde_json::json!(to);
+    }
+}
+
+/// Once the flow `from` in `flows_dir` is renamed to `to`, point the flows
+/// calling it as a subflow at the new name. Returns the flows changed.
+pub fn rename_subflow_callers(flows_dir: &Path, from: &str, to: &str) -> Result<Vec<String>, String> {
+    let mut changed = Vec::new();
+    for (caller, _) in subflow_callers(flows_dir, from) {
+        let path = flows_dir.join(format!("{}.json", caller));
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut flow: Flow = serde_json::from_str(&text).map_err(|e| format!("Invalid flow {}: {}", path.display(), e))?;
+        rename_subflow_calls(&mut flow, from, to);
+        let json = serde_json::to_string_pretty(&flow).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        changed.push(caller);
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renaming_a_flow_rewrites_its_subflow_callers() {
+        let dir = std::env::temp_dir().join(format!("autograph-impact-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let save = |name: &str, flow: &Flow| std::fs::write(dir.join(format!("{}.json", name)), serde_json::to_string(flow).unwrap()).unwrap();
+        let caller = Flow { nodes: vec![Node::new("enrich", "subflow", json!({"flow": "lookup"}))], ..Default::default() };
+        save("orders", &caller);
+        save("renamed", &Flow::default());
+
+        assert_eq!(flow_impact(&dir, "lookup", &[], &[]), ["Flow 'orders' calls it as a subflow (nodes: enrich)"]);
+        assert_eq!(rename_subflow_callers(&dir, "lookup", "renamed").unwrap(), ["orders"]);
+        assert!(flow_impact(&dir, "lookup", &[], &[]).is_empty());
+        let orders: Flow = serde_json::from_str(&std::fs::read_to_string(dir.join("orders.json")).unwrap()).unwrap();
+        assert_eq!(orders.nodes[0].config["flow"], "renamed");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
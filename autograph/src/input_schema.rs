@@ -0,0 +1,188 @@
+//! Flow Input Schemas
+//!
+//! A flow can declare the shape of its input as a JSON Schema. The server
+//! checks `/run` payloads against it and the editor builds its run form
+//! from it. The supported subset covers what input forms need: `type`
+//! (one or a list), `properties`, `required`, `additionalProperties: false`,
+//! `items`, `enum`, `minimum`/`maximum`, `minLength`/`maxLength`, `pattern`
+//! and `default`.
+
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+
+/// A part of the input that doesn't match the schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    /// Where in the input, as a JSON pointer; `""` is the whole input
+    pub path: String,
+    pub message: String,
+}
+
+/// Check `value` against `schema`, returning every mismatch
+pub fn validate(schema: &JsonValue, value: &JsonValue) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    check(schema, value, "", &mut errors);
+    errors
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &JsonValue, expected: &str) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+/// The schema's allowed types; empty allows any
+pub fn types(schema: &JsonValue) -> Vec<&str> {
+    match &schema["type"] {
+        JsonValue::String(t) => vec![t.as_str()],
+        JsonValue::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn check(schema: &JsonValue, value: &JsonValue, path: &str, errors: &mut Vec<FieldError>) {
+    let mut fail = |message: String| errors.push(FieldError { path: path.to_string(), message });
+
+    let types = types(schema);
+    if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+        fail(format!("expected {}, got {}", types.join(" or "), type_name(value)));
+        return;
+    }
+    if let Some(options) = schema["enum"].as_array() {
+        if !options.contains(value) {
+            let options: Vec<String> = options.iter().map(JsonValue::to_string).collect();
+            fail(format!("must be one of {}", options.join(", ")));
+        }
+    }
+
+    match value {
+        JsonValue::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = schema["minimum"].as_f64().filter(|min| n < *min) {
+                fail(format!("must be at least {}", min));
+            }
+            if let Some(max) = schema["maximum"].as_f64().filter(|max| n > *max) {
+                fail(format!("must be at most {}", max));
+            }
+        }
+        JsonValue::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema["minLength"].as_u64().filter(|min| len < *min) {
+                fail(format!("must be at least {} characters", min));
+            }
+            if let Some(max) = schema["maxLength"].as_u64().filter(|max| len > *max) {
+                fail(format!("must be at most {} characters", max));
+            }
+            if let Some(pattern) = schema["pattern"].as_str() {
+                match regex::Regex::new(pattern) {
+                    Ok(re) if !re.is_match(s) => fail(format!("must match {}", pattern)),
+                    Ok(_) => {}
+                    Err(e) => fail(format!("schema pattern is invalid: {}", e)),
+                }
+            }
+        }
+        JsonValue::Array(items) => {
+            if schema["items"].is_object() {
+                for (i, item) in items.iter().enumerate() {
+                    check(&schema["items"], item, &format!("{}/{}", path, i), errors);
+                }
+            }
+        }
+        JsonValue::Object(fields) => check_object(schema, fields, path, errors),
+        _ => {}
+    }
+}
+
+fn check_object(schema: &JsonValue, fields: &Map<String, JsonValue>, path: &str, errors: &mut Vec<FieldError>) {
+    let properties = schema["properties"].as_object();
+    for name in schema["required"].as_array().into_iter().flatten().filter_map(|n| n.as_str()) {
+        if !fields.contains_key(name) {
+            errors.push(FieldError { path: format!("{}/{}", path, name), message: "is required".to_string() });
+        }
+    }
+    for (name, value) in fields {
+        let field_path = format!("{}/{}", path, name);
+        match properties.and_then(|p| p.get(name)) {
+            Some(field_schema) => check(field_schema, value, &field_path, errors),
+            None if schema["additionalProperties"] == JsonValue::Bool(false) => {
+                errors.push(FieldError { path: field_path, message: "is not allowed".to_string() });
+            }
+            None => {}
+        }
+    }
+}
+
+/// A starting value for a form built from `schema`: its `default`, or an
+/// empty value of its type with defaults filled in for required object
+/// fields. Optional fields are left out until they are set.
+pub fn default_value(schema: &JsonValue) -> JsonValue {
+    if let Some(default) = schema.get("default") {
+        return default.clone();
+    }
+    if let Some(first) = schema["enum"].as_array().and_then(|options| options.first()) {
+        return first.clone();
+    }
+    match types(schema).first().copied() {
+        Some("object") => {
+            let required: Vec<&str> = schema["required"].as_array().into_iter().flatten()
+                .filter_map(|n| n.as_str())
+                .collect();
+            let fields = schema["properties"].as_object().into_iter().flatten()
+                .filter(|(name, _)| required.contains(&name.as_str()))
+                .map(|(name, field)| (name.clone(), default_value(field)))
+                .collect();
+            JsonValue::Object(fields)
+        }
+        Some("array") => JsonValue::Array(Vec::new()),
+        Some("string") => JsonValue::String(String::new()),
+        Some("integer") | Some("number") => JsonValue::from(0),
+        Some("boolean") => JsonValue::Bool(false),
+        _ => JsonValue::Null,
+    }
+}
+
+/// One line per error, e.g. `/email: is required`
+pub fn describe(errors: &[FieldError]) -> Vec<String> {
+    errors.iter()
+        .map(|e| format!("{}: {}", if e.path.is_empty() { "input" } else { &e.path }, e.message))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_every_mismatch_with_its_path() {
+        let schema = json!({
+            "type": "object",
+            "required": ["email", "count"],
+            "additionalProperties": false,
+            "properties": {
+                "email": {"type": "string", "pattern": "@"},
+                "count": {"type": "integer", "minimum": 1},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}},
+            },
+        });
+
+        assert!(validate(&schema, &json!({"email": "x@y", "count": 2, "tags": ["a"]})).is_empty());
+        let errors = validate(&schema, &json!({"email": "nope", "tags": ["c"], "extra": 1}));
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["/count", "/email", "/extra", "/tags/0"]);
+        assert_eq!(validate(&schema, &json!(null))[0].message, "expected object, got null");
+
+        assert_eq!(default_value(&schema), json!({"email": "", "count": 0}));
+    }
+}
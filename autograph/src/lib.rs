@@ -0,0 +1,128 @@
+//! Autograph
+//!
+//! The editor, server and CLI of the `autograph` binary, as a library so
+//! binaries embedding autograph can contribute templates and node types
+//! (see `registry` and `nodes::register_dynamic`) before starting the
+//! editor with `ui::run` or serving flows themselves.
+
+pub mod api_spec;
+pub mod auth;
+pub mod bundle;
+pub mod bytes;
+pub mod chaos;
+pub mod cli;
+pub mod compile_cache;
+pub mod complexity;
+pub mod concurrency;
+pub mod db_pool;
+pub mod decompile;
+pub mod diagnostics;
+pub mod email;
+pub mod expr;
+pub mod fallback;
+pub mod flow;
+pub mod gpu_stats;
+pub mod http_auth;
+pub mod impact;
+pub mod input_schema;
+pub mod llm;
+pub mod metrics;
+pub mod mock;
+pub mod nats;
+pub mod node_red;
+pub mod nodes;
+pub mod offline;
+pub mod openapi;
+pub mod patterns;
+pub mod pinning;
+pub mod plugins;
+pub mod preflight;
+pub mod profile;
+pub mod project;
+pub mod prompt;
+pub mod query;
+pub mod registry;
+pub mod remote;
+pub mod report;
+pub mod retry_queue;
+#[cfg(test)]
+mod roundtrip;
+pub mod run_diff;
+pub mod run_env;
+pub mod runs;
+pub mod signing;
+pub mod state_store;
+pub mod streaming;
+pub mod templates;
+pub mod testing;
+pub mod throttle;
+pub mod ui;
+pub mod vectors;
+pub mod versions;
+
+use hlx_compiler::{lower, parser::Parser as ParseTrait, HlxaParser};
+use hlx_core::Value;
+use hlx_runtime::config::BackendType;
+use hlx_runtime::{execute_with_config, RuntimeConfig};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// Variables only one run's `env()` sees, ahead of the process environment,
+/// e.g. its state store
+pub type RunVars = HashMap<String, String>;
+
+/// Compile and run HLX source with an input on the runtime's default backend
+pub fn compile_and_run(source: &str, input_json: JsonValue, vars: &RunVars) -> anyhow::Result<Value> {
+    compile_and_run_on(source, input_json, None, vars)
+}
+
+/// A compiled flow, run with an input and run variables on a backend, or
+/// the runtime's default for `None`
+pub type CompiledFlow = Box<dyn Fn(JsonValue, Option<BackendType>, &RunVars) -> anyhow::Result<Value> + Send + Sync>;
+
+/// Parse and lower HLX source, ready to run any number of times
+pub fn compile_source(source: &str) -> anyhow::Result<CompiledFlow> {
+    // Parse
+    let parser = HlxaParser::new();
+    let ast = parser.parse(source).map_err(|e| anyhow::anyhow!("Parse error: {:?}", e))?;
+
+    // Lower
+    let krate = lower::lower_to_crate(&ast).map_err(|e| anyhow::anyhow!("Lowering error: {:?}", e))?;
+    let source = source.to_string();
+
+    Ok(Box::new(move |input_json: JsonValue, backend: Option<BackendType>, vars: &RunVars| {
+        state_store::require(&source, vars).map_err(|e| anyhow::anyhow!(e))?;
+        // OAuth2 tokens of the auth profiles the program uses
+        let tokens = http_auth::refresh_tokens(&source, vars).map_err(|e| anyhow::anyhow!(e))?;
+        // Held until the run ends, so concurrency-capped hosts see at most that many runs
+        let _slots = throttle::acquire(&source);
+
+        // Setup config with main input
+        let mut config = RuntimeConfig::default();
+        if let Some(backend) = backend {
+            config.backend = backend;
+        }
+        db_pool::attach(&mut config);
+        // Only this run sees its variables and tokens, not the process or other runs
+        config.env.extend(vars.clone());
+        config.env.extend(tokens);
+        let hlx_input = Value::from_json(input_json).map_err(|e| anyhow::anyhow!("Input conversion error: {:?}", e))?;
+
+        // We pass the input as a string to main(input) for now, or we could modify the runtime to take a Value
+        // The current runtime.main_input is a Option<String>
+        config.main_input = Some(serde_json::to_string(&hlx_input.to_json()?)?);
+
+        // Execute
+        execute_with_config(&krate, &config).map_err(|e| anyhow::anyhow!("Runtime error: {:?}", e))
+    }))
+}
+
+/// Compile and run on a specific backend, or the runtime's default for `None`
+pub fn compile_and_run_on(
+    source: &str,
+    input_json: JsonValue,
+    backend: Option<BackendType>,
+    vars: &RunVars,
+) -> anyhow::Result<Value> {
+    compile_source(source)?(input_json, backend, vars)
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+//! LLM Completions
+//!
+//! `llm_complete` talks to OpenAI-compatible chat APIs (OpenAI, and the
+//! many servers copying its `/chat/completions` endpoint) and to local
+//! Ollama servers. Either way the node outputs `{text, model, usage}`, with
+//! usage normalized to prompt, completion and total tokens; the editor
+//! shows the counts on the node's timeline entry.
+
+use serde_json::Value as JsonValue;
+
+/// An API `llm_complete` can call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    OpenAi,
+    Ollama,
+}
+
+/// Names of the providers, as set in `provider`
+pub const PROVIDERS: &[&str] = &["openai", "ollama"];
+
+impl Provider {
+    pub fn parse(name: &str) -> Option<Provider> {
+        match name {
+            "openai" => Some(Provider::OpenAi),
+            "ollama" => Some(Provider::Ollama),
+            _ => None,
+        }
+    }
+
+    /// Base URL used when the node has no `base_url`
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            Provider::OpenAi => "https://api.openai.com/v1",
+            Provider::Ollama => "http://localhost:11434",
+        }
+    }
+
+    /// API key sent when the node has no `api_key`; local Ollama servers
+    /// take none
+    pub fn default_api_key(&self) -> &'static str {
+        match self {
+            Provider::OpenAi => "env:OPENAI_API_KEY",
+            Provider::Ollama => "",
+        }
+    }
+
+    /// Path of the chat endpoint under the base URL
+    pub fn chat_path(&self) -> &'static str {
+        match self {
+            Provider::OpenAi => "/chat/completions",
+            Provider::Ollama => "/api/chat",
+        }
+    }
+
+    /// Path of the embeddings endpoint under the base URL
+    pub fn embeddings_path(&self) -> &'static str {
+        match self {
+            Provider::OpenAi => "/embeddings",
+            Provider::Ollama => "/api/embed",
+        }
+    }
+}
+
+/// Tokens a completion used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl Usage {
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+/// Token usage reported in an `llm_complete` node's output
+pub fn usage(output: &JsonValue) -> Option<Usage> {
+    let usage = &output["usage"];
+    Some(Usage {
+        prompt_tokens: usage["prompt_tokens"].as_u64()?,
+        completion_tokens: usage["completion_tokens"].as_u64()?,
+    })
+}
+
+/// Timeline summary of a completion's usage, e.g. `412 tokens (380 in, 32 out)`
+pub fn describe(usage: &Usage) -> String {
+    format!("{} tokens ({} in, {} out)", usage.total(), usage.prompt_tokens, usage.completion_tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn usage_is_read_from_the_output_for_the_timeline() {
+        let output = json!({
+            "text": "Paris",
+            "model": "llama3.1",
+            "usage": {"prompt_tokens": 380, "completion_tokens": 32, "total_tokens": 412},
+        });
+        let usage = usage(&output).unwrap();
+        assert_eq!(describe(&usage), "412 tokens (380 in, 32 out)");
+        assert_eq!(super::usage(&json!({"text": "no usage"})), None);
+
+        let provider = Provider::parse("ollama").unwrap();
+        assert_eq!(format!("{}{}", provider.default_base_url(), provider.chat_path()), "http://localhost:11434/api/chat");
+        assert_eq!(Provider::parse("anthropic"), None);
+    }
+}
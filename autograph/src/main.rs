@@ -1,22 +1,34 @@
-mod flow;
-mod ui;
-mod nodes;
-mod templates;
+use autograph::{
+    api_spec, auth, bundle, chaos, cli, compile_cache, complexity, concurrency, email, fallback,
+    flow, gpu_stats, http_auth, impact, input_schema, metrics, nats, nodes, offline, plugins,
+    preflight, project, report, retry_queue, run_env, runs, signing, state_store, ui,
+};
+use autograph::{compile_and_run, compile_and_run_on, compile_source, CompiledFlow, RunVars};
 
 use axum::{
-    extract::{Path, State},
-    routing::post,
-    Json, Router,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Extension, Json, Router,
 };
 use clap::{Parser, Subcommand};
 use serde_json::{Value as JsonValue};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::path::PathBuf;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 use hlx_core::Value;
 use hlx_compiler::{HlxaParser, parser::Parser as ParseTrait, lower};
 use hlx_runtime::{execute_with_config, RuntimeConfig};
-use flow::Flow;
+use hlx_runtime::config::BackendType;
+use auth::{AuthConfig, Principal, Scope};
+use flow::{CompileOptions, Flow, InterruptPolicy};
+use metrics::Metrics;
+use run_env::RunEnvironment;
+use retry_queue::{RetryQueue, ScheduledRetry};
+use runs::{LogLevel, RunRecord, RunStatus, RunStore};
 
 #[derive(Parser)]
 #[command(name = "autograph")]
@@ -35,25 +47,249 @@ enum Commands {
         /// Port to listen on
         #[arg(short, long, default_value = "3000")]
         port: u16,
+        /// JSON file of API keys and their scopes; without it the API is open
+        #[arg(long)]
+        auth_config: Option<PathBuf>,
+        /// Block nodes that reach the network in every run
+        #[arg(long)]
+        offline: bool,
+        /// Trust list of publisher keys; when given, only bundles signed by
+        /// a trusted publisher can be deployed
+        #[arg(long)]
+        trusted_publishers: Option<PathBuf>,
+        /// Runs executing at once; more wait in per-flow queues
+        #[arg(long, default_value = "8")]
+        max_concurrent_runs: usize,
+        /// Runs of one flow that may wait before new ones get a 429
+        #[arg(long, default_value = "100")]
+        max_queued_runs: usize,
+        /// Write server logs as JSON lines, with each run's ID on its events
+        #[arg(long)]
+        log_json: bool,
+    },
+    /// Compile a flow to HLX source
+    Compile {
+        /// Flow definition (.json)
+        flow: PathBuf,
+        /// Write the source here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// RNG seed for random nodes
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Compile and run a flow, printing its result as JSON
+    Run {
+        /// Flow definition (.json)
+        flow: PathBuf,
+        /// JSON file passed to the flow as input
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+        /// RNG seed for random nodes
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Fail instead of running nodes that reach the network
+        #[arg(long)]
+        offline: bool,
+        /// Inject the faults listed in the flow's `chaos` rules
+        #[arg(long)]
+        chaos: bool,
+        /// Use the flow's mocks instead of real services and a scratch
+        /// directory with its mock files for file nodes
+        #[arg(long)]
+        mock: bool,
+        /// Label the run (`key=value`, repeatable); labelled runs are
+        /// recorded in the run history
+        #[arg(long = "label")]
+        labels: Vec<String>,
+    },
+    /// Run a flow's test cases (`<flow>.tests.json`) and its assertion nodes
+    Test {
+        /// Flow definition (.json)
+        flow: PathBuf,
+        /// Use the flow's mocks and mock files instead of real services
+        #[arg(long)]
+        mock: bool,
+    },
+    /// Check a flow for structural errors and unknown node types
+    Validate {
+        /// Flow definition (.json)
+        flow: PathBuf,
+    },
+    /// Verify the environment variables, credentials, files and hosts a flow requires
+    Check {
+        /// Flow definition (.json)
+        flow: PathBuf,
+        /// Project directory holding the flow's files and `.env`
+        #[arg(long, default_value = ".")]
+        project_dir: PathBuf,
+        /// Skip host connectivity checks
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Rebuild a flow definition from a compiled HLX program
+    Decompile {
+        /// HLX source (.hlxa)
+        source: PathBuf,
+        /// Write the flow here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Time each node of a flow and write a performance report
+    Profile {
+        /// Flow definition (.json)
+        flow: PathBuf,
+        /// JSON file passed to the flow as input
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+        /// Write the report here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Report format: md or html (default: from the output extension)
+        #[arg(long)]
+        format: Option<String>,
+        /// Timed runs per node; the fastest is reported
+        #[arg(long, default_value = "3")]
+        iterations: usize,
+        /// RNG seed for random nodes
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Create a publisher key for signing bundles
+    Keygen {
+        /// Publisher name recorded in signatures
+        publisher: String,
+        /// Key file to create
+        #[arg(short, long, default_value = "publisher.key")]
+        output: PathBuf,
+    },
+    /// Sign an exported bundle
+    Sign {
+        /// Bundle (.autograph)
+        bundle: PathBuf,
+        /// Publisher key file
+        #[arg(short, long)]
+        key: PathBuf,
+    },
+    /// Check a bundle's checksums and signature
+    Verify {
+        /// Bundle (.autograph)
+        bundle: PathBuf,
+        /// Trust list the signer must be on
+        #[arg(long)]
+        trusted_publishers: Option<PathBuf>,
     },
 }
 
+/// Where the server keeps deployed flows, apart from the editor's `flows/`
+/// so saving or compiling there doesn't change what the server runs
+const DEPLOY_DIR: &str = "deployed";
+
 struct AppState {
+    /// Deployed flows: the definition (`<name>.json`) and compiled source
+    /// (`<name>.hlxa`) as of their last deploy
     flows_dir: PathBuf,
+    runs: RunStore,
+    metrics: Metrics,
+    started: std::time::Instant,
+    /// Runtime backends detected at startup
+    backends: Vec<BackendStatus>,
+    /// Block nodes that reach the network in every run
+    offline: bool,
+    /// Publishers allowed to deploy; `None` accepts plain flows
+    trusted: Option<signing::TrustedPublishers>,
+    /// Failed runs waiting for an automatic re-run
+    retries: RetryQueue,
+    /// Deployed flows compiled by earlier runs
+    compiled: compile_cache::CompileCache<CompiledFlow>,
+    /// Caps concurrent runs, queueing the rest per flow
+    limiter: Arc<concurrency::RunLimiter>,
+}
+
+/// Availability of a runtime backend
+#[derive(Debug, Clone, serde::Serialize)]
+struct BackendStatus {
+    name: &'static str,
+    available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 fn main() {
-    tracing_subscriber::fmt::init();
+    let cli = Cli::parse();
+    match &cli.command {
+        Some(Commands::Server { log_json: true, .. }) => tracing_subscriber::fmt().json().init(),
+        _ => tracing_subscriber::fmt::init(),
+    }
 
     // Ensure flows dir exists
     std::fs::create_dir_all("flows").ok();
 
-    let cli = Cli::parse();
+    // Node types generated at runtime (e.g. from OpenAPI specs)
+    let (count, errors) = nodes::load_dynamic_nodes(std::path::Path::new("nodes"));
+    if count > 0 {
+        info!("Loaded {} generated node type(s)", count);
+    }
+    for e in errors {
+        warn!("Failed to load generated nodes: {}", e);
+    }
+
+    // Organization-specific node types from plugin files
+    let (count, errors) = plugins::load_plugins(std::path::Path::new(plugins::PLUGINS_DIR));
+    if count > 0 {
+        info!("Loaded {} plugin node type(s)", count);
+    }
+    for e in errors {
+        warn!("Failed to load plugin nodes: {}", e);
+    }
+
+    // Auth profiles HTTP nodes can use, from the project file
+    let project_file = std::path::Path::new(project::PROJECT_FILE);
+    if project_file.exists() {
+        match project::Project::load(project_file) {
+            Ok(project) => http_auth::set_profiles(&project.auth_profiles),
+            Err(e) => warn!("Failed to load auth profiles: {}", e),
+        }
+    }
 
     let result = match cli.command {
-        Some(Commands::Server { port }) => {
+        Some(Commands::Server { port, auth_config, offline, trusted_publishers, max_concurrent_runs, max_queued_runs, .. }) => {
             // Run REST API server
-            run_server(port).map_err(|e| eprintln!("Server error: {}", e))
+            let limits = concurrency::RunLimits { max_running: max_concurrent_runs.max(1), max_queued: max_queued_runs };
+            run_server(port, auth_config, offline || offline::enabled_by_env(), trusted_publishers, limits)
+                .map_err(|e| eprintln!("Server error: {}", e))
+        }
+        Some(Commands::Compile { flow, output, seed }) => {
+            cli::compile(&flow, output.as_deref(), seed).map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Run { flow, input, seed, offline, chaos, mock, labels }) => {
+            cli::run(&flow, input.as_deref(), seed, offline || offline::enabled_by_env(), chaos, mock, &labels)
+                .map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Test { flow, mock }) => {
+            cli::test(&flow, mock).map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Validate { flow }) => {
+            cli::validate(&flow).map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Check { flow, project_dir, offline }) => {
+            cli::check(&flow, &project_dir, offline || offline::enabled_by_env()).map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Decompile { source, output }) => {
+            cli::decompile(&source, output.as_deref()).map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Profile { flow, input, output, format, iterations, seed }) => {
+            cli::profile(&flow, input.as_deref(), output.as_deref(), format.as_deref(), iterations, seed)
+                .map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Keygen { publisher, output }) => {
+            cli::keygen(&publisher, &output).map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Sign { bundle, key }) => {
+            cli::sign(&bundle, &key).map_err(|e| eprintln!("Error: {}", e))
+        }
+        Some(Commands::Verify { bundle, trusted_publishers }) => {
+            cli::verify(&bundle, trusted_publishers.as_deref()).map_err(|e| eprintln!("Error: {}", e))
         }
         Some(Commands::Ui) | None => {
             // Run native UI (default)
@@ -67,14 +303,86 @@ fn main() {
 }
 
 #[tokio::main]
-async fn run_server(port: u16) -> anyhow::Result<()> {
+async fn run_server(
+    port: u16,
+    auth_config: Option<PathBuf>,
+    offline: bool,
+    trusted_publishers: Option<PathBuf>,
+    limits: concurrency::RunLimits,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(DEPLOY_DIR)?;
+    let auth = auth_config.as_deref().map(AuthConfig::load).transpose()?.map(Arc::new);
+    match &auth {
+        Some(config) => info!("API key authentication enabled ({} keys)", config.keys.len()),
+        None => warn!("No --auth-config given: anyone who can reach the port can deploy and run flows"),
+    }
+
+    let trusted = trusted_publishers.as_deref()
+        .map(signing::TrustedPublishers::load)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!(e))?;
+    if let Some(trusted) = &trusted {
+        info!("Only bundles signed by trusted publishers can be deployed ({} keys)", trusted.publishers.len());
+    }
+
     let state = Arc::new(AppState {
-        flows_dir: PathBuf::from("flows"),
+        flows_dir: PathBuf::from(DEPLOY_DIR),
+        runs: RunStore::new("runs"),
+        metrics: Metrics::default(),
+        started: std::time::Instant::now(),
+        backends: probe_backends(),
+        offline,
+        trusted,
+        retries: RetryQueue::new("retry_queue.json"),
+        compiled: Default::default(),
+        limiter: concurrency::RunLimiter::new(limits),
     });
+    info!("Up to {} concurrent runs, {} queued per flow", limits.max_running, limits.max_queued);
+    if offline {
+        info!("Offline mode: HTTP and database nodes are blocked");
+    }
+
+    for backend in &state.backends {
+        info!("Backend {}: {}", backend.name, if backend.available { "available" } else { "unavailable" });
+    }
+
+    recover_interrupted_runs(&state);
+    tokio::spawn(run_retry_scheduler(state.clone()));
+    tokio::spawn(watch_flows(state.clone()));
+    tokio::spawn(supervise_triggers(state.clone(), nats::triggers));
+    tokio::spawn(supervise_triggers(state.clone(), email::triggers));
+
+    // Require a key with the given scope when authentication is enabled
+    let protect = |router: Router<Arc<AppState>>, scope: Scope| match &auth {
+        Some(config) => router.route_layer(middleware::from_fn_with_state(
+            (config.clone(), scope),
+            auth::require_scope,
+        )),
+        None => router,
+    };
+
+    let run_routes = Router::new()
+        .route("/run/:flow_name", post(run_flow));
+    let deploy_routes = Router::new()
+        .route("/deploy/:flow_name", post(deploy_flow).delete(undeploy_flow));
+    let read_routes = Router::new()
+        .route("/flows/:flow_name", get(get_flow))
+        .route("/flows/:flow_name/runs", get(list_runs))
+        .route("/flows/:flow_name/stats", get(flow_stats))
+        .route("/runs", get(search_runs))
+        .route("/runs/:run_id", get(get_run))
+        .route("/runs/:run_id/logs", get(get_run_logs))
+        .route("/runs/:run_id/report", get(get_run_report));
 
     let app = Router::new()
-        .route("/run/:flow_name", post(run_flow))
-        .route("/deploy/:flow_name", post(deploy_flow))
+        .merge(protect(run_routes, Scope::Run))
+        .merge(protect(deploy_routes, Scope::Deploy))
+        .merge(protect(read_routes, Scope::Read))
+        .route("/metrics", get(metrics))
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/openapi.json", get(|| async { Json(api_spec::spec()) }))
+        .route("/docs", get(|| async { axum::response::Html(api_spec::docs_html(&api_spec::spec())) }))
         .with_state(state);
 
     let addr = format!("0.0.0.0:{}", port);
@@ -85,88 +393,1079 @@ async fn run_server(port: u16) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Changelog details for a deploy, passed as query parameters
+#[derive(serde::Deserialize)]
+struct DeployParams {
+    message: Option<String>,
+    author: Option<String>,
+    /// Deploy even if the flow's required resources are missing here
+    #[serde(default)]
+    skip_preflight: bool,
+}
+
+/// Confirmation for a delete, passed as a query parameter
+#[derive(serde::Deserialize)]
+struct DeleteParams {
+    /// Delete even though other flows, triggers or retries depend on the flow
+    #[serde(default)]
+    confirm: bool,
+}
+
+/// Per-run options, passed as query parameters
+#[derive(serde::Deserialize)]
+struct RunParams {
+    /// Block nodes that reach the network for this run
+    #[serde(default)]
+    offline: bool,
+    /// Inject the flow's chaos faults into this run
+    #[serde(default)]
+    chaos: bool,
+    /// Labels to attach to the run, as `key=value,key=value`
+    labels: Option<String>,
+    /// Wait in the flow's queue when every run slot is busy; `false`
+    /// answers 429 at once instead
+    #[serde(default = "RunParams::wait_by_default")]
+    wait: bool,
+}
+
+impl RunParams {
+    fn wait_by_default() -> bool {
+        true
+    }
+}
+
+/// Filters for run listings, passed as query parameters
+#[derive(serde::Deserialize)]
+struct RunsQuery {
+    /// Only runs carrying all of these labels, as `key=value,key=value`
+    labels: Option<String>,
+}
+
+/// Format of a run report, passed as a query parameter
+#[derive(serde::Deserialize)]
+struct ReportParams {
+    /// `json` (default) or `html`
+    format: Option<String>,
+}
+
+/// Load a deployed flow definition
+fn load_flow_def(flows_dir: &std::path::Path, flow_name: &str) -> Option<Flow> {
+    let json = std::fs::read_to_string(flows_dir.join(format!("{}.json", flow_name))).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Body of a deploy request: a flow, or a bundle whose root is deployed
+/// under the requested name alongside its subflows
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Deployment {
+    Bundle(bundle::Bundle),
+    Flow(Flow),
+}
+
+/// 400 response for a flow name that can't be a file in the flows directory
+fn reject_flow_name(flow_name: &str) -> Option<Response> {
+    (!nodes::valid_flow_name(flow_name)).then(|| {
+        warn!("Rejected invalid flow name {:?}", flow_name);
+        (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Invalid flow name"}))).into_response()
+    })
+}
+
 async fn deploy_flow(
     Path(flow_name): Path<String>,
     State(state): State<Arc<AppState>>,
-    Json(flow): Json<Flow>,
-) -> Json<JsonValue> {
+    Query(params): Query<DeployParams>,
+    principal: Option<Extension<Principal>>,
+    Json(deployment): Json<Deployment>,
+) -> Response {
+    if let Some(rejection) = reject_flow_name(&flow_name) {
+        return rejection;
+    }
     info!("Deploying flow: {}", flow_name);
 
-    let source = flow.compile_to_hlx();
+    let flows = match deployment {
+        Deployment::Flow(_) if state.trusted.is_some() => {
+            error!("Rejected unsigned deploy of {}", flow_name);
+            return Json(serde_json::json!({"error": "This server only accepts bundles signed by a trusted publisher"})).into_response();
+        }
+        Deployment::Flow(flow) => vec![(flow_name.clone(), flow)],
+        Deployment::Bundle(bundle) => {
+            let trusted = bundle.check().and_then(|()| match &state.trusted {
+                Some(trusted) => trusted.check(bundle.signature.as_ref()).map(Some),
+                None => Ok(None),
+            });
+            match trusted {
+                Ok(Some(publisher)) => info!("Bundle signed by trusted publisher '{}'", publisher),
+                Ok(None) => {}
+                Err(e) => {
+                    error!("Rejected bundle for {}: {}", flow_name, e);
+                    return Json(serde_json::json!({"error": format!("Rejected bundle: {}", e)})).into_response();
+                }
+            }
+            let root = bundle.manifest.root.clone();
+            bundle.flows.into_iter()
+                .map(|(name, flow)| (if name == root { flow_name.clone() } else { name }, flow))
+                .collect()
+        }
+    };
+
+    if let Some((name, e)) = flows.iter().find_map(|(name, flow)| flow.validate().err().map(|e| (name, e))) {
+        error!("Invalid flow {}: {}", name, e);
+        return Json(serde_json::json!({"error": format!("Invalid flow {}: {}", name, e)})).into_response();
+    }
+
+    // Catch missing keys, files and hosts now rather than on the first run
+    if !params.skip_preflight {
+        // Host checks block for up to seconds, so they stay off the async runtime
+        let checked: Vec<Flow> = flows.iter().map(|(_, flow)| flow.clone()).collect();
+        let offline = state.offline;
+        let failures = tokio::task::spawn_blocking(move || {
+            checked.iter()
+                .map(|flow| preflight::failures(&preflight::check(flow, std::path::Path::new("."), &RunVars::new(), offline)))
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_else(|e| vec![vec![format!("preflight aborted: {}", e)]; flows.len()]);
+        for ((name, _), problems) in flows.iter().zip(failures) {
+            if !problems.is_empty() {
+                error!("Preflight failed for {}: {}", name, problems.join("; "));
+                return Json(serde_json::json!({
+                    "error": format!("Preflight failed for {}", name),
+                    "missing": problems,
+                }))
+                .into_response();
+            }
+        }
+    }
+
+    // An authenticated key is the author; otherwise trust the caller
+    let author = principal
+        .map(|Extension(Principal(name))| name)
+        .or(params.author)
+        .unwrap_or_else(|| "api".to_string());
+    let message = params.message.unwrap_or_else(|| "Deployed via API".to_string());
+
+    let mut root_source = String::new();
+    for (name, flow) in flows {
+        match save_deployment(&state, &name, flow, &author, &message) {
+            Ok(source) if name == flow_name => root_source = source,
+            Ok(_) => {}
+            Err(e) => {
+                error!("{}", e);
+                return Json(serde_json::json!({"error": e})).into_response();
+            }
+        }
+    }
+
+    Json(serde_json::json!({
+        "status": "success",
+        "message": "Flow compiled and deployed",
+        "source": root_source
+    }))
+    .into_response()
+}
+
+/// Record the change and write a flow's definition and compiled source,
+/// returning the source
+fn save_deployment(state: &AppState, flow_name: &str, mut flow: Flow, author: &str, message: &str) -> Result<String, String> {
+    // Keep the deployed history when the client sends a flow without one
+    if flow.changelog.is_empty() {
+        if let Some(existing) = load_flow_def(&state.flows_dir, flow_name) {
+            flow.changelog = existing.changelog;
+        }
+    }
+    flow.log_change(author, message);
+    state.compiled.invalidate(flow_name);
+
+    let source = flow.compile_with(&CompileOptions { flow_name: Some(flow_name.to_string()), ..Default::default() });
     let flow_path = state.flows_dir.join(format!("{}.hlxa", flow_name));
 
-    match std::fs::write(&flow_path, &source) {
-        Ok(_) => {
-            info!("Flow saved to {}", flow_path.display());
-            Json(serde_json::json!({
-                "status": "success",
-                "message": "Flow compiled and deployed",
-                "source": source
-            }))
-        },
-        Err(e) => {
-            error!("Failed to save flow: {}", e);
-            Json(serde_json::json!({"error": format!("Failed to save flow: {}", e)}))
+    // Keep the flow definition next to the source so runs can capture per-node outputs
+    let def_path = state.flows_dir.join(format!("{}.json", flow_name));
+    match serde_json::to_string_pretty(&flow) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&def_path, json) {
+                error!("Failed to save flow definition: {}", e);
+            }
         }
+        Err(e) => error!("Failed to serialize flow definition: {}", e),
     }
+
+    std::fs::write(&flow_path, &source).map_err(|e| format!("Failed to save flow: {}", e))?;
+    info!("Flow saved to {}", flow_path.display());
+    Ok(source)
 }
 
+/// Delete a deployed flow. If anything depends on it, nothing is deleted
+/// and the impact is returned until the request is repeated with `?confirm=true`.
+async fn undeploy_flow(
+    Path(flow_name): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DeleteParams>,
+) -> Response {
+    if let Some(rejection) = reject_flow_name(&flow_name) {
+        return rejection;
+    }
+    let def_path = state.flows_dir.join(format!("{}.json", flow_name));
+    let source_path = state.flows_dir.join(format!("{}.hlxa", flow_name));
+    if !def_path.exists() && !source_path.exists() {
+        return Json(serde_json::json!({"error": "Flow not found"})).into_response();
+    }
+
+    let retries = match state.retries.list() {
+        Ok(retries) => retries,
+        Err(e) => {
+            error!("Failed to read retry queue: {}", e);
+            return Json(serde_json::json!({"error": format!("Failed to read retry queue: {}", e)})).into_response();
+        }
+    };
+    let impact = impact::flow_impact(&state.flows_dir, &flow_name, &retries, &state.runs.list_all());
+    if !impact.is_empty() && !params.confirm {
+        return Json(serde_json::json!({
+            "status": "confirmation_required",
+            "impact": impact,
+            "message": "Repeat with ?confirm=true to delete anyway",
+        }))
+        .into_response();
+    }
+
+    for path in [&def_path, &source_path] {
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(path) {
+                error!("Failed to delete {}: {}", path.display(), e);
+                return Json(serde_json::json!({"error": format!("Failed to delete flow: {}", e)})).into_response();
+            }
+        }
+    }
+    state.compiled.invalidate(&flow_name);
+    if let Err(e) = state.retries.remove_flow(&flow_name) {
+        error!("Failed to drop retries of {}: {}", flow_name, e);
+    }
+
+    warn!("Deleted flow {} ({} dependent(s))", flow_name, impact.len());
+    Json(serde_json::json!({"status": "deleted", "flow": flow_name, "impact": impact})).into_response()
+}
 
 async fn run_flow(
     Path(flow_name): Path<String>,
     State(state): State<Arc<AppState>>,
+    Query(params): Query<RunParams>,
     Json(payload): Json<JsonValue>,
-) -> Json<JsonValue> {
-    info!("Running flow: {}", flow_name);
+) -> Response {
+    if let Some(rejection) = reject_flow_name(&flow_name) {
+        return rejection;
+    }
+    let labels = match runs::parse_labels(params.labels.as_deref().unwrap_or("")) {
+        Ok(labels) => labels,
+        Err(e) => return Json(serde_json::json!({"error": e})).into_response(),
+    };
+
+    // Reject input the flow doesn't declare before it takes a run slot
+    if let Some(schema) = load_flow_def(&state.flows_dir, &flow_name).and_then(|f| f.input_schema) {
+        let errors = input_schema::validate(&schema, &payload);
+        if !errors.is_empty() {
+            warn!("Rejected input for {}: {}", flow_name, input_schema::describe(&errors).join("; "));
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "Input does not match the flow's input schema", "fields": errors})),
+            )
+                .into_response();
+        }
+    }
+
+    let _permit = match state.limiter.acquire(&flow_name, params.wait).await {
+        Ok(permit) => permit,
+        Err(saturated) => {
+            warn!("Refused run of {}: {} running, {} queued", flow_name, saturated.running, saturated.queued);
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, "1")],
+                Json(serde_json::json!({
+                    "error": "Too many concurrent runs",
+                    "running": saturated.running,
+                    "queue_position": saturated.queued + 1,
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let offline = state.offline || params.offline;
+    let run_state = state.clone();
+    let (run_id, outcome) = tokio::task::spawn_blocking(move || {
+        execute_run(&run_state, &flow_name, payload, None, offline, params.chaos, labels)
+    })
+    .await
+    .unwrap_or_else(|e| (None, Err(format!("Run aborted: {}", e))));
+    let mut response = match outcome {
+        Ok(j) => Json(j).into_response(),
+        Err(e) => Json(serde_json::json!({"error": e, "run_id": run_id})).into_response(),
+    };
+    // The result body is the flow's own output, so the run ID goes in a header
+    if let Some(value) = run_id.and_then(|id| header::HeaderValue::from_str(&id).ok()) {
+        response.headers_mut().insert("x-run-id", value);
+    }
+    response
+}
+
+/// Execute a deployed flow and record the run in the history store.
+/// Returns the run's ID, unless the flow didn't exist, with its outcome.
+fn execute_run(
+    state: &AppState,
+    flow_name: &str,
+    payload: JsonValue,
+    retry_of: Option<String>,
+    offline: bool,
+    chaos: bool,
+    labels: BTreeMap<String, String>,
+) -> (Option<String>, Result<JsonValue, String>) {
+    let mut record = RunRecord::new(flow_name, runs::now_ms(), payload);
+    match retry_of.as_deref().and_then(|id| state.runs.load(id)) {
+        Some(previous) => record.continue_chain(&previous),
+        None => record.retry_of = retry_of,
+    }
+    record.chaos = chaos;
+    record.labels.extend(labels);
+    execute_record(state, record, offline)
+}
+
+/// Execute the run of a new record, or of one a trigger queued.
+///
+/// The run is persisted as `running` before execution starts, so a run cut
+/// short by a crash can be detected and recovered on the next startup.
+fn execute_record(state: &AppState, mut record: RunRecord, offline: bool) -> (Option<String>, Result<JsonValue, String>) {
+    let _job = state.metrics.job_started();
+    let flow_name = record.flow_name.clone();
+    let flow_name = flow_name.as_str();
+    let payload = record.input.clone();
 
     let flow_path = state.flows_dir.join(format!("{}.hlxa", flow_name));
     if !flow_path.exists() {
         error!("Flow not found: {}", flow_path.display());
-        return Json(serde_json::json!({"error": "Flow not found"}));
+        // A queued run whose flow was undeployed meanwhile never starts
+        if record.status == RunStatus::Queued {
+            record.finish(0, Err("Flow not found".to_string()));
+            if let Err(e) = state.runs.update(&record) {
+                error!("Failed to save run history: {}", e);
+            }
+        }
+        return (None, Err("Flow not found".to_string()));
     }
 
-    let source = match std::fs::read_to_string(&flow_path) {
-        Ok(s) => s,
-        Err(e) => {
-            error!("Failed to read flow: {}", e);
-            return Json(serde_json::json!({"error": format!("Failed to read flow: {}", e)}));
+    // Every server log event of the run carries its ID
+    let span = tracing::info_span!("run", run_id = %record.id, flow = %flow_name);
+    let _span = span.enter();
+    info!("Running flow: {}", flow_name);
+    let saved = match record.status {
+        RunStatus::Queued => {
+            record.status = RunStatus::Running;
+            state.runs.update(&record)
+        }
+        _ => state.runs.save(&mut record),
+    };
+    if let Err(e) = saved {
+        error!("Failed to save run history: {}", e);
+    }
+
+    // Prefer the deployed flow definition so per-node outputs can be captured.
+    // Both come from the last deploy, never from the editor's saved flows.
+    let stamp = compile_cache::stamp(&state.flows_dir, flow_name);
+    let flow_def = load_flow_def(&state.flows_dir, flow_name);
+    if let Some(flow) = &flow_def {
+        record.set_flow(flow);
+    }
+
+    // Compile and run, converting the result back to JSON
+    let start = std::time::Instant::now();
+    gpu_stats::begin_run();
+    let to_json = |result: anyhow::Result<Value>| match result {
+        Ok(result) => result.to_json().map_err(|e| format!("Serialization failed: {}", e)),
+        Err(e) => Err(format!("Execution failed: {}", e)),
+    };
+    let run = |source: &str, input: &JsonValue, backend: Option<BackendType>, vars: &RunVars| {
+        to_json(compile_and_run_on(source, input.clone(), backend, vars))
+    };
+    let prepared = check_offline(offline, flow_def.as_ref(), &mut record)
+        .and_then(|()| prepare_source(state, flow_def.as_ref(), &flow_path, &mut record))
+        .and_then(|(source, options)| {
+            if offline && flow_def.is_none() {
+                offline::check_source(&source)?;
+            }
+            // The state store is resolved per run, never compiled into the source
+            let vars = state_store::prepare(&source, flow_name, &options)?;
+            Ok((source, options, vars))
+        });
+    // The flow as deployed is compiled once; reruns of it skip parsing and lowering
+    let mut outcome = prepared.clone().and_then(|(source, _, vars)| {
+        to_json(
+            state.compiled
+                .get_or_compile(flow_name, stamp, &source, || compile_source(&source))
+                .and_then(|compiled| compiled(payload.clone(), None, &vars)),
+        )
+    });
+
+    // Pin the outputs of tensor nodes the GPU couldn't run and retry
+    if let (Err(_), Some(flow), Ok((_, options, vars))) = (&outcome, &flow_def, &prepared) {
+        if flow.cpu_fallback {
+            let fallback = fallback::resolve_on_cpu(flow, options, &payload, |source, input, on_cpu| {
+                run(source, input, on_cpu.then_some(BackendType::Cpu), vars)
+            });
+            if let Some(fallback) = fallback {
+                for line in fallback.log {
+                    record.log(LogLevel::Warn, None, line);
+                }
+                let options = CompileOptions { fixed_outputs: fallback.fixed_outputs, ..options.clone() };
+                outcome = run(&flow.compile_with(&options), &payload, None, vars);
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    record.gpu_memory = gpu_stats::end_run();
+    if let Some(stats) = &record.gpu_memory {
+        let summary = format!("GPU memory: {}", stats.summary());
+        record.log(LogLevel::Info, None, summary);
+    }
+    let duration_ms = elapsed.as_millis() as u64;
+    state.metrics.record_run(flow_name, outcome.is_ok(), elapsed);
+
+    let outcome = outcome.map(|json| {
+        if flow_def.is_none() {
+            return json;
+        }
+        if let Some(outputs) = json["outputs"].as_object() {
+            record.node_outputs = outputs.clone().into_iter().collect();
+        }
+        // The compiled flow reports its node outputs only once it has run,
+        // so these events carry the run's end time (see `LogEvent`)
+        for (node_id, output) in record.node_outputs.clone() {
+            record.log(LogLevel::Info, Some(&node_id), format!("Produced {}", describe_output(&output)));
         }
-    } ;
+        json["result"].clone()
+    });
 
-    // Compile and run
-    let result = match compile_and_run(&source, payload) {
-        Ok(res) => res,
+    match &outcome {
+        Ok(_) => {
+            info!("Completed in {}ms", duration_ms);
+            record.log(LogLevel::Info, None, format!("Completed in {}ms", duration_ms));
+        }
         Err(e) => {
             error!("Flow execution failed: {}", e);
-            return Json(serde_json::json!({"error": format!("Execution failed: {}", e)}));
+            record.log(LogLevel::Error, None, e.clone());
+            if let Some(policy) = flow_def.as_ref().and_then(|f| f.retry.as_ref()) {
+                schedule_retry(state, &mut record, policy, e);
+            }
         }
+    }
+
+    record.finish(duration_ms, outcome.clone());
+    if let Err(e) = state.runs.update(&record) {
+        error!("Failed to save run history: {}", e);
+    }
+
+    (Some(record.id), outcome)
+}
+
+/// Short description of a node's output for the run log, e.g. `array of 3 items`
+fn describe_output(output: &JsonValue) -> String {
+    match output {
+        JsonValue::Null => "null".to_string(),
+        JsonValue::Bool(b) => format!("boolean {}", b),
+        JsonValue::Number(n) => format!("number {}", n),
+        JsonValue::String(s) => format!("string of {} chars", s.chars().count()),
+        JsonValue::Array(items) => format!("array of {} items", items.len()),
+        JsonValue::Object(fields) => format!("object with {} fields", fields.len()),
+    }
+}
+
+/// Queue a re-run of a failed run if its flow's retry policy allows one
+fn schedule_retry(state: &AppState, record: &mut RunRecord, policy: &flow::RetryPolicy, error: &str) {
+    if !policy.should_retry(record.attempt, error) {
+        if record.attempt > 1 {
+            let message = format!("Giving up after {} attempts", record.attempt);
+            record.log(LogLevel::Error, None, message);
+        }
+        return;
+    }
+
+    let due_at = runs::now_ms() + policy.delay_secs * 1000;
+    let retry = ScheduledRetry { run_id: record.id.clone(), flow_name: record.flow_name.clone(), due_at };
+    match state.retries.push(retry) {
+        Ok(()) => {
+            record.retry_at = Some(due_at);
+            let message = format!(
+                "Retry {} of {} scheduled in {}s",
+                record.attempt, policy.max_retries, policy.delay_secs
+            );
+            record.log(LogLevel::Warn, None, message);
+        }
+        Err(e) => error!("Failed to schedule retry of {}: {}", record.id, e),
+    }
+}
+
+/// How often the server looks for flows edited on disk
+const FLOW_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Drop the compiled copies of flows whose files were edited or removed
+/// outside the API, so the next run picks up the change without a restart
+async fn watch_flows(state: Arc<AppState>) {
+    let mut known = compile_cache::fingerprint(&state.flows_dir);
+    let mut interval = tokio::time::interval(FLOW_WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let current = compile_cache::fingerprint(&state.flows_dir);
+        for (name, stamp) in &known {
+            if current.get(name) != Some(stamp) && state.compiled.invalidate(name) {
+                info!("Flow {} changed on disk; recompiling on its next run", name);
+            }
+        }
+        known = current;
+    }
+}
+
+/// A deployed flow's message source that starts runs while the server
+/// listens to it
+trait Listener: Clone + Ord + Send + 'static {
+    fn flow_name(&self) -> &str;
+
+    /// The run's `trigger` label, e.g. `nats:orders.created`
+    fn label(&self) -> String;
+
+    /// What is listened to, for the server log
+    fn describe(&self) -> String;
+
+    /// Listen on a background thread until `stop` is set, handing
+    /// `deliver` each message's run input or the error of a failed attempt
+    fn listen(&self, stop: Arc<std::sync::atomic::AtomicBool>, deliver: impl Fn(Result<JsonValue, String>) + Send + 'static);
+}
+
+impl Listener for nats::Trigger {
+    fn flow_name(&self) -> &str {
+        &self.flow_name
+    }
+
+    fn label(&self) -> String {
+        nats::Trigger::label(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("{} for '{}' ({})", self.subject, self.flow_name, self.node_id)
+    }
+
+    fn listen(&self, stop: Arc<std::sync::atomic::AtomicBool>, deliver: impl Fn(Result<JsonValue, String>) + Send + 'static) {
+        nats::spawn_subscriber(self.clone(), stop, deliver);
+    }
+}
+
+impl Listener for email::Trigger {
+    fn flow_name(&self) -> &str {
+        &self.flow_name
+    }
+
+    fn label(&self) -> String {
+        email::Trigger::label(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("{} every {}s for '{}' ({})", self.folder, self.poll_secs, self.flow_name, self.node_id)
+    }
+
+    fn listen(&self, stop: Arc<std::sync::atomic::AtomicBool>, deliver: impl Fn(Result<JsonValue, String>) + Send + 'static) {
+        let poll = |flow: &Flow| {
+            compile_and_run(&flow.compile_to_hlx(), JsonValue::Null, &RunVars::new())
+                .map_err(|e| e.to_string())
+                .and_then(|result| result.to_json().map_err(|e| format!("Serialization failed: {}", e)))
+        };
+        email::spawn_poller(self.clone(), stop, poll, deliver);
+    }
+}
+
+/// Keep a listener running for every trigger `triggers` finds in the
+/// deployed flows, following deploys and undeploys, and start a run per
+/// message. Each message is saved as a queued run as soon as it arrives, so
+/// one a restart cuts off still runs on the next start.
+///
+/// A listener's messages wait in a channel as long as the flow's run queue;
+/// while it is full the listener blocks, so a busy flow stops taking
+/// messages instead of piling them up in memory.
+async fn supervise_triggers<T: Listener>(state: Arc<AppState>, triggers: fn(&str, &Flow) -> Vec<T>) {
+    let capacity = state.limiter.limits().max_queued.max(1);
+    let mut listening: BTreeMap<T, Arc<std::sync::atomic::AtomicBool>> = BTreeMap::new();
+    let mut interval = tokio::time::interval(FLOW_WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let mut names: Vec<String> = compile_cache::fingerprint(&state.flows_dir).into_keys().collect();
+        names.sort();
+        let wanted: Vec<T> = names.iter()
+            .filter_map(|name| Some(triggers(name, &load_flow_def(&state.flows_dir, name)?)))
+            .flatten()
+            .collect();
+
+        listening.retain(|trigger, stop| {
+            let keep = wanted.contains(trigger);
+            if !keep {
+                info!("Stopped listening to {}", trigger.describe());
+                stop.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+            keep
+        });
+        for trigger in wanted {
+            if listening.contains_key(&trigger) {
+                continue;
+            }
+            if state.offline {
+                warn!("Offline mode: not listening to {}", trigger.describe());
+                continue;
+            }
+            info!("Listening to {}", trigger.describe());
+            let stop = Arc::new(std::sync::atomic::AtomicBool::new(false));
+            let (sender, queued) = tokio::sync::mpsc::channel::<String>(capacity);
+            tokio::spawn(dispatch_queued_runs(state.clone(), queued));
+            let (run_state, delivered) = (state.clone(), trigger.clone());
+            trigger.listen(stop.clone(), move |message| match message {
+                Ok(input) => match queue_run(&run_state, delivered.flow_name(), delivered.label(), input) {
+                    Ok(run_id) => {
+                        // Blocks the listener's thread while the channel is full
+                        let _ = sender.blocking_send(run_id);
+                    }
+                    Err(e) => error!("Failed to queue a run of '{}': {}", delivered.flow_name(), e),
+                },
+                Err(e) => warn!("Listening to {} failed: {}", delivered.describe(), e),
+            });
+            listening.insert(trigger, stop);
+        }
+    }
+}
+
+/// Start a listener's queued runs one by one as they get run slots, until
+/// the listener stops
+async fn dispatch_queued_runs(state: Arc<AppState>, mut queued: tokio::sync::mpsc::Receiver<String>) {
+    while let Some(run_id) = queued.recv().await {
+        let Some(record) = state.runs.load(&run_id) else {
+            warn!("Dropping unknown queued run {}", run_id);
+            continue;
+        };
+        // The next message is taken only once this run has its slot
+        let permit = admit(&state, &record.flow_name).await;
+        let run_state = state.clone();
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            execute_record(&run_state, record, run_state.offline)
+        });
+    }
+}
+
+/// Save a trigger's message as a queued run of `flow_name`, labelled
+/// `trigger=<label>`, returning the run's ID
+fn queue_run(state: &AppState, flow_name: &str, label: String, input: JsonValue) -> std::io::Result<String> {
+    let mut record = RunRecord::new(flow_name, runs::now_ms(), input);
+    record.status = RunStatus::Queued;
+    record.labels.insert("trigger".to_string(), label);
+    state.runs.save(&mut record)?;
+    Ok(record.id)
+}
+
+/// Take a run slot for a run of `flow_name` the server starts itself, for a
+/// trigger or after a restart. With the flow's queue full, the run waits
+/// outside it instead of being dropped.
+async fn admit(state: &Arc<AppState>, flow_name: &str) -> concurrency::RunPermit {
+    loop {
+        match state.limiter.acquire(flow_name, true).await {
+            Ok(permit) => return permit,
+            Err(_) => tokio::time::sleep(std::time::Duration::from_secs(1)).await,
+        }
+    }
+}
+
+/// Execute a queued run once it gets a run slot
+async fn start_queued_run(state: Arc<AppState>, record: RunRecord) {
+    let _permit = admit(&state, &record.flow_name).await;
+    let run_state = state.clone();
+    let _ = tokio::task::spawn_blocking(move || execute_record(&run_state, record, run_state.offline)).await;
+}
+
+/// How often the scheduler looks for due retries
+const RETRY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Re-run failed runs as their scheduled retries come due
+async fn run_retry_scheduler(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(RETRY_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        // A due retry becomes a queued run before it leaves the retry queue,
+        // so a restart in between starts it from the run history instead
+        let mut queued = Vec::new();
+        let mut continued: Option<std::collections::HashSet<String>> = None;
+        let taken = state.retries.take_due(runs::now_ms(), |retry| {
+            let Some(failed) = state.runs.load(&retry.run_id) else {
+                warn!("Dropping retry of unknown run {}", retry.run_id);
+                return true;
+            };
+            // Already queued on an earlier tick whose queue update failed
+            let continued = continued.get_or_insert_with(|| {
+                state.runs.list_all().into_iter().filter_map(|r| r.retry_of).collect()
+            });
+            if continued.contains(&retry.run_id) {
+                return true;
+            }
+            let mut record = RunRecord::new(&retry.flow_name, runs::now_ms(), failed.input.clone());
+            record.continue_chain(&failed);
+            record.chaos = failed.chaos;
+            record.status = RunStatus::Queued;
+            match state.runs.save(&mut record) {
+                Ok(()) => {
+                    queued.push(record);
+                    true
+                }
+                Err(e) => {
+                    error!("Failed to queue retry of {}: {}", retry.run_id, e);
+                    false
+                }
+            }
+        });
+        if let Err(e) = taken {
+            error!("Failed to update retry queue: {}", e);
+        }
+
+        // Retries take run slots like any other run, waiting outside a full queue
+        for record in queued {
+            info!("Retrying run {} of '{}' (attempt {})", record.retry_of.as_deref().unwrap_or("?"), record.flow_name, record.attempt);
+            tokio::spawn(start_queued_run(state.clone(), record));
+        }
+    }
+}
+
+/// In offline mode, fail the run if any node would reach the network,
+/// logging an error for each such node
+fn check_offline(offline: bool, flow_def: Option<&Flow>, record: &mut RunRecord) -> Result<(), String> {
+    let Some(flow) = flow_def.filter(|_| offline) else {
+        return Ok(());
+    };
+
+    let blocked = offline::blocked_nodes(flow, &Default::default());
+    if blocked.is_empty() {
+        return Ok(());
+    }
+    for (node_id, error) in &blocked {
+        record.log(LogLevel::Error, Some(node_id), error.clone());
+    }
+    let ids: Vec<&str> = blocked.iter().map(|(id, _)| id.as_str()).collect();
+    Err(format!("Offline mode blocked {} node(s): {}", blocked.len(), ids.join(", ")))
+}
+
+/// Produce the HLX source for a run, along with the options it was compiled with.
+///
+/// With a flow definition the run is compiled with output capture inside its
+/// own working directory; otherwise the deployed source is used as-is.
+fn prepare_source(
+    state: &AppState,
+    flow_def: Option<&Flow>,
+    flow_path: &std::path::Path,
+    record: &mut RunRecord,
+) -> Result<(String, CompileOptions), String> {
+    match flow_def {
+        Some(flow) => {
+            let mut options = CompileOptions {
+                capture_outputs: true,
+                flow_name: Some(record.flow_name.clone()),
+                ..Default::default()
+            };
+
+            // Give the run its own working directory
+            let env = RunEnvironment::create(state.runs.dir(), &record.id, flow, std::path::Path::new("."))
+                .map_err(|e| format!("Failed to create run environment: {}", e))?;
+            record.log(LogLevel::Info, None, format!("Working directory: {}", env.work_dir.display()));
+            record.work_dir = Some(env.work_dir.to_string_lossy().into_owned());
+            env.apply(&mut options);
+
+            if record.chaos {
+                options.faults = chaos::plan(&flow.chaos, flow.seed, record.attempt);
+                for fault in chaos::describe(&options.faults) {
+                    record.log(LogLevel::Warn, None, fault);
+                }
+            }
+
+            record.log(LogLevel::Info, None, "Compiled from flow definition with output capture");
+            Ok((flow.compile_with(&options), options))
+        }
+        None => {
+            let source = std::fs::read_to_string(flow_path)
+                .map_err(|e| format!("Failed to read flow: {}", e))?;
+            record.log(LogLevel::Info, None, format!("Loaded source from {}", flow_path.display()));
+            Ok((source, CompileOptions::default()))
+        }
+    }
+}
+
+/// Start the runs triggers queued before the last stop, and mark runs left
+/// `running` by a previous process as interrupted, re-running them when
+/// their flow's policy asks for it
+fn recover_interrupted_runs(state: &Arc<AppState>) {
+    // Messages triggers accepted and retries that came due, but never got to run
+    for record in state.runs.list_all().into_iter().filter(|r| r.status == RunStatus::Queued) {
+        info!("Starting queued run {} of '{}'", record.id, record.flow_name);
+        tokio::spawn(start_queued_run(state.clone(), record));
+    }
+
+    let interrupted: Vec<RunRecord> = state.runs
+        .list_all()
+        .into_iter()
+        .filter(|r| r.status == RunStatus::Running)
+        .collect();
+
+    for mut record in interrupted {
+        warn!("Run {} of '{}' was interrupted", record.id, record.flow_name);
+        record.status = RunStatus::Interrupted;
+        record.error = Some("Server stopped before the run finished".to_string());
+        record.log(LogLevel::Error, None, "Marked as interrupted on server startup");
+        if let Err(e) = state.runs.update(&record) {
+            error!("Failed to update run {}: {}", record.id, e);
+            continue;
+        }
+
+        let policy = load_flow_def(&state.flows_dir, &record.flow_name)
+            .map(|flow| flow.on_interrupt)
+            .unwrap_or_default();
+
+        if policy == InterruptPolicy::Rerun {
+            info!("Re-running interrupted run {} per flow policy", record.id);
+            let state = state.clone();
+            // Re-runs take run slots like any other run
+            tokio::spawn(async move {
+                let _permit = admit(&state, &record.flow_name).await;
+                let run_state = state.clone();
+                let _ = tokio::task::spawn_blocking(move || {
+                    execute_run(&run_state, &record.flow_name, record.input, Some(record.id), run_state.offline, record.chaos, BTreeMap::new())
+                })
+                .await;
+            });
+        }
+    }
+}
+
+async fn get_flow(
+    Path(flow_name): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    if let Some(rejection) = reject_flow_name(&flow_name) {
+        return rejection;
+    }
+    let body = match load_flow_def(&state.flows_dir, &flow_name) {
+        Some(flow) => serde_json::json!({
+            "name": flow_name,
+            "nodes": flow.nodes.len(),
+            "edges": flow.edges.len(),
+            "changelog": flow.changelog,
+            "flow": flow,
+        }),
+        None if state.flows_dir.join(format!("{}.hlxa", flow_name)).exists() => {
+            serde_json::json!({
+                "name": flow_name,
+                "changelog": [],
+                "message": "Flow was deployed as source only; no definition available",
+            })
+        }
+        None => serde_json::json!({"error": "Flow not found"}),
+    };
+    Json(body).into_response()
+}
+
+/// Graph metrics of a deployed flow, with complexity warnings
+async fn flow_stats(
+    Path(flow_name): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    if let Some(rejection) = reject_flow_name(&flow_name) {
+        return rejection;
+    }
+    let body = match load_flow_def(&state.flows_dir, &flow_name) {
+        Some(flow) => {
+            let metrics = complexity::GraphMetrics::of(&flow);
+            serde_json::json!({
+                "name": flow_name,
+                "metrics": metrics,
+                "warnings": metrics.warnings(),
+            })
+        }
+        None => serde_json::json!({"error": "Flow definition not found"}),
+    };
+    Json(body).into_response()
+}
+
+async fn list_runs(
+    Path(flow_name): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RunsQuery>,
+) -> Response {
+    if let Some(rejection) = reject_flow_name(&flow_name) {
+        return rejection;
+    }
+    let filter = match runs::parse_labels(query.labels.as_deref().unwrap_or("")) {
+        Ok(filter) => filter,
+        Err(e) => return Json(serde_json::json!({"error": e})).into_response(),
+    };
+    let runs: Vec<JsonValue> = state.runs
+        .list_for_flow(&flow_name)
+        .iter()
+        .filter(|r| r.has_labels(&filter))
+        .map(RunRecord::summary)
+        .collect();
+
+    Json(serde_json::json!({"flow": flow_name, "runs": runs})).into_response()
+}
+
+/// Runs of every flow, newest first, e.g. all runs for one customer
+async fn search_runs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RunsQuery>,
+) -> Json<JsonValue> {
+    let filter = match runs::parse_labels(query.labels.as_deref().unwrap_or("")) {
+        Ok(filter) => filter,
+        Err(e) => return Json(serde_json::json!({"error": e})),
     };
+    let mut matching: Vec<RunRecord> = state.runs
+        .list_all()
+        .into_iter()
+        .filter(|r| r.has_labels(&filter))
+        .collect();
+    matching.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+
+    let runs: Vec<JsonValue> = matching.iter().map(RunRecord::summary).collect();
+    Json(serde_json::json!({"runs": runs}))
+}
+
+async fn get_run(
+    Path(run_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Json<JsonValue> {
+    match state.runs.load(&run_id) {
+        Some(record) => {
+            let chain = state.runs.list_chain(record.chain_id());
+            let mut json = serde_json::to_value(&record).unwrap_or_default();
+            json["logs"] = record.log_lines().into();
+            // Every attempt of a retried run, so the chain reads as one logical run
+            if chain.len() > 1 {
+                json["attempts"] = chain.iter().map(RunRecord::summary).collect();
+            }
+            Json(json)
+        }
+        None => Json(serde_json::json!({"error": "Run not found"})),
+    }
+}
+
+/// The structured log of a run
+async fn get_run_logs(
+    Path(run_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Json<JsonValue> {
+    match state.runs.load(&run_id) {
+        Some(record) => Json(serde_json::json!({
+            "run_id": record.id,
+            "flow_name": record.flow_name,
+            "status": record.status,
+            "events": record.structured_log(),
+        })),
+        None => Json(serde_json::json!({"error": "Run not found"})),
+    }
+}
 
-    // Convert result back to JSON
-    match result.to_json() {
-        Ok(j) => Json(j),
-        Err(e) => Json(serde_json::json!({"error": format!("Serialization failed: {}", e)})),
+/// A run's shareable report, as JSON or a self-contained HTML page
+async fn get_run_report(
+    Path(run_id): Path<String>,
+    Query(params): Query<ReportParams>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let format = match params.format.as_deref().map(report::ReportFormat::parse) {
+        None => report::ReportFormat::Json,
+        Some(Some(format)) => format,
+        Some(None) => return Json(serde_json::json!({"error": "Unknown report format; use json or html"})).into_response(),
+    };
+    match state.runs.load(&run_id) {
+        Some(record) => (
+            [(header::CONTENT_TYPE, format.content_type())],
+            report::render(&record, format),
+        ).into_response(),
+        None => Json(serde_json::json!({"error": "Run not found"})).into_response(),
     }
 }
 
-fn compile_and_run(source: &str, input_json: JsonValue) -> anyhow::Result<Value> {
-    // Parse
-    let parser = HlxaParser::new();
-    let ast = parser.parse(source).map_err(|e| anyhow::anyhow!("Parse error: {:?}", e))?;
+async fn metrics(State(state): State<Arc<AppState>>) -> ([(header::HeaderName, &'static str); 1], String) {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
+/// Liveness probe: the process is up and serving requests
+async fn health(State(state): State<Arc<AppState>>) -> Json<JsonValue> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+        "uptime_secs": state.started.elapsed().as_secs(),
+        "active_jobs": state.metrics.active_jobs(),
+        "pending_retries": state.retries.len().ok(),
+        "running": state.limiter.load().0,
+        "queued": state.limiter.load().1,
+        "compile_cache": {"hits": state.compiled.stats().0, "misses": state.compiled.stats().1},
+    }))
+}
+
+/// Readiness probe: flows can be deployed and run
+async fn ready(State(state): State<Arc<AppState>>) -> (StatusCode, Json<JsonValue>) {
+    let flows_dir = check_dir_writable(&state.flows_dir);
+    let runs_dir = check_dir_writable(state.runs.dir());
+    let any_backend = state.backends.iter().any(|b| b.available);
+    let is_ready = flows_dir.is_ok() && runs_dir.is_ok() && any_backend;
+
+    let check = |result: &Result<(), String>| match result {
+        Ok(()) => serde_json::json!({"ok": true}),
+        Err(e) => serde_json::json!({"ok": false, "error": e}),
+    };
+
+    let body = serde_json::json!({
+        "status": if is_ready { "ready" } else { "not_ready" },
+        "checks": {
+            "flows_dir": check(&flows_dir),
+            "runs_dir": check(&runs_dir),
+        },
+        "backends": state.backends,
+    });
 
-    // Lower
-    let krate = lower::lower_to_crate(&ast).map_err(|e| anyhow::anyhow!("Lowering error: {:?}", e))?;
+    let status = if is_ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(body))
+}
 
-    // Setup config with main input
-    let mut config = RuntimeConfig::default();
-    let hlx_input = Value::from_json(input_json).map_err(|e| anyhow::anyhow!("Input conversion error: {:?}", e))?;
-    
-    // We pass the input as a string to main(input) for now, or we could modify the runtime to take a Value
-    // The current runtime.main_input is a Option<String>
-    config.main_input = Some(serde_json::to_string(&hlx_input.to_json()?)?);
+/// Verify a directory exists and accepts writes
+fn check_dir_writable(dir: &std::path::Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let probe = dir.join(".ready_probe");
+    std::fs::write(&probe, b"").map_err(|e| format!("{} is not writable: {}", dir.display(), e))?;
+    std::fs::remove_file(&probe).ok();
+    Ok(())
+}
 
-    // Execute
-    let result = execute_with_config(&krate, &config).map_err(|e| anyhow::anyhow!("Runtime error: {:?}", e))?;
+/// Detect usable runtime backends by executing a trivial program on each
+fn probe_backends() -> Vec<BackendStatus> {
+    let probe = |name: &'static str, backend: BackendType| {
+        let result = (|| -> anyhow::Result<()> {
+            let ast = HlxaParser::new()
+                .parse("program probe {\n\nfn main(input) {\n    return 1;\n}\n\n}\n")
+                .map_err(|e| anyhow::anyhow!("Parse error: {:?}", e))?;
+            let krate = lower::lower_to_crate(&ast).map_err(|e| anyhow::anyhow!("Lowering error: {:?}", e))?;
+            let mut config = RuntimeConfig::default();
+            config.backend = backend;
+            execute_with_config(&krate, &config).map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            Ok(())
+        })();
 
-    Ok(result)
-}
\ No newline at end of file
+        BackendStatus {
+            name,
+            available: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    };
+
+    vec![
+        probe("cpu", BackendType::Cpu),
+        probe("vulkan", BackendType::Vulkan),
+    ]
+}
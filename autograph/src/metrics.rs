@@ -0,0 +1,149 @@
+//! Prometheus Metrics
+//!
+//! Minimal in-process registry for the REST server, rendered in the
+//! Prometheus text exposition format at `GET /metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the run duration histogram buckets
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Cumulative histogram of run durations for one flow
+struct Histogram {
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: vec![0; DURATION_BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if value <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Server-wide metrics registry
+#[derive(Default)]
+pub struct Metrics {
+    /// Runs per (flow, status)
+    runs: Mutex<BTreeMap<(String, &'static str), u64>>,
+
+    /// Failed runs per flow
+    errors: Mutex<BTreeMap<String, u64>>,
+
+    /// Run duration per flow
+    durations: Mutex<BTreeMap<String, Histogram>>,
+
+    /// Runs currently executing
+    active_jobs: AtomicI64,
+}
+
+/// Marks a job as active until dropped
+pub struct ActiveJob<'a> {
+    metrics: &'a Metrics,
+}
+
+impl Drop for ActiveJob<'_> {
+    fn drop(&mut self) {
+        self.metrics.active_jobs.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Metrics {
+    /// Count a job as active for the lifetime of the returned guard
+    pub fn job_started(&self) -> ActiveJob<'_> {
+        self.active_jobs.fetch_add(1, Ordering::Relaxed);
+        ActiveJob { metrics: self }
+    }
+
+    /// Number of jobs currently executing
+    pub fn active_jobs(&self) -> i64 {
+        self.active_jobs.load(Ordering::Relaxed)
+    }
+
+    /// Record a finished run
+    pub fn record_run(&self, flow: &str, success: bool, duration: Duration) {
+        let status = if success { "success" } else { "error" };
+        *self.runs.lock().unwrap().entry((flow.to_string(), status)).or_insert(0) += 1;
+
+        if !success {
+            *self.errors.lock().unwrap().entry(flow.to_string()).or_insert(0) += 1;
+        }
+
+        self.durations.lock().unwrap()
+            .entry(flow.to_string())
+            .or_insert_with(Histogram::new)
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Render all metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP autograph_flow_runs_total Total flow runs by outcome.\n");
+        out.push_str("# TYPE autograph_flow_runs_total counter\n");
+        for ((flow, status), count) in self.runs.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "autograph_flow_runs_total{{flow=\"{}\",status=\"{}\"}} {}",
+                escape_label(flow), status, count
+            );
+        }
+
+        out.push_str("# HELP autograph_flow_errors_total Total failed flow runs.\n");
+        out.push_str("# TYPE autograph_flow_errors_total counter\n");
+        for (flow, count) in self.errors.lock().unwrap().iter() {
+            let _ = writeln!(out, "autograph_flow_errors_total{{flow=\"{}\"}} {}", escape_label(flow), count);
+        }
+
+        out.push_str("# HELP autograph_flow_run_duration_seconds Flow run duration.\n");
+        out.push_str("# TYPE autograph_flow_run_duration_seconds histogram\n");
+        for (flow, hist) in self.durations.lock().unwrap().iter() {
+            let flow = escape_label(flow);
+            for (bound, count) in DURATION_BUCKETS.iter().zip(&hist.buckets) {
+                let _ = writeln!(
+                    out,
+                    "autograph_flow_run_duration_seconds_bucket{{flow=\"{}\",le=\"{}\"}} {}",
+                    flow, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "autograph_flow_run_duration_seconds_bucket{{flow=\"{}\",le=\"+Inf\"}} {}",
+                flow, hist.count
+            );
+            let _ = writeln!(out, "autograph_flow_run_duration_seconds_sum{{flow=\"{}\"}} {}", flow, hist.sum);
+            let _ = writeln!(out, "autograph_flow_run_duration_seconds_count{{flow=\"{}\"}} {}", flow, hist.count);
+        }
+
+        out.push_str("# HELP autograph_active_jobs Flow runs currently executing.\n");
+        out.push_str("# TYPE autograph_active_jobs gauge\n");
+        let _ = writeln!(out, "autograph_active_jobs {}", self.active_jobs());
+
+        out
+    }
+}
+
+/// Escape a label value per the Prometheus text format
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,137 @@
+//! Mock Mode
+//!
+//! Test runs that leave real services alone. Nodes with a mock return its
+//! canned output, such as an HTTP response, instead of running; nodes that
+//! would reach the network without one fail the run; and file nodes work
+//! in a scratch directory seeded with the flow's mock files, so every test
+//! run reads the same files and writes nothing outside it. Mocks are
+//! ignored by normal runs.
+
+use crate::flow::{CompileOptions, Flow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Canned output for a node in mock runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MockRule {
+    pub node: String,
+    pub output: JsonValue,
+}
+
+/// Scratch directory holding a mock run's files, removed when dropped
+pub struct MockFs {
+    pub dir: PathBuf,
+}
+
+impl MockFs {
+    /// A fresh directory containing `files` (path to content)
+    pub fn create(files: &BTreeMap<String, String>) -> Result<Self, String> {
+        let mut token = [0u8; 8];
+        getrandom::getrandom(&mut token).map_err(|e| format!("No randomness available: {}", e))?;
+        let name: String = token.iter().map(|b| format!("{:02x}", b)).collect();
+        let fs = Self { dir: std::env::temp_dir().join(format!("autograph-mock-{}", name)) };
+        std::fs::create_dir_all(&fs.dir).map_err(|e| format!("Failed to create mock file system: {}", e))?;
+
+        for (path, content) in files {
+            let target = fs.dir.join(confined(path).ok_or_else(|| format!("Mock file path {:?} leaves the mock file system", path))?);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            std::fs::write(&target, content).map_err(|e| format!("Failed to write mock file {}: {}", path, e))?;
+        }
+        Ok(fs)
+    }
+}
+
+impl Drop for MockFs {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// `path` made relative, or `None` if it climbs out with `..`
+pub fn confined(path: &str) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::ParentDir => return None,
+            _ => {}
+        }
+    }
+    Some(relative)
+}
+
+/// Switch `options` to mock mode for `flow`: pin mocked nodes' outputs and
+/// confine file paths to a new mock file system, which must outlive the run.
+/// Fails, naming each node, if a node would reach the network unmocked.
+pub fn prepare(flow: &Flow, options: &mut CompileOptions) -> Result<MockFs, String> {
+    for rule in &flow.mocks {
+        options.fixed_outputs.entry(rule.node.clone()).or_insert_with(|| rule.output.clone());
+    }
+
+    let unmocked = unmocked_nodes(flow, options);
+    if !unmocked.is_empty() {
+        return Err(format!("No mock for {} node(s) that reach real services: {}", unmocked.len(), unmocked.join(", ")));
+    }
+
+    let fs = MockFs::create(&flow.mock_files)?;
+    options.work_dir = Some(fs.dir.clone());
+    options.confine_paths = true;
+    Ok(fs)
+}
+
+/// Nodes that would reach the network in a mock run, e.g. `fetch (http_get)`
+pub fn unmocked_nodes(flow: &Flow, options: &CompileOptions) -> Vec<String> {
+    crate::offline::blocked_nodes(flow, &options.fixed_outputs)
+        .into_iter()
+        .map(|(id, _)| {
+            let type_name = flow.nodes.iter().find(|n| n.id == id).map(|n| n.type_name.as_str()).unwrap_or("?");
+            format!("{} ({})", id, type_name)
+        })
+        .collect()
+}
+
+/// Log lines describing a mock run
+pub fn describe(flow: &Flow) -> Vec<String> {
+    let mut lines: Vec<String> = flow.mocks.iter()
+        .map(|rule| format!("Mock: {} returns its canned output", rule.node))
+        .collect();
+    lines.sort();
+    lines.push(format!("Mock: file nodes use a scratch directory with {} mock file(s)", flow.mock_files.len()));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::Node;
+    use serde_json::json;
+
+    fn node(id: &str, type_name: &str) -> Node {
+        Node::new(id, type_name, json!({"url": "https://example.com", "path": "/data/in.txt"}))
+    }
+
+    #[test]
+    fn mocks_network_nodes_and_confines_files() {
+        let mut flow = Flow { nodes: vec![node("fetch", "http_get"), node("read", "file_read")], ..Default::default() };
+        flow.mock_files.insert("data/in.txt".to_string(), "hello".to_string());
+
+        let err = prepare(&flow, &mut CompileOptions::default()).err().unwrap();
+        assert!(err.contains("fetch (http_get)"), "{}", err);
+
+        flow.mocks.push(MockRule { node: "fetch".to_string(), output: json!({"status": 200}) });
+        let mut options = CompileOptions::default();
+        let fs = prepare(&flow, &mut options).unwrap();
+        assert_eq!(options.fixed_outputs["fetch"], json!({"status": 200}));
+        assert_eq!(std::fs::read_to_string(fs.dir.join("data/in.txt")).unwrap(), "hello");
+        assert!(flow.compile_with(&options).contains(&*fs.dir.join("data/in.txt").to_string_lossy()));
+
+        let dir = fs.dir.clone();
+        drop(fs);
+        assert!(!dir.exists());
+        assert_eq!(confined("../etc/passwd"), None);
+    }
+}
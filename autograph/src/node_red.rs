@@ -0,0 +1,164 @@
+//! Node-RED Import
+//!
+//! Translates a Node-RED `flows.json` export into an Autograph flow.
+//! Common nodes map to their closest equivalents; anything without one
+//! becomes a pass-through `print` node carrying the original settings, so
+//! the graph and its layout survive the import and can be fixed by hand.
+
+use crate::flow::{Edge, Flow, Node, Position};
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+
+/// Vertical gap between tabs, which Node-RED lays out on separate canvases
+const TAB_SPACING: f32 = 600.0;
+
+/// Node types that only exist for Node-RED's editor and carry no behaviour
+const SKIPPED_TYPES: &[&str] = &["tab", "comment", "group", "junction"];
+
+/// A translated flow and the compromises made along the way
+pub struct NodeRedImport {
+    pub flow: Flow,
+    /// Name of the first tab, if any
+    pub name: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Translate the contents of a Node-RED `flows.json` export
+pub fn import(json: &str) -> Result<NodeRedImport, String> {
+    let value: JsonValue = serde_json::from_str(json).map_err(|e| format!("Invalid flows.json: {}", e))?;
+    let items = value.as_array().ok_or("flows.json should be an array of nodes")?;
+
+    // Node-RED tabs become vertical bands on a single canvas
+    let tabs: Vec<&JsonValue> = items.iter().filter(|i| i["type"] == "tab").collect();
+    let tab_offset = |z: &str| {
+        tabs.iter().position(|t| t["id"] == z).unwrap_or(0) as f32 * TAB_SPACING
+    };
+
+    let mut flow = Flow::default();
+    let mut warnings = Vec::new();
+    let mut ids: HashMap<&str, String> = HashMap::new();
+
+    for item in items {
+        let Some(nr_id) = item["id"].as_str() else { continue };
+        let nr_type = item["type"].as_str().unwrap_or("");
+        if SKIPPED_TYPES.contains(&nr_type) || item.get("z").is_none() {
+            // Config nodes (brokers, TLS settings, ...) have no tab
+            continue;
+        }
+
+        let id = unique_id(&flow, nr_id);
+        let (type_name, config) = translate(item, &mut warnings);
+        let offset = tab_offset(item["z"].as_str().unwrap_or(""));
+
+        flow.nodes.push(Node {
+            id: id.clone(),
+            type_name: type_name.to_string(),
+            config,
+            position: Some(Position {
+                x: item["x"].as_f64().unwrap_or(0.0) as f32,
+                y: item["y"].as_f64().unwrap_or(0.0) as f32 + offset,
+            }),
+            ..Default::default()
+        });
+        ids.insert(nr_id, id);
+    }
+
+    // `wires` holds, per output port, the IDs of the nodes it connects to
+    for item in items {
+        let Some(source) = item["id"].as_str().and_then(|id| ids.get(id)) else { continue };
+        let Some(outputs) = item["wires"].as_array() else { continue };
+
+        for (port, targets) in outputs.iter().enumerate() {
+            for target in targets.as_array().into_iter().flatten() {
+                let Some(target) = target.as_str().and_then(|id| ids.get(id)) else { continue };
+                flow.edges.push(Edge {
+                    source: source.clone(),
+                    target: target.clone(),
+                    source_handle: (outputs.len() > 1).then(|| format!("out{}", port)),
+                    target_handle: None,
+                });
+            }
+        }
+    }
+
+    let name = tabs.first()
+        .and_then(|t| t["label"].as_str())
+        .map(|label| label.to_string());
+
+    Ok(NodeRedImport { flow, name, warnings })
+}
+
+/// Pick the Autograph node type and config for a Node-RED node
+fn translate(item: &JsonValue, warnings: &mut Vec<String>) -> (&'static str, JsonValue) {
+    let nr_type = item["type"].as_str().unwrap_or("");
+    let label = item["name"].as_str().filter(|n| !n.is_empty()).unwrap_or(nr_type);
+
+    match nr_type {
+        "inject" => ("start", json!({})),
+        "debug" => ("print", json!({})),
+        "http request" => {
+            let method = match item["method"].as_str().unwrap_or("GET") {
+                // "use" takes the method from msg.method at runtime
+                "use" => "GET",
+                m => m,
+            };
+            let url = item["url"].as_str().unwrap_or("");
+            if url.is_empty() {
+                warnings.push(format!("'{}': URL was taken from msg.url; set it in the config", label));
+            }
+            ("http_request", json!({"method": method.to_uppercase(), "url": url}))
+        }
+        "change" => translate_change(item, label, warnings),
+        "function" => {
+            warnings.push(format!("'{}': JavaScript functions can't run in HLX; code kept in config", label));
+            ("print", json!({"node_red": {"type": nr_type, "func": item["func"]}}))
+        }
+        "switch" => {
+            warnings.push(format!(
+                "'{}': switch rules have no equivalent yet; all outputs now receive the message",
+                label
+            ));
+            ("print", json!({"node_red": {"type": nr_type, "property": item["property"], "rules": item["rules"]}}))
+        }
+        _ => {
+            warnings.push(format!("'{}': unsupported Node-RED node type '{}'", label, nr_type));
+            ("print", json!({"node_red": {"type": nr_type}}))
+        }
+    }
+}
+
+/// A change node that sets one `msg.payload` field maps to `json_set`
+fn translate_change(item: &JsonValue, label: &str, warnings: &mut Vec<String>) -> (&'static str, JsonValue) {
+    let rules = item["rules"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+
+    if let [rule] = rules {
+        let key = rule["p"].as_str().and_then(|p| p.strip_prefix("payload."));
+        if let (Some("set"), Some(key)) = (rule["t"].as_str(), key) {
+            let value = match &rule["to"] {
+                JsonValue::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            return ("json_set", json!({"key": key, "value": value}));
+        }
+    }
+
+    warnings.push(format!("'{}': only a single \"set msg.payload.<field>\" rule can be translated", label));
+    ("print", json!({"node_red": {"type": "change", "rules": item["rules"]}}))
+}
+
+/// Turn a Node-RED ID (e.g. `a1b2c3d4.e5f6`) into a free HLX identifier
+fn unique_id(flow: &Flow, nr_id: &str) -> String {
+    let base: String = nr_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let base = format!("nr_{}", base);
+
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while flow.nodes.iter().any(|n| n.id == id) {
+        id = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    id
+}
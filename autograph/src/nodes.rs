@@ -3,23 +3,298 @@
 //! Centralized definitions for all node types, their metadata,
 //! config schemas, and HLX code generation logic.
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
 
-/// Node type metadata and code generation
+/// Node type metadata and code generation. Built-in types borrow their
+/// text; dynamic ones own it, so reloading them frees the old definition.
+#[derive(Clone)]
 pub struct NodeDef {
-    pub name: &'static str,
-    pub category: &'static str,
-    pub description: &'static str,
+    pub name: Cow<'static, str>,
+    pub category: Cow<'static, str>,
+    pub description: Cow<'static, str>,
+    /// What the node expects on its inputs
+    pub input_type: DataType,
+    /// What the node outputs
+    pub output_type: DataType,
     pub default_config: fn() -> JsonValue,
     pub generate_code: fn(&str, &JsonValue, Option<&str>) -> String,
 }
 
+/// A node type registered at runtime instead of compiled in, e.g. one
+/// generated from an OpenAPI spec
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicNodeDef {
+    pub name: String,
+    pub category: String,
+    pub description: String,
+    pub default_config: JsonValue,
+    pub kind: DynamicKind,
+    /// Canvas color as `#rrggbb`; without one the category picks a color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Config fields shown as a form in the properties panel, as
+    /// `{"<key>": {"type": "string|number|integer|boolean", "description", "enum", "default"}}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<JsonValue>,
+}
+
+/// How a dynamic node generates its code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DynamicKind {
+    /// One operation of an HTTP API, see `crate::openapi`; everything
+    /// node-specific lives in the node's config
+    OpenApi,
+    /// A node from a plugin file with its own code template, see `crate::plugins`
+    Plugin { code: String },
+}
+
+impl DynamicKind {
+    fn generate_code(&self, node_id: &str, config: &JsonValue, input_var: Option<&str>) -> String {
+        match self {
+            DynamicKind::OpenApi => crate::openapi::generate_code(node_id, config, input_var),
+            DynamicKind::Plugin { code } => crate::plugins::generate_code(code, node_id, config, input_var),
+        }
+    }
+}
+
+/// A registered dynamic node with its real default config and code generator
+struct Registered {
+    def: Arc<NodeDef>,
+    default_config: JsonValue,
+    kind: DynamicKind,
+    color: [u8; 3],
+    schema: Option<JsonValue>,
+}
+
+/// Dynamic node types. Re-registering or unregistering one drops the
+/// registry's `Arc`, and the definition goes once the last listing of it
+/// is gone.
+static DYNAMIC_NODES: RwLock<Vec<Registered>> = RwLock::new(Vec::new());
+
 /// Get all registered node types
-pub fn all_nodes() -> Vec<&'static NodeDef> {
+pub fn all_nodes() -> Vec<Arc<NodeDef>> {
+    static BUILTIN: OnceLock<Vec<Arc<NodeDef>>> = OnceLock::new();
+    let mut nodes = BUILTIN.get_or_init(|| builtin_nodes().into_iter().map(|def| Arc::new(def.clone())).collect()).clone();
+    nodes.extend(DYNAMIC_NODES.read().unwrap().iter().map(|r| r.def.clone()));
+    nodes
+}
+
+/// Default config for a new node of the given type. Dynamic types get the
+/// `default` of each schema field their default config leaves out.
+pub fn default_config(name: &str) -> JsonValue {
+    if let Some(registered) = DYNAMIC_NODES.read().unwrap().iter().find(|r| r.def.name == name) {
+        let mut config = registered.default_config.clone();
+        let fields = registered.schema.as_ref().and_then(|s| s.as_object());
+        if let (Some(config), Some(fields)) = (config.as_object_mut(), fields) {
+            for (key, field) in fields {
+                if let Some(default) = field.get("default").filter(|_| !config.contains_key(key)) {
+                    config.insert(key.clone(), default.clone());
+                }
+            }
+        }
+        return config;
+    }
+    builtin_nodes()
+        .into_iter()
+        .find(|def| def.name == name)
+        .map(|def| (def.default_config)())
+        .unwrap_or(serde_json::json!({}))
+}
+
+/// Generate the HLX for a node of the given type, or `None` if the type is unknown
+pub fn generate_code(type_name: &str, node_id: &str, config: &JsonValue, input_var: Option<&str>) -> Option<String> {
+    if let Some(registered) = DYNAMIC_NODES.read().unwrap().iter().find(|r| r.def.name == type_name) {
+        return Some(registered.kind.generate_code(node_id, config, input_var));
+    }
+    builtin_nodes()
+        .into_iter()
+        .find(|def| def.name == type_name)
+        .map(|def| (def.generate_code)(node_id, config, input_var))
+}
+
+/// Named input ports of a node type, in order. Nodes without any take
+/// a single unnamed input; edges pick a port with their `target_handle`.
+pub fn input_ports(type_name: &str) -> &'static [&'static str] {
+    match type_name {
+        "tensor_matmul" | "tensor_add" | "tensor_mul" => &["a", "b"],
+        _ => &[],
+    }
+}
+
+/// Whether a node type takes an input; `start` only produces one
+pub fn has_input(type_name: &str) -> bool {
+    type_name != "start"
+}
+
+/// Kind of value a port carries, shown as the color of its handle and
+/// checked across edges by `Flow::type_mismatches`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Any,
+    String,
+    Number,
+    Boolean,
+    Object,
+    Array,
+    Tensor,
+    /// Binary data as `{"$bytes": "<base64>"}`
+    Bytes,
+}
+
+impl DataType {
+    pub fn name(self) -> &'static str {
+        match self {
+            DataType::Any => "any",
+            DataType::String => "string",
+            DataType::Number => "number",
+            DataType::Boolean => "boolean",
+            DataType::Object => "object",
+            DataType::Array => "array",
+            DataType::Tensor => "tensor",
+            DataType::Bytes => "bytes",
+        }
+    }
+
+    /// Whether a port of this type takes a value of type `found`; `Any`
+    /// on either side matches everything
+    pub fn accepts(self, found: DataType) -> bool {
+        self == found || self == DataType::Any || found == DataType::Any
+    }
+}
+
+/// Type a node type expects on its inputs and the type it outputs;
+/// unknown types take and give anything
+pub fn port_types(type_name: &str) -> (DataType, DataType) {
+    all_nodes()
+        .into_iter()
+        .find(|def| def.name == type_name)
+        .map_or((DataType::Any, DataType::Any), |def| (def.input_type, def.output_type))
+}
+
+/// Canvas color of a dynamic node type; built-in types are colored by the canvas
+pub fn node_color(type_name: &str) -> Option<[u8; 3]> {
+    DYNAMIC_NODES.read().unwrap().iter().find(|r| r.def.name == type_name).map(|r| r.color)
+}
+
+/// Config form fields of a node type, if it declares any
+pub fn config_schema(type_name: &str) -> Option<JsonValue> {
+    DYNAMIC_NODES.read().unwrap().iter().find(|r| r.def.name == type_name)?.schema.clone()
+}
+
+/// Forget every plugin node type, before reloading the plugin directory
+pub fn unregister_plugins() {
+    DYNAMIC_NODES.write().unwrap().retain(|r| !matches!(r.kind, DynamicKind::Plugin { .. }));
+}
+
+/// Register a dynamic node type, replacing an earlier one of the same name
+pub fn register_dynamic(node: DynamicNodeDef) -> Result<(), String> {
+    if builtin_nodes().iter().any(|def| def.name == node.name) {
+        return Err(format!("'{}' is a built-in node type", node.name));
+    }
+    let color = match &node.color {
+        Some(hex) => parse_color(hex).ok_or_else(|| format!("invalid color {:?} (use #rrggbb)", hex))?,
+        None => crate::registry::category_color(&node.category).unwrap_or_else(|| category_color(&node.category)),
+    };
+
+    let def = Arc::new(NodeDef {
+        name: Cow::Owned(node.name),
+        category: Cow::Owned(node.category),
+        description: Cow::Owned(node.description),
+        input_type: DataType::Any,
+        output_type: DataType::Any,
+        default_config: || serde_json::json!({}),
+        // Dynamic nodes are generated through `generate_code`, which can
+        // reach their registered kind
+        generate_code: |node_id, _, _| format!("    let {}_out = null;\n", node_id),
+    });
+
+    let mut nodes = DYNAMIC_NODES.write().unwrap();
+    nodes.retain(|r| r.def.name != def.name);
+    nodes.push(Registered {
+        def,
+        default_config: node.default_config,
+        kind: node.kind,
+        color,
+        schema: node.schema,
+    });
+    Ok(())
+}
+
+/// `#rrggbb` as RGB
+pub(crate) fn parse_color(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// A stable, readable color per category, so nodes of one plugin match
+fn category_color(category: &str) -> [u8; 3] {
+    let hash = category.bytes().fold(0x811c9dc5u32, |h, b| (h ^ b as u32).wrapping_mul(0x01000193));
+    let channel = |shift: u32| 70 + ((hash >> shift) & 0xff) as u8 % 120;
+    [channel(0), channel(8), channel(16)]
+}
+
+/// Register every dynamic node saved under `dir` (one JSON array of
+/// `DynamicNodeDef` per file, in subdirectories too), returning the number
+/// registered and any files that couldn't be loaded
+pub fn load_dynamic_nodes(dir: &Path) -> (usize, Vec<String>) {
+    let mut count = 0;
+    let mut errors = Vec::new();
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, errors);
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            let (n, errs) = load_dynamic_nodes(&path);
+            count += n;
+            errors.extend(errs);
+            continue;
+        }
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        let defs: Result<Vec<DynamicNodeDef>, String> = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()));
+        match defs {
+            Ok(defs) => {
+                for def in defs {
+                    match register_dynamic(def) {
+                        Ok(()) => count += 1,
+                        Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+                    }
+                }
+            }
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    (count, errors)
+}
+
+/// Node types compiled into Autograph
+pub(crate) fn builtin_nodes() -> Vec<&'static NodeDef> {
     vec![
         // Control
         &START,
         &PRINT,
+        &HLX_SCRIPT,
+        &SUBFLOW,
+
+        // Testing
+        &ASSERT_EQUALS,
+        &ASSERT_CONTAINS,
+        &ASSERT_SCHEMA,
 
         // HTTP
         &HTTP_GET,
@@ -27,11 +302,24 @@ pub fn all_nodes() -> Vec<&'static NodeDef> {
         &HTTP_PUT,
         &HTTP_DELETE,
         &HTTP_REQUEST,
+        &HTTP_PAGINATE,
+        &HTTP_RESPONSE_GET,
+        &FEED_FETCH,
+
+        // Messaging
+        &SLACK_SEND,
+        &DISCORD_SEND,
+        &TELEGRAM_SEND,
+
+        // Queue
+        &NATS_PUBLISH,
+        &NATS_CONSUME,
 
         // Data - JSON
         &JSON_PARSE,
         &JSON_STRINGIFY,
         &JSON_GET,
+        &JSON_QUERY,
         &JSON_SET,
 
         // Data - String
@@ -42,6 +330,10 @@ pub fn all_nodes() -> Vec<&'static NodeDef> {
         &STRING_SPLIT,
         &STRING_REPLACE,
         &STRING_LENGTH,
+        &TEMPLATE_RENDER,
+        &REGEX_MATCH,
+        &REGEX_EXTRACT,
+        &REGEX_REPLACE,
 
         // Data - Array
         &ARRAY_MAP,
@@ -51,6 +343,19 @@ pub fn all_nodes() -> Vec<&'static NodeDef> {
         &ARRAY_CONCAT,
         &ARRAY_SORT,
         &ARRAY_LENGTH,
+        &ARRAY_SAMPLE,
+        &ARRAY_GROUP_BY,
+        &ARRAY_SUM,
+        &ARRAY_AVERAGE,
+        &ARRAY_MIN,
+        &ARRAY_MAX,
+        &ARRAY_COUNT,
+        &ARRAY_DISTINCT,
+        &ARRAY_ZIP,
+        &ARRAY_FLATTEN,
+        &ARRAY_UNIQUE,
+        &ARRAY_CHUNK,
+        &ARRAY_REVERSE,
 
         // Data - Object
         &OBJECT_GET,
@@ -62,12 +367,32 @@ pub fn all_nodes() -> Vec<&'static NodeDef> {
         // Files
         &FILE_READ,
         &FILE_WRITE,
+        &FILE_READ_BINARY,
+        &FILE_WRITE_BINARY,
+        &FILE_READ_LINES,
+        &FILE_STREAM,
         &FILE_EXISTS,
         &FILE_DELETE,
         &FILE_LIST,
         &DIR_CREATE,
         &JSON_READ,
         &JSON_WRITE,
+        &JSONL_READ,
+        &JSONL_WRITE,
+        &JSONL_APPEND,
+
+        // Database
+        &DB_QUERY,
+        &DB_EXECUTE,
+        &STATE_GET,
+        &STATE_SET,
+        &STATE_INCREMENT,
+
+        // Transfer
+        &SFTP_UPLOAD,
+        &SFTP_DOWNLOAD,
+        &SFTP_LIST,
+        &EMAIL_READ,
 
         // Math
         &MATH_ADD,
@@ -79,16 +404,53 @@ pub fn all_nodes() -> Vec<&'static NodeDef> {
         &MATH_ROUND,
         &MATH_SQRT,
         &MATH_RANDOM,
+        &UUID_V4,
 
         // Type Conversion
         &TO_STRING,
         &TO_INT,
         &TO_FLOAT,
 
+        // Crypto & Encoding
+        &HASH,
+        &HMAC_SIGN,
+        &BASE64_ENCODE,
+        &BASE64_DECODE,
+        &BYTES_FROM_BASE64,
+        &BYTES_TO_BASE64,
+        &URL_ENCODE,
+        &URL_DECODE,
+        &UUID_GENERATE,
+
+        // LLM
+        &PROMPT_TEMPLATE,
+        &TOKEN_COUNT,
+        &TRUNCATE_TOKENS,
+        &LLM_COMPLETE,
+
         // ML/GPU
         &TENSOR_CREATE,
         &TENSOR_MATMUL,
         &TENSOR_ADD,
+        &TENSOR_STACK,
+        &TENSOR_BATCH,
+        &TENSOR_FROM_CSV,
+        &TENSOR_FROM_NPY,
+        &TENSOR_RANDOM,
+        &TENSOR_MUL,
+        &TENSOR_TRANSPOSE,
+        &TENSOR_RESHAPE,
+        &TENSOR_SLICE,
+        &TENSOR_REDUCE_SUM,
+        &TENSOR_REDUCE_MEAN,
+        &TENSOR_RELU,
+        &TENSOR_SIGMOID,
+        &TENSOR_SOFTMAX,
+        &TENSOR_ARGMAX,
+        &MODEL_INFER,
+        &EMBED_TEXT,
+        &VECTOR_INDEX,
+        &VECTOR_SEARCH,
 
         // System
         &SLEEP,
@@ -96,6 +458,68 @@ pub fn all_nodes() -> Vec<&'static NodeDef> {
     ]
 }
 
+/// Render a config string as an HLX string literal.
+///
+/// Quotes, backslashes and control characters are escaped so that odd
+/// config values can never produce a syntactically invalid program.
+pub fn hlx_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A config value as an HLX expression
+pub fn hlx_literal(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => hlx_string(s),
+        JsonValue::Array(_) | JsonValue::Object(_) => format!("json_parse({})", hlx_string(&value.to_string())),
+        other => other.to_string(),
+    }
+}
+
+/// Derive a node's RNG seed from a flow/run seed and the node ID.
+///
+/// Each node gets an independent stream, and the result fits in an
+/// HLX integer literal.
+pub fn node_seed(seed: u64, node_id: &str) -> u64 {
+    // FNV-1a over the node ID
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in node_id.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    splitmix64(seed ^ hash) >> 1
+}
+
+/// SplitMix64 step, used to expand a node seed into random values
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Tokenizers understood by the runtime's token builtins
+pub const TOKENIZERS: &[&str] = &["cl100k_base", "o200k_base", "whitespace", "chars"];
+
+/// Tokenizer named in a node config, falling back to `cl100k_base`
+fn tokenizer(config: &JsonValue) -> &'static str {
+    let name = config["tokenizer"].as_str().unwrap_or("");
+    TOKENIZERS.iter().find(|t| **t == name).copied().unwrap_or(TOKENIZERS[0])
+}
+
 // Helper to get input variable from edges
 fn input_var(node_id: &str, default: &str) -> String {
     format!("{{ let input_var = edges_to_{}; if input_var then input_var else {} }}", node_id, default)
@@ -106,9 +530,11 @@ fn input_var(node_id: &str, default: &str) -> String {
 // ====================
 
 static START: NodeDef = NodeDef {
-    name: "start",
-    category: "Control",
-    description: "Entry point for workflow",
+    name: Cow::Borrowed("start"),
+    category: Cow::Borrowed("Control"),
+    description: Cow::Borrowed("Entry point for workflow"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, _input_var| {
         format!("    let {}_out = input;\n", node_id)
@@ -116,9 +542,11 @@ static START: NodeDef = NodeDef {
 };
 
 static PRINT: NodeDef = NodeDef {
-    name: "print",
-    category: "Debug",
-    description: "Print value to console",
+    name: Cow::Borrowed("print"),
+    category: Cow::Borrowed("Debug"),
+    description: Cow::Borrowed("Print value to console"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("null");
@@ -126,77 +554,657 @@ static PRINT: NodeDef = NodeDef {
     },
 };
 
+// ====================
+// TESTING NODES
+// ====================
+
+// Assertions pass their input on; `crate::testing` checks them after the run
+
+fn pass_through(node_id: &str, input_var: Option<&str>) -> String {
+    format!("    let {}_out = {};\n", node_id, input_var.unwrap_or("null"))
+}
+
+static ASSERT_EQUALS: NodeDef = NodeDef {
+    name: Cow::Borrowed("assert_equals"),
+    category: Cow::Borrowed("Testing"),
+    description: Cow::Borrowed("In tests, fail unless the input equals the expected value"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"expected": null}),
+    generate_code: |node_id, _config, input_var| pass_through(node_id, input_var),
+};
+
+static ASSERT_CONTAINS: NodeDef = NodeDef {
+    name: Cow::Borrowed("assert_contains"),
+    category: Cow::Borrowed("Testing"),
+    description: Cow::Borrowed("In tests, fail unless the input contains the value (substring, element, key or fields)"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"value": ""}),
+    generate_code: |node_id, _config, input_var| pass_through(node_id, input_var),
+};
+
+static ASSERT_SCHEMA: NodeDef = NodeDef {
+    name: Cow::Borrowed("assert_schema"),
+    category: Cow::Borrowed("Testing"),
+    description: Cow::Borrowed("In tests, fail unless the input matches a JSON Schema"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"schema": {"type": "object"}}),
+    generate_code: |node_id, _config, input_var| pass_through(node_id, input_var),
+};
+
+/// Whether `name` can be passed to `run_flow` and used as a file name in the flows directory
+pub fn valid_flow_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+static SUBFLOW: NodeDef = NodeDef {
+    name: Cow::Borrowed("subflow"),
+    category: Cow::Borrowed("Control"),
+    description: Cow::Borrowed("Run another saved flow with this node's input"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"flow": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let name = config["flow"].as_str().unwrap_or("");
+        if !valid_flow_name(name) {
+            return format!("    // subflow: invalid flow name {:?}\n    let {}_out = null;\n", name, node_id);
+        }
+        format!("    let {}_out = run_flow({}, {});\n", node_id, hlx_string(name), input)
+    },
+};
+
+static HLX_SCRIPT: NodeDef = NodeDef {
+    name: Cow::Borrowed("hlx_script"),
+    category: Cow::Borrowed("Control"),
+    description: Cow::Borrowed("Run inline HLX code"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({
+        "inputs": {},
+        "code": "// `input` is the first connected node's output\nreturn input;"
+    }),
+    generate_code: |node_id, config, input_var| {
+        let args: Vec<String> = std::iter::once(input_var.unwrap_or("null").to_string())
+            .chain(script_inputs(config).into_iter().map(|(_, source)| format!("{}_out", source)))
+            .collect();
+        format!("    let {}_out = {}_script({});\n", node_id, node_id, args.join(", "))
+    },
+};
+
+/// Named inputs of an `hlx_script` node: parameter name and source node ID
+pub fn script_inputs(config: &JsonValue) -> Vec<(String, String)> {
+    config["inputs"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(name, _)| is_identifier(name) && name.as_str() != "input")
+        .filter_map(|(name, source)| Some((name.clone(), source.as_str()?.to_string())))
+        .collect()
+}
+
+/// Name every connected node that the script doesn't already alias after
+/// its own ID, so all connections are available as named inputs
+pub fn bind_script_inputs<'a>(config: &mut JsonValue, sources: impl Iterator<Item = &'a str>) {
+    if !config.is_object() {
+        return;
+    }
+    if !config["inputs"].is_object() {
+        config["inputs"] = serde_json::json!({});
+    }
+    let inputs = config["inputs"].as_object_mut().unwrap();
+    for source in sources {
+        if !inputs.values().any(|v| v.as_str() == Some(source)) && !inputs.contains_key(source) {
+            inputs.insert(source.to_string(), JsonValue::String(source.to_string()));
+        }
+    }
+}
+
+/// Drop the named inputs of an `hlx_script` node that aren't identifiers or
+/// don't name one of the `upstream` nodes, returning the dropped names
+pub fn retain_script_inputs(config: &mut JsonValue, upstream: &[&str]) -> Vec<String> {
+    let Some(inputs) = config.get_mut("inputs").and_then(|inputs| inputs.as_object_mut()) else {
+        return Vec::new();
+    };
+    let dropped: Vec<String> = inputs.iter()
+        .filter(|(name, source)| {
+            !is_identifier(name) || source.as_str().is_none_or(|source| !upstream.contains(&source))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    for name in &dropped {
+        inputs.remove(name);
+    }
+    dropped
+}
+
+/// The function an `hlx_script` node's code runs in. Called from `main`
+/// with the input and named inputs, so the script gets its own scope and
+/// its `return` produces the node's output.
+pub fn script_function(node_id: &str, config: &JsonValue) -> String {
+    let params: Vec<String> = std::iter::once("input".to_string())
+        .chain(script_inputs(config).into_iter().map(|(name, _)| name))
+        .collect();
+    let body: String = config["code"]
+        .as_str()
+        .unwrap_or("return input;")
+        .lines()
+        .map(|line| format!("    {}\n", line))
+        .collect();
+    format!("fn {}_script({}) {{\n{}}}\n\n", node_id, params.join(", "), body)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 // ====================
 // HTTP NODES
 // ====================
 
+/// Node types that send requests, and take an auth profile and rate limit
+pub const HTTP_REQUEST_NODES: &[&str] = &["http_get", "http_post", "http_put", "http_delete", "http_request", "http_paginate", "feed_fetch"];
+
+/// What an HTTP node outputs: the parsed body, the whole response as
+/// `{status, ok, headers, body, duration_ms}`, or the raw body as bytes
+pub const HTTP_OUTPUTS: &[&str] = &["body", "response", "bytes"];
+
+/// Statuses a response is `ok` within when the node has no `expect_status`
+const EXPECTED_STATUS: &str = "2xx, 3xx";
+
 static HTTP_GET: NodeDef = NodeDef {
-    name: "http_get",
-    category: "HTTP",
-    description: "HTTP GET request",
+    name: Cow::Borrowed("http_get"),
+    category: Cow::Borrowed("HTTP"),
+    description: Cow::Borrowed("HTTP GET request"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"url": "https://example.com"}),
-    generate_code: |node_id, config, _input_var| {
-        let url = config["url"].as_str().unwrap_or("https://example.com");
-        format!("    let {}_out = http_request(\"GET\", \"{}\", null, {{}});\n", node_id, url)
-    },
+    generate_code: |node_id, config, _input_var| http_call(node_id, config, "\"GET\"", "null"),
 };
 
 static HTTP_POST: NodeDef = NodeDef {
-    name: "http_post",
-    category: "HTTP",
-    description: "HTTP POST request",
+    name: Cow::Borrowed("http_post"),
+    category: Cow::Borrowed("HTTP"),
+    description: Cow::Borrowed("HTTP POST request"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"url": "https://example.com"}),
-    generate_code: |node_id, config, input_var| {
-        let url = config["url"].as_str().unwrap_or("https://example.com");
-        let body = input_var.unwrap_or("null");
-        format!("    let {}_out = http_request(\"POST\", \"{}\", {}, {{}});\n", node_id, url, body)
-    },
+    generate_code: |node_id, config, input_var| http_call(node_id, config, "\"POST\"", input_var.unwrap_or("null")),
 };
 
 static HTTP_PUT: NodeDef = NodeDef {
-    name: "http_put",
-    category: "HTTP",
-    description: "HTTP PUT request",
+    name: Cow::Borrowed("http_put"),
+    category: Cow::Borrowed("HTTP"),
+    description: Cow::Borrowed("HTTP PUT request"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"url": "https://example.com"}),
-    generate_code: |node_id, config, input_var| {
-        let url = config["url"].as_str().unwrap_or("https://example.com");
-        let body = input_var.unwrap_or("null");
-        format!("    let {}_out = http_request(\"PUT\", \"{}\", {}, {{}});\n", node_id, url, body)
-    },
+    generate_code: |node_id, config, input_var| http_call(node_id, config, "\"PUT\"", input_var.unwrap_or("null")),
 };
 
 static HTTP_DELETE: NodeDef = NodeDef {
-    name: "http_delete",
-    category: "HTTP",
-    description: "HTTP DELETE request",
+    name: Cow::Borrowed("http_delete"),
+    category: Cow::Borrowed("HTTP"),
+    description: Cow::Borrowed("HTTP DELETE request"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"url": "https://example.com"}),
+    generate_code: |node_id, config, _input_var| http_call(node_id, config, "\"DELETE\"", "null"),
+};
+
+static HTTP_REQUEST: NodeDef = NodeDef {
+    name: Cow::Borrowed("http_request"),
+    category: Cow::Borrowed("HTTP"),
+    description: Cow::Borrowed("Custom HTTP request"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"method": "GET", "url": "https://example.com", "output": "response"}),
+    generate_code: |node_id, config, input_var| {
+        let method = hlx_string(config["method"].as_str().unwrap_or("GET"));
+        http_call(node_id, config, &method, input_var.unwrap_or("null"))
+    },
+};
+
+/// `http_request` to the node's `url`, authenticated with its `auth` profile
+/// and held to its rate limit
+fn http_call(node_id: &str, config: &JsonValue, method: &str, body: &str) -> String {
+    let url = config["url"].as_str().unwrap_or("https://example.com");
+    let code = crate::http_auth::request_auth(config).and_then(|auth| {
+        let (url, headers) = (auth.url(url), auth.header_object());
+        let wait = crate::throttle::wait_code(config, "    ");
+        match config["output"].as_str().unwrap_or("body") {
+            "body" => Ok(format!(
+                "{}    let {}_out = {};\n",
+                wait,
+                node_id,
+                crate::throttle::request_call(config, method, &url, body, &headers)
+            )),
+            "response" => Ok(format!("{}{}", wait, response_code(node_id, config, method, &url, body, &headers)?)),
+            "bytes" => Ok(format!(
+                "{}    let {}_out = {{ {}: http_request_base64({}, {}, {}, {}, {}) }};\n",
+                wait,
+                node_id,
+                hlx_string(crate::bytes::BYTES_KEY),
+                method,
+                url,
+                body,
+                headers,
+                crate::throttle::retries(config)
+            )),
+            other => Err(format!("unknown output {:?} (use {})", other, HTTP_OUTPUTS.join(", "))),
+        }
+    });
+    code.unwrap_or_else(|e| format!("    // {}\n    let {}_out = null;\n", e, node_id))
+}
+
+/// Parse `expect_status`: comma-separated codes (`404`), ranges
+/// (`200-204`) and classes (`2xx`)
+pub fn status_ranges(spec: &str) -> Result<Vec<(u16, u16)>, String> {
+    let code = |s: &str| match s.trim().parse::<u16>() {
+        Ok(code) if (100..=599).contains(&code) => Ok(code),
+        _ => Err(format!("{:?} is not an HTTP status", s.trim())),
+    };
+    spec.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            if let Some(class) = part.strip_suffix("xx").or_else(|| part.strip_suffix("XX")) {
+                let first = code(&format!("{}00", class))?;
+                Ok((first, first + 99))
+            } else if let Some((low, high)) = part.split_once('-') {
+                let range = (code(low)?, code(high)?);
+                if range.0 > range.1 {
+                    return Err(format!("{:?} is an empty range", part));
+                }
+                Ok(range)
+            } else {
+                code(part).map(|c| (c, c))
+            }
+        })
+        .collect()
+}
+
+/// Request returning the whole response, `ok` when its status is expected.
+/// With `fail_on_status` an unexpected status fails the run.
+fn response_code(node_id: &str, config: &JsonValue, method: &str, url: &str, body: &str, headers: &str) -> Result<String, String> {
+    let spec = config["expect_status"].as_str().unwrap_or(EXPECTED_STATUS);
+    let ranges = status_ranges(spec).map_err(|e| format!("expect_status: {}", e))?;
+    if ranges.is_empty() {
+        return Err("expect_status: no statuses given".to_string());
+    }
+    let status = format!("{}_status", node_id);
+    let ok = ranges.iter()
+        .map(|(low, high)| match low == high {
+            true => format!("{} == {}", status, low),
+            false => format!("({} >= {} && {} <= {})", status, low, status, high),
+        })
+        .collect::<Vec<_>>()
+        .join(" || ");
+
+    let mut code = format!(
+        "    let {id}_out = http_fetch({method}, {url}, {body}, {headers}, {retries});\n    let {status} = get({id}_out, \"status\");\n    {id}_out = set({id}_out, \"ok\", {ok});\n",
+        id = node_id,
+        method = method,
+        url = url,
+        body = body,
+        headers = headers,
+        retries = crate::throttle::retries(config),
+        status = status,
+        ok = ok,
+    );
+    if config["fail_on_status"].as_bool().unwrap_or(false) {
+        code.push_str(&format!(
+            "    if (get({id}_out, \"ok\") == false) {{\n        fail({message} + to_string({status}));\n    }}\n",
+            id = node_id,
+            message = hlx_string(&format!("{}: unexpected HTTP status ", node_id)),
+            status = status,
+        ));
+    }
+    Ok(code)
+}
+
+/// Ways `http_paginate` finds the next page
+pub const PAGINATION_STRATEGIES: &[&str] = &["next_url", "cursor", "offset"];
+
+/// Page limit of `http_paginate` without a `max_pages`
+const MAX_PAGES: u64 = 10;
+
+static HTTP_PAGINATE: NodeDef = NodeDef {
+    name: Cow::Borrowed("http_paginate"),
+    category: Cow::Borrowed("HTTP"),
+    description: Cow::Borrowed("GET every page of a paginated API and concatenate the items"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({
+        "url": "https://example.com/api/items",
+        "strategy": "next_url",
+        "items_path": "$.items",
+        "next_path": "$.next",
+        "max_pages": MAX_PAGES
+    }),
     generate_code: |node_id, config, _input_var| {
-        let url = config["url"].as_str().unwrap_or("https://example.com");
-        format!("    let {}_out = http_request(\"DELETE\", \"{}\", null, {{}});\n", node_id, url)
+        match paginate_code(node_id, config) {
+            Ok(code) => code,
+            Err(e) => format!(
+                "    // Invalid pagination in {}: {}\n    let {}_out = [];\n",
+                node_id,
+                e.lines().next().unwrap_or("").trim(),
+                node_id
+            ),
+        }
     },
 };
 
-static HTTP_REQUEST: NodeDef = NodeDef {
-    name: "http_request",
-    category: "HTTP",
-    description: "Custom HTTP request",
-    default_config: || serde_json::json!({"method": "GET", "url": "https://example.com"}),
+/// A JSONPath from the node's config run against each page (`null` when it doesn't match)
+fn page_query(node_id: &str, config: &JsonValue, key: &str) -> Result<Option<String>, String> {
+    let path = config[key].as_str().unwrap_or("").trim();
+    if path.is_empty() {
+        return Ok(None);
+    }
+    serde_json_path::JsonPath::parse(path).map_err(|e| format!("{}: {}", key, e))?;
+    Ok(Some(format!("json_query({}_body, \"jsonpath\", {}, false)", node_id, hlx_string(path))))
+}
+
+/// Request pages until the strategy runs out of them or `max_pages` is
+/// reached, concatenating each page's items
+fn paginate_code(node_id: &str, config: &JsonValue) -> Result<String, String> {
+    let url = config["url"].as_str().unwrap_or("");
+    let separator = if url.contains('?') { "&" } else { "?" };
+    let mut headers: Vec<String> = config["headers"].as_object().into_iter().flatten()
+        .filter_map(|(k, v)| Some(format!("{}: {}", hlx_string(k), connection_expr(v.as_str()?))))
+        .collect();
+    let auth = crate::http_auth::request_auth(config)?;
+    if auth.query.is_some() {
+        // Next-page URLs come from the API, so a key can't be added to them
+        return Err("query API keys aren't supported; send the key in a header".to_string());
+    }
+    headers.extend(auth.headers);
+    let max_pages = config["max_pages"].as_u64().unwrap_or(MAX_PAGES).max(1);
+    // Without `items_path` the page itself is the item list
+    let items = page_query(node_id, config, "items_path")?.unwrap_or_else(|| format!("{}_body", node_id));
+
+    let (setup, request, advance) = match config["strategy"].as_str().unwrap_or("next_url") {
+        "next_url" => {
+            let next = page_query(node_id, config, "next_path")?.ok_or("next_path is required")?;
+            (
+                format!("    let {id}_url = {url};\n", id = node_id, url = hlx_string(url)),
+                format!("        let {id}_request = {id}_url;\n", id = node_id),
+                format!(
+                    "        {id}_url = {next};\n        {id}_more = {id}_url != null && {id}_url != \"\";\n",
+                    id = node_id,
+                    next = next
+                ),
+            )
+        }
+        "cursor" => {
+            let next = page_query(node_id, config, "cursor_path")?.ok_or("cursor_path is required")?;
+            let with_cursor = format!("{}{}{}=", url, separator, config["cursor_param"].as_str().unwrap_or("cursor"));
+            (
+                format!("    let {}_cursor = null;\n", node_id),
+                format!(
+                    "        let {id}_request = {first};\n        if ({id}_cursor != null) {{\n            {id}_request = {with_cursor} + url_encode(to_string({id}_cursor));\n        }}\n",
+                    id = node_id,
+                    first = hlx_string(url),
+                    with_cursor = hlx_string(&with_cursor),
+                ),
+                format!(
+                    "        {id}_cursor = {next};\n        {id}_more = {id}_cursor != null && {id}_cursor != \"\";\n",
+                    id = node_id,
+                    next = next
+                ),
+            )
+        }
+        "offset" => {
+            let page_size = config["page_size"].as_u64().unwrap_or(100).max(1);
+            let base = format!(
+                "{}{}{}={}&{}=",
+                url,
+                separator,
+                config["limit_param"].as_str().unwrap_or("limit"),
+                page_size,
+                config["offset_param"].as_str().unwrap_or("offset")
+            );
+            (
+                format!("    let {}_offset = 0;\n", node_id),
+                format!("        let {id}_request = {base} + to_string({id}_offset);\n", id = node_id, base = hlx_string(&base)),
+                // A short page is the last one
+                format!(
+                    "        {id}_offset = {id}_offset + len({id}_batch);\n        {id}_more = len({id}_batch) >= {size};\n",
+                    id = node_id,
+                    size = page_size
+                ),
+            )
+        }
+        other => return Err(format!("unknown strategy {:?} (use {})", other, PAGINATION_STRATEGIES.join(", "))),
+    };
+
+    let fetch = crate::throttle::request_call(config, "\"GET\"", &format!("{}_request", node_id), "null", &format!("{{ {} }}", headers.join(", ")));
+    Ok(format!(
+        "    let {id}_out = [];\n{setup}    let {id}_more = true;\n    loop ({id}_more, {max}) {{\n{request}{wait}        let {id}_body = json_parse({fetch});\n        let {id}_batch = {items};\n        if ({id}_batch == null) {{\n            {id}_batch = [];\n        }}\n        {id}_out = arr_concat({id}_out, {id}_batch);\n{advance}    }}\n",
+        id = node_id,
+        setup = setup,
+        max = max_pages,
+        request = request,
+        wait = crate::throttle::wait_code(config, "        "),
+        fetch = fetch,
+        items = items,
+        advance = advance,
+    ))
+}
+
+/// Fields of each entry `feed_fetch` outputs; `id` falls back to the link
+pub const FEED_FIELDS: &[&str] = &["id", "title", "link", "published", "summary"];
+
+static FEED_FETCH: NodeDef = NodeDef {
+    name: Cow::Borrowed("feed_fetch"),
+    category: Cow::Borrowed("HTTP"),
+    description: Cow::Borrowed("Download an RSS or Atom feed as an array of entries"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"url": "https://example.com/feed.xml", "max_entries": 0}),
+    generate_code: |node_id, config, _input_var| {
+        let url = config["url"].as_str().unwrap_or("https://example.com/feed.xml");
+        let code = crate::http_auth::request_auth(config).map(|auth| {
+            let request = crate::throttle::request_call(config, "\"GET\"", &auth.url(url), "null", &auth.header_object());
+            let mut code = format!(
+                "{wait}    let {id}_xml = {request};\n    let {id}_out = feed_parse({id}_xml);\n",
+                wait = crate::throttle::wait_code(config, "    "),
+                id = node_id,
+                request = request,
+            );
+            // Feeds list the newest entries first; zero keeps them all
+            if let Some(max) = config["max_entries"].as_u64().filter(|n| *n > 0) {
+                code.push_str(&format!("    {id}_out = arr_slice({id}_out, 0, {max});\n", id = node_id, max = max));
+            }
+            code
+        });
+        code.unwrap_or_else(|e| format!("    // {}\n    let {}_out = null;\n", e, node_id))
+    },
+};
+
+/// Parts of a response `http_response_get` reads
+pub const RESPONSE_FIELDS: &[&str] = &["status", "ok", "header", "headers", "body", "duration_ms"];
+
+static HTTP_RESPONSE_GET: NodeDef = NodeDef {
+    name: Cow::Borrowed("http_response_get"),
+    category: Cow::Borrowed("HTTP"),
+    description: Cow::Borrowed("Read the status, a header or the body of an HTTP response"),
+    input_type: DataType::Object,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"field": "status"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let value = match config["field"].as_str().unwrap_or("status") {
+            // Responses carry header names lowercased
+            "header" => Ok(format!(
+                "get(get({}, \"headers\"), {})",
+                input,
+                hlx_string(&config["header"].as_str().unwrap_or("").trim().to_ascii_lowercase())
+            )),
+            "body" => match config["path"].as_str().map(str::trim).filter(|p| !p.is_empty()) {
+                Some(path) => serde_json_path::JsonPath::parse(path)
+                    .map(|_| format!("json_query(get({}, \"body\"), \"jsonpath\", {}, false)", input, hlx_string(path)))
+                    .map_err(|e| format!("Invalid path in {}: {}", node_id, e.to_string().lines().next().unwrap_or("").trim())),
+                None => Ok(format!("get({}, \"body\")", input)),
+            },
+            field if RESPONSE_FIELDS.contains(&field) => Ok(format!("get({}, {})", input, hlx_string(field))),
+            other => Err(format!("http_response_get: unknown field {:?} (use {})", other, RESPONSE_FIELDS.join(", "))),
+        };
+        match value {
+            Ok(value) => format!("    let {}_out = {};\n", node_id, value),
+            Err(e) => format!("    // {}\n    let {}_out = null;\n", e, node_id),
+        }
+    },
+};
+
+// ====================
+// MESSAGING NODES
+// ====================
+
+/// Attempts after the first when a service rate-limits (HTTP 429, waiting
+/// out `Retry-After`) or fails with a 5xx
+const NOTIFY_RETRIES: u64 = 3;
+
+static SLACK_SEND: NodeDef = NodeDef {
+    name: Cow::Borrowed("slack_send"),
+    category: Cow::Borrowed("Messaging"),
+    description: Cow::Borrowed("Post to a Slack incoming webhook"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"webhook": "env:SLACK_WEBHOOK_URL", "message": "", "retries": NOTIFY_RETRIES}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let body = match notify_message(config, input) {
+            Some(message) => format!("{{ \"text\": {} }}", message),
+            None => input.to_string(),
+        };
+        notify_request(node_id, &connection_expr(config["webhook"].as_str().unwrap_or("")), &body, config)
+    },
+};
+
+static DISCORD_SEND: NodeDef = NodeDef {
+    name: Cow::Borrowed("discord_send"),
+    category: Cow::Borrowed("Messaging"),
+    description: Cow::Borrowed("Post to a Discord webhook"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"webhook": "env:DISCORD_WEBHOOK_URL", "message": "", "retries": NOTIFY_RETRIES}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let body = match notify_message(config, input) {
+            Some(message) => format!("{{ \"content\": {} }}", message),
+            None => input.to_string(),
+        };
+        notify_request(node_id, &connection_expr(config["webhook"].as_str().unwrap_or("")), &body, config)
+    },
+};
+
+static TELEGRAM_SEND: NodeDef = NodeDef {
+    name: Cow::Borrowed("telegram_send"),
+    category: Cow::Borrowed("Messaging"),
+    description: Cow::Borrowed("Send a message to a Telegram chat via a bot"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({
+        "token": "env:TELEGRAM_BOT_TOKEN",
+        "chat_id": "",
+        "message": "",
+        "retries": NOTIFY_RETRIES
+    }),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let token = connection_expr(config["token"].as_str().unwrap_or(""));
+        let url = format!("\"https://api.telegram.org/bot\" + {} + \"/sendMessage\"", token);
+        let chat_id = connection_expr(config["chat_id"].as_str().unwrap_or(""));
+        let text = notify_message(config, input).unwrap_or_else(|| format!("to_string({})", input));
+        let body = format!("{{ \"chat_id\": {}, \"text\": {} }}", chat_id, text);
+        notify_request(node_id, &url, &body, config)
+    },
+};
+
+/// The rendered `message` template, or `None` to send the input as-is
+fn notify_message(config: &JsonValue, input: &str) -> Option<String> {
+    render_call(config["message"].as_str()?, input)
+}
+
+/// HLX rendering a `{{variable}}` template from `input`, or `None` for a
+/// blank template
+fn render_call(template: &str, input: &str) -> Option<String> {
+    if template.trim().is_empty() {
+        return None;
+    }
+    let variables: Vec<String> = crate::prompt::placeholders(template).iter().map(|v| hlx_string(v)).collect();
+    Some(format!("render_template({}, {}, [{}])", hlx_string(template), input, variables.join(", ")))
+}
+
+/// POST a JSON body, retrying on rate limits and server errors
+fn notify_request(node_id: &str, url: &str, body: &str, config: &JsonValue) -> String {
+    let retries = config["retries"].as_u64().unwrap_or(NOTIFY_RETRIES);
+    format!(
+        "    let {}_out = http_request_retry(\"POST\", {}, {}, {{ \"Content-Type\": \"application/json\" }}, {});\n",
+        node_id, url, body, retries
+    )
+}
+
+// ====================
+// QUEUE NODES
+// ====================
+
+static NATS_PUBLISH: NodeDef = NodeDef {
+    name: Cow::Borrowed("nats_publish"),
+    category: Cow::Borrowed("Queue"),
+    description: Cow::Borrowed("Publish a message to a NATS subject"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"server": "env:NATS_URL", "subject": "events", "message": ""}),
     generate_code: |node_id, config, input_var| {
-        let url = config["url"].as_str().unwrap_or("https://example.com");
-        let method = config["method"].as_str().unwrap_or("GET");
-        let body = input_var.unwrap_or("null");
-        format!("    let {}_out = http_request(\"{}\", \"{}\", {}, {{}});\n", node_id, method, url, body)
+        let input = input_var.unwrap_or("null");
+        let subject = config["subject"].as_str().unwrap_or("").trim();
+        if subject.is_empty() || subject.contains(['*', '>', ' ']) {
+            return format!(
+                "    // nats_publish: subject must be a single subject without wildcards, got {:?}\n    let {}_out = null;\n",
+                subject, node_id
+            );
+        }
+        let server = connection_expr(config["server"].as_str().unwrap_or("env:NATS_URL"));
+        let payload = notify_message(config, input).unwrap_or_else(|| format!("json_stringify({})", input));
+        // The input passes on, so a flow can publish and keep going
+        format!(
+            "    nats_publish({}, {}, {});\n    let {}_out = {};\n",
+            server,
+            hlx_string(subject),
+            payload,
+            node_id,
+            input
+        )
     },
 };
 
+static NATS_CONSUME: NodeDef = NodeDef {
+    name: Cow::Borrowed("nats_consume"),
+    category: Cow::Borrowed("Queue"),
+    description: Cow::Borrowed("Start a run per message on a NATS subject (server mode)"),
+    input_type: DataType::Any,
+    output_type: DataType::Object,
+    default_config: || serde_json::json!({"server": "env:NATS_URL", "subject": "events.>", "queue": "autograph"}),
+    // The server delivers each message as the run's input
+    generate_code: |node_id, _config, _input_var| format!("    let {}_out = input;\n", node_id),
+};
+
 // ====================
 // DATA - JSON NODES
 // ====================
 
 static JSON_PARSE: NodeDef = NodeDef {
-    name: "json_parse",
-    category: "Data",
-    description: "Parse JSON string",
+    name: Cow::Borrowed("json_parse"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Parse JSON string"),
+    input_type: DataType::String,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("null");
@@ -205,9 +1213,11 @@ static JSON_PARSE: NodeDef = NodeDef {
 };
 
 static JSON_STRINGIFY: NodeDef = NodeDef {
-    name: "json_stringify",
-    category: "Data",
-    description: "Convert value to JSON string",
+    name: Cow::Borrowed("json_stringify"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Convert value to JSON string"),
+    input_type: DataType::Any,
+    output_type: DataType::String,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("null");
@@ -216,27 +1226,60 @@ static JSON_STRINGIFY: NodeDef = NodeDef {
 };
 
 static JSON_GET: NodeDef = NodeDef {
-    name: "json_get",
-    category: "Data",
-    description: "Get value from JSON object",
+    name: Cow::Borrowed("json_get"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Get value from JSON object"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"key": "field"}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("null");
-        let key = config["key"].as_str().unwrap_or("field");
-        format!("    let {}_out = get({}, \"{}\");\n", node_id, input, key)
+        let key = hlx_string(config["key"].as_str().unwrap_or("field"));
+        format!("    let {}_out = get({}, {});\n", node_id, input, key)
+    },
+};
+
+static JSON_QUERY: NodeDef = NodeDef {
+    name: Cow::Borrowed("json_query"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Extract nested values with JSONPath or JMESPath"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"syntax": "jsonpath", "query": "$.items[*].id", "all": true}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let query = config["query"].as_str().unwrap_or("");
+        match crate::query::check(config) {
+            Ok(()) => format!(
+                "    let {}_out = json_query({}, {}, {}, {});\n",
+                node_id,
+                input,
+                hlx_string(crate::query::syntax(config)),
+                hlx_string(query),
+                config["all"].as_bool().unwrap_or(false)
+            ),
+            Err(e) => format!(
+                "    // Invalid query in {}: {}\n    let {}_out = null;\n",
+                node_id,
+                e.lines().next().unwrap_or("").trim(),
+                node_id
+            ),
+        }
     },
 };
 
 static JSON_SET: NodeDef = NodeDef {
-    name: "json_set",
-    category: "Data",
-    description: "Set value in JSON object",
+    name: Cow::Borrowed("json_set"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Set value in JSON object"),
+    input_type: DataType::Object,
+    output_type: DataType::Object,
     default_config: || serde_json::json!({"key": "field", "value": ""}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("{}");
-        let key = config["key"].as_str().unwrap_or("field");
-        let value = config["value"].as_str().unwrap_or("");
-        format!("    let {}_out = set({}, \"{}\", \"{}\");\n", node_id, input, key, value)
+        let key = hlx_string(config["key"].as_str().unwrap_or("field"));
+        let value = hlx_string(config["value"].as_str().unwrap_or(""));
+        format!("    let {}_out = set({}, {}, {});\n", node_id, input, key, value)
     },
 };
 
@@ -245,21 +1288,25 @@ static JSON_SET: NodeDef = NodeDef {
 // ====================
 
 static STRING_CONCAT: NodeDef = NodeDef {
-    name: "string_concat",
-    category: "Data",
-    description: "Concatenate strings",
+    name: Cow::Borrowed("string_concat"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Concatenate strings"),
+    input_type: DataType::Any,
+    output_type: DataType::String,
     default_config: || serde_json::json!({"separator": ""}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("\"\"");
-        let sep = config["separator"].as_str().unwrap_or("");
-        format!("    let {}_out = concat({}, \"{}\");\n", node_id, input, sep)
+        let sep = hlx_string(config["separator"].as_str().unwrap_or(""));
+        format!("    let {}_out = concat({}, {});\n", node_id, input, sep)
     },
 };
 
 static STRING_UPPER: NodeDef = NodeDef {
-    name: "string_upper",
-    category: "Data",
-    description: "Convert to uppercase",
+    name: Cow::Borrowed("string_upper"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Convert to uppercase"),
+    input_type: DataType::String,
+    output_type: DataType::String,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("\"\"");
@@ -268,9 +1315,11 @@ static STRING_UPPER: NodeDef = NodeDef {
 };
 
 static STRING_LOWER: NodeDef = NodeDef {
-    name: "string_lower",
-    category: "Data",
-    description: "Convert to lowercase",
+    name: Cow::Borrowed("string_lower"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Convert to lowercase"),
+    input_type: DataType::String,
+    output_type: DataType::String,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("\"\"");
@@ -279,9 +1328,11 @@ static STRING_LOWER: NodeDef = NodeDef {
 };
 
 static STRING_TRIM: NodeDef = NodeDef {
-    name: "string_trim",
-    category: "Data",
-    description: "Trim whitespace",
+    name: Cow::Borrowed("string_trim"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Trim whitespace"),
+    input_type: DataType::String,
+    output_type: DataType::String,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("\"\"");
@@ -290,9 +1341,11 @@ static STRING_TRIM: NodeDef = NodeDef {
 };
 
 static STRING_SPLIT: NodeDef = NodeDef {
-    name: "string_split",
-    category: "Data",
-    description: "Split string into array",
+    name: Cow::Borrowed("string_split"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Split string into array"),
+    input_type: DataType::String,
+    output_type: DataType::Array,
     default_config: || serde_json::json!({"delimiter": ","}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("\"\"");
@@ -303,9 +1356,11 @@ static STRING_SPLIT: NodeDef = NodeDef {
 };
 
 static STRING_REPLACE: NodeDef = NodeDef {
-    name: "string_replace",
-    category: "Data",
-    description: "Replace substring",
+    name: Cow::Borrowed("string_replace"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Replace substring"),
+    input_type: DataType::String,
+    output_type: DataType::String,
     default_config: || serde_json::json!({"find": "", "replace": ""}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("\"\"");
@@ -315,9 +1370,11 @@ static STRING_REPLACE: NodeDef = NodeDef {
 };
 
 static STRING_LENGTH: NodeDef = NodeDef {
-    name: "string_length",
-    category: "Data",
-    description: "Get string length",
+    name: Cow::Borrowed("string_length"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Get string length"),
+    input_type: DataType::String,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("\"\"");
@@ -325,47 +1382,188 @@ static STRING_LENGTH: NodeDef = NodeDef {
     },
 };
 
+static TEMPLATE_RENDER: NodeDef = NodeDef {
+    name: Cow::Borrowed("template_render"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Render a {{field}} template from the input object"),
+    input_type: DataType::Object,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({"template": "Hello {{name}}!"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("{}");
+        let template = config["template"].as_str().unwrap_or("");
+        let fields: Vec<String> = crate::prompt::placeholders(template).iter().map(|f| hlx_string(f)).collect();
+        format!(
+            "    let {}_out = render_template({}, {}, [{}]);\n",
+            node_id,
+            hlx_string(template),
+            input,
+            fields.join(", ")
+        )
+    },
+};
+
+static REGEX_MATCH: NodeDef = NodeDef {
+    name: Cow::Borrowed("regex_match"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Test text against a regular expression"),
+    input_type: DataType::String,
+    output_type: DataType::Boolean,
+    default_config: || serde_json::json!({"pattern": "^\\d+$", "flags": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        regex_call(node_id, config, |pattern| format!("regex_match({}, {})", input, pattern))
+    },
+};
+
+static REGEX_EXTRACT: NodeDef = NodeDef {
+    name: Cow::Borrowed("regex_extract"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Extract capture groups into an object or array"),
+    input_type: DataType::String,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"pattern": "(?P<user>[^@\\s]+)@(?P<domain>\\S+)", "flags": "", "all": false}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        let all = config["all"].as_bool().unwrap_or(false);
+        regex_call(node_id, config, |pattern| format!("regex_extract({}, {}, {})", input, pattern, all))
+    },
+};
+
+static REGEX_REPLACE: NodeDef = NodeDef {
+    name: Cow::Borrowed("regex_replace"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Replace regex matches ($1, $name refer to groups)"),
+    input_type: DataType::String,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({"pattern": "\\s+", "replacement": " ", "flags": "", "all": true}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        let replacement = hlx_string(config["replacement"].as_str().unwrap_or(""));
+        let all = config["all"].as_bool().unwrap_or(true);
+        regex_call(node_id, config, |pattern| format!("regex_replace({}, {}, {}, {})", input, pattern, replacement, all))
+    },
+};
+
+/// Emit a regex builtin call, or `null` with the error for an invalid pattern
+fn regex_call(node_id: &str, config: &JsonValue, call: impl Fn(&str) -> String) -> String {
+    match crate::patterns::compile(config) {
+        Ok(_) => format!("    let {}_out = {};\n", node_id, call(&hlx_string(&crate::patterns::pattern(config)))),
+        Err(e) => format!(
+            "    // Invalid regex in {}: {}\n    let {}_out = null;\n",
+            node_id,
+            e.lines().last().unwrap_or("").trim(),
+            node_id
+        ),
+    }
+}
+
 // ====================
 // DATA - ARRAY NODES
 // ====================
 
+/// Iteration cap for the loops of `array_map`, `array_filter` and `array_reduce`
+const MAX_ITEMS: u64 = 1_000_000;
+
+/// The node's item expression (`key` in its config) compiled with `names`
+/// bound to `{node_id}_{name}` variables
+fn item_expression(node_id: &str, config: &JsonValue, key: &str, names: &[&str]) -> Result<String, String> {
+    let vars: Vec<String> = names.iter().map(|name| format!("{}_{}", node_id, name)).collect();
+    let bindings: Vec<(&str, &str)> = names.iter().copied().zip(vars.iter().map(String::as_str)).collect();
+    crate::expr::compile(config[key].as_str().unwrap_or(""), &bindings)
+}
+
+/// Loop over the input array with `{node_id}_item` and `{node_id}_index`
+/// bound, running `body` (already indented) for each element
+fn item_loop(node_id: &str, input: &str, setup: &str, body: &str) -> String {
+    format!(
+        "    let {id}_items = {input};\n{setup}    let {id}_index = 0;\n    loop ({id}_index < len({id}_items), {max}) {{\n        let {id}_item = {id}_items[{id}_index];\n{body}        {id}_index = {id}_index + 1;\n    }}\n",
+        id = node_id,
+        input = input,
+        setup = setup,
+        max = MAX_ITEMS,
+        body = body,
+    )
+}
+
+/// Generated code for an expression that didn't compile
+fn invalid_expression(type_name: &str, node_id: &str, error: &str) -> String {
+    format!("    // {}: {}\n    let {}_out = null;\n", type_name, error, node_id)
+}
+
 static ARRAY_MAP: NodeDef = NodeDef {
-    name: "array_map",
-    category: "Data",
-    description: "Map function over array",
-    default_config: || serde_json::json!({"function": ""}),
-    generate_code: |node_id, _config, _input_var| {
-        // TODO: Requires lambda support
-        format!("    // TODO: Implement array_map\n    let {}_out = [];\n", node_id)
+    name: Cow::Borrowed("array_map"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Transform each element with an expression, e.g. item.name"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"expression": "item"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        match item_expression(node_id, config, "expression", &["item", "index"]) {
+            Ok(expr) => item_loop(
+                node_id,
+                input,
+                &format!("    let {}_out = [];\n", node_id),
+                &format!("        {id}_out = arr_concat({id}_out, [{expr}]);\n", id = node_id, expr = expr),
+            ),
+            Err(e) => invalid_expression("array_map", node_id, &e),
+        }
     },
 };
 
 static ARRAY_FILTER: NodeDef = NodeDef {
-    name: "array_filter",
-    category: "Data",
-    description: "Filter array elements",
-    default_config: || serde_json::json!({"condition": ""}),
-    generate_code: |node_id, _config, _input_var| {
-        // TODO: Requires lambda support
-        format!("    // TODO: Implement array_filter\n    let {}_out = [];\n", node_id)
+    name: Cow::Borrowed("array_filter"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Keep elements matching a condition, e.g. item.price > 10"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"condition": "item"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        match item_expression(node_id, config, "condition", &["item", "index"]) {
+            Ok(condition) => item_loop(
+                node_id,
+                input,
+                &format!("    let {}_out = [];\n", node_id),
+                &format!(
+                    "        if ({cond}) {{\n            {id}_out = arr_concat({id}_out, [{id}_item]);\n        }}\n",
+                    id = node_id,
+                    cond = condition
+                ),
+            ),
+            Err(e) => invalid_expression("array_filter", node_id, &e),
+        }
     },
 };
 
 static ARRAY_REDUCE: NodeDef = NodeDef {
-    name: "array_reduce",
-    category: "Data",
-    description: "Reduce array to single value",
-    default_config: || serde_json::json!({"initial": 0}),
-    generate_code: |node_id, _config, _input_var| {
-        // TODO: Requires lambda support
-        format!("    // TODO: Implement array_reduce\n    let {}_out = null;\n", node_id)
+    name: Cow::Borrowed("array_reduce"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Fold elements into one value, e.g. acc + item.total"),
+    input_type: DataType::Array,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"expression": "acc + item", "initial": 0}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        match item_expression(node_id, config, "expression", &["acc", "item", "index"]) {
+            Ok(expr) => item_loop(
+                node_id,
+                input,
+                &format!("    let {}_acc = {};\n", node_id, hlx_literal(&config["initial"])),
+                &format!("        {id}_acc = {expr};\n", id = node_id, expr = expr),
+            ) + &format!("    let {id}_out = {id}_acc;\n", id = node_id),
+            Err(e) => invalid_expression("array_reduce", node_id, &e),
+        }
     },
 };
 
 static ARRAY_SLICE: NodeDef = NodeDef {
-    name: "array_slice",
-    category: "Data",
-    description: "Slice array",
+    name: Cow::Borrowed("array_slice"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Slice array"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
     default_config: || serde_json::json!({"start": 0, "end": 10}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("[]");
@@ -376,9 +1574,11 @@ static ARRAY_SLICE: NodeDef = NodeDef {
 };
 
 static ARRAY_CONCAT: NodeDef = NodeDef {
-    name: "array_concat",
-    category: "Data",
-    description: "Concatenate arrays",
+    name: Cow::Borrowed("array_concat"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Concatenate arrays"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("[]");
@@ -387,9 +1587,11 @@ static ARRAY_CONCAT: NodeDef = NodeDef {
 };
 
 static ARRAY_SORT: NodeDef = NodeDef {
-    name: "array_sort",
-    category: "Data",
-    description: "Sort array",
+    name: Cow::Borrowed("array_sort"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Sort array"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
     default_config: || serde_json::json!({"order": "asc"}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("[]");
@@ -399,9 +1601,11 @@ static ARRAY_SORT: NodeDef = NodeDef {
 };
 
 static ARRAY_LENGTH: NodeDef = NodeDef {
-    name: "array_length",
-    category: "Data",
-    description: "Get array length",
+    name: Cow::Borrowed("array_length"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Get array length"),
+    input_type: DataType::Array,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("[]");
@@ -409,66 +1613,272 @@ static ARRAY_LENGTH: NodeDef = NodeDef {
     },
 };
 
-// ====================
-// DATA - OBJECT NODES
-// ====================
-
-static OBJECT_GET: NodeDef = NodeDef {
-    name: "object_get",
-    category: "Data",
-    description: "Get object property",
-    default_config: || serde_json::json!({"key": "field"}),
+static ARRAY_SAMPLE: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_sample"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Pick random elements from array"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"count": 1}),
     generate_code: |node_id, config, input_var| {
-        let input = input_var.unwrap_or("{}");
-        let key = config["key"].as_str().unwrap_or("field");
-        format!("    let {}_out = get({}, \"{}\");\n", node_id, input, key)
+        let input = input_var.unwrap_or("[]");
+        let count = config["count"].as_i64().unwrap_or(1);
+        let seed = config["seed"]
+            .as_u64()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "null".to_string());
+        format!("    let {}_out = arr_sample({}, {}, {});\n", node_id, input, count, seed)
     },
 };
 
-static OBJECT_SET: NodeDef = NodeDef {
-    name: "object_set",
-    category: "Data",
-    description: "Set object property",
-    default_config: || serde_json::json!({"key": "field", "value": ""}),
+// Aggregations take an optional `field`: when set, each element is an
+// object and the field's value is used; when empty, the elements themselves
+
+/// The `field` (or `key`) config as an HLX argument, `null` when empty
+fn field_arg(config: &JsonValue, key: &str) -> String {
+    match config[key].as_str().map(str::trim).filter(|f| !f.is_empty()) {
+        Some(field) => hlx_string(field),
+        None => "null".to_string(),
+    }
+}
+
+static ARRAY_GROUP_BY: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_group_by"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Group objects into {value: [items]} by a key"),
+    input_type: DataType::Array,
+    output_type: DataType::Object,
+    default_config: || serde_json::json!({"key": "category"}),
     generate_code: |node_id, config, input_var| {
-        let input = input_var.unwrap_or("{}");
-        let key = config["key"].as_str().unwrap_or("field");
-        let value = config["value"].as_str().unwrap_or("");
-        format!("    let {}_out = set({}, \"{}\", \"{}\");\n", node_id, input, key, value)
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_group_by({}, {});\n", node_id, input, field_arg(config, "key"))
     },
 };
 
-static OBJECT_KEYS: NodeDef = NodeDef {
-    name: "object_keys",
-    category: "Data",
-    description: "Get object keys",
-    default_config: || serde_json::json!({}),
-    generate_code: |node_id, _config, input_var| {
-        let input = input_var.unwrap_or("{}");
-        format!("    let {}_out = keys({});\n", node_id, input)
+static ARRAY_SUM: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_sum"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Sum numbers, or a numeric field of objects"),
+    input_type: DataType::Array,
+    output_type: DataType::Number,
+    default_config: || serde_json::json!({"field": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_sum({}, {});\n", node_id, input, field_arg(config, "field"))
     },
 };
 
-static OBJECT_VALUES: NodeDef = NodeDef {
-    name: "object_values",
-    category: "Data",
-    description: "Get object values",
-    default_config: || serde_json::json!({}),
-    generate_code: |node_id, _config, input_var| {
-        let input = input_var.unwrap_or("{}");
-        format!("    let {}_out = values({});\n", node_id, input)
+static ARRAY_AVERAGE: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_average"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Mean of numbers, or of a numeric field (null when empty)"),
+    input_type: DataType::Array,
+    output_type: DataType::Number,
+    default_config: || serde_json::json!({"field": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_avg({}, {});\n", node_id, input, field_arg(config, "field"))
     },
 };
 
-static OBJECT_HAS_KEY: NodeDef = NodeDef {
-    name: "object_has_key",
-    category: "Data",
-    description: "Check if object has key",
-    default_config: || serde_json::json!({"key": "field"}),
+static ARRAY_MIN: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_min"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Smallest value, or smallest field value (null when empty)"),
+    input_type: DataType::Array,
+    output_type: DataType::Number,
+    default_config: || serde_json::json!({"field": ""}),
     generate_code: |node_id, config, input_var| {
-        let input = input_var.unwrap_or("{}");
-        let key = config["key"].as_str().unwrap_or("field");
-        format!("    let {}_out = has_key({}, \"{}\");\n", node_id, input, key)
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_min({}, {});\n", node_id, input, field_arg(config, "field"))
+    },
+};
+
+static ARRAY_MAX: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_max"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Largest value, or largest field value (null when empty)"),
+    input_type: DataType::Array,
+    output_type: DataType::Number,
+    default_config: || serde_json::json!({"field": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_max({}, {});\n", node_id, input, field_arg(config, "field"))
+    },
+};
+
+static ARRAY_COUNT: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_count"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Count elements, or count per value of a key as {value: n}"),
+    input_type: DataType::Array,
+    output_type: DataType::Number,
+    default_config: || serde_json::json!({"key": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        if config["key"].as_str().is_none_or(|k| k.trim().is_empty()) {
+            format!("    let {}_out = len({});\n", node_id, input)
+        } else {
+            format!("    let {}_out = arr_count_by({}, {});\n", node_id, input, field_arg(config, "key"))
+        }
+    },
+};
+
+static ARRAY_DISTINCT: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_distinct"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Unique elements, or unique values of a field, in first-seen order"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"field": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_distinct({}, {});\n", node_id, input, field_arg(config, "field"))
+    },
+};
+
+static ARRAY_ZIP: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_zip"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Pair up items of a list of arrays: [[1,2],[a,b]] -> [[1,a],[2,b]]"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_zip({});\n", node_id, input)
+    },
+};
+
+static ARRAY_FLATTEN: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_flatten"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Flatten nested arrays by depth levels (0 = all the way)"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"depth": 1}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        let depth = config["depth"].as_u64().unwrap_or(1);
+        format!("    let {}_out = arr_flatten({}, {});\n", node_id, input, depth)
+    },
+};
+
+static ARRAY_UNIQUE: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_unique"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Remove duplicate elements, keeping first occurrences"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_distinct({}, null);\n", node_id, input)
+    },
+};
+
+static ARRAY_CHUNK: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_chunk"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Split into batches of size N (the last may be shorter)"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"size": 10}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        match config["size"].as_u64().filter(|size| *size > 0) {
+            Some(size) => format!("    let {}_out = arr_chunk({}, {});\n", node_id, input, size),
+            None => format!(
+                "    // array_chunk: size must be a positive integer\n    let {}_out = null;\n",
+                node_id
+            ),
+        }
+    },
+};
+
+static ARRAY_REVERSE: NodeDef = NodeDef {
+    name: Cow::Borrowed("array_reverse"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Reverse element order"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = arr_reverse({});\n", node_id, input)
+    },
+};
+
+// ====================
+// DATA - OBJECT NODES
+// ====================
+
+static OBJECT_GET: NodeDef = NodeDef {
+    name: Cow::Borrowed("object_get"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Get object property"),
+    input_type: DataType::Object,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"key": "field"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("{}");
+        let key = hlx_string(config["key"].as_str().unwrap_or("field"));
+        format!("    let {}_out = get({}, {});\n", node_id, input, key)
+    },
+};
+
+static OBJECT_SET: NodeDef = NodeDef {
+    name: Cow::Borrowed("object_set"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Set object property"),
+    input_type: DataType::Object,
+    output_type: DataType::Object,
+    default_config: || serde_json::json!({"key": "field", "value": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("{}");
+        let key = hlx_string(config["key"].as_str().unwrap_or("field"));
+        let value = hlx_string(config["value"].as_str().unwrap_or(""));
+        format!("    let {}_out = set({}, {}, {});\n", node_id, input, key, value)
+    },
+};
+
+static OBJECT_KEYS: NodeDef = NodeDef {
+    name: Cow::Borrowed("object_keys"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Get object keys"),
+    input_type: DataType::Object,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("{}");
+        format!("    let {}_out = keys({});\n", node_id, input)
+    },
+};
+
+static OBJECT_VALUES: NodeDef = NodeDef {
+    name: Cow::Borrowed("object_values"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Get object values"),
+    input_type: DataType::Object,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("{}");
+        format!("    let {}_out = values({});\n", node_id, input)
+    },
+};
+
+static OBJECT_HAS_KEY: NodeDef = NodeDef {
+    name: Cow::Borrowed("object_has_key"),
+    category: Cow::Borrowed("Data"),
+    description: Cow::Borrowed("Check if object has key"),
+    input_type: DataType::Object,
+    output_type: DataType::Boolean,
+    default_config: || serde_json::json!({"key": "field"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("{}");
+        let key = hlx_string(config["key"].as_str().unwrap_or("field"));
+        format!("    let {}_out = has_key({}, {});\n", node_id, input, key)
     },
 };
 
@@ -477,103 +1887,681 @@ static OBJECT_HAS_KEY: NodeDef = NodeDef {
 // ====================
 
 static FILE_READ: NodeDef = NodeDef {
-    name: "file_read",
-    category: "Files",
-    description: "Read file contents",
+    name: Cow::Borrowed("file_read"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Read file contents"),
+    input_type: DataType::Any,
+    output_type: DataType::String,
     default_config: || serde_json::json!({"path": "file.txt"}),
     generate_code: |node_id, config, _input_var| {
-        let path = config["path"].as_str().unwrap_or("file.txt");
-        format!("    let {}_out = read_file(\"{}\");\n", node_id, path)
+        let path = hlx_string(config["path"].as_str().unwrap_or("file.txt"));
+        format!("    let {}_out = read_file({});\n", node_id, path)
     },
 };
 
 static FILE_WRITE: NodeDef = NodeDef {
-    name: "file_write",
-    category: "Files",
-    description: "Write file contents",
+    name: Cow::Borrowed("file_write"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Write file contents"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"path": "file.txt"}),
     generate_code: |node_id, config, input_var| {
-        let path = config["path"].as_str().unwrap_or("file.txt");
+        let path = hlx_string(config["path"].as_str().unwrap_or("file.txt"));
         let content = input_var.unwrap_or("\"\"");
-        format!("    let {}_out = write_file(\"{}\", {});\n", node_id, path, content)
+        format!("    let {}_out = write_file({}, {});\n", node_id, path, content)
+    },
+};
+
+static FILE_READ_BINARY: NodeDef = NodeDef {
+    name: Cow::Borrowed("file_read_binary"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Read a file as bytes"),
+    input_type: DataType::Any,
+    output_type: DataType::Bytes,
+    default_config: || serde_json::json!({"path": "image.png"}),
+    generate_code: |node_id, config, _input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("image.png"));
+        format!("    let {}_out = {{ {}: read_file_base64({}) }};\n", node_id, hlx_string(crate::bytes::BYTES_KEY), path)
+    },
+};
+
+static FILE_WRITE_BINARY: NodeDef = NodeDef {
+    name: Cow::Borrowed("file_write_binary"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Write bytes to a file"),
+    input_type: DataType::Bytes,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"path": "image.png"}),
+    generate_code: |node_id, config, input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("image.png"));
+        let input = input_var.unwrap_or("null");
+        format!(
+            "    let {}_out = write_file_base64({}, get({}, {}));\n",
+            node_id,
+            path,
+            input,
+            hlx_string(crate::bytes::BYTES_KEY)
+        )
+    },
+};
+
+/// Code binding `{node_id}_items` to `lines` (an HLX array of lines)
+/// parsed as `format`; CSV rows become objects keyed by `header` if it
+/// isn't `null`, and blank JSONL lines are skipped
+fn parse_lines_code(node_id: &str, format: &str, lines: &str, header: &str, indent: &str) -> String {
+    match format {
+        "csv" => format!("{}let {}_items = csv_parse_lines({}, {});\n", indent, node_id, lines, header),
+        "jsonl" => format!(
+            "{i}let {id}_items = [];\n{i}let {id}_line = 0;\n{i}loop ({id}_line < len({lines}), {max}) {{\n{i}    if (len(trim({lines}[{id}_line])) > 0) {{\n{i}        {id}_items = arr_concat({id}_items, [json_parse({lines}[{id}_line])]);\n{i}    }}\n{i}    {id}_line = {id}_line + 1;\n{i}}}\n",
+            i = indent,
+            id = node_id,
+            lines = lines,
+            max = MAX_ITEMS,
+        ),
+        _ => format!("{}let {}_items = {};\n", indent, node_id, lines),
+    }
+}
+
+/// Format of a streaming node, or the comment explaining why it's invalid
+fn stream_format(type_name: &str, node_id: &str, config: &JsonValue) -> Result<String, String> {
+    let format = config["format"].as_str().unwrap_or("lines");
+    match crate::streaming::FORMATS.contains(&format) {
+        true => Ok(format.to_string()),
+        false => Err(format!("    // {}: unknown format {:?}\n    let {}_out = null;\n", type_name, format, node_id)),
+    }
+}
+
+/// Declare `{node_id}_header` (the CSV header line, or `null`) and return
+/// the first data line
+fn header_code(node_id: &str, format: &str, config: &JsonValue, path: &str) -> (String, u64) {
+    if format == "csv" && config["header"].as_bool().unwrap_or(true) {
+        (format!("    let {id}_header = read_lines({path}, 0, 1)[0];\n", id = node_id, path = path), 1)
+    } else {
+        (format!("    let {}_header = null;\n", node_id), 0)
+    }
+}
+
+static FILE_READ_LINES: NodeDef = NodeDef {
+    name: Cow::Borrowed("file_read_lines"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Read a window of lines without loading the whole file"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"path": "data.txt", "start": 0, "count": 1000, "format": "lines"}),
+    generate_code: |node_id, config, _input_var| {
+        let format = match stream_format("file_read_lines", node_id, config) {
+            Ok(format) => format,
+            Err(code) => return code,
+        };
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.txt"));
+        let (mut code, first) = header_code(node_id, &format, config, &path);
+        code.push_str(&format!(
+            "    let {id}_lines = read_lines({path}, {start}, {count});\n",
+            id = node_id,
+            path = path,
+            start = first + config["start"].as_u64().unwrap_or(0),
+            count = config["count"].as_u64().unwrap_or(1000),
+        ));
+        code.push_str(&parse_lines_code(node_id, &format, &format!("{}_lines", node_id), &format!("{}_header", node_id), "    "));
+        code.push_str(&format!("    let {id}_out = {id}_items;\n", id = node_id));
+        code
+    },
+};
+
+static FILE_STREAM: NodeDef = NodeDef {
+    name: Cow::Borrowed("file_stream"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Run a flow on each chunk of a large text, CSV or JSONL file"),
+    input_type: DataType::Any,
+    output_type: DataType::Object,
+    default_config: || serde_json::json!({
+        "path": "data.jsonl",
+        "format": "jsonl",
+        "chunk_lines": crate::streaming::DEFAULT_CHUNK_LINES,
+        "flow": "",
+        "max_chunks": 0
+    }),
+    generate_code: |node_id, config, _input_var| {
+        let format = match stream_format("file_stream", node_id, config) {
+            Ok(format) => format,
+            Err(code) => return code,
+        };
+        let name = config["flow"].as_str().unwrap_or("");
+        if !valid_flow_name(name) {
+            return format!("    // file_stream: invalid flow name {:?}\n    let {}_out = null;\n", name, node_id);
+        }
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.jsonl"));
+        let chunk_lines = config["chunk_lines"].as_u64().filter(|n| *n > 0).unwrap_or(crate::streaming::DEFAULT_CHUNK_LINES);
+        // Zero chunks means the whole file
+        let max_chunks = config["max_chunks"].as_u64().filter(|n| *n > 0).unwrap_or(MAX_ITEMS);
+
+        // Each window resumes at the byte the last one ended at, so no line is read twice
+        let mut code = match format == "csv" && config["header"].as_bool().unwrap_or(true) {
+            true => format!(
+                "    let {id}_head = read_lines_from({path}, 0, 1);\n    let {id}_header = get({id}_head, \"lines\")[0];\n    let {id}_pos = get({id}_head, \"next\");\n",
+                id = node_id,
+                path = path,
+            ),
+            false => format!("    let {id}_header = null;\n    let {id}_pos = 0;\n", id = node_id),
+        };
+        code.push_str(&format!(
+            "    let {id}_offset = 0;\n    let {id}_chunk = 0;\n    let {id}_last = null;\n    let {id}_more = true;\n    loop ({id}_more, {max}) {{\n        let {id}_read = read_lines_from({path}, {id}_pos, {size});\n        let {id}_lines = get({id}_read, \"lines\");\n        if (len({id}_lines) == 0) {{\n            {id}_more = false;\n        }} else {{\n",
+            id = node_id,
+            max = max_chunks,
+            path = path,
+            size = chunk_lines,
+        ));
+        code.push_str(&parse_lines_code(node_id, &format, &format!("{}_lines", node_id), &format!("{}_header", node_id), "            "));
+        // Only the last result is kept; earlier ones would pile up over a large file
+        code.push_str(&format!(
+            "            {id}_last = run_flow({flow}, {{ \"chunk\": {id}_chunk, \"offset\": {id}_offset, \"items\": {id}_items }});\n            {id}_pos = get({id}_read, \"next\");\n            {id}_offset = {id}_offset + len({id}_lines);\n            {id}_chunk = {id}_chunk + 1;\n            if (len({id}_lines) < {size}) {{\n                {id}_more = false;\n            }}\n        }}\n    }}\n    let {id}_out = {{ \"chunks\": {id}_chunk, \"lines\": {id}_offset, \"last\": {id}_last }};\n",
+            id = node_id,
+            flow = hlx_string(name),
+            size = chunk_lines,
+        ));
+        code
     },
 };
 
 static FILE_EXISTS: NodeDef = NodeDef {
-    name: "file_exists",
-    category: "Files",
-    description: "Check if file exists",
+    name: Cow::Borrowed("file_exists"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Check if file exists"),
+    input_type: DataType::Any,
+    output_type: DataType::Boolean,
     default_config: || serde_json::json!({"path": "file.txt"}),
     generate_code: |node_id, config, _input_var| {
-        let path = config["path"].as_str().unwrap_or("file.txt");
-        format!("    let {}_out = file_exists(\"{}\");\n", node_id, path)
+        let path = hlx_string(config["path"].as_str().unwrap_or("file.txt"));
+        format!("    let {}_out = file_exists({});\n", node_id, path)
     },
 };
 
 static FILE_DELETE: NodeDef = NodeDef {
-    name: "file_delete",
-    category: "Files",
-    description: "Delete file",
+    name: Cow::Borrowed("file_delete"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Delete file"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"path": "file.txt"}),
     generate_code: |node_id, config, _input_var| {
-        let path = config["path"].as_str().unwrap_or("file.txt");
-        format!("    let {}_out = delete_file(\"{}\");\n", node_id, path)
+        let path = hlx_string(config["path"].as_str().unwrap_or("file.txt"));
+        format!("    let {}_out = delete_file({});\n", node_id, path)
     },
 };
 
 static FILE_LIST: NodeDef = NodeDef {
-    name: "file_list",
-    category: "Files",
-    description: "List files in directory",
+    name: Cow::Borrowed("file_list"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("List files in directory"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
     default_config: || serde_json::json!({"path": "."}),
     generate_code: |node_id, config, _input_var| {
-        let path = config["path"].as_str().unwrap_or(".");
-        format!("    let {}_out = list_files(\"{}\");\n", node_id, path)
+        let path = hlx_string(config["path"].as_str().unwrap_or("."));
+        format!("    let {}_out = list_files({});\n", node_id, path)
     },
 };
 
 static DIR_CREATE: NodeDef = NodeDef {
-    name: "dir_create",
-    category: "Files",
-    description: "Create directory",
+    name: Cow::Borrowed("dir_create"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Create directory"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"path": "new_dir"}),
     generate_code: |node_id, config, _input_var| {
-        let path = config["path"].as_str().unwrap_or("new_dir");
-        format!("    let {}_out = create_dir(\"{}\");\n", node_id, path)
+        let path = hlx_string(config["path"].as_str().unwrap_or("new_dir"));
+        format!("    let {}_out = create_dir({});\n", node_id, path)
     },
 };
 
 static JSON_READ: NodeDef = NodeDef {
-    name: "json_read",
-    category: "Files",
-    description: "Read JSON file",
+    name: Cow::Borrowed("json_read"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Read JSON file"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"path": "data.json"}),
     generate_code: |node_id, config, _input_var| {
-        let path = config["path"].as_str().unwrap_or("data.json");
-        format!("    let {}_out = read_json(\"{}\");\n", node_id, path)
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.json"));
+        format!("    let {}_out = read_json({});\n", node_id, path)
     },
 };
 
 static JSON_WRITE: NodeDef = NodeDef {
-    name: "json_write",
-    category: "Files",
-    description: "Write JSON file",
+    name: Cow::Borrowed("json_write"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Write JSON file"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"path": "data.json"}),
     generate_code: |node_id, config, input_var| {
-        let path = config["path"].as_str().unwrap_or("data.json");
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.json"));
         let content = input_var.unwrap_or("null");
-        format!("    let {}_out = write_json(\"{}\", {});\n", node_id, path, content)
+        format!("    let {}_out = write_json({}, {});\n", node_id, path, content)
+    },
+};
+
+static JSONL_READ: NodeDef = NodeDef {
+    name: Cow::Borrowed("jsonl_read"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Read a JSONL (NDJSON) file as an array of records"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"path": "data.jsonl", "max_lines": 0}),
+    generate_code: |node_id, config, _input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.jsonl"));
+        // Zero reads the whole file
+        let max_lines = config["max_lines"].as_u64().filter(|n| *n > 0).unwrap_or(MAX_ITEMS);
+        let mut code = format!("    let {}_lines = read_lines({}, 0, {});\n", node_id, path, max_lines);
+        code.push_str(&parse_lines_code(node_id, "jsonl", &format!("{}_lines", node_id), "null", "    "));
+        code.push_str(&format!("    let {id}_out = {id}_items;\n", id = node_id));
+        code
+    },
+};
+
+/// Code binding `{node_id}_text` to the JSONL text of `records`, one line
+/// per element with a trailing newline
+fn jsonl_text_code(node_id: &str, records: &str) -> String {
+    item_loop(
+        node_id,
+        records,
+        &format!("    let {}_lines = [];\n", node_id),
+        &format!("        {id}_lines = arr_concat({id}_lines, [json_stringify({id}_item)]);\n", id = node_id),
+    ) + &format!("    let {id}_text = concat(arr_concat({id}_lines, [\"\"]), \"\\n\");\n", id = node_id)
+}
+
+static JSONL_WRITE: NodeDef = NodeDef {
+    name: Cow::Borrowed("jsonl_write"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Write an array as a JSONL (NDJSON) file, one record per line"),
+    input_type: DataType::Array,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"path": "data.jsonl"}),
+    generate_code: |node_id, config, input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.jsonl"));
+        jsonl_text_code(node_id, input_var.unwrap_or("[]"))
+            + &format!("    let {id}_out = write_file({path}, {id}_text);\n", id = node_id, path = path)
+    },
+};
+
+static JSONL_APPEND: NodeDef = NodeDef {
+    name: Cow::Borrowed("jsonl_append"),
+    category: Cow::Borrowed("Files"),
+    description: Cow::Borrowed("Append the input to a JSONL (NDJSON) file as a line, or a line per element"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"path": "data.jsonl", "each": false}),
+    generate_code: |node_id, config, input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.jsonl"));
+        let input = input_var.unwrap_or("null");
+        // Without "each", an array input is a single record
+        let records = match config["each"].as_bool().unwrap_or(false) {
+            true => input.to_string(),
+            false => format!("[{}]", input),
+        };
+        jsonl_text_code(node_id, &records)
+            + &format!("    let {id}_out = append_file({path}, {id}_text);\n", id = node_id, path = path)
     },
 };
 
+// ====================
+// DATABASE NODES
+// ====================
+
+/// Connection string schemes the runtime has drivers for
+pub const DB_SCHEMES: &[&str] = &["postgres", "postgresql", "mysql", "sqlite"];
+
+static DB_QUERY: NodeDef = NodeDef {
+    name: Cow::Borrowed("db_query"),
+    category: Cow::Borrowed("Database"),
+    description: Cow::Borrowed("Run a SQL query, returning rows as objects"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({
+        "connection": "env:DATABASE_URL",
+        "sql": "SELECT * FROM users WHERE id = $1",
+        "params": ["$input.id"]
+    }),
+    generate_code: |node_id, config, input_var| db_call("db_query", node_id, config, input_var),
+};
+
+static DB_EXECUTE: NodeDef = NodeDef {
+    name: Cow::Borrowed("db_execute"),
+    category: Cow::Borrowed("Database"),
+    description: Cow::Borrowed("Run a SQL statement, returning the affected row count"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({
+        "connection": "env:DATABASE_URL",
+        "sql": "UPDATE users SET name = $1 WHERE id = $2",
+        "params": ["$input.name", "$input.id"]
+    }),
+    generate_code: |node_id, config, input_var| db_call("db_execute", node_id, config, input_var),
+};
+
+/// Emit a database builtin call. SQL and parameters are passed separately
+/// so values are always bound by the driver, never spliced into the SQL.
+fn db_call(function: &str, node_id: &str, config: &JsonValue, input_var: Option<&str>) -> String {
+    let input = input_var.unwrap_or("null");
+    let connection = config["connection"].as_str().unwrap_or("");
+    let sql = hlx_string(config["sql"].as_str().unwrap_or(""));
+
+    let mut code = String::new();
+    if let Some(scheme) = connection.split_once("://").map(|(scheme, _)| scheme) {
+        if !DB_SCHEMES.contains(&scheme) {
+            code.push_str(&format!("    // Unsupported database scheme: {:?}\n", scheme));
+        }
+    }
+
+    let params: Vec<String> = match config["params"].as_array() {
+        Some(params) => params.iter().map(|param| db_param(param, input)).collect(),
+        // Without params, an array input supplies them positionally
+        None => return format!(
+            "{}    let {}_out = {}({}, {}, {});\n",
+            code, node_id, function, connection_expr(connection), sql, input
+        ),
+    };
+
+    code.push_str(&format!(
+        "    let {}_out = {}({}, {}, [{}]);\n",
+        node_id, function, connection_expr(connection), sql, params.join(", ")
+    ));
+    code
+}
+
+/// HLX for a parameter: `$input` and `$input.<key>` read from the node's
+/// input; anything else is a literal value
+fn db_param(param: &JsonValue, input: &str) -> String {
+    match param.as_str() {
+        Some("$input") => input.to_string(),
+        Some(p) if p.starts_with("$input.") => format!("get({}, {})", input, hlx_string(&p["$input.".len()..])),
+        Some(p) => hlx_string(p),
+        None if param.is_array() || param.is_object() => format!("json_parse({})", hlx_string(&param.to_string())),
+        None => param.to_string(),
+    }
+}
+
+// ====================
+// STATE NODES
+// ====================
+
+/// Table of a flow's state store: JSON values by key
+const STATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS state (key TEXT PRIMARY KEY, value TEXT NOT NULL)";
+
+/// Code creating the state table and binding `{node_id}_key`, or the
+/// comment explaining why the node can't run
+fn state_setup(type_name: &str, node_id: &str, config: &JsonValue, input: &str) -> Result<(String, String), String> {
+    if config["key"].as_str().is_none_or(|key| key.trim().is_empty()) {
+        return Err(format!("    // {}: no key\n    let {}_out = null;\n", type_name, node_id));
+    }
+    let store = connection_expr(&crate::state_store::connection());
+    let code = format!(
+        "    db_execute({store}, {table}, []);\n    let {id}_key = {key};\n",
+        store = store,
+        table = hlx_string(STATE_TABLE),
+        id = node_id,
+        key = db_param(&config["key"], input),
+    );
+    Ok((code, store))
+}
+
+static STATE_GET: NodeDef = NodeDef {
+    name: Cow::Borrowed("state_get"),
+    category: Cow::Borrowed("State"),
+    description: Cow::Borrowed("Read a value this flow stored in an earlier run"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"key": "cursor", "default": null}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let (mut code, store) = match state_setup("state_get", node_id, config, input) {
+            Ok(setup) => setup,
+            Err(code) => return code,
+        };
+        code.push_str(&format!(
+            "    let {id}_rows = db_query({store}, \"SELECT value FROM state WHERE key = ?1\", [{id}_key]);\n    let {id}_out = {default};\n    if (len({id}_rows) > 0) {{\n        {id}_out = json_parse(get({id}_rows[0], \"value\"));\n    }}\n",
+            id = node_id,
+            store = store,
+            default = db_param(&config["default"], input),
+        ));
+        code
+    },
+};
+
+static STATE_SET: NodeDef = NodeDef {
+    name: Cow::Borrowed("state_set"),
+    category: Cow::Borrowed("State"),
+    description: Cow::Borrowed("Store a value for later runs of this flow, passing the input on"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"key": "cursor", "value": "$input"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let (mut code, store) = match state_setup("state_set", node_id, config, input) {
+            Ok(setup) => setup,
+            Err(code) => return code,
+        };
+        let value = match config.get("value") {
+            Some(value) => db_param(value, input),
+            None => input.to_string(),
+        };
+        code.push_str(&format!(
+            "    db_execute({store}, \"INSERT INTO state (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value\", [{id}_key, json_stringify({value})]);\n    let {id}_out = {input};\n",
+            id = node_id,
+            store = store,
+            value = value,
+            input = input,
+        ));
+        code
+    },
+};
+
+static STATE_INCREMENT: NodeDef = NodeDef {
+    name: Cow::Borrowed("state_increment"),
+    category: Cow::Borrowed("State"),
+    description: Cow::Borrowed("Add to a stored counter, outputting its new value"),
+    input_type: DataType::Any,
+    output_type: DataType::Number,
+    default_config: || serde_json::json!({"key": "runs", "by": 1}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let (mut code, store) = match state_setup("state_increment", node_id, config, input) {
+            Ok(setup) => setup,
+            Err(code) => return code,
+        };
+        // A missing counter starts at 0, so the first increment returns `by`
+        code.push_str(&format!(
+            "    let {id}_rows = db_query({store}, \"INSERT INTO state (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = value + ?2 RETURNING value\", [{id}_key, {by}]);\n    let {id}_out = json_parse(get({id}_rows[0], \"value\"));\n",
+            id = node_id,
+            store = store,
+            by = Some(&config["by"]).filter(|by| by.is_number()).map_or_else(|| "1".to_string(), JsonValue::to_string),
+        ));
+        code
+    },
+};
+
+// ====================
+// TRANSFER NODES
+// ====================
+
+/// Port of SFTP servers without a `port`
+const SFTP_PORT: u64 = 22;
+
+/// Connection settings every SFTP node starts with
+fn sftp_defaults() -> JsonValue {
+    serde_json::json!({
+        "host": "sftp.example.com",
+        "port": SFTP_PORT,
+        "username": "env:SFTP_USER",
+        "password": "env:SFTP_PASSWORD",
+        "private_key": "",
+        "passphrase": ""
+    })
+}
+
+static SFTP_UPLOAD: NodeDef = NodeDef {
+    name: Cow::Borrowed("sftp_upload"),
+    category: Cow::Borrowed("Transfer"),
+    description: Cow::Borrowed("Upload a local file to an SFTP server"),
+    input_type: DataType::Any,
+    output_type: DataType::Object,
+    default_config: || {
+        let mut config = sftp_defaults();
+        config["path"] = serde_json::json!("out/report.csv");
+        config["remote_path"] = serde_json::json!("/inbox/report.csv");
+        config
+    },
+    generate_code: |node_id, config, _input_var| {
+        sftp_call(node_id, config, |connection| {
+            format!("sftp_upload({}, {}, {})", connection, hlx_string(config["path"].as_str().unwrap_or("")), remote_path(config))
+        })
+    },
+};
+
+static SFTP_DOWNLOAD: NodeDef = NodeDef {
+    name: Cow::Borrowed("sftp_download"),
+    category: Cow::Borrowed("Transfer"),
+    description: Cow::Borrowed("Download a file from an SFTP server to a local path"),
+    input_type: DataType::Any,
+    output_type: DataType::Object,
+    default_config: || {
+        let mut config = sftp_defaults();
+        config["remote_path"] = serde_json::json!("/outbox/orders.csv");
+        config["path"] = serde_json::json!("in/orders.csv");
+        config
+    },
+    generate_code: |node_id, config, _input_var| {
+        sftp_call(node_id, config, |connection| {
+            format!("sftp_download({}, {}, {})", connection, remote_path(config), hlx_string(config["path"].as_str().unwrap_or("")))
+        })
+    },
+};
+
+static SFTP_LIST: NodeDef = NodeDef {
+    name: Cow::Borrowed("sftp_list"),
+    category: Cow::Borrowed("Transfer"),
+    description: Cow::Borrowed("List a directory on an SFTP server"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
+    default_config: || {
+        let mut config = sftp_defaults();
+        config["remote_path"] = serde_json::json!("/outbox");
+        config
+    },
+    generate_code: |node_id, config, _input_var| {
+        sftp_call(node_id, config, |connection| format!("sftp_list({}, {})", connection, remote_path(config)))
+    },
+};
+
+fn remote_path(config: &JsonValue) -> String {
+    hlx_string(config["remote_path"].as_str().unwrap_or(""))
+}
+
+/// Emit an SFTP builtin call with the node's connection object. Credentials
+/// go through `connection_expr`, so `env:NAME` keeps them out of the flow.
+/// A `private_key` is a key file's path, or the key itself from `env:NAME`.
+fn sftp_call(node_id: &str, config: &JsonValue, call: impl Fn(&str) -> String) -> String {
+    let field = |key: &str| config[key].as_str().map(str::trim).filter(|v| !v.is_empty());
+    let (Some(host), Some(username)) = (field("host"), field("username")) else {
+        return format!("    // {}: host and username are required\n    let {}_out = null;\n", node_id, node_id);
+    };
+    if field("password").is_none() && field("private_key").is_none() {
+        return format!("    // {}: set a password or a private_key\n    let {}_out = null;\n", node_id, node_id);
+    }
+    if field("remote_path").is_none() {
+        return format!("    // {}: remote_path is required\n    let {}_out = null;\n", node_id, node_id);
+    }
+
+    let mut entries = vec![
+        format!("\"host\": {}", connection_expr(host)),
+        format!("\"port\": {}", config["port"].as_u64().unwrap_or(SFTP_PORT)),
+        format!("\"username\": {}", connection_expr(username)),
+    ];
+    for key in ["password", "private_key", "passphrase"] {
+        if let Some(value) = field(key) {
+            entries.push(format!("{}: {}", hlx_string(key), connection_expr(value)));
+        }
+    }
+    let connection = format!("{{ {} }}", entries.join(", "));
+    format!("    let {}_out = {};\n", node_id, call(&connection))
+}
+
+// ====================
+// EMAIL NODES
+// ====================
+
+/// Port of IMAP servers without a `port` (IMAP over TLS)
+const IMAP_PORT: u64 = 993;
+
+static EMAIL_READ: NodeDef = NodeDef {
+    name: Cow::Borrowed("email_read"),
+    category: Cow::Borrowed("Email"),
+    description: Cow::Borrowed("Read messages from an IMAP mailbox, or start a run per new message (server mode)"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({
+        "host": "imap.example.com",
+        "port": IMAP_PORT,
+        "username": "env:IMAP_USER",
+        "password": "env:IMAP_PASSWORD",
+        "folder": "INBOX",
+        "unseen_only": true,
+        "mark_read": false,
+        "max_messages": 50,
+        "trigger": false,
+        "poll_secs": crate::email::DEFAULT_POLL_SECS
+    }),
+    generate_code: |node_id, config, _input_var| {
+        // As a trigger, the server delivers each message as the run's input
+        if config["trigger"].as_bool().unwrap_or(false) {
+            return format!("    let {}_out = input;\n", node_id);
+        }
+        let field = |key: &str| config[key].as_str().map(str::trim).filter(|v| !v.is_empty());
+        let (Some(host), Some(username), Some(password)) = (field("host"), field("username"), field("password")) else {
+            return format!("    // {}: host, username and password are required\n    let {}_out = null;\n", node_id, node_id);
+        };
+        let connection = format!(
+            "{{ \"host\": {}, \"port\": {}, \"tls\": {}, \"username\": {}, \"password\": {} }}",
+            connection_expr(host),
+            config["port"].as_u64().unwrap_or(IMAP_PORT),
+            config["tls"].as_bool().unwrap_or(true),
+            connection_expr(username),
+            connection_expr(password),
+        );
+        format!(
+            "    let {}_out = imap_fetch({}, {}, {}, {}, {});\n",
+            node_id,
+            connection,
+            hlx_string(field("folder").unwrap_or("INBOX")),
+            config["unseen_only"].as_bool().unwrap_or(true),
+            config["mark_read"].as_bool().unwrap_or(false),
+            config["max_messages"].as_u64().unwrap_or(50),
+        )
+    },
+};
+
+/// A connection string or other secret, or `env("NAME")` for `env:NAME` so
+/// credentials stay in the environment rather than in the flow file or
+/// compiled source
+pub(crate) fn connection_expr(connection: &str) -> String {
+    match connection.strip_prefix("env:") {
+        Some(name) => format!("env({})", hlx_string(name.trim())),
+        None => hlx_string(connection),
+    }
+}
+
 // ====================
 // MATH NODES
 // ====================
 
 static MATH_ADD: NodeDef = NodeDef {
-    name: "math_add",
-    category: "Math",
-    description: "Add two numbers",
+    name: Cow::Borrowed("math_add"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Add two numbers"),
+    input_type: DataType::Number,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({"value": 0}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("0");
@@ -583,9 +2571,11 @@ static MATH_ADD: NodeDef = NodeDef {
 };
 
 static MATH_SUBTRACT: NodeDef = NodeDef {
-    name: "math_subtract",
-    category: "Math",
-    description: "Subtract two numbers",
+    name: Cow::Borrowed("math_subtract"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Subtract two numbers"),
+    input_type: DataType::Number,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({"value": 0}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("0");
@@ -595,9 +2585,11 @@ static MATH_SUBTRACT: NodeDef = NodeDef {
 };
 
 static MATH_MULTIPLY: NodeDef = NodeDef {
-    name: "math_multiply",
-    category: "Math",
-    description: "Multiply two numbers",
+    name: Cow::Borrowed("math_multiply"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Multiply two numbers"),
+    input_type: DataType::Number,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({"value": 1}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("1");
@@ -607,9 +2599,11 @@ static MATH_MULTIPLY: NodeDef = NodeDef {
 };
 
 static MATH_DIVIDE: NodeDef = NodeDef {
-    name: "math_divide",
-    category: "Math",
-    description: "Divide two numbers",
+    name: Cow::Borrowed("math_divide"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Divide two numbers"),
+    input_type: DataType::Number,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({"value": 1}),
     generate_code: |node_id, config, input_var| {
         let input = input_var.unwrap_or("1");
@@ -619,9 +2613,11 @@ static MATH_DIVIDE: NodeDef = NodeDef {
 };
 
 static MATH_FLOOR: NodeDef = NodeDef {
-    name: "math_floor",
-    category: "Math",
-    description: "Floor of number",
+    name: Cow::Borrowed("math_floor"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Floor of number"),
+    input_type: DataType::Number,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("0");
@@ -630,9 +2626,11 @@ static MATH_FLOOR: NodeDef = NodeDef {
 };
 
 static MATH_CEIL: NodeDef = NodeDef {
-    name: "math_ceil",
-    category: "Math",
-    description: "Ceiling of number",
+    name: Cow::Borrowed("math_ceil"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Ceiling of number"),
+    input_type: DataType::Number,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("0");
@@ -641,9 +2639,11 @@ static MATH_CEIL: NodeDef = NodeDef {
 };
 
 static MATH_ROUND: NodeDef = NodeDef {
-    name: "math_round",
-    category: "Math",
-    description: "Round number",
+    name: Cow::Borrowed("math_round"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Round number"),
+    input_type: DataType::Number,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("0");
@@ -652,9 +2652,11 @@ static MATH_ROUND: NodeDef = NodeDef {
 };
 
 static MATH_SQRT: NodeDef = NodeDef {
-    name: "math_sqrt",
-    category: "Math",
-    description: "Square root",
+    name: Cow::Borrowed("math_sqrt"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Square root"),
+    input_type: DataType::Number,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("0");
@@ -663,23 +2665,66 @@ static MATH_SQRT: NodeDef = NodeDef {
 };
 
 static MATH_RANDOM: NodeDef = NodeDef {
-    name: "math_random",
-    category: "Math",
-    description: "Random number (0-1)",
+    name: Cow::Borrowed("math_random"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Random number (0-1)"),
+    input_type: DataType::Any,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
-    generate_code: |node_id, _config, _input_var| {
-        format!("    let {}_out = random();\n", node_id)
+    generate_code: |node_id, config, _input_var| {
+        match config["seed"].as_u64() {
+            Some(seed) => {
+                let value = (splitmix64(seed) >> 11) as f64 / (1u64 << 53) as f64;
+                format!("    let {}_out = {:?};\n", node_id, value)
+            }
+            None => format!("    let {}_out = random();\n", node_id),
+        }
+    },
+};
+
+static UUID_V4: NodeDef = NodeDef {
+    name: Cow::Borrowed("uuid_v4"),
+    category: Cow::Borrowed("Math"),
+    description: Cow::Borrowed("Random UUID (v4)"),
+    input_type: DataType::Any,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, config, _input_var| {
+        format!("    let {}_out = {};\n", node_id, uuid_v4_expr(config))
     },
 };
 
+/// A v4 UUID: fixed when the node is seeded, otherwise `uuid_v4()`
+fn uuid_v4_expr(config: &JsonValue) -> String {
+    let Some(seed) = config["seed"].as_u64() else {
+        return "uuid_v4()".to_string();
+    };
+    let hi = splitmix64(seed);
+    let lo = splitmix64(hi);
+    // Set the version (4) and variant (10xx) bits
+    let hi = (hi & !0xf000) | 0x4000;
+    let lo = (lo & !(0xc_u64 << 60)) | (0x8_u64 << 60);
+    let uuid = format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        hi >> 32,
+        (hi >> 16) & 0xffff,
+        hi & 0xffff,
+        lo >> 48,
+        lo & 0xffff_ffff_ffff
+    );
+    hlx_string(&uuid)
+}
+
 // ====================
 // TYPE CONVERSION NODES
 // ====================
 
 static TO_STRING: NodeDef = NodeDef {
-    name: "to_string",
-    category: "Convert",
-    description: "Convert to string",
+    name: Cow::Borrowed("to_string"),
+    category: Cow::Borrowed("Convert"),
+    description: Cow::Borrowed("Convert to string"),
+    input_type: DataType::Any,
+    output_type: DataType::String,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("null");
@@ -688,9 +2733,11 @@ static TO_STRING: NodeDef = NodeDef {
 };
 
 static TO_INT: NodeDef = NodeDef {
-    name: "to_int",
-    category: "Convert",
-    description: "Convert to integer",
+    name: Cow::Borrowed("to_int"),
+    category: Cow::Borrowed("Convert"),
+    description: Cow::Borrowed("Convert to integer"),
+    input_type: DataType::Any,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("0");
@@ -699,9 +2746,11 @@ static TO_INT: NodeDef = NodeDef {
 };
 
 static TO_FLOAT: NodeDef = NodeDef {
-    name: "to_float",
-    category: "Convert",
-    description: "Convert to float",
+    name: Cow::Borrowed("to_float"),
+    category: Cow::Borrowed("Convert"),
+    description: Cow::Borrowed("Convert to float"),
+    input_type: DataType::Any,
+    output_type: DataType::Number,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, input_var| {
         let input = input_var.unwrap_or("0");
@@ -709,53 +2758,862 @@ static TO_FLOAT: NodeDef = NodeDef {
     },
 };
 
+// ====================
+// CRYPTO & ENCODING NODES
+// ====================
+
+/// Digest algorithms supported by `hash` and `hmac_sign`
+pub const HASH_ALGORITHMS: &[&str] = &["md5", "sha1", "sha256", "sha512"];
+
+/// Well-known namespaces for name-based (v5) UUIDs
+const UUID_NAMESPACES: &[(&str, &str)] = &[
+    ("dns", "6ba7b810-9dad-11d1-80b4-00c04fd430c8"),
+    ("url", "6ba7b811-9dad-11d1-80b4-00c04fd430c8"),
+    ("oid", "6ba7b812-9dad-11d1-80b4-00c04fd430c8"),
+    ("x500", "6ba7b814-9dad-11d1-80b4-00c04fd430c8"),
+];
+
+static HASH: NodeDef = NodeDef {
+    name: Cow::Borrowed("hash"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("Hash the input (md5, sha1, sha256, sha512)"),
+    input_type: DataType::String,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({"algorithm": "sha256", "encoding": "hex"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        digest_call(node_id, config, |algorithm, encoding| {
+            format!("hash({}, {}, {})", algorithm, input, encoding)
+        })
+    },
+};
+
+static HMAC_SIGN: NodeDef = NodeDef {
+    name: Cow::Borrowed("hmac_sign"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("HMAC signature of the input, e.g. for webhook payloads"),
+    input_type: DataType::String,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({"algorithm": "sha256", "key": "env:WEBHOOK_SECRET", "encoding": "hex"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        let key = connection_expr(config["key"].as_str().unwrap_or(""));
+        digest_call(node_id, config, |algorithm, encoding| {
+            format!("hmac({}, {}, {}, {})", algorithm, key, input, encoding)
+        })
+    },
+};
+
+/// A `hash`/`hmac` call with the node's algorithm and output encoding
+/// (`hex` or `base64`), or a comment and `null` for unknown algorithms
+fn digest_call(node_id: &str, config: &JsonValue, call: impl Fn(&str, &str) -> String) -> String {
+    let algorithm = config["algorithm"].as_str().unwrap_or("sha256");
+    if !HASH_ALGORITHMS.contains(&algorithm) {
+        return format!(
+            "    // Unknown hash algorithm in {}: {} (expected one of {})\n    let {}_out = null;\n",
+            node_id,
+            algorithm.replace('\n', " "),
+            HASH_ALGORITHMS.join(", "),
+            node_id
+        );
+    }
+    let encoding = if config["encoding"].as_str() == Some("base64") { "base64" } else { "hex" };
+    format!("    let {}_out = {};\n", node_id, call(&hlx_string(algorithm), &hlx_string(encoding)))
+}
+
+static BASE64_ENCODE: NodeDef = NodeDef {
+    name: Cow::Borrowed("base64_encode"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("Encode text as base64"),
+    input_type: DataType::String,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({"url_safe": false}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        let url_safe = config["url_safe"].as_bool().unwrap_or(false);
+        format!("    let {}_out = base64_encode({}, {});\n", node_id, input, url_safe)
+    },
+};
+
+static BASE64_DECODE: NodeDef = NodeDef {
+    name: Cow::Borrowed("base64_decode"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("Decode base64 to text"),
+    input_type: DataType::String,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({"url_safe": false}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        let url_safe = config["url_safe"].as_bool().unwrap_or(false);
+        format!("    let {}_out = base64_decode({}, {});\n", node_id, input, url_safe)
+    },
+};
+
+static BYTES_FROM_BASE64: NodeDef = NodeDef {
+    name: Cow::Borrowed("bytes_from_base64"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("Turn base64 text into bytes"),
+    input_type: DataType::String,
+    output_type: DataType::Bytes,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        format!("    let {}_out = {{ {}: {} }};\n", node_id, hlx_string(crate::bytes::BYTES_KEY), input)
+    },
+};
+
+static BYTES_TO_BASE64: NodeDef = NodeDef {
+    name: Cow::Borrowed("bytes_to_base64"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("Turn bytes into base64 text"),
+    input_type: DataType::Bytes,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("null");
+        format!("    let {}_out = get({}, {});\n", node_id, input, hlx_string(crate::bytes::BYTES_KEY))
+    },
+};
+
+static URL_ENCODE: NodeDef = NodeDef {
+    name: Cow::Borrowed("url_encode"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("Percent-encode text for use in a URL"),
+    input_type: DataType::String,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        format!("    let {}_out = url_encode({});\n", node_id, input)
+    },
+};
+
+static URL_DECODE: NodeDef = NodeDef {
+    name: Cow::Borrowed("url_decode"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("Decode percent-encoded text"),
+    input_type: DataType::String,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        format!("    let {}_out = url_decode({});\n", node_id, input)
+    },
+};
+
+static UUID_GENERATE: NodeDef = NodeDef {
+    name: Cow::Borrowed("uuid_generate"),
+    category: Cow::Borrowed("Crypto"),
+    description: Cow::Borrowed("Random (v4) UUID, or name-based (v5) from the input for idempotency keys"),
+    input_type: DataType::Any,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({"version": "v4", "namespace": "url"}),
+    generate_code: |node_id, config, input_var| {
+        if config["version"].as_str() != Some("v5") {
+            return format!("    let {}_out = {};\n", node_id, uuid_v4_expr(config));
+        }
+
+        // The same input always gives the same UUID
+        let input = input_var.unwrap_or("\"\"");
+        let namespace = config["namespace"].as_str().unwrap_or("url");
+        let namespace = UUID_NAMESPACES
+            .iter()
+            .find(|(name, _)| *name == namespace)
+            .map_or(namespace, |(_, uuid)| *uuid);
+        format!("    let {}_out = uuid_v5({}, {});\n", node_id, hlx_string(namespace), input)
+    },
+};
+
+// ====================
+// LLM NODES
+// ====================
+
+static PROMPT_TEMPLATE: NodeDef = NodeDef {
+    name: Cow::Borrowed("prompt_template"),
+    category: Cow::Borrowed("LLM"),
+    description: Cow::Borrowed("Render a prompt from {{variables}} in the input"),
+    input_type: DataType::Object,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({
+        "template": "You are a helpful assistant.\n\nAnswer this question:\n{{question}}",
+        "variables": ["question"]
+    }),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("{}");
+        let template = config["template"].as_str().unwrap_or("");
+        let variables: Vec<String> = crate::prompt::declared_variables(config)
+            .iter()
+            .map(|v| hlx_string(v))
+            .collect();
+
+        // Surface template/declaration mismatches in the generated source
+        let mut code = String::new();
+        for problem in crate::prompt::check(template, &crate::prompt::declared_variables(config)) {
+            code.push_str(&format!("    // prompt_template {}: {}\n", node_id, problem.replace('\n', " ")));
+        }
+        code.push_str(&format!(
+            "    let {}_out = render_template({}, {}, [{}]);\n",
+            node_id,
+            hlx_string(template),
+            input,
+            variables.join(", ")
+        ));
+        code
+    },
+};
+
+static TOKEN_COUNT: NodeDef = NodeDef {
+    name: Cow::Borrowed("token_count"),
+    category: Cow::Borrowed("LLM"),
+    description: Cow::Borrowed("Count tokens in text"),
+    input_type: DataType::String,
+    output_type: DataType::Number,
+    default_config: || serde_json::json!({"tokenizer": "cl100k_base"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        let tokenizer = hlx_string(tokenizer(config));
+        format!("    let {}_out = count_tokens({}, {});\n", node_id, input, tokenizer)
+    },
+};
+
+static TRUNCATE_TOKENS: NodeDef = NodeDef {
+    name: Cow::Borrowed("truncate_tokens"),
+    category: Cow::Borrowed("LLM"),
+    description: Cow::Borrowed("Cut text down to a token budget"),
+    input_type: DataType::String,
+    output_type: DataType::String,
+    default_config: || serde_json::json!({"max_tokens": 4096, "tokenizer": "cl100k_base"}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        let max_tokens = config["max_tokens"].as_u64().unwrap_or(4096);
+        let tokenizer = hlx_string(tokenizer(config));
+        format!("    let {}_out = truncate_tokens({}, {}, {});\n", node_id, input, max_tokens, tokenizer)
+    },
+};
+
+/// Retries of rate-limited and failed completion requests
+const LLM_RETRIES: u64 = 2;
+
+/// Headers of a request to an LLM provider: JSON, with the node's
+/// `api_key` (or the provider's default when unset) as a Bearer token
+fn provider_headers(provider: crate::llm::Provider, config: &JsonValue) -> Vec<String> {
+    let mut headers = vec!["\"Content-Type\": \"application/json\"".to_string()];
+    let key = config["api_key"].as_str().unwrap_or(provider.default_api_key()).trim();
+    if !key.is_empty() {
+        headers.push(format!("\"Authorization\": \"Bearer \" + {}", connection_expr(key)));
+    }
+    headers
+}
+
+static LLM_COMPLETE: NodeDef = NodeDef {
+    name: Cow::Borrowed("llm_complete"),
+    category: Cow::Borrowed("LLM"),
+    description: Cow::Borrowed("Complete a prompt with an OpenAI-compatible or Ollama model"),
+    input_type: DataType::Any,
+    output_type: DataType::Object,
+    default_config: || serde_json::json!({
+        "provider": "openai",
+        "base_url": "",
+        "model": "gpt-4o-mini",
+        "system": "You are a helpful assistant.",
+        "prompt": "{{question}}",
+        "temperature": 0.7,
+        "max_tokens": 512,
+        "retries": LLM_RETRIES
+    }),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let name = config["provider"].as_str().unwrap_or("openai");
+        let Some(provider) = crate::llm::Provider::parse(name) else {
+            return format!(
+                "    // llm_complete: unknown provider {:?} (use {})\n    let {}_out = null;\n",
+                name,
+                crate::llm::PROVIDERS.join(", "),
+                node_id
+            );
+        };
+        let model = config["model"].as_str().map(str::trim).unwrap_or("");
+        if model.is_empty() {
+            return format!("    // llm_complete: model is required\n    let {}_out = null;\n", node_id);
+        }
+
+        let base_url = config["base_url"].as_str().map(str::trim).filter(|u| !u.is_empty())
+            .unwrap_or(provider.default_base_url())
+            .trim_end_matches('/');
+        let url = format!("{}{}", base_url, provider.chat_path());
+        let headers = provider_headers(provider, config);
+
+        // The prompt template reads the input; without one the input is the prompt
+        let prompt = render_call(config["prompt"].as_str().unwrap_or(""), input)
+            .unwrap_or_else(|| format!("to_string({})", input));
+        let mut messages = Vec::new();
+        if let Some(system) = config["system"].as_str().filter(|s| !s.trim().is_empty()) {
+            messages.push(format!("{{ \"role\": \"system\", \"content\": {} }}", hlx_string(system)));
+        }
+        messages.push(format!("{{ \"role\": \"user\", \"content\": {}_prompt }}", node_id));
+
+        let number = |key: &str, default: &str| Some(&config[key]).filter(|v| v.is_number()).map_or_else(|| default.to_string(), JsonValue::to_string);
+        let (temperature, max_tokens) = (number("temperature", "0.7"), number("max_tokens", "512"));
+        let body = match provider {
+            crate::llm::Provider::OpenAi => format!(
+                "{{ \"model\": {}, \"temperature\": {}, \"max_tokens\": {}, \"messages\": [{}] }}",
+                hlx_string(model), temperature, max_tokens, messages.join(", ")
+            ),
+            crate::llm::Provider::Ollama => format!(
+                "{{ \"model\": {}, \"stream\": false, \"messages\": [{}], \"options\": {{ \"temperature\": {}, \"num_predict\": {} }} }}",
+                hlx_string(model), messages.join(", "), temperature, max_tokens
+            ),
+        };
+
+        // Both APIs' answers become {text, model, usage}
+        let output = match provider {
+            crate::llm::Provider::OpenAi => format!(
+                "    let {id}_usage = get({id}_response, \"usage\");\n    let {id}_out = {{ \"text\": get(get(get({id}_response, \"choices\")[0], \"message\"), \"content\"), \"model\": get({id}_response, \"model\"), \"usage\": {{ \"prompt_tokens\": get({id}_usage, \"prompt_tokens\"), \"completion_tokens\": get({id}_usage, \"completion_tokens\"), \"total_tokens\": get({id}_usage, \"total_tokens\") }} }};\n",
+                id = node_id
+            ),
+            crate::llm::Provider::Ollama => format!(
+                "    let {id}_in = get({id}_response, \"prompt_eval_count\");\n    let {id}_done = get({id}_response, \"eval_count\");\n    let {id}_out = {{ \"text\": get(get({id}_response, \"message\"), \"content\"), \"model\": get({id}_response, \"model\"), \"usage\": {{ \"prompt_tokens\": {id}_in, \"completion_tokens\": {id}_done, \"total_tokens\": {id}_in + {id}_done }} }};\n",
+                id = node_id
+            ),
+        };
+        format!(
+            "    let {id}_prompt = {prompt};\n    let {id}_response = json_parse(http_request_retry(\"POST\", {url}, {body}, {{ {headers} }}, {retries}));\n{output}",
+            id = node_id,
+            prompt = prompt,
+            url = hlx_string(&url),
+            body = body,
+            headers = headers.join(", "),
+            retries = config["retries"].as_u64().unwrap_or(LLM_RETRIES),
+            output = output,
+        )
+    },
+};
+
 // ====================
 // ML/GPU NODES
 // ====================
 
 static TENSOR_CREATE: NodeDef = NodeDef {
-    name: "tensor_create",
-    category: "ML/GPU",
-    description: "Create 2D tensor",
+    name: Cow::Borrowed("tensor_create"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Create 2D tensor"),
+    input_type: DataType::Any,
+    output_type: DataType::Tensor,
     default_config: || serde_json::json!({"rows": 2, "cols": 2, "values": [1.0, 0.0, 0.0, 1.0]}),
     generate_code: |node_id, config, _input_var| {
-        let rows = config["rows"].as_u64().unwrap_or(2);
-        let cols = config["cols"].as_u64().unwrap_or(2);
-        let vals = config["values"].as_array();
+        let mut code = tensor_literal(&format!("{}_t", node_id), config);
+        code.push_str(&format!("    let {}_out = {}_t;\n", node_id, node_id));
+        code
+    },
+};
+
+/// Emit code building a 2D tensor named `var` from a
+/// `{"rows", "cols", "values"}` config
+fn tensor_literal(var: &str, config: &JsonValue) -> String {
+    let rows = config["rows"].as_u64().unwrap_or(2);
+    let cols = config["cols"].as_u64().unwrap_or(2);
+    let vals = config["values"].as_array();
+
+    let mut code = format!("    let {} = tensor_new_2d({}, {});\n", var, rows, cols);
+
+    if let Some(values) = vals {
+        for (i, v) in values.iter().enumerate() {
+            let val = v.as_f64().unwrap_or(0.0);
+            code.push_str(&format!("    let {}_data = {}[2];\n", var, var));
+            code.push_str(&format!("    {}_data[{}] = {};\n", var, i, val));
+        }
+    }
+    code
+}
 
-        let mut code = format!("    let {}_t = tensor_new_2d({}, {});\n", node_id, rows, cols);
+/// Source node connected to a named input port, as bound by the compiler
+fn port_source<'a>(config: &'a JsonValue, port: &str) -> Option<&'a str> {
+    config["inputs"][port].as_str()
+}
 
-        if let Some(values) = vals {
-            for (i, v) in values.iter().enumerate() {
-                let val = v.as_f64().unwrap_or(0.0);
-                code.push_str(&format!("    let {}_data = {}_t[2];\n", node_id, node_id));
-                code.push_str(&format!("    {}_data[{}] = {};\n", node_id, i, val));
+static TENSOR_MATMUL: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_matmul"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Matrix multiplication a × b"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, config, input_var| {
+        match (port_source(config, "a"), port_source(config, "b"), config.get("rhs")) {
+            (Some(a), Some(b), _) => format!("    let {}_out = tensor_matmul({}_out, {}_out);\n", node_id, a, b),
+            // A constant right-hand side multiplies the input, e.g. a weight
+            // matrix applied to a whole batch in one GPU matmul
+            (_, None, Some(rhs)) if input_var.is_some() => {
+                let input = port_source(config, "a").map(|a| format!("{}_out", a));
+                let input = input.as_deref().or(input_var).unwrap_or("null");
+                let mut code = tensor_literal(&format!("{}_rhs", node_id), rhs);
+                code.push_str(&format!("    let {}_out = tensor_matmul({}, {}_rhs);\n", node_id, input, node_id));
+                code
             }
+            _ => format!(
+                "    // tensor_matmul: connect tensors to both inputs (a, b) or set \"rhs\"\n    let {}_out = null;\n",
+                node_id
+            ),
         }
-        code.push_str(&format!("    let {}_out = {}_t;\n", node_id, node_id));
-        code
     },
 };
 
-static TENSOR_MATMUL: NodeDef = NodeDef {
-    name: "tensor_matmul",
-    category: "ML/GPU",
-    description: "Matrix multiplication",
+static TENSOR_STACK: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_stack"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Stack equal-shaped 2D tensors into a 3D tensor"),
+    input_type: DataType::Array,
+    output_type: DataType::Tensor,
     default_config: || serde_json::json!({}),
-    generate_code: |node_id, _config, _input_var| {
-        // Needs two inputs from edges
-        format!("    // TODO: Get two tensor inputs from edges\n    let {}_out = null;\n", node_id)
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        format!("    let {}_out = tensor_stack({});\n", node_id, input)
+    },
+};
+
+static TENSOR_BATCH: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_batch"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Pack an array of rows into a [batch, dim] tensor"),
+    input_type: DataType::Array,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"batch_size": 0}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("[]");
+        // 0 keeps every row; otherwise rows are zero-padded or cut to batch_size
+        let batch_size = config["batch_size"].as_u64().unwrap_or(0);
+        format!("    let {}_out = tensor_batch({}, {});\n", node_id, input, batch_size)
     },
 };
 
 static TENSOR_ADD: NodeDef = NodeDef {
-    name: "tensor_add",
-    category: "ML/GPU",
-    description: "Element-wise tensor addition",
+    name: Cow::Borrowed("tensor_add"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Element-wise tensor addition a + b"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
     default_config: || serde_json::json!({}),
-    generate_code: |node_id, _config, _input_var| {
-        // Needs two inputs from edges
-        format!("    // TODO: Get two tensor inputs from edges\n    let {}_out = null;\n", node_id)
+    generate_code: |node_id, config, _input_var| {
+        match (port_source(config, "a"), port_source(config, "b")) {
+            (Some(a), Some(b)) => format!("    let {}_out = tensor_add({}_out, {}_out);\n", node_id, a, b),
+            _ => format!(
+                "    // tensor_add: connect tensors to both inputs (a, b)\n    let {}_out = null;\n",
+                node_id
+            ),
+        }
+    },
+};
+
+static TENSOR_MUL: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_mul"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Element-wise tensor multiplication a * b"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, config, _input_var| {
+        match (port_source(config, "a"), port_source(config, "b")) {
+            (Some(a), Some(b)) => format!("    let {}_out = tensor_mul({}_out, {}_out);\n", node_id, a, b),
+            _ => format!(
+                "    // tensor_mul: connect tensors to both inputs (a, b)\n    let {}_out = null;\n",
+                node_id
+            ),
+        }
+    },
+};
+
+/// The node's `axis` as an HLX argument: an index counted from the end
+/// when negative, or `default` (`null` meaning every axis) when unset
+fn axis_arg(config: &JsonValue, default: Option<i64>) -> String {
+    config["axis"]
+        .as_i64()
+        .or(default)
+        .map_or_else(|| "null".to_string(), |axis| axis.to_string())
+}
+
+/// A single-input tensor intrinsic taking the node's axis
+fn tensor_axis_call(function: &str, node_id: &str, config: &JsonValue, input_var: Option<&str>, default: Option<i64>) -> String {
+    let input = input_var.unwrap_or("null");
+    format!("    let {}_out = {}({}, {});\n", node_id, function, input, axis_arg(config, default))
+}
+
+static TENSOR_TRANSPOSE: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_transpose"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Swap the last two axes ([rows, cols] -> [cols, rows])"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("null");
+        format!("    let {}_out = tensor_transpose({});\n", node_id, input)
+    },
+};
+
+static TENSOR_RESHAPE: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_reshape"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Change shape keeping element count; one size may be -1 to infer it"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"shape": [-1]}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let shape: Option<Vec<i64>> = config["shape"]
+            .as_array()
+            .filter(|dims| !dims.is_empty())
+            .and_then(|dims| dims.iter().map(|d| d.as_i64().filter(|d| *d > 0 || *d == -1)).collect());
+        match shape.filter(|dims| dims.iter().filter(|d| **d == -1).count() <= 1) {
+            Some(dims) => {
+                let dims: Vec<String> = dims.iter().map(i64::to_string).collect();
+                format!("    let {}_out = tensor_reshape({}, [{}]);\n", node_id, input, dims.join(", "))
+            }
+            None => format!(
+                "    // tensor_reshape: shape must be positive sizes with at most one -1\n    let {}_out = null;\n",
+                node_id
+            ),
+        }
+    },
+};
+
+static TENSOR_SLICE: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_slice"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Take indexes start..end along an axis"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"axis": 0, "start": 0, "end": 1}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let start = config["start"].as_i64().unwrap_or(0);
+        let end = config["end"].as_i64().unwrap_or(1);
+        format!(
+            "    let {}_out = tensor_slice({}, {}, {}, {});\n",
+            node_id, input, axis_arg(config, Some(0)), start, end
+        )
+    },
+};
+
+static TENSOR_REDUCE_SUM: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_reduce_sum"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Sum along an axis, or of every element without one"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"axis": null}),
+    generate_code: |node_id, config, input_var| tensor_axis_call("tensor_sum", node_id, config, input_var, None),
+};
+
+static TENSOR_REDUCE_MEAN: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_reduce_mean"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Mean along an axis, or of every element without one"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"axis": null}),
+    generate_code: |node_id, config, input_var| tensor_axis_call("tensor_mean", node_id, config, input_var, None),
+};
+
+static TENSOR_RELU: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_relu"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("max(x, 0) element-wise"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("null");
+        format!("    let {}_out = tensor_relu({});\n", node_id, input)
+    },
+};
+
+static TENSOR_SIGMOID: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_sigmoid"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("1 / (1 + e^-x) element-wise"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({}),
+    generate_code: |node_id, _config, input_var| {
+        let input = input_var.unwrap_or("null");
+        format!("    let {}_out = tensor_sigmoid({});\n", node_id, input)
+    },
+};
+
+static TENSOR_SOFTMAX: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_softmax"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Softmax along an axis (default the last)"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"axis": -1}),
+    generate_code: |node_id, config, input_var| tensor_axis_call("tensor_softmax", node_id, config, input_var, Some(-1)),
+};
+
+static TENSOR_ARGMAX: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_argmax"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Index of the largest value along an axis (default the last)"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"axis": -1}),
+    generate_code: |node_id, config, input_var| tensor_axis_call("tensor_argmax", node_id, config, input_var, Some(-1)),
+};
+
+static MODEL_INFER: NodeDef = NodeDef {
+    name: Cow::Borrowed("model_infer"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Run the input tensor through an ONNX model"),
+    input_type: DataType::Tensor,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"path": "model.onnx", "input_name": "", "output_name": ""}),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("null");
+        let path = config["path"].as_str().unwrap_or("model.onnx");
+        let mut code = String::new();
+        if !path.ends_with(".onnx") {
+            code.push_str(&format!("    // model_infer: {:?} is not an .onnx file\n", path));
+        }
+        // Empty names select the model's first input and output
+        code.push_str(&format!(
+            "    let {}_out = onnx_infer({}, {}, {}, {});\n",
+            node_id,
+            hlx_string(path),
+            input,
+            field_arg(config, "input_name"),
+            field_arg(config, "output_name")
+        ));
+        code
+    },
+};
+
+/// Where `embed_text` gets embeddings: the LLM providers' APIs, or an
+/// ONNX model run on the tensor backend
+pub const EMBEDDING_PROVIDERS: &[&str] = &["openai", "ollama", "local"];
+
+static EMBED_TEXT: NodeDef = NodeDef {
+    name: Cow::Borrowed("embed_text"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Embed text as a vector, through an API or a local ONNX model"),
+    input_type: DataType::Any,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({
+        "provider": "openai",
+        "base_url": "",
+        "model": "text-embedding-3-small",
+        "batch": false,
+        "retries": LLM_RETRIES
+    }),
+    generate_code: |node_id, config, input_var| {
+        let input = input_var.unwrap_or("\"\"");
+        // A batch embeds an array of texts at once, outputting a vector per text
+        let batch = config["batch"].as_bool().unwrap_or(false);
+        let texts = if batch { input.to_string() } else { format!("[to_string({})]", input) };
+        let name = config["provider"].as_str().unwrap_or("openai");
+
+        let mut code = format!("    let {}_texts = {};\n", node_id, texts);
+        if name == "local" {
+            let path = config["path"].as_str().unwrap_or("");
+            if !path.ends_with(".onnx") {
+                return format!("    // embed_text: path {:?} is not an .onnx model\n    let {}_out = null;\n", path, node_id);
+            }
+            code.push_str(&format!("    let {id}_vectors = embed_onnx({path}, {id}_texts);\n", id = node_id, path = hlx_string(path)));
+        } else {
+            let Some(provider) = crate::llm::Provider::parse(name) else {
+                return format!(
+                    "    // embed_text: unknown provider {:?} (use {})\n    let {}_out = null;\n",
+                    name,
+                    EMBEDDING_PROVIDERS.join(", "),
+                    node_id
+                );
+            };
+            let model = config["model"].as_str().map(str::trim).unwrap_or("");
+            if model.is_empty() {
+                return format!("    // embed_text: model is required\n    let {}_out = null;\n", node_id);
+            }
+            let base_url = config["base_url"].as_str().map(str::trim).filter(|u| !u.is_empty())
+                .unwrap_or(provider.default_base_url())
+                .trim_end_matches('/');
+            let headers = provider_headers(provider, config);
+            code.push_str(&format!(
+                "    let {id}_response = json_parse(http_request_retry(\"POST\", {url}, {{ \"model\": {model}, \"input\": {id}_texts }}, {{ {headers} }}, {retries}));\n",
+                id = node_id,
+                url = hlx_string(&format!("{}{}", base_url, provider.embeddings_path())),
+                model = hlx_string(model),
+                headers = headers.join(", "),
+                retries = config["retries"].as_u64().unwrap_or(LLM_RETRIES),
+            ));
+            code.push_str(&match provider {
+                crate::llm::Provider::OpenAi => item_loop(
+                    node_id,
+                    &format!("get({}_response, \"data\")", node_id),
+                    &format!("    let {}_vectors = [];\n", node_id),
+                    &format!("        {id}_vectors = arr_concat({id}_vectors, [get({id}_item, \"embedding\")]);\n", id = node_id),
+                ),
+                crate::llm::Provider::Ollama => format!("    let {id}_vectors = get({id}_response, \"embeddings\");\n", id = node_id),
+            });
+        }
+        let out = if batch { format!("{}_vectors", node_id) } else { format!("{}_vectors[0]", node_id) };
+        code.push_str(&format!("    let {}_out = {};\n", node_id, out));
+        code
+    },
+};
+
+static VECTOR_INDEX: NodeDef = NodeDef {
+    name: Cow::Borrowed("vector_index"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Add {id, vector, metadata} records to an on-disk vector index"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
+    default_config: || serde_json::json!({"path": "vectors.jsonl", "each": false}),
+    generate_code: |node_id, config, input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("vectors.jsonl"));
+        let input = input_var.unwrap_or("null");
+        let records = match config["each"].as_bool().unwrap_or(false) {
+            true => input.to_string(),
+            false => format!("[{}]", input),
+        };
+        jsonl_text_code(node_id, &records)
+            + &format!("    append_file({path}, {id}_text);\n    let {id}_out = {input};\n", id = node_id, path = path, input = input)
+    },
+};
+
+static VECTOR_SEARCH: NodeDef = NodeDef {
+    name: Cow::Borrowed("vector_search"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Find the indexed vectors most similar to the input vector"),
+    input_type: DataType::Array,
+    output_type: DataType::Array,
+    default_config: || serde_json::json!({"path": "vectors.jsonl", "top_k": 5, "min_score": 0.0}),
+    generate_code: |node_id, config, input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("vectors.jsonl"));
+        let top_k = config["top_k"].as_u64().filter(|k| *k > 0).unwrap_or(5);
+        let min_score = Some(&config["min_score"]).filter(|s| s.is_number()).map_or_else(|| "0".to_string(), JsonValue::to_string);
+        let mut code = format!("    let {}_lines = read_lines({}, 0, {});\n", node_id, path, MAX_ITEMS);
+        code.push_str(&parse_lines_code(node_id, "jsonl", &format!("{}_lines", node_id), "null", "    "));
+        // Cosine similarity of each record, kept in a best-first list of top_k
+        code.push_str(&format!(
+            "    let {id}_query = {input};\n    let {id}_qnorm = 0;\n    let {id}_d = 0;\n    loop ({id}_d < len({id}_query), {max}) {{\n        {id}_qnorm = {id}_qnorm + {id}_query[{id}_d] * {id}_query[{id}_d];\n        {id}_d = {id}_d + 1;\n    }}\n    {id}_qnorm = sqrt({id}_qnorm);\n    let {id}_out = [];\n",
+            id = node_id,
+            input = input_var.unwrap_or("[]"),
+            max = MAX_ITEMS,
+        ));
+        code.push_str(&item_loop(
+            node_id,
+            &format!("{}_items", node_id),
+            "",
+            &format!(
+                concat!(
+                    "        let {id}_vector = get({id}_item, \"vector\");\n",
+                    "        let {id}_dot = 0;\n        let {id}_norm = 0;\n        {id}_d = 0;\n",
+                    "        loop ({id}_d < len({id}_vector) && len({id}_vector) == len({id}_query), {max}) {{\n",
+                    "            {id}_dot = {id}_dot + {id}_vector[{id}_d] * {id}_query[{id}_d];\n",
+                    "            {id}_norm = {id}_norm + {id}_vector[{id}_d] * {id}_vector[{id}_d];\n",
+                    "            {id}_d = {id}_d + 1;\n",
+                    "        }}\n",
+                    "        let {id}_score = 0;\n",
+                    "        if ({id}_qnorm * {id}_norm > 0) {{\n            {id}_score = {id}_dot / ({id}_qnorm * sqrt({id}_norm));\n        }}\n",
+                    "        if ({id}_score >= {min_score}) {{\n",
+                    "            let {id}_hit = {{ \"id\": get({id}_item, \"id\"), \"score\": {id}_score, \"metadata\": get({id}_item, \"metadata\") }};\n",
+                    "            let {id}_ranked = [];\n            let {id}_placed = false;\n            let {id}_rank = 0;\n",
+                    "            loop ({id}_rank < len({id}_out), {top_k}) {{\n",
+                    "                if (!{id}_placed && {id}_score > get({id}_out[{id}_rank], \"score\")) {{\n",
+                    "                    {id}_ranked = arr_concat({id}_ranked, [{id}_hit]);\n                    {id}_placed = true;\n",
+                    "                }}\n",
+                    "                {id}_ranked = arr_concat({id}_ranked, [{id}_out[{id}_rank]]);\n",
+                    "                {id}_rank = {id}_rank + 1;\n",
+                    "            }}\n",
+                    "            if (!{id}_placed) {{\n                {id}_ranked = arr_concat({id}_ranked, [{id}_hit]);\n            }}\n",
+                    "            {id}_out = arr_slice({id}_ranked, 0, {top_k});\n",
+                    "        }}\n",
+                ),
+                id = node_id,
+                max = MAX_ITEMS,
+                min_score = min_score,
+                top_k = top_k,
+            ),
+        ));
+        code
+    },
+};
+
+static TENSOR_FROM_CSV: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_from_csv"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Load numeric CSV columns as a [rows, cols] tensor"),
+    input_type: DataType::Any,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"path": "data.csv", "header": true, "columns": []}),
+    generate_code: |node_id, config, _input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.csv"));
+        let header = config["header"].as_bool().unwrap_or(true);
+        // No columns selects every column
+        let columns: Vec<String> = config["columns"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|c| c.as_str().map(hlx_string).or_else(|| c.as_u64().map(|i| i.to_string())))
+            .collect();
+        format!(
+            "    let {}_out = tensor_from_csv({}, {}, [{}]);\n",
+            node_id, path, header, columns.join(", ")
+        )
+    },
+};
+
+static TENSOR_FROM_NPY: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_from_npy"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Load a NumPy .npy array (float or int) as a tensor"),
+    input_type: DataType::Any,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"path": "data.npy"}),
+    generate_code: |node_id, config, _input_var| {
+        let path = hlx_string(config["path"].as_str().unwrap_or("data.npy"));
+        format!("    let {}_out = tensor_from_npy({});\n", node_id, path)
+    },
+};
+
+/// Distributions `tensor_random` samples from, with their parameters and defaults
+pub const DISTRIBUTIONS: &[(&str, [(&str, f64); 2])] = &[
+    ("uniform", [("low", 0.0), ("high", 1.0)]),
+    ("normal", [("mean", 0.0), ("std", 1.0)]),
+];
+
+static TENSOR_RANDOM: NodeDef = NodeDef {
+    name: Cow::Borrowed("tensor_random"),
+    category: Cow::Borrowed("ML/GPU"),
+    description: Cow::Borrowed("Random tensor of a shape, from a uniform or normal distribution"),
+    input_type: DataType::Any,
+    output_type: DataType::Tensor,
+    default_config: || serde_json::json!({"shape": [2, 2], "distribution": "uniform", "low": 0.0, "high": 1.0}),
+    generate_code: |node_id, config, _input_var| {
+        let shape: Option<Vec<u64>> = config["shape"]
+            .as_array()
+            .filter(|dims| !dims.is_empty())
+            .and_then(|dims| dims.iter().map(|d| d.as_u64().filter(|d| *d > 0)).collect());
+        let Some(shape) = shape else {
+            return format!(
+                "    // tensor_random: shape must be a list of positive sizes, e.g. [64, 3]\n    let {}_out = null;\n",
+                node_id
+            );
+        };
+
+        let name = config["distribution"].as_str().unwrap_or("uniform");
+        let Some((_, params)) = DISTRIBUTIONS.iter().find(|(d, _)| *d == name) else {
+            return format!(
+                "    // tensor_random: unknown distribution {:?}\n    let {}_out = null;\n",
+                name, node_id
+            );
+        };
+        let [first, second] = params.map(|(key, default)| config[key].as_f64().unwrap_or(default));
+        let seed = config["seed"].as_u64().map(|s| s.to_string()).unwrap_or_else(|| "null".to_string());
+
+        let dims: Vec<String> = shape.iter().map(u64::to_string).collect();
+        format!(
+            "    let {}_out = tensor_random([{}], {}, {:?}, {:?}, {});\n",
+            node_id, dims.join(", "), hlx_string(name), first, second, seed
+        )
     },
 };
 
@@ -764,9 +3622,11 @@ static TENSOR_ADD: NodeDef = NodeDef {
 // ====================
 
 static SLEEP: NodeDef = NodeDef {
-    name: "sleep",
-    category: "System",
-    description: "Sleep for milliseconds",
+    name: Cow::Borrowed("sleep"),
+    category: Cow::Borrowed("System"),
+    description: Cow::Borrowed("Sleep for milliseconds"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({"ms": 1000}),
     generate_code: |node_id, config, input_var| {
         let ms = config["ms"].as_i64().unwrap_or(1000);
@@ -776,11 +3636,30 @@ static SLEEP: NodeDef = NodeDef {
 };
 
 static CAPTURE_SCREEN: NodeDef = NodeDef {
-    name: "capture_screen",
-    category: "System",
-    description: "Capture screenshot",
+    name: Cow::Borrowed("capture_screen"),
+    category: Cow::Borrowed("System"),
+    description: Cow::Borrowed("Capture screenshot"),
+    input_type: DataType::Any,
+    output_type: DataType::Any,
     default_config: || serde_json::json!({}),
     generate_code: |node_id, _config, _input_var| {
         format!("    let {}_out = capture_screen();\n", node_id)
     },
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_ranges_parse_classes_ranges_and_codes() {
+        assert_eq!(status_ranges("2xx,404").unwrap(), [(200, 299), (404, 404)]);
+        assert_eq!(status_ranges(" 200-204 , 3XX, ").unwrap(), [(200, 204), (300, 399)]);
+        assert!(status_ranges("").unwrap().is_empty());
+
+        assert_eq!(status_ranges("2xx,abc").unwrap_err(), "\"abc\" is not an HTTP status");
+        assert_eq!(status_ranges("204-200").unwrap_err(), "\"204-200\" is an empty range");
+        assert!(status_ranges("6xx").is_err());
+        assert!(status_ranges("99").is_err());
+    }
+}
@@ -0,0 +1,64 @@
+//! Offline Mode
+//!
+//! Blocks runs that would reach the network, so flows can be developed on
+//! planes and in air-gapped environments without surprise calls. Nodes are
+//! checked by the HLX they compile to, which covers built-in, generated and
+//! plugin nodes alike; each offending node gets its own error.
+
+use crate::flow::Flow;
+use std::collections::HashMap;
+
+/// Environment variable that turns offline mode on for the whole project
+pub const OFFLINE_ENV: &str = "AUTOGRAPH_OFFLINE";
+
+/// HLX builtins that perform network I/O
+const NETWORK_CALLS: &[&str] = &[
+    "http_request(", "http_request_retry(", "http_fetch(", "http_request_base64(",
+    "http_get(", "http_post(", "http_put(", "http_delete(", "nats_publish(", "sftp_upload(", "sftp_download(", "sftp_list(",
+    "imap_fetch(",
+];
+
+/// Database builtins, which only stay local for SQLite
+const DB_CALLS: &[&str] = &["db_query(", "db_execute("];
+
+/// Whether `AUTOGRAPH_OFFLINE` is set to a true value
+pub fn enabled_by_env() -> bool {
+    std::env::var(OFFLINE_ENV)
+        .map(|v| matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Nodes that would reach the network, with the error to show for each.
+/// Nodes whose output is pinned don't run and are never blocked.
+pub fn blocked_nodes(flow: &Flow, fixed_outputs: &HashMap<String, serde_json::Value>) -> Vec<(String, String)> {
+    flow.nodes
+        .iter()
+        .filter(|node| !fixed_outputs.contains_key(&node.id))
+        .filter_map(|node| {
+            let mut code = crate::nodes::generate_code(&node.type_name, &node.id, &node.config, None)?;
+            if let Some(script) = node.config["code"].as_str().filter(|_| node.type_name == "hlx_script") {
+                code.push_str(script);
+            }
+
+            // State nodes always use their flow's local SQLite store
+            let local = crate::state_store::STATE_NODES.contains(&node.type_name.as_str())
+                || node.config["connection"].as_str().unwrap_or("").starts_with("sqlite:");
+            let remote_db = DB_CALLS.iter().any(|call| code.contains(call)) && !local;
+            if NETWORK_CALLS.iter().any(|call| code.contains(call)) {
+                Some((node.id.clone(), format!("Blocked by offline mode: {} makes network requests", node.type_name)))
+            } else if remote_db {
+                Some((node.id.clone(), format!("Blocked by offline mode: {} connects to a database server", node.type_name)))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Check deployed HLX source that has no flow definition to inspect
+pub fn check_source(source: &str) -> Result<(), String> {
+    match NETWORK_CALLS.iter().chain(DB_CALLS).find(|call| source.contains(*call)) {
+        Some(call) => Err(format!("Blocked by offline mode: source calls {}...)", call)),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,287 @@
+//! OpenAPI Node Generation
+//!
+//! Turns an OpenAPI 3 or Swagger 2 spec into a family of HTTP nodes, one
+//! per operation, with the method, path, parameters and auth pre-filled in
+//! each node's default config. The nodes are registered under the API's
+//! title and saved to `nodes/openapi/` so they are available on every start.
+
+use crate::nodes::{hlx_string, DynamicKind, DynamicNodeDef};
+use serde_json::{json, Map, Value as JsonValue};
+use std::path::{Path, PathBuf};
+
+/// Where generated node families are saved
+pub const OPENAPI_NODES_DIR: &str = "nodes/openapi";
+
+const METHODS: &[&str] = &["get", "post", "put", "patch", "delete", "head", "options"];
+
+/// Nodes generated from one spec
+pub struct GeneratedApi {
+    pub title: String,
+    pub nodes: Vec<DynamicNodeDef>,
+}
+
+/// Fetch a spec from a URL (or read it from a local file) and generate its nodes
+pub fn generate_from(location: &str) -> Result<GeneratedApi, String> {
+    let text = if location.starts_with("http://") || location.starts_with("https://") {
+        ureq::get(location)
+            .call()
+            .map_err(|e| format!("Failed to fetch {}: {}", location, e))?
+            .into_string()
+            .map_err(|e| format!("Failed to read {}: {}", location, e))?
+    } else {
+        std::fs::read_to_string(location).map_err(|e| format!("Failed to read {}: {}", location, e))?
+    };
+
+    // YAML is a superset of JSON, so one parser covers both spec formats
+    let spec: JsonValue = serde_yaml::from_str(&text).map_err(|e| format!("Invalid spec: {}", e))?;
+    generate(&spec, location)
+}
+
+/// Generate one node per operation in a parsed spec
+pub fn generate(spec: &JsonValue, location: &str) -> Result<GeneratedApi, String> {
+    if spec.get("openapi").is_none() && spec.get("swagger").is_none() {
+        return Err("Not an OpenAPI or Swagger document".to_string());
+    }
+    let paths = spec["paths"].as_object().ok_or("Spec has no paths")?;
+
+    let title = spec["info"]["title"].as_str().unwrap_or("API").to_string();
+    let prefix = identifier(&title);
+    let base_url = base_url(spec, location);
+    let auth = default_auth(spec);
+
+    let mut nodes: Vec<DynamicNodeDef> = Vec::new();
+    for (path, item) in paths {
+        for method in METHODS {
+            let Some(op) = item.get(*method) else { continue };
+
+            let op_name = op["operationId"]
+                .as_str()
+                .map(identifier)
+                .unwrap_or_else(|| identifier(&format!("{}_{}", method, path)));
+            let mut name = format!("{}_{}", prefix, op_name);
+            while nodes.iter().any(|n| n.name == name) {
+                name.push('_');
+            }
+
+            // Path-level parameters apply to every operation under the path
+            let params = item["parameters"].as_array().into_iter().flatten()
+                .chain(op["parameters"].as_array().into_iter().flatten());
+            let mut path_params = Map::new();
+            let mut query = Map::new();
+            let mut headers = Map::new();
+            for param in params {
+                let param = resolve(spec, param);
+                let Some(param_name) = param["name"].as_str() else { continue };
+                let default = param["schema"]["default"].clone();
+                let default = match default {
+                    JsonValue::Null => json!(""),
+                    JsonValue::String(_) => default,
+                    other => json!(other.to_string()),
+                };
+                match param["in"].as_str() {
+                    Some("path") => path_params.insert(param_name.to_string(), default),
+                    Some("query") => query.insert(param_name.to_string(), default),
+                    Some("header") => headers.insert(param_name.to_string(), default),
+                    _ => None,
+                };
+            }
+
+            let summary = op["summary"].as_str()
+                .or_else(|| op["description"].as_str())
+                .unwrap_or("");
+            let description = format!("{} {} {}", method.to_uppercase(), path, summary).trim().to_string();
+
+            nodes.push(DynamicNodeDef {
+                name,
+                category: title.clone(),
+                description,
+                default_config: json!({
+                    "base_url": base_url,
+                    "method": method.to_uppercase(),
+                    "path": path,
+                    "path_params": path_params,
+                    "query": query,
+                    "headers": headers,
+                    "auth": auth,
+                }),
+                kind: DynamicKind::OpenApi,
+                color: None,
+                schema: None,
+            });
+        }
+    }
+
+    if nodes.is_empty() {
+        return Err("Spec defines no operations".to_string());
+    }
+    Ok(GeneratedApi { title, nodes })
+}
+
+impl GeneratedApi {
+    /// Register the nodes with the palette and save them for later starts
+    pub fn install(&self, nodes_dir: &Path) -> Result<PathBuf, String> {
+        for node in &self.nodes {
+            crate::nodes::register_dynamic(node.clone())?;
+        }
+
+        std::fs::create_dir_all(nodes_dir).map_err(|e| format!("Failed to create {}: {}", nodes_dir.display(), e))?;
+        let path = nodes_dir.join(format!("{}.json", identifier(&self.title)));
+        let json = serde_json::to_string_pretty(&self.nodes).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+/// Code generator for every OpenAPI node: the request is fully described
+/// by the node's config, and the input becomes the request body
+pub fn generate_code(node_id: &str, config: &JsonValue, input_var: Option<&str>) -> String {
+    let method = config["method"].as_str().unwrap_or("GET");
+
+    // Fill `{param}` segments of the path
+    let mut path = config["path"].as_str().unwrap_or("/").to_string();
+    for (name, value) in config["path_params"].as_object().into_iter().flatten() {
+        path = path.replace(&format!("{{{}}}", name), &percent_encode(&config_str(value)));
+    }
+
+    let mut url = format!("{}{}", config["base_url"].as_str().unwrap_or("").trim_end_matches('/'), path);
+    let mut query: Vec<String> = config["query"].as_object().into_iter().flatten()
+        .filter(|(_, v)| !config_str(v).is_empty())
+        .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(&config_str(v))))
+        .collect();
+
+    let mut headers: Vec<String> = config["headers"].as_object().into_iter().flatten()
+        .filter(|(_, v)| !config_str(v).is_empty())
+        .map(|(k, v)| format!("{}: {}", hlx_string(k), hlx_string(&config_str(v))))
+        .collect();
+
+    let auth = &config["auth"];
+    let secret = auth["value"].as_str().unwrap_or("");
+    if !secret.is_empty() {
+        let header_name = auth["name"].as_str().unwrap_or("X-API-Key");
+        match (auth["type"].as_str(), auth["in"].as_str()) {
+            (Some("bearer"), _) => headers.push(format!("\"Authorization\": {}", hlx_string(&format!("Bearer {}", secret)))),
+            (Some("basic"), _) => headers.push(format!("\"Authorization\": {}", hlx_string(&format!("Basic {}", secret)))),
+            (Some("api_key"), Some("query")) => query.push(format!("{}={}", percent_encode(header_name), percent_encode(secret))),
+            (Some("api_key"), _) => headers.push(format!("{}: {}", hlx_string(header_name), hlx_string(secret))),
+            _ => {}
+        }
+    }
+
+    if !query.is_empty() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&query.join("&"));
+    }
+
+    let body = match method {
+        "GET" | "HEAD" | "DELETE" | "OPTIONS" => "null",
+        _ => input_var.unwrap_or("null"),
+    };
+
+    format!(
+        "    let {}_out = http_request({}, {}, {}, {{ {} }});\n",
+        node_id,
+        hlx_string(method),
+        hlx_string(&url),
+        body,
+        headers.join(", ")
+    )
+}
+
+/// Server URL from `servers` (OpenAPI 3) or `host`/`basePath` (Swagger 2)
+fn base_url(spec: &JsonValue, location: &str) -> String {
+    if let Some(url) = spec["servers"][0]["url"].as_str() {
+        // Relative server URLs are relative to where the spec was fetched
+        if url.starts_with('/') {
+            if let Some(origin) = origin(location) {
+                return format!("{}{}", origin, url);
+            }
+        }
+        return url.to_string();
+    }
+
+    match spec["host"].as_str() {
+        Some(host) => {
+            let scheme = spec["schemes"][0].as_str().unwrap_or("https");
+            format!("{}://{}{}", scheme, host, spec["basePath"].as_str().unwrap_or(""))
+        }
+        None => origin(location).unwrap_or_default(),
+    }
+}
+
+/// `scheme://host[:port]` of a URL
+fn origin(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let host = rest.split('/').next()?;
+    Some(format!("{}://{}", scheme, host))
+}
+
+/// Auth config for the spec's first security scheme, with the secret left blank
+fn default_auth(spec: &JsonValue) -> JsonValue {
+    let schemes = spec["components"]["securitySchemes"]
+        .as_object()
+        .or_else(|| spec["securityDefinitions"].as_object());
+    let Some((_, scheme)) = schemes.and_then(|s| s.iter().next()) else {
+        return json!({"type": "none"});
+    };
+
+    match (scheme["type"].as_str(), scheme["scheme"].as_str()) {
+        (Some("apiKey"), _) => json!({
+            "type": "api_key",
+            "name": scheme["name"].as_str().unwrap_or("X-API-Key"),
+            "in": scheme["in"].as_str().unwrap_or("header"),
+            "value": "",
+        }),
+        (Some("http"), Some("basic")) | (Some("basic"), _) => json!({"type": "basic", "value": ""}),
+        (Some("http"), _) | (Some("oauth2"), _) | (Some("openIdConnect"), _) => {
+            json!({"type": "bearer", "value": ""})
+        }
+        _ => json!({"type": "none"}),
+    }
+}
+
+/// Follow a local `$ref` such as `#/components/parameters/Limit`
+fn resolve<'a>(spec: &'a JsonValue, value: &'a JsonValue) -> &'a JsonValue {
+    match value["$ref"].as_str().and_then(|r| r.strip_prefix('#')) {
+        Some(pointer) => spec.pointer(pointer).unwrap_or(value),
+        None => value,
+    }
+}
+
+/// Config value as plain text
+fn config_str(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Lowercase snake_case identifier from arbitrary text
+fn identifier(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            // Split camelCase operation IDs into words
+            if c.is_ascii_uppercase() && out.chars().last().is_some_and(|p| p.is_ascii_lowercase()) {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    let out = out.trim_end_matches('_').to_string();
+    if out.is_empty() { "api".to_string() } else { out }
+}
+
+/// Percent-encode a URL component
+fn percent_encode(text: &str) -> String {
+    let mut out = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
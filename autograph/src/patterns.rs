@@ -0,0 +1,76 @@
+//! Regex Patterns
+//!
+//! Pattern handling for the `regex_match`, `regex_extract` and
+//! `regex_replace` nodes. Code generation uses it to reject invalid
+//! patterns, and the pattern tester in the properties panel uses it to
+//! preview what a node will output for sample text.
+
+use regex::{Captures, Regex};
+use serde_json::{Map, Value as JsonValue};
+
+/// The node's pattern with its `flags` (any of `imsx`) applied inline
+pub fn pattern(config: &JsonValue) -> String {
+    let pattern = config["pattern"].as_str().unwrap_or("");
+    let flags: String = config["flags"]
+        .as_str()
+        .unwrap_or("")
+        .chars()
+        .filter(|c| "imsx".contains(*c))
+        .collect();
+
+    if flags.is_empty() {
+        pattern.to_string()
+    } else {
+        format!("(?{}){}", flags, pattern)
+    }
+}
+
+/// Compile the node's pattern
+pub fn compile(config: &JsonValue) -> Result<Regex, String> {
+    Regex::new(&pattern(config)).map_err(|e| e.to_string())
+}
+
+/// What `regex_extract` outputs: named groups as an object, otherwise the
+/// numbered groups as an array, or the whole match for a pattern without
+/// groups. `all` returns a list with one entry per match; without it, no
+/// match is `null`.
+pub fn extract(re: &Regex, text: &str, all: bool) -> JsonValue {
+    if all {
+        re.captures_iter(text).map(|caps| captures_to_json(re, &caps)).collect()
+    } else {
+        re.captures(text).map_or(JsonValue::Null, |caps| captures_to_json(re, &caps))
+    }
+}
+
+fn captures_to_json(re: &Regex, caps: &Captures) -> JsonValue {
+    let group = |m: Option<regex::Match>| m.map_or(JsonValue::Null, |m| JsonValue::String(m.as_str().to_string()));
+
+    let names: Vec<&str> = re.capture_names().flatten().collect();
+    if !names.is_empty() {
+        let fields: Map<String, JsonValue> = names.iter().map(|name| (name.to_string(), group(caps.name(name)))).collect();
+        JsonValue::Object(fields)
+    } else if caps.len() > 1 {
+        caps.iter().skip(1).map(group).collect()
+    } else {
+        group(caps.get(0))
+    }
+}
+
+/// Preview a regex node's output for sample text
+pub fn preview(type_name: &str, config: &JsonValue, text: &str) -> Result<JsonValue, String> {
+    let re = compile(config)?;
+    Ok(match type_name {
+        "regex_match" => JsonValue::Bool(re.is_match(text)),
+        "regex_extract" => extract(&re, text, config["all"].as_bool().unwrap_or(false)),
+        "regex_replace" => {
+            let replacement = config["replacement"].as_str().unwrap_or("");
+            let replaced = if config["all"].as_bool().unwrap_or(true) {
+                re.replace_all(text, replacement)
+            } else {
+                re.replace(text, replacement)
+            };
+            JsonValue::String(replaced.into_owned())
+        }
+        other => return Err(format!("{} is not a regex node", other)),
+    })
+}
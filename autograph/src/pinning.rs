@@ -0,0 +1,103 @@
+//! Data Pinning
+//!
+//! A node's captured output can be pinned to it as fixture data. Editor
+//! runs then use the pinned value instead of running the node, and skip the
+//! ancestors that only feed pinned nodes, so downstream transforms can be
+//! worked on against a fixed API response without calling the API again.
+//! Pins are ignored by the CLI and the server.
+
+use crate::flow::{CompileOptions, Flow};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeSet, HashMap};
+
+/// Pinned outputs of the flow's nodes (subgraphs keep every pin)
+fn pins(flow: &Flow) -> impl Iterator<Item = (&String, &JsonValue)> {
+    flow.pinned.iter().filter(|(node_id, _)| flow.nodes.iter().any(|n| &n.id == *node_id))
+}
+
+/// Ancestors of pinned nodes that a run can skip: nodes whose every
+/// consumer is pinned or skipped itself. Nodes without outgoing edges
+/// always run.
+pub fn skipped_ancestors(flow: &Flow) -> BTreeSet<String> {
+    let mut skipped = BTreeSet::new();
+    loop {
+        let next = flow.nodes.iter().find(|node| {
+            let is_unneeded = |target: &String| flow.pinned.contains_key(target) || skipped.contains(target);
+            let mut consumers = flow.edges.iter().filter(|e| e.source == node.id).map(|e| &e.target).peekable();
+            !flow.pinned.contains_key(&node.id)
+                && !skipped.contains(&node.id)
+                && consumers.peek().is_some()
+                && consumers.all(is_unneeded)
+        });
+        match next {
+            Some(node) => skipped.insert(node.id.clone()),
+            None => return skipped,
+        };
+    }
+}
+
+/// Pin the flow's pinned outputs in `options`, overriding restored ones,
+/// and stand in for skipped ancestors with their `last` outputs (null if
+/// they never ran). Returns the skipped ancestors.
+pub fn apply(flow: &Flow, options: &mut CompileOptions, last: &HashMap<String, JsonValue>) -> BTreeSet<String> {
+    for (node_id, value) in pins(flow) {
+        options.fixed_outputs.insert(node_id.clone(), value.clone());
+    }
+    let skipped = skipped_ancestors(flow);
+    for node_id in &skipped {
+        options.fixed_outputs
+            .entry(node_id.clone())
+            .or_insert_with(|| last.get(node_id).cloned().unwrap_or(JsonValue::Null));
+    }
+    skipped
+}
+
+/// Log lines describing a run with pins
+pub fn describe(flow: &Flow, skipped: &BTreeSet<String>) -> Vec<String> {
+    let mut lines: Vec<String> = pins(flow)
+        .map(|(node_id, _)| format!("{} uses its pinned output", node_id))
+        .collect();
+    if !skipped.is_empty() {
+        lines.push(format!(
+            "Skipped {} node(s) that only feed pinned nodes: {}",
+            skipped.len(),
+            skipped.iter().cloned().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::{Edge, Node};
+    use serde_json::json;
+
+    #[test]
+    fn pinned_nodes_skip_the_ancestors_only_they_need() {
+        let node = |id: &str| Node::new(id, "transform", json!({}));
+        let edge = |source: &str, target: &str| Edge {
+            source: source.to_string(),
+            target: target.to_string(),
+            source_handle: None,
+            target_handle: None,
+        };
+        // start -> auth -> fetch -> shape, and start -> audit
+        let mut flow = Flow {
+            nodes: ["start", "auth", "fetch", "shape", "audit"].into_iter().map(node).collect(),
+            edges: vec![edge("start", "auth"), edge("auth", "fetch"), edge("fetch", "shape"), edge("start", "audit")],
+            ..Default::default()
+        };
+        flow.pinned.insert("fetch".to_string(), json!({"items": [1]}));
+
+        let last = HashMap::from([("auth".to_string(), json!("token"))]);
+        let mut options = CompileOptions::default();
+        let skipped = apply(&flow, &mut options, &last);
+
+        assert_eq!(skipped.into_iter().collect::<Vec<_>>(), ["auth"]);
+        assert_eq!(options.fixed_outputs["fetch"], json!({"items": [1]}));
+        assert_eq!(options.fixed_outputs["auth"], json!("token"));
+        assert!(!options.fixed_outputs.contains_key("start"));
+        assert!(!options.fixed_outputs.contains_key("shape"));
+    }
+}
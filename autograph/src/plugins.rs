@@ -0,0 +1,205 @@
+//! Node Plugins
+//!
+//! Organization-specific node types defined in JSON files under `plugins/`,
+//! so they can be added without recompiling Autograph. Each node's HLX is
+//! written as a code template:
+//!
+//! ```json
+//! {
+//!   "plugin": "acme",
+//!   "nodes": [{
+//!     "name": "acme_lookup",
+//!     "category": "Acme",
+//!     "description": "Look up a customer record",
+//!     "default_config": {"region": "eu"},
+//!     "code": "http_get(\"https://api.acme.test/\" + {{config.region}} + \"/customers\")"
+//!   }]
+//! }
+//! ```
+//!
+//! A template is an HLX expression for the node's output. Templates that
+//! need several statements assign `{{out}}` themselves instead. Nodes can
+//! also set a canvas `color` and a `schema` for the properties form.
+//!
+//! The editor polls the directory and reloads plugins when a file is added,
+//! changed or removed, so edits show up without a restart.
+
+use crate::nodes::{hlx_literal, DynamicKind, DynamicNodeDef};
+use serde::Deserialize;
+use serde_json::{Map, Value as JsonValue};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Where plugin files are loaded from
+pub const PLUGINS_DIR: &str = "plugins";
+
+/// One plugin file
+#[derive(Deserialize)]
+struct PluginFile {
+    plugin: String,
+    nodes: Vec<PluginNode>,
+}
+
+#[derive(Deserialize)]
+struct PluginNode {
+    name: String,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default = "empty_object")]
+    default_config: JsonValue,
+    code: String,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    schema: Option<JsonValue>,
+}
+
+fn empty_object() -> JsonValue {
+    JsonValue::Object(Map::new())
+}
+
+/// Register the nodes of every plugin file in `dir`, returning the number
+/// registered and any files or nodes that couldn't be loaded
+pub fn load_plugins(dir: &Path) -> (usize, Vec<String>) {
+    let mut count = 0;
+    let mut errors = Vec::new();
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, errors);
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        let file: PluginFile = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        {
+            Ok(file) => file,
+            Err(e) => {
+                errors.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+
+        for node in file.nodes {
+            let registered = check_template(&node.code).and_then(|()| {
+                crate::nodes::register_dynamic(DynamicNodeDef {
+                    category: node.category.unwrap_or_else(|| file.plugin.clone()),
+                    description: node.description,
+                    default_config: node.default_config,
+                    kind: DynamicKind::Plugin { code: node.code },
+                    name: node.name.clone(),
+                    color: node.color,
+                    schema: node.schema,
+                })
+            });
+            match registered {
+                Ok(()) => count += 1,
+                Err(e) => errors.push(format!("{}: {}: {}", path.display(), node.name, e)),
+            }
+        }
+    }
+    (count, errors)
+}
+
+/// Drop the loaded plugin nodes and load `dir` again
+pub fn reload_plugins(dir: &Path) -> (usize, Vec<String>) {
+    crate::nodes::unregister_plugins();
+    load_plugins(dir)
+}
+
+/// How often the watcher looks at the plugin directory
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Notices plugin files being added, changed or removed
+pub struct PluginWatcher {
+    dir: PathBuf,
+    stamp: Vec<(PathBuf, Option<SystemTime>)>,
+    checked: Instant,
+}
+
+impl PluginWatcher {
+    /// Watch `dir`, taking its current contents as already loaded
+    pub fn new(dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf(), stamp: fingerprint(dir), checked: Instant::now() }
+    }
+
+    /// Reload the plugins if the directory changed since the last poll,
+    /// returning the reload's outcome. Cheap enough to call every frame.
+    pub fn poll(&mut self) -> Option<(usize, Vec<String>)> {
+        if self.checked.elapsed() < POLL_INTERVAL {
+            return None;
+        }
+        self.checked = Instant::now();
+
+        let stamp = fingerprint(&self.dir);
+        if stamp == self.stamp {
+            return None;
+        }
+        self.stamp = stamp;
+        Some(reload_plugins(&self.dir))
+    }
+}
+
+/// Plugin files with their modification times, sorted by path
+fn fingerprint(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(PathBuf, Option<SystemTime>)> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .map(|e| (e.path(), e.metadata().and_then(|m| m.modified()).ok()))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Reject placeholders a template can never fill
+fn check_template(code: &str) -> Result<(), String> {
+    for name in crate::prompt::placeholders(code) {
+        if !matches!(name.as_str(), "id" | "input" | "out") && !name.starts_with("config.") {
+            return Err(format!("unknown placeholder {{{{{}}}}}", name));
+        }
+    }
+    Ok(())
+}
+
+/// Generate a plugin node's HLX from its code template
+pub fn generate_code(code: &str, node_id: &str, config: &JsonValue, input_var: Option<&str>) -> String {
+    let out = format!("{}_out", node_id);
+
+    let mut vars = Map::new();
+    vars.insert("id".to_string(), JsonValue::String(node_id.to_string()));
+    vars.insert("input".to_string(), JsonValue::String(input_var.unwrap_or("null").to_string()));
+    vars.insert("out".to_string(), JsonValue::String(out.clone()));
+    for name in crate::prompt::placeholders(code) {
+        if let Some(key) = name.strip_prefix("config.") {
+            vars.insert(name.clone(), JsonValue::String(hlx_literal(&config[key])));
+        }
+    }
+
+    let rendered = match crate::prompt::render(code, &JsonValue::Object(vars)) {
+        Ok(rendered) => rendered,
+        Err(missing) => {
+            return format!(
+                "    // Plugin template has unknown placeholders: {}\n    let {} = null;\n",
+                missing.join(", "),
+                out
+            );
+        }
+    };
+
+    if crate::prompt::placeholders(code).iter().any(|name| name == "out") {
+        rendered
+            .lines()
+            .map(|line| format!("    {}\n", line.trim_end()))
+            .collect()
+    } else {
+        format!("    let {} = {};\n", out, rendered.trim())
+    }
+}
@@ -0,0 +1,194 @@
+//! Preflight Checks
+//!
+//! Flows declare the external resources they need (environment variables,
+//! credentials, files and network hosts) in their `requires` metadata.
+//! Preflight verifies them before a run, so a missing API key or an
+//! unreachable host is reported up front rather than as a mysterious
+//! failure halfway through the first run. `env:NAME` references in node
+//! configs count as required environment variables even when undeclared.
+
+use crate::flow::Flow;
+use serde::{Deserialize, Serialize};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How long to wait for a required host, name lookup included, to accept
+/// a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// External resources a flow needs to run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Requirements {
+    /// Environment variables that must be set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+    /// Environment variables holding secrets; must be non-empty and are
+    /// never echoed back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub credentials: Vec<String>,
+    /// Files or directories, relative to the project directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// `host` or `host:port` (default port 443) that must accept connections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+}
+
+impl Requirements {
+    pub fn is_empty(&self) -> bool {
+        self.env.is_empty() && self.credentials.is_empty() && self.files.is_empty() && self.hosts.is_empty()
+    }
+}
+
+/// Outcome of checking one resource
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    /// What was checked, e.g. `env OPENAI_API_KEY` or `host api.example.com:443`
+    pub resource: String,
+    /// Why the check failed, or `None` if the resource is available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CheckResult {
+    pub fn ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Check every resource the flow needs against `project_dir`, which holds
+/// its relative files and `.env`, and `vars`, the variables its runs get
+/// besides the environment. Host checks are skipped in offline mode; they
+/// run at once and take up to `CONNECT_TIMEOUT`, so call this off the UI
+/// thread and async runtimes.
+pub fn check(flow: &Flow, project_dir: &Path, vars: &crate::RunVars, offline: bool) -> Vec<CheckResult> {
+    let requires = &flow.requires;
+    let dotenv = read_dotenv(&project_dir.join(".env"));
+    let lookup = |name: &str| {
+        vars.get(name).cloned()
+            .or_else(|| std::env::var(name).ok())
+            .or_else(|| dotenv.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone()))
+    };
+
+    let mut results = Vec::new();
+
+    let mut env_vars = requires.env.clone();
+    for name in referenced_env(flow) {
+        if !env_vars.contains(&name) && !requires.credentials.contains(&name) {
+            env_vars.push(name);
+        }
+    }
+    for name in &env_vars {
+        results.push(CheckResult {
+            resource: format!("env {}", name),
+            error: lookup(name).is_none().then(|| "not set".to_string()),
+        });
+    }
+
+    for name in &requires.credentials {
+        let error = match lookup(name) {
+            None => Some("not set".to_string()),
+            Some(value) if value.trim().is_empty() => Some("set but empty".to_string()),
+            Some(_) => None,
+        };
+        results.push(CheckResult { resource: format!("credential {}", name), error });
+    }
+
+    for file in &requires.files {
+        let path = project_dir.join(file);
+        results.push(CheckResult {
+            resource: format!("file {}", file),
+            error: (!path.exists()).then(|| format!("{} does not exist", path.display())),
+        });
+    }
+
+    let probes: Vec<_> = requires.hosts.iter()
+        .map(|host| {
+            let address = if host.contains(':') { host.clone() } else { format!("{}:443", host) };
+            let probe = (!offline).then(|| probe(address.clone()));
+            (address, probe)
+        })
+        .collect();
+    for (address, probe) in probes {
+        let error = probe.and_then(|answer| match answer.recv_timeout(CONNECT_TIMEOUT) {
+            Ok(result) => result.err(),
+            Err(_) => Some(format!("no answer within {}s", CONNECT_TIMEOUT.as_secs())),
+        });
+        results.push(CheckResult { resource: format!("host {}", address), error });
+    }
+
+    results
+}
+
+/// Human-readable errors for the failed checks
+pub fn failures(results: &[CheckResult]) -> Vec<String> {
+    results
+        .iter()
+        .filter_map(|r| r.error.as_ref().map(|e| format!("{}: {}", r.resource, e)))
+        .collect()
+}
+
+/// Variables named by `env:NAME` values anywhere in the flow's node configs
+pub fn referenced_env(flow: &Flow) -> Vec<String> {
+    fn visit(value: &serde_json::Value, names: &mut Vec<String>) {
+        match value {
+            serde_json::Value::String(s) => {
+                if let Some(name) = s.strip_prefix("env:").filter(|n| !n.is_empty()) {
+                    if !names.iter().any(|n| n == name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| visit(v, names)),
+            serde_json::Value::Object(fields) => fields.values().for_each(|v| visit(v, names)),
+            _ => {}
+        }
+    }
+
+    let mut names = Vec::new();
+    for node in &flow.nodes {
+        visit(&node.config, &mut names);
+    }
+    names
+}
+
+/// Connect to `address` on a thread of its own, so a name lookup that
+/// hangs can't hold up the check
+fn probe(address: String) -> mpsc::Receiver<Result<(), String>> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(connect(&address));
+    });
+    receiver
+}
+
+fn connect(address: &str) -> Result<(), String> {
+    let addrs: Vec<_> = address
+        .to_socket_addrs()
+        .map_err(|e| format!("cannot resolve: {}", e))?
+        .collect();
+    let mut last_error = "no addresses".to_string();
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+    Err(format!("unreachable: {}", last_error))
+}
+
+/// `KEY=VALUE` pairs from a `.env` file, ignoring comments and blank lines
+fn read_dotenv(path: &Path) -> Vec<(String, String)> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.strip_prefix("export ").unwrap_or(line).split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().trim_matches('"').to_string()))
+        .collect()
+}
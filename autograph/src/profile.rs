@@ -0,0 +1,203 @@
+//! Flow Profiling
+//!
+//! Measures how long each node of a flow takes and how much data it
+//! produces, for performance reviews of production flows. A flow compiles
+//! to a single HLX program, so each node is timed on its own: its ancestors'
+//! outputs are pinned from a full run, and the time of the same program with
+//! the node pinned too is subtracted to cancel out the fixed overhead.
+//!
+//! Flows have no loop nodes yet, so every node runs exactly once per run.
+
+use crate::flow::{CompileOptions, Flow};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Timings and sizes for one node
+pub struct NodeProfile {
+    pub id: String,
+    pub type_name: String,
+    /// Fastest of the timed iterations
+    pub duration: Duration,
+    pub calls: usize,
+    /// Size of the node's output as JSON
+    pub output_bytes: usize,
+}
+
+/// Profile of a whole flow
+pub struct FlowProfile {
+    pub flow_name: String,
+    /// Wall time of the full run
+    pub total: Duration,
+    pub input_bytes: usize,
+    pub nodes: Vec<NodeProfile>,
+    /// HLX source of the full run
+    pub source: String,
+}
+
+/// Run a flow once to capture every output, then time each node.
+///
+/// `run(source, input)` compiles and executes HLX; each node is timed over
+/// `iterations` runs and the fastest is kept.
+pub fn profile(
+    flow_name: &str,
+    flow: &Flow,
+    input: &JsonValue,
+    seed: Option<u64>,
+    iterations: usize,
+    mut run: impl FnMut(&str, &JsonValue) -> Result<JsonValue, String>,
+) -> Result<FlowProfile, String> {
+    let options = CompileOptions { capture_outputs: true, seed, ..Default::default() };
+    let source = flow.compile_with(&options);
+
+    let start = Instant::now();
+    let json = run(&source, input)?;
+    let total = start.elapsed();
+
+    let outputs: HashMap<String, JsonValue> = json["outputs"]
+        .as_object()
+        .map(|o| o.clone().into_iter().collect())
+        .unwrap_or_default();
+
+    let mut nodes = Vec::new();
+    for node in &flow.nodes {
+        let subflow = flow.ancestor_subgraph(&node.id);
+        let upstream: HashMap<String, JsonValue> = outputs.iter()
+            .filter(|(id, _)| *id != &node.id && subflow.nodes.iter().any(|n| &n.id == *id))
+            .map(|(id, value)| (id.clone(), value.clone()))
+            .collect();
+
+        let with_node = subflow.compile_with(&CompileOptions { fixed_outputs: upstream.clone(), ..options.clone() });
+        let mut pinned = upstream;
+        pinned.insert(node.id.clone(), outputs.get(&node.id).cloned().unwrap_or(JsonValue::Null));
+        let without_node = subflow.compile_with(&CompileOptions { fixed_outputs: pinned, ..options.clone() });
+
+        let measured = fastest(iterations, || run(&with_node, input))
+            .map_err(|e| format!("Node {} failed: {}", node.id, e))?;
+        let overhead = fastest(iterations, || run(&without_node, input))?;
+
+        nodes.push(NodeProfile {
+            id: node.id.clone(),
+            type_name: node.type_name.clone(),
+            duration: measured.saturating_sub(overhead),
+            calls: 1,
+            output_bytes: outputs.get(&node.id).map_or(0, |v| v.to_string().len()),
+        });
+    }
+
+    Ok(FlowProfile {
+        flow_name: flow_name.to_string(),
+        total,
+        input_bytes: input.to_string().len(),
+        nodes,
+        source,
+    })
+}
+
+/// Fastest wall time of `iterations` runs
+fn fastest(
+    iterations: usize,
+    mut run: impl FnMut() -> Result<JsonValue, String>,
+) -> Result<Duration, String> {
+    let mut best = Duration::MAX;
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        run()?;
+        best = best.min(start.elapsed());
+    }
+    Ok(best)
+}
+
+impl FlowProfile {
+    /// Nodes ordered slowest first
+    fn by_duration(&self) -> Vec<&NodeProfile> {
+        let mut nodes: Vec<&NodeProfile> = self.nodes.iter().collect();
+        nodes.sort_by(|a, b| b.duration.cmp(&a.duration));
+        nodes
+    }
+
+    /// Share of the summed node time spent in a node
+    fn share(&self, node: &NodeProfile) -> f64 {
+        let sum: Duration = self.nodes.iter().map(|n| n.duration).sum();
+        if sum.is_zero() {
+            return 0.0;
+        }
+        node.duration.as_secs_f64() / sum.as_secs_f64() * 100.0
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Profile: {}\n\n", self.flow_name);
+        out.push_str(&format!("- Total run time: {}\n", format_duration(self.total)));
+        out.push_str(&format!("- Input size: {}\n", crate::gpu_stats::format_bytes(self.input_bytes as u64)));
+        out.push_str(&format!("- Nodes: {}\n\n", self.nodes.len()));
+
+        out.push_str("## Nodes\n\n");
+        out.push_str("| Node | Type | Duration | Share | Calls | Output size |\n");
+        out.push_str("|------|------|---------:|------:|------:|------------:|\n");
+        for node in self.by_duration() {
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {:.1}% | {} | {} |\n",
+                node.id,
+                node.type_name,
+                format_duration(node.duration),
+                self.share(node),
+                node.calls,
+                crate::gpu_stats::format_bytes(node.output_bytes as u64)
+            ));
+        }
+
+        out.push_str("\n## Generated HLX\n\n```hlx\n");
+        out.push_str(&self.source);
+        out.push_str("```\n");
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut rows = String::new();
+        for node in self.by_duration() {
+            rows.push_str(&format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td>\
+                 <td><div class=\"bar\" style=\"width:{:.0}%\"></div>{:.1}%</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&node.id),
+                escape_html(&node.type_name),
+                format_duration(node.duration),
+                self.share(node),
+                self.share(node),
+                node.calls,
+                crate::gpu_stats::format_bytes(node.output_bytes as u64)
+            ));
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Profile: {name}</title>\n\
+             <style>\n\
+             body {{ font-family: sans-serif; margin: 2em; }}\n\
+             table {{ border-collapse: collapse; }}\n\
+             th, td {{ padding: 4px 12px; border-bottom: 1px solid #ddd; text-align: left; }}\n\
+             .bar {{ display: inline-block; height: 0.8em; background: #4a90d9; margin-right: 6px; }}\n\
+             pre {{ background: #f5f5f5; padding: 1em; }}\n\
+             </style>\n</head>\n<body>\n\
+             <h1>Profile: {name}</h1>\n\
+             <p>Total run time: {total} &middot; Input size: {input} &middot; Nodes: {count}</p>\n\
+             <h2>Nodes</h2>\n<table>\n\
+             <tr><th>Node</th><th>Type</th><th>Duration</th><th>Share</th><th>Calls</th><th>Output size</th></tr>\n\
+             {rows}</table>\n\
+             <h2>Generated HLX</h2>\n<pre>{source}</pre>\n</body>\n</html>\n",
+            name = escape_html(&self.flow_name),
+            total = format_duration(self.total),
+            input = crate::gpu_stats::format_bytes(self.input_bytes as u64),
+            count = self.nodes.len(),
+            rows = rows,
+            source = escape_html(&self.source),
+        )
+    }
+}
+
+/// Milliseconds with sub-millisecond precision
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
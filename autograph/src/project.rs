@@ -0,0 +1,115 @@
+//! Projects
+//!
+//! A project file, `autograph.project.json`, records the flows open in the
+//! editor's tabs along with variables and secrets shared by every flow in
+//! the project, and the auth profiles its HTTP nodes use. Secrets are
+//! referenced by the name of the environment variable holding them; their
+//! values are never written to the file, and a profile with a secret typed
+//! in as plain text is rejected.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Default project file, in the working directory
+pub const PROJECT_FILE: &str = "autograph.project.json";
+
+/// Open flows plus shared variables and secret references
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    /// Names of the flows open in tabs, in tab order
+    #[serde(default)]
+    pub open_flows: Vec<String>,
+    /// Index into `open_flows` of the active tab
+    #[serde(default)]
+    pub active: usize,
+    /// Values every flow can read as `env:NAME`, unless the environment
+    /// already sets `NAME`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Environment variables holding secrets the project's flows use
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+    /// Credentials HTTP nodes pick by name with `"auth"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub auth_profiles: BTreeMap<String, crate::http_auth::AuthProfile>,
+}
+
+impl Project {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+        let project: Project = serde_json::from_str(&json).map_err(|e| format!("Invalid project file: {}", e))?;
+        project.validate()?;
+        Ok(project)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        self.validate()?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Can't write {}: {}", path.display(), e))
+    }
+
+    /// Reject names that can't be environment variables and auth profiles
+    /// with plain-text secrets
+    pub fn validate(&self) -> Result<(), String> {
+        for name in self.variables.keys() {
+            if !is_valid_env_name(name) {
+                return Err(format!("Invalid variable name '{}'", name));
+            }
+        }
+        for name in &self.secrets {
+            if !is_valid_env_name(name) {
+                return Err(format!("Invalid secret name '{}'", name));
+            }
+        }
+        for (name, profile) in &self.auth_profiles {
+            if !is_valid_env_name(name) {
+                return Err(format!("Invalid auth profile name '{}'", name));
+            }
+            if let Some(field) = profile.plaintext_secrets().first() {
+                return Err(format!("Auth profile '{}': {} must be an env:NAME reference, not the secret itself", name, field));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Letters, digits and underscores, not starting with a digit
+pub fn is_valid_env_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_round_trips_and_rejects_bad_names() {
+        let dir = std::env::temp_dir().join(format!("autograph-project-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(PROJECT_FILE);
+
+        let mut project = Project {
+            open_flows: vec!["fetch".to_string(), "report".to_string()],
+            active: 1,
+            ..Default::default()
+        };
+        project.variables.insert("BASE_URL".to_string(), "https://example.com".to_string());
+        project.secrets.push("API_KEY".to_string());
+        project.save(&path).unwrap();
+        assert_eq!(Project::load(&path).unwrap(), project);
+
+        project.secrets.push("1BAD".to_string());
+        assert!(project.save(&path).is_err());
+        project.secrets.pop();
+
+        // Secrets in auth profiles are env:NAME references only
+        let plaintext = crate::http_auth::AuthProfile::Bearer { token: "ghp_123".to_string() };
+        project.auth_profiles.insert("github".to_string(), plaintext);
+        assert!(project.save(&path).unwrap_err().contains("token must be an env:NAME reference"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
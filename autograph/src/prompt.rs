@@ -0,0 +1,130 @@
+//! Prompt Templates
+//!
+//! Multi-line templates with `{{variable}}` placeholders, used by the
+//! `prompt_template` and `template_render` nodes. The same parsing drives
+//! code generation, the declared-variable checks and the rendered preview
+//! in the inspector. Placeholders may be dotted paths such as
+//! `{{user.name}}` or `{{items.0}}`.
+
+use serde_json::Value as JsonValue;
+
+/// Names of the `{{placeholders}}` in a template, in order of first use
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in segments(template).iter().filter_map(Segment::name) {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Mismatches between a template's placeholders and its declared variables
+pub fn check(template: &str, declared: &[String]) -> Vec<String> {
+    let used = placeholders(template);
+    let mut problems = Vec::new();
+
+    for name in &used {
+        if !declared.contains(name) {
+            problems.push(format!("{{{{{}}}}} is used but not declared", name));
+        }
+    }
+    for name in declared {
+        if !used.contains(name) {
+            problems.push(format!("'{}' is declared but never used", name));
+        }
+    }
+    problems
+}
+
+/// Render a template with variables taken from a JSON object, resolving
+/// dotted paths into nested objects and arrays.
+///
+/// Fails with the names of any placeholders the object doesn't supply.
+pub fn render(template: &str, vars: &JsonValue) -> Result<String, Vec<String>> {
+    let mut out = String::new();
+    let mut missing = Vec::new();
+
+    for segment in segments(template) {
+        match segment {
+            Segment::Text(text) => out.push_str(text),
+            Segment::Placeholder(raw, name) => match lookup(vars, name) {
+                Some(JsonValue::String(s)) => out.push_str(s),
+                Some(value) => out.push_str(&value.to_string()),
+                None => {
+                    if !missing.iter().any(|m| m == name) {
+                        missing.push(name.to_string());
+                    }
+                    out.push_str(raw);
+                }
+            },
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(out)
+    } else {
+        Err(missing)
+    }
+}
+
+/// Value at a dotted path, where numeric segments index arrays
+fn lookup<'v>(vars: &'v JsonValue, path: &str) -> Option<&'v JsonValue> {
+    path.split('.').try_fold(vars, |value, key| match value {
+        JsonValue::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })
+}
+
+/// Declared variable names from a node config's `variables` array
+pub fn declared_variables(config: &JsonValue) -> Vec<String> {
+    config["variables"]
+        .as_array()
+        .map(|vars| vars.iter().filter_map(|v| v.as_str()).map(|v| v.to_string()).collect())
+        .unwrap_or_default()
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    /// Raw `{{ name }}` text and the trimmed name
+    Placeholder(&'a str, &'a str),
+}
+
+impl<'a> Segment<'a> {
+    fn name(&self) -> Option<&'a str> {
+        match self {
+            Segment::Placeholder(_, name) => Some(*name),
+            Segment::Text(_) => None,
+        }
+    }
+}
+
+/// Split a template into literal text and placeholders
+fn segments(template: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len + 2;
+        let name = rest[start + 2..end - 2].trim();
+
+        // `{{}}` and names with spaces are left as literal text
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            segments.push(Segment::Text(&rest[..end]));
+        } else {
+            if start > 0 {
+                segments.push(Segment::Text(&rest[..start]));
+            }
+            segments.push(Segment::Placeholder(&rest[start..end], name));
+        }
+        rest = &rest[end..];
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    segments
+}
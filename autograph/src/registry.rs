@@ -0,0 +1,120 @@
+//! Contribution Registry
+//!
+//! Lets binaries embedding autograph contribute their own workflow
+//! templates and node categories in code rather than through files.
+//! Everything a crate contributes lives under its namespace and is shown
+//! as `namespace/Name`, so contributions can't clash with the built-ins or
+//! with each other. Registering a template again replaces the earlier one.
+
+use crate::flow::Flow;
+use crate::templates::{TemplateFlow, WorkflowTemplate};
+use std::borrow::Cow;
+use std::sync::{Arc, RwLock};
+
+/// Namespaces no embedder may claim
+const RESERVED: &[&str] = &["autograph", "builtin", "core", "hlx"];
+
+/// A node category contributed under a namespace
+struct Category {
+    name: String,
+    color: [u8; 3],
+}
+
+static TEMPLATES: RwLock<Vec<Arc<WorkflowTemplate>>> = RwLock::new(Vec::new());
+static CATEGORIES: RwLock<Vec<Category>> = RwLock::new(Vec::new());
+
+/// Handle for contributing under one namespace, e.g. `Namespace::new("acme")`
+#[derive(Debug, Clone)]
+pub struct Namespace {
+    name: String,
+}
+
+impl Namespace {
+    /// Claim a namespace: lowercase letters, digits, `_` and `-`, starting
+    /// with a letter, and not one of the reserved names
+    pub fn new(name: &str) -> Result<Self, String> {
+        let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if !valid {
+            return Err(format!("invalid namespace {:?} (use lowercase letters, digits, _ and -)", name));
+        }
+        if RESERVED.contains(&name) {
+            return Err(format!("namespace '{}' is reserved", name));
+        }
+        Ok(Self { name: name.to_string() })
+    }
+
+    /// `namespace/name`
+    pub fn qualify(&self, name: &str) -> String {
+        format!("{}/{}", self.name, name)
+    }
+
+    /// Add a template to the Templates menu as `namespace/name`, replacing
+    /// an earlier one of the same name
+    pub fn register_template(
+        &self,
+        name: &str,
+        category: &str,
+        description: &str,
+        create: fn() -> Flow,
+    ) -> Result<Arc<WorkflowTemplate>, String> {
+        if name.trim().is_empty() {
+            return Err("template name is empty".to_string());
+        }
+        let template = Arc::new(WorkflowTemplate {
+            name: Cow::Owned(self.qualify(name)),
+            description: Cow::Owned(description.to_string()),
+            category: Cow::Owned(category.to_string()),
+            params: Cow::Borrowed(&[]),
+            flow: TemplateFlow::Code(create),
+        });
+
+        let mut templates = TEMPLATES.write().unwrap();
+        templates.retain(|t| t.name != template.name);
+        templates.push(template.clone());
+        Ok(template)
+    }
+
+    /// Add a node category shown as `namespace/name` with its canvas color.
+    /// Returns the qualified name to use as a `DynamicNodeDef`'s category.
+    pub fn register_category(&self, name: &str, color: [u8; 3]) -> Result<String, String> {
+        if name.trim().is_empty() {
+            return Err("category name is empty".to_string());
+        }
+        let name = self.qualify(name);
+
+        let mut categories = CATEGORIES.write().unwrap();
+        categories.retain(|c| c.name != name);
+        categories.push(Category { name: name.clone(), color });
+        Ok(name)
+    }
+}
+
+/// Every contributed template
+pub fn templates() -> Vec<Arc<WorkflowTemplate>> {
+    TEMPLATES.read().unwrap().clone()
+}
+
+/// Canvas color of a contributed category
+pub fn category_color(category: &str) -> Option<[u8; 3]> {
+    CATEGORIES.read().unwrap().iter().find(|c| c.name == category).map(|c| c.color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contributions_are_namespaced_and_listed_with_the_built_ins() {
+        assert!(Namespace::new("hlx").is_err());
+        assert!(Namespace::new("Acme").is_err());
+
+        let acme = Namespace::new("acme").unwrap();
+        acme.register_template("Invoice Sync", "Billing", "Pull invoices", Flow::default).unwrap();
+        let listed: Vec<String> = crate::templates::all_templates().iter().map(|t| t.name.to_string()).collect();
+        assert!(listed.contains(&"acme/Invoice Sync".to_string()));
+
+        let category = acme.register_category("Billing", [46, 139, 87]).unwrap();
+        assert_eq!(category_color(&category), Some([46, 139, 87]));
+    }
+}
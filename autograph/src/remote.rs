@@ -0,0 +1,110 @@
+//! Remote Server Client
+//!
+//! Connects the editor to a running `autograph server`: deploy the flow in
+//! the editor through `/deploy`, run it through `/run`, and fetch the run's
+//! record, with every node's output, to show in the editor's timeline.
+//! Each remote run is labelled with a random `editor_request` so its record
+//! can be found among concurrent runs.
+
+use crate::flow::Flow;
+use crate::runs::RunRecord;
+use serde_json::Value as JsonValue;
+
+/// Label key that identifies a run started from the editor
+const REQUEST_LABEL: &str = "editor_request";
+
+/// Address and credentials of an Autograph server
+#[derive(Debug, Clone)]
+pub struct RemoteServer {
+    /// Base URL, e.g. `http://localhost:3000`
+    pub url: String,
+    /// API key, sent as `Authorization: Bearer`, when the server requires one
+    pub api_key: Option<String>,
+}
+
+impl RemoteServer {
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.url.trim_end_matches('/'), path)
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let request = ureq::request(method, &self.endpoint(path));
+        match self.api_key.as_deref().filter(|k| !k.is_empty()) {
+            Some(key) => request.set("Authorization", &format!("Bearer {}", key)),
+            None => request,
+        }
+    }
+
+    /// Deploy `flow` as `flow_name`, returning the server's message
+    pub fn deploy(&self, flow_name: &str, flow: &Flow, message: &str, author: &str) -> Result<String, String> {
+        let body = serde_json::to_string(flow).map_err(|e| e.to_string())?;
+        let response = self
+            .request("POST", &format!("/deploy/{}", flow_name))
+            .query("message", message)
+            .query("author", author)
+            .set("Content-Type", "application/json")
+            .send_string(&body);
+        let json = read_json(response)?;
+        if let Some(error) = json.get("error") {
+            let missing = json["missing"].as_array()
+                .map(|m| m.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join("; "))
+                .filter(|m| !m.is_empty());
+            return Err(match missing {
+                Some(missing) => format!("{}: {}", error.as_str().unwrap_or_default(), missing),
+                None => error.as_str().unwrap_or_default().to_string(),
+            });
+        }
+        Ok(json["message"].as_str().unwrap_or("Deployed").to_string())
+    }
+
+    /// Run the deployed flow `flow_name` and fetch the run's record. Fails
+    /// only when the server couldn't start the run; a run that failed
+    /// comes back as a record with its error.
+    pub fn run(&self, flow_name: &str, input: &JsonValue) -> Result<RunRecord, String> {
+        let token = request_token()?;
+        let labels = format!("{}={}", REQUEST_LABEL, token);
+        let response = self
+            .request("POST", &format!("/run/{}", flow_name))
+            .query("labels", &labels)
+            .set("Content-Type", "application/json")
+            .send_string(&input.to_string());
+        let result = read_json(response);
+
+        let runs = read_json(
+            self.request("GET", &format!("/flows/{}/runs", flow_name))
+                .query("labels", &labels)
+                .call(),
+        )?;
+        let Some(run_id) = runs["runs"].get(0).and_then(|r| r["id"].as_str()) else {
+            // No record: the server rejected the run before starting it
+            return Err(match result {
+                Ok(json) => json["error"].as_str().unwrap_or("The server recorded no run").to_string(),
+                Err(e) => e,
+            });
+        };
+        let record = read_json(self.request("GET", &format!("/runs/{}", run_id)).call())?;
+        serde_json::from_value(record).map_err(|e| format!("Unexpected run record: {}", e))
+    }
+}
+
+/// The JSON body of a response, with HTTP and transport errors as messages
+fn read_json(response: Result<ureq::Response, ureq::Error>) -> Result<JsonValue, String> {
+    let response = match response {
+        Ok(response) => response,
+        Err(ureq::Error::Status(401, _)) => return Err("The server needs an API key (401)".to_string()),
+        Err(ureq::Error::Status(403, _)) => return Err("The API key lacks the scope for this (403)".to_string()),
+        Err(ureq::Error::Status(code, response)) => {
+            return Err(format!("Server returned {}: {}", code, response.into_string().unwrap_or_default()));
+        }
+        Err(e) => return Err(format!("Can't reach the server: {}", e)),
+    };
+    let text = response.into_string().map_err(|e| e.to_string())?;
+    serde_json::from_str(&text).map_err(|e| format!("Unexpected response: {}", e))
+}
+
+/// Random hex token identifying one remote run
+fn request_token() -> Result<String, String> {
+    let mut bytes = [0u8; 8];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("No randomness available: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
@@ -0,0 +1,255 @@
+//! Execution Reports
+//!
+//! A self-contained report of one recorded run, for sharing a debugging
+//! session: the flow as it ran, the input, each node's output and timing,
+//! the log and the final result. As JSON it is the run record with the
+//! flow's nodes and outputs put side by side; as HTML it is a single page
+//! with no external assets, so it can be attached to a ticket or mailed.
+
+use crate::profile::escape_html;
+use crate::runs::{format_timestamp, RunRecord, RunStatus};
+use serde_json::{json, Value as JsonValue};
+
+/// Format a report is rendered in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Html,
+}
+
+impl ReportFormat {
+    /// `json` or `html`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Self::Json),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+/// One node's part of a run
+struct NodeRow<'a> {
+    id: &'a str,
+    type_name: &'a str,
+    duration_ms: Option<u64>,
+    output: Option<&'a JsonValue>,
+}
+
+/// The run's nodes in flow order, then any outputs of nodes the recorded
+/// flow doesn't have (runs of compiled-only deployments have no flow)
+fn node_rows(record: &RunRecord) -> Vec<NodeRow<'_>> {
+    let nodes = record.flow.as_ref().map(|f| f.nodes.as_slice()).unwrap_or_default();
+    let mut rows: Vec<NodeRow> = nodes.iter()
+        .map(|node| NodeRow {
+            id: &node.id,
+            type_name: &node.type_name,
+            duration_ms: record.node_durations.get(&node.id).copied(),
+            output: record.node_outputs.get(&node.id),
+        })
+        .collect();
+    for (id, output) in &record.node_outputs {
+        if !nodes.iter().any(|n| &n.id == id) {
+            rows.push(NodeRow {
+                id,
+                type_name: "?",
+                duration_ms: record.node_durations.get(id).copied(),
+                output: Some(output),
+            });
+        }
+    }
+    rows
+}
+
+/// Render the report for a run
+pub fn render(record: &RunRecord, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&to_json(record)).unwrap_or_default(),
+        ReportFormat::Html => to_html(record),
+    }
+}
+
+pub fn to_json(record: &RunRecord) -> JsonValue {
+    let nodes: Vec<JsonValue> = node_rows(record).iter()
+        .map(|row| json!({
+            "id": row.id,
+            "type": row.type_name,
+            "duration_ms": row.duration_ms,
+            "output": row.output,
+        }))
+        .collect();
+
+    json!({
+        "run": record.summary(),
+        "flow": record.flow,
+        "input": record.input,
+        "nodes": nodes,
+        "logs": record.structured_log(),
+        "result": record.result,
+        "error": record.error,
+        "gpu_memory": record.gpu_memory,
+    })
+}
+
+pub fn to_html(record: &RunRecord) -> String {
+    let pretty = |value: &JsonValue| escape_html(&serde_json::to_string_pretty(value).unwrap_or_default());
+
+    let (status, color) = match record.status {
+        RunStatus::Queued => ("queued", "#757575"),
+        RunStatus::Running => ("running", "#b8860b"),
+        RunStatus::Success => ("success", "#2e7d32"),
+        RunStatus::Error => ("error", "#c62828"),
+        RunStatus::Interrupted => ("interrupted", "#ef6c00"),
+    };
+
+    let mut summary = format!(
+        "<p><b style=\"color:{}\">{}</b> &middot; started {} &middot; {} ms",
+        color,
+        status,
+        format_timestamp(record.started_at),
+        record.duration_ms
+    );
+    if record.attempt > 1 {
+        summary.push_str(&format!(" &middot; attempt {}", record.attempt));
+    }
+    if record.chaos {
+        summary.push_str(" &middot; chaos");
+    }
+    for (key, value) in &record.labels {
+        summary.push_str(&format!(" &middot; <code>{}={}</code>", escape_html(key), escape_html(value)));
+    }
+    if let Some(stats) = &record.gpu_memory {
+        summary.push_str(&format!(" &middot; GPU memory: {}", escape_html(&stats.summary())));
+    }
+    summary.push_str("</p>\n");
+
+    let outcome = match (&record.result, &record.error) {
+        (_, Some(error)) => format!("<h2>Error</h2>\n<pre class=\"error\">{}</pre>\n", escape_html(error)),
+        (Some(result), None) => format!("<h2>Result</h2>\n<pre>{}</pre>\n", pretty(result)),
+        (None, None) => String::new(),
+    };
+
+    let mut nodes = String::new();
+    for row in node_rows(record) {
+        let duration = row.duration_ms.map_or("&mdash;".to_string(), |ms| format!("{} ms", ms));
+        let output = match row.output {
+            Some(output) => format!(
+                "<details><summary>{}</summary><pre>{}</pre></details>",
+                crate::gpu_stats::format_bytes(output.to_string().len() as u64),
+                pretty(output)
+            ),
+            None => "&mdash;".to_string(),
+        };
+        nodes.push_str(&format!(
+            "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(row.id),
+            escape_html(row.type_name),
+            duration,
+            output
+        ));
+    }
+
+    let mut logs = String::new();
+    for event in record.structured_log() {
+        let node = event.node.as_deref().map(|n| format!("<code>{}</code> ", escape_html(n))).unwrap_or_default();
+        logs.push_str(&format!(
+            "<tr class=\"{level:?}\"><td>{}</td><td>{level:?}</td><td>{}{}</td></tr>\n",
+            format_timestamp(event.at),
+            node,
+            escape_html(&event.message),
+            level = event.level,
+        ));
+    }
+
+    let flow = match &record.flow {
+        Some(flow) => format!(
+            "<h2>Flow</h2>\n<details><summary>{} node(s), {} edge(s)</summary><pre>{}</pre></details>\n",
+            flow.nodes.len(),
+            flow.edges.len(),
+            escape_html(&serde_json::to_string_pretty(flow).unwrap_or_default())
+        ),
+        None => String::new(),
+    };
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Run {id}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ padding: 4px 12px; border-bottom: 1px solid #ddd; text-align: left; vertical-align: top; }}\n\
+         pre {{ background: #f5f5f5; padding: 1em; margin: 0; }}\n\
+         .error {{ color: #c62828; }}\n\
+         .Warn td {{ color: #ef6c00; }}\n\
+         .Error td {{ color: #c62828; }}\n\
+         </style>\n</head>\n<body>\n\
+         <h1>Run of {name}</h1>\n\
+         <p><code>{id}</code></p>\n\
+         {summary}\
+         {outcome}\
+         <h2>Input</h2>\n<pre>{input}</pre>\n\
+         <h2>Nodes</h2>\n<table>\n\
+         <tr><th>Node</th><th>Type</th><th>Duration</th><th>Output</th></tr>\n\
+         {nodes}</table>\n\
+         <h2>Log</h2>\n<table>\n{logs}</table>\n\
+         {flow}\
+         </body>\n</html>\n",
+        id = escape_html(&record.id),
+        name = escape_html(&record.flow_name),
+        summary = summary,
+        outcome = outcome,
+        input = pretty(&record.input),
+        nodes = nodes,
+        logs = logs,
+        flow = flow,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::{Flow, Node};
+    use crate::runs::LogLevel;
+
+    #[test]
+    fn reports_nodes_in_flow_order_with_outputs_and_timings() {
+        let node = |id: &str| Node::new(id, "transform", json!({"token": "s3cret"}));
+        let mut record = RunRecord::new("orders", 0, json!({"id": 7}));
+        record.set_flow(&Flow { nodes: vec![node("load"), node("shape")], ..Default::default() });
+        record.node_outputs.insert("shape".to_string(), json!({"html": "<b>"}));
+        record.node_outputs.insert("load".to_string(), json!(1));
+        record.node_durations.insert("load".to_string(), 12);
+        record.log(LogLevel::Warn, Some("shape"), "slow");
+        record.finish(20, Ok(json!("done")));
+
+        let report = to_json(&record);
+        let ids: Vec<&str> = report["nodes"].as_array().unwrap().iter().map(|n| n["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["load", "shape"]);
+        assert_eq!(report["nodes"][0]["duration_ms"], 12);
+        assert_eq!(report["nodes"][1]["duration_ms"], JsonValue::Null);
+        assert_eq!(report["logs"][0]["node"], "shape");
+        assert_eq!(record.log_lines(), ["shape: slow"]);
+        assert!(record.logs.is_empty());
+
+        assert_eq!(report["flow"]["nodes"][0]["type_name"], "transform");
+        assert!(!report.to_string().contains("s3cret"));
+        assert_eq!(record.flow_hash.as_ref().map(String::len), Some(64));
+
+        let html = to_html(&record);
+        assert!(html.contains("&quot;html&quot;: &quot;&lt;b&gt;&quot;"));
+        assert!(html.contains("12 ms"));
+    }
+}
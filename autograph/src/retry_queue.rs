@@ -0,0 +1,127 @@
+//! Retry Queue
+//!
+//! Failed server runs waiting for an automatic re-run under their flow's
+//! `retry` policy. The queue lives in one JSON file so pending retries
+//! survive a restart; the server's scheduler polls it for due entries.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A re-run waiting to happen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledRetry {
+    /// The failed run to re-run
+    pub run_id: String,
+    pub flow_name: String,
+    /// Unix timestamp in milliseconds
+    pub due_at: u64,
+}
+
+/// File-backed queue of scheduled retries
+#[derive(Debug)]
+pub struct RetryQueue {
+    path: PathBuf,
+    /// Serializes read-modify-write cycles on the file
+    lock: Mutex<()>,
+}
+
+impl RetryQueue {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), lock: Mutex::new(()) }
+    }
+
+    /// Schedule a retry
+    pub fn push(&self, retry: ScheduledRetry) -> std::io::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut pending = self.load()?;
+        pending.push(retry);
+        self.store(&pending)
+    }
+
+    /// Hand each retry due at `now` to `hand_off`, removing the ones it
+    /// accepts. A retry only leaves the queue once it's recorded elsewhere,
+    /// so a crash in between can't lose it.
+    pub fn take_due(&self, now: u64, mut hand_off: impl FnMut(&ScheduledRetry) -> bool) -> std::io::Result<usize> {
+        let _guard = self.lock.lock().unwrap();
+        let (taken, pending): (Vec<_>, Vec<_>) = self.load()?
+            .into_iter()
+            .partition(|r| r.due_at <= now && hand_off(r));
+        if !taken.is_empty() {
+            self.store(&pending)?;
+        }
+        Ok(taken.len())
+    }
+
+    /// Number of retries waiting
+    pub fn len(&self) -> std::io::Result<usize> {
+        let _guard = self.lock.lock().unwrap();
+        Ok(self.load()?.len())
+    }
+
+    /// Whether no retry is waiting
+    pub fn is_empty(&self) -> std::io::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Every retry waiting
+    pub fn list(&self) -> std::io::Result<Vec<ScheduledRetry>> {
+        let _guard = self.lock.lock().unwrap();
+        self.load()
+    }
+
+    /// Drop the retries of a deleted flow, returning how many were dropped
+    pub fn remove_flow(&self, flow_name: &str) -> std::io::Result<usize> {
+        let _guard = self.lock.lock().unwrap();
+        let (dropped, pending): (Vec<_>, Vec<_>) = self.load()?.into_iter().partition(|r| r.flow_name == flow_name);
+        if !dropped.is_empty() {
+            self.store(&pending)?;
+        }
+        Ok(dropped.len())
+    }
+
+    /// The queued retries; a missing file is an empty queue, but a corrupt
+    /// one is an error rather than silently dropping every retry
+    fn load(&self) -> std::io::Result<Vec<ScheduledRetry>> {
+        let json = match std::fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&json).map_err(|e| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", self.path.display(), e))
+        })
+    }
+
+    /// Replace the file via a rename, so a crash mid-write leaves the old queue
+    fn store(&self, pending: &[ScheduledRetry]) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(pending)?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_leave_the_queue_only_when_handed_off() {
+        let dir = std::env::temp_dir().join(format!("autograph-retry-queue-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let queue = RetryQueue::new(dir.join("retry_queue.json"));
+        for (run_id, due_at) in [("a", 10), ("b", 10), ("c", 99)] {
+            queue.push(ScheduledRetry { run_id: run_id.to_string(), flow_name: "f".to_string(), due_at }).unwrap();
+        }
+
+        assert_eq!(queue.take_due(50, |retry| retry.run_id == "a").unwrap(), 1);
+        let left: Vec<String> = queue.list().unwrap().into_iter().map(|r| r.run_id).collect();
+        assert_eq!(left, ["b", "c"]);
+
+        std::fs::write(dir.join("retry_queue.json"), "not json").unwrap();
+        assert!(queue.len().is_err());
+        assert!(queue.push(ScheduledRetry { run_id: "d".to_string(), flow_name: "f".to_string(), due_at: 0 }).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
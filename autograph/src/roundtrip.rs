@@ -0,0 +1,198 @@
+//! Round-trip Tests
+//!
+//! Runs the fixture flows in `tests/fixtures/roundtrip` through the whole
+//! pipeline: Flow → HLX → parse → execute, and HLX → decompile → Flow.
+//! Every built-in node type must appear in a fixture, so a registry or
+//! codegen change that breaks deployed flows fails `cargo test`.
+//!
+//! Each fixture file holds a list of cases:
+//!
+//! ```json
+//! {"name": "...", "flow": {...}, "input": ..., "expected": ..., "execute": true}
+//! ```
+//!
+//! Every executed case needs `expected`, and seeded ones must also give it
+//! again on a second run. Cases that need the network, a database server or
+//! a GPU set `"execute": false` and are only compiled.
+//! Cases of API nodes can set `"respond"` instead: a local HTTP server
+//! answers every request with it as JSON, and `base_url` configs of
+//! `mock://` are pointed at that server.
+
+use crate::flow::{CompileOptions, Flow};
+use crate::testing::same;
+use hlx_compiler::{parser::Parser as ParseTrait, HlxaParser};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+struct Case {
+    name: String,
+    flow: Flow,
+    #[serde(default)]
+    input: JsonValue,
+    /// Present as `null` when the flow should output null
+    #[serde(default, deserialize_with = "present")]
+    expected: Option<JsonValue>,
+    #[serde(default = "yes")]
+    execute: bool,
+    /// Body the mock API server answers with
+    #[serde(default)]
+    respond: Option<JsonValue>,
+}
+
+fn yes() -> bool {
+    true
+}
+
+/// Keep an explicit `null` apart from a missing field
+fn present<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<JsonValue>, D::Error> {
+    JsonValue::deserialize(deserializer).map(Some)
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/roundtrip")
+}
+
+/// Every case, labelled `file/name`
+fn load_cases() -> Vec<(String, Case)> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(fixtures_dir())
+        .expect("fixture directory")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+
+    let mut cases = Vec::new();
+    for path in files {
+        let file = path.file_stem().unwrap().to_string_lossy().to_string();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let parsed: Vec<Case> = serde_json::from_str(&text)
+            .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        cases.extend(parsed.into_iter().map(|case| (format!("{}/{}", file, case.name), case)));
+    }
+    cases
+}
+
+/// Run a case in its own scratch directory so file nodes don't touch the repo
+fn run(label: &str, case: &Case) -> Result<JsonValue, String> {
+    let work_dir = std::env::temp_dir().join(format!(
+        "autograph-roundtrip-{}-{}",
+        std::process::id(),
+        label.replace('/', "-")
+    ));
+    std::fs::create_dir_all(&work_dir).map_err(|e| e.to_string())?;
+
+    let mut flow = case.flow.clone();
+    if let Some(body) = &case.respond {
+        let url = serve(body.clone());
+        for node in &mut flow.nodes {
+            if node.config["base_url"] == "mock://" {
+                node.config["base_url"] = JsonValue::String(url.clone());
+            }
+        }
+    }
+
+    let options = CompileOptions { work_dir: Some(work_dir.clone()), ..Default::default() };
+    let source = flow.compile_with(&options);
+    let vars = crate::state_store::vars(&work_dir.join(".state.sqlite"));
+    let result = crate::compile_and_run(&source, case.input.clone(), &vars)
+        .map_err(|e| e.to_string())
+        .and_then(|value| value.to_json().map_err(|e| format!("Serialization failed: {}", e)));
+
+    std::fs::remove_dir_all(&work_dir).ok();
+    result
+}
+
+/// Start a server answering every request with `body`, returning its URL
+fn serve(body: JsonValue) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("mock server");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let body = body.to_string();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            // Read the request through its body before answering
+            let mut reader = BufReader::new(&mut stream);
+            let mut length = 0;
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 0) && line != "\r\n" {
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                line.clear();
+            }
+            let mut request = vec![0; length];
+            reader.read_exact(&mut request).ok();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).ok();
+        }
+    });
+    url
+}
+
+#[test]
+fn every_builtin_node_type_has_a_fixture() {
+    let covered: HashSet<String> = load_cases()
+        .iter()
+        .flat_map(|(_, case)| case.flow.nodes.iter().map(|n| n.type_name.clone()))
+        .collect();
+
+    let missing: Vec<&str> = crate::nodes::builtin_nodes()
+        .into_iter()
+        .map(|def| def.name.as_ref())
+        .filter(|name| !covered.contains(*name))
+        .collect();
+    assert!(missing.is_empty(), "node types without a round-trip fixture: {:?}", missing);
+}
+
+#[test]
+fn fixtures_compile_to_valid_hlx() {
+    for (label, case) in load_cases() {
+        let source = case.flow.compile_to_hlx();
+        if let Err(e) = HlxaParser::new().parse(&source) {
+            panic!("{} does not parse: {:?}\n{}", label, e, source);
+        }
+    }
+}
+
+#[test]
+fn fixtures_decompile_to_the_same_flow() {
+    for (label, case) in load_cases() {
+        let decompiled = crate::decompile::decompile(&case.flow.compile_to_hlx())
+            .unwrap_or_else(|e| panic!("{}: {}", label, e));
+
+        assert!(decompiled.warnings.is_empty(), "{}: {:?}", label, decompiled.warnings);
+        assert_eq!(
+            serde_json::to_value(&decompiled.flow).unwrap(),
+            serde_json::to_value(&case.flow).unwrap(),
+            "{} changed after decompiling",
+            label
+        );
+    }
+}
+
+#[test]
+fn fixtures_execute_to_the_expected_output() {
+    for (label, case) in load_cases().iter().filter(|(_, case)| case.execute) {
+        let output = run(label, case).unwrap_or_else(|e| panic!("{} failed: {}", label, e));
+
+        let expected = case.expected.as_ref().unwrap_or_else(|| panic!("{} runs but has no expected output", label));
+        assert!(same(&output, expected), "{}: expected {}, got {}", label, expected, output);
+
+        let seeded = case.flow.seed.is_some() || case.flow.nodes.iter().any(|n| n.config.get("seed").is_some());
+        if seeded {
+            let again = run(label, case).unwrap_or_else(|e| panic!("{} failed on rerun: {}", label, e));
+            assert!(same(&output, &again), "{} is seeded but gave {} then {}", label, output, again);
+        }
+    }
+}
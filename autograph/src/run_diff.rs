@@ -0,0 +1,189 @@
+//! Run Comparison
+//!
+//! Lines up two recorded runs of a flow node by node, to answer "what
+//! changed between last night's run and today's?". A node differs when its
+//! output changed or its duration moved by more than the thresholds, so
+//! ordinary timing noise isn't flagged.
+
+use crate::runs::RunRecord;
+use crate::testing::same;
+use serde_json::Value as JsonValue;
+
+/// How far a node's duration may move before it counts as changed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    /// Relative change, in percent of the faster run
+    pub duration_pct: f64,
+    /// Absolute change in milliseconds, so fast nodes' jitter is ignored
+    pub duration_ms: u64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self { duration_pct: 25.0, duration_ms: 10 }
+    }
+}
+
+/// One node across the two runs
+#[derive(Debug, Clone)]
+pub struct NodeComparison {
+    pub id: String,
+    pub before: Option<JsonValue>,
+    pub after: Option<JsonValue>,
+    pub before_ms: Option<u64>,
+    pub after_ms: Option<u64>,
+    /// Where the outputs differ, as JSON pointers (`""` for the whole output)
+    pub changed_paths: Vec<String>,
+    pub duration_changed: bool,
+}
+
+impl NodeComparison {
+    pub fn output_changed(&self) -> bool {
+        !self.changed_paths.is_empty()
+    }
+
+    pub fn differs(&self) -> bool {
+        self.output_changed() || self.duration_changed
+    }
+}
+
+/// Two runs compared node by node
+#[derive(Debug, Clone)]
+pub struct RunComparison {
+    /// Nodes of either run, in the order of the later run's flow
+    pub nodes: Vec<NodeComparison>,
+    pub input_changed: bool,
+    pub result_changed: bool,
+}
+
+impl RunComparison {
+    /// Compare `before` with `after`
+    pub fn between(before: &RunRecord, after: &RunRecord, thresholds: Thresholds) -> Self {
+        let mut ids: Vec<String> = Vec::new();
+        for run in [after, before] {
+            let flow_order = run.flow.iter().flat_map(|f| f.nodes.iter().map(|n| &n.id));
+            for id in flow_order.chain(run.node_outputs.keys()).chain(run.node_durations.keys()) {
+                if !ids.contains(id) {
+                    ids.push(id.clone());
+                }
+            }
+        }
+
+        let nodes = ids.into_iter()
+            .map(|id| {
+                let before_output = before.node_outputs.get(&id).cloned();
+                let after_output = after.node_outputs.get(&id).cloned();
+                let mut changed_paths = Vec::new();
+                match (&before_output, &after_output) {
+                    (Some(a), Some(b)) => differing_paths(a, b, "", &mut changed_paths),
+                    (None, None) => {}
+                    _ => changed_paths.push(String::new()),
+                }
+                let before_ms = before.node_durations.get(&id).copied();
+                let after_ms = after.node_durations.get(&id).copied();
+                NodeComparison {
+                    duration_changed: duration_changed(before_ms, after_ms, thresholds),
+                    id,
+                    before: before_output,
+                    after: after_output,
+                    before_ms,
+                    after_ms,
+                    changed_paths,
+                }
+            })
+            .collect();
+
+        let result_changed = match (&before.result, &after.result) {
+            (Some(a), Some(b)) => !same(a, b),
+            (a, b) => a.is_some() != b.is_some() || before.error != after.error,
+        };
+        Self { nodes, input_changed: !same(&before.input, &after.input), result_changed }
+    }
+
+    /// Nodes that differ beyond the thresholds
+    pub fn changed(&self) -> impl Iterator<Item = &NodeComparison> {
+        self.nodes.iter().filter(|n| n.differs())
+    }
+
+    /// One line, e.g. `2 of 7 node(s) differ; input changed`
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} of {} node(s) differ", self.changed().count(), self.nodes.len())];
+        if self.input_changed {
+            parts.push("input changed".to_string());
+        }
+        if self.result_changed {
+            parts.push("result changed".to_string());
+        }
+        parts.join("; ")
+    }
+}
+
+fn duration_changed(before: Option<u64>, after: Option<u64>, thresholds: Thresholds) -> bool {
+    let (Some(a), Some(b)) = (before, after) else {
+        return false;
+    };
+    let delta = a.abs_diff(b);
+    let pct = delta as f64 / a.min(b).max(1) as f64 * 100.0;
+    delta > thresholds.duration_ms && pct > thresholds.duration_pct
+}
+
+/// Collect the JSON pointers at which `a` and `b` differ, descending into
+/// objects and equal-length arrays
+fn differing_paths(a: &JsonValue, b: &JsonValue, path: &str, out: &mut Vec<String>) {
+    match (a, b) {
+        (JsonValue::Object(x), JsonValue::Object(y)) => {
+            let mut keys: Vec<&String> = x.keys().chain(y.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{}/{}", path, key);
+                match (x.get(key), y.get(key)) {
+                    (Some(v), Some(w)) => differing_paths(v, w, &child, out),
+                    _ => out.push(child),
+                }
+            }
+        }
+        (JsonValue::Array(x), JsonValue::Array(y)) if x.len() == y.len() => {
+            for (i, (v, w)) in x.iter().zip(y).enumerate() {
+                differing_paths(v, w, &format!("{}/{}", path, i), out);
+            }
+        }
+        _ if same(a, b) => {}
+        _ => out.push(path.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flags_changed_outputs_and_durations_beyond_thresholds() {
+        let run = |outputs: JsonValue, durations: JsonValue| {
+            let mut record = RunRecord::new("nightly", 0, json!({}));
+            record.node_outputs = serde_json::from_value(outputs).unwrap();
+            record.node_durations = serde_json::from_value(durations).unwrap();
+            record
+        };
+        let last_night = run(
+            json!({"load": {"rows": 10, "tags": ["a"]}, "shape": 1, "slow": 0}),
+            json!({"load": 100, "shape": 2, "slow": 100}),
+        );
+        let today = run(
+            json!({"load": {"rows": 12, "tags": ["a"]}, "shape": 1.0, "slow": 0, "new": true}),
+            json!({"load": 110, "shape": 9, "slow": 300}),
+        );
+
+        let diff = RunComparison::between(&last_night, &today, Thresholds::default());
+        let changed: Vec<(&str, &[String], bool)> = diff.changed()
+            .map(|n| (n.id.as_str(), n.changed_paths.as_slice(), n.duration_changed))
+            .collect();
+        assert_eq!(changed, [
+            ("load", &["/rows".to_string()][..], false),
+            ("new", &[String::new()][..], false),
+            ("slow", &[][..], true),
+        ]);
+        assert_eq!(diff.summary(), "3 of 4 node(s) differ");
+    }
+}
@@ -0,0 +1,70 @@
+//! Per-Run Environments
+//!
+//! Each run gets its own working directory under `runs/<id>/work`, seeded
+//! with a snapshot of the project: the files the flow reads by relative
+//! path and the project's `.env`. Relative paths in node configs are
+//! resolved inside that directory, so concurrent runs of file-writing
+//! flows never touch each other's files.
+
+use crate::flow::{CompileOptions, Flow};
+use std::path::{Component, Path, PathBuf};
+
+/// Isolated working directory for a single run
+#[derive(Debug, Clone)]
+pub struct RunEnvironment {
+    pub work_dir: PathBuf,
+}
+
+impl RunEnvironment {
+    /// Create the run's working directory and snapshot the project into it
+    pub fn create(runs_dir: &Path, run_id: &str, flow: &Flow, project_dir: &Path) -> std::io::Result<Self> {
+        let work_dir = runs_dir.join(run_id).join("work");
+        std::fs::create_dir_all(&work_dir)?;
+
+        // Snapshot files the flow references, as they are at run start
+        for path in flow.relative_paths() {
+            // Never copy from outside the project
+            if Path::new(&path).components().any(|c| matches!(c, Component::ParentDir)) {
+                continue;
+            }
+
+            let source = project_dir.join(&path);
+            let dest = work_dir.join(&path);
+            if source.is_dir() {
+                copy_dir(&source, &dest)?;
+            } else if source.is_file() {
+                if let Some(parent) = dest.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::copy(&source, &dest)?;
+            }
+        }
+
+        // Snapshot the project environment
+        let env_file = project_dir.join(".env");
+        if env_file.is_file() {
+            std::fs::copy(&env_file, work_dir.join(".env"))?;
+        }
+
+        Ok(Self { work_dir })
+    }
+
+    /// Add this environment to a set of compile options
+    pub fn apply(&self, options: &mut CompileOptions) {
+        options.work_dir = Some(self.work_dir.clone());
+    }
+}
+
+fn copy_dir(source: &Path, dest: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
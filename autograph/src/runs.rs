@@ -0,0 +1,400 @@
+//! Run History
+//!
+//! Every execution is persisted as a JSON record under `runs/` so that
+//! past runs can be audited from the UI or the REST API.
+
+use crate::flow::Flow;
+use crate::gpu_stats::GpuMemoryStats;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Outcome of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// A trigger's message, saved until the run gets a slot
+    Queued,
+    /// Still executing, or the process died before it finished
+    Running,
+    Success,
+    Error,
+    /// The process stopped before the run finished
+    Interrupted,
+}
+
+/// Severity of a run log event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// One structured entry of a run's log. A deployed flow runs as one
+/// compiled program, so the server logs node outputs when the run ends;
+/// their `at` is then the end of the run, not when the node ran.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEvent {
+    /// Unix timestamp in milliseconds
+    pub at: u64,
+    pub level: LogLevel,
+    /// Node the event is about, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    pub message: String,
+}
+
+/// A persisted execution of a flow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: String,
+    pub flow_name: String,
+    /// Unix timestamp in milliseconds
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub status: RunStatus,
+    pub input: JsonValue,
+    pub result: Option<JsonValue>,
+    pub error: Option<String>,
+    /// Output of each node, when captured
+    #[serde(default)]
+    pub node_outputs: BTreeMap<String, JsonValue>,
+    /// Time each node took in milliseconds, when measured; the editor
+    /// times nodes, the server only whole runs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub node_durations: BTreeMap<String, u64>,
+    /// The flow's nodes and edges as it ran, for reports, without node
+    /// configs, mocks or pins, which may hold secrets; absent when only
+    /// compiled HLX was deployed. Set with [`RunRecord::set_flow`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow: Option<Flow>,
+    /// SHA-256 of the whole flow as it ran, to tell which saved flow or
+    /// version it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow_hash: Option<String>,
+    /// Plain log lines, kept by the editor and by runs recorded before
+    /// structured logging; read both kinds of log with [`RunRecord::log_lines`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<String>,
+    /// The log with timestamps, levels and nodes, added with [`RunRecord::log`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<LogEvent>,
+    /// Isolated working directory the run executed in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_dir: Option<String>,
+    /// ID of the run this one re-executes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_of: Option<String>,
+    /// ID of the first run of the retry chain this run belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    /// Position in the retry chain, starting at 1
+    #[serde(default = "first_attempt", skip_serializing_if = "is_first_attempt")]
+    pub attempt: u32,
+    /// When the scheduled re-run of this failed run is due (Unix ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_at: Option<u64>,
+    /// Whether the run injected its flow's chaos faults
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub chaos: bool,
+    /// Key/value labels attached when the run was triggered, e.g. a
+    /// customer, dataset or ticket number
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Device memory used, when the run touched the Vulkan backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_memory: Option<GpuMemoryStats>,
+}
+
+impl RunRecord {
+    /// Create a record for a run that started at `started_at`
+    pub fn new(flow_name: &str, started_at: u64, input: JsonValue) -> Self {
+        let safe_name: String = flow_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+            .collect();
+
+        Self {
+            id: format!("{}-{}", started_at, safe_name),
+            flow_name: flow_name.to_string(),
+            started_at,
+            duration_ms: 0,
+            status: RunStatus::Running,
+            input,
+            result: None,
+            error: None,
+            node_outputs: BTreeMap::new(),
+            node_durations: BTreeMap::new(),
+            flow: None,
+            flow_hash: None,
+            logs: Vec::new(),
+            events: Vec::new(),
+            work_dir: None,
+            retry_of: None,
+            chain: None,
+            attempt: 1,
+            retry_at: None,
+            chaos: false,
+            labels: BTreeMap::new(),
+            gpu_memory: None,
+        }
+    }
+
+    /// Record the flow the run executed: its hash, and its shape with the
+    /// parts that may hold secrets left out
+    pub fn set_flow(&mut self, flow: &Flow) {
+        self.flow_hash = serde_json::to_vec(flow).ok().map(|json| crate::signing::sha256_hex(&json));
+        let mut shape = flow.clone();
+        for node in &mut shape.nodes {
+            node.config = serde_json::json!({});
+        }
+        shape.mocks.clear();
+        shape.mock_files.clear();
+        shape.pinned.clear();
+        self.flow = Some(shape);
+    }
+
+    /// Make this run the next attempt after `previous` in its retry chain
+    pub fn continue_chain(&mut self, previous: &RunRecord) {
+        self.retry_of = Some(previous.id.clone());
+        self.chain = Some(previous.chain_id().to_string());
+        self.attempt = previous.attempt + 1;
+        self.labels = previous.labels.clone();
+    }
+
+    /// Whether the run carries every label in `filter`
+    pub fn has_labels(&self, filter: &BTreeMap<String, String>) -> bool {
+        filter.iter().all(|(key, value)| self.labels.get(key) == Some(value))
+    }
+
+    /// ID of the retry chain's first run; a run that was never retried is its own chain
+    pub fn chain_id(&self) -> &str {
+        self.chain.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Add a log event about the run or one of its nodes
+    pub fn log(&mut self, level: LogLevel, node: Option<&str>, message: impl Into<String>) {
+        let message = message.into();
+        self.events.push(LogEvent { at: now_ms(), level, node: node.map(str::to_string), message });
+    }
+
+    /// The run's log as plain lines, events prefixed with their node
+    pub fn log_lines(&self) -> Vec<String> {
+        if self.events.is_empty() {
+            return self.logs.clone();
+        }
+        self.events.iter()
+            .map(|event| match &event.node {
+                Some(node) => format!("{}: {}", node, event.message),
+                None => event.message.clone(),
+            })
+            .collect()
+    }
+
+    /// The run's structured log. Runs recorded before structured logging
+    /// get their plain lines back as info events at the run's start.
+    pub fn structured_log(&self) -> Vec<LogEvent> {
+        if !self.events.is_empty() || self.logs.is_empty() {
+            return self.events.clone();
+        }
+        self.logs.iter()
+            .map(|line| LogEvent { at: self.started_at, level: LogLevel::Info, node: None, message: line.clone() })
+            .collect()
+    }
+
+    /// Record the outcome of the run
+    pub fn finish(&mut self, duration_ms: u64, outcome: Result<JsonValue, String>) {
+        self.duration_ms = duration_ms;
+        match outcome {
+            Ok(result) => {
+                self.status = RunStatus::Success;
+                self.result = Some(result);
+            }
+            Err(e) => {
+                self.status = RunStatus::Error;
+                self.error = Some(e);
+            }
+        }
+    }
+
+    /// Short form used when listing runs
+    pub fn summary(&self) -> JsonValue {
+        serde_json::json!({
+            "id": self.id,
+            "flow_name": self.flow_name,
+            "started_at": self.started_at,
+            "duration_ms": self.duration_ms,
+            "status": self.status,
+            "error": self.error,
+            "attempt": self.attempt,
+            "chain": self.chain_id(),
+            "retry_at": self.retry_at,
+            "chaos": self.chaos,
+            "labels": self.labels,
+        })
+    }
+}
+
+/// Parse `key=value` labels separated by commas, as given to
+/// `?labels=` and `--label`
+pub fn parse_labels(spec: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut labels = BTreeMap::new();
+    for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("label {:?} is not key=value", pair))?;
+        let key = key.trim();
+        let valid = !key.is_empty()
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'));
+        if !valid {
+            return Err(format!("invalid label key {:?} (use letters, digits, _ - . /)", key));
+        }
+        labels.insert(key.to_string(), value.trim().to_string());
+    }
+    Ok(labels)
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+fn is_first_attempt(attempt: &u32) -> bool {
+    *attempt == 1
+}
+
+/// On-disk store of run records, one JSON file per run
+#[derive(Debug, Clone)]
+pub struct RunStore {
+    dir: PathBuf,
+}
+
+impl RunStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory the records are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Persist a run, assigning a unique ID if another run already has it
+    pub fn save(&self, record: &mut RunRecord) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        // Claim the file before writing it, so two runs started in the same
+        // millisecond can't both take an ID
+        let base_id = record.id.clone();
+        let mut suffix = 1;
+        let mut file = loop {
+            let path = self.path_for(&record.id)
+                .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid run ID"))?;
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(file) => break file,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    record.id = format!("{}-{}", base_id, suffix);
+                    suffix += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+        let json = serde_json::to_string_pretty(record)?;
+        file.write_all(json.as_bytes())
+    }
+
+    /// Overwrite an already saved run
+    pub fn update(&self, record: &RunRecord) -> std::io::Result<()> {
+        let path = self.path_for(&record.id)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid run ID"))?;
+        let json = serde_json::to_string_pretty(record)?;
+        std::fs::write(path, json)
+    }
+
+    /// Load a single run by ID
+    pub fn load(&self, id: &str) -> Option<RunRecord> {
+        let json = std::fs::read_to_string(self.path_for(id)?).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// All runs of a flow, newest first
+    pub fn list_for_flow(&self, flow_name: &str) -> Vec<RunRecord> {
+        let mut runs: Vec<RunRecord> = self.list_all()
+            .into_iter()
+            .filter(|r| r.flow_name == flow_name)
+            .collect();
+        runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        runs
+    }
+
+    /// All stored runs, in no particular order
+    pub fn list_all(&self) -> Vec<RunRecord> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect()
+    }
+
+    /// Every attempt of a retry chain, first attempt first
+    pub fn list_chain(&self, chain_id: &str) -> Vec<RunRecord> {
+        let mut runs: Vec<RunRecord> = self.list_all()
+            .into_iter()
+            .filter(|r| r.chain_id() == chain_id)
+            .collect();
+        runs.sort_by_key(|r| r.attempt);
+        runs
+    }
+
+    /// File path for a run ID, rejecting IDs that could escape the store
+    fn path_for(&self, id: &str) -> Option<PathBuf> {
+        let valid = !id.is_empty()
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        valid.then(|| self.dir.join(format!("{}.json", id)))
+    }
+}
+
+/// Current time as a Unix timestamp in milliseconds
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Format a Unix timestamp in milliseconds as `YYYY-MM-DD HH:MM:SS` (UTC)
+pub fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil date from days since epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
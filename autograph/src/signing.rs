@@ -0,0 +1,213 @@
+//! Bundle Signing
+//!
+//! Publishers sign exported bundles with an ed25519 key; importers and
+//! deploy servers check the signature against a list of trusted publisher
+//! keys. The signature covers the manifest, which carries a SHA-256
+//! checksum of every flow, so no flow can be changed without invalidating it.
+//!
+//! Key file (keep private):
+//!
+//! ```json
+//! { "publisher": "acme", "secret_key": "<64 hex chars>" }
+//! ```
+//!
+//! Trust list:
+//!
+//! ```json
+//! { "publishers": [ { "name": "acme", "public_key": "<64 hex chars>" } ] }
+//! ```
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Trust list the UI checks imported bundles against, when present
+pub const TRUSTED_PUBLISHERS_FILE: &str = "trusted_publishers.json";
+
+/// A publisher's signing key
+pub struct PublisherKey {
+    pub publisher: String,
+    key: SigningKey,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    publisher: String,
+    secret_key: String,
+}
+
+impl PublisherKey {
+    /// Create a new random key
+    pub fn generate(publisher: &str) -> Result<Self, String> {
+        let mut secret = [0u8; 32];
+        getrandom::getrandom(&mut secret).map_err(|e| format!("No randomness available: {}", e))?;
+        Ok(Self { publisher: publisher.to_string(), key: SigningKey::from_bytes(&secret) })
+    }
+
+    /// Read a key file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read key {}: {}", path.display(), e))?;
+        let file: KeyFile = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid key file {}: {}", path.display(), e))?;
+        let secret = from_hex::<32>(&file.secret_key)
+            .ok_or_else(|| format!("Invalid secret key in {}", path.display()))?;
+        Ok(Self { publisher: file.publisher, key: SigningKey::from_bytes(&secret) })
+    }
+
+    /// Write a key file, readable by its owner only, refusing to replace an
+    /// existing one
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        use std::io::Write;
+
+        let file = KeyFile { publisher: self.publisher.clone(), secret_key: to_hex(self.key.as_bytes()) };
+        let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut out = options.open(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => format!("{} already exists", path.display()),
+            _ => format!("Failed to write {}: {}", path.display(), e),
+        })?;
+        out.write_all(json.as_bytes()).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Public key to add to trust lists
+    pub fn public_key(&self) -> String {
+        to_hex(self.key.verifying_key().as_bytes())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> BundleSignature {
+        BundleSignature {
+            publisher: self.publisher.clone(),
+            public_key: self.public_key(),
+            signature: to_hex(&self.key.sign(message).to_bytes()),
+        }
+    }
+}
+
+/// Signature attached to a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSignature {
+    pub publisher: String,
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl BundleSignature {
+    /// Check that the signature matches `message` under its own public key.
+    /// Whether that key is trusted is up to [`TrustedPublishers::check`].
+    pub fn verify(&self, message: &[u8]) -> Result<(), String> {
+        let key = from_hex::<32>(&self.public_key)
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+            .ok_or("Signature has an invalid public key")?;
+        let signature = from_hex::<64>(&self.signature)
+            .map(|bytes| Signature::from_bytes(&bytes))
+            .ok_or("Signature is malformed")?;
+        key.verify_strict(message, &signature)
+            .map_err(|_| format!("Signature by '{}' does not match the bundle contents", self.publisher))
+    }
+}
+
+/// Publisher keys whose bundles are accepted
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TrustedPublishers {
+    pub publishers: Vec<TrustedPublisher>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrustedPublisher {
+    pub name: String,
+    pub public_key: String,
+}
+
+impl TrustedPublishers {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read trust list {}: {}", path.display(), e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Invalid trust list {}: {}", path.display(), e))
+    }
+
+    /// The trust list in the working directory, if there is one
+    pub fn load_default() -> Result<Option<Self>, String> {
+        let path = Path::new(TRUSTED_PUBLISHERS_FILE);
+        if path.exists() {
+            Self::load(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Name of the trusted publisher behind an already-verified signature
+    pub fn check(&self, signature: Option<&BundleSignature>) -> Result<String, String> {
+        let signature = signature.ok_or("Bundle is not signed; only signed bundles are accepted")?;
+        self.publishers
+            .iter()
+            .find(|p| p.public_key.eq_ignore_ascii_case(&signature.public_key))
+            .map(|p| p.name.clone())
+            .ok_or_else(|| format!("Bundle is signed by '{}', whose key is not trusted", signature.publisher))
+    }
+}
+
+/// SHA-256 of `bytes` as lowercase hex
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    let hex = hex.trim();
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        return None;
+    }
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_round_trip_and_catch_tampering() {
+        let dir = std::env::temp_dir().join(format!("autograph-signing-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("publisher.key");
+        std::fs::remove_file(&path).ok();
+
+        let key = PublisherKey::generate("acme").unwrap();
+        key.save(&path).unwrap();
+        assert!(key.save(&path).unwrap_err().contains("already exists"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let key = PublisherKey::load(&path).unwrap();
+
+        let signature = key.sign(b"manifest");
+        assert_eq!(signature.verify(b"manifest"), Ok(()));
+        assert!(signature.verify(b"manifest!").is_err());
+
+        let mut forged = signature.clone();
+        forged.public_key = PublisherKey::generate("mallory").unwrap().public_key();
+        assert!(forged.verify(b"manifest").is_err());
+
+        let trusted = TrustedPublishers {
+            publishers: vec![TrustedPublisher { name: "Acme".to_string(), public_key: key.public_key().to_uppercase() }],
+        };
+        assert_eq!(trusted.check(Some(&signature)), Ok("Acme".to_string()));
+        assert!(trusted.check(Some(&forged)).is_err());
+        assert!(trusted.check(None).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,117 @@
+//! Persistent Flow State
+//!
+//! `state_get`, `state_set` and `state_increment` nodes keep values between
+//! runs (cursors, seen IDs, counters) in a SQLite file per flow under
+//! `.autograph/state`. Compiled flows read the store's connection from the
+//! `AUTOGRAPH_STATE_STORE` run variable, so deployed source never embeds a
+//! path; each run resolves it for its flow. Runs reach it through the
+//! runtime's SQLite driver, so concurrent runs of a flow share one store and
+//! increments are atomic. Mock runs get a throwaway store in their working
+//! directory instead.
+
+use crate::flow::CompileOptions;
+use crate::RunVars;
+use std::path::{Path, PathBuf};
+
+/// Directory holding the stores, relative to the project
+pub const STATE_DIR: &str = ".autograph/state";
+
+/// Nodes that read or write the store
+pub const STATE_NODES: &[&str] = &["state_get", "state_set", "state_increment"];
+
+/// Run variable holding the store's SQLite connection string
+pub const STORE_VAR: &str = "AUTOGRAPH_STATE_STORE";
+
+/// Store file of runs confined to a working directory
+const WORK_DIR_STORE: &str = ".state.sqlite";
+
+/// Store file of a flow, e.g. `.autograph/state/nightly_sync.sqlite`, or
+/// `None` for a flow without a usable name
+pub fn store_path(flow_name: &str) -> Option<PathBuf> {
+    let name: String = flow_name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if name.is_empty() {
+        return None;
+    }
+    let dir = std::env::current_dir().unwrap_or_default().join(STATE_DIR);
+    Some(dir.join(format!("{}.sqlite", name)))
+}
+
+/// Connection string of a state node; compiled code reads it from the run
+pub fn connection() -> String {
+    format!("env:{}", STORE_VAR)
+}
+
+/// Whether compiled source has state nodes that need a store
+pub fn uses_store(source: &str) -> bool {
+    source.contains(&format!("env(\"{}\")", STORE_VAR))
+}
+
+/// Run variables pointing state nodes at the store file `path`
+pub fn vars(path: &Path) -> RunVars {
+    RunVars::from([(STORE_VAR.to_string(), format!("sqlite://{}", path.display()))])
+}
+
+/// Run variables for the store of a run of `source`, creating its
+/// directory: the flow's own store, or one in the working directory of a
+/// confined (mock) run. Empty if the source has no state nodes.
+pub fn prepare(source: &str, flow_name: &str, options: &CompileOptions) -> Result<RunVars, String> {
+    if !uses_store(source) {
+        return Ok(RunVars::new());
+    }
+    let path = match &options.work_dir {
+        Some(dir) if options.confine_paths => dir.join(WORK_DIR_STORE),
+        _ => store_path(flow_name)
+            .ok_or_else(|| "State nodes need a state store: save the flow under a name first".to_string())?,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    Ok(vars(&path))
+}
+
+/// Fail a run of `source` whose state nodes were given no store
+pub fn require(source: &str, vars: &RunVars) -> Result<(), String> {
+    if uses_store(source) && !vars.contains_key(STORE_VAR) {
+        return Err(format!("State nodes need a state store, but the run has no {}", STORE_VAR));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::{Flow, Node};
+    use serde_json::json;
+
+    #[test]
+    fn state_nodes_read_their_store_from_the_run() {
+        assert!(store_path("nightly sync").unwrap().ends_with(".autograph/state/nightly_sync.sqlite"));
+        assert_eq!(store_path("  "), None);
+
+        let flow = Flow {
+            nodes: vec![Node::new("cursor", "state_get", json!({"key": "last_id", "default": 0}))],
+            ..Default::default()
+        };
+        // No path is compiled in, so deployed source works wherever it runs
+        let source = flow.compile_to_hlx();
+        assert!(source.contains("db_query(env(\"AUTOGRAPH_STATE_STORE\")"));
+        assert!(!source.contains("sqlite://"));
+
+        // Without a store the run fails instead of sharing one with other flows
+        assert!(require(&source, &RunVars::new()).is_err());
+        assert!(prepare(&source, "", &CompileOptions::default()).is_err());
+
+        // Mock runs keep their state in their scratch directory
+        let dir = std::env::temp_dir().join(format!("autograph-state-{}", std::process::id()));
+        let options = CompileOptions { work_dir: Some(dir.clone()), confine_paths: true, ..Default::default() };
+        let vars = prepare(&source, "orders", &options).unwrap();
+        assert_eq!(vars[STORE_VAR], format!("sqlite://{}", dir.join(".state.sqlite").display()));
+        assert!(require(&source, &vars).is_ok());
+        std::fs::remove_dir_all(&dir).ok();
+
+        // Flows without state nodes need nothing
+        assert!(prepare("fn main(input) { return input; }", "", &CompileOptions::default()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+//! Streaming File Processing
+//!
+//! `file_stream` nodes work through files too large to hold as one string:
+//! they read a window of lines at a time, resuming at the byte offset the
+//! last window ended at (`read_lines_from`), parse it as text, CSV or
+//! JSONL, and run a subflow on each chunk. Only the last chunk's result is
+//! kept, with the chunk and line counts, so memory stays flat however big
+//! the file is.
+
+use serde_json::Value as JsonValue;
+
+/// Formats chunks are parsed as
+pub const FORMATS: &[&str] = &["lines", "csv", "jsonl"];
+
+/// Lines per chunk when the node doesn't say
+pub const DEFAULT_CHUNK_LINES: u64 = 10_000;
+
+/// Chunks and lines a `file_stream` node's output reports
+pub fn counts(output: &JsonValue) -> Option<(u64, u64)> {
+    Some((output["chunks"].as_u64()?, output["lines"].as_u64()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn counts_are_read_from_the_output() {
+        assert_eq!(counts(&json!({"chunks": 3, "lines": 25, "last": {"ok": true}})), Some((3, 25)));
+        assert_eq!(counts(&json!("not a stream")), None);
+    }
+}
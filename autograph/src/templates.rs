@@ -1,32 +1,235 @@
 //! Workflow Templates
 //!
-//! Pre-built workflow templates for common automation tasks
+//! Pre-built workflow templates for common automation tasks, plus user
+//! templates saved from the editor into `templates/`, one JSON file each.
+//! A template's parameters are asked for when it is used and written into
+//! the node configs they name.
 
 use crate::flow::{Flow, Node, Edge, Position};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
 
+/// Directory user templates are saved to
+pub const USER_TEMPLATES_DIR: &str = "templates";
+
+/// A template; built-in ones borrow their text, contributed and user ones own it
+#[derive(Clone)]
 pub struct WorkflowTemplate {
-    pub name: &'static str,
-    pub description: &'static str,
-    pub category: &'static str,
-    pub create: fn() -> Flow,
+    pub name: Cow<'static, str>,
+    pub description: Cow<'static, str>,
+    pub category: Cow<'static, str>,
+    pub params: Cow<'static, [TemplateParam]>,
+    pub flow: TemplateFlow,
+}
+
+/// A value asked for when a template is used, written into one node's config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateParam {
+    /// Shown in the prompt, e.g. `API URL`
+    pub label: Cow<'static, str>,
+    pub node: Cow<'static, str>,
+    /// Config field of the node, e.g. `url`
+    pub field: Cow<'static, str>,
+}
+
+impl TemplateParam {
+    pub const fn new(label: &'static str, node: &'static str, field: &'static str) -> Self {
+        Self { label: Cow::Borrowed(label), node: Cow::Borrowed(node), field: Cow::Borrowed(field) }
+    }
+
+    /// The parameter's value in `flow`, as typed in the prompt
+    pub fn value_in(&self, flow: &Flow) -> String {
+        match flow.nodes.iter().find(|n| n.id == self.node).and_then(|n| n.config.get(self.field.as_ref())) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Write a typed value into `flow`. Fields holding numbers, booleans or
+    /// JSON keep their type when the value parses as JSON.
+    pub fn apply(&self, flow: &mut Flow, value: &str) {
+        let Some(node) = flow.nodes.iter_mut().find(|n| n.id == self.node) else {
+            return;
+        };
+        let value = match node.config.get(self.field.as_ref()) {
+            Some(serde_json::Value::String(_)) | None => serde_json::Value::String(value.to_string()),
+            Some(_) => serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string())),
+        };
+        if !node.config.is_object() {
+            node.config = json!({});
+        }
+        node.config[self.field.as_ref()] = value;
+    }
+}
+
+/// How a template builds its flow
+#[derive(Clone)]
+pub enum TemplateFlow {
+    /// Built in code
+    Code(fn() -> Flow),
+    /// Saved from the editor
+    Saved(Flow),
+}
+
+impl WorkflowTemplate {
+    /// A fresh copy of the template's flow
+    pub fn create(&self) -> Flow {
+        match &self.flow {
+            TemplateFlow::Code(create) => create(),
+            TemplateFlow::Saved(flow) => flow.clone(),
+        }
+    }
+
+    /// The template's flow with `values`, in `params` order, written into
+    /// the node configs
+    pub fn instantiate(&self, values: &[String]) -> Flow {
+        let mut flow = self.create();
+        for (param, value) in self.params.iter().zip(values) {
+            param.apply(&mut flow, value);
+        }
+        flow.template = Some(self.name.to_string());
+        flow
+    }
+}
+
+/// A user template as stored in `templates/<name>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "UserTemplate::default_category")]
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<TemplateParam>,
+    pub flow: Flow,
+}
+
+impl UserTemplate {
+    fn default_category() -> String {
+        "My Templates".to_string()
+    }
+}
+
+/// User templates, loaded from `USER_TEMPLATES_DIR` on first use. Saving
+/// one replaces the entry with the same key, dropping the old template.
+static USER_TEMPLATES: RwLock<Option<Vec<Arc<WorkflowTemplate>>>> = RwLock::new(None);
+
+/// Built-in templates, those contributed through `crate::registry`, then
+/// the user's own
+pub fn all_templates() -> Vec<Arc<WorkflowTemplate>> {
+    static BUILTIN: OnceLock<Vec<Arc<WorkflowTemplate>>> = OnceLock::new();
+    let mut templates = BUILTIN
+        .get_or_init(|| {
+            [&HTTP_TO_JSON_TO_PRINT, &FILE_READ_TRANSFORM_WRITE, &JSON_API_PIPELINE, &DATA_PROCESSING, &MATH_CALCULATOR, &BATCHED_GPU_MATMUL]
+                .into_iter()
+                .map(|template| Arc::new(template.clone()))
+                .collect()
+        })
+        .clone();
+    templates.extend(crate::registry::templates());
+    templates.extend(user_templates());
+    templates
+}
+
+/// User templates, loading them on first call
+fn user_templates() -> Vec<Arc<WorkflowTemplate>> {
+    if let Some(templates) = USER_TEMPLATES.read().unwrap().as_ref() {
+        return templates.clone();
+    }
+    let loaded: Vec<Arc<WorkflowTemplate>> = load_user_templates(Path::new(USER_TEMPLATES_DIR))
+        .into_iter()
+        .map(|template| Arc::new(template.into()))
+        .collect();
+    *USER_TEMPLATES.write().unwrap() = Some(loaded.clone());
+    loaded
+}
+
+/// Every parseable template file in `dir`, by name. Of templates whose
+/// names share a key, only the first is kept.
+pub fn load_user_templates(dir: &Path) -> Vec<UserTemplate> {
+    let mut templates: Vec<UserTemplate> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut keys = std::collections::HashSet::new();
+    templates.retain(|t| keys.insert(template_key(&t.name)));
+    templates
+}
+
+/// Save a flow as a user template in `dir`, replacing one of the same name,
+/// and add it to the Templates menu. Its changelog and template origin are
+/// dropped.
+pub fn save_user_template(dir: &Path, mut template: UserTemplate) -> Result<(), String> {
+    template.name = template.name.trim().to_string();
+    if template.name.is_empty() {
+        return Err("Template name is empty".to_string());
+    }
+    // Also loads the existing user templates, so the cache below is filled
+    let key = template_key(&template.name);
+    if key.chars().all(|c| c == '_') {
+        return Err(format!("Template name '{}' needs a letter or digit", template.name));
+    }
+    if all_templates().iter().any(|t| template_key(&t.name) == key && matches!(t.flow, TemplateFlow::Code(_))) {
+        return Err(format!("'{}' is a built-in template", template.name));
+    }
+    if template.category.trim().is_empty() {
+        template.category = UserTemplate::default_category();
+    }
+    template.flow.changelog.clear();
+    template.flow.template = None;
+    template.params.retain(|p| template.flow.nodes.iter().any(|n| n.id == p.node));
+
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.json", key));
+    let json = serde_json::to_string_pretty(&template).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    let mut guard = USER_TEMPLATES.write().unwrap();
+    let templates = guard.get_or_insert_with(Vec::new);
+    templates.retain(|t| template_key(&t.name) != key);
+    templates.push(Arc::new(template.into()));
+    Ok(())
+}
+
+/// A user template's key, also its file name: the name lowercased, other
+/// characters than letters and digits as `_`. Names that only differ in
+/// case or punctuation share a key, so one replaces the other instead of
+/// both ending up in one file on case-insensitive filesystems.
+fn template_key(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
 }
 
-pub fn all_templates() -> Vec<&'static WorkflowTemplate> {
-    vec![
-        &HTTP_TO_JSON_TO_PRINT,
-        &FILE_READ_TRANSFORM_WRITE,
-        &JSON_API_PIPELINE,
-        &DATA_PROCESSING,
-        &MATH_CALCULATOR,
-    ]
+impl From<UserTemplate> for WorkflowTemplate {
+    fn from(template: UserTemplate) -> Self {
+        WorkflowTemplate {
+            name: Cow::Owned(template.name),
+            description: Cow::Owned(template.description),
+            category: Cow::Owned(template.category),
+            params: Cow::Owned(template.params),
+            flow: TemplateFlow::Saved(template.flow),
+        }
+    }
 }
 
 static HTTP_TO_JSON_TO_PRINT: WorkflowTemplate = WorkflowTemplate {
-    name: "HTTP → JSON → Print",
-    description: "Fetch JSON from API and print result",
-    category: "API",
-    create: || {
+    name: Cow::Borrowed("HTTP → JSON → Print"),
+    description: Cow::Borrowed("Fetch JSON from API and print result"),
+    category: Cow::Borrowed("API"),
+    params: Cow::Borrowed(&[TemplateParam::new("API URL", "http1", "url")]),
+    flow: TemplateFlow::Code(|| {
         Flow {
             nodes: vec![
                 Node {
@@ -34,21 +237,21 @@ static HTTP_TO_JSON_TO_PRINT: WorkflowTemplate = WorkflowTemplate {
                     type_name: "http_get".to_string(),
                     config: json!({"url": "https://api.github.com/users/octocat"}),
                     position: Some(Position { x: 100.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "json1".to_string(),
                     type_name: "json_parse".to_string(),
                     config: json!({}),
                     position: Some(Position { x: 300.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "print1".to_string(),
                     type_name: "print".to_string(),
                     config: json!({}),
                     position: Some(Position { x: 500.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
             ],
             edges: vec![
@@ -65,15 +268,20 @@ static HTTP_TO_JSON_TO_PRINT: WorkflowTemplate = WorkflowTemplate {
                     target_handle: None,
                 },
             ],
+            ..Default::default()
         }
-    },
+    }),
 };
 
 static FILE_READ_TRANSFORM_WRITE: WorkflowTemplate = WorkflowTemplate {
-    name: "File Processing",
-    description: "Read file, transform, write back",
-    category: "Files",
-    create: || {
+    name: Cow::Borrowed("File Processing"),
+    description: Cow::Borrowed("Read file, transform, write back"),
+    category: Cow::Borrowed("Files"),
+    params: Cow::Borrowed(&[
+        TemplateParam::new("Input file", "read1", "path"),
+        TemplateParam::new("Output file", "write1", "path"),
+    ]),
+    flow: TemplateFlow::Code(|| {
         Flow {
             nodes: vec![
                 Node {
@@ -81,21 +289,21 @@ static FILE_READ_TRANSFORM_WRITE: WorkflowTemplate = WorkflowTemplate {
                     type_name: "file_read".to_string(),
                     config: json!({"path": "input.txt"}),
                     position: Some(Position { x: 100.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "upper1".to_string(),
                     type_name: "string_upper".to_string(),
                     config: json!({}),
                     position: Some(Position { x: 300.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "write1".to_string(),
                     type_name: "file_write".to_string(),
                     config: json!({"path": "output.txt"}),
                     position: Some(Position { x: 500.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
             ],
             edges: vec![
@@ -112,15 +320,21 @@ static FILE_READ_TRANSFORM_WRITE: WorkflowTemplate = WorkflowTemplate {
                     target_handle: None,
                 },
             ],
+            ..Default::default()
         }
-    },
+    }),
 };
 
 static JSON_API_PIPELINE: WorkflowTemplate = WorkflowTemplate {
-    name: "JSON API Pipeline",
-    description: "Fetch, parse, extract, save to file",
-    category: "API",
-    create: || {
+    name: Cow::Borrowed("JSON API Pipeline"),
+    description: Cow::Borrowed("Fetch, parse, extract, save to file"),
+    category: Cow::Borrowed("API"),
+    params: Cow::Borrowed(&[
+        TemplateParam::new("API URL", "http1", "url"),
+        TemplateParam::new("Field to extract", "get1", "key"),
+        TemplateParam::new("Output file", "write1", "path"),
+    ]),
+    flow: TemplateFlow::Code(|| {
         Flow {
             nodes: vec![
                 Node {
@@ -128,28 +342,28 @@ static JSON_API_PIPELINE: WorkflowTemplate = WorkflowTemplate {
                     type_name: "http_get".to_string(),
                     config: json!({"url": "https://api.example.com/data"}),
                     position: Some(Position { x: 100.0, y: 150.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "json1".to_string(),
                     type_name: "json_parse".to_string(),
                     config: json!({}),
                     position: Some(Position { x: 300.0, y: 150.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "get1".to_string(),
                     type_name: "json_get".to_string(),
                     config: json!({"key": "results"}),
                     position: Some(Position { x: 500.0, y: 150.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "write1".to_string(),
                     type_name: "json_write".to_string(),
                     config: json!({"path": "results.json"}),
                     position: Some(Position { x: 700.0, y: 150.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
             ],
             edges: vec![
@@ -172,15 +386,20 @@ static JSON_API_PIPELINE: WorkflowTemplate = WorkflowTemplate {
                     target_handle: None,
                 },
             ],
+            ..Default::default()
         }
-    },
+    }),
 };
 
 static DATA_PROCESSING: WorkflowTemplate = WorkflowTemplate {
-    name: "Data Processing",
-    description: "Load JSON, transform, filter, save",
-    category: "Data",
-    create: || {
+    name: Cow::Borrowed("Data Processing"),
+    description: Cow::Borrowed("Load JSON, transform, filter, save"),
+    category: Cow::Borrowed("Data"),
+    params: Cow::Borrowed(&[
+        TemplateParam::new("Data file", "read1", "path"),
+        TemplateParam::new("Array field", "get1", "key"),
+    ]),
+    flow: TemplateFlow::Code(|| {
         Flow {
             nodes: vec![
                 Node {
@@ -188,28 +407,28 @@ static DATA_PROCESSING: WorkflowTemplate = WorkflowTemplate {
                     type_name: "json_read".to_string(),
                     config: json!({"path": "data.json"}),
                     position: Some(Position { x: 100.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "get1".to_string(),
                     type_name: "object_get".to_string(),
                     config: json!({"key": "items"}),
                     position: Some(Position { x: 300.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "len1".to_string(),
                     type_name: "array_length".to_string(),
                     config: json!({}),
                     position: Some(Position { x: 500.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "print1".to_string(),
                     type_name: "print".to_string(),
                     config: json!({}),
                     position: Some(Position { x: 700.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
             ],
             edges: vec![
@@ -232,15 +451,20 @@ static DATA_PROCESSING: WorkflowTemplate = WorkflowTemplate {
                     target_handle: None,
                 },
             ],
+            ..Default::default()
         }
-    },
+    }),
 };
 
 static MATH_CALCULATOR: WorkflowTemplate = WorkflowTemplate {
-    name: "Math Calculator",
-    description: "Chain math operations",
-    category: "Math",
-    create: || {
+    name: Cow::Borrowed("Math Calculator"),
+    description: Cow::Borrowed("Chain math operations"),
+    category: Cow::Borrowed("Math"),
+    params: Cow::Borrowed(&[
+        TemplateParam::new("Add", "add1", "value"),
+        TemplateParam::new("Multiply by", "mult1", "value"),
+    ]),
+    flow: TemplateFlow::Code(|| {
         Flow {
             nodes: vec![
                 Node {
@@ -248,28 +472,28 @@ static MATH_CALCULATOR: WorkflowTemplate = WorkflowTemplate {
                     type_name: "math_add".to_string(),
                     config: json!({"value": 10}),
                     position: Some(Position { x: 100.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "mult1".to_string(),
                     type_name: "math_multiply".to_string(),
                     config: json!({"value": 2}),
                     position: Some(Position { x: 300.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "sqrt1".to_string(),
                     type_name: "math_sqrt".to_string(),
                     config: json!({}),
                     position: Some(Position { x: 500.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
                 Node {
                     id: "print1".to_string(),
                     type_name: "print".to_string(),
                     config: json!({}),
                     position: Some(Position { x: 700.0, y: 200.0 }),
-                    breakpoint: false,
+                    ..Default::default()
                 },
             ],
             edges: vec![
@@ -292,6 +516,111 @@ static MATH_CALCULATOR: WorkflowTemplate = WorkflowTemplate {
                     target_handle: None,
                 },
             ],
+            ..Default::default()
+        }
+    }),
+};
+
+static BATCHED_GPU_MATMUL: WorkflowTemplate = WorkflowTemplate {
+    name: Cow::Borrowed("Batched GPU MatMul"),
+    description: Cow::Borrowed("Load rows from JSON, batch them and apply a weight matrix in one matmul (select the GPU backend)"),
+    category: Cow::Borrowed("ML/GPU"),
+    params: Cow::Borrowed(&[TemplateParam::new("Samples file", "read1", "path")]),
+    flow: TemplateFlow::Code(|| {
+        Flow {
+            nodes: vec![
+                Node {
+                    id: "read1".to_string(),
+                    type_name: "json_read".to_string(),
+                    config: json!({"path": "examples/batch_samples.json"}),
+                    position: Some(Position { x: 100.0, y: 200.0 }),
+                    ..Default::default()
+                },
+                Node {
+                    id: "get1".to_string(),
+                    type_name: "object_get".to_string(),
+                    config: json!({"key": "samples"}),
+                    position: Some(Position { x: 300.0, y: 200.0 }),
+                    ..Default::default()
+                },
+                Node {
+                    id: "batch1".to_string(),
+                    type_name: "tensor_batch".to_string(),
+                    config: json!({"batch_size": 0}),
+                    position: Some(Position { x: 500.0, y: 200.0 }),
+                    ..Default::default()
+                },
+                Node {
+                    id: "matmul1".to_string(),
+                    type_name: "tensor_matmul".to_string(),
+                    config: json!({
+                        "rhs": {"rows": 3, "cols": 2, "values": [1.0, 0.0, 0.0, 1.0, 1.0, 1.0]}
+                    }),
+                    position: Some(Position { x: 700.0, y: 200.0 }),
+                    ..Default::default()
+                },
+                Node {
+                    id: "print1".to_string(),
+                    type_name: "print".to_string(),
+                    config: json!({}),
+                    position: Some(Position { x: 900.0, y: 200.0 }),
+                    ..Default::default()
+                },
+            ],
+            edges: vec![
+                Edge {
+                    source: "read1".to_string(),
+                    target: "get1".to_string(),
+                    source_handle: None,
+                    target_handle: None,
+                },
+                Edge {
+                    source: "get1".to_string(),
+                    target: "batch1".to_string(),
+                    source_handle: None,
+                    target_handle: None,
+                },
+                Edge {
+                    source: "batch1".to_string(),
+                    target: "matmul1".to_string(),
+                    source_handle: None,
+                    target_handle: None,
+                },
+                Edge {
+                    source: "matmul1".to_string(),
+                    target: "print1".to_string(),
+                    source_handle: None,
+                    target_handle: None,
+                },
+            ],
+            ..Default::default()
         }
-    },
+    }),
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_templates_share_a_key_when_names_differ_in_case() {
+        assert_eq!(template_key("Invoice Sync"), "invoice_sync");
+        assert_eq!(template_key("invoice-SYNC"), template_key("Invoice Sync"));
+
+        let dir = std::env::temp_dir().join(format!("autograph-templates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, name) in [("a.json", "Invoice Sync"), ("b.json", "invoice sync")] {
+            let template = UserTemplate {
+                name: name.to_string(),
+                description: String::new(),
+                category: UserTemplate::default_category(),
+                params: Vec::new(),
+                flow: Flow::default(),
+            };
+            std::fs::write(dir.join(file), serde_json::to_string(&template).unwrap()).unwrap();
+        }
+        let names: Vec<String> = load_user_templates(&dir).into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["Invoice Sync"]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,230 @@
+//! Flow Tests
+//!
+//! Regression tests for flows. Assertion nodes (`assert_equals`,
+//! `assert_contains`, `assert_schema`) pass their input through unchanged
+//! when compiled; after a test or editor run, each is checked against the
+//! value that reached it. Test cases live next to the flow in
+//! `<flow>.tests.json`:
+//!
+//! ```json
+//! {"cases": [{"name": "vip customer", "input": {"id": 7}, "expected": {"tier": "gold"}}]}
+//! ```
+//!
+//! `autograph test <flow>` runs every case, against the flow's mocks with
+//! `--mock`, and fails if an output differs from `expected` or an assertion
+//! node fails. Assertion nodes a case never reaches, e.g. in a branch it
+//! didn't take, are skipped.
+
+use crate::flow::{CompileOptions, Flow, Node};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Node types checked after a run
+pub const ASSERTION_NODES: &[&str] = &["assert_equals", "assert_contains", "assert_schema"];
+
+/// A flow's test cases
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestSuite {
+    pub cases: Vec<TestCase>,
+}
+
+/// One named input fixture and, optionally, the output it must produce
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    #[serde(default)]
+    pub input: JsonValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<JsonValue>,
+}
+
+/// Outcome of one test case
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub name: String,
+    /// Why the case failed; empty when it passed
+    pub failures: Vec<String>,
+    /// Assertion nodes the case didn't reach
+    pub skipped: Vec<String>,
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// `flows/orders.json` → `flows/orders.tests.json`
+pub fn suite_path(flow_path: &Path) -> PathBuf {
+    let stem = flow_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    flow_path.with_file_name(format!("{}.tests.json", stem))
+}
+
+impl TestSuite {
+    /// The suite next to `flow_path`; empty if there is none
+    pub fn load(flow_path: &Path) -> Result<Self, String> {
+        let path = suite_path(flow_path);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&text).map_err(|e| format!("Invalid test suite {}: {}", path.display(), e))
+    }
+}
+
+/// JSON equality that treats `2` and `2.0` as the same number
+pub fn same(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a, b) {
+        (JsonValue::Number(x), JsonValue::Number(y)) => x.as_f64() == y.as_f64(),
+        (JsonValue::Array(x), JsonValue::Array(y)) => x.len() == y.len() && x.iter().zip(y).all(|(a, b)| same(a, b)),
+        (JsonValue::Object(x), JsonValue::Object(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| same(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Whether `haystack` contains `needle`: a substring of a string, an
+/// element of an array, or a key (string needle) or a subset of fields
+/// (object needle) of an object
+fn contains(haystack: &JsonValue, needle: &JsonValue) -> bool {
+    match (haystack, needle) {
+        (JsonValue::String(s), JsonValue::String(n)) => s.contains(n.as_str()),
+        (JsonValue::Array(items), _) => items.iter().any(|item| same(item, needle)),
+        (JsonValue::Object(fields), JsonValue::String(key)) => fields.contains_key(key),
+        (JsonValue::Object(fields), JsonValue::Object(subset)) => {
+            subset.iter().all(|(k, v)| fields.get(k).is_some_and(|w| same(v, w)))
+        }
+        _ => false,
+    }
+}
+
+/// Check one assertion node's config against the value that reached it
+pub fn check_assertion(type_name: &str, config: &JsonValue, value: &JsonValue) -> Result<(), String> {
+    match type_name {
+        "assert_equals" if same(value, &config["expected"]) => Ok(()),
+        "assert_equals" => Err(format!("expected {}, got {}", config["expected"], value)),
+        "assert_contains" if contains(value, &config["value"]) => Ok(()),
+        "assert_contains" => Err(format!("{} does not contain {}", value, config["value"])),
+        "assert_schema" => {
+            let errors = crate::input_schema::validate(&config["schema"], value);
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(crate::input_schema::describe(&errors).join("; "))
+            }
+        }
+        other => Err(format!("{} is not an assertion", other)),
+    }
+}
+
+fn assertion_nodes(flow: &Flow) -> impl Iterator<Item = &Node> {
+    flow.nodes.iter().filter(|n| n.enabled && ASSERTION_NODES.contains(&n.type_name.as_str()))
+}
+
+/// Failures of the flow's enabled assertion nodes, as `node: message`,
+/// given every node's output from a run. Nodes without an output didn't
+/// run and are left to [`skipped_assertions`].
+pub fn failed_assertions(flow: &Flow, outputs: &BTreeMap<String, JsonValue>) -> Vec<(String, String)> {
+    assertion_nodes(flow)
+        .filter_map(|node| {
+            let value = outputs.get(&node.id)?;
+            let message = check_assertion(&node.type_name, &node.config, value).err()?;
+            Some((node.id.clone(), message))
+        })
+        .collect()
+}
+
+/// The flow's enabled assertion nodes that have no output from a run,
+/// such as those in a branch it didn't take
+pub fn skipped_assertions(flow: &Flow, outputs: &BTreeMap<String, JsonValue>) -> Vec<String> {
+    assertion_nodes(flow)
+        .filter(|node| !outputs.contains_key(&node.id))
+        .map(|node| node.id.clone())
+        .collect()
+}
+
+/// Run one case of the flow saved as `flow_name`. `execute` runs compiled
+/// source on an input with run variables and returns
+/// `{"result": ..., "outputs": {...}}`.
+pub fn run_case(
+    flow: &Flow,
+    flow_name: &str,
+    case: &TestCase,
+    mock: bool,
+    execute: impl Fn(&str, &JsonValue, &crate::RunVars) -> Result<JsonValue, String>,
+) -> CaseResult {
+    let fail = |message: String| CaseResult { name: case.name.clone(), failures: vec![message], skipped: Vec::new() };
+
+    let mut options = CompileOptions { capture_outputs: true, ..Default::default() };
+    // The mock file system lives until the case is done
+    let _mock_fs = if mock {
+        match crate::mock::prepare(flow, &mut options) {
+            Ok(fs) => Some(fs),
+            Err(e) => return fail(e),
+        }
+    } else {
+        None
+    };
+
+    let source = flow.compile_with(&options);
+    let vars = match crate::state_store::prepare(&source, flow_name, &options) {
+        Ok(vars) => vars,
+        Err(e) => return fail(e),
+    };
+    let json = match execute(&source, &case.input, &vars) {
+        Ok(json) => json,
+        Err(e) => return fail(e),
+    };
+    let outputs: BTreeMap<String, JsonValue> = json["outputs"].as_object()
+        .map(|o| o.clone().into_iter().collect())
+        .unwrap_or_default();
+
+    let mut failures: Vec<String> = failed_assertions(flow, &outputs)
+        .into_iter()
+        .map(|(node, message)| format!("{}: {}", node, message))
+        .collect();
+    if let Some(expected) = case.expected.as_ref().filter(|e| !same(e, &json["result"])) {
+        failures.push(format!("output: expected {}, got {}", expected, json["result"]));
+    }
+    CaseResult { name: case.name.clone(), failures, skipped: skipped_assertions(flow, &outputs) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn assertions_check_the_value_that_reached_them() {
+        assert!(check_assertion("assert_equals", &json!({"expected": {"n": 2}}), &json!({"n": 2.0})).is_ok());
+        assert!(check_assertion("assert_equals", &json!({"expected": 1}), &json!(2)).is_err());
+
+        let contains = |value: JsonValue, needle: JsonValue| check_assertion("assert_contains", &json!({"value": needle}), &value).is_ok();
+        assert!(contains(json!("hello world"), json!("world")));
+        assert!(contains(json!([1, {"a": 1}]), json!({"a": 1})));
+        assert!(contains(json!({"a": 1, "b": 2}), json!({"b": 2})));
+        assert!(contains(json!({"a": 1}), json!("a")));
+        assert!(!contains(json!([1, 2]), json!(3)));
+
+        let schema = json!({"schema": {"type": "object", "required": ["id"]}});
+        assert_eq!(check_assertion("assert_schema", &schema, &json!({})).unwrap_err(), "/id: is required");
+    }
+
+    #[test]
+    fn assertions_in_branches_not_taken_are_skipped() {
+        let flow = Flow {
+            nodes: vec![
+                Node::new("vip", "assert_equals", json!({"expected": "gold"})),
+                Node::new("regular", "assert_equals", json!({"expected": "silver"})),
+            ],
+            ..Default::default()
+        };
+        let outputs = BTreeMap::from([("vip".to_string(), json!("bronze"))]);
+
+        assert_eq!(failed_assertions(&flow, &outputs), [("vip".to_string(), "expected \"gold\", got \"bronze\"".to_string())]);
+        assert_eq!(skipped_assertions(&flow, &outputs), ["regular"]);
+    }
+}
@@ -0,0 +1,286 @@
+//! HTTP Rate Limits
+//!
+//! Keeps batch flows under third-party API quotas. A limit is set per host
+//! on the flow (`rate_limits`) or per node (`rate_limit` in an HTTP node's
+//! config, which wins) and enforced three ways:
+//!
+//! - Requests per minute: every request after a run's first to the same
+//!   host (or node) sleeps the minimum interval plus up to 10% jitter.
+//! - Concurrency: runs using the limit hold one of `concurrency` slots for
+//!   the whole run, in the editor, CLI and server alike. The interval is
+//!   scaled by the cap so runs at once stay under `per_minute` together.
+//!   A per-minute limit without a cap takes one slot, and a run waits an
+//!   interval after the last run under the key let go of its slot, so the
+//!   limit holds across the process's runs and not just within each one.
+//! - Rate-limited (429) and 5xx responses wait out `Retry-After` and are
+//!   retried up to `retries` times.
+
+use crate::flow::{Flow, Node};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source line declaring the run slots a program holds: `<key> <count> <interval_ms>`
+const SLOTS_MARKER: &str = "// @rate-limit-slots ";
+
+/// Line opening a compiled flow's `main`, which the declarations follow
+const MAIN_HEADER: &str = "fn main(input) {";
+
+/// Limits on the requests a flow sends to a host or from a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+    /// Requests per minute; 0 for no limit
+    pub per_minute: u32,
+    /// Runs sending requests at once; 0 for no cap, or one at a time
+    /// under a per-minute limit
+    pub concurrency: u32,
+    /// Retries of rate-limited (429) and 5xx responses
+    pub retries: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self { per_minute: 60, concurrency: 0, retries: 3 }
+    }
+}
+
+impl RateLimit {
+    /// Runs allowed at once, 0 for any number
+    fn slots(&self) -> u32 {
+        match (self.concurrency, self.per_minute) {
+            (0, 0) => 0,
+            (0, _) => 1,
+            (concurrency, _) => concurrency,
+        }
+    }
+
+    /// Milliseconds between a run's requests
+    fn interval_ms(&self) -> u64 {
+        match self.per_minute {
+            0 => 0,
+            per_minute => 60_000 * u64::from(self.slots()) / u64::from(per_minute),
+        }
+    }
+}
+
+/// Host of a literal URL, lowercased and without port or credentials
+pub fn host(url: &str) -> Option<String> {
+    let (_, rest) = url.trim().split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    let valid = !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    valid.then(|| host.to_ascii_lowercase())
+}
+
+/// The limit an HTTP node's requests fall under, with the key runs share
+/// it by: `<flow_name>/<node_id>` for the node's own, or its URL's host
+pub fn node_limit(flow: &Flow, node: &Node, flow_name: Option<&str>) -> Option<(String, RateLimit)> {
+    if !crate::nodes::HTTP_REQUEST_NODES.contains(&node.type_name.as_str()) {
+        return None;
+    }
+    if let Some(limit) = node.config.get("rate_limit") {
+        // The key goes in a marker line, so it can't hold whitespace
+        let key = match flow_name {
+            Some(name) => format!("{}/{}", name.replace(char::is_whitespace, "_"), node.id),
+            None => node.id.clone(),
+        };
+        return serde_json::from_value(limit.clone()).ok().map(|limit| (key, limit));
+    }
+    let host = host(node.config["url"].as_str()?)?;
+    flow.rate_limits.get(&host).map(|limit| (host, *limit))
+}
+
+/// Name of the request counter a run keeps for a key
+fn counter(key: &str) -> String {
+    let name: String = key.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    format!("_throttle_{}", name)
+}
+
+/// What a node's code needs to apply a limit, passed in its config as
+/// `throttle`
+pub fn settings(key: &str, limit: &RateLimit) -> JsonValue {
+    serde_json::json!({
+        "key": key,
+        "counter": counter(key),
+        "interval_ms": limit.interval_ms(),
+        "retries": limit.retries,
+    })
+}
+
+/// Declarations at the top of `main` for the limits a program's nodes use:
+/// the run slots it holds and a request counter per key
+pub fn declare_code(limits: &BTreeMap<String, RateLimit>) -> String {
+    let mut code = String::new();
+    for (key, limit) in limits {
+        if limit.slots() > 0 {
+            code.push_str(&format!("    {}{} {} {}\n", SLOTS_MARKER, key, limit.slots(), limit.interval_ms()));
+        }
+        if limit.interval_ms() > 0 {
+            code.push_str(&format!("    let {} = 0;\n", counter(key)));
+        }
+    }
+    code
+}
+
+/// Wait, before a request, until the limit allows it
+pub fn wait_code(config: &JsonValue, indent: &str) -> String {
+    let throttle = &config["throttle"];
+    let interval = throttle["interval_ms"].as_u64().unwrap_or(0);
+    if interval == 0 {
+        return String::new();
+    }
+    format!(
+        "{i}if ({c} > 0) {{\n{i}    sleep({interval} + floor(random() * {jitter}));\n{i}}}\n{i}{c} = {c} + 1;\n",
+        i = indent,
+        c = throttle["counter"].as_str().unwrap_or(""),
+        interval = interval,
+        jitter = (interval / 10).max(1),
+    )
+}
+
+/// Retries of rate-limited requests; none without a limit
+pub fn retries(config: &JsonValue) -> u64 {
+    config["throttle"]["retries"].as_u64().unwrap_or(0)
+}
+
+/// HLX call sending a request, retrying rate-limited ones under a limit
+pub fn request_call(config: &JsonValue, method: &str, url: &str, body: &str, headers: &str) -> String {
+    match retries(config) {
+        0 => format!("http_request({}, {}, {}, {})", method, url, body, headers),
+        retries => format!("http_request_retry({}, {}, {}, {}, {})", method, url, body, headers, retries),
+    }
+}
+
+static SLOTS: Mutex<BTreeMap<String, u32>> = Mutex::new(BTreeMap::new());
+static FREED: Condvar = Condvar::new();
+/// When a run last gave back a slot of each key
+static RELEASED: Mutex<BTreeMap<String, Instant>> = Mutex::new(BTreeMap::new());
+
+/// Run slots held by a run, given back when dropped
+pub struct RunSlots(Vec<String>);
+
+impl Drop for RunSlots {
+    fn drop(&mut self) {
+        let now = Instant::now();
+        let mut released = RELEASED.lock().unwrap();
+        for key in &self.0 {
+            released.insert(key.clone(), now);
+        }
+        drop(released);
+
+        let mut taken = SLOTS.lock().unwrap();
+        for key in &self.0 {
+            if let Some(count) = taken.get_mut(key) {
+                *count -= 1;
+                if *count == 0 {
+                    taken.remove(key);
+                }
+            }
+        }
+        FREED.notify_all();
+    }
+}
+
+/// Take a slot of every limit compiled into `source`, waiting for runs
+/// holding them to finish, then for an interval after the last of those
+/// let go so its final request and this run's first are spaced too.
+///
+/// Only the declarations `declare_code` puts at the top of `main` count,
+/// not marker-like text a node's code happens to contain.
+pub fn acquire(source: &str) -> RunSlots {
+    let mut wanted: Vec<(String, u32, u64)> = source.lines()
+        .skip_while(|line| *line != MAIN_HEADER)
+        .skip(1)
+        .take_while(|line| {
+            line.strip_prefix("    ").is_some_and(|decl| decl.starts_with(SLOTS_MARKER) || decl.starts_with("let _throttle_"))
+        })
+        .filter_map(|line| {
+            let mut parts = line.trim().strip_prefix(SLOTS_MARKER)?.split_whitespace();
+            let key = parts.next()?.to_string();
+            // No run could ever take one of 0 slots
+            let count = parts.next()?.parse().ok().filter(|count| *count > 0)?;
+            let interval = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+            Some((key, count, interval))
+        })
+        .collect();
+    // Always in key order, so two runs can't each hold what the other waits for
+    wanted.sort();
+    wanted.dedup_by(|a, b| a.0 == b.0);
+
+    let mut taken = SLOTS.lock().unwrap();
+    for (key, count, _) in &wanted {
+        while taken.get(key).copied().unwrap_or(0) >= *count {
+            taken = FREED.wait(taken).unwrap();
+        }
+        *taken.entry(key.clone()).or_default() += 1;
+    }
+    drop(taken);
+
+    let released = RELEASED.lock().unwrap();
+    let ready = wanted.iter()
+        .filter_map(|(key, _, interval)| Some(*released.get(key)? + Duration::from_millis(*interval)))
+        .max();
+    drop(released);
+    if let Some(wait) = ready.and_then(|ready| ready.checked_duration_since(Instant::now())) {
+        std::thread::sleep(wait);
+    }
+    RunSlots(wanted.into_iter().map(|(key, _, _)| key).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn host_limits_space_requests_and_cap_runs() {
+        let node = |id: &str, config: JsonValue| Node::new(id, "http_get", config);
+        let mut flow = Flow {
+            nodes: vec![
+                node("users", json!({"url": "https://API.example.com/users"})),
+                node("orders", json!({"url": "https://api.example.com:443/orders?page=1"})),
+                node("own", json!({"url": "https://api.example.com/x", "rate_limit": {"per_minute": 0, "retries": 0}})),
+            ],
+            ..Default::default()
+        };
+        flow.rate_limits.insert("api.example.com".to_string(), RateLimit { per_minute: 30, concurrency: 2, retries: 5 });
+
+        let source = flow.compile_to_hlx();
+        // 30 a minute shared by 2 runs at once
+        assert_eq!(source.matches("// @rate-limit-slots api.example.com 2 4000").count(), 1);
+        assert_eq!(source.matches("let _throttle_api_example_com = 0;").count(), 1);
+        assert_eq!(source.matches("sleep(4000 + floor(random() * 400));").count(), 2);
+        assert_eq!(source.matches("http_request_retry(").count(), 2);
+        assert!(source.contains("let own_out = http_request(\"GET\""));
+
+        // The counter is declared ahead of every node, so one skipped by a
+        // pinned output doesn't leave the next without it
+        let options = crate::flow::CompileOptions {
+            fixed_outputs: [("users".to_string(), json!([]))].into(),
+            ..Default::default()
+        };
+        let pinned = flow.compile_with(&options);
+        let declared = pinned.find("let _throttle_api_example_com = 0;").unwrap();
+        assert!(declared < pinned.find("let orders_out").unwrap());
+
+        // Without a cap, a per-minute limit holds across runs with one slot
+        flow.rate_limits.insert("api.example.com".to_string(), RateLimit { per_minute: 60, concurrency: 0, retries: 0 });
+        assert!(flow.compile_to_hlx().contains("// @rate-limit-slots api.example.com 1 1000"));
+
+        // A node's own limit is its flow's, not every flow's with that node ID
+        assert_eq!(node_limit(&flow, &flow.nodes[2], Some("batch")).unwrap().0, "batch/own");
+
+        // Only declarations at the top of main count, and never 0 slots
+        let spoofed = acquire("fn main(input) {\n    // @rate-limit-slots a 0 0\n    let b_out = 1;\n    // @rate-limit-slots c 1 0\n}\n");
+        assert!(spoofed.0.is_empty());
+
+        let first = acquire(&source);
+        let second = acquire(&source);
+        assert_eq!(SLOTS.lock().unwrap()["api.example.com"], 2);
+        drop((first, second));
+        assert!(!SLOTS.lock().unwrap().contains_key("api.example.com"));
+    }
+}
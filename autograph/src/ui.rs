@@ -3,18 +3,56 @@
 //! egui-based visual flow editor for HLX workflows
 
 use eframe::egui;
-use crate::flow::{Flow, Node, Edge, Position};
-use std::collections::HashMap;
+use crate::flow::{CompileOptions, Flow, Node, Edge, Position};
+use crate::diagnostics::Diagnostic;
+use crate::gpu_stats::GpuMemoryStats;
+use crate::runs::{now_ms, RunRecord, RunStore};
+use std::collections::{BTreeMap, HashMap};
 
+mod bundle_dialog;
 mod canvas;
+mod code_editor;
+mod flow_browser;
+mod generated_code;
+mod history;
+mod inspector;
+mod json_tree;
+mod metadata;
+mod notes;
 mod palette;
+mod project;
 mod properties;
+mod quick_add;
+mod remote;
+mod run_compare;
+mod run_form;
+mod stats;
+mod template_dialog;
+mod template_gallery;
+mod tensor_view;
 mod timeline;
+mod undo;
+mod versions;
 
+use bundle_dialog::{BundleDialog, BundleDialogAction, BundleMode};
 use canvas::Canvas;
+use flow_browser::{FlowBrowser, FlowBrowserAction};
+use generated_code::GeneratedCode;
+use history::{HistoryAction, HistoryPanel};
+use inspector::{InspectorAction, Moment};
+use metadata::{SaveDialog, SaveDialogAction};
 use palette::NodePalette;
+use project::{ProjectAction, ProjectPanel};
 use properties::PropertiesPanel;
+use quick_add::{QuickAdd, QuickAddAction};
+use remote::{RemoteAction, RemoteJob, RemoteOutcome, RemotePanel};
+use run_compare::RunCompare;
+use run_form::{RunForm, RunFormAction};
+use template_dialog::{TemplateDialog, TemplateDialogAction};
+use template_gallery::{GalleryAction, TemplateGallery};
 use timeline::{Timeline, TimelineEntry};
+use undo::{Cached, UndoHistory};
+use versions::{VersionsAction, VersionsPanel};
 
 /// Execution state for a node
 #[derive(Debug, Clone, PartialEq)]
@@ -29,8 +67,65 @@ pub enum ExecutionState {
 #[derive(Debug, Clone)]
 pub struct NodeExecution {
     pub state: ExecutionState,
-    pub output: Option<String>,
+    /// Pretty-printed output; replace it with `set_output`
+    output: Option<String>,
     pub duration_ms: Option<u64>,
+    /// `output` parsed on first use, so large outputs aren't parsed every frame
+    parsed_output: std::cell::OnceCell<Option<serde_json::Value>>,
+}
+
+impl NodeExecution {
+    fn new(state: ExecutionState, output: Option<String>, duration_ms: Option<u64>) -> Self {
+        Self { state, output, duration_ms, parsed_output: Default::default() }
+    }
+
+    /// Replace the output, forgetting its parsed value
+    pub fn set_output(&mut self, output: Option<String>) {
+        self.output = output;
+        self.parsed_output = Default::default();
+    }
+
+    /// The output as JSON, if it is valid JSON
+    pub fn output_value(&self) -> Option<&serde_json::Value> {
+        self.parsed_output
+            .get_or_init(|| self.output.as_deref().and_then(|output| serde_json::from_str(output).ok()))
+            .as_ref()
+    }
+}
+
+/// Two versions of the flow being compared on the canvas
+struct VersionDiff {
+    /// The newer version, shown read-only; None when comparing with the editor's flow
+    shown: Option<Flow>,
+    label: String,
+    summary: String,
+}
+
+/// The state of a flow open in a tab. The active tab's state lives in the
+/// app's own fields; switching tabs swaps it with the tab's slot.
+#[derive(Default)]
+struct FlowTab {
+    flow: Flow,
+    flow_name: String,
+    selected_node: Option<String>,
+    canvas: Canvas,
+    execution_result: Option<String>,
+    error_message: Option<String>,
+    node_executions: HashMap<String, NodeExecution>,
+    execution_log: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+    timeline_entries: Vec<TimelineEntry>,
+    captured_outputs: HashMap<String, serde_json::Value>,
+    last_input: serde_json::Value,
+    moment: Option<Moment>,
+    version_diff: Option<VersionDiff>,
+    undo: UndoHistory,
+}
+
+impl FlowTab {
+    fn new(flow_name: &str) -> Self {
+        Self { flow_name: flow_name.to_string(), ..Default::default() }
+    }
 }
 
 /// Main Autograph application
@@ -65,6 +160,9 @@ pub struct AutographApp {
     /// Execution log entries
     execution_log: Vec<String>,
 
+    /// Compiler errors of the last run or compile, located on their nodes
+    diagnostics: Vec<Diagnostic>,
+
     /// Whether execution is in progress
     executing: bool,
 
@@ -80,11 +178,134 @@ pub struct AutographApp {
     /// Selected backend for execution
     backend_selection: BackendType,
 
+    /// Refuse to run nodes that reach the network
+    offline: bool,
+
+    /// Inject the flow's chaos faults into runs
+    chaos: bool,
+
+    /// Run with the flow's mocks instead of real services
+    mock: bool,
+
     /// Dark mode enabled
     dark_mode: bool,
 
     /// Show mini-map
     show_minimap: bool,
+
+    /// Per-node outputs captured during the last run, used for replay
+    captured_outputs: HashMap<String, serde_json::Value>,
+
+    /// Input of the last run, reused for replay
+    last_input: serde_json::Value,
+
+    /// Persisted run history
+    run_store: RunStore,
+
+    /// Run history browser
+    history: HistoryPanel,
+
+    /// Generated HLX viewer in the output panel
+    generated_code: GeneratedCode,
+
+    /// Show run history window
+    show_history: bool,
+
+    /// Snapshots of each flow taken on save and run
+    version_store: crate::versions::VersionStore,
+
+    /// Flow version browser
+    versions: VersionsPanel,
+
+    /// Show flow versions window
+    show_versions: bool,
+
+    /// Browser of the flows in `flows/`
+    flow_browser: FlowBrowser,
+
+    /// Show the flow browser window
+    show_flows: bool,
+
+    /// Versions being compared on the canvas
+    version_diff: Option<VersionDiff>,
+
+    /// Show flow metadata window
+    show_metadata: bool,
+
+    /// Show the flow statistics overlay
+    show_stats: bool,
+
+    /// Pending save prompt
+    save_dialog: Option<SaveDialog>,
+
+    /// Input form for a run of a flow with an input schema
+    run_form: Option<RunForm>,
+
+    /// Two runs being compared
+    run_compare: Option<RunCompare>,
+
+    /// Timeline moment the canvas is rewound to, if time-travelling
+    moment: Option<Moment>,
+
+    /// Show the value inspector window
+    show_inspector: bool,
+
+    /// Open quick-add popup
+    quick_add: Option<QuickAdd>,
+
+    /// Pending bundle export/import prompt
+    bundle_dialog: Option<BundleDialog>,
+
+    /// Pending save-as-template prompt
+    template_dialog: Option<TemplateDialog>,
+
+    /// Template gallery
+    template_gallery: TemplateGallery,
+
+    /// Show the template gallery window
+    show_templates: bool,
+
+    /// Node awaiting delete confirmation, with what depends on it
+    pending_delete: Option<(String, Vec<String>)>,
+
+    /// Device memory used by the last run on the Vulkan backend
+    gpu_memory: Option<GpuMemoryStats>,
+
+    /// Reloads plugin nodes when the plugin directory changes
+    plugin_watcher: crate::plugins::PluginWatcher,
+
+    /// Open tabs; the active one's slot is stale until switched away from
+    tabs: Vec<FlowTab>,
+
+    /// Index of the active tab
+    active_tab: usize,
+
+    /// Undo history of the active tab's flow
+    undo: UndoHistory,
+
+    /// Config expression errors of the active tab's flow
+    expression_errors: Cached<Vec<(String, String)>>,
+
+    /// Open flows, shared variables and secret references
+    project: crate::project::Project,
+
+    /// Project window
+    project_panel: ProjectPanel,
+
+    /// Show the project window
+    show_project: bool,
+
+    /// Server to deploy to and run on
+    remote: RemotePanel,
+
+    /// Show the server window
+    show_remote: bool,
+
+    /// Deploy or remote run in progress
+    remote_job: Option<RemoteJob>,
+
+    /// Preflight in progress on another thread, as host checks take seconds
+    preflight_job: Option<std::sync::mpsc::Receiver<Vec<crate::preflight::CheckResult>>>,
 }
 
 /// Backend type for execution
@@ -116,10 +337,7 @@ impl BackendType {
 impl Default for AutographApp {
     fn default() -> Self {
         Self {
-            flow: Flow {
-                nodes: Vec::new(),
-                edges: Vec::new(),
-            },
+            flow: Flow::default(),
             selected_node: None,
             canvas: Canvas::default(),
             palette: NodePalette::default(),
@@ -129,13 +347,55 @@ impl Default for AutographApp {
             error_message: None,
             node_executions: HashMap::new(),
             execution_log: Vec::new(),
+            diagnostics: Vec::new(),
             executing: false,
             inspected_node: None,
             timeline: Timeline::default(),
             timeline_entries: Vec::new(),
             backend_selection: BackendType::Auto,
+            offline: crate::offline::enabled_by_env(),
+            chaos: false,
+            mock: false,
             dark_mode: true,  // Default to dark mode
             show_minimap: true,  // Show minimap by default
+            captured_outputs: HashMap::new(),
+            last_input: serde_json::Value::Null,
+            run_store: RunStore::new("runs"),
+            history: HistoryPanel::default(),
+            generated_code: GeneratedCode::default(),
+            show_history: false,
+            version_store: crate::versions::VersionStore::new("flows/.versions"),
+            versions: VersionsPanel::default(),
+            show_versions: false,
+            flow_browser: FlowBrowser::default(),
+            show_flows: false,
+            version_diff: None,
+            show_metadata: false,
+            show_stats: false,
+            save_dialog: None,
+            run_form: None,
+            run_compare: None,
+            moment: None,
+            show_inspector: false,
+            quick_add: None,
+            bundle_dialog: None,
+            template_dialog: None,
+            template_gallery: TemplateGallery::default(),
+            show_templates: false,
+            pending_delete: None,
+            gpu_memory: None,
+            plugin_watcher: crate::plugins::PluginWatcher::new(std::path::Path::new(crate::plugins::PLUGINS_DIR)),
+            tabs: vec![FlowTab::new("untitled")],
+            active_tab: 0,
+            undo: UndoHistory::default(),
+            expression_errors: Cached::default(),
+            project: crate::project::Project::default(),
+            project_panel: ProjectPanel::default(),
+            show_project: false,
+            remote: RemotePanel::default(),
+            show_remote: false,
+            remote_job: None,
+            preflight_job: None,
         }
     }
 }
@@ -143,16 +403,174 @@ impl Default for AutographApp {
 impl AutographApp {
     /// Create a new Autograph app
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self::default()
+        let mut app = Self::default();
+        // Reopen the tabs of the last saved project
+        if std::path::Path::new(crate::project::PROJECT_FILE).exists() {
+            app.open_project();
+        }
+        app
+    }
+
+    /// Exchange the app's active state with a tab's slot
+    fn swap_tab(&mut self, index: usize) {
+        let tab = &mut self.tabs[index];
+        std::mem::swap(&mut self.flow, &mut tab.flow);
+        std::mem::swap(&mut self.flow_name, &mut tab.flow_name);
+        std::mem::swap(&mut self.selected_node, &mut tab.selected_node);
+        std::mem::swap(&mut self.canvas, &mut tab.canvas);
+        std::mem::swap(&mut self.execution_result, &mut tab.execution_result);
+        std::mem::swap(&mut self.error_message, &mut tab.error_message);
+        std::mem::swap(&mut self.node_executions, &mut tab.node_executions);
+        std::mem::swap(&mut self.execution_log, &mut tab.execution_log);
+        std::mem::swap(&mut self.diagnostics, &mut tab.diagnostics);
+        std::mem::swap(&mut self.timeline_entries, &mut tab.timeline_entries);
+        std::mem::swap(&mut self.captured_outputs, &mut tab.captured_outputs);
+        std::mem::swap(&mut self.last_input, &mut tab.last_input);
+        std::mem::swap(&mut self.moment, &mut tab.moment);
+        std::mem::swap(&mut self.version_diff, &mut tab.version_diff);
+        std::mem::swap(&mut self.undo, &mut tab.undo);
+    }
+
+    /// Make another tab the active one
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+        self.swap_tab(self.active_tab);
+        self.swap_tab(index);
+        self.active_tab = index;
+        self.reset_tab_view();
+    }
+
+    /// Drop app-wide state that belonged to the previously active tab
+    fn reset_tab_view(&mut self) {
+        self.timeline.select(self.moment.as_ref().map(|m| m.entry_idx));
+        self.pending_delete = None;
+        self.gpu_memory = None;
+        self.timeline.set_gpu_memory(None);
+    }
+
+    /// Name of the flow in a tab
+    fn tab_name(&self, index: usize) -> &str {
+        if index == self.active_tab { &self.flow_name } else { &self.tabs[index].flow_name }
+    }
+
+    /// Open an empty flow in a new tab
+    pub fn new_tab(&mut self) {
+        self.tabs.push(FlowTab::new("untitled"));
+        self.switch_tab(self.tabs.len() - 1);
+    }
+
+    /// Open a saved flow in a new tab, or switch to it if it is already open
+    pub fn open_tab(&mut self, name: String) {
+        if let Some(index) = (0..self.tabs.len()).find(|&i| self.tab_name(i) == name) {
+            self.switch_tab(index);
+            return;
+        }
+        self.new_tab();
+        self.load_flow(name);
+    }
+
+    /// Close a tab, keeping at least one open
+    pub fn close_tab(&mut self, index: usize) {
+        if self.tabs.len() == 1 {
+            self.tabs[0] = FlowTab::new("untitled");
+            self.swap_tab(0);
+            self.clear_execution();
+            return;
+        }
+        self.tabs.remove(index);
+        if index < self.active_tab {
+            self.active_tab -= 1;
+        } else if index == self.active_tab {
+            // The removed slot was stale; load the neighbour's state
+            self.active_tab = index.min(self.tabs.len() - 1);
+            self.swap_tab(self.active_tab);
+            self.reset_tab_view();
+        }
+    }
+
+    /// Undo the active tab's last edit
+    pub fn undo(&mut self) {
+        if self.undo.undo(&mut self.flow) {
+            self.prune_selection();
+        }
+    }
+
+    /// Redo the active tab's last undone edit
+    pub fn redo(&mut self) {
+        if self.undo.redo(&mut self.flow) {
+            self.prune_selection();
+        }
+    }
+
+    /// Drop the selection if undo or redo removed the node
+    fn prune_selection(&mut self) {
+        if let Some(id) = &self.selected_node {
+            if !self.flow.nodes.iter().any(|n| &n.id == id) {
+                self.selected_node = None;
+            }
+        }
+    }
+
+    /// Write the project file with the flows open in tabs
+    pub fn save_project(&mut self) {
+        let mut open = Vec::new();
+        let mut active = 0;
+        for index in 0..self.tabs.len() {
+            let name = self.tab_name(index);
+            if std::path::Path::new(&format!("flows/{}.json", name)).exists() {
+                if index == self.active_tab {
+                    active = open.len();
+                }
+                open.push(name.to_string());
+            }
+        }
+        self.project.open_flows = open;
+        self.project.active = active;
+        match self.project.save(std::path::Path::new(crate::project::PROJECT_FILE)) {
+            Ok(()) => self.execution_log.push(format!(
+                "✓ Saved project with {} flow(s) to {}",
+                self.project.open_flows.len(),
+                crate::project::PROJECT_FILE
+            )),
+            Err(e) => self.execution_log.push(format!("❌ Failed to save the project: {}", e)),
+        }
+    }
+
+    /// Replace the open tabs with the project file's flows
+    pub fn open_project(&mut self) {
+        let project = match crate::project::Project::load(std::path::Path::new(crate::project::PROJECT_FILE)) {
+            Ok(project) => project,
+            Err(e) => {
+                self.execution_log.push(format!("❌ Failed to open the project: {}", e));
+                return;
+            }
+        };
+        self.tabs = vec![FlowTab::new("untitled")];
+        self.active_tab = 0;
+        self.swap_tab(0);
+        self.clear_execution();
+        for (index, name) in project.open_flows.iter().enumerate() {
+            if index == 0 {
+                self.load_flow(name.clone());
+            } else {
+                self.open_tab(name.clone());
+            }
+        }
+        self.switch_tab(project.active);
+        crate::http_auth::set_profiles(&project.auth_profiles);
+        self.project = project;
     }
 
     /// Add a new node to the flow
     pub fn add_node(&mut self, type_name: String, position: Position) {
-        let id = format!("node_{}", self.flow.nodes.len());
+        let id = self.flow.next_node_id();
         let config = match type_name.as_str() {
             "http_request" => serde_json::json!({
                 "method": "GET",
-                "url": "https://example.com"
+                "url": "https://example.com",
+                "output": "response"
             }),
             "tensor_create" => serde_json::json!({
                 "rows": 2,
@@ -170,27 +588,56 @@ impl AutographApp {
             type_name,
             config,
             position: Some(position),
-            breakpoint: false,
+            ..Default::default()
         });
 
         self.selected_node = Some(id);
     }
 
-    /// Delete selected node
-    pub fn delete_selected_node(&mut self) {
-        if let Some(node_id) = &self.selected_node {
-            // Remove node
-            self.flow.nodes.retain(|n| &n.id != node_id);
+    /// Delete the selected node, first asking for confirmation if other
+    /// nodes depend on it
+    pub fn request_delete_selected_node(&mut self) {
+        let Some(node_id) = self.selected_node.clone() else {
+            return;
+        };
+        let impact = crate::impact::node_impact(&self.flow, &node_id);
+        if impact.is_empty() {
+            self.delete_node(&node_id);
+        } else {
+            self.pending_delete = Some((node_id, impact));
+        }
+    }
 
-            // Remove connected edges
-            self.flow.edges.retain(|e| {
-                e.source != *node_id && e.target != *node_id
-            });
+    /// Remove a node with its edges, chaos rule, mock and pinned output
+    fn delete_node(&mut self, node_id: &str) {
+        self.flow.nodes.retain(|n| n.id != node_id);
+        self.flow.edges.retain(|e| e.source != node_id && e.target != node_id);
+        self.flow.chaos.retain(|rule| rule.node != node_id);
+        self.flow.mocks.retain(|rule| rule.node != node_id);
+        self.flow.pinned.remove(node_id);
+        for group in &mut self.flow.groups {
+            group.nodes.retain(|id| id != node_id);
+        }
+        self.flow.groups.retain(|g| !g.nodes.is_empty());
 
+        if self.selected_node.as_deref() == Some(node_id) {
             self.selected_node = None;
         }
     }
 
+    /// Pin a node's last captured output to it as fixture data
+    fn pin_output(&mut self, node_id: &str) {
+        match self.captured_outputs.get(node_id) {
+            Some(output) => {
+                self.flow.pinned.insert(node_id.to_string(), output.clone());
+                self.execution_log.push(format!("📌 Pinned the output of {}", node_id));
+            }
+            None => {
+                self.error_message = Some(format!("{} has no captured output to pin. Run the flow first.", node_id));
+            }
+        }
+    }
+
     /// Add edge between two nodes
     pub fn add_edge(&mut self, source: String, target: String) {
         // Check if edge already exists
@@ -210,6 +657,11 @@ impl AutographApp {
 
     /// Compile flow to HLX
     pub fn compile_flow(&mut self) {
+        if let Err(e) = self.flow.validate() {
+            self.error_message = Some(format!("Invalid flow: {}", e));
+            return;
+        }
+
         let source = self.flow.compile_to_hlx();
 
         // Save to file
@@ -220,43 +672,61 @@ impl AutographApp {
             self.error_message = None;
             self.execution_result = Some(format!("Compiled successfully to {}", path));
         }
+
+        self.diagnostics.clear();
+        if let Some(diagnostic) = crate::diagnostics::check(&self.flow, &source) {
+            self.execution_result = None;
+            self.show_diagnostic(diagnostic);
+        }
     }
 
     /// Clear execution state
     pub fn clear_execution(&mut self) {
         self.node_executions.clear();
         self.execution_log.clear();
+        self.diagnostics.clear();
         self.timeline_entries.clear();
         self.executing = false;
         self.execution_result = None;
         self.error_message = None;
+        self.moment = None;
+        self.timeline.select(None);
+        self.gpu_memory = None;
+        self.timeline.set_gpu_memory(None);
+    }
+
+    /// Rewind the canvas to a timeline entry, or return to the live state
+    fn travel_to(&mut self, entry_idx: Option<usize>) {
+        self.moment = entry_idx.and_then(|idx| {
+            Moment::at(&self.flow, &self.timeline_entries, idx, &self.captured_outputs)
+        });
+        self.timeline.select(self.moment.as_ref().map(|m| m.entry_idx));
+        if let Some(moment) = &self.moment {
+            self.selected_node = Some(moment.node_id.clone());
+        }
     }
 
     /// Mark all nodes as pending
     fn mark_nodes_pending(&mut self) {
         for node in &self.flow.nodes {
-            self.node_executions.insert(
-                node.id.clone(),
-                NodeExecution {
-                    state: ExecutionState::Pending,
-                    output: None,
-                    duration_ms: None,
-                },
-            );
+            self.node_executions.insert(node.id.clone(), NodeExecution::new(ExecutionState::Pending, None, None));
+        }
+    }
+
+    /// Run the flow, asking for its input first if it declares a schema
+    fn start_run(&mut self) {
+        match &self.flow.input_schema {
+            Some(schema) => self.run_form = Some(RunForm::new(schema.clone(), &self.last_input)),
+            None => self.run_flow(serde_json::json!(null)),
         }
     }
 
     /// Execute flow with input
     pub fn run_flow(&mut self, input: serde_json::Value) {
-        use hlx_compiler::hlxa::HlxaParser;
-        use hlx_compiler::parser::Parser;
-        use hlx_compiler::lower::lower_to_crate;
-        use hlx_runtime::config::RuntimeConfig;
-        use hlx_runtime::execute_with_config;
-        use std::time::Instant;
-
         // Clear previous execution
         self.clear_execution();
+        self.captured_outputs.clear();
+        self.last_input = input.clone();
 
         // Mark all nodes as pending
         self.mark_nodes_pending();
@@ -264,6 +734,7 @@ impl AutographApp {
         self.execution_log.push(format!("=== Starting execution of '{}' ===", self.flow_name));
         self.execution_log.push(format!("Backend: {}", self.backend_selection.as_str()));
         self.execution_log.push(format!("Input: {}", serde_json::to_string(&input).unwrap_or("null".to_string())));
+        self.snapshot_version("run");
 
         // First compile
         self.compile_flow();
@@ -274,105 +745,597 @@ impl AutographApp {
         }
 
         self.execution_log.push("✓ Compilation successful".to_string());
+
+        let options = CompileOptions {
+            capture_outputs: true,
+            ..Default::default()
+        };
+        self.execute_captured(&input, &options);
+
+        self.execution_log.push("=== Execution finished ===".to_string());
+    }
+
+    /// Re-run the flow from a timeline entry.
+    ///
+    /// Outputs of nodes outside the entry's downstream subgraph are restored
+    /// from the last run's snapshot, so only the downstream nodes execute.
+    pub fn replay_from(&mut self, entry_idx: usize) {
+        let Some(entry) = self.timeline_entries.get(entry_idx) else {
+            return;
+        };
+        let node_id = entry.node_id.clone();
+
+        if self.captured_outputs.is_empty() {
+            self.error_message = Some("No captured outputs to replay from. Run the flow first.".to_string());
+            return;
+        }
+
+        let downstream = self.flow.downstream_ids(&node_id);
+        let fixed_outputs: HashMap<String, serde_json::Value> = self.captured_outputs
+            .iter()
+            .filter(|(id, _)| !downstream.contains(*id))
+            .map(|(id, value)| (id.clone(), value.clone()))
+            .collect();
+        let input = self.last_input.clone();
+
+        self.clear_execution();
+        self.mark_nodes_pending();
+
+        self.execution_log.push(format!(
+            "=== Replaying '{}' from {} ({} of {} nodes re-executed) ===",
+            self.flow_name,
+            node_id,
+            self.flow.nodes.iter().filter(|n| !fixed_outputs.contains_key(&n.id)).count(),
+            self.flow.nodes.len()
+        ));
+        self.execution_log.push(format!("Backend: {}", self.backend_selection.as_str()));
+
+        let options = CompileOptions {
+            capture_outputs: true,
+            fixed_outputs,
+            ..Default::default()
+        };
+        self.execute_captured(&input, &options);
+
+        self.execution_log.push("=== Execution finished ===".to_string());
+    }
+
+    /// Apply the editor's pins, mocks, offline mode and chaos to the options
+    /// for running `flow`. Returns `None` if the run can't go ahead, otherwise
+    /// the mock file system, if mocking, which must outlive the run.
+    fn prepare_run_options(&mut self, flow: &Flow, options: &mut CompileOptions) -> Option<Option<crate::mock::MockFs>> {
+        options.flow_name = Some(self.flow_name.clone());
+        // Pins go first so they win over mocks
+        let skipped = crate::pinning::apply(flow, options, &self.captured_outputs);
+        for line in crate::pinning::describe(flow, &skipped) {
+            self.execution_log.push(format!("📌 {}", line));
+        }
+
+        let mock_fs = if self.mock {
+            match crate::mock::prepare(flow, options) {
+                Ok(fs) => {
+                    self.execution_log.extend(crate::mock::describe(flow).into_iter().map(|line| format!("🎭 {}", line)));
+                    Some(fs)
+                }
+                Err(e) => {
+                    self.execution_log.push(format!("❌ {}", e));
+                    self.error_message = Some(e);
+                    return None;
+                }
+            }
+        } else {
+            None
+        };
+
+        if self.offline {
+            let blocked = crate::offline::blocked_nodes(flow, &options.fixed_outputs);
+            if !blocked.is_empty() {
+                self.report_blocked(blocked);
+                return None;
+            }
+        }
+
+        if self.chaos {
+            options.faults = crate::chaos::plan(&flow.chaos, options.seed.or(flow.seed), 1);
+            options.faults.retain(|id, _| !options.fixed_outputs.contains_key(id));
+            for line in crate::chaos::describe(&options.faults) {
+                self.execution_log.push(format!("🧪 {}", line));
+            }
+        }
+
+        Some(mock_fs)
+    }
+
+    /// Execute the flow compiled with output capture, recording each node's
+    /// output in its execution state, the timeline and the replay snapshot
+    fn execute_captured(&mut self, input: &serde_json::Value, options: &CompileOptions) {
+        use std::time::Instant;
+
+        let mut options = options.clone();
+        let flow = self.flow.clone();
+        // The mock file system lives until the run is done
+        let Some(_mock_fs) = self.prepare_run_options(&flow, &mut options) else {
+            return;
+        };
+
+        let source = self.flow.compile_with(&options);
+        let Some(vars) = self.run_vars(&source, &options) else {
+            return;
+        };
         self.executing = true;
+        self.execution_log.push("⚡ Executing workflow...".to_string());
 
-        // Load and execute
-        let path = format!("flows/{}.hlxa", self.flow_name);
-        match std::fs::read_to_string(&path) {
-            Ok(source) => {
-                let parser = HlxaParser;
-                match parser.parse(&source) {
-                    Ok(program) => {
-                        self.execution_log.push("✓ Parsed HLX source".to_string());
-
-                        match lower_to_crate(&program) {
-                            Ok(krate) => {
-                                self.execution_log.push("✓ Lowered to IR".to_string());
-                                self.execution_log.push("⚡ Executing workflow...".to_string());
-
-                                let mut config = RuntimeConfig::default();
-                                config.main_input = Some(input.to_string());
-                                config.backend = self.backend_selection.to_runtime_backend();
-
-                                let start = Instant::now();
-                                match execute_with_config(&krate, &config) {
-                                    Ok(result) => {
-                                        let duration = start.elapsed();
-                                        self.execution_log.push(format!("✓ Execution completed in {}ms", duration.as_millis()));
-
-                                        // Mark all nodes as completed and create timeline entries
-                                        let mut timeline_offset_ms = 0u64;
-                                        for node in &self.flow.nodes {
-                                            // Simulate per-node timing (in reality, all execute together)
-                                            // In Phase 4 Part 2, we'll get real per-node timing from runtime
-                                            let node_duration = duration.as_millis() as u64 / self.flow.nodes.len() as u64;
-
-                                            if let Some(exec) = self.node_executions.get_mut(&node.id) {
-                                                exec.state = ExecutionState::Completed;
-                                                exec.duration_ms = Some(node_duration);
-                                            }
-
-                                            // Add timeline entry
-                                            self.timeline_entries.push(TimelineEntry {
-                                                node_id: node.id.clone(),
-                                                node_name: node.type_name.clone(),
-                                                timestamp_ms: timeline_offset_ms,
-                                                duration_ms: node_duration,
-                                                state: ExecutionState::Completed,
-                                                output: None, // TODO: Capture from runtime
-                                            });
-
-                                            timeline_offset_ms += node_duration;
-                                        }
-
-                                        match result.to_json() {
-                                            Ok(json) => {
-                                                let result_str = serde_json::to_string_pretty(&json).unwrap();
-                                                self.execution_result = Some(result_str.clone());
-                                                self.execution_log.push(format!("Result: {}", result_str));
-                                                self.error_message = None;
-                                            }
-                                            Err(e) => {
-                                                self.error_message = Some(format!("JSON conversion error: {}", e));
-                                                self.execution_log.push(format!("❌ JSON conversion failed: {}", e));
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        self.error_message = Some(format!("Runtime error: {}", e));
-                                        self.execution_log.push(format!("❌ Runtime error: {}", e));
-
-                                        // Mark all nodes as error
-                                        for node in &self.flow.nodes {
-                                            if let Some(exec) = self.node_executions.get_mut(&node.id) {
-                                                exec.state = ExecutionState::Error(e.to_string());
-                                            }
-                                        }
-                                    }
-                                }
+        let started_at = now_ms();
+        let start = Instant::now();
+        let mut outcome = self.execute_measured(&source, input, &vars);
+
+        // Pin the outputs of tensor nodes the GPU couldn't run and retry
+        if outcome.is_err() && self.flow.cpu_fallback && self.backend_selection != BackendType::Cpu {
+            let backend = self.backend_selection;
+            let fallback = crate::fallback::resolve_on_cpu(&self.flow, &options, input, |source, input, on_cpu| {
+                execute_hlx(source, input, if on_cpu { BackendType::Cpu } else { backend }, &vars)
+            });
+            if let Some(fallback) = fallback {
+                self.execution_log.extend(fallback.log);
+                options.fixed_outputs = fallback.fixed_outputs;
+                let source = self.flow.compile_with(&options);
+                outcome = self.execute_measured(&source, input, &vars);
+            }
+        }
+        let duration = start.elapsed();
+
+        match &outcome {
+            Ok(json) => {
+                self.execution_log.push(format!("✓ Execution completed in {}ms", duration.as_millis()));
+
+                // Per-node timing is simulated (all nodes execute together);
+                // restored nodes didn't run, so they take no time
+                let executed = self.flow.nodes.iter().filter(|n| !options.fixed_outputs.contains_key(&n.id)).count();
+                let node_duration = duration.as_millis() as u64 / executed.max(1) as u64;
+
+                let mut timeline_offset_ms = 0u64;
+                for node in &self.flow.nodes {
+                    let duration_ms = if options.fixed_outputs.contains_key(&node.id) {
+                        0
+                    } else {
+                        node_duration
+                    };
+
+                    let output = json["outputs"].get(&node.id).cloned();
+                    let output_str = output.as_ref()
+                        .map(|v| serde_json::to_string_pretty(v).unwrap_or_default());
+                    let usage = output.as_ref().filter(|_| node.type_name == "llm_complete").and_then(crate::llm::usage);
+                    let streamed = output.as_ref().filter(|_| node.type_name == "file_stream").and_then(crate::streaming::counts);
+                    if let Some(value) = output {
+                        self.captured_outputs.insert(node.id.clone(), value);
+                    }
+
+                    if let Some(exec) = self.node_executions.get_mut(&node.id) {
+                        exec.state = ExecutionState::Completed;
+                        exec.duration_ms = Some(duration_ms);
+                        exec.set_output(output_str.clone());
+                    }
+
+                    // Completions show the tokens they used
+                    let node_name = match &usage {
+                        Some(usage) => {
+                            self.execution_log.push(format!("🤖 {}: {}", node.id, crate::llm::describe(usage)));
+                            format!("{} · {}", node.type_name, crate::llm::describe(usage))
+                        }
+                        None => node.type_name.clone(),
+                    };
+                    self.timeline_entries.push(TimelineEntry {
+                        node_id: node.id.clone(),
+                        node_name,
+                        timestamp_ms: timeline_offset_ms,
+                        duration_ms,
+                        state: ExecutionState::Completed,
+                        output: output_str,
+                    });
+
+                    if let Some((chunks, lines)) = streamed {
+                        self.execution_log.push(format!("📄 {}: {} lines in {} chunks", node.id, lines, chunks));
+                    }
+
+                    timeline_offset_ms += duration_ms;
+                }
+
+                let result_str = serde_json::to_string_pretty(&json["result"]).unwrap_or_default();
+                self.execution_log.push(format!("Result: {}", result_str));
+                self.execution_result = Some(result_str);
+                self.error_message = None;
+                self.check_assertions(json);
+            }
+            Err(e) => {
+                match Diagnostic::from_run_error(e, &self.flow, &source).filter(|d| d.node.is_some()) {
+                    // The compiler rejected one node's code; nothing ran
+                    Some(diagnostic) => self.show_diagnostic(diagnostic),
+                    None => {
+                        self.execution_log.push(format!("❌ {}", e));
+
+                        // Mark all executed nodes as error
+                        for node in &self.flow.nodes {
+                            if options.fixed_outputs.contains_key(&node.id) {
+                                continue;
                             }
-                            Err(e) => {
-                                self.error_message = Some(format!("Lowering error: {}", e));
-                                self.execution_log.push(format!("❌ Lowering error: {}", e));
+                            if let Some(exec) = self.node_executions.get_mut(&node.id) {
+                                exec.state = ExecutionState::Error(e.clone());
                             }
                         }
-                    }
-                    Err(e) => {
-                        self.error_message = Some(format!("Parse error: {}", e));
-                        self.execution_log.push(format!("❌ Parse error: {}", e));
+                        self.error_message = Some(e.clone());
                     }
                 }
             }
+        }
+
+        self.executing = false;
+
+        let node_outputs = if outcome.is_ok() {
+            self.flow.nodes.iter()
+                .filter_map(|n| self.captured_outputs.get(&n.id).map(|v| (n.id.clone(), v.clone())))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+        let outcome = outcome.map(|json| json["result"].clone());
+        self.record_run(started_at, duration.as_millis() as u64, input, outcome, node_outputs);
+    }
+
+    /// Check the flow's assertion nodes against a run's outputs, failing
+    /// the nodes whose assertion doesn't hold
+    fn check_assertions(&mut self, json: &serde_json::Value) {
+        let count = self.flow.nodes.iter()
+            .filter(|n| n.enabled && crate::testing::ASSERTION_NODES.contains(&n.type_name.as_str()))
+            .count();
+        if count == 0 {
+            return;
+        }
+
+        let outputs: BTreeMap<String, serde_json::Value> = json["outputs"].as_object()
+            .map(|o| o.clone().into_iter().collect())
+            .unwrap_or_default();
+        let failed = crate::testing::failed_assertions(&self.flow, &outputs);
+        let skipped = crate::testing::skipped_assertions(&self.flow, &outputs);
+        if !skipped.is_empty() {
+            self.execution_log.push(format!("⏭ Assertion(s) not reached: {}", skipped.join(", ")));
+        }
+        for (node_id, message) in &failed {
+            self.execution_log.push(format!("❌ {}: assertion failed: {}", node_id, message));
+            if let Some(exec) = self.node_executions.get_mut(node_id) {
+                exec.state = ExecutionState::Error(message.clone());
+            }
+        }
+        if failed.is_empty() {
+            self.execution_log.push(format!("✓ {} assertion(s) passed", count - skipped.len()));
+        } else {
+            self.error_message = Some(format!("{} of {} assertion(s) failed", failed.len(), count - skipped.len()));
+        }
+    }
+
+    /// Show a compiler error on the node it is in and in the problems list
+    fn show_diagnostic(&mut self, diagnostic: Diagnostic) {
+        self.execution_log.push(format!("❌ {}", diagnostic));
+        if let Some(node_id) = &diagnostic.node {
+            // Compiling alone leaves no execution state to mark
+            let exec = self.node_executions.entry(node_id.clone()).or_insert_with(|| {
+                NodeExecution::new(ExecutionState::Pending, None, None)
+            });
+            exec.state = ExecutionState::Error(diagnostic.to_string());
+        }
+        self.error_message = Some(diagnostic.to_string());
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Fail the nodes offline mode stopped from running, each with its own error
+    fn report_blocked(&mut self, blocked: Vec<(String, String)>) {
+        for (node_id, error) in &blocked {
+            self.execution_log.push(format!("❌ {}: {}", node_id, error));
+            if let Some(exec) = self.node_executions.get_mut(node_id) {
+                exec.state = ExecutionState::Error(error.clone());
+            }
+        }
+        self.error_message = Some(format!(
+            "Offline mode: {} node(s) need the network. Turn off ✈ Offline to run them.",
+            blocked.len()
+        ));
+    }
+
+    /// The project's variables as run variables, so `env:NAME` config
+    /// values see them, leaving out those the environment sets
+    fn project_vars(&self) -> crate::RunVars {
+        self.project.variables.iter()
+            .filter(|(name, _)| std::env::var_os(name).is_none())
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Start checking the flow's required resources, and the project's
+    /// secrets, on another thread
+    fn run_preflight(&mut self) {
+        let mut flow = self.flow.clone();
+        for name in &self.project.secrets {
+            if !flow.requires.credentials.contains(name) {
+                flow.requires.credentials.push(name.clone());
+            }
+        }
+        let vars = self.project_vars();
+        let offline = self.offline;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(crate::preflight::check(&flow, std::path::Path::new("."), &vars, offline));
+        });
+        self.preflight_job = Some(receiver);
+        self.execution_log.push("=== Preflight ===".to_string());
+    }
+
+    /// Log each preflight result
+    fn finish_preflight(&mut self, results: Vec<crate::preflight::CheckResult>) {
+        for result in &results {
+            match &result.error {
+                None => self.execution_log.push(format!("✓ {}", result.resource)),
+                Some(e) => self.execution_log.push(format!("❌ {}: {}", result.resource, e)),
+            }
+        }
+
+        let failed = results.iter().filter(|r| !r.ok()).count();
+        if failed > 0 {
+            self.error_message = Some(format!("Preflight: {} of {} required resource(s) unavailable", failed, results.len()));
+        } else {
+            self.execution_log.push(format!("✓ All {} required resource(s) available", results.len()));
+        }
+    }
+
+    /// Run variables: the project's, and those pointing the state nodes of
+    /// `source` at this flow's store; `None` after logging why the run
+    /// can't have a store
+    fn run_vars(&mut self, source: &str, options: &CompileOptions) -> Option<crate::RunVars> {
+        match crate::state_store::prepare(source, &self.flow_name, options) {
+            Ok(store) => {
+                let mut vars = self.project_vars();
+                vars.extend(store);
+                Some(vars)
+            }
             Err(e) => {
-                self.error_message = Some(format!("Failed to read compiled flow: {}", e));
-                self.execution_log.push(format!("❌ Failed to read: {}", e));
+                self.execution_log.push(format!("❌ {}", e));
+                self.error_message = Some(e);
+                None
+            }
+        }
+    }
+
+    /// Execute HLX on the selected backend, recording GPU memory use when
+    /// the run may touch the Vulkan backend
+    fn execute_measured(
+        &mut self,
+        source: &str,
+        input: &serde_json::Value,
+        vars: &crate::RunVars,
+    ) -> Result<serde_json::Value, String> {
+        if self.backend_selection == BackendType::Cpu {
+            return execute_hlx(source, input, self.backend_selection, vars);
+        }
+
+        crate::gpu_stats::begin_run();
+        let outcome = execute_hlx(source, input, self.backend_selection, vars);
+        self.gpu_memory = crate::gpu_stats::end_run();
+
+        if let Some(stats) = &self.gpu_memory {
+            self.execution_log.push(format!("GPU memory: {}", stats.summary()));
+        }
+        self.timeline.set_gpu_memory(self.gpu_memory.clone());
+        outcome
+    }
+
+    /// Persist a finished execution to the run history
+    fn record_run(
+        &mut self,
+        started_at: u64,
+        duration_ms: u64,
+        input: &serde_json::Value,
+        outcome: Result<serde_json::Value, String>,
+        node_outputs: BTreeMap<String, serde_json::Value>,
+    ) {
+        let mut record = RunRecord::new(&self.flow_name, started_at, input.clone());
+        record.finish(duration_ms, outcome);
+        record.node_outputs = node_outputs;
+        record.node_durations = self.node_executions.iter()
+            .filter_map(|(id, exec)| exec.duration_ms.map(|ms| (id.clone(), ms)))
+            .collect();
+        record.set_flow(&self.flow);
+        record.logs = self.execution_log.clone();
+        record.gpu_memory = self.gpu_memory.clone();
+
+        match self.run_store.save(&mut record) {
+            Ok(()) => self.execution_log.push(format!("Run saved as {}", record.id)),
+            Err(e) => self.execution_log.push(format!("❌ Failed to save run history: {}", e)),
+        }
+        self.history.invalidate();
+    }
+
+    /// Write a run's report to `reports/<run id>.<format>`
+    fn export_report(&mut self, run: &RunRecord, format: crate::report::ReportFormat) {
+        let path = std::path::Path::new("reports").join(format!("{}.{}", run.id, format.extension()));
+        let written = std::fs::create_dir_all("reports")
+            .and_then(|()| std::fs::write(&path, crate::report::render(run, format)));
+        match written {
+            Ok(()) => self.execution_result = Some(format!("Report written to {}", path.display())),
+            Err(e) => self.error_message = Some(format!("Failed to write report: {}", e)),
+        }
+    }
+
+    /// Load a past run's outputs into the editor so they can be inspected
+    /// and replayed from
+    pub fn restore_run(&mut self, run: RunRecord) {
+        self.clear_execution();
+        self.captured_outputs = run.node_outputs.clone().into_iter().collect();
+        self.last_input = run.input.clone();
+
+        for node in &self.flow.nodes {
+            let output = run.node_outputs.get(&node.id);
+            let state = match (&run.error, output) {
+                (_, Some(_)) => ExecutionState::Completed,
+                (Some(e), None) => ExecutionState::Error(e.clone()),
+                (None, None) => ExecutionState::Pending,
+            };
+            let output_str = output.map(|v| serde_json::to_string_pretty(v).unwrap_or_default());
+
+            self.node_executions.insert(node.id.clone(), NodeExecution::new(state.clone(), output_str.clone(), None));
+            if output.is_some() {
+                self.timeline_entries.push(TimelineEntry {
+                    node_id: node.id.clone(),
+                    node_name: node.type_name.clone(),
+                    timestamp_ms: 0,
+                    duration_ms: 0,
+                    state,
+                    output: output_str,
+                });
             }
         }
 
+        self.gpu_memory = run.gpu_memory.clone();
+        self.timeline.set_gpu_memory(run.gpu_memory.clone());
+
+        self.execution_log = run.log_lines();
+        self.execution_log.push(format!("Restored run {} from history", run.id));
+        self.execution_result = run.result.as_ref()
+            .map(|r| serde_json::to_string_pretty(r).unwrap_or_default());
+        self.error_message = run.error;
+    }
+
+    /// Deploy the flow to the server configured in the server window
+    fn deploy_remote(&mut self) {
+        if let Err(e) = self.flow.validate() {
+            self.error_message = Some(format!("Invalid flow: {}", e));
+            return;
+        }
+        let message = match self.remote.message.trim() {
+            "" => "Deployed from the editor".to_string(),
+            message => message.to_string(),
+        };
+        let job = RemoteJob::deploy(self.remote.server(), self.flow_name.clone(), self.flow.clone(), message);
+        self.execution_log.push(format!("{}…", job.label));
+        self.remote_job = Some(job);
+    }
+
+    /// Run the flow on the configured server; the run shows in the
+    /// timeline once it finishes
+    fn run_remote(&mut self) {
+        let input: serde_json::Value = serde_json::from_str(&self.remote.input).unwrap_or_default();
+        let flow = self.remote.deploy_first.then(|| self.flow.clone());
+        let job = RemoteJob::run(self.remote.server(), self.flow_name.clone(), flow, input);
+        self.clear_execution();
+        self.mark_nodes_pending();
+        self.execution_log.push(format!("{}…", job.label));
+        self.remote_job = Some(job);
+    }
+
+    fn finish_remote(&mut self, result: Result<RemoteOutcome, String>) {
+        match result {
+            Ok(RemoteOutcome::Deployed(message)) => {
+                self.execution_log.push(format!("✓ {} ({})", message, self.remote.url.trim()));
+                self.remote.message.clear();
+            }
+            Ok(RemoteOutcome::Ran(run)) => {
+                let id = run.id.clone();
+                self.restore_run(run);
+                self.execution_log.push(format!("☁ Ran remotely on {} as run {}", self.remote.url.trim(), id));
+            }
+            Err(e) => {
+                self.node_executions.clear();
+                self.execution_log.push(format!("❌ {}", e));
+                self.error_message = Some(e);
+            }
+        }
+    }
+
+    /// Execute only the ancestor subgraph of a node, stopping at that node.
+    ///
+    /// Downstream nodes are never compiled, so their side effects don't run.
+    /// The node's output is shown in the properties panel.
+    pub fn run_to_node(&mut self, node_id: &str, input: serde_json::Value) {
+        use std::time::Instant;
+
+        self.clear_execution();
+
+        let subflow = self.flow.ancestor_subgraph(node_id);
+        for node in &subflow.nodes {
+            self.node_executions.insert(node.id.clone(), NodeExecution::new(ExecutionState::Pending, None, None));
+        }
+
+        self.selected_node = Some(node_id.to_string());
+        self.execution_log.push(format!(
+            "=== Running '{}' up to {} ({} of {} nodes) ===",
+            self.flow_name, node_id, subflow.nodes.len(), self.flow.nodes.len()
+        ));
+        self.execution_log.push(format!("Backend: {}", self.backend_selection.as_str()));
+
+        let mut options = CompileOptions::default();
+        let Some(_mock_fs) = self.prepare_run_options(&subflow, &mut options) else {
+            return;
+        };
+
+        let source = subflow.compile_with(&options);
+        let Some(vars) = self.run_vars(&source, &options) else {
+            return;
+        };
+        self.executing = true;
+        self.execution_log.push("⚡ Executing partial workflow...".to_string());
+
+        let started_at = now_ms();
+        let start = Instant::now();
+        let outcome = self.execute_measured(&source, &input, &vars);
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        match &outcome {
+            Ok(json) => {
+                let result_str = serde_json::to_string_pretty(&json).unwrap_or_default();
+
+                for node in &subflow.nodes {
+                    if let Some(exec) = self.node_executions.get_mut(&node.id) {
+                        exec.state = ExecutionState::Completed;
+                    }
+                }
+                if let Some(exec) = self.node_executions.get_mut(node_id) {
+                    exec.duration_ms = Some(duration_ms);
+                    exec.set_output(Some(result_str.clone()));
+                }
+
+                self.execution_log.push(format!("✓ Partial execution completed in {}ms", duration_ms));
+                self.execution_log.push(format!("Result: {}", result_str));
+                self.execution_result = Some(result_str);
+            }
+            Err(e) => match Diagnostic::from_run_error(e, &subflow, &source).filter(|d| d.node.is_some()) {
+                Some(diagnostic) => self.show_diagnostic(diagnostic),
+                None => {
+                    self.execution_log.push(format!("❌ {}", e));
+                    for node in &subflow.nodes {
+                        if let Some(exec) = self.node_executions.get_mut(&node.id) {
+                            exec.state = ExecutionState::Error(e.clone());
+                        }
+                    }
+                    self.error_message = Some(e.clone());
+                }
+            },
+        }
+
         self.executing = false;
+
+        let mut node_outputs = BTreeMap::new();
+        if let Ok(json) = &outcome {
+            node_outputs.insert(node_id.to_string(), json.clone());
+        }
+        self.record_run(started_at, duration_ms, &input, outcome, node_outputs);
+
         self.execution_log.push("=== Execution finished ===".to_string());
     }
 
+    /// Prompt for a changelog message, then save
+    pub fn request_save(&mut self) {
+        if self.save_dialog.is_none() {
+            self.save_dialog = Some(SaveDialog::new());
+        }
+    }
+
     /// Save flow to JSON
     pub fn save_flow(&mut self) {
         let json = serde_json::to_string_pretty(&self.flow).unwrap();
@@ -382,6 +1345,64 @@ impl AutographApp {
         } else {
             self.error_message = None;
             self.execution_result = Some(format!("Saved to {}", path));
+            self.snapshot_version("save");
+        }
+    }
+
+    /// Keep a version of the flow, unless it hasn't changed since the last one
+    fn snapshot_version(&mut self, reason: &str) {
+        match self.version_store.snapshot(&self.flow_name, &self.flow, reason) {
+            Ok(true) => self.versions.invalidate(),
+            Ok(false) => {}
+            Err(e) => self.execution_log.push(format!("❌ Failed to keep a version of the flow: {}", e)),
+        }
+    }
+
+    /// Highlight the changes between two versions on the canvas
+    fn compare_versions(&mut self, base: Flow, shown: Option<Flow>, label: String) {
+        let diff = crate::versions::FlowDiff::between(&base, shown.as_ref().unwrap_or(&self.flow));
+        let summary = diff.summary();
+        self.canvas.set_diff(Some((diff, base)));
+        self.version_diff = Some(VersionDiff { shown, label, summary });
+    }
+
+    fn exit_version_diff(&mut self) {
+        self.canvas.set_diff(None);
+        self.version_diff = None;
+    }
+
+    /// Move a group's nodes into a new flow in `flows/`, named after the
+    /// group, and call it from a `subflow` node in their place
+    fn convert_group_to_subflow(&mut self, group_id: &str) {
+        let Some(group) = self.flow.groups.iter().find(|g| g.id == group_id) else {
+            return;
+        };
+        let slug: String = group.name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let base = match slug.trim_matches('_') {
+            "" => "subflow".to_string(),
+            trimmed => trimmed.to_string(),
+        };
+        let name = std::iter::once(base.clone())
+            .chain((2..).map(|i| format!("{}_{}", base, i)))
+            .find(|name| *name != self.flow_name && !std::path::Path::new(&format!("flows/{}.json", name)).exists())
+            .unwrap();
+
+        let mut flow = self.flow.clone();
+        let result = flow.extract_group(group_id, &name).and_then(|subflow| {
+            let json = serde_json::to_string_pretty(&subflow).map_err(|e| e.to_string())?;
+            std::fs::write(format!("flows/{}.json", name), json).map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => {
+                self.flow = flow;
+                self.selected_node = None;
+                self.execution_log.push(format!("✓ Moved the group into subflow flows/{}.json", name));
+            }
+            Err(e) => self.execution_log.push(format!("❌ Can't convert the group into a subflow: {}", e)),
         }
     }
 
@@ -392,8 +1413,10 @@ impl AutographApp {
             Ok(json) => {
                 match serde_json::from_str(&json) {
                     Ok(flow) => {
+                        self.exit_version_diff();
                         self.flow = flow;
                         self.flow_name = name;
+                        self.undo.clear();
                         self.error_message = None;
                         self.execution_result = Some(format!("Loaded from {}", path));
                     }
@@ -403,9 +1426,160 @@ impl AutographApp {
                 }
             }
             Err(e) => {
-                self.error_message = Some(format!("Failed to load: {}", e));
+                self.error_message = Some(format!("Failed to load: {}", e));
+            }
+        }
+    }
+
+    /// Export the flow and its subflows as a portable bundle
+    pub fn export_bundle(&mut self, path: &str, key_path: &str) {
+        let result = crate::bundle::Bundle::export(std::path::Path::new("flows"), &self.flow_name, &self.flow)
+            .and_then(|mut bundle| {
+                if !key_path.is_empty() {
+                    let key = crate::signing::PublisherKey::load(std::path::Path::new(key_path))?;
+                    bundle.sign(&key)?;
+                }
+                bundle.write(std::path::Path::new(path)).map(|()| bundle)
+            });
+
+        match result {
+            Ok(bundle) => {
+                self.error_message = None;
+                let signed = match &bundle.signature {
+                    Some(signature) => format!(", signed by '{}'", signature.publisher),
+                    None => String::new(),
+                };
+                self.execution_result = Some(format!("Exported {} flow(s) to {}{}", bundle.flows.len(), path, signed));
+            }
+            Err(e) => self.error_message = Some(format!("Export failed: {}", e)),
+        }
+    }
+
+    /// Import a bundle into the flows directory and open its root flow.
+    /// With a trust list present, only bundles signed by a trusted
+    /// publisher are accepted.
+    pub fn import_bundle(&mut self, path: &str) {
+        let result = crate::bundle::Bundle::read(std::path::Path::new(path)).and_then(|bundle| {
+            let publisher = match crate::signing::TrustedPublishers::load_default()? {
+                Some(trusted) => Some(trusted.check(bundle.signature.as_ref())?),
+                None => None,
+            };
+            Ok((bundle.import(std::path::Path::new("flows"))?, publisher))
+        });
+
+        let (report, publisher) = match result {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.error_message = Some(format!("Import failed: {}", e));
+                return;
+            }
+        };
+
+        self.selected_node = None;
+        self.clear_execution();
+        self.load_flow(report.root.clone());
+
+        if let Some(publisher) = publisher {
+            self.execution_log.push(format!("Signed by trusted publisher '{}'", publisher));
+        }
+        for (old, new) in &report.renamed {
+            self.execution_log.push(format!("'{}' already exists, imported as '{}'", old, new));
+        }
+        for name in &report.unchanged {
+            self.execution_log.push(format!("'{}' is already present and identical", name));
+        }
+        for template in &report.missing_templates {
+            self.execution_log.push(format!("❌ Template '{}' is not available in this build", template));
+        }
+    }
+
+    /// Generate palette nodes for every operation of an OpenAPI spec
+    pub fn import_openapi(&mut self, location: &str) {
+        let result = crate::openapi::generate_from(location).and_then(|api| {
+            let path = api.install(std::path::Path::new(crate::openapi::OPENAPI_NODES_DIR))?;
+            Ok((api, path))
+        });
+
+        match result {
+            Ok((api, path)) => {
+                self.error_message = None;
+                self.execution_log.push(format!(
+                    "✓ Added {} '{}' node(s) to the palette (saved to {})",
+                    api.nodes.len(),
+                    api.title,
+                    path.display()
+                ));
+                self.execution_result = Some(format!("Generated {} nodes from {}", api.nodes.len(), location));
+            }
+            Err(e) => self.error_message = Some(format!("OpenAPI import failed: {}", e)),
+        }
+    }
+
+    /// Translate a Node-RED flows.json export into a new, unsaved flow
+    pub fn import_node_red(&mut self, path: &str) {
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path, e))
+            .and_then(|json| crate::node_red::import(&json));
+
+        let imported = match result {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.error_message = Some(format!("Import failed: {}", e));
+                return;
+            }
+        };
+
+        self.flow = imported.flow;
+        self.flow_name = imported.name
+            .map(|name| name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_"))
+            .unwrap_or_else(|| "node_red_import".to_string());
+        self.selected_node = None;
+        self.clear_execution();
+
+        self.execution_log.push(format!(
+            "Imported {} node(s) and {} edge(s) from {}",
+            self.flow.nodes.len(),
+            self.flow.edges.len(),
+            path
+        ));
+        for warning in imported.warnings {
+            self.execution_log.push(format!("❌ {}", warning));
+        }
+        self.error_message = None;
+    }
+
+    /// Rebuild a flow from an `.hlxa` program as a new, unsaved flow
+    pub fn import_hlx(&mut self, path: &str) {
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path, e))
+            .and_then(|source| crate::decompile::decompile(&source));
+
+        let decompiled = match result {
+            Ok(decompiled) => decompiled,
+            Err(e) => {
+                self.error_message = Some(format!("Import failed: {}", e));
+                return;
             }
+        };
+
+        self.flow = decompiled.flow;
+        self.flow_name = std::path::Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "hlx_import".to_string());
+        self.selected_node = None;
+        self.clear_execution();
+
+        self.execution_log.push(format!(
+            "Rebuilt {} node(s) and {} edge(s) from {}",
+            self.flow.nodes.len(),
+            self.flow.edges.len(),
+            path
+        ));
+        for warning in decompiled.warnings {
+            self.execution_log.push(format!("❌ {}", warning));
         }
+        self.error_message = None;
     }
 }
 
@@ -415,12 +1589,12 @@ impl eframe::App for AutographApp {
         ctx.input(|i| {
             // Ctrl+S: Save
             if i.modifiers.ctrl && i.key_pressed(egui::Key::S) {
-                self.save_flow();
+                self.request_save();
             }
 
             // Ctrl+R: Run
             if i.modifiers.ctrl && i.key_pressed(egui::Key::R) {
-                self.run_flow(serde_json::json!(null));
+                self.start_run();
             }
 
             // Ctrl+B: Compile
@@ -428,11 +1602,19 @@ impl eframe::App for AutographApp {
                 self.compile_flow();
             }
 
-            // Ctrl+N: New
+            // Ctrl+N: New tab
             if i.modifiers.ctrl && i.key_pressed(egui::Key::N) {
-                self.flow = Flow { nodes: Vec::new(), edges: Vec::new() };
-                self.selected_node = None;
-                self.clear_execution();
+                self.new_tab();
+            }
+
+            // Ctrl+W: Close tab
+            if i.modifiers.ctrl && i.key_pressed(egui::Key::W) {
+                self.close_tab(self.active_tab);
+            }
+
+            // Ctrl+Tab: Next tab
+            if i.modifiers.ctrl && i.key_pressed(egui::Key::Tab) {
+                self.switch_tab((self.active_tab + 1) % self.tabs.len());
             }
 
             // Ctrl+K: Clear execution
@@ -442,10 +1624,53 @@ impl eframe::App for AutographApp {
 
             // F5: Run (alternative)
             if i.key_pressed(egui::Key::F5) {
-                self.run_flow(serde_json::json!(null));
+                self.start_run();
+            }
+
+            // Ctrl+0: Reset zoom to 100%
+            if i.modifiers.ctrl && i.key_pressed(egui::Key::Num0) {
+                self.canvas.reset_zoom();
             }
         });
 
+        // F: Fit all nodes, Shift+F: fit the selection, Ctrl+Z/Ctrl+Y: undo
+        // and redo, Tab: quick-add (not while typing, where text fields undo
+        // themselves)
+        let mut open_quick_add = false;
+        if !ctx.wants_keyboard_input() {
+            ctx.input(|i| {
+                if i.key_pressed(egui::Key::Tab) && !i.modifiers.ctrl {
+                    open_quick_add = true;
+                }
+                if i.modifiers.ctrl && i.key_pressed(egui::Key::Z) {
+                    if i.modifiers.shift {
+                        self.redo();
+                    } else {
+                        self.undo();
+                    }
+                }
+                if i.modifiers.ctrl && i.key_pressed(egui::Key::Y) {
+                    self.redo();
+                }
+                if i.key_pressed(egui::Key::F) && !i.modifiers.ctrl {
+                    if i.modifiers.shift {
+                        self.canvas.zoom_to_selection(&self.flow, self.selected_node.as_ref());
+                    } else {
+                        self.canvas.zoom_to_fit(&self.flow);
+                    }
+                }
+            });
+        }
+
+        // Pick up added, edited or removed plugin nodes
+        if let Some((count, errors)) = self.plugin_watcher.poll() {
+            self.execution_log.push(format!("🔌 Plugins reloaded: {} node type(s)", count));
+            for e in errors {
+                self.execution_log.push(format!("❌ Plugin: {}", e));
+            }
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs(1));
+
         // Apply theme
         ctx.set_visuals(if self.dark_mode {
             egui::Visuals::dark()
@@ -460,10 +1685,13 @@ impl eframe::App for AutographApp {
                 ui.separator();
 
                 ui.label("Flow:");
-                ui.text_edit_singleline(&mut self.flow_name);
+                if ui.button(format!("📂 {}", self.flow_name)).on_hover_text("Open, rename, duplicate or delete flows").clicked() {
+                    self.show_flows = !self.show_flows;
+                    self.flow_browser.invalidate();
+                }
 
                 if ui.button("Save").clicked() {
-                    self.save_flow();
+                    self.request_save();
                 }
 
                 if ui.button("Compile").clicked() {
@@ -471,13 +1699,22 @@ impl eframe::App for AutographApp {
                 }
 
                 if ui.button("Run").clicked() {
-                    self.run_flow(serde_json::json!(null));
+                    self.start_run();
                 }
 
-                if ui.button("New").clicked() {
-                    self.flow = Flow { nodes: Vec::new(), edges: Vec::new() };
-                    self.selected_node = None;
-                    self.clear_execution();
+                if ui.button("New").on_hover_text("Open an empty flow in a new tab (Ctrl+N)").clicked() {
+                    self.new_tab();
+                }
+
+                if ui.add_enabled(self.undo.can_undo(), egui::Button::new("↶")).on_hover_text("Undo (Ctrl+Z)").clicked() {
+                    self.undo();
+                }
+                if ui.add_enabled(self.undo.can_redo(), egui::Button::new("↷")).on_hover_text("Redo (Ctrl+Y)").clicked() {
+                    self.redo();
+                }
+
+                if ui.button("🗂 Project").on_hover_text("Open tabs, shared variables and secrets").clicked() {
+                    self.show_project = !self.show_project;
                 }
 
                 ui.separator();
@@ -488,31 +1725,37 @@ impl eframe::App for AutographApp {
 
                 ui.separator();
 
-                // Templates menu
-                ui.menu_button("Templates ▼", |ui| {
-                    use std::collections::BTreeMap;
-                    let mut categories = BTreeMap::new();
-
-                    for template in crate::templates::all_templates() {
-                        categories
-                            .entry(template.category)
-                            .or_insert_with(Vec::new)
-                            .push(template);
-                    }
-
-                    for (category, templates) in categories {
-                        ui.menu_button(category, |ui| {
-                            for template in templates {
-                                if ui.button(template.name).on_hover_text(template.description).clicked() {
-                                    self.flow = (template.create)();
-                                    self.clear_execution();
-                                    ui.close_menu();
-                                }
-                            }
-                        });
+                // Bundle export/import
+                ui.menu_button("File ▼", |ui| {
+                    if ui.button("📦 Export…").clicked() {
+                        self.bundle_dialog = Some(BundleDialog::new(BundleMode::Export, &self.flow_name));
+                        ui.close_menu();
+                    }
+                    if ui.button("📥 Import…").clicked() {
+                        self.bundle_dialog = Some(BundleDialog::new(BundleMode::Import, &self.flow_name));
+                        ui.close_menu();
+                    }
+                    if ui.button("📥 Import from Node-RED…").clicked() {
+                        self.bundle_dialog = Some(BundleDialog::new(BundleMode::ImportNodeRed, &self.flow_name));
+                        ui.close_menu();
+                    }
+                    if ui.button("📥 Import HLX Source…").clicked() {
+                        self.bundle_dialog = Some(BundleDialog::new(BundleMode::ImportHlx, &self.flow_name));
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("🔌 Nodes from OpenAPI…").clicked() {
+                        self.bundle_dialog = Some(BundleDialog::new(BundleMode::ImportOpenApi, &self.flow_name));
+                        ui.close_menu();
                     }
                 });
 
+                // Template gallery toggle
+                if ui.button("📋 Templates").on_hover_text("Start from a template, or save this flow as one").clicked() {
+                    self.show_templates = !self.show_templates;
+                    self.template_gallery.invalidate();
+                }
+
                 ui.separator();
 
                 // Backend selection
@@ -525,6 +1768,33 @@ impl eframe::App for AutographApp {
                         ui.selectable_value(&mut self.backend_selection, BackendType::Vulkan, "GPU (Vulkan)");
                     });
 
+                ui.checkbox(&mut self.offline, "✈ Offline")
+                    .on_hover_text("Block HTTP and database nodes instead of letting them reach the network");
+                ui.checkbox(&mut self.chaos, "🧪 Chaos")
+                    .on_hover_text("Inject the failures configured on nodes to test error handling and retries");
+                ui.checkbox(&mut self.mock, "🎭 Mock")
+                    .on_hover_text("Test mode: mocked nodes return their canned output, file nodes use the flow's mock files");
+                if ui.add_enabled(self.preflight_job.is_none(), egui::Button::new("🔍 Preflight"))
+                    .on_hover_text("Check the env vars, credentials, files and hosts the flow requires")
+                    .clicked()
+                {
+                    self.run_preflight();
+                }
+
+                ui.separator();
+
+                // RNG seed for reproducible random nodes
+                let mut seeded = self.flow.seed.is_some();
+                if ui.checkbox(&mut seeded, "Seed")
+                    .on_hover_text("Make random nodes reproducible across runs and replays")
+                    .changed()
+                {
+                    self.flow.seed = if seeded { Some(0) } else { None };
+                }
+                if let Some(seed) = &mut self.flow.seed {
+                    ui.add(egui::DragValue::new(seed));
+                }
+
                 ui.separator();
 
                 // Theme toggle
@@ -532,12 +1802,83 @@ impl eframe::App for AutographApp {
                     self.dark_mode = !self.dark_mode;
                 }
 
+                // View controls
+                if ui.button("⛶ Fit").on_hover_text("Zoom to fit all nodes (F)").clicked() {
+                    self.canvas.zoom_to_fit(&self.flow);
+                }
+                if ui.button("🎯 Selection").on_hover_text("Zoom to the selection (Shift+F)").clicked() {
+                    self.canvas.zoom_to_selection(&self.flow, self.selected_node.as_ref());
+                }
+                if ui.button("100%").on_hover_text("Reset zoom (Ctrl+0)").clicked() {
+                    self.canvas.reset_zoom();
+                }
+
                 // Mini-map toggle
                 if ui.button(if self.show_minimap { "🗺 Hide Map" } else { "🗺 Show Map" }).clicked() {
                     self.show_minimap = !self.show_minimap;
                 }
+
+                // Run history toggle
+                if ui.button("📜 History").clicked() {
+                    self.show_history = !self.show_history;
+                    self.history.invalidate();
+                }
+
+                // Flow versions toggle
+                if ui.button("🕘 Versions").clicked() {
+                    self.show_versions = !self.show_versions;
+                    self.versions.invalidate();
+                }
+
+                // Value inspector toggle
+                if ui.button("🔍 Inspector").on_hover_text("Input and output of the selected node").clicked() {
+                    self.show_inspector = !self.show_inspector;
+                }
+
+                // Server window toggle
+                if ui.button("🌐 Server").on_hover_text("Deploy to and run on an Autograph server").clicked() {
+                    self.show_remote = !self.show_remote;
+                }
+
+                // Flow metadata toggle
+                if ui.button("ℹ Info").clicked() {
+                    self.show_metadata = !self.show_metadata;
+                }
+
+                // Flow statistics toggle, flagged when the flow is getting too complex
+                let complex = !crate::complexity::GraphMetrics::of(&self.flow).warnings().is_empty();
+                if ui.button(if complex { "📊 Stats ⚠" } else { "📊 Stats" }).clicked() {
+                    self.show_stats = !self.show_stats;
+                }
+            });
+        });
+
+        // Tabs of the open flows
+        let mut switch_to = None;
+        let mut close = None;
+        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for index in 0..self.tabs.len() {
+                    let name = self.tab_name(index).to_string();
+                    if ui.selectable_label(index == self.active_tab, format!("📄 {}", name)).clicked() {
+                        switch_to = Some(index);
+                    }
+                    if ui.small_button("✖").on_hover_text("Close tab (Ctrl+W)").clicked() {
+                        close = Some(index);
+                    }
+                    ui.separator();
+                }
+                if ui.small_button("➕").on_hover_text("New tab (Ctrl+N)").clicked() {
+                    self.new_tab();
+                }
             });
         });
+        if let Some(index) = switch_to {
+            self.switch_tab(index);
+        }
+        if let Some(index) = close {
+            self.close_tab(index);
+        }
 
         // Node palette (left side) - top half
         egui::SidePanel::left("palette").min_width(200.0).max_width(250.0).show(ctx, |ui| {
@@ -556,13 +1897,15 @@ impl eframe::App for AutographApp {
             ui.push_id("timeline_section", |ui| {
                 ui.set_max_height(total_height * 0.5);
                 let mut clicked_entry = None;
-                self.timeline.show(ui, &self.timeline_entries, &mut clicked_entry);
+                let mut replay_entry = None;
+                self.timeline.show(ui, &self.timeline_entries, &mut clicked_entry, &mut replay_entry);
 
                 if let Some(idx) = clicked_entry {
-                    if let Some(entry) = self.timeline_entries.get(idx) {
-                        // Select the node on canvas
-                        self.selected_node = Some(entry.node_id.clone());
-                    }
+                    self.travel_to(Some(idx));
+                }
+
+                if let Some(idx) = replay_entry {
+                    self.replay_from(idx);
                 }
             });
         });
@@ -578,16 +1921,51 @@ impl eframe::App for AutographApp {
             );
         });
 
-        if delete_requested {
-            self.delete_selected_node();
+        if delete_requested && self.version_diff.is_none() {
+            self.request_delete_selected_node();
         }
 
         // Bottom panel for results/errors
+        let mut focus_node = None;
         egui::TopBottomPanel::bottom("output").min_height(200.0).show(ctx, |ui| {
             ui.heading("Output");
             ui.separator();
 
             egui::ScrollArea::vertical().show(ui, |ui| {
+                // Problems found before running and compiler errors, click
+                // one to select its node
+                let mismatches = self.flow.type_mismatches();
+                let expression_errors = self.expression_errors.get(self.undo.revision(), || self.flow.expression_errors()).clone();
+                let count = mismatches.len() + expression_errors.len() + self.diagnostics.len();
+                if count > 0 {
+                    egui::CollapsingHeader::new(format!("⚠ Problems ({})", count))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for (node_id, error) in &expression_errors {
+                                let text = egui::RichText::new(format!("❌ {}: invalid expression in {}", node_id, error))
+                                    .color(egui::Color32::RED);
+                                if ui.add(egui::Label::new(text).sense(egui::Sense::click())).clicked() {
+                                    self.selected_node = Some(node_id.clone());
+                                }
+                            }
+                            for diagnostic in &self.diagnostics {
+                                let text = egui::RichText::new(format!("❌ {}", diagnostic)).color(egui::Color32::RED);
+                                let label = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                                if let Some(node_id) = diagnostic.node.as_ref().filter(|_| label.clicked()) {
+                                    focus_node = Some(node_id.clone());
+                                }
+                            }
+                            for mismatch in &mismatches {
+                                let text = egui::RichText::new(format!("⚠ Type mismatch: {}", mismatch))
+                                    .color(egui::Color32::from_rgb(230, 140, 40));
+                                if ui.add(egui::Label::new(text).sense(egui::Sense::click())).clicked() {
+                                    self.selected_node = Some(mismatch.target.clone());
+                                }
+                            }
+                        });
+                    ui.separator();
+                }
+
                 // Execution log section
                 ui.collapsing("Execution Log", |ui| {
                     if self.execution_log.is_empty() {
@@ -608,6 +1986,14 @@ impl eframe::App for AutographApp {
                     }
                 });
 
+                // Compiled HLX, each line tied to its node
+                egui::CollapsingHeader::new("📜 Generated Code").show(ui, |ui| {
+                    // Keep a focus request made elsewhere this frame
+                    if let Some(id) = self.generated_code.show(ui, &self.flow, self.undo.revision(), self.selected_node.as_deref()) {
+                        focus_node = Some(id);
+                    }
+                });
+
                 ui.separator();
 
                 // Error section
@@ -619,15 +2005,472 @@ impl eframe::App for AutographApp {
                 // Result section
                 if let Some(result) = &self.execution_result {
                     ui.label("Result:");
-                    ui.monospace(result);
+                    json_tree::show_text(ui, "execution_result", result);
                 }
             });
         });
 
+        if let Some(node_id) = focus_node {
+            self.canvas.center_on_node(&self.flow, &node_id);
+            self.selected_node = Some(node_id);
+        }
+
         // Central canvas
+        let mut run_to_node = None;
+        let mut exit_diff = false;
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.canvas.show(ui, &mut self.flow, &mut self.selected_node, &self.node_executions);
+            if let Some(diff) = &self.version_diff {
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::from_rgb(230, 140, 40), format!("🕘 Comparing {}: {}", diff.label, diff.summary));
+                    ui.weak("green added, orange changed, red removed");
+                    if ui.button("✖ Exit comparison").clicked() {
+                        exit_diff = true;
+                    }
+                });
+            }
+
+            // While time-travelling, draw the rewound states instead of the live ones
+            let executions = self.moment.as_ref().map_or(&self.node_executions, |m| &m.states);
+            match self.version_diff.as_ref().and_then(|d| d.shown.clone()) {
+                // An older version is shown read-only: edits to it are dropped
+                Some(mut shown) => {
+                    self.canvas.show(ui, &mut shown, &mut self.selected_node, executions);
+                }
+                None => run_to_node = self.canvas.show(ui, &mut self.flow, &mut self.selected_node, executions),
+            }
+        });
+        if exit_diff {
+            self.exit_version_diff();
+        }
+
+        // With the input of the last full run, so upstream nodes see what they'd see there
+        if let Some(node_id) = run_to_node {
+            self.run_to_node(&node_id, self.last_input.clone());
+        }
+
+        // Requests made on an older version shown read-only are dropped, not
+        // applied to the live flow
+        let comparing = self.version_diff.is_some();
+        if self.canvas.take_delete_request() && !comparing {
+            self.request_delete_selected_node();
+        }
+
+        if let Some(group_id) = self.canvas.take_subflow_request().filter(|_| !comparing) {
+            self.convert_group_to_subflow(&group_id);
+        }
+
+        if let Some(node_id) = self.canvas.take_pin_request().filter(|_| !comparing) {
+            self.pin_output(&node_id);
+        }
+
+        // Quick-add, from Tab at the pointer or a double-click on the canvas;
+        // not while an older version is shown read-only
+        let quick_add_at = self.canvas.take_quick_add_request()
+            .or_else(|| open_quick_add.then(|| self.canvas.quick_add_pos(ctx.pointer_latest_pos())));
+        if let Some(screen_pos) = quick_add_at.filter(|_| self.version_diff.is_none()) {
+            self.quick_add = Some(QuickAdd::new(screen_pos, self.canvas.canvas_pos(screen_pos)));
+        }
+        let quick_add_action = self.quick_add.as_mut().map(|quick_add| (quick_add.show(ctx), quick_add.position));
+        match quick_add_action {
+            Some((QuickAddAction::Insert(type_name), position)) => {
+                // Connected after the selected node, which the new one replaces
+                let previous = self.selected_node.clone().filter(|_| crate::nodes::has_input(&type_name));
+                let id = self.flow.next_node_id();
+                // Configured like a node added from the palette
+                let config = crate::nodes::default_config(&type_name);
+                self.flow.nodes.push(Node { id: id.clone(), type_name, config, position: Some(position), ..Default::default() });
+                if let Some(source) = previous {
+                    self.add_edge(source, id.clone());
+                }
+                self.selected_node = Some(id);
+                self.quick_add = None;
+            }
+            Some((QuickAddAction::Close, _)) => self.quick_add = None,
+            _ => {}
+        }
+
+        // Delete confirmation, listing what the delete would break
+        if let Some((node_id, impact)) = self.pending_delete.clone() {
+            let mut confirmed = None;
+            egui::Window::new("🗑 Delete Node")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(format!("Deleting {} affects:", node_id));
+                    for line in &impact {
+                        ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", line));
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Delete").clicked() {
+                            confirmed = Some(true);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            confirmed = Some(false);
+                        }
+                    });
+                });
+            if let Some(confirmed) = confirmed {
+                if confirmed {
+                    self.delete_node(&node_id);
+                }
+                self.pending_delete = None;
+            }
+        }
+
+        // Bundle export/import prompt
+        if let Some(dialog) = &mut self.bundle_dialog {
+            match dialog.show(ctx) {
+                BundleDialogAction::Confirm => {
+                    let path = dialog.path.trim().to_string();
+                    let key_path = dialog.key_path.trim().to_string();
+                    let mode = dialog.mode;
+                    self.bundle_dialog = None;
+                    match mode {
+                        BundleMode::Export => self.export_bundle(&path, &key_path),
+                        BundleMode::Import => self.import_bundle(&path),
+                        BundleMode::ImportNodeRed => self.import_node_red(&path),
+                        BundleMode::ImportOpenApi => self.import_openapi(&path),
+                        BundleMode::ImportHlx => self.import_hlx(&path),
+                    }
+                }
+                BundleDialogAction::Cancel => self.bundle_dialog = None,
+                BundleDialogAction::None => {}
+            }
+        }
+
+        // Run comparison
+        if let Some(compare) = &mut self.run_compare {
+            if !compare.show(ctx) {
+                self.run_compare = None;
+            }
+        }
+
+        // Run input form
+        if let Some(form) = &mut self.run_form {
+            match form.show(ctx) {
+                RunFormAction::Run(input) => {
+                    self.run_form = None;
+                    self.run_flow(input);
+                }
+                RunFormAction::Cancel => self.run_form = None,
+                RunFormAction::None => {}
+            }
+        }
+
+        // Save prompt
+        if let Some(dialog) = &mut self.save_dialog {
+            match dialog.show(ctx) {
+                SaveDialogAction::Save => {
+                    let message = dialog.message.trim().to_string();
+                    let author = dialog.author.trim().to_string();
+                    self.save_dialog = None;
+                    if !message.is_empty() {
+                        self.flow.log_change(&author, &message);
+                    }
+                    self.save_flow();
+                }
+                SaveDialogAction::Cancel => self.save_dialog = None,
+                SaveDialogAction::None => {}
+            }
+        }
+
+        // Template gallery window
+        if self.show_templates {
+            let mut action = GalleryAction::None;
+            let mut open = self.show_templates;
+            egui::Window::new("📋 Templates")
+                .open(&mut open)
+                .default_size([620.0, 440.0])
+                .show(ctx, |ui| {
+                    action = self.template_gallery.show(ui);
+                });
+            self.show_templates = open;
+
+            match action {
+                GalleryAction::None => {}
+                GalleryAction::Use(flow) => {
+                    self.flow = flow;
+                    self.selected_node = None;
+                    self.clear_execution();
+                    self.show_templates = false;
+                }
+                GalleryAction::SaveCurrent => {
+                    self.template_dialog = Some(TemplateDialog::new(&self.flow_name, &self.flow));
+                }
+            }
+        }
+
+        // Save-as-template prompt
+        if let Some(dialog) = &mut self.template_dialog {
+            match dialog.show(ctx) {
+                TemplateDialogAction::Save => {
+                    let template = crate::templates::UserTemplate {
+                        name: dialog.name.clone(),
+                        description: dialog.description.trim().to_string(),
+                        category: dialog.category.trim().to_string(),
+                        params: dialog.params(),
+                        flow: self.flow.clone(),
+                    };
+                    match crate::templates::save_user_template(std::path::Path::new(crate::templates::USER_TEMPLATES_DIR), template) {
+                        Ok(()) => {
+                            self.execution_log.push(format!("✓ Saved template '{}'", dialog.name.trim()));
+                            self.template_dialog = None;
+                            self.template_gallery.invalidate();
+                        }
+                        Err(e) => dialog.error = Some(e),
+                    }
+                }
+                TemplateDialogAction::Cancel => self.template_dialog = None,
+                TemplateDialogAction::None => {}
+            }
+        }
+
+        // Flow metadata window
+        if self.show_metadata {
+            let mut open = self.show_metadata;
+            egui::Window::new("ℹ Flow Info")
+                .open(&mut open)
+                .default_size([350.0, 400.0])
+                .show(ctx, |ui| {
+                    metadata::show_metadata(ui, &self.flow_name, &mut self.flow);
+                });
+            self.show_metadata = open;
+        }
+
+        // Flow statistics window
+        if self.show_stats {
+            let mut open = self.show_stats;
+            let metrics = crate::complexity::GraphMetrics::of(&self.flow);
+            egui::Window::new("📊 Flow Statistics")
+                .open(&mut open)
+                .default_size([320.0, 300.0])
+                .show(ctx, |ui| {
+                    stats::show_stats(ui, &metrics);
+                });
+            self.show_stats = open;
+        }
+
+        // Run history window
+        if self.show_history {
+            let mut action = HistoryAction::None;
+            let mut open = self.show_history;
+            egui::Window::new("📜 Run History")
+                .open(&mut open)
+                .default_size([400.0, 500.0])
+                .show(ctx, |ui| {
+                    action = self.history.show(ui, &self.flow_name, &self.run_store);
+                });
+            self.show_history = open;
+
+            match action {
+                HistoryAction::None => {}
+                HistoryAction::Restore(run) => self.restore_run(run),
+                HistoryAction::ExportReport(run, format) => self.export_report(&run, format),
+                HistoryAction::Compare(a, b) => self.run_compare = Some(RunCompare::new(a, b)),
+            }
+        }
+
+        // Flow browser window
+        if self.show_flows {
+            let mut action = FlowBrowserAction::None;
+            let mut open = self.show_flows;
+            egui::Window::new("📂 Flows")
+                .open(&mut open)
+                .default_size([420.0, 480.0])
+                .show(ctx, |ui| {
+                    action = self.flow_browser.show(ui, std::path::Path::new("flows"), &self.flow_name, &self.run_store);
+                });
+            self.show_flows = open;
+
+            match action {
+                FlowBrowserAction::None => {}
+                FlowBrowserAction::Open(name) => self.open_tab(name),
+                FlowBrowserAction::SaveAs(name) => {
+                    self.flow_name = name;
+                    self.request_save();
+                }
+                FlowBrowserAction::Renamed { from, to, callers } => {
+                    if self.flow_name == from {
+                        self.flow_name = to.clone();
+                    }
+                    // Open flows calling it would save the old name back
+                    crate::impact::rename_subflow_calls(&mut self.flow, &from, &to);
+                    for tab in &mut self.tabs {
+                        if tab.flow_name == from {
+                            tab.flow_name = to.clone();
+                        }
+                        crate::impact::rename_subflow_calls(&mut tab.flow, &from, &to);
+                    }
+                    if !callers.is_empty() {
+                        self.execution_log.push(format!("Renamed subflow calls to {} in: {}", to, callers.join(", ")));
+                    }
+                }
+                FlowBrowserAction::Deleted(name) => {
+                    if self.flow_name == name {
+                        self.execution_log.push(format!("Deleted flows/{}.json; the flow stays open until you close it, save to keep it", name));
+                    }
+                }
+            }
+        }
+
+        // Server window
+        if self.show_remote {
+            let mut action = RemoteAction::None;
+            let mut open = self.show_remote;
+            egui::Window::new("🌐 Server")
+                .open(&mut open)
+                .default_size([340.0, 360.0])
+                .show(ctx, |ui| {
+                    action = self.remote.show(ui, &self.flow_name, self.remote_job.as_ref());
+                });
+            self.show_remote = open;
+
+            match action {
+                RemoteAction::None => {}
+                RemoteAction::Deploy => self.deploy_remote(),
+                RemoteAction::Run => self.run_remote(),
+            }
+        }
+
+        // Finished deploy or remote run
+        if let Some(job) = &self.remote_job {
+            match job.poll() {
+                None => ctx.request_repaint_after(std::time::Duration::from_millis(200)),
+                Some(result) => {
+                    self.remote_job = None;
+                    self.finish_remote(result);
+                }
+            }
+        }
+
+        // Finished preflight
+        if let Some(job) = &self.preflight_job {
+            match job.try_recv() {
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint_after(std::time::Duration::from_millis(200));
+                }
+                Ok(results) => {
+                    self.preflight_job = None;
+                    self.finish_preflight(results);
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.preflight_job = None;
+                    self.error_message = Some("Preflight stopped unexpectedly".to_string());
+                }
+            }
+        }
+
+        // Project window
+        if self.show_project {
+            let mut action = ProjectAction::None;
+            let mut open = self.show_project;
+            egui::Window::new("🗂 Project")
+                .open(&mut open)
+                .default_size([380.0, 400.0])
+                .show(ctx, |ui| {
+                    action = self.project_panel.show(ui, &mut self.project, crate::project::PROJECT_FILE);
+                });
+            self.show_project = open;
+
+            match action {
+                ProjectAction::None => {}
+                ProjectAction::ProfilesChanged => crate::http_auth::set_profiles(&self.project.auth_profiles),
+                ProjectAction::Save => self.save_project(),
+                ProjectAction::Open => self.open_project(),
+            }
+        }
+
+        // Flow versions window
+        if self.show_versions {
+            let mut action = VersionsAction::None;
+            let mut open = self.show_versions;
+            egui::Window::new("🕘 Versions")
+                .open(&mut open)
+                .default_size([420.0, 400.0])
+                .show(ctx, |ui| {
+                    action = self.versions.show(ui, &self.flow_name, &self.version_store);
+                });
+            self.show_versions = open;
+
+            match action {
+                VersionsAction::None => {}
+                VersionsAction::Restore(flow) => {
+                    self.exit_version_diff();
+                    self.flow = flow;
+                    self.selected_node = None;
+                    self.clear_execution();
+                    self.execution_log.push("Restored an earlier version; save to keep it".to_string());
+                }
+                VersionsAction::Compare { base, shown, label } => self.compare_versions(base, shown, label),
+            }
+        }
+
+        // Time-travel inspector
+        if let Some(moment) = &self.moment {
+            let mut action = InspectorAction::None;
+            let mut open = true;
+            egui::Window::new("🕰 Time Travel")
+                .open(&mut open)
+                .default_size([600.0, 400.0])
+                .show(ctx, |ui| {
+                    action = inspector::show(
+                        ui,
+                        &self.flow,
+                        moment,
+                        self.timeline_entries.len(),
+                        &self.captured_outputs,
+                        &self.last_input,
+                    );
+                });
+
+            match action {
+                InspectorAction::Step(idx) => self.travel_to(Some(idx)),
+                InspectorAction::Exit => self.travel_to(None),
+                InspectorAction::None if !open => self.travel_to(None),
+                InspectorAction::None => {}
+            }
+        }
+
+        // Value inspector for the selected node
+        if self.show_inspector {
+            let mut action = InspectorAction::None;
+            let mut open = self.show_inspector;
+            egui::Window::new("🔍 Inspector")
+                .open(&mut open)
+                .default_size([600.0, 450.0])
+                .show(ctx, |ui| {
+                    action = inspector::show_node(
+                        ui,
+                        &self.flow,
+                        self.selected_node.as_deref(),
+                        self.moment.as_ref(),
+                        &self.timeline_entries,
+                        &self.captured_outputs,
+                        &self.last_input,
+                    );
+                });
+            self.show_inspector = open;
+
+            match action {
+                InspectorAction::Step(idx) => self.travel_to(Some(idx)),
+                InspectorAction::Exit => self.travel_to(None),
+                InspectorAction::None => {}
+            }
+        }
+
+        // A new undo step once an edit is done; a drag only counts on release.
+        // Only input other than bare pointer movement can have edited the flow.
+        let edited = ctx.input(|i| {
+            i.events.iter().any(|e| !matches!(e, egui::Event::PointerMoved(_) | egui::Event::MouseMoved(_)))
         });
+        if edited {
+            self.undo.mark_dirty();
+        }
+        if !ctx.input(|i| i.pointer.any_down()) && self.undo.record(&self.flow) {
+            // Values cached by revision catch up on the next frame
+            ctx.request_repaint();
+        }
 
         // Mini-map overlay
         if self.show_minimap && !self.flow.nodes.is_empty() {
@@ -662,7 +2505,7 @@ impl eframe::App for AutographApp {
 
                     let (response, painter) = ui.allocate_painter(
                         egui::Vec2::new(minimap_size, minimap_size),
-                        egui::Sense::hover()
+                        egui::Sense::click_and_drag()
                     );
 
                     let minimap_rect = response.rect;
@@ -687,7 +2530,13 @@ impl eframe::App for AutographApp {
                                 egui::vec2(minimap_w.max(3.0), minimap_h.max(3.0))
                             );
 
-                            // Color based on execution state
+                            // The node's color tag, or its group's
+                            let tag = node.color.as_deref()
+                                .or_else(|| self.flow.groups.iter().find(|g| g.nodes.contains(&node.id))?.color.as_deref())
+                                .and_then(crate::nodes::parse_color)
+                                .map(|[r, g, b]| egui::Color32::from_rgb(r, g, b));
+
+                            // Color based on execution state, outlined in the tag color
                             let color = if let Some(exec) = self.node_executions.get(&node.id) {
                                 match &exec.state {
                                     ExecutionState::Completed => egui::Color32::from_rgb(0, 150, 0),
@@ -696,39 +2545,89 @@ impl eframe::App for AutographApp {
                                     ExecutionState::Pending => egui::Color32::from_rgb(80, 80, 80),
                                 }
                             } else {
-                                egui::Color32::from_rgb(100, 100, 100)
+                                tag.unwrap_or(egui::Color32::from_rgb(100, 100, 100))
                             };
 
                             painter.rect_filled(node_rect, 1.0, color);
+                            if let Some(tag) = tag.filter(|tag| *tag != color) {
+                                painter.rect_stroke(node_rect, 1.0, egui::Stroke::new(1.0, tag));
+                            }
                         }
                     }
 
                     // Draw viewport indicator (current view)
-                    let canvas_offset = self.canvas.offset();
-                    let viewport_scale = 1.0 / self.canvas.zoom();
-                    let viewport_width = 800.0 * viewport_scale;  // Approximate canvas width
-                    let viewport_height = 600.0 * viewport_scale;
-
-                    let viewport_x = minimap_rect.min.x + (canvas_offset.x - min_x) * scale;
-                    let viewport_y = minimap_rect.min.y + (canvas_offset.y - min_y) * scale;
-                    let viewport_w = viewport_width * scale;
-                    let viewport_h = viewport_height * scale;
-
-                    let viewport_rect = egui::Rect::from_min_size(
-                        egui::pos2(viewport_x, viewport_y),
-                        egui::vec2(viewport_w, viewport_h)
+                    let to_minimap = |p: egui::Pos2| egui::pos2(
+                        minimap_rect.min.x + (p.x - min_x) * scale,
+                        minimap_rect.min.y + (p.y - min_y) * scale,
                     );
+                    let visible = self.canvas.visible_area();
+                    let viewport_rect = egui::Rect::from_min_max(to_minimap(visible.min), to_minimap(visible.max));
 
                     painter.rect_stroke(
                         viewport_rect,
                         0.0,
                         egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 150, 255))
                     );
+
+                    // Drag the viewport rectangle to pan; click or drag elsewhere to jump there
+                    let grabbed_id = response.id.with("viewport_grabbed");
+                    if response.drag_started() {
+                        let grabbed = response.interact_pointer_pos().is_some_and(|p| viewport_rect.contains(p));
+                        ui.memory_mut(|m| m.data.insert_temp(grabbed_id, grabbed));
+                    }
+                    let grabbed = ui.memory(|m| m.data.get_temp::<bool>(grabbed_id)).unwrap_or(false);
+                    if response.dragged() && grabbed {
+                        self.canvas.pan_by(response.drag_delta() / scale);
+                    } else if response.clicked() || response.dragged() {
+                        if let Some(pointer) = response.interact_pointer_pos() {
+                            let target = (pointer - minimap_rect.min) / scale;
+                            self.canvas.center_on(egui::pos2(min_x + target.x, min_y + target.y));
+                        }
+                    }
+                    if response.hovered() {
+                        ui.ctx().set_cursor_icon(if grabbed && response.dragged() {
+                            egui::CursorIcon::Grabbing
+                        } else {
+                            egui::CursorIcon::PointingHand
+                        });
+                    }
                 });
         }
     }
 }
 
+/// Parse, lower and execute HLX source with run variables, returning the
+/// result as JSON
+fn execute_hlx(
+    source: &str,
+    input: &serde_json::Value,
+    backend: BackendType,
+    vars: &crate::RunVars,
+) -> Result<serde_json::Value, String> {
+    use hlx_compiler::hlxa::HlxaParser;
+    use hlx_compiler::parser::Parser;
+    use hlx_compiler::lower::lower_to_crate;
+    use hlx_runtime::config::RuntimeConfig;
+    use hlx_runtime::execute_with_config;
+
+    let program = HlxaParser.parse(source).map_err(|e| format!("Parse error: {}", e))?;
+    let krate = lower_to_crate(&program).map_err(|e| format!("Lowering error: {}", e))?;
+
+    crate::state_store::require(source, vars)?;
+    let tokens = crate::http_auth::refresh_tokens(source, vars)?;
+    let _slots = crate::throttle::acquire(source);
+
+    let mut config = RuntimeConfig::default();
+    config.main_input = Some(input.to_string());
+    config.backend = backend.to_runtime_backend();
+    crate::db_pool::attach(&mut config);
+    config.env.extend(vars.clone());
+    config.env.extend(tokens);
+
+    let result = execute_with_config(&krate, &config).map_err(|e| format!("Runtime error: {}", e))?;
+    result.to_json().map_err(|e| format!("JSON conversion error: {}", e))
+}
+
 /// Launch the Autograph UI
 pub fn run() -> eframe::Result<()> {
     let options = eframe::NativeOptions {
@@ -0,0 +1,99 @@
+//! Bundle Export/Import Dialog
+//!
+//! Asks for the `.autograph` file to export the current flow to or to
+//! import flows from, for a Node-RED `flows.json` to translate, for an
+//! OpenAPI spec to generate nodes from, or for an `.hlxa` program to
+//! turn back into a flow.
+
+use eframe::egui;
+
+/// Which way the bundle is going
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleMode {
+    Export,
+    Import,
+    /// Translate a Node-RED `flows.json` export
+    ImportNodeRed,
+    /// Generate HTTP nodes from an OpenAPI/Swagger spec
+    ImportOpenApi,
+    /// Rebuild a flow from an `.hlxa` program
+    ImportHlx,
+}
+
+/// Path prompt for exporting or importing a bundle
+pub struct BundleDialog {
+    pub mode: BundleMode,
+    pub path: String,
+    /// Key file to sign exports with; empty exports unsigned
+    pub key_path: String,
+}
+
+/// Outcome of the prompt for this frame
+pub enum BundleDialogAction {
+    None,
+    Confirm,
+    Cancel,
+}
+
+impl BundleDialog {
+    pub fn new(mode: BundleMode, flow_name: &str) -> Self {
+        let path = match mode {
+            BundleMode::Export => format!("{}.{}", flow_name, crate::bundle::BUNDLE_EXTENSION),
+            BundleMode::Import => String::new(),
+            BundleMode::ImportNodeRed => "flows.json".to_string(),
+            BundleMode::ImportOpenApi => "https://".to_string(),
+            BundleMode::ImportHlx => format!("{}.hlxa", flow_name),
+        };
+        Self { mode, path, key_path: String::new() }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> BundleDialogAction {
+        let mut action = BundleDialogAction::None;
+
+        let (title, button) = match self.mode {
+            BundleMode::Export => ("📦 Export Flow", "Export"),
+            BundleMode::Import => ("📥 Import Flow", "Import"),
+            BundleMode::ImportNodeRed => ("📥 Import from Node-RED", "Import"),
+            BundleMode::ImportOpenApi => ("🔌 Nodes from OpenAPI", "Generate"),
+            BundleMode::ImportHlx => ("📥 Import HLX Source", "Import"),
+        };
+
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(match self.mode {
+                    BundleMode::Export => "Packages the flow, its subflows and a manifest into one file.",
+                    BundleMode::Import => "Flows whose names are taken are imported under a new name.",
+                    BundleMode::ImportNodeRed => "Opens the translated flow in the editor; save it to keep it.",
+                    BundleMode::ImportOpenApi => "Spec URL or file. One node per operation is added to the palette.",
+                    BundleMode::ImportHlx => "Rebuilds the flow from a compiled program; hand-edited nodes become hlx_script nodes.",
+                });
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(300.0));
+                });
+                if self.mode == BundleMode::Export {
+                    ui.horizontal(|ui| {
+                        ui.label("Sign with:");
+                        ui.add(egui::TextEdit::singleline(&mut self.key_path)
+                            .hint_text("key file (optional)")
+                            .desired_width(270.0));
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    let ready = !self.path.trim().is_empty();
+                    if ui.add_enabled(ready, egui::Button::new(button)).clicked() {
+                        action = BundleDialogAction::Confirm;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = BundleDialogAction::Cancel;
+                    }
+                });
+            });
+
+        action
+    }
+}
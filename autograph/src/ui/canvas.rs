@@ -1,6 +1,9 @@
 //! Canvas for drawing and manipulating flow nodes and edges
 
 use eframe::egui;
+use super::notes::{self, NoteEditorAction};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
 /// Canvas state and rendering
 #[derive(Default)]
@@ -14,11 +17,174 @@ pub struct Canvas {
     /// Node being dragged
     dragging_node: Option<String>,
 
-    /// Edge being drawn (from node_id)
+    /// Edge being dragged out of a node's output handle (from node_id)
     drawing_edge: Option<String>,
 
     /// Mouse position for edge preview
     mouse_pos: egui::Pos2,
+
+    /// Delete key pressed with a node selected
+    delete_requested: bool,
+
+    /// Note open in the note editor
+    editing_note: Option<String>,
+
+    /// Where the canvas context menu was opened
+    menu_pos: egui::Pos2,
+
+    /// Nodes picked with Shift+Click, to be grouped
+    group_selection: Vec<String>,
+
+    /// Group the user asked to turn into a subflow
+    subflow_request: Option<String>,
+
+    /// Node whose last output the user asked to pin
+    pin_request: Option<String>,
+
+    /// Screen position of a double-click on empty canvas, to open quick-add at
+    quick_add_request: Option<egui::Pos2>,
+
+    /// Node deselected by the last click on empty canvas, reselected if it
+    /// was the first half of a double-click
+    deselected: Option<String>,
+
+    /// Edge clicked on
+    selected_edge: Option<EdgeKey>,
+
+    /// End of the selected edge being dragged to another node (true for its target end)
+    rewiring: Option<(EdgeKey, bool)>,
+
+    /// Screen area the canvas was last drawn in, for framing nodes
+    view_rect: egui::Rect,
+
+    /// Label every edge with the value that crossed it in the last run
+    pin_edge_values: bool,
+
+    /// Changes from an older version to highlight, with that version
+    diff: Option<(crate::versions::FlowDiff, crate::flow::Flow)>,
+
+    /// Curve of each edge in canvas space, kept while its ends stay put
+    edge_cache: HashMap<EdgeKey, CachedEdge>,
+
+    /// Type mismatches with the fingerprint of the graph they were found in
+    mismatch_cache: Option<(u64, Vec<crate::flow::TypeMismatch>)>,
+
+    /// Edge style of the flow being drawn
+    edge_style: crate::flow::EdgeStyle,
+}
+
+/// An edge's curve, computed once for where its ends were
+struct CachedEdge {
+    start: egui::Pos2,
+    end: egui::Pos2,
+    style: crate::flow::EdgeStyle,
+    points: Vec<egui::Pos2>,
+}
+
+/// Where an edge was drawn this frame, for hit-testing
+struct EdgePath {
+    index: usize,
+    /// Screen-space points along the curve
+    points: Vec<egui::Pos2>,
+    bounds: egui::Rect,
+}
+
+impl EdgePath {
+    fn start(&self) -> egui::Pos2 {
+        self.points[0]
+    }
+
+    fn end(&self) -> egui::Pos2 {
+        self.points[self.points.len() - 1]
+    }
+
+    /// Halfway along the curve; straight edges have just their two ends
+    fn middle(&self) -> egui::Pos2 {
+        let n = self.points.len();
+        if n % 2 == 1 {
+            self.points[n / 2]
+        } else {
+            self.points[n / 2 - 1].lerp(self.points[n / 2], 0.5)
+        }
+    }
+}
+
+/// Hash of what type checking depends on: node types, bypasses and edges
+fn graph_fingerprint(flow: &crate::flow::Flow) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for node in &flow.nodes {
+        (&node.id, &node.type_name, node.enabled).hash(&mut hasher);
+    }
+    for edge in &flow.edges {
+        edge_key(edge).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// An input or output handle on a node box
+struct Handle {
+    node_id: String,
+    /// Named input port; None for a node's single input and its output
+    port: Option<String>,
+    is_input: bool,
+    pos: egui::Pos2,
+    data_type: crate::nodes::DataType,
+}
+
+/// At most `max` characters of `text`, marking a cut with "…"
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// An edge identified by its source, target and target port
+type EdgeKey = (String, String, Option<String>);
+
+fn edge_key(edge: &crate::flow::Edge) -> EdgeKey {
+    (edge.source.clone(), edge.target.clone(), edge.target_handle.clone())
+}
+
+/// A color tag as drawn, if it is a valid `#rrggbb`
+fn tag_color(color: Option<&str>) -> Option<egui::Color32> {
+    let [r, g, b] = crate::nodes::parse_color(color?)?;
+    Some(egui::Color32::from_rgb(r, g, b))
+}
+
+/// Color tag and icon pickers of a node's or group's context menu
+fn tag_menu(ui: &mut egui::Ui, color: &mut Option<String>, icon: &mut Option<String>) {
+    ui.horizontal(|ui| {
+        ui.label("Color:");
+        for (name, hex) in crate::flow::TAG_COLORS {
+            let [r, g, b] = crate::nodes::parse_color(hex).unwrap_or([200, 200, 200]);
+            let mark = if color.as_deref() == Some(*hex) { " ✔ " } else { "   " };
+            let swatch = egui::Button::new(mark).fill(egui::Color32::from_rgb(r, g, b));
+            if ui.add(swatch).on_hover_text(*name).clicked() {
+                *color = Some(hex.to_string());
+            }
+        }
+        if ui.add_enabled(color.is_some(), egui::Button::new("✖")).on_hover_text("No color").clicked() {
+            *color = None;
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Icon:");
+        for choice in crate::flow::TAG_ICONS {
+            if ui.selectable_label(icon.as_deref() == Some(*choice), *choice).clicked() {
+                *icon = Some(choice.to_string());
+            }
+        }
+        if ui.add_enabled(icon.is_some(), egui::Button::new("✖")).on_hover_text("No icon").clicked() {
+            *icon = None;
+        }
+    });
+}
+
+/// Change to a group chosen on its frame this frame
+enum GroupAction {
+    ToggleCollapsed(String),
+    Ungroup(String),
 }
 
 impl Canvas {
@@ -26,24 +192,177 @@ impl Canvas {
     const NODE_HEIGHT: f32 = 60.0;
     const NODE_ROUNDING: f32 = 5.0;
     const EDGE_THICKNESS: f32 = 2.0;
+    const EDGE_SEGMENTS: usize = 20;
+    /// How close a click must be to an edge's curve to pick it
+    const EDGE_HIT_DISTANCE: f32 = 6.0;
+    const HANDLE_RADIUS: f32 = 5.0;
+    /// Characters of a value shown in an edge's hover preview and pinned label
+    const PREVIEW_CHARS: usize = 300;
+    const PINNED_CHARS: usize = 40;
+    /// How close a drop must be to a handle to connect to it
+    const HANDLE_HIT_DISTANCE: f32 = 12.0;
+    const GROUP_PADDING: f32 = 16.0;
+    const GROUP_TITLE_HEIGHT: f32 = 22.0;
+    /// Screen space left around nodes framed by zoom to fit
+    const FIT_MARGIN: f32 = 40.0;
+    /// Zoom to fit never zooms in further than this
+    const FIT_MAX_ZOOM: f32 = 1.5;
+    /// Closest grid lines are drawn, in screen pixels
+    const MIN_GRID_SPACING: f32 = 12.0;
+    /// Below this zoom, nodes are drawn as plain boxes and edges as straight lines
+    const DETAIL_ZOOM: f32 = 0.45;
+    /// Distance orthogonal edges run straight out of and into a node
+    const ELBOW_MARGIN: f32 = 20.0;
+    const ARROW_SIZE: f32 = 9.0;
+    /// Hovered edge and the nodes at its ends
+    const HOVER_COLOR: egui::Color32 = egui::Color32::from_rgb(120, 200, 255);
+
+    /// Whether the Delete key asked to delete the selected node since the last call
+    pub fn take_delete_request(&mut self) -> bool {
+        std::mem::take(&mut self.delete_requested)
+    }
+
+    /// Highlight what changed since `base` (None to stop)
+    pub fn set_diff(&mut self, diff: Option<(crate::versions::FlowDiff, crate::flow::Flow)>) {
+        self.diff = diff;
+    }
+
+    /// Group the user asked to convert into a subflow since the last call
+    pub fn take_subflow_request(&mut self) -> Option<String> {
+        self.subflow_request.take()
+    }
+
+    /// Node whose output the user asked to pin since the last call
+    pub fn take_pin_request(&mut self) -> Option<String> {
+        self.pin_request.take()
+    }
+
+    /// Where a double-click asked to open quick-add since the last call
+    pub fn take_quick_add_request(&mut self) -> Option<egui::Pos2> {
+        self.quick_add_request.take()
+    }
+
+    /// Where quick-add opens from the keyboard: at the pointer when it's over
+    /// the canvas, otherwise in the middle of the view
+    pub fn quick_add_pos(&self, pointer: Option<egui::Pos2>) -> egui::Pos2 {
+        pointer.filter(|pos| self.view_rect.contains(*pos)).unwrap_or(self.view_rect.center())
+    }
+
+    /// Canvas position under a screen position
+    pub fn canvas_pos(&self, screen_pos: egui::Pos2) -> crate::flow::Position {
+        crate::flow::Position {
+            x: (screen_pos.x - self.pan_offset.x) / self.zoom,
+            y: (screen_pos.y - self.pan_offset.y) / self.zoom,
+        }
+    }
+
+    /// Canvas-space bounds of a group's nodes
+    fn group_bounds(flow: &crate::flow::Flow, group: &crate::flow::NodeGroup) -> Option<egui::Rect> {
+        flow.nodes.iter()
+            .filter(|n| group.nodes.contains(&n.id))
+            .filter_map(|n| n.position)
+            .map(Self::node_bounds)
+            .reduce(|a, b| a.union(b))
+    }
+
+    /// Zoom and pan so every node and note is in view
+    pub fn zoom_to_fit(&mut self, flow: &crate::flow::Flow) {
+        let nodes = flow.nodes.iter().filter_map(|n| n.position).map(Self::node_bounds);
+        let notes = flow.notes.iter().map(|n| {
+            egui::Rect::from_min_size(egui::Pos2::new(n.position.x, n.position.y), egui::Vec2::new(n.size[0], n.size[1]))
+        });
+        if let Some(bounds) = nodes.chain(notes).reduce(|a, b| a.union(b)) {
+            self.frame(bounds);
+        }
+    }
+
+    /// Zoom and pan to the selected node, the selected edge's ends and the
+    /// nodes picked for grouping; fits everything when nothing is selected
+    pub fn zoom_to_selection(&mut self, flow: &crate::flow::Flow, selected_node: Option<&String>) {
+        let mut ids: Vec<&String> = self.group_selection.iter().chain(selected_node).collect();
+        if let Some((source, target, _)) = &self.selected_edge {
+            ids.extend([source, target]);
+        }
+        let bounds = flow.nodes.iter()
+            .filter(|n| ids.contains(&&n.id))
+            .filter_map(|n| n.position)
+            .map(Self::node_bounds)
+            .reduce(|a, b| a.union(b));
+        match bounds {
+            Some(bounds) => self.frame(bounds),
+            None => self.zoom_to_fit(flow),
+        }
+    }
+
+    /// Back to 100% zoom, keeping the middle of the view in place
+    pub fn reset_zoom(&mut self) {
+        let center = self.view_rect.center().to_vec2();
+        if self.zoom > 0.0 {
+            self.pan_offset = center - (center - self.pan_offset) / self.zoom;
+        }
+        self.zoom = 1.0;
+    }
+
+    fn node_bounds(position: crate::flow::Position) -> egui::Rect {
+        egui::Rect::from_min_size(egui::Pos2::new(position.x, position.y), egui::Vec2::new(Self::NODE_WIDTH, Self::NODE_HEIGHT))
+    }
+
+    /// Center canvas-space `bounds` in the view at the largest zoom that fits
+    fn frame(&mut self, bounds: egui::Rect) {
+        let view = self.view_rect.shrink(Self::FIT_MARGIN);
+        if view.width() <= 0.0 || view.height() <= 0.0 {
+            return;
+        }
+        self.zoom = (view.width() / bounds.width())
+            .min(view.height() / bounds.height())
+            .clamp(0.1, Self::FIT_MAX_ZOOM);
+        self.pan_offset = view.center().to_vec2() - bounds.center().to_vec2() * self.zoom;
+    }
+
+    /// Canvas-space area currently in view, for the minimap
+    pub fn visible_area(&self) -> egui::Rect {
+        let zoom = if self.zoom > 0.0 { self.zoom } else { 1.0 };
+        egui::Rect::from_min_max(
+            ((self.view_rect.min - self.pan_offset).to_vec2() / zoom).to_pos2(),
+            ((self.view_rect.max - self.pan_offset).to_vec2() / zoom).to_pos2(),
+        )
+    }
+
+    /// Pan so canvas-space `point` is in the middle of the view
+    pub fn center_on(&mut self, point: egui::Pos2) {
+        let zoom = if self.zoom > 0.0 { self.zoom } else { 1.0 };
+        self.pan_offset = self.view_rect.center().to_vec2() - point.to_vec2() * zoom;
+    }
+
+    /// Pan so a node is in the middle of the view, keeping the zoom
+    pub fn center_on_node(&mut self, flow: &crate::flow::Flow, node_id: &str) {
+        if let Some(position) = flow.nodes.iter().find(|n| n.id == node_id).and_then(|n| n.position) {
+            self.center_on(Self::node_bounds(position).center());
+        }
+    }
 
-    /// Get current pan offset for minimap
-    pub fn offset(&self) -> egui::Vec2 {
-        -self.pan_offset  // Negative because pan_offset moves the view
+    /// Move the view by a canvas-space distance
+    pub fn pan_by(&mut self, delta: egui::Vec2) {
+        self.pan_offset -= delta * self.zoom;
     }
 
-    /// Get current zoom level for minimap
-    pub fn zoom(&self) -> f32 {
-        self.zoom
+    /// Whether the view is zoomed out far enough to skip text and curves
+    fn simplified(&self) -> bool {
+        self.zoom < Self::DETAIL_ZOOM
     }
 
+    /// Draw the canvas and handle interaction.
+    ///
+    /// Returns the node ID if "Run to here" was chosen from a node's context menu.
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
         flow: &mut crate::flow::Flow,
         selected_node: &mut Option<String>,
         node_executions: &std::collections::HashMap<String, super::NodeExecution>,
-    ) {
+    ) -> Option<String> {
+        let mut run_to_node = None;
+
         // Initialize zoom if needed
         if self.zoom == 0.0 {
             self.zoom = 1.0;
@@ -54,8 +373,11 @@ impl Canvas {
             egui::Sense::click_and_drag(),
         );
 
-        // Store mouse position
-        if let Some(pos) = response.hover_pos() {
+        self.view_rect = response.rect;
+        self.edge_style = flow.edge_style;
+
+        // Store mouse position, also while a handle is being dragged
+        if let Some(pos) = ui.input(|i| i.pointer.latest_pos()) {
             self.mouse_pos = pos;
         }
 
@@ -93,26 +415,221 @@ impl Canvas {
         // Draw grid
         self.draw_grid(&painter, response.rect, self.zoom, self.pan_offset);
 
-        // Draw edges
-        for edge in &flow.edges {
-            if let (Some(source_node), Some(target_node)) = (
-                flow.nodes.iter().find(|n| n.id == edge.source),
-                flow.nodes.iter().find(|n| n.id == edge.target),
-            ) {
-                if let (Some(source_pos), Some(target_pos)) = (&source_node.position, &target_node.position) {
-                    let start = to_screen(egui::Pos2::new(
-                        source_pos.x + Self::NODE_WIDTH / 2.0,
-                        source_pos.y + Self::NODE_HEIGHT,
-                    ));
-                    let end = to_screen(egui::Pos2::new(
-                        target_pos.x + Self::NODE_WIDTH / 2.0,
-                        target_pos.y,
-                    ));
+        // Only what overlaps the view is drawn and hit-tested
+        let view = response.rect;
 
-                    // Draw bezier curve for edge
-                    self.draw_edge(&painter, start, end, egui::Color32::GRAY);
+        // Sticky notes, behind edges and nodes
+        let mut delete_note = None;
+        for note in flow.notes.iter_mut() {
+            let rect = egui::Rect::from_min_size(
+                to_screen(egui::Pos2::new(note.position.x, note.position.y)),
+                egui::Vec2::new(note.size[0], note.size[1]) * self.zoom,
+            );
+            if !rect.intersects(view) {
+                continue;
+            }
+
+            let note_response = ui.interact(rect, egui::Id::new(("note", &note.id)), egui::Sense::click_and_drag());
+            if note_response.dragged() {
+                note.position.x += note_response.drag_delta().x / self.zoom;
+                note.position.y += note_response.drag_delta().y / self.zoom;
+            }
+            if note_response.double_clicked() {
+                self.editing_note = Some(note.id.clone());
+            }
+            note_response.context_menu(|ui| {
+                if ui.button("✏ Edit note").clicked() {
+                    self.editing_note = Some(note.id.clone());
+                    ui.close_menu();
                 }
+                if ui.button("🗑 Delete note").clicked() {
+                    delete_note = Some(note.id.clone());
+                    ui.close_menu();
+                }
+            });
+
+            let grip_rect = egui::Rect::from_min_max(rect.max - egui::Vec2::splat(notes::GRIP_SIZE * self.zoom), rect.max);
+            let grip = ui.interact(grip_rect, egui::Id::new(("note_grip", &note.id)), egui::Sense::drag());
+            if grip.hovered() || grip.dragged() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeNwSe);
             }
+            if grip.dragged() {
+                let [min_width, min_height] = crate::flow::StickyNote::MIN_SIZE;
+                note.size[0] = (note.size[0] + grip.drag_delta().x / self.zoom).max(min_width);
+                note.size[1] = (note.size[1] + grip.drag_delta().y / self.zoom).max(min_height);
+            }
+
+            let is_editing = self.editing_note.as_ref() == Some(&note.id);
+            notes::paint_note(&painter, rect, note, self.zoom, is_editing);
+        }
+        if let Some(id) = delete_note {
+            flow.notes.retain(|n| n.id != id);
+        }
+
+        // Members of collapsed groups are drawn as one block at the group's corner
+        let mut collapsed: std::collections::HashMap<&str, (&str, crate::flow::Position)> = std::collections::HashMap::new();
+        for group in flow.groups.iter().filter(|g| g.collapsed) {
+            if let Some(bounds) = Self::group_bounds(flow, group) {
+                let anchor = crate::flow::Position { x: bounds.min.x, y: bounds.min.y };
+                for id in &group.nodes {
+                    collapsed.insert(id.as_str(), (group.id.as_str(), anchor));
+                }
+            }
+        }
+
+        // Type checking walks the whole graph, so only redo it when the graph changes
+        let fingerprint = graph_fingerprint(flow);
+        if self.mismatch_cache.as_ref().map(|(f, _)| *f) != Some(fingerprint) {
+            self.mismatch_cache = Some((fingerprint, flow.type_mismatches()));
+        }
+        let mismatches: HashMap<EdgeKey, &crate::flow::TypeMismatch> = self.mismatch_cache.iter()
+            .flat_map(|(_, found)| found)
+            .map(|m| ((m.source.clone(), m.target.clone(), m.target_handle.clone()), m))
+            .collect();
+
+        // Draw edges in view, remembering where each one runs for hit-testing
+        let nodes_by_id: HashMap<&str, &crate::flow::Node> = flow.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+        let mut edge_cache = HashMap::with_capacity(flow.edges.len());
+        let mut edge_paths = Vec::new();
+        for (index, edge) in flow.edges.iter().enumerate() {
+            let source_group = collapsed.get(edge.source.as_str());
+            let target_group = collapsed.get(edge.target.as_str());
+            if let (Some((a, _)), Some((b, _))) = (source_group, target_group) {
+                if a == b {
+                    continue;
+                }
+            }
+
+            let (Some(source_node), Some(target_node)) = (nodes_by_id.get(edge.source.as_str()), nodes_by_id.get(edge.target.as_str())) else {
+                continue;
+            };
+            let source_pos = source_group.map(|(_, anchor)| *anchor).or(source_node.position);
+            let target_pos = target_group.map(|(_, anchor)| *anchor).or(target_node.position);
+            let (Some(source_pos), Some(target_pos)) = (source_pos, target_pos) else {
+                continue;
+            };
+
+            // Edges into named ports end at their port
+            let ports = if target_group.is_some() { &[][..] } else { crate::nodes::input_ports(&target_node.type_name) };
+            let port = if ports.is_empty() {
+                None
+            } else {
+                flow.port_sources(&target_node.id, ports)
+                    .iter()
+                    .position(|e| e.is_some_and(|e| std::ptr::eq(e, edge)))
+            };
+            let start = egui::Pos2::new(source_pos.x + Self::NODE_WIDTH / 2.0, source_pos.y + Self::NODE_HEIGHT);
+            let end = egui::Pos2::new(target_pos.x + Self::port_x(port.unwrap_or(0), ports.len()), target_pos.y);
+
+            // Reuse the curve unless an end moved
+            let key = edge_key(edge);
+            let style = self.edge_style;
+            let cached = match self.edge_cache.remove(&key) {
+                Some(cached) if cached.start == start && cached.end == end && cached.style == style => cached,
+                _ => CachedEdge { start, end, style, points: Self::edge_points(style, start, end, 1.0) },
+            };
+            let points: Vec<egui::Pos2> = if self.simplified() {
+                vec![to_screen(start), to_screen(end)]
+            } else {
+                cached.points.iter().map(|p| to_screen(*p)).collect()
+            };
+            let bounds = egui::Rect::from_points(&points);
+            let is_selected = self.selected_edge.as_ref() == Some(&key);
+            let mismatch = mismatches.get(&key).copied();
+            edge_cache.insert(key, cached);
+
+            // The selected edge is kept for its end handles even when out of view
+            if !bounds.expand(Self::EDGE_HIT_DISTANCE).intersects(view) && !is_selected {
+                continue;
+            }
+
+            // Draw bezier curve for edge
+            if is_selected {
+                self.draw_path(&painter, &points, egui::Color32::YELLOW, Self::EDGE_THICKNESS * 2.0);
+            } else if self.diff.as_ref().is_some_and(|(diff, _)| diff.is_added_edge(edge)) {
+                self.draw_path(&painter, &points, egui::Color32::GREEN, Self::EDGE_THICKNESS);
+            } else if mismatch.is_some() {
+                self.draw_path(&painter, &points, egui::Color32::from_rgb(230, 140, 40), Self::EDGE_THICKNESS);
+            } else {
+                self.draw_path(&painter, &points, egui::Color32::GRAY, Self::EDGE_THICKNESS);
+            }
+            let path = EdgePath { index, points, bounds };
+
+            // Badges and labels only when zoomed in enough to read them
+            if self.simplified() {
+                edge_paths.push(path);
+                continue;
+            }
+
+            // Warning badge halfway along edges carrying the wrong type
+            if let Some(mismatch) = mismatch {
+                let middle = path.middle();
+                painter.circle(middle, 8.0, egui::Color32::from_rgb(230, 140, 40), egui::Stroke::new(1.0, egui::Color32::BLACK));
+                painter.text(middle, egui::Align2::CENTER_CENTER, "!", egui::FontId::proportional(12.0), egui::Color32::BLACK);
+                ui.interact(egui::Rect::from_center_size(middle, egui::Vec2::splat(16.0)), egui::Id::new(("mismatch", index)), egui::Sense::hover())
+                    .on_hover_text(format!("⚠ {}", mismatch));
+            }
+
+            if self.pin_edge_values {
+                if let Some(output) = node_executions.get(&edge.source).and_then(|e| e.output.as_deref()) {
+                    let offset = if mismatch.is_some() { 16.0 } else { 0.0 };
+                    let galley = painter.layout_no_wrap(
+                        truncate(output, Self::PINNED_CHARS),
+                        egui::FontId::monospace(10.0),
+                        egui::Color32::WHITE,
+                    );
+                    let rect = egui::Rect::from_center_size(path.middle() + egui::Vec2::new(0.0, offset), galley.size() + egui::Vec2::splat(6.0));
+                    painter.rect_filled(rect, 3.0, egui::Color32::from_black_alpha(200));
+                    painter.galley(rect.min + egui::Vec2::splat(3.0), galley, egui::Color32::WHITE);
+                }
+            }
+            edge_paths.push(path);
+        }
+        self.edge_cache = edge_cache;
+
+        // Nodes and edges removed since the compared version, where they used to be
+        if let Some((diff, base)) = &self.diff {
+            let removed = egui::Color32::from_rgb(220, 60, 60);
+            let position = |id: &str| base.nodes.iter().find(|n| n.id == id).and_then(|n| n.position);
+            for edge in &diff.removed_edges {
+                if let (Some(source), Some(target)) = (position(&edge.source), position(&edge.target)) {
+                    let start = to_screen(egui::Pos2::new(source.x + Self::NODE_WIDTH / 2.0, source.y + Self::NODE_HEIGHT));
+                    let end = to_screen(egui::Pos2::new(target.x + Self::NODE_WIDTH / 2.0, target.y));
+                    self.draw_edge(&painter, start, end, removed, 1.0);
+                }
+            }
+            for id in &diff.removed_nodes {
+                let Some(node) = base.nodes.iter().find(|n| &n.id == id) else { continue };
+                let Some(pos) = node.position else { continue };
+                let rect = egui::Rect::from_min_size(
+                    to_screen(egui::Pos2::new(pos.x, pos.y)),
+                    egui::Vec2::new(Self::NODE_WIDTH, Self::NODE_HEIGHT) * self.zoom,
+                );
+                painter.rect_filled(rect, Self::NODE_ROUNDING, egui::Color32::from_rgba_unmultiplied(220, 60, 60, 40));
+                painter.rect_stroke(rect, Self::NODE_ROUNDING, egui::Stroke::new(2.0, removed));
+                painter.text(rect.center(), egui::Align2::CENTER_CENTER, format!("− {}", node.type_name), egui::FontId::proportional(13.0), removed);
+            }
+        }
+
+        // Hovering an edge highlights it and, below, the nodes it connects
+        let hovered_edge = response.hover_pos().and_then(|pos| Self::edge_at(&edge_paths, pos));
+        if let Some(path) = hovered_edge.and_then(|index| edge_paths.iter().find(|p| p.index == index)) {
+            self.draw_path(&painter, &path.points, Self::HOVER_COLOR, Self::EDGE_THICKNESS * 1.5);
+        }
+        let hovered_ends = hovered_edge.map(|index| (flow.edges[index].source.clone(), flow.edges[index].target.clone()));
+
+        // After a run, hover an edge for the value that crossed it
+        let hovered_value = hovered_edge
+            .and_then(|index| {
+                let edge = &flow.edges[index];
+                let output = node_executions.get(&edge.source)?.output.as_deref()?;
+                Some((format!("{} → {}", edge.source, edge.target), truncate(output, Self::PREVIEW_CHARS)))
+            });
+        if let Some((label, value)) = hovered_value {
+            response.clone().on_hover_ui_at_pointer(|ui| {
+                ui.label(label);
+                ui.monospace(value);
+            });
         }
 
         // Draw edge preview if currently drawing
@@ -123,20 +640,108 @@ impl Canvas {
                         source_pos.x + Self::NODE_WIDTH / 2.0,
                         source_pos.y + Self::NODE_HEIGHT,
                     ));
-                    self.draw_edge(&painter, start, self.mouse_pos, egui::Color32::LIGHT_GRAY);
+                    self.draw_edge(&painter, start, self.mouse_pos, egui::Color32::LIGHT_GRAY, Self::EDGE_THICKNESS);
+                }
+            }
+        }
+
+        let hidden: std::collections::HashSet<String> = collapsed.keys().map(|id| id.to_string()).collect();
+
+        // Group frames, or one block per collapsed group
+        let mut group_actions = Vec::new();
+        for index in 0..flow.groups.len() {
+            let group = flow.groups[index].clone();
+            let Some(bounds) = Self::group_bounds(flow, &group) else { continue };
+
+            let (handle_rect, frame_rect) = if group.collapsed {
+                let block = egui::Rect::from_min_size(
+                    to_screen(bounds.min),
+                    egui::Vec2::new(Self::NODE_WIDTH, Self::NODE_HEIGHT) * self.zoom,
+                );
+                (block, block)
+            } else {
+                let frame = egui::Rect::from_min_max(
+                    to_screen(bounds.min - egui::Vec2::new(Self::GROUP_PADDING, Self::GROUP_PADDING + Self::GROUP_TITLE_HEIGHT)),
+                    to_screen(bounds.max + egui::Vec2::splat(Self::GROUP_PADDING)),
+                );
+                let title = egui::Rect::from_min_size(frame.min, egui::Vec2::new(frame.width(), Self::GROUP_TITLE_HEIGHT * self.zoom));
+                (title, frame)
+            };
+
+            if !frame_rect.intersects(view) {
+                continue;
+            }
+            self.draw_group(&painter, frame_rect, &group);
+
+            let handle = ui.interact(handle_rect, egui::Id::new(("group", &group.id)), egui::Sense::click_and_drag());
+            if handle.dragged() {
+                let delta = handle.drag_delta() / self.zoom;
+                for node in flow.nodes.iter_mut().filter(|n| group.nodes.contains(&n.id)) {
+                    if let Some(pos) = &mut node.position {
+                        pos.x += delta.x;
+                        pos.y += delta.y;
+                    }
+                }
+            }
+            if handle.double_clicked() {
+                group_actions.push(GroupAction::ToggleCollapsed(group.id.clone()));
+            }
+            handle.context_menu(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut flow.groups[index].name);
+                });
+                let toggle = if group.collapsed { "▾ Expand" } else { "▸ Collapse" };
+                if ui.button(toggle).clicked() {
+                    group_actions.push(GroupAction::ToggleCollapsed(group.id.clone()));
+                    ui.close_menu();
+                }
+                if ui.button("Ungroup").clicked() {
+                    group_actions.push(GroupAction::Ungroup(group.id.clone()));
+                    ui.close_menu();
+                }
+                if ui.button("📦 Convert to subflow").clicked() {
+                    self.subflow_request = Some(group.id.clone());
+                    ui.close_menu();
+                }
+                ui.menu_button("🏷 Tag", |ui| {
+                    let group = &mut flow.groups[index];
+                    tag_menu(ui, &mut group.color, &mut group.icon);
+                });
+            });
+        }
+        for action in group_actions {
+            match action {
+                GroupAction::ToggleCollapsed(id) => {
+                    if let Some(group) = flow.groups.iter_mut().find(|g| g.id == id) {
+                        group.collapsed = !group.collapsed;
+                    }
                 }
+                GroupAction::Ungroup(id) => flow.groups.retain(|g| g.id != id),
             }
         }
 
         // Draw nodes
+        let mut handles = Vec::new();
         let mut nodes_to_draw = Vec::new();
-        for node in &flow.nodes {
+        for node in flow.nodes.iter().filter(|n| !hidden.contains(&n.id)) {
             if let Some(pos) = &node.position {
-                nodes_to_draw.push((node.id.clone(), node.type_name.clone(), *pos, node.breakpoint));
+                // Skip nodes out of view, keeping room for handles and badges
+                let rect = egui::Rect::from_min_size(
+                    to_screen(egui::Pos2::new(pos.x, pos.y)),
+                    egui::Vec2::new(Self::NODE_WIDTH, Self::NODE_HEIGHT) * self.zoom,
+                );
+                let dragged = self.dragging_node.as_ref() == Some(&node.id);
+                if !rect.expand(Self::HANDLE_HIT_DISTANCE * 2.0).intersects(view) && !dragged {
+                    continue;
+                }
+                let pinned = flow.pinned.contains_key(&node.id);
+                nodes_to_draw.push((node.clone(), *pos, pinned));
             }
         }
 
-        for (node_id, type_name, pos, has_breakpoint) in nodes_to_draw {
+        for (node, pos, pinned) in nodes_to_draw {
+            let (node_id, type_name, has_breakpoint, enabled) = (node.id.clone(), node.type_name.clone(), node.breakpoint, node.enabled);
             let is_selected = selected_node.as_ref() == Some(&node_id);
             let execution_state = node_executions.get(&node_id);
             let screen_pos = to_screen(egui::Pos2::new(pos.x, pos.y));
@@ -177,82 +782,273 @@ impl Canvas {
                 }
             }
 
-            // Handle selection (left click)
-            if node_response.clicked() {
+            // Handle selection (left click); Shift+Click picks nodes to group
+            if node_response.clicked() && ui.input(|i| i.modifiers.shift) {
+                match self.group_selection.iter().position(|id| *id == node_id) {
+                    Some(i) => {
+                        self.group_selection.remove(i);
+                    }
+                    None => self.group_selection.push(node_id.clone()),
+                }
+            } else if node_response.clicked() {
                 *selected_node = Some(node_id.clone());
+                self.selected_edge = None;
             }
 
-            // Handle breakpoint toggle (right click)
-            if node_response.secondary_clicked() {
-                if let Some(node) = flow.nodes.iter_mut().find(|n| n.id == node_id) {
-                    node.breakpoint = !node.breakpoint;
+            // Context menu (right click)
+            node_response.context_menu(|ui| {
+                if ui.button("▶ Run to here").clicked() {
+                    run_to_node = Some(node_id.clone());
+                    ui.close_menu();
                 }
-            }
 
-            // Handle edge creation (ctrl+click)
-            if node_response.clicked() && ui.input(|i| i.modifiers.ctrl) {
-                if let Some(source) = &self.drawing_edge {
-                    use crate::flow::Edge;
+                let breakpoint_label = if has_breakpoint { "Remove Breakpoint" } else { "Set Breakpoint" };
+                if ui.button(breakpoint_label).clicked() {
+                    if let Some(node) = flow.nodes.iter_mut().find(|n| n.id == node_id) {
+                        node.breakpoint = !node.breakpoint;
+                    }
+                    ui.close_menu();
+                }
 
-                    // Complete edge - check if edge already exists
-                    let exists = flow.edges.iter().any(|e| {
-                        e.source == *source && e.target == node_id
-                    });
+                let enabled_label = if enabled { "⏸ Disable (bypass)" } else { "▶ Enable" };
+                if ui.button(enabled_label).clicked() {
+                    if let Some(node) = flow.nodes.iter_mut().find(|n| n.id == node_id) {
+                        node.enabled = !node.enabled;
+                    }
+                    ui.close_menu();
+                }
 
-                    if !exists {
-                        flow.edges.push(Edge {
-                            source: source.clone(),
-                            target: node_id.clone(),
-                            source_handle: None,
-                            target_handle: None,
-                        });
+                if pinned {
+                    if ui.button("📌 Unpin output").clicked() {
+                        flow.pinned.remove(&node_id);
+                        ui.close_menu();
                     }
-                    self.drawing_edge = None;
-                } else {
-                    // Start edge
-                    self.drawing_edge = Some(node_id.clone());
+                } else if ui.button("📌 Pin output").on_hover_text("Reuse the last output in later runs instead of running this node").clicked() {
+                    self.pin_request = Some(node_id.clone());
+                    ui.close_menu();
                 }
+
+                ui.menu_button("🏷 Tag", |ui| {
+                    if let Some(node) = flow.nodes.iter_mut().find(|n| n.id == node_id) {
+                        tag_menu(ui, &mut node.color, &mut node.icon);
+                    }
+                });
+            });
+
+            // Labelled nodes name their type on hover, above any notes
+            let notes = node.notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+            let hover = match (node.display_name() != type_name, notes) {
+                (true, Some(notes)) => Some(format!("{}\n\n{}", type_name, notes)),
+                (true, None) => Some(type_name.clone()),
+                (false, Some(notes)) => Some(notes.to_string()),
+                (false, None) => None,
+            };
+            if let Some(hover) = hover {
+                node_response.on_hover_text(hover);
             }
 
             // Draw node
-            self.draw_node(&painter, node_rect, &type_name, is_selected, execution_state, has_breakpoint);
+            self.draw_node(&painter, node_rect, &node, is_selected, execution_state);
+            if !enabled {
+                // Dim bypassed nodes
+                painter.rect_filled(node_rect, Self::NODE_ROUNDING, egui::Color32::from_black_alpha(150));
+            }
+            if !enabled && !self.simplified() {
+                painter.text(
+                    node_rect.right_top() + egui::Vec2::new(-6.0, 8.0),
+                    egui::Align2::RIGHT_CENTER,
+                    "⏸ bypassed",
+                    egui::FontId::proportional(10.0),
+                    egui::Color32::from_gray(200),
+                );
+            }
+            if pinned && !self.simplified() {
+                painter.text(
+                    node_rect.right_bottom() + egui::Vec2::new(-6.0, -8.0),
+                    egui::Align2::RIGHT_CENTER,
+                    "📌 pinned",
+                    egui::FontId::proportional(10.0),
+                    egui::Color32::from_rgb(255, 200, 80),
+                );
+            }
+            if hovered_ends.as_ref().is_some_and(|(source, target)| *source == node_id || *target == node_id) {
+                painter.rect_stroke(node_rect.expand(4.0), Self::NODE_ROUNDING + 2.0, egui::Stroke::new(2.0, Self::HOVER_COLOR));
+            }
+            if self.group_selection.contains(&node_id) {
+                painter.rect_stroke(node_rect.expand(5.0), Self::NODE_ROUNDING + 2.0, egui::Stroke::new(2.0, egui::Color32::LIGHT_BLUE));
+            }
+            if let Some((diff, _)) = &self.diff {
+                let color = if diff.added_nodes.contains(&node_id) {
+                    Some(egui::Color32::GREEN)
+                } else if diff.changed_nodes.contains(&node_id) {
+                    Some(egui::Color32::from_rgb(230, 140, 40))
+                } else {
+                    None
+                };
+                if let Some(color) = color {
+                    painter.rect_stroke(node_rect.expand(7.0), Self::NODE_ROUNDING + 3.0, egui::Stroke::new(3.0, color));
+                }
+            }
+            let node_handles = self.node_handles(&node_id, &type_name, node_rect);
+            if !self.simplified() {
+                self.draw_handles(&painter, &node_handles);
+            }
+            handles.extend(node_handles);
+        }
+
+        // Drag from an output handle and drop on an input handle to connect
+        for handle in &handles {
+            let rect = egui::Rect::from_center_size(handle.pos, egui::Vec2::splat(Self::HANDLE_HIT_DISTANCE));
+            let id = egui::Id::new(("handle", &handle.node_id, &handle.port, handle.is_input));
+            let response = ui.interact(rect, id, egui::Sense::drag())
+                .on_hover_text(format!("{}: {}", handle.port.as_deref().unwrap_or(if handle.is_input { "in" } else { "out" }), handle.data_type.name()));
+            if !handle.is_input && response.drag_started() {
+                self.drawing_edge = Some(handle.node_id.clone());
+            }
+        }
+        if self.drawing_edge.is_some() && ui.input(|i| i.pointer.any_released()) {
+            if let (Some(source), Some(target)) = (self.drawing_edge.take(), Self::handle_at(&handles, self.mouse_pos, true)) {
+                Self::connect(flow, &source, target);
+            }
+        }
+
+        // Handles on the selected edge's ends; drag one onto another node's handle to reconnect
+        let selected_path = self.selected_edge.as_ref().and_then(|key| {
+            edge_paths.iter().find(|path| edge_key(&flow.edges[path.index]) == *key)
+        });
+        if let Some(path) = selected_path {
+            for (is_target, point) in [(false, path.start()), (true, path.end())] {
+                painter.circle(point, 5.0, egui::Color32::YELLOW, egui::Stroke::new(1.0, egui::Color32::BLACK));
+                let handle_rect = egui::Rect::from_center_size(point, egui::Vec2::splat(12.0));
+                let handle = ui.interact(handle_rect, egui::Id::new(("edge_end", is_target)), egui::Sense::drag());
+                if handle.drag_started() {
+                    self.rewiring = self.selected_edge.clone().map(|key| (key, is_target));
+                }
+            }
+        }
+        if let Some((key, is_target)) = self.rewiring.clone() {
+            let pointer = ui.input(|i| i.pointer.latest_pos()).unwrap_or(self.mouse_pos);
+            match selected_path {
+                Some(path) if is_target => self.draw_edge(&painter, path.start(), pointer, egui::Color32::LIGHT_GRAY, Self::EDGE_THICKNESS),
+                Some(path) => self.draw_edge(&painter, pointer, path.end(), egui::Color32::LIGHT_GRAY, Self::EDGE_THICKNESS),
+                None => self.rewiring = None,
+            }
+            if ui.input(|i| i.pointer.any_released()) {
+                if let Some(handle) = Self::handle_at(&handles, pointer, is_target) {
+                    if let Some(new_key) = Self::reconnect_edge(flow, &key, is_target, &handle.node_id, handle.port.clone()) {
+                        self.selected_edge = Some(new_key);
+                    }
+                }
+                self.rewiring = None;
+            }
         }
 
         // Cancel edge drawing on escape
         ui.input(|i| {
             if i.key_pressed(egui::Key::Escape) {
                 self.drawing_edge = None;
+                self.group_selection.clear();
             }
         });
 
-        // Delete selected node on Delete key
-        ui.input(|i| {
-            if i.key_pressed(egui::Key::Delete) {
-                if let Some(node_id) = selected_node {
-                    // Remove node
-                    flow.nodes.retain(|n| &n.id != node_id);
-
-                    // Remove connected edges
-                    flow.edges.retain(|e| {
-                        e.source != *node_id && e.target != *node_id
-                    });
+        // Delete the selected edge on Delete key, or the selected node once the
+        // editor has checked what depends on it
+        if ui.input(|i| i.key_pressed(egui::Key::Delete)) {
+            if let Some(key) = self.selected_edge.take() {
+                flow.edges.retain(|e| edge_key(e) != key);
+            } else if selected_node.is_some() {
+                self.delete_requested = true;
+            }
+        }
 
-                    *selected_node = None;
+        // Right-click on empty canvas to add a note
+        if response.secondary_clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                self.menu_pos = pos;
+            }
+        }
+        let edge_under_menu = Self::edge_at(&edge_paths, self.menu_pos).map(|index| edge_key(&flow.edges[index]));
+        response.context_menu(|ui| {
+            if let Some(key) = &edge_under_menu {
+                ui.label(format!("{} → {}", key.0, key.1));
+                if ui.button("✂ Delete connection").clicked() {
+                    flow.edges.retain(|e| edge_key(e) != *key);
+                    if self.selected_edge.as_ref() == Some(key) {
+                        self.selected_edge = None;
+                    }
+                    ui.close_menu();
+                }
+                ui.separator();
+            }
+            if !self.group_selection.is_empty() {
+                if ui.button(format!("🗂 Group {} selected node(s)", self.group_selection.len())).clicked() {
+                    let name = format!("Group {}", flow.groups.len() + 1);
+                    flow.group_nodes(&self.group_selection, &name);
+                    self.group_selection.clear();
+                    ui.close_menu();
                 }
+                if ui.button("Clear selection").clicked() {
+                    self.group_selection.clear();
+                    ui.close_menu();
+                }
+                ui.separator();
+            }
+            ui.checkbox(&mut self.pin_edge_values, "📌 Show values on edges")
+                .on_hover_text("Label each edge with the value that crossed it in the last run");
+            ui.menu_button("〰 Edge style", |ui| {
+                for style in crate::flow::EdgeStyle::ALL {
+                    ui.radio_value(&mut flow.edge_style, *style, style.label());
+                }
+            });
+            if ui.button("📝 Add note here").clicked() {
+                let pos = to_canvas(self.menu_pos);
+                let id = flow.next_note_id();
+                flow.notes.push(crate::flow::StickyNote::new(id.clone(), crate::flow::Position { x: pos.x, y: pos.y }));
+                self.editing_note = Some(id);
+                ui.close_menu();
             }
         });
 
-        // Click on empty canvas to deselect
-        if response.clicked() && !ui.input(|i| i.modifiers.ctrl) {
-            *selected_node = None;
+        if let Some(id) = self.editing_note.clone() {
+            match flow.notes.iter_mut().find(|n| n.id == id) {
+                Some(note) => match notes::show_note_editor(ui.ctx(), note) {
+                    NoteEditorAction::None => {}
+                    NoteEditorAction::Close => self.editing_note = None,
+                    NoteEditorAction::Delete => {
+                        flow.notes.retain(|n| n.id != id);
+                        self.editing_note = None;
+                    }
+                },
+                None => self.editing_note = None,
+            }
+        }
+
+        // Double-click on empty canvas to quick-add a node there, keeping the
+        // selection the first click cleared so the new node connects after it
+        let on_edge = response.interact_pointer_pos().and_then(|pos| Self::edge_at(&edge_paths, pos)).is_some();
+        if response.double_clicked() && !on_edge {
+            self.quick_add_request = response.interact_pointer_pos();
+            *selected_node = self.deselected.take();
+        } else if response.clicked() {
+            // Click on an edge to select it, on empty canvas to deselect
+            self.deselected = selected_node.take();
+            self.selected_edge = response.interact_pointer_pos()
+                .and_then(|pos| Self::edge_at(&edge_paths, pos))
+                .map(|index| edge_key(&flow.edges[index]));
         }
 
         // Instructions
-        ui.label("Drag nodes to move | Ctrl+Click to connect | Right-Click for breakpoint | Delete key to remove | Shift+Drag to pan");
+        ui.label("Drag nodes to move | Drag from a node's bottom handle to another's top handle to connect | Click an edge to select it, drag its ends to reconnect | Shift+Click to select for grouping | Right-Click for run/breakpoint/pin menu, to delete a connection or to add a note | Tab or Double-Click to quick-add a node | Delete key to remove | Shift+Drag to pan");
+
+        run_to_node
     }
 
     fn draw_grid(&self, painter: &egui::Painter, rect: egui::Rect, zoom: f32, offset: egui::Vec2) {
-        let grid_spacing = 50.0 * zoom;
+        // Zoomed far out, skip lines rather than drawing hundreds of them
+        let mut grid_spacing = 50.0 * zoom;
+        while grid_spacing < Self::MIN_GRID_SPACING {
+            grid_spacing *= 2.0;
+        }
         let color = egui::Color32::from_gray(30);
 
         // Vertical lines
@@ -282,25 +1078,23 @@ impl Canvas {
         &self,
         painter: &egui::Painter,
         rect: egui::Rect,
-        type_name: &str,
+        node: &crate::flow::Node,
         is_selected: bool,
         execution_state: Option<&super::NodeExecution>,
-        has_breakpoint: bool,
     ) {
         use super::ExecutionState;
+        let type_name = node.type_name.as_str();
 
         // Base node colors by type
         let base_color = match type_name {
             "start" => egui::Color32::from_rgb(50, 150, 50),
-            "http_get" | "http_post" | "http_put" | "http_delete" | "http_request" => {
+            "http_get" | "http_post" | "http_put" | "http_delete" | "http_request" | "http_paginate"
+            | "http_response_get" | "feed_fetch" => {
                 egui::Color32::from_rgb(70, 130, 180)
             }
             "json_parse" | "json_stringify" | "json_get" | "json_set" => {
                 egui::Color32::from_rgb(200, 120, 50)
             }
-            "tensor_create" | "tensor_matmul" | "tensor_add" => {
-                egui::Color32::from_rgb(150, 50, 150)
-            }
             "print" => egui::Color32::from_rgb(100, 100, 100),
             _ if type_name.starts_with("string_") => egui::Color32::from_rgb(180, 140, 70),
             _ if type_name.starts_with("array_") => egui::Color32::from_rgb(120, 180, 140),
@@ -309,8 +1103,17 @@ impl Canvas {
                 egui::Color32::from_rgb(180, 100, 50)
             }
             _ if type_name.starts_with("math_") => egui::Color32::from_rgb(100, 150, 200),
+            _ if crate::fallback::is_tensor_node(type_name) => egui::Color32::from_rgb(150, 50, 150),
+            "embed_text" | "vector_index" | "vector_search" => egui::Color32::from_rgb(120, 70, 170),
             _ if type_name.starts_with("to_") => egui::Color32::from_rgb(150, 150, 100),
-            _ => egui::Color32::DARK_GRAY,
+            _ if type_name.starts_with("nats_") => egui::Color32::from_rgb(60, 160, 160),
+            _ if type_name.starts_with("sftp_") => egui::Color32::from_rgb(160, 110, 70),
+            _ if type_name.starts_with("email_") => egui::Color32::from_rgb(170, 90, 110),
+            _ if type_name.starts_with("state_") => egui::Color32::from_rgb(110, 140, 90),
+            _ => match crate::nodes::node_color(type_name) {
+                Some([r, g, b]) => egui::Color32::from_rgb(r, g, b),
+                None => egui::Color32::DARK_GRAY,
+            },
         };
 
         // Override color based on execution state
@@ -349,8 +1152,24 @@ impl Canvas {
 
         let text_color = egui::Color32::WHITE;
 
+        // Zoomed out, a colored box is all that can be made out
+        if self.simplified() {
+            // Tags color idle nodes, so tagged branches still stand out
+            let fill = match execution_state {
+                None => tag_color(node.color.as_deref()).unwrap_or(bg_color),
+                Some(_) => bg_color,
+            };
+            painter.rect_filled(rect, 1.0, fill);
+            if is_selected {
+                painter.rect_stroke(rect.expand(2.0), 1.0, egui::Stroke::new(2.0, egui::Color32::YELLOW));
+            } else if execution_state.is_some() {
+                painter.rect_stroke(rect, 1.0, egui::Stroke::new(border_width, border_color));
+            }
+            return;
+        }
+
         // Draw breakpoint indicator (red circle in top-left corner)
-        if has_breakpoint {
+        if node.breakpoint {
             let breakpoint_center = rect.min + egui::Vec2::new(8.0, 8.0);
             painter.circle_filled(breakpoint_center, 6.0, egui::Color32::RED);
             painter.circle_stroke(
@@ -370,20 +1189,25 @@ impl Canvas {
             );
         }
 
-        // Draw node body
+        // Draw node body, with a band across the top in its tag color
         painter.rect_filled(rect, Self::NODE_ROUNDING, bg_color);
+        if let Some(tag) = tag_color(node.color.as_deref()) {
+            let band = egui::Rect::from_min_max(rect.min, egui::pos2(rect.max.x, rect.min.y + 6.0 * self.zoom));
+            let rounding = egui::Rounding { nw: Self::NODE_ROUNDING, ne: Self::NODE_ROUNDING, sw: 0.0, se: 0.0 };
+            painter.rect_filled(band, rounding, tag);
+        }
         painter.rect_stroke(
             rect,
             Self::NODE_ROUNDING,
             egui::Stroke::new(border_width, border_color),
         );
 
-        // Draw type name
+        // Draw the label, or the type name
         let text_pos = rect.center() - egui::Vec2::new(0.0, 5.0);
         painter.text(
             text_pos,
             egui::Align2::CENTER_CENTER,
-            type_name,
+            node.title(),
             egui::FontId::proportional(14.0),
             text_color,
         );
@@ -403,29 +1227,271 @@ impl Canvas {
         }
     }
 
-    fn draw_edge(&self, painter: &egui::Painter, start: egui::Pos2, end: egui::Pos2, color: egui::Color32) {
-        // Simple bezier curve for edges
-        let ctrl_offset = (end.y - start.y).abs() * 0.5;
-        let ctrl1 = egui::Pos2::new(start.x, start.y + ctrl_offset);
-        let ctrl2 = egui::Pos2::new(end.x, end.y - ctrl_offset);
+    /// A group's frame around its nodes, or its block when collapsed
+    fn draw_group(&self, painter: &egui::Painter, rect: egui::Rect, group: &crate::flow::NodeGroup) {
+        let accent = tag_color(group.color.as_deref()).unwrap_or(egui::Color32::from_rgb(110, 140, 200));
+        let name = match group.icon.as_deref().filter(|i| !i.is_empty()) {
+            Some(icon) => format!("{} {}", icon, group.name),
+            None => group.name.clone(),
+        };
+        if group.collapsed {
+            let block = egui::Color32::from_rgb(accent.r() / 2, accent.g() / 2, accent.b() / 2);
+            painter.rect_filled(rect, Self::NODE_ROUNDING, block);
+            painter.rect_stroke(rect, Self::NODE_ROUNDING, egui::Stroke::new(2.0, accent));
+            if self.simplified() {
+                return;
+            }
+            painter.text(
+                rect.center() - egui::Vec2::new(0.0, 8.0),
+                egui::Align2::CENTER_CENTER,
+                format!("▸ {}", name),
+                egui::FontId::proportional(14.0),
+                egui::Color32::WHITE,
+            );
+            painter.text(
+                rect.center() + egui::Vec2::new(0.0, 12.0),
+                egui::Align2::CENTER_CENTER,
+                format!("{} nodes", group.nodes.len()),
+                egui::FontId::proportional(10.0),
+                egui::Color32::from_rgba_unmultiplied(255, 255, 255, 180),
+            );
+        } else {
+            let tint = egui::Color32::from_rgba_unmultiplied(accent.r(), accent.g(), accent.b(), 25);
+            painter.rect_filled(rect, Self::NODE_ROUNDING, tint);
+            painter.rect_stroke(rect, Self::NODE_ROUNDING, egui::Stroke::new(1.5, accent));
+            if self.simplified() {
+                return;
+            }
+            painter.text(
+                rect.min + egui::Vec2::new(8.0, Self::GROUP_TITLE_HEIGHT * self.zoom / 2.0),
+                egui::Align2::LEFT_CENTER,
+                format!("▾ {}", name),
+                egui::FontId::proportional(13.0),
+                egui::Color32::WHITE,
+            );
+        }
+    }
 
-        // Draw bezier with line segments
-        let segments = 20;
-        for i in 0..segments {
-            let t1 = i as f32 / segments as f32;
-            let t2 = (i + 1) as f32 / segments as f32;
+    /// Offset from a node's left edge of input port `index` of `count`;
+    /// single-input nodes are fed at the middle
+    fn port_x(index: usize, count: usize) -> f32 {
+        Self::NODE_WIDTH * (index + 1) as f32 / (count.max(1) + 1) as f32
+    }
 
-            let p1 = self.bezier_point(start, ctrl1, ctrl2, end, t1);
-            let p2 = self.bezier_point(start, ctrl1, ctrl2, end, t2);
+    /// Input handles along the top of a node box (one per named port, or a
+    /// single one) and its output handle at the bottom
+    fn node_handles(&self, node_id: &str, type_name: &str, rect: egui::Rect) -> Vec<Handle> {
+        let (input_type, output_type) = crate::nodes::port_types(type_name);
+        let ports = crate::nodes::input_ports(type_name);
+        let inputs = if crate::nodes::has_input(type_name) { ports.len().max(1) } else { 0 };
 
-            painter.line_segment(
-                [p1, p2],
-                egui::Stroke::new(Self::EDGE_THICKNESS, color),
+        let mut handles: Vec<Handle> = (0..inputs)
+            .map(|i| Handle {
+                node_id: node_id.to_string(),
+                port: ports.get(i).map(|p| p.to_string()),
+                is_input: true,
+                pos: egui::Pos2::new(rect.min.x + Self::port_x(i, ports.len()) * self.zoom, rect.min.y),
+                data_type: input_type,
+            })
+            .collect();
+        handles.push(Handle {
+            node_id: node_id.to_string(),
+            port: None,
+            is_input: false,
+            pos: egui::Pos2::new(rect.center().x, rect.max.y),
+            data_type: output_type,
+        });
+        handles
+    }
+
+    /// The input (or output) handle nearest `pos`, within the hit distance
+    fn handle_at(handles: &[Handle], pos: egui::Pos2, is_input: bool) -> Option<&Handle> {
+        handles.iter()
+            .filter(|h| h.is_input == is_input)
+            .map(|h| (h, h.pos.distance(pos)))
+            .filter(|(_, distance)| *distance <= Self::HANDLE_HIT_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(h, _)| h)
+    }
+
+    /// Connect `source`'s output to an input handle. A named port takes one
+    /// edge, so connecting to it replaces whatever was there.
+    fn connect(flow: &mut crate::flow::Flow, source: &str, target: &Handle) {
+        if target.node_id == source {
+            return;
+        }
+        if let Some(port) = &target.port {
+            flow.edges.retain(|e| e.target != target.node_id || e.target_handle.as_ref() != Some(port));
+        }
+        let exists = flow.edges.iter().any(|e| {
+            e.source == source && e.target == target.node_id && e.target_handle == target.port
+        });
+        if !exists {
+            flow.edges.push(crate::flow::Edge {
+                source: source.to_string(),
+                target: target.node_id.clone(),
+                source_handle: None,
+                target_handle: target.port.clone(),
+            });
+        }
+    }
+
+    /// Move one end of edge `key` to `node_id`. A new target port replaces
+    /// whatever was connected to it. Returns the edge's new key, or None if
+    /// the move would make a self-loop or duplicate an edge.
+    fn reconnect_edge(
+        flow: &mut crate::flow::Flow,
+        key: &EdgeKey,
+        is_target: bool,
+        node_id: &str,
+        port: Option<String>,
+    ) -> Option<EdgeKey> {
+        let (source, target, handle) = key.clone();
+        let new_key = if is_target {
+            (source, node_id.to_string(), port)
+        } else {
+            (node_id.to_string(), target, handle)
+        };
+        if new_key.0 == new_key.1 || flow.edges.iter().any(|e| edge_key(e) == new_key) {
+            return None;
+        }
+
+        if is_target && new_key.2.is_some() {
+            flow.edges.retain(|e| e.target != new_key.1 || e.target_handle != new_key.2);
+        }
+        let edge = flow.edges.iter_mut().find(|e| edge_key(e) == *key)?;
+        edge.source = new_key.0.clone();
+        edge.target = new_key.1.clone();
+        edge.target_handle = new_key.2.clone();
+        Some(new_key)
+    }
+
+    /// Index of the edge whose curve passes nearest `pos`, within the hit
+    /// distance; only edges whose bounds are near `pos` are measured
+    fn edge_at(paths: &[EdgePath], pos: egui::Pos2) -> Option<usize> {
+        paths.iter()
+            .filter(|path| path.bounds.expand(Self::EDGE_HIT_DISTANCE).contains(pos))
+            .map(|path| {
+                let distance = path.points.windows(2)
+                    .map(|w| Self::distance_to_segment(pos, w[0], w[1]))
+                    .fold(f32::INFINITY, f32::min);
+                (path.index, distance)
+            })
+            .filter(|(_, distance)| *distance <= Self::EDGE_HIT_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+    }
+
+    fn distance_to_segment(p: egui::Pos2, a: egui::Pos2, b: egui::Pos2) -> f32 {
+        let ab = b - a;
+        let t = if ab.length_sq() > 0.0 { ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0) } else { 0.0 };
+        p.distance(a + ab * t)
+    }
+
+    /// Handles colored by the data type they carry; named inputs are labelled
+    fn draw_handles(&self, painter: &egui::Painter, handles: &[Handle]) {
+        for handle in handles {
+            painter.circle(
+                handle.pos,
+                Self::HANDLE_RADIUS,
+                Self::data_type_color(handle.data_type),
+                egui::Stroke::new(1.0, egui::Color32::WHITE),
             );
+            if let Some(port) = &handle.port {
+                painter.text(
+                    handle.pos + egui::Vec2::new(0.0, 12.0 * self.zoom),
+                    egui::Align2::CENTER_CENTER,
+                    port,
+                    egui::FontId::proportional(10.0),
+                    egui::Color32::from_rgba_unmultiplied(255, 255, 255, 180),
+                );
+            }
+        }
+    }
+
+    fn data_type_color(data_type: crate::nodes::DataType) -> egui::Color32 {
+        use crate::nodes::DataType;
+        match data_type {
+            DataType::Any => egui::Color32::from_gray(150),
+            DataType::String => egui::Color32::from_rgb(230, 180, 80),
+            DataType::Number => egui::Color32::from_rgb(90, 160, 240),
+            DataType::Boolean => egui::Color32::from_rgb(240, 110, 110),
+            DataType::Object => egui::Color32::from_rgb(170, 130, 230),
+            DataType::Array => egui::Color32::from_rgb(100, 210, 140),
+            DataType::Tensor => egui::Color32::from_rgb(230, 90, 230),
+            DataType::Bytes => egui::Color32::from_rgb(120, 200, 220),
+        }
+    }
+
+    fn draw_edge(&self, painter: &egui::Painter, start: egui::Pos2, end: egui::Pos2, color: egui::Color32, width: f32) {
+        let points = Self::edge_points(self.edge_style, start, end, self.zoom);
+        self.draw_path(painter, &points, color, width);
+    }
+
+    /// An edge as one polyline shape rather than a shape per segment, with
+    /// an arrowhead at its end unless zoomed out
+    fn draw_path(&self, painter: &egui::Painter, points: &[egui::Pos2], color: egui::Color32, width: f32) {
+        painter.add(egui::Shape::line(points.to_vec(), egui::Stroke::new(width, color)));
+        if self.simplified() {
+            return;
+        }
+        let [.., from, end] = points else { return };
+        let direction = (*end - *from).normalized();
+        if !direction.x.is_finite() || !direction.y.is_finite() {
+            return;
         }
+        // The tip stops at the input handle's rim
+        let size = Self::ARROW_SIZE * self.zoom.clamp(0.6, 1.5);
+        let tip = *end - direction * Self::HANDLE_RADIUS;
+        let base = tip - direction * size;
+        let side = direction.rot90() * size * 0.5;
+        painter.add(egui::Shape::convex_polygon(vec![tip, base + side, base - side], color, egui::Stroke::NONE));
+    }
+
+    /// Points along an edge drawn in `style`, between ends in a space
+    /// `scale` times canvas space
+    fn edge_points(style: crate::flow::EdgeStyle, start: egui::Pos2, end: egui::Pos2, scale: f32) -> Vec<egui::Pos2> {
+        use crate::flow::EdgeStyle;
+        let margin = Self::ELBOW_MARGIN * scale;
+        let width = Self::NODE_WIDTH * scale;
+        match style {
+            EdgeStyle::Bezier => Self::bezier_points(start, end),
+            EdgeStyle::Straight => vec![start, end],
+            // Down, across and down into a target below; around the side into one above
+            EdgeStyle::Orthogonal if end.y - start.y >= margin * 2.0 => {
+                let middle = (start.y + end.y) / 2.0;
+                vec![start, egui::pos2(start.x, middle), egui::pos2(end.x, middle), end]
+            }
+            EdgeStyle::Orthogonal => {
+                let side = if (end.x - start.x).abs() > width {
+                    (start.x + end.x) / 2.0
+                } else {
+                    start.x.max(end.x) + width * 0.75
+                };
+                vec![
+                    start,
+                    egui::pos2(start.x, start.y + margin),
+                    egui::pos2(side, start.y + margin),
+                    egui::pos2(side, end.y - margin),
+                    egui::pos2(end.x, end.y - margin),
+                    end,
+                ]
+            }
+        }
+    }
+
+    /// Points along an edge's bezier curve, shared by drawing and hit-testing
+    fn bezier_points(start: egui::Pos2, end: egui::Pos2) -> Vec<egui::Pos2> {
+        let ctrl_offset = (end.y - start.y).abs() * 0.5;
+        let ctrl1 = egui::Pos2::new(start.x, start.y + ctrl_offset);
+        let ctrl2 = egui::Pos2::new(end.x, end.y - ctrl_offset);
+
+        (0..=Self::EDGE_SEGMENTS)
+            .map(|i| Self::bezier_point(start, ctrl1, ctrl2, end, i as f32 / Self::EDGE_SEGMENTS as f32))
+            .collect()
     }
 
-    fn bezier_point(&self, p0: egui::Pos2, p1: egui::Pos2, p2: egui::Pos2, p3: egui::Pos2, t: f32) -> egui::Pos2 {
+    fn bezier_point(p0: egui::Pos2, p1: egui::Pos2, p2: egui::Pos2, p3: egui::Pos2, t: f32) -> egui::Pos2 {
         let t2 = t * t;
         let t3 = t2 * t;
         let mt = 1.0 - t;
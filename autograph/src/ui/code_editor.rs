@@ -0,0 +1,85 @@
+//! HLX Code Editor
+//!
+//! Monospace editor with basic syntax highlighting for inline HLX, used
+//! by the `hlx_script` node's properties.
+
+use eframe::egui;
+use egui::text::LayoutJob;
+use egui::{Color32, FontId, TextFormat};
+
+const KEYWORDS: &[&str] = &[
+    "fn", "let", "return", "if", "else", "while", "loop", "for", "in", "break", "continue",
+];
+const LITERALS: &[&str] = &["true", "false", "null"];
+
+const KEYWORD_COLOR: Color32 = Color32::from_rgb(198, 120, 221);
+const LITERAL_COLOR: Color32 = Color32::from_rgb(209, 154, 102);
+const STRING_COLOR: Color32 = Color32::from_rgb(152, 195, 121);
+const COMMENT_COLOR: Color32 = Color32::from_rgb(110, 118, 129);
+const CALL_COLOR: Color32 = Color32::from_rgb(97, 175, 239);
+
+/// Multi-line HLX editor; returns the text edit's response
+pub fn show(ui: &mut egui::Ui, code: &mut String, rows: usize) -> egui::Response {
+    let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+        let mut job = highlight(text, ui.visuals().text_color());
+        job.wrap.max_width = wrap_width;
+        ui.fonts(|f| f.layout_job(job))
+    };
+
+    ui.add(
+        egui::TextEdit::multiline(code)
+            .desired_width(ui.available_width())
+            .desired_rows(rows)
+            .code_editor()
+            .layouter(&mut layouter),
+    )
+}
+
+/// Colour keywords, literals, strings, comments and function calls
+pub fn highlight(code: &str, default: Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let mut rest = code;
+
+    while !rest.is_empty() {
+        let (len, color) = if rest.starts_with("//") {
+            (rest.find('\n').unwrap_or(rest.len()), COMMENT_COLOR)
+        } else if rest.starts_with('"') {
+            (string_len(rest), STRING_COLOR)
+        } else if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            (rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '.').unwrap_or(rest.len()), LITERAL_COLOR)
+        } else if rest.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            let len = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+            let word = &rest[..len];
+            let color = if KEYWORDS.contains(&word) {
+                KEYWORD_COLOR
+            } else if LITERALS.contains(&word) {
+                LITERAL_COLOR
+            } else if rest[len..].starts_with('(') {
+                CALL_COLOR
+            } else {
+                default
+            };
+            (len, color)
+        } else {
+            (rest.chars().next().map_or(1, char::len_utf8), default)
+        };
+
+        job.append(&rest[..len], 0.0, TextFormat::simple(FontId::monospace(12.0), color));
+        rest = &rest[len..];
+    }
+    job
+}
+
+/// Length of a string literal at the start of `text`, including its quotes
+fn string_len(text: &str) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return i + 1,
+            '\n' => return i,
+            _ => escaped = false,
+        }
+    }
+    text.len()
+}
@@ -0,0 +1,289 @@
+//! Flow Browser
+//!
+//! Lists every flow in `flows/` with when it was last saved and a thumbnail
+//! of its graph, to open, rename, duplicate or delete it without typing
+//! its name. Deleting shows what depends on the flow first, and renaming
+//! points the flows calling it as a subflow at the new name.
+
+use eframe::egui;
+use crate::flow::Flow;
+use crate::runs::{format_timestamp, RunStore};
+use std::path::{Path, PathBuf};
+
+/// A flow file found in the flows directory
+struct FlowEntry {
+    name: String,
+    /// Last modified, Unix ms
+    modified: u64,
+    flow: Flow,
+}
+
+/// Flow browser state
+#[derive(Default)]
+pub struct FlowBrowser {
+    entries: Vec<FlowEntry>,
+
+    /// Whether `entries` reflects the directory
+    loaded: bool,
+
+    /// Flow being renamed, with the name typed so far
+    renaming: Option<(String, String)>,
+
+    /// Flow awaiting delete confirmation, with what deleting it would break
+    confirm_delete: Option<(String, Vec<String>)>,
+
+    /// Name typed for "Save current as"
+    save_as: String,
+
+    /// Last failed file operation
+    error: Option<String>,
+}
+
+/// File operation picked on an entry this frame
+enum Operation {
+    StartRename(String),
+    CancelRename,
+    Rename(String, String),
+    Duplicate(String),
+    AskDelete(String),
+    CancelDelete,
+    Delete(String),
+}
+
+/// What the user asked for this frame
+pub enum FlowBrowserAction {
+    None,
+    Open(String),
+    /// Save the editor's flow under a new name
+    SaveAs(String),
+    /// A flow was renamed; `callers` are the flows whose subflow nodes
+    /// now call it by its new name
+    Renamed { from: String, to: String, callers: Vec<String> },
+    Deleted(String),
+}
+
+/// Whether `name` is usable as a flow file name
+pub fn is_valid_flow_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl FlowBrowser {
+    /// Re-read the flows directory on next show
+    pub fn invalidate(&mut self) {
+        self.loaded = false;
+    }
+
+    fn load(&mut self, dir: &Path) {
+        let mut entries: Vec<FlowEntry> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = path.file_stem()?.to_str()?.to_string();
+                let modified = entry.metadata().ok()?.modified().ok()?
+                    .duration_since(std::time::UNIX_EPOCH).ok()?
+                    .as_millis() as u64;
+                let flow = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+                Some(FlowEntry { name, modified, flow })
+            })
+            .collect();
+        entries.sort_by(|a, b| b.modified.cmp(&a.modified));
+        self.entries = entries;
+        self.loaded = true;
+    }
+
+    /// Draw the browser; `runs` tells deletes whether the flow still runs
+    pub fn show(&mut self, ui: &mut egui::Ui, dir: &Path, current: &str, runs: &RunStore) -> FlowBrowserAction {
+        let mut action = FlowBrowserAction::None;
+        if !self.loaded {
+            self.load(dir);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{} flow(s) in {}", self.entries.len(), dir.display()));
+            if ui.button("⟳ Refresh").clicked() {
+                self.invalidate();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.save_as).hint_text("new_flow_name").desired_width(160.0));
+            let valid = is_valid_flow_name(&self.save_as);
+            if ui.add_enabled(valid, egui::Button::new("💾 Save current as")).clicked() {
+                action = FlowBrowserAction::SaveAs(std::mem::take(&mut self.save_as));
+                self.invalidate();
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, format!("❌ {}", error));
+        }
+        ui.separator();
+
+        let mut operation = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for entry in &self.entries {
+                ui.horizontal(|ui| {
+                    thumbnail(ui, &entry.flow, egui::vec2(96.0, 64.0));
+                    ui.vertical(|ui| {
+                        let title = if entry.name == current { format!("{} (open)", entry.name) } else { entry.name.clone() };
+                        ui.strong(title);
+                        ui.weak(format!("{} · {} nodes", format_timestamp(entry.modified), entry.flow.nodes.len()));
+
+                        ui.horizontal(|ui| match &mut self.renaming {
+                            Some((from, to)) if *from == entry.name => {
+                                ui.add(egui::TextEdit::singleline(to).desired_width(140.0));
+                                if ui.add_enabled(is_valid_flow_name(to), egui::Button::new("✓")).clicked() {
+                                    operation = Some(Operation::Rename(from.clone(), to.clone()));
+                                }
+                                if ui.button("✖").clicked() {
+                                    operation = Some(Operation::CancelRename);
+                                }
+                            }
+                            _ if self.confirm_delete.as_ref().is_some_and(|(name, _)| *name == entry.name) => {
+                                ui.vertical(|ui| {
+                                    ui.colored_label(egui::Color32::YELLOW, "Delete this flow?");
+                                    for line in self.confirm_delete.iter().flat_map(|(_, impact)| impact) {
+                                        ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", line));
+                                    }
+                                    ui.horizontal(|ui| {
+                                        if ui.button("Delete").clicked() {
+                                            operation = Some(Operation::Delete(entry.name.clone()));
+                                        }
+                                        if ui.button("Cancel").clicked() {
+                                            operation = Some(Operation::CancelDelete);
+                                        }
+                                    });
+                                });
+                            }
+                            _ => {
+                                if ui.button("📂 Open").clicked() {
+                                    action = FlowBrowserAction::Open(entry.name.clone());
+                                }
+                                if ui.button("✏ Rename").clicked() {
+                                    operation = Some(Operation::StartRename(entry.name.clone()));
+                                }
+                                if ui.button("⧉ Duplicate").clicked() {
+                                    operation = Some(Operation::Duplicate(entry.name.clone()));
+                                }
+                                if ui.button("🗑 Delete").clicked() {
+                                    operation = Some(Operation::AskDelete(entry.name.clone()));
+                                }
+                            }
+                        });
+                    });
+                });
+                ui.separator();
+            }
+        });
+
+        let result = match operation {
+            None => return action,
+            Some(Operation::StartRename(name)) => {
+                self.renaming = Some((name.clone(), name));
+                Ok(FlowBrowserAction::None)
+            }
+            Some(Operation::CancelRename) => {
+                self.renaming = None;
+                Ok(FlowBrowserAction::None)
+            }
+            Some(Operation::Rename(from, to)) => self.rename(dir, &from, &to),
+            Some(Operation::Duplicate(name)) => self.duplicate(dir, &name),
+            Some(Operation::AskDelete(name)) => {
+                let impact = crate::impact::flow_impact(dir, &name, &[], &runs.list_for_flow(&name));
+                self.confirm_delete = Some((name, impact));
+                Ok(FlowBrowserAction::None)
+            }
+            Some(Operation::CancelDelete) => {
+                self.confirm_delete = None;
+                Ok(FlowBrowserAction::None)
+            }
+            Some(Operation::Delete(name)) => self.delete(dir, &name),
+        };
+        match result {
+            Ok(result) => {
+                self.error = None;
+                if !matches!(result, FlowBrowserAction::None) {
+                    action = result;
+                }
+            }
+            Err(e) => self.error = Some(e),
+        }
+        action
+    }
+
+    fn rename(&mut self, dir: &Path, from: &str, to: &str) -> Result<FlowBrowserAction, String> {
+        let target = flow_path(dir, to);
+        if from != to {
+            if target.exists() {
+                return Err(format!("A flow named '{}' already exists", to));
+            }
+            std::fs::rename(flow_path(dir, from), &target).map_err(|e| e.to_string())?;
+            // Versions follow the flow; a flow without any is fine
+            let _ = std::fs::rename(dir.join(".versions").join(from), dir.join(".versions").join(to));
+        }
+        self.renaming = None;
+        self.invalidate();
+        let callers = if from == to { Vec::new() } else { crate::impact::rename_subflow_callers(dir, from, to)? };
+        Ok(FlowBrowserAction::Renamed { from: from.to_string(), to: to.to_string(), callers })
+    }
+
+    fn duplicate(&mut self, dir: &Path, name: &str) -> Result<FlowBrowserAction, String> {
+        let copy = std::iter::once(format!("{}_copy", name))
+            .chain((2..).map(|i| format!("{}_copy_{}", name, i)))
+            .find(|copy| !flow_path(dir, copy).exists())
+            .unwrap();
+        std::fs::copy(flow_path(dir, name), flow_path(dir, &copy)).map_err(|e| e.to_string())?;
+        self.invalidate();
+        Ok(FlowBrowserAction::None)
+    }
+
+    fn delete(&mut self, dir: &Path, name: &str) -> Result<FlowBrowserAction, String> {
+        std::fs::remove_file(flow_path(dir, name)).map_err(|e| e.to_string())?;
+        self.confirm_delete = None;
+        self.invalidate();
+        Ok(FlowBrowserAction::Deleted(name.to_string()))
+    }
+}
+
+fn flow_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.json", name))
+}
+
+/// The flow's nodes and edges scaled into a box of `size`
+pub fn thumbnail(ui: &mut egui::Ui, flow: &Flow, size: egui::Vec2) -> egui::Response {
+    const NODE: egui::Vec2 = egui::vec2(150.0, 60.0);
+
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 3.0, egui::Color32::from_gray(25));
+
+    let positions: Vec<egui::Pos2> = flow.nodes.iter().filter_map(|n| n.position).map(|p| egui::pos2(p.x, p.y)).collect();
+    let Some(bounds) = positions.iter()
+        .map(|p| egui::Rect::from_min_size(*p, NODE))
+        .reduce(|a, b| a.union(b))
+    else {
+        return response;
+    };
+    let inner = rect.shrink(4.0);
+    let scale = (inner.width() / bounds.width()).min(inner.height() / bounds.height());
+    let to_thumb = |p: egui::Pos2| inner.min + (p - bounds.min) * scale;
+    let position = |id: &str| flow.nodes.iter().find(|n| n.id == id).and_then(|n| n.position);
+
+    for edge in &flow.edges {
+        if let (Some(source), Some(target)) = (position(&edge.source), position(&edge.target)) {
+            painter.line_segment(
+                [
+                    to_thumb(egui::pos2(source.x + NODE.x / 2.0, source.y + NODE.y)),
+                    to_thumb(egui::pos2(target.x + NODE.x / 2.0, target.y)),
+                ],
+                egui::Stroke::new(1.0, egui::Color32::GRAY),
+            );
+        }
+    }
+    for pos in positions {
+        let node = egui::Rect::from_min_size(to_thumb(pos), (NODE * scale).max(egui::vec2(2.0, 2.0)));
+        painter.rect_filled(node, 1.0, egui::Color32::from_rgb(70, 130, 180));
+    }
+    response
+}
@@ -0,0 +1,80 @@
+//! Generated Code Panel
+//!
+//! The HLX the flow compiles to, highlighted, with each line tied to the
+//! node that generated it: clicking a line selects its node, and the
+//! selected node's lines are highlighted, for debugging code generation.
+//! The flow is compiled again only when its revision changes.
+
+use eframe::egui;
+use crate::flow::Flow;
+use super::code_editor;
+use super::undo::Cached;
+
+const SELECTED_BACKGROUND: egui::Color32 = egui::Color32::from_rgb(60, 70, 40);
+
+/// Generated code panel state
+#[derive(Default)]
+pub struct GeneratedCode {
+    /// Show the `// @autograph-*` source map comments
+    show_source_map: bool,
+
+    /// Node whose lines were last scrolled to
+    scrolled_to: Option<String>,
+
+    /// The flow's source and the node owning each of its lines
+    compiled: Cached<(String, Vec<Option<String>>)>,
+}
+
+impl GeneratedCode {
+    /// Draw the code of `flow` at `revision`. Returns the node of a clicked line.
+    pub fn show(&mut self, ui: &mut egui::Ui, flow: &Flow, revision: u64, selected: Option<&str>) -> Option<String> {
+        let mut clicked = None;
+
+        let (source, owners) = self.compiled.get(revision, || {
+            let source = flow.compile_to_hlx();
+            let owners = crate::decompile::line_nodes(&source);
+            (source, owners)
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_source_map, "Show source map comments");
+            ui.weak("Click a line to select its node");
+        });
+
+        // Scroll to the selected node's code once, when it becomes selected
+        let scroll = selected.is_some() && selected != self.scrolled_to.as_deref();
+        self.scrolled_to = selected.map(str::to_string);
+        let mut scrolled = false;
+
+        egui::ScrollArea::vertical().id_source("generated_code").max_height(300.0).show(ui, |ui| {
+            ui.spacing_mut().item_spacing.y = 0.0;
+            for (number, (line, owner)) in source.lines().zip(owners).enumerate() {
+                if !self.show_source_map && line.trim_start().starts_with("// @autograph-") {
+                    continue;
+                }
+
+                let is_selected = owner.is_some() && owner.as_deref() == selected;
+                let mut job = code_editor::highlight(&format!("{:>4}  {}", number + 1, line), ui.visuals().text_color());
+                if is_selected {
+                    for section in &mut job.sections {
+                        section.format.background = SELECTED_BACKGROUND;
+                    }
+                }
+
+                let mut response = ui.add(egui::Label::new(job).sense(egui::Sense::click()));
+                if let Some(owner) = owner {
+                    response = response.on_hover_text(format!("Generated by {}", owner));
+                    if response.clicked() {
+                        clicked = Some(owner.clone());
+                    }
+                }
+                if scroll && is_selected && !scrolled {
+                    response.scroll_to_me(Some(egui::Align::Center));
+                    scrolled = true;
+                }
+            }
+        });
+
+        clicked
+    }
+}
@@ -0,0 +1,177 @@
+//! Run History Panel
+//!
+//! Browse persisted executions of the current flow, restore their
+//! captured outputs into the editor, compare two of them and export
+//! reports of them.
+
+use eframe::egui;
+use crate::report::ReportFormat;
+use crate::runs::{format_timestamp, RunRecord, RunStatus, RunStore};
+
+/// Run history browser state
+#[derive(Default)]
+pub struct HistoryPanel {
+    /// Runs of the flow currently listed
+    runs: Vec<RunRecord>,
+
+    /// Flow the list was loaded for
+    loaded_for: Option<String>,
+
+    /// Index of the run being inspected
+    selected: Option<usize>,
+
+    /// Index of the run marked to compare the inspected run with
+    compare_with: Option<usize>,
+
+    /// Only list runs with these labels (`key=value,key=value`)
+    label_filter: String,
+}
+
+/// What the panel asks the editor to do this frame
+pub enum HistoryAction {
+    None,
+    /// Load the run's outputs into the editor
+    Restore(RunRecord),
+    /// Save the run's report
+    ExportReport(RunRecord, ReportFormat),
+    /// Show two runs side by side
+    Compare(RunRecord, RunRecord),
+}
+
+impl HistoryPanel {
+    /// Reload the run list on next show
+    pub fn invalidate(&mut self) {
+        self.loaded_for = None;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, flow_name: &str, store: &RunStore) -> HistoryAction {
+        let mut action = HistoryAction::None;
+
+        if self.loaded_for.as_deref() != Some(flow_name) {
+            self.runs = store.list_for_flow(flow_name);
+            self.loaded_for = Some(flow_name.to_string());
+            self.selected = None;
+            self.compare_with = None;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{} run(s) of '{}'", self.runs.len(), flow_name));
+            if ui.button("⟳ Refresh").clicked() {
+                self.invalidate();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Labels:");
+            ui.add(egui::TextEdit::singleline(&mut self.label_filter).hint_text("customer=acme"));
+        });
+        let filter = match crate::runs::parse_labels(&self.label_filter) {
+            Ok(filter) => filter,
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, format!("❌ {}", e));
+                Default::default()
+            }
+        };
+        ui.separator();
+
+        if self.runs.is_empty() {
+            ui.label("No runs recorded yet.");
+            return HistoryAction::None;
+        }
+
+        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+            for (idx, run) in self.runs.iter().enumerate().filter(|(_, run)| run.has_labels(&filter)) {
+                let (icon, color) = match run.status {
+                    RunStatus::Queued => ("⏸", egui::Color32::GRAY),
+                    RunStatus::Running => ("⏳", egui::Color32::YELLOW),
+                    RunStatus::Success => ("✓", egui::Color32::GREEN),
+                    RunStatus::Error => ("❌", egui::Color32::RED),
+                    RunStatus::Interrupted => ("⚠", egui::Color32::from_rgb(255, 165, 0)),
+                };
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.compare_with, Some(idx), "")
+                        .on_hover_text("Mark this run to compare the selected run with");
+                    ui.colored_label(color, icon);
+                    let mut text = format!("{}  ({}ms)", format_timestamp(run.started_at), run.duration_ms);
+                    if run.attempt > 1 {
+                        text.push_str(&format!("  ↻ attempt {}", run.attempt));
+                    }
+                    if run.retry_at.is_some() {
+                        text.push_str("  ⏱ retry scheduled");
+                    }
+                    if ui.selectable_label(self.selected == Some(idx), text).clicked() {
+                        self.selected = Some(idx);
+                    }
+                });
+            }
+        });
+
+        if let Some(run) = self.selected.and_then(|idx| self.runs.get(idx)) {
+            ui.separator();
+            ui.label(format!("Run: {}", run.id));
+            if !run.labels.is_empty() {
+                let labels: Vec<String> = run.labels.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                ui.label(format!("Labels: {}", labels.join(", ")));
+            }
+            if let Some(chain) = &run.chain {
+                ui.label(format!("Attempt {} of retry chain {}", run.attempt, chain));
+            }
+            if let Some(due) = run.retry_at {
+                ui.label(format!("Retry scheduled for {}", format_timestamp(due)));
+            }
+            ui.label(format!("Input: {}", run.input));
+
+            if let Some(stats) = &run.gpu_memory {
+                ui.label(format!("GPU memory: {}", stats.summary()));
+            }
+            if let Some(error) = &run.error {
+                ui.colored_label(egui::Color32::RED, format!("Error: {}", error));
+            }
+            if let Some(result) = &run.result {
+                ui.label("Result:");
+                ui.monospace(serde_json::to_string_pretty(result).unwrap_or_default());
+            }
+
+            ui.collapsing(format!("Node outputs ({})", run.node_outputs.len()), |ui| {
+                for (node_id, output) in &run.node_outputs {
+                    ui.monospace(format!("{}: {}", node_id, output));
+                }
+            });
+
+            ui.collapsing("Logs", |ui| {
+                for line in &run.log_lines() {
+                    ui.monospace(line);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Restore outputs")
+                    .on_hover_text("Load this run's node outputs into the editor for inspection and replay")
+                    .clicked()
+                {
+                    action = HistoryAction::Restore(run.clone());
+                }
+                let other = self.compare_with.filter(|i| Some(*i) != self.selected).and_then(|i| self.runs.get(i));
+                if ui.add_enabled(other.is_some(), egui::Button::new("⇄ Compare"))
+                    .on_hover_text("Compare with the run marked with ◉, highlighting nodes whose outputs or durations differ")
+                    .clicked()
+                {
+                    if let Some(other) = other {
+                        action = HistoryAction::Compare(other.clone(), run.clone());
+                    }
+                }
+                ui.label("📄 Report:");
+                for format in [ReportFormat::Html, ReportFormat::Json] {
+                    if ui.button(format.extension().to_uppercase())
+                        .on_hover_text("Save a self-contained report of this run to share")
+                        .clicked()
+                    {
+                        action = HistoryAction::ExportReport(run.clone(), format);
+                    }
+                }
+            });
+        }
+
+        action
+    }
+}
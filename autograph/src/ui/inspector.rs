@@ -0,0 +1,208 @@
+//! Time-Travel Inspector
+//!
+//! Rewinds the canvas to the moment a timeline entry executed, using the
+//! per-node outputs captured during the run. The Inspector window shows a
+//! selected node's full input and output at the chosen timeline entry (or
+//! at the end of the run) as JSON trees.
+
+use eframe::egui;
+use crate::flow::Flow;
+use super::{ExecutionState, NodeExecution};
+use super::json_tree;
+use super::timeline::TimelineEntry;
+use std::collections::HashMap;
+
+/// The canvas as it was when one timeline entry executed
+pub struct Moment {
+    /// Timeline entry being inspected
+    pub entry_idx: usize,
+
+    /// Node that executed at this moment
+    pub node_id: String,
+
+    /// Node states to draw instead of the live ones
+    pub states: HashMap<String, NodeExecution>,
+}
+
+/// What the user asked the inspector to do this frame
+pub enum InspectorAction {
+    None,
+    /// Jump to another timeline entry
+    Step(usize),
+    /// Leave time travel and show the live state again
+    Exit,
+}
+
+impl Moment {
+    /// Rebuild the node states at a timeline entry.
+    ///
+    /// The entry's node and everything that ran before it, plus its graph
+    /// ancestors, are completed with their captured values; the rest,
+    /// including everything downstream of it, is pending.
+    pub fn at(
+        flow: &Flow,
+        entries: &[TimelineEntry],
+        entry_idx: usize,
+        captured: &HashMap<String, serde_json::Value>,
+    ) -> Option<Self> {
+        let entry = entries.get(entry_idx)?;
+
+        let upstream = flow.upstream_ids(&entry.node_id);
+        let downstream = flow.downstream_ids(&entry.node_id);
+        let ran_before: Vec<&str> = entries[..=entry_idx].iter().map(|e| e.node_id.as_str()).collect();
+
+        let states = flow.nodes.iter()
+            .map(|node| {
+                // Both sets include the entry's own node, which has just run
+                let done = node.id == entry.node_id
+                    || (ran_before.contains(&node.id.as_str()) || upstream.contains(&node.id))
+                        && !downstream.contains(&node.id);
+
+                let exec = if done {
+                    NodeExecution::new(
+                        ExecutionState::Completed,
+                        captured.get(&node.id).map(|v| serde_json::to_string_pretty(v).unwrap_or_default()),
+                        entries.iter().find(|e| e.node_id == node.id).map(|e| e.duration_ms),
+                    )
+                } else {
+                    NodeExecution::new(ExecutionState::Pending, None, None)
+                };
+                (node.id.clone(), exec)
+            })
+            .collect();
+
+        Some(Self {
+            entry_idx,
+            node_id: entry.node_id.clone(),
+            states,
+        })
+    }
+}
+
+/// Draw the inspected node's input and output side by side
+pub fn show(
+    ui: &mut egui::Ui,
+    flow: &Flow,
+    moment: &Moment,
+    entry_count: usize,
+    captured: &HashMap<String, serde_json::Value>,
+    flow_input: &serde_json::Value,
+) -> InspectorAction {
+    let mut action = InspectorAction::None;
+
+    ui.horizontal(|ui| {
+        if ui.add_enabled(moment.entry_idx > 0, egui::Button::new("⏮ Prev")).clicked() {
+            action = InspectorAction::Step(moment.entry_idx - 1);
+        }
+        ui.label(format!("Step {} of {}", moment.entry_idx + 1, entry_count));
+        if ui.add_enabled(moment.entry_idx + 1 < entry_count, egui::Button::new("Next ⏭")).clicked() {
+            action = InspectorAction::Step(moment.entry_idx + 1);
+        }
+        ui.separator();
+        if ui.button("⏹ Back to live").clicked() {
+            action = InspectorAction::Exit;
+        }
+    });
+
+    let type_name = flow.nodes.iter()
+        .find(|n| n.id == moment.node_id)
+        .map(|n| n.type_name.as_str())
+        .unwrap_or("?");
+    ui.heading(format!("{} ({})", moment.node_id, type_name));
+    ui.separator();
+
+    values(ui, flow, &moment.node_id, captured, flow_input);
+
+    action
+}
+
+/// Draw the selected node's input and output, at the inspected timeline
+/// entry if time-travelling and otherwise at the end of the loaded run
+pub fn show_node(
+    ui: &mut egui::Ui,
+    flow: &Flow,
+    node_id: Option<&str>,
+    moment: Option<&Moment>,
+    entries: &[TimelineEntry],
+    captured: &HashMap<String, serde_json::Value>,
+    flow_input: &serde_json::Value,
+) -> InspectorAction {
+    let mut action = InspectorAction::None;
+
+    let Some(node) = node_id.and_then(|id| flow.nodes.iter().find(|n| n.id == id)) else {
+        ui.label("Select a node on the canvas to inspect its values.");
+        return action;
+    };
+    ui.heading(format!("{} ({})", node.id, node.type_name));
+
+    let entry_label = |idx: usize| {
+        let entry = &entries[idx];
+        format!("Step {} · {} · {}ms", idx + 1, entry.node_id, entry.duration_ms)
+    };
+    let selected = match moment {
+        Some(moment) => entry_label(moment.entry_idx),
+        None => "End of run".to_string(),
+    };
+    egui::ComboBox::from_label("Timeline entry")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(moment.is_none(), "End of run").clicked() {
+                action = InspectorAction::Exit;
+            }
+            // Entries of this node first, then the rest of the run
+            let (own, others): (Vec<usize>, Vec<usize>) = (0..entries.len()).partition(|&i| entries[i].node_id == node.id);
+            for idx in own.into_iter().chain(others) {
+                let current = moment.is_some_and(|m| m.entry_idx == idx);
+                if ui.selectable_label(current, entry_label(idx)).clicked() {
+                    action = InspectorAction::Step(idx);
+                }
+            }
+        });
+    ui.separator();
+
+    let not_run = moment
+        .and_then(|m| m.states.get(&node.id))
+        .is_some_and(|exec| exec.state == ExecutionState::Pending);
+    if not_run {
+        ui.label("(not run yet at this step)");
+    } else {
+        values(ui, flow, &node.id, captured, flow_input);
+    }
+
+    action
+}
+
+/// A node's input and output side by side
+fn values(
+    ui: &mut egui::Ui,
+    flow: &Flow,
+    node_id: &str,
+    captured: &HashMap<String, serde_json::Value>,
+    flow_input: &serde_json::Value,
+) {
+    // Nodes without an incoming edge read the flow input
+    let (input_label, input) = match flow.edges.iter().find(|e| e.target == node_id) {
+        Some(edge) => (format!("Input (from {})", edge.source), captured.get(&edge.source)),
+        None => ("Input (flow input)".to_string(), Some(flow_input)),
+    };
+    let output = captured.get(node_id);
+
+    ui.columns(2, |columns| {
+        value_column(&mut columns[0], &format!("inspector_input_{}", node_id), &input_label, input);
+        value_column(&mut columns[1], &format!("inspector_output_{}", node_id), "Output", output);
+    });
+}
+
+fn value_column(ui: &mut egui::Ui, id: &str, label: &str, value: Option<&serde_json::Value>) {
+    ui.strong(label);
+    match value {
+        Some(value) => {
+            egui::ScrollArea::vertical().id_source(id).max_height(400.0).show(ui, |ui| {
+                json_tree::show(ui, id, value);
+            });
+        }
+        None => {
+            ui.label("(not captured)");
+        }
+    }
+}
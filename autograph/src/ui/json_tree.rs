@@ -0,0 +1,226 @@
+//! JSON Tree View
+//!
+//! Shows a JSON value as collapsible objects and arrays instead of a wall
+//! of pretty-printed text. The key search highlights matching keys and
+//! opens the branches leading to them, the value filter hides every leaf
+//! whose value doesn't contain it, and right-clicking any row copies its
+//! JSONPath (e.g. `$.items[0].name`) or its value.
+
+use eframe::egui;
+use serde_json::Value as JsonValue;
+
+/// Most children drawn under one object or array
+const MAX_CHILDREN: usize = 500;
+
+/// Longest text `show_text` parses into a tree; longer text is shown as is
+const MAX_TREE_CHARS: usize = 256 * 1024;
+
+/// A tree's key search and value filter, kept in egui's memory
+#[derive(Clone, Default)]
+struct Search {
+    keys: String,
+    values: String,
+}
+
+/// Lowercase queries of a search, empty when unset
+struct Query {
+    keys: String,
+    values: String,
+}
+
+/// Draw `value` as a tree with a key search and value filter; `id_source`
+/// keeps each tree's search and open branches apart
+pub fn show(ui: &mut egui::Ui, id_source: &str, value: &JsonValue) {
+    let id = ui.make_persistent_id(id_source);
+    let mut search: Search = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+
+    // Scalars have nothing to expand or search
+    if !value.is_object() && !value.is_array() {
+        leaf(ui, "$", None, value, &Query { keys: String::new(), values: String::new() });
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("🔑");
+        ui.add(egui::TextEdit::singleline(&mut search.keys).hint_text("Search keys").desired_width(120.0));
+        ui.label("🔍");
+        ui.add(egui::TextEdit::singleline(&mut search.values).hint_text("Filter values").desired_width(120.0));
+        if (!search.keys.is_empty() || !search.values.is_empty()) && ui.small_button("✖").clicked() {
+            search = Search::default();
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(id, search.clone()));
+
+    let query = Query {
+        keys: search.keys.trim().to_lowercase(),
+        values: search.values.trim().to_lowercase(),
+    };
+    if !query.keys.is_empty() {
+        ui.weak(format!("{} matching keys", count_keys(value, &query)));
+    }
+    if !visible(value, &query.values) {
+        ui.weak("No values match the filter");
+        return;
+    }
+    node(ui, id, "$", None, value, &query);
+}
+
+/// `show` for a value held as text, such as a pretty-printed output; text
+/// that isn't JSON, or is too long to expand, is shown as it is
+pub fn show_text(ui: &mut egui::Ui, id_source: &str, text: &str) {
+    let value = (text.len() <= MAX_TREE_CHARS)
+        .then(|| serde_json::from_str::<JsonValue>(text).ok())
+        .flatten();
+    match value {
+        Some(value) => show(ui, id_source, &value),
+        None => {
+            ui.monospace(text);
+        }
+    }
+}
+
+/// A key in an object or an index in an array
+enum Step<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl Step<'_> {
+    fn key(&self) -> Option<&str> {
+        match self {
+            Step::Key(key) => Some(key),
+            Step::Index(_) => None,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Step::Key(key) => key.to_string(),
+            Step::Index(i) => i.to_string(),
+        }
+    }
+}
+
+/// JSONPath of a child, e.g. `$.items` + `0` = `$.items[0]`. Keys that
+/// aren't plain identifiers are bracketed: `$["first name"]`.
+fn child_path(parent: &str, step: &Step) -> String {
+    match step {
+        Step::Index(i) => format!("{}[{}]", parent, i),
+        Step::Key(key) => {
+            let plain = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if plain {
+                format!("{}.{}", parent, key)
+            } else {
+                format!("{}[{}]", parent, JsonValue::from(*key))
+            }
+        }
+    }
+}
+
+fn children(value: &JsonValue) -> Vec<(Step, &JsonValue)> {
+    match value {
+        JsonValue::Object(map) => map.iter().map(|(k, v)| (Step::Key(k), v)).collect(),
+        JsonValue::Array(items) => items.iter().enumerate().map(|(i, v)| (Step::Index(i), v)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn key_matches(step: Option<&Step>, query: &str) -> bool {
+    !query.is_empty() && step.and_then(Step::key).is_some_and(|k| k.to_lowercase().contains(query))
+}
+
+/// Whether a value has a leaf containing the (lowercase) filter; everything
+/// is visible without one
+fn visible(value: &JsonValue, filter: &str) -> bool {
+    match value {
+        _ if filter.is_empty() => true,
+        JsonValue::Object(_) | JsonValue::Array(_) => children(value).iter().any(|(_, v)| visible(v, filter)),
+        JsonValue::String(s) => s.to_lowercase().contains(filter),
+        other => other.to_string().contains(filter),
+    }
+}
+
+/// Keys under `value` matching the search, among the rows the filter shows
+fn count_keys(value: &JsonValue, query: &Query) -> usize {
+    children(value).iter()
+        .filter(|(_, v)| visible(v, &query.values))
+        .map(|(step, v)| key_matches(Some(step), &query.keys) as usize + count_keys(v, query))
+        .sum()
+}
+
+fn node(ui: &mut egui::Ui, id: egui::Id, path: &str, step: Option<&Step>, value: &JsonValue, query: &Query) {
+    if !value.is_object() && !value.is_array() {
+        leaf(ui, path, step, value, query);
+        return;
+    }
+    let children: Vec<(Step, &JsonValue)> = children(value).into_iter()
+        .filter(|(_, v)| visible(v, &query.values))
+        .collect();
+
+    let len = match value {
+        JsonValue::Object(map) => map.len(),
+        _ => value.as_array().map_or(0, Vec::len),
+    };
+    let mut summary = match value {
+        JsonValue::Object(_) => format!("{{{} {}}}", len, if len == 1 { "key" } else { "keys" }),
+        _ => format!("[{} {}]", len, if len == 1 { "item" } else { "items" }),
+    };
+    if children.len() < len {
+        summary = format!("{}, {} shown", summary, children.len());
+    }
+    let label = match step {
+        Some(step) => format!("{}: {}", step.name(), summary),
+        None => summary,
+    };
+    let text = highlighted(ui, label, key_matches(step, &query.keys));
+
+    let mut header = egui::CollapsingHeader::new(text)
+        .id_source(id.with(path))
+        .default_open(path == "$");
+    let searching = (!query.keys.is_empty() && count_keys(value, query) > 0) || !query.values.is_empty();
+    if searching {
+        header = header.open(Some(true));
+    }
+
+    let response = header.show(ui, |ui| {
+        for (step, child) in children.iter().take(MAX_CHILDREN) {
+            node(ui, id, &child_path(path, step), Some(step), child, query);
+        }
+        if children.len() > MAX_CHILDREN {
+            ui.weak(format!("… {} more", children.len() - MAX_CHILDREN));
+        }
+    });
+    response.header_response.on_hover_text(path).context_menu(|ui| copy_menu(ui, path, value));
+}
+
+fn leaf(ui: &mut egui::Ui, path: &str, step: Option<&Step>, value: &JsonValue, query: &Query) {
+    let hit = key_matches(step, &query.keys) || !query.values.is_empty();
+    let text = match step {
+        Some(step) => format!("{}: {}", step.name(), value),
+        None => value.to_string(),
+    };
+    let text = highlighted(ui, text, hit);
+    let response = ui.add(egui::Label::new(text.monospace()).sense(egui::Sense::click()));
+    response.on_hover_text(path).context_menu(|ui| copy_menu(ui, path, value));
+}
+
+fn highlighted(ui: &egui::Ui, text: String, hit: bool) -> egui::RichText {
+    let text = egui::RichText::new(text);
+    if hit {
+        text.background_color(ui.visuals().selection.bg_fill)
+    } else {
+        text
+    }
+}
+
+fn copy_menu(ui: &mut egui::Ui, path: &str, value: &JsonValue) {
+    if ui.button("📋 Copy as JSONPath").clicked() {
+        ui.ctx().copy_text(path.to_string());
+        ui.close_menu();
+    }
+    if ui.button("📋 Copy value").clicked() {
+        ui.ctx().copy_text(serde_json::to_string_pretty(value).unwrap_or_default());
+        ui.close_menu();
+    }
+}
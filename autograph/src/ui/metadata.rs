@@ -0,0 +1,299 @@
+//! Flow Metadata Panel
+//!
+//! Shows flow-level information and its changelog, and prompts for a
+//! changelog message when saving.
+
+use eframe::egui;
+use crate::flow::{Flow, InterruptPolicy, RetryPolicy};
+use crate::runs::format_timestamp;
+use crate::throttle::RateLimit;
+
+/// Draw flow metadata and changelog
+pub fn show_metadata(ui: &mut egui::Ui, flow_name: &str, flow: &mut Flow) {
+    ui.label(format!("Flow: {}", flow_name));
+    ui.label(format!("Nodes: {}  Edges: {}", flow.nodes.len(), flow.edges.len()));
+    if let Some(seed) = flow.seed {
+        ui.label(format!("Seed: {}", seed));
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("On interrupted run:");
+        egui::ComboBox::from_id_source("interrupt_policy")
+            .selected_text(match flow.on_interrupt {
+                InterruptPolicy::Mark => "Mark only",
+                InterruptPolicy::Rerun => "Re-run",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut flow.on_interrupt, InterruptPolicy::Mark, "Mark only");
+                ui.selectable_value(&mut flow.on_interrupt, InterruptPolicy::Rerun, "Re-run");
+            });
+    });
+    ui.horizontal(|ui| {
+        ui.label("Edge style:");
+        egui::ComboBox::from_id_source("edge_style")
+            .selected_text(flow.edge_style.label())
+            .show_ui(ui, |ui| {
+                for style in crate::flow::EdgeStyle::ALL {
+                    ui.selectable_value(&mut flow.edge_style, *style, style.label());
+                }
+            });
+    });
+    ui.checkbox(&mut flow.cpu_fallback, "Retry failed tensor nodes on CPU")
+        .on_hover_text("If a tensor node fails on the GPU, run it on the CPU instead of failing the flow");
+
+    let mut retry = flow.retry.is_some();
+    if ui.checkbox(&mut retry, "Retry failed server runs")
+        .on_hover_text("The server re-runs failed runs with the same input; attempts are linked in the run history")
+        .changed()
+    {
+        flow.retry = retry.then(RetryPolicy::default);
+    }
+    if let Some(policy) = &mut flow.retry {
+        ui.horizontal(|ui| {
+            ui.label("Max retries:");
+            ui.add(egui::DragValue::new(&mut policy.max_retries).range(1..=100));
+            ui.label("Delay (s):");
+            ui.add(egui::DragValue::new(&mut policy.delay_secs));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Only on errors:");
+            list_edit(ui, "retry_on_errors", "any error, or e.g. 429, timed out", &mut policy.on_errors);
+        });
+    }
+
+    ui.separator();
+    ui.heading("Requires");
+    let requires = &mut flow.requires;
+    egui::Grid::new("requires").num_columns(2).show(ui, |ui| {
+        list_field(ui, "Env vars:", "API_BASE_URL, REGION", &mut requires.env);
+        list_field(ui, "Credentials:", "OPENAI_API_KEY", &mut requires.credentials);
+        list_field(ui, "Files:", "data/input.csv", &mut requires.files);
+        list_field(ui, "Hosts:", "api.example.com:443", &mut requires.hosts);
+    });
+    let referenced: Vec<String> = crate::preflight::referenced_env(flow)
+        .into_iter()
+        .filter(|name| !flow.requires.env.contains(name) && !flow.requires.credentials.contains(name))
+        .collect();
+    if !referenced.is_empty() {
+        ui.label(format!("Also checked (env: in node configs): {}", referenced.join(", ")));
+    }
+
+    ui.separator();
+    ui.heading("Rate limits");
+    rate_limits_field(ui, &mut flow.rate_limits);
+
+    ui.separator();
+    ui.heading("Input schema");
+    input_schema_field(ui, flow_name, &mut flow.input_schema);
+
+    ui.separator();
+    ui.heading("Mock files");
+    mock_files_field(ui, &mut flow.mock_files);
+
+    ui.separator();
+    ui.heading("Changelog");
+
+    if flow.changelog.is_empty() {
+        ui.label("No changes recorded yet. Add a message when saving.");
+        return;
+    }
+
+    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+        for entry in flow.changelog.iter().rev() {
+            ui.horizontal(|ui| {
+                ui.monospace(format_timestamp(entry.timestamp));
+                ui.strong(&entry.author);
+            });
+            ui.label(&entry.message);
+            ui.add_space(4.0);
+        }
+    });
+}
+
+/// JSON editor for the flow's input schema. The text is kept between
+/// frames so it can be invalid while typing; it follows the flow when the
+/// schema changes elsewhere, e.g. on undo.
+fn input_schema_field(ui: &mut egui::Ui, flow_name: &str, schema: &mut Option<serde_json::Value>) {
+    let pretty = |schema: &Option<serde_json::Value>| {
+        schema.as_ref().and_then(|s| serde_json::to_string_pretty(s).ok()).unwrap_or_default()
+    };
+    let id = ui.make_persistent_id(("input_schema", flow_name));
+    let mut text = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_else(|| pretty(schema));
+    let parsed = |text: &str| match text.trim() {
+        "" => Ok(None),
+        text => serde_json::from_str::<serde_json::Value>(text).map(Some),
+    };
+    if parsed(&text).is_ok_and(|p| p.as_ref().is_none_or(|v| v.is_object()) && p != *schema) {
+        text = pretty(schema);
+    }
+
+    ui.label("Runs through the server must match it; Run asks for input with a form built from it.");
+    ui.add(
+        egui::TextEdit::multiline(&mut text)
+            .code_editor()
+            .hint_text(r#"{"type": "object", "required": ["email"], "properties": {"email": {"type": "string"}}}"#)
+            .desired_rows(4)
+            .desired_width(f32::INFINITY),
+    );
+    match parsed(&text) {
+        Ok(Some(value)) if !value.is_object() => {
+            ui.colored_label(egui::Color32::RED, "A schema is a JSON object");
+        }
+        Ok(value) => *schema = value,
+        Err(e) => {
+            ui.colored_label(egui::Color32::RED, format!("Invalid JSON: {}", e));
+        }
+    }
+    ui.data_mut(|d| d.insert_temp(id, text));
+}
+
+/// Editor for the files file nodes see in mock runs
+fn mock_files_field(ui: &mut egui::Ui, files: &mut std::collections::BTreeMap<String, String>) {
+    ui.label("File nodes read and write these, in a scratch directory, when 🎭 Mock is on.");
+    let mut removed = None;
+    for (path, content) in files.iter_mut() {
+        ui.horizontal(|ui| {
+            ui.monospace(path);
+            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                removed = Some(path.clone());
+            }
+        });
+        ui.add(egui::TextEdit::multiline(content).code_editor().desired_rows(2).desired_width(f32::INFINITY));
+    }
+    if let Some(path) = removed {
+        files.remove(&path);
+    }
+
+    let id = ui.make_persistent_id("new_mock_file");
+    let mut new_path = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut new_path).hint_text("data/input.csv"));
+        let valid = !new_path.trim().is_empty() && crate::mock::confined(new_path.trim()).is_some();
+        if ui.add_enabled(valid, egui::Button::new("➕ Add file")).clicked() {
+            files.entry(new_path.trim().to_string()).or_default();
+            new_path.clear();
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(id, new_path));
+}
+
+/// Editor for the per-host limits on the flow's HTTP requests
+fn rate_limits_field(ui: &mut egui::Ui, limits: &mut std::collections::BTreeMap<String, RateLimit>) {
+    ui.label("Requests to these hosts are spaced out and retried on 429; 0 means no limit.");
+    let mut removed = None;
+    egui::Grid::new("rate_limits").num_columns(5).show(ui, |ui| {
+        ui.strong("Host");
+        ui.strong("Per minute");
+        ui.strong("Runs at once");
+        ui.strong("429 retries");
+        ui.end_row();
+        for (host, limit) in limits.iter_mut() {
+            ui.monospace(host);
+            ui.add(egui::DragValue::new(&mut limit.per_minute));
+            ui.add(egui::DragValue::new(&mut limit.concurrency));
+            ui.add(egui::DragValue::new(&mut limit.retries).range(0..=20));
+            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                removed = Some(host.clone());
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(host) = removed {
+        limits.remove(&host);
+    }
+
+    let id = ui.make_persistent_id("new_rate_limit");
+    let mut new_host = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut new_host).hint_text("api.example.com"));
+        let host = crate::throttle::host(&format!("https://{}", new_host.trim()));
+        if ui.add_enabled(host.is_some(), egui::Button::new("➕ Add host")).clicked() {
+            limits.entry(host.unwrap_or_default()).or_default();
+            new_host.clear();
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(id, new_host));
+}
+
+/// Comma-separated editor for a list of names, as a grid row
+fn list_field(ui: &mut egui::Ui, label: &str, hint: &str, values: &mut Vec<String>) {
+    ui.label(label);
+    list_edit(ui, label, hint, values);
+    ui.end_row();
+}
+
+/// Single-line comma-separated editor for `values`. The text is kept
+/// between frames so a trailing comma or an empty item can be typed; it
+/// follows the list when that changes elsewhere, e.g. on undo.
+fn list_edit(ui: &mut egui::Ui, id_source: &str, hint: &str, values: &mut Vec<String>) {
+    let parse = |text: &str| -> Vec<String> {
+        text.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect()
+    };
+    let id = ui.make_persistent_id(("list", id_source));
+    let mut text = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_else(|| values.join(", "));
+    if parse(&text) != *values {
+        text = values.join(", ");
+    }
+    if ui.add(egui::TextEdit::singleline(&mut text).hint_text(hint)).changed() {
+        *values = parse(&text);
+    }
+    ui.data_mut(|d| d.insert_temp(id, text));
+}
+
+/// Save prompt asking for a changelog message
+pub struct SaveDialog {
+    pub author: String,
+    pub message: String,
+}
+
+/// Outcome of the save prompt for this frame
+pub enum SaveDialogAction {
+    None,
+    Save,
+    Cancel,
+}
+
+impl SaveDialog {
+    pub fn new() -> Self {
+        let author = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        Self {
+            author,
+            message: String::new(),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> SaveDialogAction {
+        let mut action = SaveDialogAction::None;
+
+        egui::Window::new("💾 Save Flow")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Author:");
+                    ui.text_edit_singleline(&mut self.author);
+                });
+                ui.label("What changed? (leave empty to skip the changelog)");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.message)
+                        .desired_rows(3)
+                        .desired_width(300.0),
+                );
+
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        action = SaveDialogAction::Save;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = SaveDialogAction::Cancel;
+                    }
+                });
+            });
+
+        action
+    }
+}
@@ -0,0 +1,138 @@
+//! Sticky Notes
+//!
+//! Drawing and editing of the notes that document a flow on the canvas.
+//! The canvas handles moving and resizing them.
+
+use eframe::egui;
+use crate::flow::StickyNote;
+
+/// Height of a note's title bar at zoom 1
+pub const TITLE_HEIGHT: f32 = 22.0;
+/// Size of the resize grip in a note's bottom-right corner
+pub const GRIP_SIZE: f32 = 12.0;
+
+/// Note background, falling back to the first palette color
+fn note_color(note: &StickyNote) -> egui::Color32 {
+    let [r, g, b] = crate::nodes::parse_color(&note.color)
+        .or_else(|| crate::nodes::parse_color(StickyNote::COLORS[0].1))
+        .unwrap_or([245, 225, 122]);
+    egui::Color32::from_rgb(r, g, b)
+}
+
+/// Paint a note into its screen rectangle
+pub fn paint_note(painter: &egui::Painter, rect: egui::Rect, note: &StickyNote, zoom: f32, is_selected: bool) {
+    let ink = egui::Color32::from_gray(40);
+    let painter = painter.with_clip_rect(rect.expand(4.0));
+
+    painter.rect_filled(rect.translate(egui::Vec2::splat(3.0)), 3.0, egui::Color32::from_black_alpha(60));
+    painter.rect_filled(rect, 3.0, note_color(note));
+    if is_selected {
+        painter.rect_stroke(rect.expand(2.0), 4.0, egui::Stroke::new(2.0, egui::Color32::YELLOW));
+    }
+
+    let padding = 6.0 * zoom;
+    let title_height = TITLE_HEIGHT * zoom;
+    painter.text(
+        rect.min + egui::Vec2::new(padding, title_height / 2.0),
+        egui::Align2::LEFT_CENTER,
+        &note.title,
+        egui::FontId::proportional(13.0 * zoom),
+        ink,
+    );
+    painter.line_segment(
+        [rect.min + egui::Vec2::new(padding, title_height), egui::Pos2::new(rect.max.x - padding, rect.min.y + title_height)],
+        egui::Stroke::new(1.0, egui::Color32::from_black_alpha(80)),
+    );
+
+    let body_rect = egui::Rect::from_min_max(
+        rect.min + egui::Vec2::new(padding, title_height + padding / 2.0),
+        rect.max - egui::Vec2::splat(padding),
+    );
+    let galley = painter.layout_job(body_layout(&note.body, zoom, body_rect.width(), ink));
+    painter.with_clip_rect(body_rect).galley(body_rect.min, galley, ink);
+
+    // Resize grip
+    let grip = GRIP_SIZE * zoom;
+    for i in 1..=2 {
+        let offset = grip * i as f32 / 2.0;
+        painter.line_segment(
+            [egui::Pos2::new(rect.max.x - offset, rect.max.y - 2.0), egui::Pos2::new(rect.max.x - 2.0, rect.max.y - offset)],
+            egui::Stroke::new(1.0, egui::Color32::from_black_alpha(120)),
+        );
+    }
+}
+
+/// The body with `# ` lines as headings and `- ` / `* ` lines as bullets
+fn body_layout(body: &str, zoom: f32, width: f32, ink: egui::Color32) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    job.wrap.max_width = width.max(1.0);
+
+    for (i, line) in body.lines().enumerate() {
+        let (text, size) = if let Some(heading) = line.strip_prefix("# ") {
+            (heading.to_string(), 14.0)
+        } else if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            (format!("• {}", item), 11.0)
+        } else {
+            (line.to_string(), 11.0)
+        };
+        let newline = if i > 0 { "\n" } else { "" };
+        job.append(
+            &format!("{}{}", newline, text),
+            0.0,
+            egui::TextFormat::simple(egui::FontId::proportional(size * zoom), ink),
+        );
+    }
+    job
+}
+
+/// What the note editor asked for this frame
+pub enum NoteEditorAction {
+    None,
+    Close,
+    Delete,
+}
+
+/// Window editing a note's title, body and color
+pub fn show_note_editor(ctx: &egui::Context, note: &mut StickyNote) -> NoteEditorAction {
+    let mut action = NoteEditorAction::None;
+    let mut open = true;
+
+    egui::Window::new("📝 Note")
+        .id(egui::Id::new(("note_editor", &note.id)))
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Title:");
+                ui.text_edit_singleline(&mut note.title);
+            });
+            ui.add(
+                egui::TextEdit::multiline(&mut note.body)
+                    .hint_text("# Heading\n- bullet\nplain text")
+                    .desired_rows(8),
+            );
+            ui.horizontal(|ui| {
+                ui.label("Color:");
+                for (name, hex) in StickyNote::COLORS {
+                    let [r, g, b] = crate::nodes::parse_color(hex).unwrap_or([200, 200, 200]);
+                    let swatch = egui::Button::new("   ").fill(egui::Color32::from_rgb(r, g, b));
+                    if ui.add(swatch).on_hover_text(*name).clicked() {
+                        note.color = hex.to_string();
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Done").clicked() {
+                    action = NoteEditorAction::Close;
+                }
+                if ui.button("🗑 Delete").clicked() {
+                    action = NoteEditorAction::Delete;
+                }
+            });
+        });
+
+    if !open {
+        action = NoteEditorAction::Close;
+    }
+    action
+}
@@ -8,14 +8,6 @@ use crate::flow::Position;
 pub struct NodePalette {}
 
 impl NodePalette {
-    fn get_node_defs(&self) -> Vec<(&'static str, &'static str, &'static str)> {
-        // Get all nodes from registry
-        crate::nodes::all_nodes()
-            .into_iter()
-            .map(|def| (def.name, def.category, def.description))
-            .collect()
-    }
-
     pub fn show(&mut self, ui: &mut egui::Ui, flow: &mut crate::flow::Flow, selected_node: &mut Option<String>) {
         ui.heading("Node Palette");
         ui.separator();
@@ -23,10 +15,12 @@ impl NodePalette {
         egui::ScrollArea::vertical().show(ui, |ui| {
             // Group nodes by category with stable ordering
             use std::collections::BTreeMap;
+            let defs = crate::nodes::all_nodes();
             let mut categories = BTreeMap::new();
-            for (name, category, description) in self.get_node_defs() {
+            for def in &defs {
+                let (name, description) = (def.name.as_ref(), def.description.as_ref());
                 categories
-                    .entry(category)
+                    .entry(def.category.as_ref())
                     .or_insert_with(Vec::new)
                     .push((name, description));
             }
@@ -44,14 +38,10 @@ impl NodePalette {
                             use crate::flow::Node;
 
                             let node_count = flow.nodes.len();
-                            let id = format!("node_{}", node_count);
+                            let id = flow.next_node_id();
 
                             // Get default config from node registry
-                            let config = crate::nodes::all_nodes()
-                                .into_iter()
-                                .find(|def| def.name == name)
-                                .map(|def| (def.default_config)())
-                                .unwrap_or(serde_json::json!({}));
+                            let config = crate::nodes::default_config(name);
 
                             flow.nodes.push(Node {
                                 id: id.clone(),
@@ -61,7 +51,7 @@ impl NodePalette {
                                     x: 300.0 + (node_count as f32 * 20.0),
                                     y: 200.0 + (node_count as f32 * 20.0),
                                 }),
-                                breakpoint: false,
+                                ..Default::default()
                             });
 
                             *selected_node = Some(id);
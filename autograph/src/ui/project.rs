@@ -0,0 +1,190 @@
+//! Project Panel
+//!
+//! Edit the variables, secret references and auth profiles shared by the
+//! project's flows, and save or reopen the project with its set of open tabs.
+
+use eframe::egui;
+use crate::http_auth::AuthProfile;
+use crate::project::{is_valid_env_name, Project};
+
+/// Project window state
+#[derive(Default)]
+pub struct ProjectPanel {
+    /// Variable being added, name and value
+    new_variable: (String, String),
+
+    /// Secret env var being added
+    new_secret: String,
+
+    /// Auth profile being added, name and type
+    new_profile: (String, String),
+}
+
+/// What the user asked for this frame
+pub enum ProjectAction {
+    None,
+    /// Write the project file with the current tabs
+    Save,
+    /// Replace the open tabs with the project file's
+    Open,
+    /// An auth profile was added, edited or removed
+    ProfilesChanged,
+}
+
+impl ProjectPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, project: &mut Project, path: &str) -> ProjectAction {
+        let mut action = ProjectAction::None;
+
+        ui.horizontal(|ui| {
+            ui.label(path);
+            if ui.button("💾 Save project").on_hover_text("Record the open tabs, variables and secrets").clicked() {
+                action = ProjectAction::Save;
+            }
+            if ui.button("📂 Open project").on_hover_text("Reopen the tabs recorded in the project file").clicked() {
+                action = ProjectAction::Open;
+            }
+        });
+        ui.separator();
+
+        ui.strong("Variables");
+        ui.weak("Flows read these as env:NAME; the environment overrides them");
+        let mut removed = None;
+        egui::Grid::new("project_variables").striped(true).show(ui, |ui| {
+            for (name, value) in project.variables.iter_mut() {
+                ui.monospace(name);
+                ui.add(egui::TextEdit::singleline(value).desired_width(180.0));
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(name.clone());
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(name) = removed {
+            project.variables.remove(&name);
+        }
+        ui.horizontal(|ui| {
+            let (name, value) = &mut self.new_variable;
+            ui.add(egui::TextEdit::singleline(name).hint_text("NAME").desired_width(100.0));
+            ui.add(egui::TextEdit::singleline(value).hint_text("value").desired_width(140.0));
+            let valid = is_valid_env_name(name) && !project.variables.contains_key(name.as_str());
+            if ui.add_enabled(valid, egui::Button::new("➕ Add")).clicked() {
+                let (name, value) = std::mem::take(&mut self.new_variable);
+                project.variables.insert(name, value);
+            }
+        });
+
+        ui.separator();
+        ui.strong("Secrets");
+        ui.weak("Only the env var names are stored; preflight checks they are set");
+        let mut removed = None;
+        for (index, name) in project.secrets.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let set = std::env::var(name).is_ok_and(|v| !v.trim().is_empty());
+                ui.monospace(name);
+                if set {
+                    ui.colored_label(egui::Color32::GREEN, "set");
+                } else {
+                    ui.colored_label(egui::Color32::RED, "not set");
+                }
+                if ui.small_button("🗑").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            project.secrets.remove(index);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_secret).hint_text("ENV_VAR").desired_width(140.0));
+            let valid = is_valid_env_name(&self.new_secret) && !project.secrets.contains(&self.new_secret);
+            if ui.add_enabled(valid, egui::Button::new("➕ Add")).clicked() {
+                project.secrets.push(std::mem::take(&mut self.new_secret));
+            }
+        });
+
+        ui.separator();
+        if self.show_auth_profiles(ui, project) && matches!(action, ProjectAction::None) {
+            action = ProjectAction::ProfilesChanged;
+        }
+
+        action
+    }
+
+    /// Credentials HTTP nodes select with `"auth"`; fields take env:NAME.
+    /// Returns whether a profile was added, edited or removed.
+    fn show_auth_profiles(&mut self, ui: &mut egui::Ui, project: &mut Project) -> bool {
+        ui.strong("Auth profiles");
+        ui.weak("HTTP nodes pick one by name; secrets must be env:NAME. OAuth2 tokens are fetched and refreshed for you");
+        let mut removed = None;
+        let mut edited_any = false;
+        for (name, profile) in project.auth_profiles.iter_mut() {
+            egui::CollapsingHeader::new(format!("{} ({})", name, profile.type_name()))
+                .id_source(("auth_profile", name))
+                .show(ui, |ui| {
+                    // Edit the profile's fields through its JSON form
+                    let mut value = serde_json::to_value(&*profile).unwrap_or_default();
+                    let plaintext = profile.plaintext_secrets();
+                    let mut changed = false;
+                    egui::Grid::new(("auth_profile_fields", name)).show(ui, |ui| {
+                        for (key, field) in value.as_object_mut().into_iter().flatten().filter(|(key, _)| *key != "type") {
+                            let mut text = field.as_str().unwrap_or_default().to_string();
+                            ui.label(key);
+                            if key == "in" {
+                                egui::ComboBox::from_id_source(("auth_key_location", name))
+                                    .selected_text(&text)
+                                    .show_ui(ui, |ui| {
+                                        for location in ["header", "query"] {
+                                            changed |= ui.selectable_value(&mut text, location.to_string(), location).changed();
+                                        }
+                                    });
+                            } else {
+                                changed |= ui.add(egui::TextEdit::singleline(&mut text).desired_width(200.0)).changed();
+                                if plaintext.iter().any(|field| *field == key.as_str()) {
+                                    ui.colored_label(egui::Color32::YELLOW, "⚠ use env:NAME")
+                                        .on_hover_text("Secrets are never saved in the project file; the project won't save until this is a reference");
+                                }
+                            }
+                            *field = serde_json::json!(text);
+                            ui.end_row();
+                        }
+                    });
+                    if changed {
+                        if let Ok(edited) = serde_json::from_value(value) {
+                            *profile = edited;
+                            edited_any = true;
+                        }
+                    }
+                    if ui.small_button("🗑 Remove").clicked() {
+                        removed = Some(name.clone());
+                    }
+                });
+        }
+        if let Some(name) = removed {
+            project.auth_profiles.remove(&name);
+            edited_any = true;
+        }
+
+        ui.horizontal(|ui| {
+            let (name, type_name) = &mut self.new_profile;
+            if type_name.is_empty() {
+                *type_name = AuthProfile::TYPES[0].to_string();
+            }
+            ui.add(egui::TextEdit::singleline(name).hint_text("name").desired_width(100.0));
+            egui::ComboBox::from_id_source("new_auth_profile_type")
+                .selected_text(type_name.as_str())
+                .show_ui(ui, |ui| {
+                    for t in AuthProfile::TYPES {
+                        ui.selectable_value(type_name, t.to_string(), *t);
+                    }
+                });
+            let valid = is_valid_env_name(name) && !project.auth_profiles.contains_key(name.as_str());
+            if ui.add_enabled(valid, egui::Button::new("➕ Add")).clicked() {
+                if let Some(profile) = AuthProfile::new(type_name) {
+                    project.auth_profiles.insert(std::mem::take(name), profile);
+                    edited_any = true;
+                }
+            }
+        });
+        edited_any
+    }
+}
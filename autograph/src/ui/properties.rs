@@ -10,6 +10,16 @@ pub struct PropertiesPanel {
 
     /// Whether JSON is being edited
     editing: bool,
+
+    /// Sample text for the regex pattern tester
+    regex_sample: String,
+
+    /// Sample payload for the JSON query preview
+    query_sample: String,
+
+    /// Mock output editor buffer, and the node it belongs to
+    mock_json: String,
+    mock_node: String,
 }
 
 impl PropertiesPanel {
@@ -26,9 +36,37 @@ impl PropertiesPanel {
         ui.separator();
 
         if let Some(node_id) = selected_node.clone() {
+            // Output of the upstream node, if the last run captured it
+            let upstream_output: Option<&serde_json::Value> = flow.edges.iter()
+                .find(|e| e.target == node_id)
+                .and_then(|e| node_executions.get(&e.source))
+                .and_then(|exec| exec.output_value());
+
             if let Some(node) = flow.nodes.iter_mut().find(|n| n.id == node_id) {
                 ui.label(format!("Node: {}", node.id));
                 ui.label(format!("Type: {}", node.type_name));
+                ui.checkbox(&mut node.enabled, "Enabled")
+                    .on_hover_text("Disabled nodes are skipped when compiling; their input passes straight through");
+                ui.horizontal(|ui| {
+                    ui.label("Label:");
+                    let mut label = node.label.clone().unwrap_or_default();
+                    let response = ui.add(egui::TextEdit::singleline(&mut label).hint_text(node.type_name.as_str()))
+                        .on_hover_text("Name shown on the canvas instead of the type");
+                    if response.changed() {
+                        node.label = Some(label).filter(|l| !l.trim().is_empty());
+                    }
+                });
+                ui.label("Notes:");
+                let mut notes = node.notes.clone().unwrap_or_default();
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut notes)
+                        .hint_text("Shown when hovering the node")
+                        .desired_width(ui.available_width())
+                        .desired_rows(2),
+                );
+                if response.changed() {
+                    node.notes = Some(notes).filter(|n| !n.trim().is_empty());
+                }
                 ui.separator();
 
                 // Position
@@ -45,6 +83,14 @@ impl PropertiesPanel {
                 ui.separator();
                 ui.label("Configuration:");
 
+                // Form for node types that describe their config fields
+                if let Some(schema) = crate::nodes::config_schema(&node.type_name) {
+                    if show_schema_form(ui, &schema, &mut node.config) {
+                        self.editing = false;
+                    }
+                    ui.add_space(4.0);
+                }
+
                 // Initialize JSON buffer if not editing
                 if !self.editing {
                     self.config_json = serde_json::to_string_pretty(&node.config).unwrap();
@@ -77,6 +123,66 @@ impl PropertiesPanel {
 
                 ui.separator();
 
+                if node.type_name == "hlx_script" {
+                    // Connected nodes are bound as named inputs at compile time
+                    let mut config = node.config.clone();
+                    let sources = flow.edges.iter().filter(|e| e.target == node_id).map(|e| e.source.as_str());
+                    crate::nodes::bind_script_inputs(&mut config, sources);
+                    let params: Vec<String> = std::iter::once("input".to_string())
+                        .chain(crate::nodes::script_inputs(&config).into_iter().map(|(name, _)| name))
+                        .collect();
+
+                    ui.label("Script:");
+                    ui.monospace(format!("fn {}_script({}) {{", node.id, params.join(", ")));
+                    let mut code = node.config["code"].as_str().unwrap_or("").to_string();
+                    if super::code_editor::show(ui, &mut code, 12).changed() && node.config.is_object() {
+                        node.config["code"] = serde_json::json!(code);
+                        self.editing = false;
+                    }
+                    ui.monospace("}");
+                    ui.separator();
+                }
+
+                if node.type_name == "prompt_template" || node.type_name == "template_render" {
+                    show_template_preview(ui, &node.config, upstream_output);
+                    ui.separator();
+                }
+
+                if node.type_name.starts_with("regex_") {
+                    self.show_regex_tester(ui, &node.type_name, &node.config, upstream_output);
+                    ui.separator();
+                }
+
+                if node.type_name == "json_query" {
+                    self.show_query_preview(ui, &node.config, upstream_output);
+                    ui.separator();
+                }
+
+                if node.type_name == "vector_search" {
+                    show_vector_preview(ui, &node.config, upstream_output);
+                    ui.separator();
+                }
+
+                if crate::nodes::HTTP_REQUEST_NODES.contains(&node.type_name.as_str()) && node.config.is_object() {
+                    if show_auth_picker(ui, &mut node.config) {
+                        self.editing = false;
+                    }
+                    if show_rate_limit(ui, &mut node.config, &flow.rate_limits) {
+                        self.editing = false;
+                    }
+                    ui.separator();
+                }
+
+                show_chaos_rule(ui, &mut flow.chaos, &node.id);
+                ui.separator();
+
+                let last_output = node_executions.get(&node.id).and_then(|exec| exec.output_value());
+                self.show_mock_rule(ui, &mut flow.mocks, &node.id, last_output);
+                ui.separator();
+
+                show_pinned_output(ui, &mut flow.pinned, &node.id, last_output);
+                ui.separator();
+
                 // Execution Data Section
                 if let Some(exec) = node_executions.get(&node.id) {
                     ui.heading("Execution Data");
@@ -98,12 +204,24 @@ impl PropertiesPanel {
                             if let Some(output) = &exec.output {
                                 ui.separator();
                                 ui.label("Output:");
-                                ui.add(
-                                    egui::TextEdit::multiline(&mut output.as_str())
-                                        .desired_width(ui.available_width())
-                                        .desired_rows(10)
-                                        .code_editor(),
-                                );
+                                let value = exec.output_value();
+                                let tensor = value
+                                    .filter(|_| crate::fallback::is_tensor_node(&node.type_name))
+                                    .and_then(super::tensor_view::Tensor::parse);
+                                let bytes = value.and_then(crate::bytes::base64_of);
+                                match (tensor, bytes) {
+                                    (Some(tensor), _) => {
+                                        super::tensor_view::show_tensor(ui, &tensor);
+                                        ui.collapsing("Raw output", |ui| show_raw_output(ui, output));
+                                    }
+                                    (None, Some(base64)) => show_bytes(ui, base64),
+                                    (None, None) => match value {
+                                        Some(value) if output.len() <= RAW_OUTPUT_CHARS => {
+                                            super::json_tree::show(ui, &format!("output_{}", node.id), value);
+                                        }
+                                        _ => show_raw_output(ui, output),
+                                    },
+                                }
                             } else {
                                 ui.label("Output data not captured (requires runtime hooks)");
                                 ui.label("💡 Full per-node inspection coming in Phase 4!");
@@ -128,22 +246,208 @@ impl PropertiesPanel {
                 match node.type_name.as_str() {
                     "http_request" | "http_get" | "http_post" | "http_put" | "http_delete" => {
                         ui.label("HTTP request node");
+                        ui.label("Auth: pick a profile from the project to authenticate the request");
+                        ui.label(format!("Output: {}", crate::nodes::HTTP_OUTPUTS.join(" or ")));
+                        ui.label("response: {status, ok, headers, body, duration_ms}; ok when the status is in expect_status (default 2xx, 3xx)");
+                        ui.label("expect_status: e.g. 200-204, 304; fail_on_status: true fails the run otherwise");
+                    }
+                    "http_response_get" => {
+                        ui.label(format!("Field: {}", crate::nodes::RESPONSE_FIELDS.join(", ")));
+                        ui.label("header: the header named in \"header\", case-insensitive");
+                        ui.label("body: optional JSONPath in \"path\", e.g. $.data[0].id");
+                    }
+                    "feed_fetch" => {
+                        ui.label("RSS 2.0 and Atom feeds; entries in feed order, newest first for most feeds");
+                        ui.label(format!("Entry fields: {}", crate::nodes::FEED_FIELDS.join(", ")));
+                        ui.label("max_entries: keep the first N (0 for all); dedupe with state_increment keyed on $input.id");
+                    }
+                    "http_paginate" => {
+                        ui.label(format!("Strategy: {}", crate::nodes::PAGINATION_STRATEGIES.join(", ")));
+                        ui.label("next_url: next_path finds the next page's URL, e.g. $.links.next");
+                        ui.label("cursor: cursor_path finds the cursor, sent as cursor_param");
+                        ui.label("offset: offset_param and limit_param, page_size items per page");
+                        ui.label("items_path picks each page's items (empty: the page is the list); stops after max_pages");
                     }
                     "json_parse" | "json_stringify" => {
                         ui.label("JSON parsing/serialization");
                     }
+                    "json_query" => {
+                        ui.label(format!("Syntax: {}", crate::query::SYNTAXES.join(" or ")));
+                        ui.label("JSONPath: $.orders[*].id, $..email, $.items[?@.price > 10]; \"all\" returns every match");
+                        ui.label("JMESPath: orders[*].id, items[?price > `10`].name");
+                    }
+                    "file_read_lines" | "file_stream" => {
+                        ui.label(format!("Format: {}", crate::streaming::FORMATS.join(", ")));
+                        ui.label("csv: rows become objects keyed by the header line unless \"header\": false");
+                        if node.type_name == "file_stream" {
+                            ui.label("flow: run on each chunk with {chunk, offset, items}; outputs {chunks, lines, last}");
+                            ui.label("max_chunks: stop after this many (0 for the whole file)");
+                        } else {
+                            ui.label("Reads count lines from line start (after any header)");
+                        }
+                    }
+                    "jsonl_read" | "jsonl_write" | "jsonl_append" => {
+                        ui.label("One JSON value per line; blank lines are skipped when reading");
+                        match node.type_name.as_str() {
+                            "jsonl_read" => ui.label("max_lines: read at most this many (0 for all)"),
+                            "jsonl_append" => ui.label("each: append an array input a line per element"),
+                            _ => ui.label("Input: the array of records; replaces the file"),
+                        };
+                    }
+                    "state_get" | "state_set" | "state_increment" => {
+                        ui.label(format!("Store: {}/<flow>.sqlite, kept between runs", crate::state_store::STATE_DIR));
+                        ui.label("key: a name, or $input / $input.<field> to key by the input");
+                        if node.type_name == "state_increment" {
+                            ui.label("Outputs the new count; 1 means the key is new, e.g. for dedupe");
+                        }
+                    }
+                    "email_read" => {
+                        ui.label("IMAP over TLS (port 993); username and password accept env:NAME");
+                        ui.label("Messages: {uid, from, to, cc, subject, date, text, html, attachments}; attachments hold bytes");
+                        ui.label("trigger: the server polls every poll_secs and runs the flow per new message, marking it read");
+                    }
+                    "embed_text" => {
+                        ui.label(format!("Provider: {}", crate::nodes::EMBEDDING_PROVIDERS.join(", ")));
+                        ui.label("local: set \"path\" to an .onnx embedding model, run on the tensor backend");
+                        ui.label("batch: embed an array of texts, outputting a vector per text");
+                    }
+                    "vector_index" | "vector_search" => {
+                        ui.label("Index: a JSONL file of {id, vector, metadata} records");
+                        if node.type_name == "vector_index" {
+                            ui.label("Input: a record, or an array of them with \"each\": true; passed on");
+                        } else {
+                            ui.label("Input: a query vector; outputs the top_k [{id, score, metadata}] by cosine similarity");
+                        }
+                    }
                     "tensor_create" => {
                         ui.label("Config: { \"rows\": N, \"cols\": M, \"values\": [...] }");
                     }
-                    "tensor_matmul" | "tensor_add" => {
-                        ui.label("Tensor operation (connects to 2 tensor inputs)");
+                    "tensor_matmul" | "tensor_add" | "tensor_mul" => {
+                        ui.label("Inputs: a and b; drop an edge on a port's handle to connect to it");
+                        if node.type_name == "tensor_matmul" {
+                            ui.label("Or set \"rhs\": { \"rows\", \"cols\", \"values\" } to multiply the input by a constant");
+                        }
+                    }
+                    "tensor_from_csv" => {
+                        ui.label("Columns: names (with a header) or 0-based indexes; empty loads every column");
+                        ui.label("Every loaded cell must be numeric");
+                    }
+                    "tensor_random" => {
+                        ui.label("Shape: sizes per dimension, e.g. [64, 3]");
+                        for (name, [(a, _), (b, _)]) in crate::nodes::DISTRIBUTIONS {
+                            ui.label(format!("\"distribution\": \"{}\" with \"{}\" and \"{}\"", name, a, b));
+                        }
+                        ui.label("Seeded flows draw the same values every run");
+                    }
+                    "tensor_slice" | "tensor_reduce_sum" | "tensor_reduce_mean" | "tensor_softmax" | "tensor_argmax" => {
+                        ui.label("Axis: 0 is the first; negative counts from the end (-1 is the last)");
+                        if node.type_name.starts_with("tensor_reduce_") {
+                            ui.label("Set \"axis\": null to reduce every element to one value");
+                        }
+                    }
+                    "tensor_reshape" => {
+                        ui.label("Shape: new sizes with the same element count, e.g. [2, -1]; -1 is inferred");
+                    }
+                    "model_infer" => {
+                        ui.label("Path: an .onnx model; relative paths resolve in the run's working directory");
+                        ui.label("Input/output name: the model's tensors to feed and read; empty uses the first");
+                    }
+                    "tensor_stack" => {
+                        ui.label("Input: array of equal-shaped matrices → [n, rows, cols]");
+                    }
+                    "tensor_batch" => {
+                        ui.label("Input: array of rows → [batch, dim]; batch_size 0 keeps all rows");
                     }
                     "print" | "start" => {
                         ui.label("Pass-through node");
                     }
+                    "hash" | "hmac_sign" => {
+                        ui.label(format!("Algorithms: {}; encoding: hex or base64", crate::nodes::HASH_ALGORITHMS.join(", ")));
+                        if node.type_name == "hmac_sign" {
+                            ui.label("Key accepts env:NAME so the secret stays in the environment");
+                        }
+                    }
+                    "uuid_generate" => {
+                        ui.label("Version: v4 (random) or v5 (same input → same UUID)");
+                        ui.label("Namespace for v5: dns, url, oid, x500 or a UUID");
+                    }
+                    "token_count" | "truncate_tokens" => {
+                        ui.label(format!("Tokenizers: {}", crate::nodes::TOKENIZERS.join(", ")));
+                        ui.label("Put truncate_tokens before the completion node to stay inside the context window");
+                    }
+                    "llm_complete" => {
+                        ui.label(format!("Provider: {}; base_url empty uses the provider's default", crate::llm::PROVIDERS.join(" or ")));
+                        ui.label("api_key: env:NAME; unset sends OPENAI_API_KEY to openai and no key to ollama");
+                        ui.label("Prompt: a {{variable}} template over the input; leave empty to send the input");
+                        ui.label("Output: {text, model, usage}; token usage shows in the timeline");
+                    }
+                    "slack_send" | "discord_send" | "telegram_send" => {
+                        ui.label("Message: a {{variable}} template over the input; leave empty to send the input as the payload");
+                        ui.label("Webhook / token / chat_id accept env:NAME; rate limits are retried after Retry-After");
+                    }
+                    "nats_publish" => {
+                        ui.label("Server: nats://[user:pass@]host[:port] or env:NAME");
+                        ui.label("Message: a {{variable}} template over the input; leave empty to publish the input as JSON");
+                    }
+                    "nats_consume" => {
+                        ui.label("Deployed to the server, the flow runs once per message on subject (wildcards * and > allowed)");
+                        ui.label("The node outputs {\"subject\", \"data\"}, data parsed from JSON when it is JSON");
+                        ui.label("queue: a queue group, so several servers share the messages instead of each getting all");
+                    }
+                    "sftp_upload" | "sftp_download" | "sftp_list" => {
+                        ui.label("Auth: password, or private_key (a key file path, or the key itself via env:NAME) with an optional passphrase");
+                        ui.label("host, username and credentials accept env:NAME; path is the local file, remote_path the server's");
+                    }
+                    "db_query" | "db_execute" => {
+                        ui.label(format!("Connection: {}://... or env:NAME to read it from the environment", crate::nodes::DB_SCHEMES.join("|")));
+                        ui.label("Params: values bound to $1, $2 (or ?); \"$input\" / \"$input.key\" read from the input");
+                    }
+                    "hlx_script" => {
+                        ui.label("Write a function body and return the node's output");
+                        ui.label("Connected nodes are parameters named by their ID; rename them with \"inputs\": { \"name\": \"node_id\" }");
+                    }
+                    "prompt_template" => {
+                        ui.label("Config: { \"template\": \"... {{name}} ...\", \"variables\": [\"name\"] }");
+                        ui.label("Each variable is read from the input object");
+                    }
+                    "template_render" => {
+                        ui.label("Config: { \"template\": \"Dear {{user.name}}, order {{order_id}} shipped\" }");
+                        ui.label("Fields are read from the input object; dotted paths reach nested fields and array items");
+                    }
+                    "regex_match" | "regex_extract" | "regex_replace" => {
+                        ui.label("Flags: any of i (ignore case), m (multi-line), s (dot matches newline), x (verbose)");
+                        if node.type_name == "regex_extract" {
+                            ui.label("Named groups (?P<name>...) give an object, numbered groups an array; \"all\" returns every match");
+                        }
+                    }
                     _ if node.type_name.starts_with("string_") => {
                         ui.label("String manipulation");
                     }
+                    "array_sum" | "array_average" | "array_min" | "array_max" | "array_distinct" => {
+                        ui.label("Field: object field to aggregate; leave empty to use the elements themselves");
+                    }
+                    "array_group_by" | "array_count" => {
+                        ui.label("Key: object field to group by; array_count with no key counts all elements");
+                    }
+                    "array_map" | "array_filter" | "array_reduce" => {
+                        ui.label("Expression names: item (the element), index, and acc (running value) in array_reduce");
+                        ui.label("e.g. item.price > 10 and item.name != 'test', item.tags[0], acc + item.total");
+                        let key = if node.type_name == "array_filter" { "condition" } else { "expression" };
+                        let names: &[(&str, &str)] = &[("item", "item"), ("index", "index"), ("acc", "acc")];
+                        let names = if node.type_name == "array_reduce" { names } else { &names[..2] };
+                        if let Err(e) = crate::expr::compile(node.config[key].as_str().unwrap_or(""), names) {
+                            ui.colored_label(egui::Color32::RED, format!("❌ {}", e));
+                        }
+                    }
+                    "array_chunk" => {
+                        ui.label("Size: elements per batch; feed batches to a loop for paginated API calls");
+                    }
+                    "array_flatten" => {
+                        ui.label("Depth: nesting levels to flatten; 0 flattens completely");
+                    }
+                    "array_zip" => {
+                        ui.label("Input: a list of arrays; output stops at the shortest");
+                    }
                     _ if node.type_name.starts_with("array_") => {
                         ui.label("Array operation");
                     }
@@ -164,3 +468,462 @@ impl PropertiesPanel {
         delete_requested
     }
 }
+
+/// Characters of an output shown before it is cut off
+const RAW_OUTPUT_CHARS: usize = 64 * 1024;
+
+/// A node's output as read-only JSON text
+fn show_raw_output(ui: &mut egui::Ui, output: &str) {
+    // Megabytes of text would stall the panel; show the start and the size
+    let shown = match output.char_indices().nth(RAW_OUTPUT_CHARS) {
+        Some((end, _)) => {
+            ui.weak(format!(
+                "Showing the first {} of {}",
+                crate::gpu_stats::format_bytes(end as u64),
+                crate::gpu_stats::format_bytes(output.len() as u64)
+            ));
+            &output[..end]
+        }
+        None => output,
+    };
+    ui.add(
+        egui::TextEdit::multiline(&mut &*shown)
+            .desired_width(ui.available_width())
+            .desired_rows(10)
+            .code_editor(),
+    );
+}
+
+/// Size, type and hex dump of a bytes output instead of its base64
+fn show_bytes(ui: &mut egui::Ui, base64: &str) {
+    ui.label(format!("Bytes: {}", crate::bytes::describe(base64)));
+    let prefix = crate::bytes::decode_prefix(base64, crate::bytes::PREVIEW_BYTES);
+    ui.add(
+        egui::TextEdit::multiline(&mut crate::bytes::hex_dump(&prefix).as_str())
+            .desired_width(ui.available_width())
+            .desired_rows(8)
+            .code_editor(),
+    );
+    if crate::bytes::size(base64) > prefix.len() {
+        ui.weak(format!("First {} bytes", prefix.len()));
+    }
+    if ui.button("📋 Copy base64").clicked() {
+        ui.ctx().copy_text(base64.to_string());
+    }
+}
+
+/// Pick one of the project's auth profiles for an HTTP node. Returns
+/// whether the config changed.
+fn show_auth_picker(ui: &mut egui::Ui, config: &mut serde_json::Value) -> bool {
+    let current = config["auth"].as_str().unwrap_or("").to_string();
+    let mut selected = current.clone();
+    ui.horizontal(|ui| {
+        ui.label("Auth profile:");
+        egui::ComboBox::from_id_source("auth_profile")
+            .selected_text(if selected.is_empty() { "(none)" } else { selected.as_str() })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, String::new(), "(none)");
+                for name in crate::http_auth::profile_names() {
+                    ui.selectable_value(&mut selected, name.clone(), name);
+                }
+            });
+    });
+    if !current.is_empty() && !crate::http_auth::profile_names().contains(&current) {
+        ui.colored_label(egui::Color32::RED, format!("No auth profile '{}' in the project", current));
+    }
+
+    if selected == current {
+        return false;
+    }
+    match config.as_object_mut() {
+        Some(fields) if selected.is_empty() => {
+            fields.remove("auth");
+        }
+        Some(fields) => {
+            fields.insert("auth".to_string(), serde_json::json!(selected));
+        }
+        None => {}
+    }
+    true
+}
+
+/// Give an HTTP node its own rate limit, or show the host limit it falls
+/// under. Returns whether the config changed.
+fn show_rate_limit(
+    ui: &mut egui::Ui,
+    config: &mut serde_json::Value,
+    host_limits: &std::collections::BTreeMap<String, crate::throttle::RateLimit>,
+) -> bool {
+    let current: Option<crate::throttle::RateLimit> = config.get("rate_limit")
+        .and_then(|limit| serde_json::from_value(limit.clone()).ok());
+    let mut own = current.is_some();
+    let mut limit = current.unwrap_or_default();
+    ui.checkbox(&mut own, "Own rate limit")
+        .on_hover_text("Overrides the flow's limit for this node's host");
+    if own {
+        ui.horizontal(|ui| {
+            ui.label("Per minute:");
+            ui.add(egui::DragValue::new(&mut limit.per_minute)).on_hover_text("0 for no limit");
+            ui.label("Runs at once:");
+            ui.add(egui::DragValue::new(&mut limit.concurrency)).on_hover_text("0 for no cap");
+            ui.label("429 retries:");
+            ui.add(egui::DragValue::new(&mut limit.retries).range(0..=20));
+        });
+    } else {
+        let host = config["url"].as_str().and_then(crate::throttle::host);
+        match host.as_ref().and_then(|h| Some((h, host_limits.get(h)?))) {
+            Some((host, limit)) => ui.label(format!("{}: {}/min, {} run(s) at once", host, limit.per_minute, limit.concurrency)),
+            None => ui.weak("No rate limit; set host limits in the flow's metadata"),
+        };
+    }
+
+    let updated = own.then_some(limit);
+    if updated == current {
+        return false;
+    }
+    match (config.as_object_mut(), updated) {
+        (Some(fields), Some(limit)) => {
+            fields.insert("rate_limit".to_string(), serde_json::json!(limit));
+        }
+        (Some(fields), None) => {
+            fields.remove("rate_limit");
+        }
+        (None, _) => {}
+    }
+    true
+}
+
+/// Pin the node's last output so editor runs reuse it instead of running
+/// the node, or show and drop the pinned one
+fn show_pinned_output(
+    ui: &mut egui::Ui,
+    pinned: &mut std::collections::BTreeMap<String, serde_json::Value>,
+    node_id: &str,
+    last_output: Option<&serde_json::Value>,
+) {
+    ui.label("Pinned data:");
+    match pinned.get(node_id) {
+        Some(value) => {
+            ui.colored_label(egui::Color32::from_rgb(255, 200, 80), "📌 Runs use this output instead of running the node");
+            super::json_tree::show(ui, &format!("pinned_{}", node_id), value);
+            ui.horizontal(|ui| {
+                if let Some(output) = last_output.filter(|output| *output != value) {
+                    if ui.button("Pin last output").clicked() {
+                        pinned.insert(node_id.to_string(), output.clone());
+                    }
+                }
+                if ui.button("Unpin").clicked() {
+                    pinned.remove(node_id);
+                }
+            });
+        }
+        None => {
+            let button = ui.add_enabled(last_output.is_some(), egui::Button::new("📌 Pin last output"))
+                .on_hover_text("Reuse this output in later runs instead of running the node and the nodes only it needs")
+                .on_disabled_hover_text("Run the flow to capture an output to pin");
+            if let Some(output) = last_output.filter(|_| button.clicked()) {
+                pinned.insert(node_id.to_string(), output.clone());
+            }
+        }
+    }
+}
+
+impl PropertiesPanel {
+    /// Pattern tester: the node's output for sample text, which starts out
+    /// as the upstream node's last output when that is a string
+    fn show_regex_tester(&mut self, ui: &mut egui::Ui, type_name: &str, config: &serde_json::Value, input: Option<&serde_json::Value>) {
+        ui.heading("Pattern Tester");
+
+        if self.regex_sample.is_empty() {
+            if let Some(text) = input.and_then(|v| v.as_str()) {
+                self.regex_sample = text.to_string();
+            }
+        }
+        ui.add(
+            egui::TextEdit::multiline(&mut self.regex_sample)
+                .hint_text("Sample text to test the pattern against")
+                .desired_width(ui.available_width())
+                .desired_rows(4),
+        );
+
+        match crate::patterns::preview(type_name, config, &self.regex_sample) {
+            Ok(output) => {
+                ui.label("Output:");
+                super::json_tree::show(ui, "regex_preview", &output);
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, format!("❌ Invalid pattern: {}", e));
+            }
+        }
+    }
+
+    /// Query preview: the node's output for a sample payload, which starts
+    /// out as the upstream node's last output
+    fn show_query_preview(&mut self, ui: &mut egui::Ui, config: &serde_json::Value, input: Option<&serde_json::Value>) {
+        ui.heading("Query Preview");
+
+        if self.query_sample.is_empty() {
+            if let Some(value) = input {
+                self.query_sample = serde_json::to_string_pretty(value).unwrap_or_default();
+            }
+        }
+        ui.add(
+            egui::TextEdit::multiline(&mut self.query_sample)
+                .hint_text("Sample JSON payload to run the query against")
+                .desired_width(ui.available_width())
+                .desired_rows(6)
+                .code_editor(),
+        );
+
+        if self.query_sample.trim().is_empty() {
+            ui.label("Paste a sample payload, or run the flow to use the upstream output.");
+            return;
+        }
+        let sample = match serde_json::from_str(&self.query_sample) {
+            Ok(sample) => sample,
+            Err(e) => {
+                ui.colored_label(egui::Color32::YELLOW, format!("⚠ Sample is not valid JSON: {}", e));
+                return;
+            }
+        };
+        match crate::query::evaluate(config, &sample) {
+            Ok(output) => {
+                ui.label("Output:");
+                super::json_tree::show(ui, "query_preview", &output);
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, format!("❌ Invalid query: {}", e));
+            }
+        }
+    }
+}
+
+impl PropertiesPanel {
+    /// Canned output the node returns in mock runs
+    fn show_mock_rule(
+        &mut self,
+        ui: &mut egui::Ui,
+        mocks: &mut Vec<crate::mock::MockRule>,
+        node_id: &str,
+        last_output: Option<&serde_json::Value>,
+    ) {
+        ui.label("Mock:");
+        let index = mocks.iter().position(|m| m.node == node_id);
+        let mut enabled = index.is_some();
+        if ui.checkbox(&mut enabled, "Return a canned output in mock runs").changed() {
+            match index {
+                Some(i) if !enabled => {
+                    mocks.remove(i);
+                }
+                None if enabled => mocks.push(crate::mock::MockRule {
+                    node: node_id.to_string(),
+                    output: last_output.cloned().unwrap_or_default(),
+                }),
+                _ => {}
+            }
+        }
+
+        let Some(rule) = mocks.iter_mut().find(|m| m.node == node_id) else {
+            return;
+        };
+        // Follow the rule unless the buffer holds an edit in progress
+        let pretty = |value: &serde_json::Value| serde_json::to_string_pretty(value).unwrap_or_default();
+        let parsed = serde_json::from_str::<serde_json::Value>(&self.mock_json);
+        if self.mock_node != node_id || parsed.is_ok_and(|v| v != rule.output) {
+            self.mock_node = node_id.to_string();
+            self.mock_json = pretty(&rule.output);
+        }
+
+        let response = ui.add(
+            egui::TextEdit::multiline(&mut self.mock_json)
+                .code_editor()
+                .desired_rows(4)
+                .desired_width(ui.available_width()),
+        );
+        match serde_json::from_str(&self.mock_json) {
+            Ok(output) if response.changed() => rule.output = output,
+            Ok(_) => {}
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, format!("Invalid JSON: {}", e));
+            }
+        }
+        if let Some(output) = last_output {
+            if ui.button("Use last output").on_hover_text("Mock the node with what it returned in the last run").clicked() {
+                rule.output = output.clone();
+                self.mock_json = pretty(output);
+            }
+        }
+    }
+}
+
+/// Fault injected into the node when the flow runs in chaos mode
+fn show_chaos_rule(ui: &mut egui::Ui, rules: &mut Vec<crate::chaos::FaultRule>, node_id: &str) {
+    use crate::chaos::{Fault, FaultRule};
+
+    ui.label("Chaos:");
+    let index = rules.iter().position(|r| r.node == node_id);
+    let mut enabled = index.is_some();
+    if ui.checkbox(&mut enabled, "Inject a fault in chaos runs").changed() {
+        match index {
+            Some(i) if !enabled => {
+                rules.remove(i);
+            }
+            None if enabled => rules.push(FaultRule::new(node_id, Fault::Http500)),
+            _ => {}
+        }
+    }
+
+    let Some(rule) = rules.iter_mut().find(|r| r.node == node_id) else {
+        return;
+    };
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source("chaos_fault")
+            .selected_text(rule.fault.label())
+            .show_ui(ui, |ui| {
+                for fault in Fault::ALL {
+                    ui.selectable_value(&mut rule.fault, fault, fault.label());
+                }
+            });
+        ui.label("Probability:");
+        ui.add(egui::Slider::new(&mut rule.probability, 0.0..=1.0));
+    });
+    if rule.fault == Fault::Timeout {
+        ui.horizontal(|ui| {
+            ui.label("Hang for:");
+            ui.add(egui::DragValue::new(&mut rule.timeout_ms).speed(100.0).suffix(" ms"));
+        });
+    }
+}
+
+/// Rendered preview for a template, plus declared-variable checks for
+/// prompt templates (which list their `variables`)
+fn show_template_preview(ui: &mut egui::Ui, config: &serde_json::Value, input: Option<&serde_json::Value>) {
+    let template = config["template"].as_str().unwrap_or("");
+
+    ui.heading("Template Preview");
+    if config.get("variables").is_some() {
+        let declared = crate::prompt::declared_variables(config);
+        for problem in crate::prompt::check(template, &declared) {
+            ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", problem));
+        }
+    }
+
+    let Some(input) = input else {
+        ui.label("Run the flow to preview the template with real input.");
+        return;
+    };
+
+    let rendered = match crate::prompt::render(template, input) {
+        Ok(text) => text,
+        Err(missing) => {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("❌ Input is missing: {}", missing.join(", ")),
+            );
+            return;
+        }
+    };
+
+    ui.add(
+        egui::TextEdit::multiline(&mut rendered.as_str())
+            .desired_width(ui.available_width())
+            .desired_rows(8),
+    );
+    ui.label(format!("{} characters", rendered.chars().count()));
+}
+
+/// Size of a vector index and, once the flow has run, the hits for the
+/// upstream vector
+fn show_vector_preview(ui: &mut egui::Ui, config: &serde_json::Value, input: Option<&serde_json::Value>) {
+    ui.heading("Search Preview");
+    let path = std::path::Path::new(config["path"].as_str().unwrap_or("vectors.jsonl"));
+    let records = match crate::vectors::load(path) {
+        Ok(records) => records,
+        Err(e) => {
+            ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", e));
+            return;
+        }
+    };
+    let dims = records.first().map_or(0, |r| r.vector.len());
+    ui.label(format!("{} vectors of {} dimensions", records.len(), dims));
+
+    let Some(query) = input.and_then(crate::vectors::as_vector) else {
+        ui.label("Run the flow to preview the search with the upstream vector.");
+        return;
+    };
+    if query.len() != dims {
+        ui.colored_label(egui::Color32::YELLOW, format!("⚠ Input has {} dimensions, the index {}", query.len(), dims));
+    }
+    let top_k = config["top_k"].as_u64().filter(|k| *k > 0).unwrap_or(5) as usize;
+    let hits = crate::vectors::search(&records, &query, top_k, config["min_score"].as_f64().unwrap_or(0.0));
+    for hit in hits {
+        ui.monospace(format!("{:.3}  {}", hit.score, hit.id));
+    }
+}
+
+/// Form fields for a config schema of `{"<key>": {"type", "description", "enum", "default"}}`.
+/// Returns whether a value changed.
+fn show_schema_form(ui: &mut egui::Ui, schema: &serde_json::Value, config: &mut serde_json::Value) -> bool {
+    let (Some(fields), Some(config)) = (schema.as_object(), config.as_object_mut()) else {
+        return false;
+    };
+
+    let mut changed = false;
+    egui::Grid::new("config_form").num_columns(2).show(ui, |ui| {
+        for (key, field) in fields {
+            let label = ui.label(key);
+            if let Some(description) = field["description"].as_str() {
+                label.on_hover_text(description);
+            }
+
+            // Fields are only written when edited; defaults went in when the node was created
+            let value = config.get(key).cloned().unwrap_or(serde_json::Value::Null);
+            let mut edited = None;
+            if let Some(options) = field["enum"].as_array() {
+                let current = value.as_str().unwrap_or("").to_string();
+                egui::ComboBox::from_id_source(key).selected_text(&current).show_ui(ui, |ui| {
+                    for option in options.iter().filter_map(|o| o.as_str()) {
+                        if ui.selectable_label(current == option, option).clicked() {
+                            edited = Some(serde_json::json!(option));
+                        }
+                    }
+                });
+            } else {
+                match field["type"].as_str().unwrap_or("string") {
+                    "boolean" => {
+                        let mut checked = value.as_bool().unwrap_or(false);
+                        if ui.checkbox(&mut checked, "").changed() {
+                            edited = Some(serde_json::json!(checked));
+                        }
+                    }
+                    "integer" => {
+                        let mut number = value.as_i64().unwrap_or(0);
+                        if ui.add(egui::DragValue::new(&mut number)).changed() {
+                            edited = Some(serde_json::json!(number));
+                        }
+                    }
+                    "number" => {
+                        let mut number = value.as_f64().unwrap_or(0.0);
+                        if ui.add(egui::DragValue::new(&mut number).speed(0.1)).changed() {
+                            edited = Some(serde_json::json!(number));
+                        }
+                    }
+                    _ => {
+                        let mut text = value.as_str().map(str::to_string).unwrap_or_else(|| {
+                            if value.is_null() { String::new() } else { value.to_string() }
+                        });
+                        if ui.text_edit_singleline(&mut text).changed() {
+                            edited = Some(serde_json::json!(text));
+                        }
+                    }
+                }
+            }
+            if let Some(edited) = edited {
+                config.insert(key.clone(), edited);
+                changed = true;
+            }
+            ui.end_row();
+        }
+    });
+    changed
+}
@@ -0,0 +1,137 @@
+//! Quick-Add Popup
+//!
+//! Tab, or a double-click on empty canvas, opens a search box at the
+//! pointer. Each word typed matches the start of a word in a node type's
+//! name, so "http g" finds `http_get`; Enter inserts the highlighted type
+//! there, connected after the selected node.
+
+use eframe::egui;
+use crate::flow::Position;
+use crate::nodes::NodeDef;
+use std::sync::Arc;
+
+/// Most matches listed at once
+const MAX_MATCHES: usize = 10;
+
+/// Quick-add popup state
+pub struct QuickAdd {
+    /// Where the popup is drawn, in screen space
+    pub screen_pos: egui::Pos2,
+    /// Where the node goes, in canvas space
+    pub position: Position,
+    query: String,
+    highlighted: usize,
+    focused: bool,
+}
+
+/// Outcome of the popup for this frame
+pub enum QuickAddAction {
+    None,
+    /// Insert a node of this type
+    Insert(String),
+    Close,
+}
+
+impl QuickAdd {
+    pub fn new(screen_pos: egui::Pos2, position: Position) -> Self {
+        Self { screen_pos, position, query: String::new(), highlighted: 0, focused: false }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> QuickAddAction {
+        let mut action = QuickAddAction::None;
+        let opening = !self.focused;
+        let found = matches(&self.query);
+        self.highlighted = self.highlighted.min(found.len().saturating_sub(1));
+
+        ctx.input(|i| {
+            if i.key_pressed(egui::Key::ArrowDown) && self.highlighted + 1 < found.len() {
+                self.highlighted += 1;
+            }
+            if i.key_pressed(egui::Key::ArrowUp) {
+                self.highlighted = self.highlighted.saturating_sub(1);
+            }
+            if i.key_pressed(egui::Key::Escape) {
+                action = QuickAddAction::Close;
+            }
+        });
+
+        let area = egui::Area::new(egui::Id::new("quick_add"))
+            .fixed_pos(self.screen_pos)
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(260.0);
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.query)
+                            .hint_text("Add node, e.g. http g")
+                            .desired_width(f32::INFINITY),
+                    );
+                    if !self.focused {
+                        response.request_focus();
+                        self.focused = true;
+                    }
+                    if response.changed() {
+                        self.highlighted = 0;
+                    }
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        action = match found.get(self.highlighted) {
+                            Some(def) => QuickAddAction::Insert(def.name.to_string()),
+                            None => QuickAddAction::Close,
+                        };
+                    }
+
+                    if found.is_empty() {
+                        ui.weak("No matching nodes");
+                    }
+                    for (index, def) in found.iter().enumerate() {
+                        let label = egui::RichText::new(format!("{}  ·  {}", def.name, def.category));
+                        let row = ui.selectable_label(index == self.highlighted, label).on_hover_text(def.description.as_ref());
+                        if row.clicked() {
+                            action = QuickAddAction::Insert(def.name.to_string());
+                        }
+                    }
+                });
+            });
+
+        // A click anywhere else dismisses it, except the double-click that opened it
+        if !opening && area.response.clicked_elsewhere() {
+            action = QuickAddAction::Close;
+        }
+        action
+    }
+}
+
+/// Node types whose names have a word starting with each word of `query`,
+/// shortest names first
+fn matches(query: &str) -> Vec<Arc<NodeDef>> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut found: Vec<Arc<NodeDef>> = crate::nodes::all_nodes()
+        .into_iter()
+        .filter(|def| {
+            let words: Vec<&str> = def.name.split('_').collect();
+            terms.iter().all(|term| words.iter().any(|word| word.starts_with(term.as_str())))
+        })
+        .collect();
+    found.sort_by(|a, b| a.name.len().cmp(&b.name.len()).then(a.name.cmp(&b.name)));
+    found.truncate(MAX_MATCHES);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(query: &str) -> Vec<String> {
+        matches(query).iter().map(|def| def.name.to_string()).collect()
+    }
+
+    #[test]
+    fn each_word_matches_the_start_of_a_name_word() {
+        assert_eq!(names("http g").first().map(String::as_str), Some("http_get"));
+        assert_eq!(names("HTTP G"), names("http g"));
+        assert!(names("http g").iter().all(|name| name.starts_with("http_") && name.contains("_g")));
+        assert!(!names("ttp").contains(&"http_get".to_string()));
+        assert!(names("zzz").is_empty());
+        assert_eq!(names("").len(), MAX_MATCHES);
+    }
+}
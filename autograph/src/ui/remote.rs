@@ -0,0 +1,155 @@
+//! Server Panel
+//!
+//! Deploy the flow in the editor to an Autograph server and run it there.
+//! Requests run on a background thread so the editor stays responsive; the
+//! finished run's record is loaded into the timeline like a run from history.
+
+use eframe::egui;
+use crate::flow::Flow;
+use crate::remote::RemoteServer;
+use crate::runs::RunRecord;
+use std::sync::mpsc;
+
+/// Server panel state
+pub struct RemotePanel {
+    /// Base URL of the server
+    pub url: String,
+
+    /// API key, when the server was started with `--auth-config`
+    pub api_key: String,
+
+    /// Changelog message for deploys
+    pub message: String,
+
+    /// Run input, as JSON
+    pub input: String,
+
+    /// Deploy the editor's flow before running it remotely
+    pub deploy_first: bool,
+}
+
+impl Default for RemotePanel {
+    fn default() -> Self {
+        Self {
+            url: std::env::var("AUTOGRAPH_SERVER").unwrap_or_else(|_| "http://localhost:3000".to_string()),
+            api_key: std::env::var("AUTOGRAPH_API_KEY").unwrap_or_default(),
+            message: String::new(),
+            input: "null".to_string(),
+            deploy_first: true,
+        }
+    }
+}
+
+/// What the user asked for this frame
+pub enum RemoteAction {
+    None,
+    Deploy,
+    Run,
+}
+
+/// Result of a finished remote request
+pub enum RemoteOutcome {
+    Deployed(String),
+    Ran(RunRecord),
+}
+
+/// A request running on a background thread
+pub struct RemoteJob {
+    /// What is running, e.g. `Deploying fetch`
+    pub label: String,
+    receiver: mpsc::Receiver<Result<RemoteOutcome, String>>,
+}
+
+impl RemoteJob {
+    fn spawn(label: String, work: impl FnOnce() -> Result<RemoteOutcome, String> + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(work());
+        });
+        Self { label, receiver }
+    }
+
+    pub fn deploy(server: RemoteServer, flow_name: String, flow: Flow, message: String) -> Self {
+        Self::spawn(format!("Deploying {} to {}", flow_name, server.url), move || {
+            server.deploy(&flow_name, &flow, &message, &author()).map(RemoteOutcome::Deployed)
+        })
+    }
+
+    /// Run the deployed flow, deploying `flow` first if given
+    pub fn run(server: RemoteServer, flow_name: String, flow: Option<Flow>, input: serde_json::Value) -> Self {
+        Self::spawn(format!("Running {} on {}", flow_name, server.url), move || {
+            if let Some(flow) = flow {
+                server.deploy(&flow_name, &flow, "Deployed to run from the editor", &author())?;
+            }
+            server.run(&flow_name, &input).map(RemoteOutcome::Ran)
+        })
+    }
+
+    /// The result, once the request is done
+    pub fn poll(&self) -> Option<Result<RemoteOutcome, String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err("The request stopped unexpectedly".to_string())),
+        }
+    }
+}
+
+fn author() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "editor".to_string())
+}
+
+impl RemotePanel {
+    /// The server as configured in the panel
+    pub fn server(&self) -> RemoteServer {
+        RemoteServer {
+            url: self.url.trim().to_string(),
+            api_key: Some(self.api_key.trim().to_string()).filter(|k| !k.is_empty()),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, flow_name: &str, job: Option<&RemoteJob>) -> RemoteAction {
+        let mut action = RemoteAction::None;
+
+        egui::Grid::new("remote_server").num_columns(2).show(ui, |ui| {
+            ui.label("Server:");
+            ui.add(egui::TextEdit::singleline(&mut self.url).desired_width(240.0));
+            ui.end_row();
+            ui.label("API key:");
+            ui.add(egui::TextEdit::singleline(&mut self.api_key).password(true).desired_width(240.0));
+            ui.end_row();
+        });
+        ui.separator();
+
+        let idle = job.is_none();
+        ui.strong(format!("Deploy '{}'", flow_name));
+        ui.add(egui::TextEdit::singleline(&mut self.message).hint_text("What changed?").desired_width(300.0));
+        if ui.add_enabled(idle, egui::Button::new("🚀 Deploy to server")).clicked() {
+            action = RemoteAction::Deploy;
+        }
+        ui.separator();
+
+        ui.strong("Run remotely");
+        ui.label("Input (JSON):");
+        ui.add(egui::TextEdit::multiline(&mut self.input).code_editor().desired_rows(2).desired_width(300.0));
+        let input_valid = serde_json::from_str::<serde_json::Value>(&self.input).is_ok();
+        if !input_valid {
+            ui.colored_label(egui::Color32::RED, "Input is not valid JSON");
+        }
+        ui.checkbox(&mut self.deploy_first, "Deploy the editor's flow first");
+        if ui.add_enabled(idle && input_valid, egui::Button::new("☁ Run remotely")).clicked() {
+            action = RemoteAction::Run;
+        }
+
+        if let Some(job) = job {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!("{}…", job.label));
+            });
+        }
+        action
+    }
+}
@@ -0,0 +1,126 @@
+//! Run Comparison Window
+//!
+//! Two runs of the flow side by side, node by node, with the nodes whose
+//! outputs or durations differ beyond the thresholds highlighted.
+
+use eframe::egui;
+use crate::run_diff::{RunComparison, Thresholds};
+use crate::runs::{format_timestamp, RunRecord};
+
+/// Run comparison state
+pub struct RunCompare {
+    before: RunRecord,
+    after: RunRecord,
+    thresholds: Thresholds,
+    comparison: RunComparison,
+
+    /// Hide nodes that don't differ
+    only_changed: bool,
+}
+
+impl RunCompare {
+    /// Compare two runs, the older one first whichever order they come in
+    pub fn new(a: RunRecord, b: RunRecord) -> Self {
+        let (before, after) = if a.started_at <= b.started_at { (a, b) } else { (b, a) };
+        let thresholds = Thresholds::default();
+        let comparison = RunComparison::between(&before, &after, thresholds);
+        Self { before, after, thresholds, comparison, only_changed: false }
+    }
+
+    /// Draw the window; returns false once it is closed
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+
+        egui::Window::new("⇄ Compare Runs")
+            .open(&mut open)
+            .default_size([720.0, 480.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("compare_runs_header").num_columns(2).show(ui, |ui| {
+                    for (label, run) in [("A", &self.before), ("B", &self.after)] {
+                        ui.strong(label);
+                        ui.label(format!("{}  {}  ({}ms, {:?})", run.id, format_timestamp(run.started_at), run.duration_ms, run.status));
+                        ui.end_row();
+                    }
+                });
+
+                let mut thresholds = self.thresholds;
+                ui.horizontal(|ui| {
+                    ui.label("Duration threshold:");
+                    ui.add(egui::DragValue::new(&mut thresholds.duration_pct).range(0.0..=1000.0).suffix(" %"));
+                    ui.label("and");
+                    ui.add(egui::DragValue::new(&mut thresholds.duration_ms).range(0..=60_000).suffix(" ms"));
+                    ui.checkbox(&mut self.only_changed, "Only differences");
+                });
+                if thresholds != self.thresholds {
+                    self.thresholds = thresholds;
+                    self.comparison = RunComparison::between(&self.before, &self.after, thresholds);
+                }
+
+                ui.label(self.comparison.summary());
+                ui.separator();
+
+                let changed = egui::Color32::from_rgb(255, 165, 0);
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("compare_runs").striped(true).num_columns(5).show(ui, |ui| {
+                        ui.strong("Node");
+                        ui.strong("A output");
+                        ui.strong("B output");
+                        ui.strong("A time");
+                        ui.strong("B time");
+                        ui.end_row();
+
+                        for node in self.comparison.nodes.iter().filter(|n| !self.only_changed || n.differs()) {
+                            if node.differs() {
+                                ui.colored_label(changed, format!("● {}", node.id));
+                            } else {
+                                ui.label(&node.id);
+                            }
+
+                            for output in [&node.before, &node.after] {
+                                let text = output.as_ref().map_or("—".to_string(), |v| v.to_string());
+                                let label = if node.output_changed() {
+                                    ui.colored_label(changed, egui::RichText::new(truncate(&text)).monospace())
+                                } else {
+                                    ui.monospace(truncate(&text))
+                                };
+                                label.on_hover_ui(|ui| {
+                                    if node.output_changed() {
+                                        ui.label(format!("Changed at: {}", paths(&node.changed_paths)));
+                                    }
+                                    ui.monospace(output.as_ref().map_or("—".to_string(), |v| serde_json::to_string_pretty(v).unwrap_or_default()));
+                                });
+                            }
+
+                            for ms in [node.before_ms, node.after_ms] {
+                                let text = ms.map_or("—".to_string(), |ms| format!("{}ms", ms));
+                                if node.duration_changed {
+                                    ui.colored_label(changed, text);
+                                } else {
+                                    ui.label(text);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        open
+    }
+}
+
+/// `/a, /b/0`, with the whole output shown as `(whole output)`
+fn paths(changed: &[String]) -> String {
+    changed.iter()
+        .map(|p| if p.is_empty() { "(whole output)" } else { p.as_str() })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn truncate(text: &str) -> String {
+    const MAX: usize = 60;
+    if text.chars().count() <= MAX {
+        return text.to_string();
+    }
+    format!("{}…", text.chars().take(MAX).collect::<String>())
+}
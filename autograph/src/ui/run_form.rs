@@ -0,0 +1,168 @@
+//! Run Input Form
+//!
+//! Asks for a run's input with a form generated from the flow's input
+//! schema: a field per property, typed by the property's schema, checked
+//! against the schema as it is filled in. Optional properties are left out
+//! of the input unless ticked.
+
+use eframe::egui;
+use crate::input_schema;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// Run form state
+pub struct RunForm {
+    schema: JsonValue,
+    value: JsonValue,
+
+    /// Text of fields edited as JSON (arrays, objects, untyped), by field
+    json_text: HashMap<String, String>,
+}
+
+/// Outcome of the form for this frame
+pub enum RunFormAction {
+    None,
+    Run(JsonValue),
+    Cancel,
+}
+
+impl RunForm {
+    /// A form for `schema`, starting from `previous` input if it fits
+    pub fn new(schema: JsonValue, previous: &JsonValue) -> Self {
+        let value = if input_schema::validate(&schema, previous).is_empty() {
+            previous.clone()
+        } else {
+            input_schema::default_value(&schema)
+        };
+        Self { schema, value, json_text: HashMap::new() }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> RunFormAction {
+        let mut action = RunFormAction::None;
+
+        egui::Window::new("▶ Run Input")
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                if let Some(description) = self.schema["description"].as_str() {
+                    ui.label(description);
+                }
+
+                let properties = self.schema["properties"].as_object().cloned().filter(|_| self.value.is_object());
+                match properties {
+                    Some(properties) => {
+                        let fields = self.value.as_object_mut().expect("checked above");
+                        let required: Vec<&str> = self.schema["required"].as_array().into_iter().flatten()
+                            .filter_map(|n| n.as_str())
+                            .collect();
+                        egui::Grid::new("run_form").num_columns(2).show(ui, |ui| {
+                            for (name, schema) in &properties {
+                                let label = if required.contains(&name.as_str()) { format!("{} *", name) } else { name.clone() };
+                                let label = ui.label(label);
+                                if let Some(description) = schema["description"].as_str() {
+                                    label.on_hover_text(description);
+                                }
+                                ui.horizontal(|ui| {
+                                    if !required.contains(&name.as_str()) {
+                                        // Optional fields stay out of the input until they are set
+                                        let mut set = fields.contains_key(name);
+                                        if ui.checkbox(&mut set, "").on_hover_text("Include this field").changed() {
+                                            if set {
+                                                fields.insert(name.clone(), input_schema::default_value(schema));
+                                            } else {
+                                                fields.remove(name);
+                                            }
+                                            self.json_text.remove(name);
+                                        }
+                                    }
+                                    if required.contains(&name.as_str()) && !fields.contains_key(name) {
+                                        fields.insert(name.clone(), input_schema::default_value(schema));
+                                    }
+                                    if let Some(value) = fields.get_mut(name) {
+                                        let text = self.json_text.entry(name.clone()).or_insert_with(|| value.to_string());
+                                        field(ui, schema, value, text);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                    }
+                    None => {
+                        let text = self.json_text.entry(String::new()).or_insert_with(|| self.value.to_string());
+                        field(ui, &self.schema, &mut self.value, text);
+                    }
+                }
+
+                let errors = input_schema::validate(&self.schema, &self.value);
+                for line in input_schema::describe(&errors) {
+                    ui.colored_label(egui::Color32::RED, line);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(errors.is_empty(), egui::Button::new("▶ Run")).clicked() {
+                        action = RunFormAction::Run(self.value.clone());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = RunFormAction::Cancel;
+                    }
+                });
+            });
+
+        action
+    }
+}
+
+/// Editor for one value, chosen by its schema
+fn field(ui: &mut egui::Ui, schema: &JsonValue, value: &mut JsonValue, text: &mut String) {
+    if let Some(options) = schema["enum"].as_array() {
+        let selected = value.to_string();
+        egui::ComboBox::from_id_source(schema.to_string())
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for option in options {
+                    if ui.selectable_label(option == value, option.to_string()).clicked() {
+                        *value = option.clone();
+                    }
+                }
+            });
+        return;
+    }
+
+    match input_schema::types(schema).first().copied() {
+        Some("string") => {
+            let mut s = value.as_str().unwrap_or_default().to_string();
+            if ui.text_edit_singleline(&mut s).changed() {
+                *value = JsonValue::String(s);
+            }
+        }
+        Some("integer") => {
+            let mut n = value.as_i64().unwrap_or_default();
+            if ui.add(egui::DragValue::new(&mut n)).changed() {
+                *value = JsonValue::from(n);
+            }
+        }
+        Some("number") => {
+            let mut n = value.as_f64().unwrap_or_default();
+            if ui.add(egui::DragValue::new(&mut n).speed(0.1)).changed() {
+                *value = JsonValue::from(n);
+            }
+        }
+        Some("boolean") => {
+            let mut b = value.as_bool().unwrap_or_default();
+            if ui.checkbox(&mut b, "").changed() {
+                *value = JsonValue::Bool(b);
+            }
+        }
+        _ => {
+            // Arrays, objects and untyped values are entered as JSON
+            let response = ui.add(egui::TextEdit::multiline(text).code_editor().desired_rows(2).desired_width(240.0));
+            match serde_json::from_str(text) {
+                Ok(parsed) if response.changed() => *value = parsed,
+                Ok(_) => {}
+                Err(_) => {
+                    ui.colored_label(egui::Color32::RED, "Not valid JSON");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Flow Statistics Overlay
+//!
+//! Graph metrics of the flow being edited, with complexity warnings that
+//! suggest splitting large flows into subflows.
+
+use eframe::egui;
+use crate::complexity::{GraphMetrics, MAX_DEPTH, MAX_FAN_OUT, MAX_NODES};
+
+/// Draw the flow's graph metrics and warnings
+pub fn show_stats(ui: &mut egui::Ui, metrics: &GraphMetrics) {
+    egui::Grid::new("flow_stats").num_columns(2).show(ui, |ui| {
+        metric_row(ui, "Nodes", metrics.nodes, MAX_NODES);
+        ui.label("Edges");
+        ui.label(metrics.edges.to_string());
+        ui.end_row();
+        metric_row(ui, "Depth", metrics.depth, MAX_DEPTH);
+        let (node, fan_out) = metrics.max_fan_out.clone().unwrap_or_default();
+        metric_row(ui, "Max fan-out", fan_out, MAX_FAN_OUT);
+        if !node.is_empty() {
+            ui.label("");
+            ui.weak(format!("from {}", node));
+            ui.end_row();
+        }
+        ui.label("Cycle attempts");
+        ui.label(metrics.cycle_edges.len().to_string());
+        ui.end_row();
+    });
+
+    ui.separator();
+    ui.label("Nodes by category:");
+    for (category, count) in &metrics.by_category {
+        ui.label(format!("  {}: {}", category, count));
+    }
+
+    let warnings = metrics.warnings();
+    if !warnings.is_empty() {
+        ui.separator();
+        for warning in warnings {
+            ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", warning));
+        }
+    }
+}
+
+/// A metric with its threshold, highlighted once it's exceeded
+fn metric_row(ui: &mut egui::Ui, label: &str, value: usize, max: usize) {
+    ui.label(label);
+    let text = format!("{} / {}", value, max);
+    if value > max {
+        ui.colored_label(egui::Color32::YELLOW, text);
+    } else {
+        ui.label(text);
+    }
+    ui.end_row();
+}
@@ -0,0 +1,120 @@
+//! Save as Template Dialog
+//!
+//! Prompt for the name, description and category of a user template made
+//! from the flow in the editor, and for which config fields to ask for when
+//! the template is used.
+
+use eframe::egui;
+use crate::flow::Flow;
+use crate::templates::TemplateParam;
+
+/// Save-as-template prompt state
+pub struct TemplateDialog {
+    pub name: String,
+    pub description: String,
+    pub category: String,
+
+    /// Config fields that could be parameters, and whether each is one
+    pub candidates: Vec<(TemplateParam, bool)>,
+
+    /// Why the last save failed
+    pub error: Option<String>,
+}
+
+/// Outcome of the prompt for this frame
+pub enum TemplateDialogAction {
+    None,
+    Save,
+    Cancel,
+}
+
+impl TemplateDialog {
+    pub fn new(flow_name: &str, flow: &Flow) -> Self {
+        let candidates = flow.nodes.iter()
+            .flat_map(|node| {
+                let fields = node.config.as_object().into_iter().flatten();
+                fields
+                    .filter(|(_, value)| value.is_string() || value.is_number())
+                    .map(|(field, _)| {
+                        let param = TemplateParam {
+                            label: field.clone().into(),
+                            node: node.id.clone().into(),
+                            field: field.clone().into(),
+                        };
+                        (param, false)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        Self {
+            name: flow_name.to_string(),
+            description: String::new(),
+            category: "My Templates".to_string(),
+            candidates,
+            error: None,
+        }
+    }
+
+    /// The config fields picked as parameters
+    pub fn params(&self) -> Vec<TemplateParam> {
+        self.candidates.iter().filter(|(_, picked)| *picked).map(|(param, _)| param.clone()).collect()
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> TemplateDialogAction {
+        let mut action = TemplateDialogAction::None;
+
+        egui::Window::new("📋 Save as Template")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Grid::new("template_fields").num_columns(2).show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.name);
+                    ui.end_row();
+                    ui.label("Category:");
+                    ui.text_edit_singleline(&mut self.category);
+                    ui.end_row();
+                });
+                ui.label("Description:");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.description)
+                        .desired_rows(2)
+                        .desired_width(300.0),
+                );
+                if !self.candidates.is_empty() {
+                    ui.collapsing("Parameters asked for on use", |ui| {
+                        egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                            for (param, picked) in &mut self.candidates {
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(picked, format!("{}.{}", param.node, param.field));
+                                    if *picked {
+                                        let mut label = param.label.to_string();
+                                        if ui.add(egui::TextEdit::singleline(&mut label).hint_text("Label").desired_width(140.0)).changed() {
+                                            param.label = label.into();
+                                        }
+                                    }
+                                });
+                            }
+                        });
+                    });
+                }
+                ui.weak(format!("Saved to {}/ and listed in the template gallery", crate::templates::USER_TEMPLATES_DIR));
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::RED, format!("❌ {}", error));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!self.name.trim().is_empty(), egui::Button::new("Save")).clicked() {
+                        action = TemplateDialogAction::Save;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = TemplateDialogAction::Cancel;
+                    }
+                });
+            });
+
+        action
+    }
+}
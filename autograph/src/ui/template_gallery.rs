@@ -0,0 +1,137 @@
+//! Template Gallery
+//!
+//! Every template as a card with a thumbnail of its graph. Picking one
+//! shows its description and prompts for its parameters, which are written
+//! into the node configs when the template is used.
+
+use eframe::egui;
+use super::flow_browser::thumbnail;
+use crate::flow::Flow;
+use crate::templates::WorkflowTemplate;
+use std::sync::Arc;
+
+/// A template with its flow built once for the thumbnails
+struct Preview {
+    template: Arc<WorkflowTemplate>,
+    flow: Flow,
+}
+
+/// Gallery state
+#[derive(Default)]
+pub struct TemplateGallery {
+    previews: Vec<Preview>,
+
+    /// Whether `previews` reflects the template list
+    loaded: bool,
+
+    /// Filter on name, category and description
+    search: String,
+
+    /// Name of the picked template
+    selected: Option<String>,
+
+    /// Values typed for the picked template's parameters
+    values: Vec<String>,
+}
+
+/// What the user asked for this frame
+pub enum GalleryAction {
+    None,
+    /// Open the template's flow with the parameters filled in
+    Use(Flow),
+    /// Save the editor's flow as a template
+    SaveCurrent,
+}
+
+impl TemplateGallery {
+    /// Rebuild the template list on next show
+    pub fn invalidate(&mut self) {
+        self.loaded = false;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) -> GalleryAction {
+        let mut action = GalleryAction::None;
+        if !self.loaded {
+            self.previews = crate::templates::all_templates()
+                .into_iter()
+                .map(|template| Preview { flow: template.create(), template })
+                .collect();
+            self.loaded = true;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search templates").desired_width(200.0));
+            if ui.button("📋 Save current as template…").clicked() {
+                action = GalleryAction::SaveCurrent;
+            }
+        });
+        ui.separator();
+
+        let search = self.search.to_lowercase();
+        let mut picked = None;
+        ui.columns(2, |columns| {
+            egui::ScrollArea::vertical().id_source("template_cards").show(&mut columns[0], |ui| {
+                for (index, preview) in self.previews.iter().enumerate() {
+                    let template = &preview.template;
+                    let matches = [&template.name, &template.category, &template.description]
+                        .iter()
+                        .any(|text| text.to_lowercase().contains(&search));
+                    if !matches {
+                        continue;
+                    }
+                    let selected = self.selected.as_deref() == Some(template.name.as_ref());
+                    let card = egui::Frame::group(ui.style())
+                        .fill(if selected { ui.visuals().selection.bg_fill } else { egui::Color32::TRANSPARENT })
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                let thumb = thumbnail(ui, &preview.flow, egui::vec2(96.0, 64.0));
+                                ui.vertical(|ui| {
+                                    ui.strong(template.name.as_ref());
+                                    ui.weak(template.category.as_ref());
+                                });
+                                thumb
+                            })
+                            .inner
+                        });
+                    if card.inner.clicked() || card.response.interact(egui::Sense::click()).clicked() {
+                        picked = Some(index);
+                    }
+                }
+            });
+
+            let ui = &mut columns[1];
+            let Some(preview) = self.previews.iter().find(|p| self.selected.as_deref() == Some(p.template.name.as_ref())) else {
+                ui.weak("Pick a template to see its details");
+                return;
+            };
+            let template = &preview.template;
+            ui.heading(template.name.as_ref());
+            ui.weak(format!("{} · {} nodes", template.category, preview.flow.nodes.len()));
+            thumbnail(ui, &preview.flow, egui::vec2(240.0, 160.0));
+            ui.label(template.description.as_ref());
+            ui.separator();
+
+            if !template.params.is_empty() {
+                egui::Grid::new("template_params").num_columns(2).show(ui, |ui| {
+                    for (param, value) in template.params.iter().zip(&mut self.values) {
+                        ui.label(param.label.as_ref()).on_hover_text(format!("{}.{}", param.node, param.field));
+                        ui.add(egui::TextEdit::singleline(value).desired_width(200.0));
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+            }
+            if ui.button("✓ Use template").clicked() {
+                action = GalleryAction::Use(template.instantiate(&self.values));
+            }
+        });
+
+        if let Some(index) = picked {
+            let preview = &self.previews[index];
+            self.selected = Some(preview.template.name.to_string());
+            self.values = preview.template.params.iter().map(|p| p.value_in(&preview.flow)).collect();
+        }
+        action
+    }
+}
@@ -0,0 +1,164 @@
+//! Tensor Output View
+//!
+//! Shows a tensor node's output as its shape and statistics with a heatmap
+//! of the values (a grid of numbers for small matrices), instead of a raw
+//! JSON dump. Tensors with more than two axes show their first matrix.
+
+use eframe::egui;
+use serde_json::Value as JsonValue;
+
+/// Largest matrix drawn cell by cell; bigger ones are downsampled
+const MAX_CELLS: usize = 48;
+/// Largest matrix that also gets its numbers printed
+const MAX_GRID: usize = 8;
+
+/// A tensor decoded from a node's JSON output
+pub struct Tensor {
+    pub shape: Vec<usize>,
+    pub dtype: String,
+    /// Row-major values
+    pub values: Vec<f64>,
+}
+
+impl Tensor {
+    /// Decode `{"shape", "data", "dtype"}`, the `[rows, cols, data]` layout
+    /// of `tensor_new_2d`, or nested numeric arrays
+    pub fn parse(value: &JsonValue) -> Option<Self> {
+        if let Some(data) = value.get("data").and_then(|d| d.as_array()) {
+            let shape = dims(value.get("shape")?)?;
+            let values = numbers(data)?;
+            let dtype = value.get("dtype").and_then(|d| d.as_str()).unwrap_or("f32");
+            return Self::checked(shape, dtype, values);
+        }
+
+        let items = value.as_array()?;
+        if let [rows, cols, JsonValue::Array(data)] = items.as_slice() {
+            if let (Some(rows), Some(cols)) = (rows.as_u64(), cols.as_u64()) {
+                return Self::checked(vec![rows as usize, cols as usize], "f32", numbers(data)?);
+            }
+        }
+
+        let mut shape = Vec::new();
+        let mut values = Vec::new();
+        nested(value, 0, &mut shape, &mut values)?;
+        Self::checked(shape, "f32", values)
+    }
+
+    fn checked(shape: Vec<usize>, dtype: &str, values: Vec<f64>) -> Option<Self> {
+        (!shape.is_empty() && shape.iter().product::<usize>() == values.len())
+            .then(|| Self { shape, dtype: dtype.to_string(), values })
+    }
+
+    /// Rows and columns of the matrix shown: the last two axes, or one row
+    /// for a vector
+    fn matrix(&self) -> (usize, usize) {
+        match self.shape.as_slice() {
+            [cols] => (1, *cols),
+            [.., rows, cols] => (*rows, *cols),
+            [] => (0, 0),
+        }
+    }
+}
+
+fn dims(value: &JsonValue) -> Option<Vec<usize>> {
+    value.as_array()?.iter().map(|d| d.as_u64().map(|d| d as usize)).collect()
+}
+
+fn numbers(values: &[JsonValue]) -> Option<Vec<f64>> {
+    values.iter().map(JsonValue::as_f64).collect()
+}
+
+/// Flatten rectangular nested arrays, recording the size at each depth
+fn nested(value: &JsonValue, depth: usize, shape: &mut Vec<usize>, values: &mut Vec<f64>) -> Option<()> {
+    match value {
+        JsonValue::Number(n) => {
+            // Every number sits at the same depth
+            (depth == shape.len()).then_some(())?;
+            values.push(n.as_f64()?);
+            Some(())
+        }
+        JsonValue::Array(items) if !items.is_empty() => {
+            match shape.get(depth) {
+                Some(size) if *size != items.len() => return None,
+                Some(_) => {}
+                None if depth == shape.len() && values.is_empty() => shape.push(items.len()),
+                None => return None,
+            }
+            items.iter().try_for_each(|item| nested(item, depth + 1, shape, values))
+        }
+        _ => None,
+    }
+}
+
+/// Summary, statistics and heatmap of a tensor
+pub fn show_tensor(ui: &mut egui::Ui, tensor: &Tensor) {
+    let shape: Vec<String> = tensor.shape.iter().map(usize::to_string).collect();
+    ui.label(format!("Tensor [{}] {} ({} values)", shape.join(" × "), tensor.dtype, tensor.values.len()));
+
+    // A zero-sized axis leaves nothing to summarize or draw
+    if tensor.values.is_empty() {
+        ui.weak("Empty tensor");
+        return;
+    }
+
+    let min = tensor.values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = tensor.values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mean = tensor.values.iter().sum::<f64>() / tensor.values.len() as f64;
+    ui.label(format!("min {:.4}   max {:.4}   mean {:.4}", min, max, mean));
+
+    let (rows, cols) = tensor.matrix();
+    if tensor.shape.len() > 2 {
+        ui.weak(format!("Showing the first {} × {} matrix", rows, cols));
+    }
+    let cells = &tensor.values[..rows * cols];
+
+    if rows <= MAX_GRID && cols <= MAX_GRID {
+        egui::Grid::new("tensor_values").striped(true).show(ui, |ui| {
+            for row in cells.chunks(cols) {
+                for value in row {
+                    ui.monospace(format!("{:.4}", value));
+                }
+                ui.end_row();
+            }
+        });
+    }
+
+    heatmap(ui, cells, rows, cols, min, max);
+}
+
+/// Cells colored from blue (min) to red (max), hover for the value;
+/// large matrices show every n-th row and column
+fn heatmap(ui: &mut egui::Ui, cells: &[f64], rows: usize, cols: usize, min: f64, max: f64) {
+    let step = rows.max(cols).div_ceil(MAX_CELLS).max(1);
+    let (shown_rows, shown_cols) = (rows.div_ceil(step), cols.div_ceil(step));
+    if step > 1 {
+        ui.weak(format!("Every {}th row and column", step));
+    }
+
+    let cell = (ui.available_width() / shown_cols as f32).clamp(4.0, 24.0);
+    let (rect, response) = ui.allocate_exact_size(
+        egui::Vec2::new(cell * shown_cols as f32, cell * shown_rows as f32),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let range = if max > min { max - min } else { 1.0 };
+
+    for r in 0..shown_rows {
+        for c in 0..shown_cols {
+            let value = cells[r * step * cols + c * step];
+            let t = ((value - min) / range) as f32;
+            let color = egui::Color32::from_rgb((255.0 * t) as u8, 60, (255.0 * (1.0 - t)) as u8);
+            let min_corner = rect.min + egui::Vec2::new(c as f32 * cell, r as f32 * cell);
+            painter.rect_filled(egui::Rect::from_min_size(min_corner, egui::Vec2::splat(cell)), 0.0, color);
+        }
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        let c = ((pointer.x - rect.min.x) / cell) as usize;
+        let r = ((pointer.y - rect.min.y) / cell) as usize;
+        if r < shown_rows && c < shown_cols {
+            let (row, col) = (r * step, c * step);
+            response.on_hover_text(format!("[{}, {}] = {}", row, col, cells[row * cols + col]));
+        }
+    }
+}
@@ -4,12 +4,16 @@
 //! any point in the execution history.
 
 use eframe::egui;
+use crate::gpu_stats::{format_bytes, GpuMemoryStats};
 
 /// Execution timeline state
 #[derive(Default)]
 pub struct Timeline {
     /// Currently selected timeline entry
     selected_entry: Option<usize>,
+
+    /// Device memory used by the run, when it ran on the GPU
+    gpu_memory: Option<GpuMemoryStats>,
 }
 
 /// Entry in the execution timeline
@@ -24,11 +28,22 @@ pub struct TimelineEntry {
 }
 
 impl Timeline {
+    /// Show the GPU memory used by the run being displayed
+    pub fn set_gpu_memory(&mut self, stats: Option<GpuMemoryStats>) {
+        self.gpu_memory = stats;
+    }
+
+    /// Highlight an entry, or none
+    pub fn select(&mut self, entry: Option<usize>) {
+        self.selected_entry = entry;
+    }
+
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
         timeline_entries: &[TimelineEntry],
         on_entry_clicked: &mut Option<usize>,
+        on_replay: &mut Option<usize>,
     ) {
         ui.heading("Execution Timeline");
         ui.separator();
@@ -76,7 +91,7 @@ impl Timeline {
                         *on_entry_clicked = Some(idx);
                     }
 
-                    response.on_hover_text(format!("Node: {}\nClick to inspect", entry.node_id));
+                    response.on_hover_text(format!("Node: {}\nClick to rewind the canvas to this step", entry.node_id));
                 });
 
                 // Show output if selected
@@ -86,19 +101,16 @@ impl Timeline {
 
                         if let Some(output) = &entry.output {
                             ui.label("Output:");
-                            ui.add(
-                                egui::TextEdit::multiline(&mut output.as_str())
-                                    .desired_width(ui.available_width())
-                                    .desired_rows(5)
-                                    .code_editor(),
-                            );
+                            super::json_tree::show_text(ui, &format!("timeline_output_{}", idx), output);
                         } else {
                             ui.label("Output: (not captured)");
                         }
 
-                        if ui.button("🔄 Replay from here").clicked() {
-                            // TODO: Implement replay
-                            ui.label("Replay coming soon!");
+                        if ui.button("🔄 Replay from here")
+                            .on_hover_text("Restore upstream outputs and re-run this node and everything after it")
+                            .clicked()
+                        {
+                            *on_replay = Some(idx);
                         }
                     });
                 }
@@ -107,5 +119,22 @@ impl Timeline {
 
         ui.separator();
         ui.label(format!("Total entries: {}", timeline_entries.len()));
+
+        if let Some(stats) = &self.gpu_memory {
+            ui.label(format!("🎮 GPU: {}", stats.device));
+            ui.add(
+                egui::ProgressBar::new(stats.peak_fraction())
+                    .text(format!(
+                        "peak {} / {}",
+                        format_bytes(stats.peak_bytes),
+                        format_bytes(stats.device_total_bytes)
+                    )),
+            )
+            .on_hover_text(format!(
+                "{} allocations, {} still allocated at the end of the run",
+                stats.allocations,
+                format_bytes(stats.allocated_bytes)
+            ));
+        }
     }
 }
@@ -0,0 +1,131 @@
+//! Undo History
+//!
+//! Each tab keeps its own history of its flow. Instead of every edit
+//! recording itself, the flow is compared with the last recorded state
+//! once input may have changed it and no mouse button is held, so a whole
+//! drag is a single step and idle frames don't serialize the flow.
+//! Every recorded step gets a new revision, which values derived from the
+//! flow are cached by.
+
+use crate::flow::Flow;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Steps kept per tab
+const LIMIT: usize = 100;
+
+/// Revisions are unique across tabs, so a cache can't mix up their flows
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+pub struct UndoHistory {
+    undo: Vec<Flow>,
+    redo: Vec<Flow>,
+    /// Last recorded state, with its JSON for cheap comparison
+    current: Option<(Flow, serde_json::Value)>,
+    revision: u64,
+    /// Whether the flow may have changed since the last comparison
+    dirty: bool,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        Self { undo: Vec::new(), redo: Vec::new(), current: None, revision: next_revision(), dirty: true }
+    }
+}
+
+impl UndoHistory {
+    /// Note that the flow may have changed, so the next `record` compares it
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Record `flow` as a new step if it was marked dirty and changed since
+    /// the last one. Returns whether it did.
+    pub fn record(&mut self, flow: &Flow) -> bool {
+        if !std::mem::take(&mut self.dirty) {
+            return false;
+        }
+        let json = serde_json::to_value(flow).unwrap_or_default();
+        if self.current.as_ref().is_some_and(|(_, last)| *last == json) {
+            return false;
+        }
+        if let Some((previous, _)) = self.current.replace((flow.clone(), json)) {
+            self.undo.push(previous);
+            if self.undo.len() > LIMIT {
+                self.undo.remove(0);
+            }
+            self.redo.clear();
+        }
+        self.revision = next_revision();
+        true
+    }
+
+    /// Revision of the flow as last recorded
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Put the previous step into `flow`. Returns whether there was one.
+    pub fn undo(&mut self, flow: &mut Flow) -> bool {
+        let Some(previous) = self.undo.pop() else {
+            return false;
+        };
+        self.redo.push(flow.clone());
+        self.restore(flow, previous);
+        true
+    }
+
+    /// Put the step undone last back into `flow`. Returns whether there was one.
+    pub fn redo(&mut self, flow: &mut Flow) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push(flow.clone());
+        self.restore(flow, next);
+        true
+    }
+
+    /// Forget every step, e.g. when another flow is loaded into the tab
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn restore(&mut self, flow: &mut Flow, step: Flow) {
+        let json = serde_json::to_value(&step).unwrap_or_default();
+        *flow = step.clone();
+        self.current = Some((step, json));
+        self.revision = next_revision();
+    }
+}
+
+/// A value derived from a tab's flow, computed again once the flow's
+/// revision changes instead of every frame
+pub struct Cached<T> {
+    value: Option<(u64, T)>,
+}
+
+impl<T> Default for Cached<T> {
+    fn default() -> Self {
+        Self { value: None }
+    }
+}
+
+impl<T> Cached<T> {
+    pub fn get(&mut self, revision: u64, compute: impl FnOnce() -> T) -> &T {
+        if self.value.as_ref().map(|(at, _)| *at) != Some(revision) {
+            self.value = Some((revision, compute()));
+        }
+        &self.value.as_ref().expect("filled above").1
+    }
+}
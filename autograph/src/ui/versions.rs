@@ -0,0 +1,104 @@
+//! Versions Panel
+//!
+//! Browse a flow's stored versions, restore one, or compare two with the
+//! differences highlighted on the canvas.
+
+use eframe::egui;
+use crate::flow::Flow;
+use crate::runs::format_timestamp;
+use crate::versions::{Version, VersionStore};
+
+/// Version browser state
+#[derive(Default)]
+pub struct VersionsPanel {
+    /// Versions of the flow currently listed, newest first
+    versions: Vec<Version>,
+
+    /// Flow the list was loaded for
+    loaded_for: Option<String>,
+
+    /// Index of the older version to compare
+    base: Option<usize>,
+
+    /// Index of the newer version to compare; None for the flow in the editor
+    target: Option<usize>,
+}
+
+/// What the user asked for this frame
+pub enum VersionsAction {
+    None,
+    Restore(Flow),
+    /// Highlight what changed from `base` to `shown` (None for the editor's flow)
+    Compare { base: Flow, shown: Option<Flow>, label: String },
+}
+
+impl VersionsPanel {
+    /// Reload the version list on next show
+    pub fn invalidate(&mut self) {
+        self.loaded_for = None;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, flow_name: &str, store: &VersionStore) -> VersionsAction {
+        let mut action = VersionsAction::None;
+
+        if self.loaded_for.as_deref() != Some(flow_name) {
+            self.versions = store.list(flow_name);
+            self.loaded_for = Some(flow_name.to_string());
+            self.base = None;
+            self.target = None;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{} version(s) of '{}'", self.versions.len(), flow_name));
+            if ui.button("⟳ Refresh").clicked() {
+                self.invalidate();
+            }
+        });
+        ui.weak("A version is kept on every save and run. Pick A and B to compare.");
+        ui.separator();
+
+        if self.versions.is_empty() {
+            ui.label("No versions yet. Save or run the flow to keep one.");
+            return action;
+        }
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.target, None, "B = current editor");
+        });
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("versions").striped(true).show(ui, |ui| {
+                for (index, version) in self.versions.iter().enumerate() {
+                    ui.radio_value(&mut self.base, Some(index), "A");
+                    ui.radio_value(&mut self.target, Some(index), "B");
+                    ui.label(format_timestamp(version.saved_at));
+                    ui.label(&version.reason);
+                    ui.label(format!("{} nodes", version.flow.nodes.len()));
+                    if ui.button("↺ Restore").clicked() {
+                        action = VersionsAction::Restore(version.flow.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+        ui.separator();
+        let Some(base) = self.base.and_then(|i| self.versions.get(i)) else {
+            ui.weak("Pick version A to compare");
+            return action;
+        };
+        let shown = self.target.and_then(|i| self.versions.get(i));
+        let label = format!(
+            "{} → {}",
+            format_timestamp(base.saved_at),
+            shown.map_or("current".to_string(), |v| format_timestamp(v.saved_at))
+        );
+        if ui.button(format!("Compare {}", label)).clicked() {
+            action = VersionsAction::Compare {
+                base: base.flow.clone(),
+                shown: shown.map(|v| v.flow.clone()),
+                label,
+            };
+        }
+        action
+    }
+}
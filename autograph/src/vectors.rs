@@ -0,0 +1,91 @@
+//! Vector Index
+//!
+//! `vector_index` and `vector_search` nodes keep embeddings in a plain
+//! JSONL file, one `{"id", "vector", "metadata"}` record per line, so an
+//! index is appended to like any log and can be inspected or rebuilt with
+//! the other file nodes. Searches rank every record by cosine similarity,
+//! which is fine for the tens of thousands of chunks a flow typically
+//! retrieves from. The same ranking previews searches in the properties
+//! panel.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::path::Path;
+
+/// A stored embedding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub id: JsonValue,
+    pub vector: Vec<f64>,
+    #[serde(default)]
+    pub metadata: JsonValue,
+}
+
+/// A search result, as `vector_search` outputs it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hit {
+    pub id: JsonValue,
+    pub score: f64,
+    pub metadata: JsonValue,
+}
+
+/// Records of an index file, skipping blank lines
+pub fn load(path: &Path) -> Result<Vec<Record>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("{} line {}: {}", path.display(), i + 1, e)))
+        .collect()
+}
+
+/// Cosine similarity, 0 when either vector is all zeros or their lengths differ
+pub fn cosine(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms = a.iter().map(|x| x * x).sum::<f64>().sqrt() * b.iter().map(|y| y * y).sum::<f64>().sqrt();
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+/// The `top_k` records most similar to `query`, best first, scoring at least `min_score`
+pub fn search(records: &[Record], query: &[f64], top_k: usize, min_score: f64) -> Vec<Hit> {
+    let mut hits: Vec<Hit> = records.iter()
+        .map(|r| Hit { id: r.id.clone(), score: cosine(&r.vector, query), metadata: r.metadata.clone() })
+        .filter(|hit| hit.score >= min_score)
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(top_k);
+    hits
+}
+
+/// A JSON value as a query vector, if it is an array of numbers
+pub fn as_vector(value: &JsonValue) -> Option<Vec<f64>> {
+    value.as_array()?.iter().map(JsonValue::as_f64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn records_are_ranked_by_cosine_similarity() {
+        let record = |id: &str, vector: Vec<f64>| Record { id: json!(id), vector, metadata: json!({"source": id}) };
+        let records = vec![
+            record("north", vec![0.0, 1.0]),
+            record("east", vec![1.0, 0.0]),
+            record("northeast", vec![1.0, 1.0]),
+            record("empty", vec![0.0, 0.0]),
+        ];
+
+        let hits = search(&records, &[0.0, 2.0], 2, 0.0);
+        assert_eq!(hits.iter().map(|h| h.id.as_str().unwrap()).collect::<Vec<_>>(), ["north", "northeast"]);
+        assert!((hits[1].score - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-9);
+        assert!(search(&records, &[0.0, 1.0], 10, 0.5).iter().all(|h| h.score >= 0.5));
+        assert_eq!(cosine(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(as_vector(&json!([0.5, 1])), Some(vec![0.5, 1.0]));
+        assert_eq!(as_vector(&json!(["a"])), None);
+    }
+}
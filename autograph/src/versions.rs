@@ -0,0 +1,198 @@
+//! Flow Versions
+//!
+//! Saving a flow overwrites `flows/<name>.json`, so every explicit save and
+//! every run also stores a snapshot under `flows/.versions/<name>/`, one
+//! JSON file per version. Versions can be listed, restored and compared.
+
+use crate::flow::{Edge, Flow};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A stored snapshot of a flow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Version {
+    /// Unix timestamp in milliseconds, also the file name
+    pub saved_at: u64,
+    /// What took the snapshot: `save` or `run`
+    pub reason: String,
+    pub flow: Flow,
+}
+
+/// On-disk store of flow versions
+#[derive(Debug, Clone)]
+pub struct VersionStore {
+    dir: PathBuf,
+}
+
+impl VersionStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Store a snapshot of `flow`, unless it is the same as the latest one.
+    /// Returns whether a version was written.
+    pub fn snapshot(&self, flow_name: &str, flow: &Flow, reason: &str) -> std::io::Result<bool> {
+        let dir = self.flow_dir(flow_name)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid flow name"))?;
+        if let Some(latest) = self.list(flow_name).first() {
+            if serde_json::to_value(&latest.flow).ok() == serde_json::to_value(flow).ok() {
+                return Ok(false);
+            }
+        }
+
+        std::fs::create_dir_all(&dir)?;
+        let mut saved_at = crate::runs::now_ms();
+        while dir.join(format!("{}.json", saved_at)).exists() {
+            saved_at += 1;
+        }
+        let version = Version { saved_at, reason: reason.to_string(), flow: flow.clone() };
+        std::fs::write(dir.join(format!("{}.json", saved_at)), serde_json::to_string_pretty(&version)?)?;
+        Ok(true)
+    }
+
+    /// Every version of a flow, newest first
+    pub fn list(&self, flow_name: &str) -> Vec<Version> {
+        let Some(Ok(entries)) = self.flow_dir(flow_name).map(std::fs::read_dir) else {
+            return Vec::new();
+        };
+        let mut versions: Vec<Version> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        versions.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+        versions
+    }
+
+    /// Directory of a flow's versions, rejecting names that could escape the store
+    fn flow_dir(&self, flow_name: &str) -> Option<PathBuf> {
+        let valid = !flow_name.is_empty()
+            && flow_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        valid.then(|| self.dir.join(flow_name))
+    }
+}
+
+/// What changed from one version of a flow to another. Moving a node is
+/// not a change.
+#[derive(Debug, Clone, Default)]
+pub struct FlowDiff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    /// Nodes whose type, config or enabled flag changed
+    pub changed_nodes: Vec<String>,
+    pub added_edges: Vec<Edge>,
+    pub removed_edges: Vec<Edge>,
+}
+
+impl FlowDiff {
+    pub fn between(old: &Flow, new: &Flow) -> Self {
+        let mut diff = FlowDiff::default();
+        for node in &new.nodes {
+            match old.nodes.iter().find(|n| n.id == node.id) {
+                None => diff.added_nodes.push(node.id.clone()),
+                Some(before) => {
+                    if before.type_name != node.type_name || before.config != node.config || before.enabled != node.enabled {
+                        diff.changed_nodes.push(node.id.clone());
+                    }
+                }
+            }
+        }
+        diff.removed_nodes = old.nodes.iter()
+            .filter(|n| !new.nodes.iter().any(|m| m.id == n.id))
+            .map(|n| n.id.clone())
+            .collect();
+
+        diff.added_edges = new.edges.iter().filter(|e| !contains_edge(old, e)).cloned().collect();
+        diff.removed_edges = old.edges.iter().filter(|e| !contains_edge(new, e)).cloned().collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+
+    /// One line, e.g. `2 node(s) added, 1 changed; 1 edge(s) removed`
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "No changes".to_string();
+        }
+        let part = |kind: &str, counts: [(usize, &str); 3]| {
+            let changes: Vec<String> = counts.iter()
+                .filter(|(count, _)| *count > 0)
+                .map(|(count, what)| format!("{} {}", count, what))
+                .collect();
+            (!changes.is_empty()).then(|| format!("{}: {}", kind, changes.join(", ")))
+        };
+        [
+            part("nodes", [(self.added_nodes.len(), "added"), (self.removed_nodes.len(), "removed"), (self.changed_nodes.len(), "changed")]),
+            part("edges", [(self.added_edges.len(), "added"), (self.removed_edges.len(), "removed"), (0, "")]),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("; ")
+    }
+
+    /// Whether `edge` was added, comparing source, target and port
+    pub fn is_added_edge(&self, edge: &Edge) -> bool {
+        self.added_edges.iter().any(|e| same_edge(e, edge))
+    }
+}
+
+fn same_edge(a: &Edge, b: &Edge) -> bool {
+    a.source == b.source && a.target == b.target && a.target_handle == b.target_handle
+}
+
+fn contains_edge(flow: &Flow, edge: &Edge) -> bool {
+    flow.edges.iter().any(|e| same_edge(e, edge))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(json: serde_json::Value) -> Flow {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn diff_lists_added_removed_and_changed_nodes_and_edges() {
+        let old = flow(serde_json::json!({
+            "nodes": [
+                {"id": "a", "type_name": "start", "config": {}, "position": {"x": 0.0, "y": 0.0}},
+                {"id": "b", "type_name": "string_upper", "config": {}, "position": {"x": 0.0, "y": 100.0}},
+                {"id": "c", "type_name": "print", "config": {}, "position": {"x": 0.0, "y": 200.0}}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "source_handle": null, "target_handle": null},
+                {"source": "b", "target": "c", "source_handle": null, "target_handle": null}
+            ]
+        }));
+        let new = flow(serde_json::json!({
+            "nodes": [
+                {"id": "a", "type_name": "start", "config": {}, "position": {"x": 50.0, "y": 0.0}},
+                {"id": "b", "type_name": "string_lower", "config": {}, "position": {"x": 0.0, "y": 100.0}},
+                {"id": "d", "type_name": "print", "config": {}, "position": {"x": 0.0, "y": 200.0}}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "source_handle": null, "target_handle": null},
+                {"source": "b", "target": "d", "source_handle": null, "target_handle": null}
+            ]
+        }));
+
+        let diff = FlowDiff::between(&old, &new);
+        assert_eq!(diff.added_nodes, ["d"]);
+        assert_eq!(diff.removed_nodes, ["c"]);
+        assert_eq!(diff.changed_nodes, ["b"]);
+        assert_eq!(diff.added_edges.len(), 1);
+        assert!(diff.is_added_edge(&new.edges[1]));
+        assert_eq!(diff.removed_edges[0].target, "c");
+        assert_eq!(diff.summary(), "nodes: 1 added, 1 removed, 1 changed; edges: 1 added, 1 removed");
+        assert!(FlowDiff::between(&new, &new).is_empty());
+    }
+}